@@ -72,3 +72,12 @@
 (define (noclip state)
     (set-floating (player-entity) state)
     (set-ghost (player-entity) state))
+
+(define (give name)
+    (add-item (player-entity) name))
+
+(define (tp target)
+    (teleport (player-entity) target))
+
+(define (spawn name)
+    (spawn-enemy name (position-entity (mouse-entity))))
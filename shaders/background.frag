@@ -0,0 +1,23 @@
+#version 450
+
+layout(location = 0) in vec2 tex_coords;
+layout(location = 1) in float depth;
+
+layout(location = 0) out vec4 f_color;
+
+layout(set = 0, binding = 0) uniform sampler2D tex;
+
+layout(push_constant) uniform WorldInfo{
+    vec3 tint;
+    float tint_amount;
+} world;
+
+void main()
+{
+    vec4 color = texture(tex, tex_coords);
+
+    // multiplied instead of mixed so night just darkens it
+    vec3 tinted = color.xyz * mix(vec3(1.0), world.tint, world.tint_amount);
+
+    f_color = vec4(tinted, color.w);
+}
@@ -13,10 +13,32 @@ layout(push_constant) uniform OutlineInfo{
     float animation;
     float outlined;
     bool keep_transparency;
+    float emissive;
+    vec4 outline_color;
+    float outline_thickness;
+    float dissolve;
 } outline;
 
 const vec3 background_color = vec3(0.831, 0.941, 0.988);
 
+const vec3 dissolve_color = vec3(1.0, 0.5, 0.1);
+
+// emissive stuff goes past 1 on purpose, thats what the bloom in the post pass picks up
+const float EMISSIVE_BRIGHTNESS = 1.5;
+
+float hash(vec2 p)
+{
+    return fract(sin(dot(p, vec2(12.9898, 78.233))) * 43758.5453);
+}
+
+// blocky noise so it crumbles in pixel sized bits
+float dissolve_noise()
+{
+    vec2 pixel = floor(tex_coords * vec2(textureSize(tex, 0)));
+
+    return hash(pixel);
+}
+
 vec4 with_mix(vec4 color)
 {
     float a = outline.keep_transparency ? color.a : 1.0;
@@ -24,15 +46,59 @@ vec4 with_mix(vec4 color)
     return mix(color, vec4(outline.other_color, a), outline.other_mix);
 }
 
+// how much of the sprite is around this pixel but not on it
+float outline_edge(float alpha)
+{
+    if (outline.outline_thickness <= 0.0 || outline.outlined <= 0.0)
+    {
+        return 0.0;
+    }
+
+    vec2 texel = outline.outline_thickness / vec2(textureSize(tex, 0));
+
+    float horizontal = max(
+        texture(tex, tex_coords + vec2(texel.x, 0.0)).a,
+        texture(tex, tex_coords - vec2(texel.x, 0.0)).a
+    );
+
+    float vertical = max(
+        texture(tex, tex_coords + vec2(0.0, texel.y)).a,
+        texture(tex, tex_coords - vec2(0.0, texel.y)).a
+    );
+
+    return max(max(horizontal, vertical) - alpha, 0.0);
+}
+
 void main()
 {
     vec4 color = texture(tex, tex_coords);
 
+    float noise = dissolve_noise();
+    if (noise < outline.dissolve)
+    {
+        discard;
+    }
+
+    float edge = outline_edge(color.a);
+
     color = with_mix(color);
 
+    color.xyz *= 1.0 + outline.emissive * EMISSIVE_BRIGHTNESS;
+
+    // the bits about to go glow a little
+    float burning = outline.dissolve > 0.0 ? 1.0 - smoothstep(0.0, 0.1, noise - outline.dissolve) : 0.0;
+    color.xyz = mix(color.xyz, dissolve_color, burning);
+
     vec3 animation_color = sin(vec3(3.0, 4.0, 2.0) * outline.animation) * vec3(0.5, 0.1, 0.3);
-    vec3 outline_color = tex_coords.xyx + animation_color + vec3(0.3, 0.4, 0.2);
-    color = mix(color, vec4(outline_color, color.w), outline.outlined * 0.5);
+    vec3 rainbow_color = tex_coords.xyx + animation_color + vec3(0.3, 0.4, 0.2);
+    vec3 outline_color = mix(outline.outline_color.xyz, rainbow_color, outline.outline_color.w);
+
+    float tint = mix(0.2, 0.5, outline.outline_color.w);
+    color = mix(color, vec4(outline_color, color.w), outline.outlined * tint);
+
+    color = mix(color, vec4(outline_color, 1.0), edge * outline.outlined);
+
+    vec3 blended_color = mix(color.xyz, background_color, depth);
 
-    f_color = vec4(mix(color.xyz, background_color, depth), color.w);
+    f_color = vec4(blended_color, color.w);
 }
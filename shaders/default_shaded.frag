@@ -13,6 +13,10 @@ layout(push_constant) uniform OutlineInfo{
     float animation;
     float outlined;
     bool keep_transparency;
+    float emissive;
+    vec4 outline_color;
+    float outline_thickness;
+    float dissolve;
 } outline;
 
 layout(constant_id = 0) const float DARKEN = 0.0;
@@ -22,6 +26,23 @@ layout(constant_id = 3) const float BLEND_BLUE = 0.0;
 
 const vec3 background_color = vec3(0.831, 0.941, 0.988);
 
+const vec3 dissolve_color = vec3(1.0, 0.5, 0.1);
+
+const float EMISSIVE_BRIGHTNESS = 1.5;
+
+float hash(vec2 p)
+{
+    return fract(sin(dot(p, vec2(12.9898, 78.233))) * 43758.5453);
+}
+
+// blocky noise so it crumbles in pixel sized bits
+float dissolve_noise()
+{
+    vec2 pixel = floor(tex_coords * vec2(textureSize(tex, 0)));
+
+    return hash(pixel);
+}
+
 vec4 with_mix(vec4 color)
 {
     float a = outline.keep_transparency ? color.a : 1.0;
@@ -32,10 +53,24 @@ vec4 with_mix(vec4 color)
 void main()
 {
     vec4 color = texture(tex, tex_coords);
+
+    float noise = dissolve_noise();
+    if (noise < outline.dissolve)
+    {
+        discard;
+    }
+
     color = with_mix(color);
 
+    float burning = outline.dissolve > 0.0 ? 1.0 - smoothstep(0.0, 0.1, noise - outline.dissolve) : 0.0;
+    color.xyz = mix(color.xyz, dissolve_color, burning);
+
     vec3 blended_color = mix(color.xyz, background_color, depth);
-    vec3 darkened_color = mix(blended_color, vec3(BLEND_RED, BLEND_GREEN, BLEND_BLUE), DARKEN);
+    // glowing stuff stays visible in the dark
+    float darken = DARKEN * (1.0 - min(outline.emissive, 1.0));
+    vec3 darkened_color = mix(blended_color, vec3(BLEND_RED, BLEND_GREEN, BLEND_BLUE), darken);
+
+    darkened_color *= 1.0 + outline.emissive * EMISSIVE_BRIGHTNESS;
 
     f_color = vec4(darkened_color, color.w);
 }
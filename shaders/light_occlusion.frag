@@ -0,0 +1,78 @@
+#version 450
+
+layout(location = 0) in vec2 tex_coords;
+layout(location = 1) in float depth;
+
+layout(location = 0) out vec4 f_color;
+
+// distance to the closest wall in texels
+layout(set = 0, binding = 0) uniform sampler2D tex;
+
+layout(push_constant) uniform LightOcclusionInfo{
+    vec4 area;
+} light;
+
+layout(constant_id = 0) const float SHADOW_RED = 0.0;
+layout(constant_id = 1) const float SHADOW_GREEN = 0.0;
+layout(constant_id = 2) const float SHADOW_BLUE = 0.0;
+layout(constant_id = 3) const float SHADOW_STRENGTH = 0.5;
+
+const int MAX_STEPS = 48;
+
+float wall_distance(vec2 texel, vec2 size)
+{
+    vec2 uv = texel / size;
+
+    // outside of the field counts as empty
+    if (uv.x < 0.0 || uv.y < 0.0 || uv.x > 1.0 || uv.y > 1.0)
+    {
+        return 1.0;
+    }
+
+    return texture(tex, uv).x * 255.0;
+}
+
+void main()
+{
+    if (length(tex_coords - vec2(0.5)) > 0.5)
+    {
+        discard;
+    }
+
+    vec2 size = vec2(textureSize(tex, 0));
+
+    vec2 start = (light.area.xy + tex_coords * light.area.zw) * size;
+    vec2 target = (light.area.xy + vec2(0.5) * light.area.zw) * size;
+
+    // the walls themselves stay lit
+    if (wall_distance(start, size) < 0.5)
+    {
+        discard;
+    }
+
+    vec2 offset = target - start;
+    float total = length(offset);
+    vec2 direction = offset / max(total, 0.001);
+
+    float travelled = 0.0;
+    for (int i = 0; i < MAX_STEPS; ++i)
+    {
+        if (travelled >= total)
+        {
+            discard;
+        }
+
+        float distance = wall_distance(start + direction * travelled, size);
+
+        if (distance < 0.5)
+        {
+            f_color = vec4(SHADOW_RED, SHADOW_GREEN, SHADOW_BLUE, SHADOW_STRENGTH);
+            return;
+        }
+
+        // chamfer distances overshoot a little so dont trust them fully
+        travelled += max(distance * 0.9, 0.5);
+    }
+
+    discard;
+}
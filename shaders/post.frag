@@ -0,0 +1,174 @@
+#version 450
+
+layout(location = 0) in vec2 tex_coords;
+
+layout(location = 0) out vec4 f_color;
+
+// the world pass, drawn right before this one
+layout(set = 0, binding = 0) uniform sampler2D world;
+
+layout(push_constant) uniform PostInfo{
+    float bloom;
+    float vignette;
+    float aberration;
+    float saturation;
+    float blood;
+    float pulse;
+    float dizziness;
+    float sway;
+    // the world only fills this much of the world image when drawn at a lower resolution
+    float scale;
+    // 2x2x2 lut, a byte per channel, corner index is red + green * 2 + blue * 4
+    uint lut[6];
+} post;
+
+// normal sprites top out at 1, only emissive ones get past it and glow
+const float BLOOM_THRESHOLD = 1.0;
+
+// in pixels, the two rings of samples around every pixel
+const float BLOOM_RADIUS = 6.0;
+const int BLOOM_SAMPLES = 12;
+
+const float TAU = 6.28318530718;
+
+vec4 over(vec4 below, vec4 above)
+{
+    float a = above.a + below.a * (1.0 - above.a);
+
+    if (a <= 0.0)
+    {
+        return vec4(0.0);
+    }
+
+    vec3 color = (above.rgb * above.a + below.rgb * below.a * (1.0 - above.a)) / a;
+
+    return vec4(color, a);
+}
+
+// screen coordinates into the scaled corner, kept half a pixel in so nothing outside of it bleeds in
+vec4 sample_world(vec2 uv)
+{
+    vec2 edge = vec2(post.scale) - 0.5 / vec2(textureSize(world, 0));
+
+    return texture(world, min(uv * post.scale, edge));
+}
+
+float luminance(vec3 color)
+{
+    return dot(color, vec3(0.2126, 0.7152, 0.0722));
+}
+
+vec3 bright_pass(vec3 color)
+{
+    float bright = luminance(color);
+
+    return color * (max(bright - BLOOM_THRESHOLD, 0.0) / max(bright, 0.0001));
+}
+
+// gaussian weighted rings of bright passed samples
+vec3 bloom(vec2 texel)
+{
+    vec3 total = bright_pass(sample_world(tex_coords).rgb);
+    float weights = 1.0;
+
+    for (int ring = 1; ring <= 2; ++ring)
+    {
+        float radius = BLOOM_RADIUS * float(ring);
+        float weight = exp(-float(ring * ring) * 0.5);
+
+        for (int i = 0; i < BLOOM_SAMPLES; ++i)
+        {
+            // every other ring is rotated so the gaps dont line up
+            float angle = (float(i) + float(ring) * 0.5) / float(BLOOM_SAMPLES) * TAU;
+            vec2 offset = vec2(cos(angle), sin(angle)) * radius * texel;
+
+            total += bright_pass(sample_world(tex_coords + offset).rgb) * weight;
+            weights += weight;
+        }
+    }
+
+    return total / weights;
+}
+
+// the red and blue channels get pulled apart towards the edges
+vec3 world_color(vec2 centered, vec2 texel)
+{
+    vec2 offset = centered * 2.0 * post.aberration * texel;
+
+    return vec3(
+        sample_world(tex_coords + offset).r,
+        sample_world(tex_coords).g,
+        sample_world(tex_coords - offset).b
+    );
+}
+
+float lut_value(int index)
+{
+    return unpackUnorm4x8(post.lut[index / 4])[index % 4];
+}
+
+vec3 lut_corner(int r, int g, int b)
+{
+    int index = (r + g * 2 + b * 4) * 3;
+
+    return vec3(lut_value(index), lut_value(index + 1), lut_value(index + 2));
+}
+
+// trilinear lookup between all 8 corners
+vec3 grade(vec3 color)
+{
+    vec3 c = clamp(color, 0.0, 1.0);
+
+    vec3 low = mix(
+        mix(lut_corner(0, 0, 0), lut_corner(1, 0, 0), c.r),
+        mix(lut_corner(0, 1, 0), lut_corner(1, 1, 0), c.r),
+        c.g
+    );
+
+    vec3 high = mix(
+        mix(lut_corner(0, 0, 1), lut_corner(1, 0, 1), c.r),
+        mix(lut_corner(0, 1, 1), lut_corner(1, 1, 1), c.r),
+        c.g
+    );
+
+    return mix(low, high, c.b);
+}
+
+void main()
+{
+    // offsets stay in screen pixels no matter the scale
+    vec2 texel = 1.0 / vec2(textureSize(world, 0));
+
+    vec3 color = world_color(tex_coords - vec2(0.5), texel);
+
+    if (post.bloom > 0.0)
+    {
+        color += bloom(texel) * post.bloom;
+    }
+
+    color = mix(vec3(luminance(color)), color, post.saturation);
+
+    vec4 screen = vec4(grade(color), 1.0);
+
+    // the dark edges drift around in circles when the player is about to pass out
+    vec2 drift = vec2(cos(post.sway), sin(post.sway * 2.0)) * post.dizziness * 0.15;
+    vec2 centered = tex_coords - vec2(0.5) - drift;
+
+    // 0 in the middle, 1 in the corners
+    float edge = dot(centered, centered) * 2.0;
+
+    float darken = smoothstep(0.2, 1.0, edge) * post.vignette;
+    screen = over(screen, vec4(0.0, 0.0, 0.0, darken));
+
+    // creeps in from the edges as the player gets worse
+    float blood_edge = smoothstep(1.0 - max(post.blood, 0.01) * 0.7, 1.0, edge) * min(post.blood, 1.0);
+    screen = over(screen, vec4(0.45, 0.0, 0.02, blood_edge * 0.8));
+
+    float faint = smoothstep(0.5 - post.dizziness * 0.4, 1.2, edge) * post.dizziness;
+    screen = over(screen, vec4(0.0, 0.0, 0.0, faint * 0.85));
+
+    float pulse = smoothstep(0.1, 0.9, edge) * post.pulse;
+    screen = over(screen, vec4(0.15, 0.0, 0.0, pulse * 0.7));
+
+    f_color = screen;
+}
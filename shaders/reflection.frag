@@ -0,0 +1,34 @@
+#version 450
+
+layout(location = 0) in vec2 tex_coords;
+layout(location = 1) in float depth;
+
+layout(location = 0) out vec4 f_color;
+
+layout(set = 0, binding = 0) uniform sampler2D tex;
+
+layout(push_constant) uniform ReflectionInfo{
+    vec3 tint;
+    float strength;
+    float animation;
+} reflection;
+
+const vec3 background_color = vec3(0.831, 0.941, 0.988);
+
+void main()
+{
+    // flipped upside down with a bit of a ripple
+    float ripple = sin(tex_coords.y * 40.0 + reflection.animation * 3.0) * 0.015;
+    vec2 uv = vec2(tex_coords.x + ripple, 1.0 - tex_coords.y);
+
+    vec4 color = texture(tex, uv);
+
+    vec3 tinted = mix(color.xyz, reflection.tint, 0.4);
+
+    vec3 blended_color = mix(tinted, background_color, depth);
+
+    // fades out further from the thing being reflected
+    float fade = 1.0 - uv.y * 0.6;
+
+    f_color = vec4(blended_color, color.w * reflection.strength * fade);
+}
@@ -7,13 +7,21 @@ layout(location = 0) out vec4 f_color;
 
 layout(set = 0, binding = 0) uniform sampler2D tex;
 
+layout(push_constant) uniform WorldInfo{
+    vec3 tint;
+    float tint_amount;
+    vec2 tile_step;
+} world;
+
 const vec3 background_color = vec3(0.831, 0.941, 0.988);
 
 void main()
 {
     vec4 color = texture(tex, tex_coords);
 
-    vec3 blended_color = mix(color.xyz, background_color, depth);
+    vec3 tinted = mix(color.xyz, world.tint, world.tint_amount);
+
+    vec3 blended_color = mix(tinted, background_color, depth);
 
     f_color = vec4(blended_color, color.w);
 }
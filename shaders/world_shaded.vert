@@ -1,19 +1,34 @@
 #version 450
 
+// the tile quad, already moved to the chunk slice
 layout(location = 0) in vec4 position;
-layout(location = 1) in vec2 uv;
+
+layout(location = 2) in vec2 tile;
+layout(location = 3) in vec4 uvs_low;
+layout(location = 4) in vec4 uvs_high;
 
 layout(location = 0) out vec2 tex_coords;
 layout(location = 1) out float depth;
 layout(location = 2) out float up_depth;
 
+layout(push_constant) uniform TilesInfo{
+    vec3 tint;
+    float tint_amount;
+    vec2 tile_step;
+} tiles;
+
 layout(constant_id = 0) const float TILE_SIZE = 0.0;
 
+// which corner every vertex of the 2 triangles is
+const int CORNERS[6] = int[](0, 1, 2, 1, 3, 2);
+
 void main()
 {
-    gl_Position = position;
+    gl_Position = position + vec4(tile * tiles.tile_step, 0.0, 0.0);
+
+    vec2 uvs[4] = vec2[](uvs_low.xy, uvs_low.zw, uvs_high.xy, uvs_high.zw);
+    tex_coords = uvs[CORNERS[gl_VertexIndex % 6]];
 
-    tex_coords = uv;
     depth = sqrt(max(0.0, position.z - TILE_SIZE * 2.0));
     up_depth = max(0.0, position.z - TILE_SIZE);
 }
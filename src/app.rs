@@ -1,5 +1,8 @@
 use std::{
-    env,
+    iter,
+    process,
+    rc::Rc,
+    path::PathBuf,
     thread::{self, JoinHandle},
     sync::{mpsc, Arc}
 };
@@ -16,30 +19,51 @@ use yanyaengine::{
 
 use crate::{
     LONGEST_FRAME,
+    complain,
+    rendering::RenderTargets,
     debug_config::*
 };
 
 use common::{
     TileMap,
+    TileMapWithTextures,
     DataInfos,
     ItemsInfo,
     EnemiesInfo,
+    PerksInfo,
+    BarksInfo,
+    BodyPlansInfo,
+    ModsInfo,
+    CollisionLayers,
+    FactionsInfo,
+    DialoguesInfo,
+    ContainersInfo,
+    RecipesInfo,
+    overrides,
+    crash_report,
+    rng,
+    ParticleEffects,
     CharactersInfo,
     CharacterInfo,
     sender_loop::{waiting_loop, DELTA_TIME}
 };
 
-use server::Server;
+use server::{
+    Server,
+    world::{World, AutosaveSettings, surface_preview, world_list}
+};
 
 use client::{
     Client,
     ClientInitInfo,
-    ClientInfo
+    ClientInfo,
+    AddressBook
 };
 
-use config::Config;
+pub use config::{Config, CameraFollow, WindowMode};
 
 mod config;
+mod bench;
 
 pub mod common;
 
@@ -53,13 +77,19 @@ pub struct ProgramShaders
     pub default_shaded: ShaderId,
     pub world: ShaderId,
     pub world_shaded: ShaderId,
+    pub background: ShaderId,
+    pub reflection: ShaderId,
     pub shadow: ShaderId,
+    pub light_occlusion: ShaderId,
+    pub post: ShaderId,
     pub ui: ShaderId
 }
 
 pub struct AppInfo
 {
-    pub shaders: ProgramShaders
+    pub shaders: ProgramShaders,
+    pub targets: RenderTargets,
+    pub config: Config
 }
 
 type SlowMode = <DebugConfig as DebugConfigTrait>::SlowMode;
@@ -180,6 +210,157 @@ impl Drop for App
     }
 }
 
+// blocks for as long as the server keeps running
+fn run_server(
+    tilemap: TileMapWithTextures,
+    data_infos: DataInfos,
+    port: u32,
+    corpse_duration: f32,
+    autosave: AutosaveSettings,
+    world_name: &str,
+    on_listen: impl FnOnce(u16)
+)
+{
+    let x = Server::new(
+        tilemap,
+        data_infos,
+        &format!("0.0.0.0:{port}"),
+        16,
+        corpse_duration,
+        autosave,
+        world_name
+    );
+
+    let (mut game_server, mut server) = match x
+    {
+        Ok(x) => x,
+        Err(err) => panic!("{err}")
+    };
+
+    on_listen(server.port());
+
+    thread::spawn(move ||
+    {
+        server.run();
+    });
+
+    waiting_loop(||
+    {
+        game_server.update(DELTA_TIME as f32)
+    });
+}
+
+// these all quit after doing their thing
+fn manage_worlds(world: &str, list: bool, delete: Option<String>, duplicate: Option<String>)
+{
+    if list
+    {
+        let worlds = world_list::list();
+
+        if worlds.is_empty()
+        {
+            println!("no worlds yet, one gets made when u start playing");
+        }
+
+        worlds.into_iter().for_each(|world| println!("{world}"));
+
+        process::exit(0);
+    }
+
+    if let Some(name) = delete
+    {
+        if let Err(err) = world_list::delete(&name)
+        {
+            complain(format!("error deleting world {name}: {err}"));
+        }
+
+        println!("deleted world {name}");
+
+        process::exit(0);
+    }
+
+    if let Some(name) = duplicate
+    {
+        if let Err(err) = world_list::duplicate(world, &name)
+        {
+            complain(format!("error copying world {world} to {name}: {err}"));
+        }
+
+        println!("copied world {world} to {name}");
+
+        process::exit(0);
+    }
+}
+
+fn pick_address(
+    address: Option<String>,
+    connect: Option<String>,
+    save_address: Option<String>,
+    list: bool
+) -> Option<String>
+{
+    let mut address_book = AddressBook::load();
+
+    if list
+    {
+        println!("{address_book}");
+
+        process::exit(0);
+    }
+
+    let address = if let Some(name) = connect
+    {
+        if address.is_some()
+        {
+            complain("cant use both an address and a saved address");
+        }
+
+        let address = address_book.get(&name).unwrap_or_else(||
+        {
+            complain(format!("no saved address named {name}, see --addresses"))
+        });
+
+        Some(address.to_owned())
+    } else
+    {
+        address
+    };
+
+    if let Some(name) = save_address
+    {
+        let address = address.clone().unwrap_or_else(|| complain("saving an address needs --address"));
+
+        address_book.insert(name.clone(), address);
+
+        match address_book.save()
+        {
+            Ok(_) => println!("saved address as {name}"),
+            Err(err) => eprintln!("error saving addresses: {err}")
+        }
+    }
+
+    address
+}
+
+// lets u look for a seed u like before making a world with it
+fn print_preview(tilemap: TileMap, seed: Option<u64>, size: usize)
+{
+    let seed = seed.unwrap_or_else(|| fastrand::u64(..));
+
+    rng::seed(seed);
+
+    match surface_preview(Rc::new(tilemap), size)
+    {
+        Ok(map) =>
+        {
+            println!("seed {seed}:\n{map}");
+            println!("# building, B bunker, \" park, -|+ roads");
+            println!("run again without --seed for another one, or with --seed {seed} to make the world");
+        },
+        Err(err) => eprintln!("error generating preview: {err}")
+    }
+}
+
 impl YanyaApp for App
 {
     type AppInfo = Option<AppInfo>;
@@ -187,14 +368,48 @@ impl YanyaApp for App
     fn init(partial_info: InitPartialInfo, app_info: Self::AppInfo) -> Self
     {
         let deferred_parse = || TileMap::parse("tiles/tiles.json", "textures/tiles/");
-        let app_info = app_info.unwrap();
+        let AppInfo{shaders, targets, config} = app_info.unwrap();
+
+        let Config{name, character, address, connect, save_address, addresses, port, server, seed, preview, mut world, worlds, continue_world, delete_world, duplicate_world, difficulty, corpse_duration, autosave, graphics, camera, physics_rate, audio, sound_cues, telemetry, bench, crash_autosave, debug, ..} = config;
+
+        crash_report::set_include_autosave(crash_autosave);
+
+        let mods_info = ModsInfo::parse("mods");
+
+        overrides::init(iter::once(PathBuf::from(overrides::USER_OVERRIDES))
+            .chain(mods_info.override_directories())
+            .collect());
+
+        if let Some(size) = preview
+        {
+            let tilemap = deferred_parse().unwrap_or_else(|err| panic!("error parsing tilemap: {err}"));
+            print_preview(tilemap.tilemap, seed, size);
 
-        let Config{name, address, port, debug} = Config::parse(env::args().skip(1));
+            process::exit(0);
+        }
+
+        manage_worlds(&world, worlds, delete_world, duplicate_world);
+
+        if continue_world
+        {
+            match world_list::latest()
+            {
+                Some(latest) =>
+                {
+                    println!("continuing world {latest}");
+
+                    world = latest;
+                },
+                None => eprintln!("no worlds to continue, starting {world}")
+            }
+        }
+
+        let address = pick_address(address, connect, save_address, addresses);
 
         let items_info = ItemsInfo::parse(
             &partial_info.assets.lock(),
             "items",
-            "items/items.json"
+            mods_info.data_files("items/items.json", "items.json")
         );
 
         let mut characters_info = CharactersInfo::new();
@@ -203,20 +418,110 @@ impl YanyaApp for App
             &partial_info.assets.lock()
         ));
 
+        let body_plans = BodyPlansInfo::parse("anatomy/body_plans.json");
+
         let enemies_info = EnemiesInfo::parse(
             &partial_info.assets.lock(),
             &mut characters_info,
+            &body_plans,
             "enemy",
-            "enemies/enemies.json"
+            mods_info.data_files("enemies/enemies.json", "enemies.json")
+        );
+
+        let perks_info = PerksInfo::parse("perks/perks.json");
+
+        let barks_info = BarksInfo::parse("barks/barks.json");
+
+        let collision_layers = CollisionLayers::parse("collision/layers.json");
+
+        let factions_info = FactionsInfo::parse("factions/factions.json");
+
+        let dialogues_info = DialoguesInfo::parse("dialogues/dialogues.json");
+
+        let containers_info = ContainersInfo::parse(
+            mods_info.data_files("containers/containers.json", "containers.json"),
+            &collision_layers
+        );
+
+        let recipes_info = RecipesInfo::parse(
+            &items_info,
+            mods_info.data_files("items/recipes.json", "recipes.json")
+        );
+
+        let particle_effects = ParticleEffects::parse(
+            &partial_info.assets.lock(),
+            "particles/particles.json"
         );
 
         let data_infos = DataInfos{
             items_info: Arc::new(items_info),
             enemies_info: Arc::new(enemies_info),
             characters_info: Arc::new(characters_info),
+            perks_info: Arc::new(perks_info),
+            barks_info: Arc::new(barks_info),
+            body_plans: Arc::new(body_plans),
+            collision_layers: Arc::new(collision_layers),
+            factions_info: Arc::new(factions_info),
+            dialogues_info: Arc::new(dialogues_info),
+            containers_info: Arc::new(containers_info),
+            recipes_info: Arc::new(recipes_info),
+            particle_effects: Arc::new(particle_effects),
+            mods_info: Arc::new(mods_info),
             player_character
         };
 
+        if let Some(ticks) = bench
+        {
+            bench::run(deferred_parse().unwrap(), data_infos, corpse_duration, ticks);
+
+            process::exit(0);
+        }
+
+        if let Some(seed) = seed
+        {
+            if address.is_some()
+            {
+                eprintln!("the seed only matters when hosting, ignoring it");
+            } else
+            {
+                World::create_seed(&world, seed);
+            }
+        }
+
+        if let Some(difficulty) = difficulty
+        {
+            if address.is_some()
+            {
+                eprintln!("the difficulty only matters when hosting, ignoring it");
+            } else
+            {
+                World::create_difficulty(&world, difficulty);
+            }
+        }
+
+        if address.is_none() && World::is_locked(&world)
+        {
+            complain(format!("world {world} ended with permadeath, delete it with --delete-world {world} to start over"));
+        }
+
+        let port = port.unwrap_or(0);
+
+        if server
+        {
+            if address.is_some()
+            {
+                complain("cant connect to another server while hosting one");
+            }
+
+            let tilemap = deferred_parse().unwrap_or_else(|err| panic!("error parsing tilemap: {err}"));
+            run_server(tilemap, data_infos, port, corpse_duration, autosave, &world, |port|
+            {
+                println!("listening on port {port}");
+            });
+
+            process::exit(0);
+        }
+
         let mut server_handle = None;
         let (host, client_address) = if let Some(address) = address
         {
@@ -226,42 +531,14 @@ impl YanyaApp for App
             let (tx, rx) = mpsc::channel();
 
             let data_infos = data_infos.clone();
+            let world = world.clone();
             server_handle = Some(thread::spawn(move ||
             {
-                match deferred_parse()
+                let tilemap = deferred_parse().unwrap_or_else(|err| panic!("error parsing tilemap: {err}"));
+                run_server(tilemap, data_infos, port, corpse_duration, autosave, &world, |port|
                 {
-                    Ok(tilemap) =>
-                    {
-                        let port = port.unwrap_or(0);
-
-                        let x = Server::new(
-                            tilemap,
-                            data_infos,
-                            &format!("0.0.0.0:{port}"),
-                            16
-                        );
-
-                        let (mut game_server, mut server) = match x
-                        {
-                            Ok(x) => x,
-                            Err(err) => panic!("{err}")
-                        };
-
-                        let port = server.port();
-                        tx.send(port).unwrap();
-
-                        thread::spawn(move ||
-                        {
-                            server.run();
-                        });
-
-                        waiting_loop(||
-                        {
-                            game_server.update(DELTA_TIME as f32)
-                        });
-                    },
-                    Err(err) => panic!("error parsing tilemap: {err}")
-                }
+                    tx.send(port).unwrap();
+                });
             }));
 
             let port = rx.recv().unwrap();
@@ -274,9 +551,17 @@ impl YanyaApp for App
             client_info: ClientInfo{
                 address: client_address,
                 name,
+                character,
+                graphics,
+                camera,
+                physics_rate,
+                audio,
+                sound_cues,
+                telemetry,
                 debug
             },
-            app_info,
+            shaders,
+            targets,
             tilemap: deferred_parse().unwrap(),
             data_infos,
             host
@@ -0,0 +1,193 @@
+use std::{
+    fs,
+    thread,
+    ops::ControlFlow,
+    net::TcpStream,
+    time::{Duration, Instant},
+    sync::{
+        mpsc,
+        Arc,
+        atomic::{AtomicUsize, Ordering}
+    }
+};
+
+use nalgebra::Vector3;
+
+use crate::{
+    server::{
+        Server,
+        world::{World, AutosaveSettings}
+    },
+    common::{
+        receiver_loop,
+        DataInfos,
+        TileMapWithTextures,
+        MessagePasser,
+        CharacterCreation,
+        message::Message,
+        sender_loop::DELTA_TIME,
+        world::TILE_SIZE
+    }
+};
+
+
+// gets wiped every run so the results dont depend on whats already generated
+const WORLD_NAME: &str = "bench";
+const SEED: u64 = 0;
+
+// in tiles per second, fast enough to keep generating new chunks
+const BOT_SPEED: f32 = 8.0;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+// runs the server as fast as it can with a bot walking around and prints how long the ticks took
+pub fn run(
+    tilemap: TileMapWithTextures,
+    data_infos: DataInfos,
+    corpse_duration: f32,
+    ticks: u32
+)
+{
+    let world_path = World::world_path_associated(WORLD_NAME);
+    let clear_world = ||
+    {
+        if world_path.exists()
+        {
+            fs::remove_dir_all(&world_path).unwrap();
+        }
+    };
+
+    clear_world();
+
+    World::create_seed(WORLD_NAME, SEED);
+
+    let autosave = AutosaveSettings{interval: 0.0, amount: 0};
+
+    let mods = data_infos.mods_info.names();
+
+    let (mut game_server, mut server) = Server::new(
+        tilemap,
+        data_infos,
+        "127.0.0.1:0",
+        2,
+        corpse_duration,
+        autosave,
+        WORLD_NAME
+    ).unwrap_or_else(|err| panic!("{err}"));
+
+    let port = server.port();
+
+    thread::spawn(move ||
+    {
+        server.run();
+    });
+
+    let received = Arc::new(AtomicUsize::new(0));
+
+    let mut bot = MessagePasser::new(TcpStream::connect(("127.0.0.1", port)).unwrap());
+    bot.send_one(&Message::PlayerConnect{name: "bot".to_owned(), creation: CharacterCreation::default(), mods}).unwrap();
+
+    let (entity_tx, entity_rx) = mpsc::channel();
+
+    {
+        let mut receiver = bot.try_clone();
+        let received = received.clone();
+
+        thread::spawn(move ||
+        {
+            let entity = match receiver.receive_one()
+            {
+                Ok(Some(Message::PlayerOnConnect{player_entity})) => player_entity,
+                x => panic!("bot received wrong message on connect: {x:?}")
+            };
+
+            entity_tx.send(entity).unwrap();
+
+            // doesnt care what the server says, just keeps it from getting stuck on a full socket
+            receiver_loop(receiver, move |_message|
+            {
+                received.fetch_add(1, Ordering::Relaxed);
+
+                ControlFlow::Continue(())
+            }, || ());
+        });
+    }
+
+    let connect_start = Instant::now();
+    let entity = loop
+    {
+        game_server.update(DELTA_TIME as f32);
+
+        if let Ok(entity) = entity_rx.try_recv()
+        {
+            break entity;
+        }
+
+        if connect_start.elapsed() > CONNECT_TIMEOUT
+        {
+            panic!("bot couldnt connect to the server");
+        }
+    };
+
+    let dt = DELTA_TIME as f32;
+
+    let times: Vec<Duration> = (0..ticks).map(|tick|
+    {
+        let time = tick as f32 * dt;
+        let position = Vector3::new(time * BOT_SPEED, (time * 0.5).sin() * 4.0, 0.0) * TILE_SIZE;
+
+        bot.send_one(&Message::SyncPosition{entity, position}).unwrap();
+
+        let start = Instant::now();
+
+        game_server.update(dt);
+
+        start.elapsed()
+    }).collect();
+
+    bot.send_one(&Message::PlayerDisconnect{host: true}).unwrap();
+
+    while !game_server.update(dt) {}
+
+    // saves the world on drop, thats part of the work too
+    let exit_start = Instant::now();
+    drop(game_server);
+    let exit_time = exit_start.elapsed();
+
+    report(times, exit_time, received.load(Ordering::Relaxed));
+
+    clear_world();
+}
+
+fn report(mut times: Vec<Duration>, exit_time: Duration, received: usize)
+{
+    if times.is_empty()
+    {
+        println!("no ticks ran");
+        return;
+    }
+
+    let total: Duration = times.iter().sum();
+
+    times.sort();
+
+    let percentile = |p: f64|
+    {
+        times[((times.len() - 1) as f64 * p).round() as usize]
+    };
+
+    let ms = |x: Duration| x.as_secs_f64() * 1000.0;
+
+    let budget = Duration::from_secs_f64(DELTA_TIME);
+    let over_budget = times.iter().filter(|x| **x > budget).count();
+
+    println!("ran {} ticks in {:.2}s", times.len(), total.as_secs_f64());
+    println!("mean: {:.3}ms", ms(total) / times.len() as f64);
+    println!("median: {:.3}ms", ms(percentile(0.5)));
+    println!("p95: {:.3}ms", ms(percentile(0.95)));
+    println!("p99: {:.3}ms", ms(percentile(0.99)));
+    println!("max: {:.3}ms", ms(percentile(1.0)));
+    println!("over the {:.3}ms budget: {over_budget}", ms(budget));
+    println!("saving on exit: {:.3}ms", ms(exit_time));
+    println!("bot received {received} messages");
+}
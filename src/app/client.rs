@@ -14,6 +14,10 @@ use parking_lot::RwLock;
 use image::error::ImageError;
 
 use yanyaengine::{
+    Object,
+    ObjectInfo,
+    Transform,
+    TextureId,
     ElementState,
     UniformLocation,
     DefaultModel,
@@ -29,12 +33,14 @@ use game_state::{GameState, GameStateInfo};
 
 use crate::{
     LOG_PATH,
-    app::AppInfo,
+    ProgramShaders,
+    rendering::{self, RenderTargets},
     common::{
         some_or_value,
         some_or_return,
         DataInfos,
         MessagePasser,
+        CharacterCreation,
         tilemap::TileMapWithTextures
     }
 };
@@ -45,14 +51,20 @@ pub use ui_element::{UiEvent, MouseEvent, UiElement};
 
 pub use game_state::{
     Ui,
+    GraphicsSettings,
+    CameraSettings,
+    DEFAULT_PHYSICS_RATE,
+    TelemetrySettings,
+    AudioSettings,
     Control,
     ControlState,
-    KeyMapping,
-    CommonTextures
+    KeyMapping
 };
 
 pub use connections_handler::ConnectionsHandler;
-pub use tiles_factory::{TilesFactory, ChunkInfo};
+pub use tiles_factory::{TilesFactory, ChunkInfo, TileInstance};
+pub use sprite_atlas::SpriteAtlas;
+pub use address_book::AddressBook;
 
 pub mod visibility_checker;
 
@@ -63,8 +75,10 @@ pub mod game;
 
 pub mod connections_handler;
 pub mod tiles_factory;
+pub mod sprite_atlas;
 
 pub mod world_receiver;
+pub mod address_book;
 
 
 pub struct RenderCreateInfo<'a, 'b>
@@ -75,10 +89,29 @@ pub struct RenderCreateInfo<'a, 'b>
     pub object_info: &'a mut ObjectCreateInfo<'b>
 }
 
+impl RenderCreateInfo<'_, '_>
+{
+    pub fn create_object(&mut self, id: TextureId, transform: Transform) -> Object
+    {
+        let (model, texture) = {
+            let assets = self.object_info.partial.assets.lock();
+
+            (assets.model(self.square).clone(), assets.texture(id).clone())
+        };
+
+        self.object_info.partial.object_factory.create(ObjectInfo{
+            model,
+            texture,
+            transform
+        })
+    }
+}
+
 pub struct ClientInitInfo
 {
     pub client_info: ClientInfo,
-    pub app_info: AppInfo,
+    pub shaders: ProgramShaders,
+    pub targets: RenderTargets,
     pub tilemap: TileMapWithTextures,
     pub data_infos: DataInfos,
     pub host: bool
@@ -88,6 +121,13 @@ pub struct ClientInfo
 {
     pub address: String,
     pub name: String,
+    pub character: CharacterCreation,
+    pub graphics: GraphicsSettings,
+    pub camera: CameraSettings,
+    pub physics_rate: u32,
+    pub audio: AudioSettings,
+    pub sound_cues: bool,
+    pub telemetry: TelemetrySettings,
     pub debug: bool
 }
 
@@ -123,7 +163,7 @@ impl Client
 
         let tiles_factory = TilesFactory::new(
             &mut info,
-            client_init_info.app_info.shaders.world,
+            client_init_info.shaders.world,
             client_init_info.tilemap
         )?;
 
@@ -134,7 +174,8 @@ impl Client
 
         let assets = info.partial.assets.clone();
         let info = GameStateInfo{
-            shaders: client_init_info.app_info.shaders,
+            shaders: client_init_info.shaders,
+            targets: client_init_info.targets,
             camera: camera.clone(),
             object_info: info,
             data_infos: client_init_info.data_infos,
@@ -201,7 +242,14 @@ impl Client
 
     pub fn draw(&mut self, mut info: DrawInfo)
     {
-        some_or_return!(&self.game_state).borrow().draw(&mut info);
+        if let Some(game_state) = &self.game_state
+        {
+            game_state.borrow().draw(&mut info);
+        } else
+        {
+            // the render pass still has to get through every subpass
+            (1..rendering::SUBPASSES).for_each(|_| info.next_subpass());
+        }
     }
 
     pub fn input(&mut self, control: yanyaengine::Control) -> bool
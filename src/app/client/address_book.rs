@@ -0,0 +1,75 @@
+use std::{
+    fs,
+    io,
+    fmt::{self, Display},
+    collections::BTreeMap
+};
+
+
+const ADDRESS_BOOK_PATH: &str = "addresses.json";
+
+// servers saved under a name so they dont have to get typed out every time
+#[derive(Debug, Default)]
+pub struct AddressBook
+{
+    addresses: BTreeMap<String, String>
+}
+
+impl Display for AddressBook
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        if self.addresses.is_empty()
+        {
+            return write!(f, "no saved addresses");
+        }
+
+        let lines = self.addresses.iter().map(|(name, address)| format!("{name}: {address}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        write!(f, "{lines}")
+    }
+}
+
+impl AddressBook
+{
+    pub fn load() -> Self
+    {
+        let addresses = match fs::read_to_string(ADDRESS_BOOK_PATH)
+        {
+            Ok(x) => serde_json::from_str(&x).unwrap_or_else(|err|
+            {
+                eprintln!("error parsing {ADDRESS_BOOK_PATH}: {err}");
+
+                BTreeMap::new()
+            }),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(err) =>
+            {
+                eprintln!("error reading {ADDRESS_BOOK_PATH}: {err}");
+
+                BTreeMap::new()
+            }
+        };
+
+        Self{addresses}
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str>
+    {
+        self.addresses.get(name).map(String::as_str)
+    }
+
+    pub fn insert(&mut self, name: String, address: String)
+    {
+        self.addresses.insert(name, address);
+    }
+
+    pub fn save(&self) -> io::Result<()>
+    {
+        let json = serde_json::to_string_pretty(&self.addresses).map_err(io::Error::other)?;
+
+        fs::write(ADDRESS_BOOK_PATH, json)
+    }
+}
@@ -1,8 +1,10 @@
 use std::{
     fs,
     f32,
+    mem,
     rc::{Rc, Weak},
-    cell::{RefMut, RefCell}
+    cell::{RefMut, RefCell},
+    collections::HashSet
 };
 
 use nalgebra::{Unit, Vector3, Vector2};
@@ -17,25 +19,41 @@ use yanyaengine::{
 };
 
 use crate::{
+    debug_config::*,
     client::UiEvent,
     common::{
         some_or_value,
         some_or_return,
         render_info::*,
+        overrides::overridden,
+        mods_info::HOOK_PREFIX,
+        crash_report,
         collider::*,
         character::*,
         SpecialTile,
         AnyEntities,
         Item,
         Inventory,
+        InventoryItem,
+        EquipSlot,
         Entity,
         EntityInfo,
+        PerkId,
+        Sound,
+        Unlock,
+        Noise,
+        NoiseKind,
+        message::Message,
+        dialogue::DIALOGUE_DISTANCE,
+        barter::TRADE_DISTANCE,
         entity::ClientEntities,
         lisp::{self, *},
         world::{CHUNK_VISUAL_SIZE, TILE_SIZE, Pos3, TilePos}
     }
 };
 
+use console::{Console, defined_names};
+
 use super::game_state::{
     GameState,
     WindowType,
@@ -44,9 +62,31 @@ use super::game_state::{
     InventoryWhich,
     UserEvent,
     ControlState,
-    Control
+    Control,
+    Hint,
+    TUTORIAL
 };
 
+mod console;
+
+
+// how much the aim gets pulled towards the locked target
+const LOCK_AIM_BIAS: f32 = 0.7;
+
+const CONTAINER_NOISE: f32 = 0.5;
+
+const HINT_LIFETIME: f32 = 6.0;
+
+// basically standing still
+const SURGERY_MAX_SPEED: f32 = TILE_SIZE * 0.1;
+
+// stays up for as long as the step isnt done
+const TUTORIAL_LIFETIME: f32 = 0.5;
+
+const LOW_STAMINA: f32 = 0.2;
+
+// how much faster the world goes while waiting around
+const FAST_FORWARD_SCALE: f32 = 4.0;
 
 pub struct Game
 {
@@ -73,7 +113,7 @@ impl Game
                 }),
                 collider: Some(ColliderInfo{
                     kind: ColliderType::RayZ,
-                    layer: ColliderLayer::Mouse,
+                    layer: ColliderLayer::MOUSE,
                     ghost: true,
                     ..Default::default()
                 }.into()),
@@ -91,14 +131,29 @@ impl Game
 
         let mut this = Self{info: Rc::new(RefCell::new(info)), game_state};
 
-        let standard_code = {
+        let (standard_code, hooks) = {
             let load = |path: &str|
             {
-                fs::read_to_string(path)
+                fs::read_to_string(overridden(path))
                     .unwrap_or_else(|err| panic!("{path} must exist ({err})"))
             };
 
-            load("lisp/standard.scm") + &load("lisp/console.scm")
+            let game_state = this.game_state.upgrade().unwrap();
+            let mods_info = game_state.borrow().mods_info.clone();
+
+            let scripts: Vec<String> = mods_info.scripts().map(|path| load(&path.to_string_lossy())).collect();
+
+            let hooks: HashSet<String> = scripts.iter().flat_map(|code| defined_names(code))
+                .filter(|name| name.starts_with(HOOK_PREFIX))
+                .collect();
+
+            // mod scripts run after the console so they can use and redefine anything in it
+            let code = scripts.into_iter().fold(load("lisp/standard.scm") + &load("lisp/console.scm"), |code, script|
+            {
+                code + &script
+            });
+
+            (code, hooks)
         };
 
         let console_infos: (LispState, Rc<Primitives>) = {
@@ -123,7 +178,31 @@ impl Game
             (state, primitives)
         };
 
-        this.info.borrow_mut().console.infos = Some(console_infos);
+        let names = {
+            let game_state = this.game_state.upgrade().unwrap();
+            let game_state = game_state.borrow();
+
+            let infos = game_state.entities().infos();
+
+            // items, enemies and recipes get passed as symbols so spaces turn into underscores
+            let data_names = infos.items_info.items().iter().map(|x| x.name.clone())
+                .chain(infos.enemies_info.items().iter().map(|x| x.name.clone()))
+                .chain(infos.recipes_info.items().iter().map(|x| x.name.clone()))
+                .map(|x| x.replace(' ', "_"));
+
+            console_infos.1.iter_infos().map(|(name, _)| name.to_owned())
+                .chain(defined_names(&standard_code))
+                .chain(data_names)
+                .collect::<Vec<_>>()
+        };
+
+        {
+            let mut info = this.info.borrow_mut();
+
+            info.console.state.set_names(names);
+            info.console.infos = Some(console_infos);
+            info.hooks = hooks;
+        }
 
         this
     }
@@ -185,6 +264,9 @@ impl Game
 
         self.player_container(|mut x| x.this_update(dt));
 
+        let scripts = mem::take(&mut self.info.borrow_mut().scripts);
+        scripts.into_iter().for_each(|script| self.console_command(script));
+
         let mut game_state_mut = game_state.borrow_mut();
         let changed_this_frame = game_state_mut.controls.changed_this_frame();
         let mouse_position = game_state_mut.ui_mouse_position();
@@ -198,10 +280,17 @@ impl Game
             {
                 let mut game_state = game_state.borrow_mut();
 
+                let clicked = event.as_mouse().map(|x| x.state == ControlState::Pressed).unwrap_or(false);
+
                 let captured = game_state.ui_input(event);
 
                 if captured
                 {
+                    if clicked
+                    {
+                        game_state.audio.play_ui("click");
+                    }
+
                     continue;
                 }
             }
@@ -237,7 +326,7 @@ impl Game
 
     fn on_key(&mut self, logical: Key, key: KeyCode) -> bool
     {
-        if self.info.borrow().console.contents.is_some()
+        if self.info.borrow().console.state.is_open()
         {
             match key
             {
@@ -246,7 +335,7 @@ impl Game
                     let mut info = self.info.borrow_mut();
                     if info.ctrl_held
                     {
-                        let contents = info.console.contents.as_mut().unwrap();
+                        let contents = info.console.state.contents_mut().unwrap();
 
                         match self.game_state.upgrade().unwrap().borrow_mut()
                             .controls
@@ -271,11 +360,7 @@ impl Game
                 },
                 KeyCode::Enter =>
                 {
-                    let contents = {
-                        let mut info = self.info.borrow_mut();
-
-                        info.console.contents.take().unwrap()
-                    };
+                    let contents = self.info.borrow_mut().console.state.submit().unwrap();
 
                     self.console_command(contents);
 
@@ -285,37 +370,52 @@ impl Game
                 },
                 KeyCode::Escape =>
                 {
-                    self.info.borrow_mut().console.contents.take();
+                    self.info.borrow_mut().console.state.close();
 
                     self.player_container(|mut x| x.update_console());
 
                     return true;
                 },
                 KeyCode::Backspace =>
+                {
+                    self.info.borrow_mut().console.state.contents_mut().unwrap().pop();
+
+                    self.player_container(|mut x| x.update_console());
+
+                    return true;
+                },
+                KeyCode::ArrowUp | KeyCode::ArrowDown =>
                 {
                     {
-                        let mut info = self.info.borrow_mut();
+                        let state = &mut self.info.borrow_mut().console.state;
 
-                        let contents = info.console.contents.as_mut().unwrap();
-                        contents.pop();
+                        if key == KeyCode::ArrowUp
+                        {
+                            state.history_previous();
+                        } else
+                        {
+                            state.history_next();
+                        }
                     }
 
                     self.player_container(|mut x| x.update_console());
 
                     return true;
                 },
+                KeyCode::Tab =>
+                {
+                    self.info.borrow_mut().console.state.complete();
+
+                    self.player_container(|mut x| x.update_console());
+
+                    return true;
+                },
                 _ => ()
             }
 
+            if let Some(text) = logical.to_text()
             {
-                let mut info = self.info.borrow_mut();
-
-                let contents = info.console.contents.as_mut().unwrap();
-
-                if let Some(text) = logical.to_text()
-                {
-                    *contents += text;
-                }
+                *self.info.borrow_mut().console.state.contents_mut().unwrap() += text;
             }
 
             self.player_container(|mut x| x.update_console());
@@ -331,20 +431,11 @@ impl Game
 
             if key == KeyCode::Backquote && is_debug()
             {
-                {
-                    let mut info = self.info.borrow_mut();
-                    info.console.contents = if info.console.contents.is_some()
-                    {
-                        None
-                    } else
-                    {
-                        Some(String::new())
-                    };
-                }
+                self.info.borrow_mut().console.state.toggle();
 
                 self.player_container(|mut x| x.update_console());
 
-                let state = if self.info.borrow().console.contents.is_some() { "opened" } else { "closed" };
+                let state = if self.info.borrow().console.state.is_open() { "opened" } else { "closed" };
                 eprintln!("debug console {state}");
 
                 true
@@ -386,6 +477,26 @@ impl Game
         memory.cons_list([tag, local, id])
     }
 
+    fn parse_faction(name: &str) -> Result<Faction, lisp::Error>
+    {
+        let faction: String = name.to_lowercase().chars().enumerate().map(|(i, c)|
+        {
+            if i == 0
+            {
+                c.to_ascii_uppercase()
+            } else
+            {
+                c
+            }
+        }).collect();
+
+        let faction = format!("\"{faction}\"");
+        serde_json::from_str(&faction).map_err(|_|
+        {
+            lisp::Error::Custom(format!("cant deserialize {faction} as Faction"))
+        })
+    }
+
     fn add_simple_setter<F>(&self, primitives: &mut Primitives, name: &str, f: F)
     where
         F: Fn(
@@ -669,23 +780,7 @@ impl Game
 
         self.add_simple_setter(&mut primitives, "set-faction", |entities, entity, memory, mut args|
         {
-            let faction = args.pop(memory).as_symbol()?;
-            let faction: String = faction.to_lowercase().chars().enumerate().map(|(i, c)|
-            {
-                if i == 0
-                {
-                    c.to_ascii_uppercase()
-                } else
-                {
-                    c
-                }
-            }).collect();
-
-            let faction = format!("\"{faction}\"");
-            let faction = serde_json::from_str(&faction).map_err(|_|
-            {
-                lisp::Error::Custom(format!("cant deserialize {faction} as Faction"))
-            })?;
+            let faction = Self::parse_faction(&args.pop(memory).as_symbol()?)?;
 
             get_component_mut!(character_mut, entities, entity).faction = faction;
 
@@ -721,6 +816,72 @@ impl Game
                 }));
         }
 
+        {
+            let game_state = self.game_state.clone();
+
+            primitives.add(
+                "spawn-enemy",
+                PrimitiveProcedureInfo::new_simple_effect(2, move |_state, memory, mut args|
+                {
+                    let game_state = game_state.upgrade().unwrap();
+                    let game_state = game_state.borrow();
+
+                    let name = args.pop(memory).as_symbol()?.replace('_', " ");
+
+                    let mut list = args.pop(memory).as_list();
+
+                    let mut next_float = ||
+                    {
+                        let current = list.clone()?;
+                        let value = current.car().as_float();
+
+                        list = current.cdr().as_list();
+
+                        value
+                    };
+
+                    let position = Vector3::new(next_float()?, next_float()?, next_float()?);
+
+                    if game_state.entities().infos().enemies_info.get_id(&name).is_none()
+                    {
+                        return Err(lisp::Error::Custom(format!("enemy named {name} doesnt exist")));
+                    }
+
+                    // only the server can make new entities
+                    game_state.send_message(Message::SpawnEnemy{name, position});
+
+                    memory.push_return(());
+
+                    Ok(())
+                }));
+        }
+
+        {
+            let game_state = self.game_state.clone();
+
+            primitives.add(
+                "craft",
+                PrimitiveProcedureInfo::new_simple_effect(1, move |_state, memory, mut args|
+                {
+                    let game_state = game_state.upgrade().unwrap();
+                    let game_state = game_state.borrow();
+
+                    let name = args.pop(memory).as_symbol()?.replace('_', " ");
+
+                    let recipe = game_state.entities().infos().recipes_info.get_id(&name).ok_or_else(||
+                    {
+                        lisp::Error::Custom(format!("recipe named {name} doesnt exist"))
+                    })?;
+
+                    // the server checks if the ingredients r there
+                    game_state.send_message(Message::Craft{recipe});
+
+                    memory.push_return(());
+
+                    Ok(())
+                }));
+        }
+
         {
             let player_entity = self.info.borrow().entity;
 
@@ -808,6 +969,27 @@ impl Game
                 }));
         }
 
+        {
+            let game_state = self.game_state.clone();
+
+            primitives.add(
+                "inspect",
+                PrimitiveProcedureInfo::new_simple_effect(1, move |_state, memory, mut args|
+                {
+                    let game_state = game_state.upgrade().unwrap();
+                    let mut game_state = game_state.borrow_mut();
+
+                    let entity = Self::pop_entity(&mut args, memory)?;
+
+                    let spawn_position = game_state.ui_mouse_position();
+                    game_state.add_window(WindowCreateInfo::EntityInspector{spawn_position, entity});
+
+                    memory.push_return(());
+
+                    Ok(())
+                }));
+        }
+
         {
             let game_state = self.game_state.clone();
 
@@ -829,6 +1011,18 @@ impl Game
                 }));
         }
 
+        primitives.add(
+            "toggle-physics-overlay",
+            PrimitiveProcedureInfo::new_simple_effect(0, move |_state, memory, _args|
+            {
+                let state = DebugConfig::toggle(DebugTool::PhysicsOverlay);
+
+                // shows up in the console as the result
+                memory.push_return(state);
+
+                Ok(())
+            }));
+
         {
             let info = self.info.clone();
 
@@ -870,12 +1064,19 @@ impl Game
             }
         };
 
+        let push_line = |line: String|
+        {
+            crash_report::push_console_line(line.clone());
+            self.info.borrow_mut().console.state.push_line(line);
+        };
+
         let mut lisp = match Lisp::new_with_config(config, &command)
         {
             Ok(x) => x,
             Err(err) =>
             {
                 eprintln!("error parsing {command}: {err}");
+                push_line(format!("{command} -> parse error: {err}"));
                 return;
             }
         };
@@ -886,11 +1087,13 @@ impl Game
             Err(err) =>
             {
                 eprintln!("error running {command}: {err}");
+                push_line(format!("{command} -> error: {err}"));
                 return;
             }
         };
 
         eprintln!("ran command {command}, result: {result}");
+        push_line(format!("{command} -> {result}"));
 
         self.info.borrow_mut().update_memory(result.into_state());
     }
@@ -918,7 +1121,7 @@ struct PlayerCreateInfo
 struct ConsoleInfo
 {
     entity: Entity,
-    contents: Option<String>,
+    state: Console,
     infos: Option<(LispState, Rc<Primitives>)>,
 }
 
@@ -928,7 +1131,7 @@ impl ConsoleInfo
     {
         Self{
             entity: info.console_entity,
-            contents: None,
+            state: Console::new(),
             infos: None
         }
     }
@@ -951,6 +1154,14 @@ impl InventoriesInfo
     }
 }
 
+struct DialogueInfo
+{
+    speaker: Entity,
+    window: WindowType,
+    // the server hasnt answered yet
+    started: bool
+}
+
 struct PlayerInfo
 {
     camera: Entity,
@@ -961,9 +1172,18 @@ struct PlayerInfo
     inventories: InventoriesInfo,
     console: ConsoleInfo,
     previous_stamina: Option<f32>,
+    previous_needs: Option<(f32, f32)>,
+    previous_health: Option<f32>,
     previous_cooldown: (f32, f32),
+    locked: Option<Entity>,
     ctrl_held: bool,
-    interacted: bool
+    interacted: bool,
+    dialogue: Option<DialogueInfo>,
+    // lisp from dialogue choices and mod hooks, ran after the update
+    scripts: Vec<String>,
+    // hook procedures the mod scripts define
+    hooks: HashSet<String>,
+    trade: Option<(Entity, WindowType)>
 }
 
 impl PlayerInfo
@@ -981,9 +1201,16 @@ impl PlayerInfo
             inventories: InventoriesInfo::new(),
             console,
             previous_stamina: None,
+            previous_needs: None,
+            previous_health: None,
             previous_cooldown: (0.0, 0.0),
+            locked: None,
             ctrl_held: false,
-            interacted: false
+            interacted: false,
+            dialogue: None,
+            scripts: Vec::new(),
+            hooks: HashSet::new(),
+            trade: None
         }
     }
 
@@ -1047,7 +1274,8 @@ impl<'a> PlayerContainer<'a>
 
         if let Some(position) = position
         {
-            self.game_state.camera.write().set_position(position.into());
+            let shaken = position + self.game_state.camera_shake_offset();
+            self.game_state.camera.write().set_position(shaken.into());
 
             self.game_state.camera_moved(position.into());
 
@@ -1087,8 +1315,39 @@ impl<'a> PlayerContainer<'a>
             x.floating()
         }).unwrap_or(false);
 
+        if state == ControlState::Pressed
+        {
+            if let Some(hint) = self.current_tutorial()
+            {
+                if hint.completed_by(control)
+                {
+                    self.mark_hint(hint);
+                }
+            }
+        }
+
         match control
         {
+            Control::Pause if state == ControlState::Pressed =>
+            {
+                let scale = if self.game_state.is_paused() { 1.0 } else { 0.0 };
+                self.game_state.request_time_scale(scale);
+            },
+            Control::FastForward if state == ControlState::Pressed =>
+            {
+                let scale = if self.game_state.time_scale() == FAST_FORWARD_SCALE { 1.0 } else { FAST_FORWARD_SCALE };
+                self.game_state.request_time_scale(scale);
+            },
+            Control::Inventory =>
+            {
+                self.toggle_inventory();
+            },
+            Control::Perks =>
+            {
+                self.perk_choices();
+            },
+            // nothing happens in a paused world
+            _ if self.game_state.is_paused() => (),
             Control::Crawl if !is_floating =>
             {
                 let entities = self.game_state.entities();
@@ -1100,13 +1359,82 @@ impl<'a> PlayerContainer<'a>
                     entities.anatomy_changed(self.info.entity);
                 }
             },
+            Control::Crouch if state == ControlState::Pressed =>
+            {
+                if let Some(mut character) = self.game_state.entities().character_mut(self.info.entity)
+                {
+                    character.crouching = !character.crouching;
+                }
+            },
             Control::Poke =>
             {
                 self.character_action(CharacterAction::Poke{state: !state.to_bool()});
             },
+            Control::Block =>
+            {
+                self.character_action(CharacterAction::Block{state: !state.to_bool()});
+            },
+            Control::LockOn =>
+            {
+                if !state.to_bool()
+                {
+                    return;
+                }
+
+                if self.info.locked.is_some()
+                {
+                    self.set_locked(None);
+                } else
+                {
+                    let target = self.game_state.entities().lock_targets(self.info.entity).first().copied();
+                    self.set_locked(target);
+                }
+            },
+            Control::CycleLock =>
+            {
+                if !state.to_bool()
+                {
+                    return;
+                }
+
+                let targets = self.game_state.entities().lock_targets(self.info.entity);
+
+                let next = self.info.locked.and_then(|locked|
+                {
+                    targets.iter().position(|x| *x == locked)
+                }).map(|index| (index + 1) % targets.len()).unwrap_or(0);
+
+                self.set_locked(targets.get(next).copied());
+            },
+            Control::Dodge =>
+            {
+                if !state.to_bool()
+                {
+                    return;
+                }
+
+                // without any movement keys held roll backwards
+                let direction = self.movement_direction().unwrap_or_else(||
+                {
+                    let rotation = self.game_state.entities().character(self.info.entity)
+                        .map(|character| character.rotation)
+                        .unwrap_or_default();
+
+                    -Vector3::new(rotation.cos(), rotation.sin(), 0.0)
+                });
+
+                self.character_action(CharacterAction::Dodge(direction));
+            },
             Control::Shoot =>
             {
                 let mut target = some_or_return!(self.mouse_position());
+
+                // pull the aim towards whatever is locked on
+                if let Some(locked) = self.locked_position()
+                {
+                    target = target.lerp(&locked, LOCK_AIM_BIAS);
+                }
+
                 target.z = some_or_return!(self.player_position()).z;
 
                 self.character_action(CharacterAction::Ranged{state: !state.to_bool(), target});
@@ -1132,33 +1460,13 @@ impl<'a> PlayerContainer<'a>
                 if let Some(mouse_touched) = entities.collider(self.info.mouse_entity)
                     .and_then(|x| x.collided().first().copied())
                 {
+                    let lootable = entities.is_lootable(mouse_touched)
+                        || entities.is_companion_of(mouse_touched, self.info.entity);
+
                     if entities.within_interactable_distance(self.info.entity, mouse_touched)
-                        && entities.is_lootable(mouse_touched)
+                        && lootable
                     {
-                        if let Some(previous) = self.info.inventories.other.take()
-                            .and_then(|x| x.upgrade())
-                        {
-                            let _ = self.game_state.remove_window(previous);
-                        }
-
-                        self.info.other_entity = Some(mouse_touched);
-
-                        let id = self.game_state.add_window(WindowCreateInfo::Inventory{
-                            spawn_position: self.game_state.ui_mouse_position(),
-                            entity: mouse_touched,
-                            on_click: Box::new(|_anchor, item|
-                            {
-                                UserEvent::UiAction(Rc::new(move |game_state|
-                                {
-                                    game_state.create_popup(vec![
-                                        UserEvent::Take(item),
-                                        UserEvent::Info{which: InventoryWhich::Other, item}
-                                    ]);
-                                }))
-                            })
-                        });
-
-                        self.info.inventories.other = Some(id);
+                        self.open_loot(mouse_touched);
 
                         return;
                     }
@@ -1174,53 +1482,198 @@ impl<'a> PlayerContainer<'a>
 
                 self.character_action(CharacterAction::Throw(mouse_transform.position));
             },
-            Control::Inventory =>
-            {
-                self.toggle_inventory();
-            },
             _ => ()
         }
     }
 
-    fn character_action(&self, action: CharacterAction)
+    // false if it was already seen before
+    fn mark_hint(&self, hint: Hint) -> bool
     {
-        if let Some(mut character) = self.game_state.entities().character_mut(self.info.entity)
+        let entities = self.game_state.entities();
+        let mut player = some_or_value!(entities.player_mut(self.info.entity), false);
+
+        if player.seen_hint(hint.name())
         {
-            character.push_action(action);
+            return false;
         }
+
+        player.hints_seen.push(hint.name().to_owned());
+
+        self.game_state.send_message(Message::SetPlayer{
+            entity: self.info.entity,
+            component: player.clone().into()
+        });
+
+        true
     }
 
-    fn update_console(&mut self)
+    fn show_hint(&mut self, hint: Hint)
     {
-        self.game_state.entities()
-            .render_mut(self.info.console.entity)
-            .unwrap()
-            .visible = self.info.console.contents.is_some();
+        if self.mark_hint(hint)
+        {
+            let text = hint.text(&self.game_state.controls);
 
-        let text = self.info.console.contents.clone().unwrap_or_default();
+            self.game_state.ui_notifications.set_hint(
+                &mut self.game_state.entities.entities,
+                self.info.entity,
+                HINT_LIFETIME,
+                text
+            );
+        }
+    }
 
-        let object = RenderObjectKind::Text{
-            text,
-            font_size: 30,
-            font: FontStyle::Sans,
-            align: TextAlign::centered()
-        }.into();
+    fn current_tutorial(&self) -> Option<Hint>
+    {
+        let player = self.game_state.entities().player(self.info.entity)?;
 
-        self.game_state.entities().set_deferred_render_object(self.info.console.entity, object);
+        TUTORIAL.into_iter().find(|hint| !player.seen_hint(hint.name()))
     }
 
-    fn handle_user_event(&mut self, event: UserEvent)
+    fn update_hints(&mut self)
     {
-        let player = self.info.entity;
+        let entities = self.game_state.entities();
 
-        self.game_state.close_popup();
-        match event
+        let health = entities.anatomy(self.info.entity).map(|x| x.health_fraction());
+        let previous_health = mem::replace(&mut self.info.previous_health, health);
+
+        if let (Some(previous), Some(current)) = (previous_health, health)
         {
-            UserEvent::UiAction(action) =>
-            {
-                action(self.game_state);
-            },
-            UserEvent::Info{which, item} =>
+            if current < previous
+            {
+                self.show_hint(Hint::FirstFight);
+            }
+        }
+
+        if self.info.previous_stamina.map(|x| x < LOW_STAMINA).unwrap_or(false)
+        {
+            self.show_hint(Hint::LowStamina);
+        }
+
+        if let Some(hint) = self.current_tutorial()
+        {
+            let text = hint.text(&self.game_state.controls);
+
+            self.game_state.ui_notifications.set_tutorial(
+                &mut self.game_state.entities.entities,
+                self.info.entity,
+                TUTORIAL_LIFETIME,
+                text
+            );
+        }
+    }
+
+    fn character_action(&self, action: CharacterAction)
+    {
+        if let Some(mut character) = self.game_state.entities().character_mut(self.info.entity)
+        {
+            character.push_action(action);
+        }
+    }
+
+    fn open_loot(&mut self, entity: Entity)
+    {
+        if let Some(previous) = self.info.inventories.other.take()
+            .and_then(|x| x.upgrade())
+        {
+            let _ = self.game_state.remove_window(previous);
+        }
+
+        if let Some(previous) = self.info.other_entity.filter(|previous| *previous != entity)
+        {
+            self.set_container_open(previous, false);
+        }
+
+        self.info.other_entity = Some(entity);
+
+        self.container_sound(entity, "container_open");
+        self.set_container_open(entity, true);
+
+        let id = self.game_state.add_window(WindowCreateInfo::Inventory{
+            spawn_position: self.game_state.ui_mouse_position(),
+            entity,
+            on_click: Box::new(|_anchor, item|
+            {
+                UserEvent::UiAction(Rc::new(move |game_state|
+                {
+                    game_state.create_popup(vec![
+                        UserEvent::Take(item),
+                        UserEvent::Info{which: InventoryWhich::Other, item}
+                    ]);
+                }))
+            })
+        });
+
+        self.info.inventories.other = Some(id);
+
+        self.game_state.lifetime_stats.add_looted();
+        self.show_hint(Hint::FirstLoot);
+    }
+
+    // the server sends the new state back if its allowed
+    fn set_container_open(&self, entity: Entity, open: bool)
+    {
+        let is_open = some_or_return!(self.game_state.entities().container(entity)).is_open();
+
+        if is_open == open
+        {
+            return;
+        }
+
+        self.game_state.send_message(Message::OpenContainer{entity, open});
+    }
+
+    // bodies dont creak when getting looted
+    fn container_sound(&self, entity: Entity, name: &str)
+    {
+        let entities = self.game_state.entities();
+
+        if entities.anatomy_exists(entity)
+        {
+            return;
+        }
+
+        let position = some_or_return!(entities.transform(entity)).position;
+
+        self.game_state.echo_message(Message::PlaySound{sound: Sound::new(name, position)});
+        self.game_state.make_noise(Noise::new(NoiseKind::Container, position, CONTAINER_NOISE).with_source(self.info.entity));
+    }
+
+    fn update_console(&mut self)
+    {
+        self.game_state.entities()
+            .render_mut(self.info.console.entity)
+            .unwrap()
+            .visible = self.info.console.state.is_open();
+
+        let text = self.info.console.state.text();
+
+        let object = RenderObjectKind::Text{
+            text,
+            font_size: 30,
+            font: FontStyle::Sans,
+            align: TextAlign::centered()
+        }.into();
+
+        self.game_state.entities().set_deferred_render_object(self.info.console.entity, object);
+    }
+
+    fn handle_user_event(&mut self, event: UserEvent)
+    {
+        let player = self.info.entity;
+
+        if let Some(hook) = event.hook().filter(|hook| self.info.hooks.contains(hook))
+        {
+            self.info.scripts.push(format!("({hook})"));
+        }
+
+        self.game_state.close_popup();
+        match event
+        {
+            UserEvent::UiAction(action) =>
+            {
+                action(self.game_state);
+            },
+            UserEvent::Info{which, item} =>
             {
                 if let Some(item) = self.get_inventory(which)
                     .and_then(|inventory| inventory.get(item).cloned())
@@ -1254,313 +1707,1169 @@ impl<'a> PlayerContainer<'a>
             {
                 self.game_state.entities().character_mut(player).unwrap().set_holding(Some(item));
             },
+            UserEvent::Equip(item) =>
+            {
+                self.equip(item);
+            },
+            UserEvent::Unequip(slot) =>
+            {
+                self.unequip(slot);
+            },
+            UserEvent::PickPerk{id, ..} =>
+            {
+                self.pick_perk(id);
+            },
             UserEvent::Take(item) =>
             {
                 if let Some(taken) = self.get_inventory(InventoryWhich::Other)
                     .and_then(|mut inventory| inventory.remove(item))
                 {
-                    self.game_state.entities()
-                        .inventory_mut(self.info.entity)
+                    let entities = self.game_state.entities();
+
+                    entities.inventory_mut(self.info.entity)
                         .unwrap()
                         .push(taken);
+
+                    // the server notices if it was stolen
+                    if let Some(other) = self.info.other_entity
+                    {
+                        self.game_state.send_message(Message::SetInventory{
+                            entity: other,
+                            component: entities.inventory(other).unwrap().clone().into()
+                        });
+                    }
+
+                    self.game_state.send_message(Message::SetInventory{
+                        entity: player,
+                        component: entities.inventory(player).unwrap().clone().into()
+                    });
                 } else
                 {
                     eprintln!("tried to take item that doesnt exist");
                 }
+            },
+            UserEvent::DialogueChoice{speaker, choice} =>
+            {
+                self.dialogue_choice(speaker, choice);
+            },
+            UserEvent::TradeOffer{vendor, offer} =>
+            {
+                self.game_state.send_message(Message::TradeOffer{entity: vendor, offer});
+            },
+            UserEvent::OpenTrade(vendor) =>
+            {
+                self.open_trade(vendor);
+            },
+            UserEvent::Script(script) =>
+            {
+                self.info.scripts.push(script);
+            },
+            UserEvent::Consume(item) =>
+            {
+                // the server removes it and sends the inventory back
+                self.game_state.send_message(Message::ConsumeItem{item});
+            },
+            UserEvent::Apply(item) =>
+            {
+                self.apply_item(item);
+            },
+            UserEvent::Operate(item) =>
+            {
+                self.operate(item);
             }
         }
     }
 
-    fn get_inventory_entity(&self, which: InventoryWhich) -> Option<Entity>
+    fn dialogue_choice(&mut self, speaker: Entity, choice: usize)
     {
-        match which
+        if !some_or_return!(self.game_state.entities().dialogue(speaker)).talking_to(self.info.entity)
         {
-            InventoryWhich::Other => self.info.other_entity,
-            InventoryWhich::Player => Some(self.info.entity)
+            return;
         }
+
+        // the server checks the choice and sends back whatever it ends up doing
+        self.game_state.send_message(Message::DialogueChoose{entity: speaker, choice: Some(choice)});
     }
 
-    fn get_inventory(&self, which: InventoryWhich) -> Option<RefMut<Inventory>>
+    fn open_trade(&mut self, vendor: Entity)
     {
-        let entity = self.get_inventory_entity(which);
+        self.close_trade();
 
-        entity.and_then(|entity| self.game_state.entities().inventory_mut(entity))
+        let window = self.game_state.add_window(WindowCreateInfo::Trade{
+            spawn_position: self.game_state.ui_mouse_position(),
+            player: self.info.entity,
+            vendor
+        });
+
+        self.info.trade = Some((vendor, window));
     }
 
-    fn update_user_events(&mut self)
+    fn close_trade(&mut self)
     {
-        let events = self.game_state.user_receiver.borrow_mut().consume();
-        events.for_each(|event|
+        let (_, window) = some_or_return!(self.info.trade.take());
+
+        if let Some(window) = window.upgrade()
         {
-            self.handle_user_event(event);
-        });
+            let _ = self.game_state.remove_window(window);
+        }
     }
 
-    fn toggle_inventory(&mut self)
+
+    fn equip(&mut self, item: InventoryItem)
     {
-        if self.info.inventories.player.take().and_then(|window|
+        let player = self.info.entity;
+        let entities = self.game_state.entities();
+
+        if !entities.equipment_exists(player)
         {
-            window.upgrade().map(|window| self.game_state.remove_window(window).is_ok())
-        }).is_none()
+            return;
+        }
+
+        let slot = {
+            let inventory = some_or_return!(entities.inventory(player));
+            let item = some_or_return!(inventory.get(item));
+
+            some_or_return!(self.game_state.items_info.get(item.id).wearable).slot
+        };
+
+        let taken = some_or_return!(entities.inventory_mut(player).and_then(|mut inventory|
         {
-            let window = self.game_state.add_window(WindowCreateInfo::Inventory{
-                spawn_position: self.game_state.ui_mouse_position(),
-                entity: self.info.entity,
-                on_click: Box::new(|_anchor, item|
-                {
-                    UserEvent::UiAction(Rc::new(move |game_state|
-                    {
-                        game_state.create_popup(vec![
-                            UserEvent::Wield(item),
-                            UserEvent::Drop{which: InventoryWhich::Player, item},
-                            UserEvent::Info{which: InventoryWhich::Player, item}
-                        ]);
-                    }))
-                })
-            });
+            inventory.remove(item)
+        }));
 
-            self.info.inventories.player = Some(window);
+        if let Some(mut character) = entities.character_mut(player)
+        {
+            character.dropped_item(item);
+        }
+
+        let previous = entities.equipment_mut(player).unwrap().equip(slot, taken);
+
+        if let Some(previous) = previous
+        {
+            entities.inventory_mut(player).unwrap().push(previous);
+        }
+
+        self.equipment_changed();
+    }
+
+    fn unequip(&mut self, slot: EquipSlot)
+    {
+        let player = self.info.entity;
+        let entities = self.game_state.entities();
+
+        let taken = some_or_return!(entities.equipment_mut(player).and_then(|mut equipment|
+        {
+            equipment.unequip(slot)
+        }));
+
+        some_or_return!(entities.inventory_mut(player)).push(taken);
+
+        self.equipment_changed();
+    }
+
+    fn apply_item(&mut self, item: InventoryItem)
+    {
+        let player = self.info.entity;
+
+        let applied = {
+            let entities = self.game_state.entities();
+
+            let id = some_or_return!(entities.inventory(player).and_then(|x| x.get(item).map(|x| x.id)));
+            let info = self.game_state.items_info.get(id);
+
+            let mut anatomy = some_or_return!(entities.anatomy_mut(player));
+
+            if let Some(bandage) = info.bandage.as_ref()
+            {
+                anatomy.bandage(bandage)
+            } else if let Some(drug) = info.drug.as_ref()
+            {
+                anatomy.apply_drug(drug)
+            } else
+            {
+                false
+            }
+        };
+
+        if !applied
+        {
+            self.player_bark("that wont do anything");
+
+            return;
+        }
+
+        self.used_on_self(item);
+    }
+
+    fn operate(&mut self, item: InventoryItem)
+    {
+        let player = self.info.entity;
+
+        let failed = {
+            let entities = self.game_state.entities();
+
+            let inventory = some_or_return!(entities.inventory(player));
+            let id = some_or_return!(inventory.get(item).map(|x| x.id));
+            let prosthetic = some_or_return!(self.game_state.items_info.get(id).prosthetic.clone());
+
+            let has_tools = inventory.items().iter().any(|x| self.game_state.items_info.get(x.id).surgical);
+            drop(inventory);
+
+            let stationary = entities.physical(player).map(|physical|
+            {
+                physical.velocity().xy().magnitude() < SURGERY_MAX_SPEED
+            }).unwrap_or(true);
+
+            if !has_tools
+            {
+                Some("i need something to operate with")
+            } else if !stationary
+            {
+                Some("i need to hold still for this")
+            } else
+            {
+                let mut anatomy = some_or_return!(entities.anatomy_mut(player));
+
+                (!anatomy.replace_part(&prosthetic)).then_some("theres nothing to replace")
+            }
+        };
+
+        if let Some(text) = failed
+        {
+            self.player_bark(text);
+
+            return;
+        }
+
+        // walking, crawling or lying all depend on what just got replaced
+        self.game_state.entities().anatomy_changed(player);
+
+        self.used_on_self(item);
+    }
+
+    fn player_bark(&mut self, text: &str)
+    {
+        self.game_state.ui_notifications.set_bark(
+            &mut self.game_state.entities.entities,
+            self.info.entity,
+            2.0,
+            text.to_owned()
+        );
+    }
+
+    // removes the item and tells the server what happened to the player
+    fn used_on_self(&mut self, item: InventoryItem)
+    {
+        let player = self.info.entity;
+        let entities = self.game_state.entities();
+
+        some_or_return!(entities.inventory_mut(player)).remove(item);
+
+        if let Some(mut character) = entities.character_mut(player)
+        {
+            character.removed_items(&[item]);
+        }
+
+        let inventory = entities.inventory(player).unwrap().clone();
+        let anatomy = entities.anatomy(player).unwrap().clone();
+
+        self.game_state.send_message(Message::SetInventory{
+            entity: player,
+            component: inventory.into()
+        });
+
+        self.game_state.send_message(Message::SetAnatomy{
+            entity: player,
+            component: anatomy.into()
+        });
+    }
+
+    fn equipment_changed(&mut self)
+    {
+        let entities = self.game_state.entities();
+        let player = self.info.entity;
+
+        if let Some(mut character) = entities.character_mut(player)
+        {
+            character.equipment_changed();
+        }
+
+        let inventory = entities.inventory(player).unwrap().clone();
+        let equipment = entities.equipment(player).unwrap().clone();
+
+        self.game_state.send_message(Message::SetInventory{
+            entity: player,
+            component: inventory.into()
+        });
+
+        self.game_state.send_message(Message::SetEquipment{
+            entity: player,
+            component: equipment.into()
+        });
+    }
+
+    fn perk_choices(&mut self)
+    {
+        let responses = {
+            let player = some_or_return!(self.game_state.entities().player(self.info.entity));
+
+            if player.perk_points == 0
+            {
+                return;
+            }
+
+            let perks_info = &self.game_state.perks_info;
+
+            perks_info.choices(&player, 3).into_iter().map(|id|
+            {
+                UserEvent::PickPerk{id, name: perks_info.get(id).name.clone()}
+            }).collect::<Vec<_>>()
+        };
+
+        if responses.is_empty()
+        {
+            return;
+        }
+
+        self.game_state.create_popup(responses);
+    }
+
+    fn pick_perk(&mut self, id: PerkId)
+    {
+        // the server sends the player and anatomy back if it was allowed
+        self.game_state.send_message(Message::PickPerk{perk: id});
+    }
+
+    fn get_inventory_entity(&self, which: InventoryWhich) -> Option<Entity>
+    {
+        match which
+        {
+            InventoryWhich::Other => self.info.other_entity,
+            InventoryWhich::Player => Some(self.info.entity)
+        }
+    }
+
+    fn get_inventory(&self, which: InventoryWhich) -> Option<RefMut<Inventory>>
+    {
+        let entity = self.get_inventory_entity(which);
+
+        entity.and_then(|entity| self.game_state.entities().inventory_mut(entity))
+    }
+
+    fn update_user_events(&mut self)
+    {
+        let events = self.game_state.user_receiver.borrow_mut().consume();
+        events.for_each(|event|
+        {
+            self.handle_user_event(event);
+        });
+    }
+
+    fn toggle_inventory(&mut self)
+    {
+        if self.info.inventories.player.take().and_then(|window|
+        {
+            window.upgrade().map(|window| self.game_state.remove_window(window).is_ok())
+        }).is_none()
+        {
+            let window = self.game_state.add_window(WindowCreateInfo::Inventory{
+                spawn_position: self.game_state.ui_mouse_position(),
+                entity: self.info.entity,
+                on_click: Box::new(|_anchor, item|
+                {
+                    UserEvent::UiAction(Rc::new(move |game_state|
+                    {
+                        let (wearable, edible, appliable, operable) = game_state.entities()
+                            .inventory(game_state.player())
+                            .and_then(|inventory| inventory.get(item).map(|item|
+                            {
+                                let info = game_state.items_info.get(item.id);
+
+                                let appliable = info.bandage.is_some() || info.drug.is_some();
+                                let operable = info.prosthetic.is_some();
+
+                                (info.wearable.is_some(), info.food.is_some(), appliable, operable)
+                            }))
+                            .unwrap_or((false, false, false, false));
+
+                        let mut responses = vec![UserEvent::Wield(item)];
+
+                        if wearable
+                        {
+                            responses.push(UserEvent::Equip(item));
+                        }
+
+                        if edible
+                        {
+                            responses.push(UserEvent::Consume(item));
+                        }
+
+                        if appliable
+                        {
+                            responses.push(UserEvent::Apply(item));
+                        }
+
+                        if operable
+                        {
+                            responses.push(UserEvent::Operate(item));
+                        }
+
+                        responses.extend([
+                            UserEvent::Drop{which: InventoryWhich::Player, item},
+                            UserEvent::Info{which: InventoryWhich::Player, item}
+                        ]);
+
+                        game_state.create_popup(responses);
+                    }))
+                })
+            });
+
+            self.info.inventories.player = Some(window);
+        }
+    }
+
+    fn update_inventory_inner(
+        entities: &mut ClientEntities,
+        info: &mut PlayerInfo,
+        which: InventoryWhich
+    )
+    {
+        let entity = match which
+        {
+            InventoryWhich::Other => info.other_entity.unwrap(),
+            InventoryWhich::Player => info.entity
+        };
+
+        let entity_creator = EntityCreator{entities};
+
+        if let Some(window) = match which
+        {
+            InventoryWhich::Player => &info.inventories.player,
+            InventoryWhich::Other => &info.inventories.other
+        }.as_ref().and_then(|window| window.upgrade())
+        {
+            let mut window = window.borrow_mut();
+            let inventory = window.as_inventory_mut().unwrap();
+
+            inventory.full_update(&entity_creator, entity);
+        }
+    }
+
+    pub fn this_update(&mut self, dt: f32)
+    {
+        if !self.exists()
+        {
+            return;
+        }
+
+        self.update_user_events();
+
+        let mouse_position = self.game_state.world_mouse_position();
+        let mouse_position = Vector3::new(mouse_position.x, mouse_position.y, 0.0);
+        let camera_position = self.game_state.camera.read().position().coords;
+
+        {
+            let entities = self.game_state.entities_mut();
+
+            entities.transform_mut(self.info.mouse_entity).unwrap()
+                .position = camera_position + mouse_position;
+
+            entities.update_mouse_highlight(
+                self.info.entity,
+                self.info.mouse_entity
+            );
+
+            let player_position = entities.transform(self.info.entity).unwrap().position;
+
+            let current_follow = entities.transform(self.info.follow).unwrap().position;
+
+            let follow_position = self.game_state.camera_follow_position(
+                current_follow,
+                player_position,
+                mouse_position
+            );
+
+            self.game_state.entities_mut().transform_mut(self.info.follow).unwrap()
+                .position = follow_position;
+        }
+
+        let entities = &mut self.game_state.entities.entities;
+        if let Some((current_stamina, current_cooldown)) = entities.character(self.info.entity).map(|x|
+        {
+            // dodging shares the bar with attacks
+            (x.stamina_fraction(entities), x.attack_cooldown().max(x.dodge_cooldown()))
+        })
+        {
+            let delay = 0.7;
+
+            if self.info.previous_stamina != current_stamina
+            {
+                let was_none = self.info.previous_stamina.is_none();
+                self.info.previous_stamina = current_stamina;
+
+                if !was_none
+                {
+                    self.game_state.ui_notifications.set_stamina_bar(
+                        entities,
+                        self.info.entity,
+                        delay,
+                        current_stamina.unwrap_or(0.0)
+                    );
+                }
+            }
+
+            if self.info.previous_cooldown.1 != current_cooldown
+            {
+                self.info.previous_cooldown.1 = current_cooldown;
+
+                self.info.previous_cooldown.0 = if current_cooldown <= 0.0
+                {
+                    0.0
+                } else
+                {
+                    self.info.previous_cooldown.0.max(current_cooldown)
+                };
+
+                let fraction = if self.info.previous_cooldown.0 > 0.0
+                {
+                    current_cooldown / self.info.previous_cooldown.0
+                } else
+                {
+                    0.0
+                };
+
+                self.game_state.ui_notifications.set_weapon_cooldown_bar(
+                    entities,
+                    self.info.entity,
+                    delay,
+                    fraction
+                );
+            }
+
+            let sneaking = entities.character(self.info.entity)
+                .map(|x| x.visibility_scale() < 1.0)
+                .unwrap_or(false);
+
+            if sneaking
+            {
+                let visibility = entities.visibility(&self.game_state.world, self.info.entity);
+
+                self.game_state.ui_notifications.set_visibility_bar(
+                    entities,
+                    self.info.entity,
+                    delay,
+                    visibility
+                );
+            }
+
+            if let Some((starving, current_needs)) = entities.needs(self.info.entity)
+                .map(|x| (x.is_starving(), (x.hunger, x.thirst)))
+            {
+                // only bother showing them after eating or when theyre getting low
+                let ate = self.info.previous_needs.map(|(hunger, thirst)|
+                {
+                    current_needs.0 > hunger || current_needs.1 > thirst
+                }).unwrap_or(false);
+
+                self.info.previous_needs = Some(current_needs);
+
+                if ate || starving
+                {
+                    let lifetime = if ate { delay * 3.0 } else { delay };
+
+                    self.game_state.ui_notifications.set_hunger_bar(
+                        entities,
+                        self.info.entity,
+                        lifetime,
+                        current_needs.0
+                    );
+
+                    self.game_state.ui_notifications.set_thirst_bar(
+                        entities,
+                        self.info.entity,
+                        lifetime,
+                        current_needs.1
+                    );
+                }
+            }
+        }
+
+        if let Some(movement) = self.movement_direction()
+        {
+            if let Some(mut character) = self.game_state.entities()
+                .character_mut(self.info.entity)
+            {
+                character.sprinting = self.game_state.pressed(Control::Sprint);
+            }
+
+            self.walk(movement, dt);
+        }
+
+        let able_to_move = self.game_state.entities()
+            .anatomy(self.info.entity)
+            .map(|anatomy| anatomy.speed().is_some())
+            .unwrap_or(false);
+
+        self.update_lock();
+
+        if able_to_move
+        {
+            if let Some(position) = self.locked_position()
+            {
+                self.look_at_world(position);
+            } else
+            {
+                self.look_at_mouse();
+            }
+        }
+
+        if let Some(other_entity) = self.info.other_entity
+        {
+            if !self.game_state.entities().within_interactable_distance(self.info.entity, other_entity)
+            {
+                if let Some(window) = self.info.inventories.other.take().and_then(|x| x.upgrade())
+                {
+                    let _ = self.game_state.remove_window(window);
+
+                    self.container_sound(other_entity, "container_close");
+                }
+            }
+
+            // closing the window by hand shuts it too
+            if self.info.inventories.other.as_ref().and_then(|x| x.upgrade()).is_none()
+            {
+                self.set_container_open(other_entity, false);
+            }
+        }
+
+        self.update_dialogue();
+
+        if let Some((vendor, _)) = self.info.trade
+        {
+            if !self.in_range(vendor, TRADE_DISTANCE)
+            {
+                self.close_trade();
+            }
+        }
+
+        let mut tile_info = None;
+        self.colliding_info(|mut colliding|
+        {
+            let world = &self.game_state.world;
+
+            let stairs: Option<TilePos> = world.tiles_inside(&colliding, |tile|
+            {
+                tile.map(|tile|
+                {
+                    world.tile_info(*tile).special == Some(SpecialTile::StairsUp)
+                }).unwrap_or(false)
+            }).next();
+
+            let interact_button = ||
+            {
+                self.game_state.controls.key_for(&Control::Interact).map(ToString::to_string)
+                    .unwrap_or_else(|| "unassigned".to_owned())
+            };
+
+            if let Some(stairs) = stairs
+            {
+                let above = stairs.offset(Pos3::new(0, 0, 1));
+
+                if world.tile(above).and_then(|tile|
+                {
+                    if world.tile_info(*tile).special == Some(SpecialTile::StairsDown)
+                    {
+                        world.tile(above.offset(Pos3::new(0, 0, 1))).map(|tile|
+                        {
+                            !world.tile_info(*tile).colliding
+                        })
+                    } else
+                    {
+                        None
+                    }
+                }).unwrap_or(false)
+                {
+                    tile_info = Some(format!("press {} to go up", interact_button()));
+
+                    if self.info.interacted
+                    {
+                        let mut transform = self.game_state.entities()
+                            .target(self.info.entity)
+                            .unwrap();
+
+                        transform.position.z += TILE_SIZE * 2.0;
+                    }
+                }
+            }
+
+            colliding.transform.position.z -= TILE_SIZE;
+
+            let stairs = world.tiles_inside(&colliding, |tile: Option<&_>|
+            {
+                tile.map(|tile|
+                {
+                    world.tile_info(*tile).special == Some(SpecialTile::StairsDown)
+                }).unwrap_or(false)
+            }).next();
+
+            if stairs.and_then(|stairs: TilePos|
+            {
+                world.tile(stairs.offset(Pos3::new(0, 0, -1))).map(|tile|
+                {
+                    !world.tile_info(*tile).colliding
+                })
+            }).unwrap_or(false)
+            {
+                tile_info = Some(format!("press {} to go down", interact_button()));
+
+                if self.info.interacted
+                {
+                    let mut transform = self.game_state.entities()
+                        .target(self.info.entity)
+                        .unwrap();
+
+                    transform.position.z -= TILE_SIZE * 2.0;
+                }
+            }
+        });
+
+        if tile_info.is_none()
+        {
+            tile_info = self.locked_interaction();
+        }
+
+        if tile_info.is_none()
+        {
+            tile_info = self.bed_interaction();
+        }
+
+        if tile_info.is_none()
+        {
+            tile_info = self.butcher_interaction();
+        }
+
+        if tile_info.is_none()
+        {
+            tile_info = self.drag_interaction();
+        }
+
+        if tile_info.is_none()
+        {
+            tile_info = self.dialogue_interaction();
+        }
+
+        if tile_info.is_none()
+        {
+            tile_info = self.companion_interaction();
+        }
+
+        if tile_info.is_none()
+        {
+            tile_info = self.perk_reminder();
+        }
+
+        if let Some(text) = tile_info
+        {
+            self.show_tile_tooltip(text);
+        }
+
+        self.update_hints();
+
+        self.game_state.sync_character(self.info.entity, dt);
+
+        self.info.interacted = false;
+    }
+
+    fn locked_interaction(&mut self) -> Option<String>
+    {
+        let entities = self.game_state.entities();
+
+        let mouse_touched = entities.collider(self.info.mouse_entity)
+            .and_then(|x| x.collided().first().copied())?;
+
+        if !entities.within_interactable_distance(self.info.entity, mouse_touched)
+        {
+            return None;
+        }
+
+        let name = {
+            let container = entities.container(mouse_touched)?;
+
+            if !container.locked
+            {
+                return None;
+            }
+
+            container.name.clone()
+        };
+
+        let unlock = some_or_value!(
+            entities.container_unlock(self.info.entity, mouse_touched),
+            Some(format!("the {name} is locked"))
+        );
+
+        let interact_button = self.game_state.controls.key_for(&Control::Interact)
+            .map(ToString::to_string)
+            .unwrap_or_else(|| "unassigned".to_owned());
+
+        if self.info.interacted
+        {
+            // the server checks it again and makes the noise
+            self.game_state.send_message(Message::UnlockContainer{entity: mouse_touched});
+
+            self.open_loot(mouse_touched);
+
+            return None;
+        }
+
+        let verb = match unlock
+        {
+            Unlock::Pry => "pry open",
+            Unlock::Pick => "pick the lock on"
+        };
+
+        Some(format!("press {interact_button} to {verb} the {name}"))
+    }
+
+    fn bed_interaction(&mut self) -> Option<String>
+    {
+        let entities = self.game_state.entities();
+
+        let mouse_touched = entities.collider(self.info.mouse_entity)
+            .and_then(|x| x.collided().first().copied())?;
+
+        if !entities.within_interactable_distance(self.info.entity, mouse_touched)
+        {
+            return None;
+        }
+
+        let is_bed = {
+            let container = entities.container(mouse_touched)?;
+
+            entities.infos().containers_info.get(&container.name)?.bed
+        };
+
+        if !is_bed
+        {
+            return None;
+        }
+
+        let position = entities.transform(mouse_touched)?.position;
+        if entities.player(self.info.entity)?.spawn == Some(position)
+        {
+            return Some("u will wake up here".to_owned());
+        }
+
+        let interact_button = self.game_state.controls.key_for(&Control::Interact)
+            .map(ToString::to_string)
+            .unwrap_or_else(|| "unassigned".to_owned());
+
+        if self.info.interacted
+        {
+            // the server checks if its close enough and sends the player back
+            self.game_state.send_message(Message::SetSpawn{bed: mouse_touched});
+        }
+
+        Some(format!("press {interact_button} to sleep here"))
+    }
+
+    fn butcher_interaction(&mut self) -> Option<String>
+    {
+        let entities = self.game_state.entities();
+
+        let mouse_touched = entities.collider(self.info.mouse_entity)
+            .and_then(|x| x.collided().first().copied())?;
+
+        if !entities.within_interactable_distance(self.info.entity, mouse_touched)
+            || entities.corpse(mouse_touched)?.is_butchered()
+        {
+            return None;
+        }
+
+        let name = {
+            let enemy = entities.enemy(mouse_touched)?;
+            let info = enemy.info(&entities.infos().enemies_info);
+
+            info.butcher.as_ref()?;
+
+            info.name.clone()
+        };
+
+        let sharp = entities.character(self.info.entity)?.holding().and_then(|held|
+        {
+            let info = self.game_state.items_info.get(entities.inventory(self.info.entity)?.get(held)?.id);
+
+            Some(info.sharpness > 0.0 || info.side_sharpness > 0.0)
+        }).unwrap_or(false);
+
+        // lets the body get dragged instead
+        if !sharp
+        {
+            return None;
+        }
+
+        let interact_button = self.game_state.controls.key_for(&Control::Interact)
+            .map(ToString::to_string)
+            .unwrap_or_else(|| "unassigned".to_owned());
+
+        if self.info.interacted
+        {
+            self.game_state.send_message(Message::Butcher{entity: mouse_touched});
+        }
+
+        Some(format!("press {interact_button} to butcher the {name}"))
+    }
+
+    fn drag_interaction(&mut self) -> Option<String>
+    {
+        let entities = self.game_state.entities();
+
+        let interact_button = self.game_state.controls.key_for(&Control::Interact)
+            .map(ToString::to_string)
+            .unwrap_or_else(|| "unassigned".to_owned());
+
+        if entities.character(self.info.entity)?.dragging().is_some()
+        {
+            if self.info.interacted
+            {
+                self.character_action(CharacterAction::Drag(None));
+            }
+
+            return Some(format!("press {interact_button} to let go"));
         }
-    }
 
-    fn update_inventory_inner(
-        entities: &mut ClientEntities,
-        info: &mut PlayerInfo,
-        which: InventoryWhich
-    )
-    {
-        let entity = match which
+        let mouse_touched = entities.collider(self.info.mouse_entity)
+            .and_then(|x| x.collided().first().copied())?;
+
+        if !entities.within_interactable_distance(self.info.entity, mouse_touched)
         {
-            InventoryWhich::Other => info.other_entity.unwrap(),
-            InventoryWhich::Player => info.entity
-        };
+            return None;
+        }
 
-        let entity_creator = EntityCreator{entities};
+        // only furniture and bodies, alive things walk away on their own
+        let alive = entities.anatomy(mouse_touched).map(|anatomy| anatomy.speed().is_some()).unwrap_or(false);
 
-        if let Some(window) = match which
+        // doors are already hinged to their frame
+        if alive || entities.player_exists(mouse_touched) || entities.joint_exists(mouse_touched)
         {
-            InventoryWhich::Player => &info.inventories.player,
-            InventoryWhich::Other => &info.inventories.other
-        }.as_ref().and_then(|window| window.upgrade())
+            return None;
+        }
+
+        if entities.physical(mouse_touched)?.inverse_mass.recip() < DRAG_MIN_MASS
         {
-            let mut window = window.borrow_mut();
-            let inventory = window.as_inventory_mut().unwrap();
+            return None;
+        }
 
-            inventory.full_update(&entity_creator, entity);
+        if self.info.interacted
+        {
+            self.character_action(CharacterAction::Drag(Some(mouse_touched)));
         }
+
+        Some(format!("press {interact_button} to drag"))
     }
 
-    pub fn this_update(&mut self, dt: f32)
+    fn in_range(&self, other: Entity, distance: f32) -> bool
     {
-        if !self.exists()
+        let entities = self.game_state.entities();
+        let position = |entity| entities.transform(entity).map(|x| x.position);
+
+        match (position(self.info.entity), position(other))
         {
-            return;
+            (Some(a), Some(b)) => a.metric_distance(&b) <= distance,
+            _ => false
         }
+    }
 
-        self.update_user_events();
+    fn start_dialogue(&mut self, speaker: Entity)
+    {
+        self.close_dialogue();
 
-        let mouse_position = self.game_state.world_mouse_position();
-        let mouse_position = Vector3::new(mouse_position.x, mouse_position.y, 0.0);
-        let camera_position = self.game_state.camera.read().position().coords;
+        let window = self.game_state.add_window(WindowCreateInfo::Dialogue{
+            spawn_position: self.game_state.ui_mouse_position(),
+            player: self.info.entity,
+            speaker
+        });
+
+        self.info.dialogue = Some(DialogueInfo{speaker, window, started: false});
+
+        self.game_state.send_message(Message::DialogueStart{entity: speaker});
+    }
+
+    fn close_dialogue(&mut self)
+    {
+        let dialogue = some_or_return!(self.info.dialogue.take());
 
+        if let Some(window) = dialogue.window.upgrade()
         {
-            let entities = self.game_state.entities_mut();
+            let _ = self.game_state.remove_window(window);
+        }
 
-            entities.transform_mut(self.info.mouse_entity).unwrap()
-                .position = camera_position + mouse_position;
+        let talking = self.game_state.entities().dialogue(dialogue.speaker)
+            .map(|x| x.talking_to(self.info.entity))
+            .unwrap_or(false);
 
-            entities.update_mouse_highlight(
-                self.info.entity,
-                self.info.mouse_entity
-            );
+        if talking
+        {
+            self.game_state.send_message(Message::DialogueChoose{entity: dialogue.speaker, choice: None});
+        }
+    }
 
-            let player_position = entities.transform(self.info.entity).unwrap().position;
+    fn update_dialogue(&mut self)
+    {
+        let (speaker, window_exists) = {
+            let dialogue = some_or_return!(self.info.dialogue.as_ref());
 
-            let follow_position = if mouse_position.magnitude() > CHUNK_VISUAL_SIZE * 2.0
-            {
-                player_position
-            } else
-            {
-                player_position + mouse_position / 5.0
-            };
+            (dialogue.speaker, dialogue.window.upgrade().is_some())
+        };
 
-            entities.transform_mut(self.info.follow).unwrap().position = follow_position;
+        if !window_exists || !self.in_range(speaker, DIALOGUE_DISTANCE)
+        {
+            self.close_dialogue();
+            return;
         }
 
-        let entities = &mut self.game_state.entities.entities;
-        if let Some((current_stamina, current_cooldown)) = entities.character(self.info.entity).map(|x|
+        let talking = self.game_state.entities().dialogue(speaker)
+            .map(|x| x.talking_to(self.info.entity))
+            .unwrap_or(false);
+
+        let dialogue = self.info.dialogue.as_mut().unwrap();
+        if talking
         {
-            (x.stamina_fraction(entities), x.attack_cooldown())
-        })
+            dialogue.started = true;
+        } else if dialogue.started
         {
-            let delay = 0.7;
-
-            if self.info.previous_stamina != current_stamina
-            {
-                let was_none = self.info.previous_stamina.is_none();
-                self.info.previous_stamina = current_stamina;
+            // the server ended it
+            self.close_dialogue();
+        }
+    }
 
-                if !was_none
-                {
-                    self.game_state.ui_notifications.set_stamina_bar(
-                        entities,
-                        self.info.entity,
-                        delay,
-                        current_stamina.unwrap_or(0.0)
-                    );
-                }
-            }
+    fn dialogue_interaction(&mut self) -> Option<String>
+    {
+        let entities = self.game_state.entities();
 
-            if self.info.previous_cooldown.1 != current_cooldown
-            {
-                self.info.previous_cooldown.1 = current_cooldown;
+        let mouse_touched = entities.collider(self.info.mouse_entity)
+            .and_then(|x| x.collided().first().copied())?;
 
-                self.info.previous_cooldown.0 = if current_cooldown <= 0.0
-                {
-                    0.0
-                } else
-                {
-                    self.info.previous_cooldown.0.max(current_cooldown)
-                };
+        if !entities.within_interactable_distance(self.info.entity, mouse_touched)
+            || !entities.dialogue_exists(mouse_touched)
+            || entities.anatomy(mouse_touched)?.speed().is_none()
+            || entities.is_hostile(mouse_touched, self.info.entity)
+            || entities.is_companion_of(mouse_touched, self.info.entity)
+        {
+            return None;
+        }
 
-                let fraction = if self.info.previous_cooldown.0 > 0.0
-                {
-                    current_cooldown / self.info.previous_cooldown.0
-                } else
-                {
-                    0.0
-                };
+        let interact_button = self.game_state.controls.key_for(&Control::Interact)
+            .map(ToString::to_string)
+            .unwrap_or_else(|| "unassigned".to_owned());
 
-                self.game_state.ui_notifications.set_weapon_cooldown_bar(
-                    entities,
-                    self.info.entity,
-                    delay,
-                    fraction
-                );
-            }
+        if self.info.interacted
+        {
+            self.start_dialogue(mouse_touched);
         }
 
-        if let Some(movement) = self.movement_direction()
-        {
-            if let Some(mut character) = self.game_state.entities()
-                .character_mut(self.info.entity)
-            {
-                character.sprinting = self.game_state.pressed(Control::Sprint);
-            }
+        Some(format!("press {interact_button} to talk"))
+    }
 
-            self.walk(movement, dt);
-        }
+    fn companion_interaction(&mut self) -> Option<String>
+    {
+        let entities = self.game_state.entities();
 
-        let able_to_move = self.game_state.entities()
-            .anatomy(self.info.entity)
-            .map(|anatomy| anatomy.speed().is_some())
-            .unwrap_or(false);
+        let mouse_touched = entities.collider(self.info.mouse_entity)
+            .and_then(|x| x.collided().first().copied())?;
 
-        if able_to_move
+        if !entities.within_interactable_distance(self.info.entity, mouse_touched)
         {
-            self.look_at_mouse();
+            return None;
         }
 
-        if let Some(other_entity) = self.info.other_entity
+        if entities.anatomy(mouse_touched)?.speed().is_none()
         {
-            if !self.game_state.entities().within_interactable_distance(self.info.entity, other_entity)
-            {
-                if let Some(window) = self.info.inventories.other.take().and_then(|x| x.upgrade())
-                {
-                    let _ = self.game_state.remove_window(window);
-                }
-            }
+            return None;
         }
 
-        let mut tile_info = None;
-        self.colliding_info(|mut colliding|
-        {
-            let world = &self.game_state.world;
+        let player_name = entities.named(self.info.entity)?.clone();
+        let player_faction = entities.character(self.info.entity)?.faction;
 
-            let stairs: Option<TilePos> = world.tiles_inside(&colliding, |tile|
+        let next_order = {
+            let enemy = entities.enemy(mouse_touched)?;
+
+            if let Some(companion) = enemy.companion()
             {
-                tile.map(|tile|
+                if companion.leader() != Some(self.info.entity)
                 {
-                    world.tile_info(*tile).special == Some(SpecialTile::StairsUp)
-                }).unwrap_or(false)
-            }).next();
+                    return None;
+                }
 
-            let interact_button = ||
+                Some(companion.order().next())
+            } else
             {
-                self.game_state.controls.key_for(&Control::Interact).map(ToString::to_string)
-                    .unwrap_or_else(|| "unassigned".to_owned())
-            };
+                let other_faction = entities.character(mouse_touched)?.faction;
+                if entities.infos().factions_info.aggressive(other_faction, player_faction)
+                {
+                    return None;
+                }
+
+                None
+            }
+        };
+
+        let interact_button = self.game_state.controls.key_for(&Control::Interact)
+            .map(ToString::to_string)
+            .unwrap_or_else(|| "unassigned".to_owned());
+
+        if self.info.interacted
+        {
+            let position = entities.transform(mouse_touched)?.position;
 
-            if let Some(stairs) = stairs
             {
-                let above = stairs.offset(Pos3::new(0, 0, 1));
+                let mut enemy = entities.enemy_mut(mouse_touched)?;
 
-                if world.tile(above).and_then(|tile|
+                if let Some(order) = next_order
                 {
-                    if world.tile_info(*tile).special == Some(SpecialTile::StairsDown)
-                    {
-                        world.tile(above.offset(Pos3::new(0, 0, 1))).map(|tile|
-                        {
-                            !world.tile_info(*tile).colliding
-                        })
-                    } else
-                    {
-                        None
-                    }
-                }).unwrap_or(false)
+                    enemy.companion_mut()?.set_order(order, position);
+                } else
                 {
-                    tile_info = Some(format!("press {} to go up", interact_button()));
-
-                    if self.info.interacted
-                    {
-                        let mut transform = self.game_state.entities()
-                            .target(self.info.entity)
-                            .unwrap();
+                    enemy.recruit(player_name, self.info.entity, position);
 
-                        transform.position.z += TILE_SIZE * 2.0;
-                    }
+                    entities.character_mut(mouse_touched)?.faction = player_faction;
                 }
             }
 
-            colliding.transform.position.z -= TILE_SIZE;
+            let enemy = entities.enemy(mouse_touched)?.clone();
+            let character = entities.character(mouse_touched)?.clone();
 
-            let stairs = world.tiles_inside(&colliding, |tile: Option<&_>|
-            {
-                tile.map(|tile|
-                {
-                    world.tile_info(*tile).special == Some(SpecialTile::StairsDown)
-                }).unwrap_or(false)
-            }).next();
+            self.game_state.send_message(Message::SetEnemy{
+                entity: mouse_touched,
+                component: enemy.into()
+            });
 
-            if stairs.and_then(|stairs: TilePos|
-            {
-                world.tile(stairs.offset(Pos3::new(0, 0, -1))).map(|tile|
-                {
-                    !world.tile_info(*tile).colliding
-                })
-            }).unwrap_or(false)
-            {
-                tile_info = Some(format!("press {} to go down", interact_button()));
+            self.game_state.send_message(Message::SetCharacter{
+                entity: mouse_touched,
+                component: character.into()
+            });
+        }
 
-                if self.info.interacted
-                {
-                    let mut transform = self.game_state.entities()
-                        .target(self.info.entity)
-                        .unwrap();
+        let action = next_order.map(|order| format!("order to {}", order.name()))
+            .unwrap_or_else(|| "recruit".to_owned());
 
-                    transform.position.z -= TILE_SIZE * 2.0;
-                }
-            }
-        });
+        Some(format!("press {interact_button} to {action}"))
+    }
 
-        if let Some(text) = tile_info
+    fn perk_reminder(&self) -> Option<String>
+    {
+        let perk_points = self.game_state.entities().player(self.info.entity)?.perk_points;
+
+        if perk_points == 0
         {
-            self.show_tile_tooltip(text);
+            return None;
         }
 
-        self.game_state.sync_character(self.info.entity);
+        let perks_button = self.game_state.controls.key_for(&Control::Perks)
+            .map(ToString::to_string)
+            .unwrap_or_else(|| "unassigned".to_owned());
 
-        self.info.interacted = false;
+        Some(format!("level up! press {perks_button} to pick a perk"))
     }
 
     fn show_tile_tooltip(&mut self, text: String)
@@ -1679,6 +2988,57 @@ impl<'a> PlayerContainer<'a>
         }
     }
 
+    fn set_locked(&mut self, target: Option<Entity>)
+    {
+        if self.info.locked == target
+        {
+            return;
+        }
+
+        self.info.locked = target;
+
+        if let Some(window) = self.game_state.ui_notifications.lock_indicator.take()
+            .and_then(|x| x.upgrade())
+        {
+            let _ = self.game_state.remove_window(window);
+        }
+    }
+
+    fn update_lock(&mut self)
+    {
+        let locked = some_or_return!(self.info.locked);
+
+        // drop the lock once the target dies or gets out of sight
+        if !self.game_state.entities().lock_targets(self.info.entity).contains(&locked)
+        {
+            self.set_locked(None);
+            return;
+        }
+
+        let entities = &mut self.game_state.entities.entities;
+        self.game_state.ui_notifications.set_lock_indicator(entities, locked, 0.1);
+    }
+
+    fn locked_position(&self) -> Option<Vector3<f32>>
+    {
+        self.info.locked.and_then(|locked|
+        {
+            self.game_state.entities().transform(locked).map(|x| x.position)
+        })
+    }
+
+    fn look_at_world(&mut self, position: Vector3<f32>)
+    {
+        let player_position = some_or_return!(self.player_position());
+
+        let offset = position - player_position;
+
+        if let Some(mut character) = self.game_state.entities().character_mut(self.info.entity)
+        {
+            character.rotation = offset.y.atan2(offset.x);
+        }
+    }
+
     pub fn look_at_mouse(&mut self)
     {
         let mouse = self.game_state.world_mouse_position();
@@ -0,0 +1,188 @@
+use std::collections::VecDeque;
+
+use crate::common::some_or_return;
+
+
+const MAX_HISTORY: usize = 100;
+
+const MAX_SCROLLBACK: usize = 200;
+
+// how many of the previous lines show up above whats being typed
+const SHOWN_SCROLLBACK: usize = 8;
+
+// anything the lisp code defines at the top level, good enough for completing
+pub fn defined_names(code: &str) -> impl Iterator<Item=String> + '_
+{
+    code.lines().filter_map(|line|
+    {
+        let rest = line.strip_prefix("(define ")?;
+        let rest = rest.strip_prefix('(').unwrap_or(rest);
+
+        let name = rest.split(|c: char| c.is_whitespace() || c == ')').next()?;
+
+        (!name.is_empty()).then(|| name.to_owned())
+    })
+}
+
+#[derive(Debug, Default)]
+pub struct Console
+{
+    contents: Option<String>,
+    history: Vec<String>,
+    // none while typing something new
+    history_index: Option<usize>,
+    scrollback: VecDeque<String>,
+    // names tab completes to, sorted
+    names: Vec<String>
+}
+
+impl Console
+{
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    pub fn is_open(&self) -> bool
+    {
+        self.contents.is_some()
+    }
+
+    pub fn toggle(&mut self)
+    {
+        self.contents = if self.contents.is_some()
+        {
+            None
+        } else
+        {
+            Some(String::new())
+        };
+
+        self.history_index = None;
+    }
+
+    pub fn close(&mut self)
+    {
+        self.contents = None;
+        self.history_index = None;
+    }
+
+    pub fn contents_mut(&mut self) -> Option<&mut String>
+    {
+        self.contents.as_mut()
+    }
+
+    // closes the console and gives back the command that was typed in
+    pub fn submit(&mut self) -> Option<String>
+    {
+        let command = self.contents.take()?;
+
+        self.history_index = None;
+
+        if !command.trim().is_empty() && self.history.last() != Some(&command)
+        {
+            if self.history.len() >= MAX_HISTORY
+            {
+                self.history.remove(0);
+            }
+
+            self.history.push(command.clone());
+        }
+
+        Some(command)
+    }
+
+    pub fn history_previous(&mut self)
+    {
+        if self.history.is_empty()
+        {
+            return;
+        }
+
+        let index = self.history_index.map(|x| x.saturating_sub(1)).unwrap_or(self.history.len() - 1);
+
+        self.history_index = Some(index);
+        self.contents = Some(self.history[index].clone());
+    }
+
+    pub fn history_next(&mut self)
+    {
+        let index = match self.history_index
+        {
+            Some(x) => x + 1,
+            None => return
+        };
+
+        if index < self.history.len()
+        {
+            self.history_index = Some(index);
+            self.contents = Some(self.history[index].clone());
+        } else
+        {
+            self.history_index = None;
+            self.contents = Some(String::new());
+        }
+    }
+
+    pub fn set_names(&mut self, names: impl IntoIterator<Item=String>)
+    {
+        self.names = names.into_iter().collect();
+
+        self.names.sort_unstable();
+        self.names.dedup();
+    }
+
+    // completes the last word as far as all the matching names agree
+    pub fn complete(&mut self)
+    {
+        let contents = some_or_return!(self.contents.as_mut());
+
+        let start = contents.rfind(|c: char| c.is_whitespace() || c == '(' || c == '\'')
+            .map(|x| x + 1)
+            .unwrap_or(0);
+
+        let word = &contents[start..];
+
+        let mut matching = self.names.iter().filter(|name| name.starts_with(word));
+
+        let first = some_or_return!(matching.next());
+
+        let common = matching.fold(first.as_str(), |common, name|
+        {
+            let length = common.chars().zip(name.chars())
+                .take_while(|(a, b)| a == b)
+                .map(|(a, _)| a.len_utf8())
+                .sum();
+
+            &common[..length]
+        });
+
+        let common = common.to_owned();
+
+        contents.truncate(start);
+        *contents += &common;
+    }
+
+    pub fn push_line(&mut self, line: impl Into<String>)
+    {
+        if self.scrollback.len() >= MAX_SCROLLBACK
+        {
+            self.scrollback.pop_front();
+        }
+
+        self.scrollback.push_back(line.into());
+    }
+
+    pub fn text(&self) -> String
+    {
+        let contents = self.contents.as_deref().unwrap_or_default();
+
+        let skipped = self.scrollback.len().saturating_sub(SHOWN_SCROLLBACK);
+
+        self.scrollback.iter().skip(skipped)
+            .cloned()
+            .chain([format!("> {contents}")])
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
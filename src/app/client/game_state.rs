@@ -3,10 +3,11 @@ use std::{
     mem,
     env,
     thread::JoinHandle,
+    path::Path,
     cell::{Ref, RefCell},
     rc::Rc,
     ops::ControlFlow,
-    collections::{BTreeMap, btree_map::Entry},
+    collections::{HashMap, BTreeMap, btree_map::Entry},
     sync::{
         Arc,
         mpsc::{self, TryRecvError, Receiver}
@@ -29,6 +30,7 @@ use yanyaengine::{
     ShaderId,
     TextureId,
     ModelId,
+    DefaultModel,
     UniformLocation,
     object::{texture::SimpleImage, Texture},
     camera::Camera,
@@ -38,30 +40,52 @@ use yanyaengine::{
 use crate::{
     debug_config::*,
     ProgramShaders,
+    rendering::RenderTargets,
     client::RenderCreateInfo,
     common::{
         some_or_return,
         some_or_value,
+        some_or_false,
+        CharacterCreation,
         sender_loop,
         receiver_loop,
         render_info::*,
+        overrides::overridden,
+        crash_report,
         lazy_transform::*,
-        SpatialGrid,
         TileMap,
         DataInfos,
         ItemsInfo,
+        EnemiesInfo,
         InventoryItem,
+        EquipSlot,
         AnyEntities,
         CharactersInfo,
+        PerksInfo,
+        PerkId,
+        BarksInfo,
+        ModsInfo,
+        mods_info::HOOK_PREFIX,
+        ParticleEffects,
+        Projectiles,
+        projectile,
+        Enemy,
         Entity,
+        Noise,
         EntityInfo,
         Entities,
         EntityPasser,
         EntitiesController,
         OccludingCaster,
+        ClientOccluder,
+        Light,
+        BarterResponse,
+        TradeOffer,
         message::Message,
+        snapshot::SnapshotDecoder,
+        items_info::ItemId,
         character::PartialCombinedInfo,
-        entity::{for_each_component, render_system, ClientEntities},
+        entity::{for_each_component, render_system, damaging_system, ClientEntities},
         world::{
             TILE_SIZE,
             World,
@@ -78,19 +102,30 @@ use super::{
     MessagePasser,
     ConnectionsHandler,
     TilesFactory,
+    SpriteAtlas,
     VisibilityChecker,
     ui_element::UiActionKind,
     world_receiver::WorldReceiver
 };
 
-pub use controls_controller::{Control, ControlState, KeyMapping};
+pub use controls_controller::{Control, ControlState, KeyMapping, InputSource};
+
+pub use hints::{Hint, TUTORIAL};
 pub use entity_creator::EntityCreator;
 
 use controls_controller::ControlsController;
 
+use gamepad::Gamepad;
+
+use sound_cues::SoundCues;
+
+use lifetime_stats::LifetimeStats;
+
+use telemetry::Telemetry;
+
 use notifications::{Notifications, Notification};
 
-pub use anatomy_locations::UiAnatomyLocations;
+pub use anatomy_locations::{UiAnatomyLocations, UiAnatomyOverlays};
 pub use ui::{
     Ui,
     UiSpecializedWindow,
@@ -101,16 +136,53 @@ pub use ui::{
 
 use ui::{NotificationCreateInfo, NotificationSeverity, NotificationKind};
 
+pub use post_effects::GraphicsSettings;
+pub use telemetry::TelemetrySettings;
+pub use camera_controller::CameraSettings;
+pub use decals::Decals;
+pub use weather_effects::WeatherEffects;
+pub use camera_shake::CameraShake;
+pub use audio::{Audio, AudioSettings};
+pub use background::Background;
+pub use reflections::Reflections;
+pub use light_occlusion::LightOcclusion;
+pub use tracers::Tracers;
+pub use physics_stepper::{PhysicsStepper, DEFAULT_PHYSICS_RATE};
+pub use sprite_batches::SpriteBatches;
+
+use camera_controller::CameraController;
+
+use post_effects::PostEffects;
+use color_grading::ColorGrading;
+use prediction::Prediction;
+
 mod controls_controller;
+mod gamepad;
+mod hints;
+mod lifetime_stats;
+mod telemetry;
+mod sound_cues;
 
 mod notifications;
 
 mod entity_creator;
 mod anatomy_locations;
 mod ui;
-
-
-const DEFAULT_ZOOM: f32 = 3.0;
+mod post_effects;
+mod camera_controller;
+mod decals;
+mod weather_effects;
+mod color_grading;
+mod render_scale;
+mod camera_shake;
+mod audio;
+mod background;
+mod reflections;
+mod light_occlusion;
+mod tracers;
+mod physics_stepper;
+mod sprite_batches;
+mod prediction;
 
 #[allow(dead_code)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -128,13 +200,28 @@ pub struct ClientEntitiesContainer
     pub ui_mouse_entity: Entity,
     visible_renders: Vec<Vec<Entity>>,
     shaded_renders: Vec<Entity>,
+    pub light_occlusion: LightOcclusion,
+    pub decals: Decals,
+    pub weather: WeatherEffects,
+    pub background: Background,
+    pub reflections: Reflections,
+    pub projectiles: RefCell<Projectiles>,
+    pub tracers: Tracers,
+    pub physics: PhysicsStepper,
+    doors: HashMap<Entity, bool>,
     player_entity: Entity,
     animation: f32
 }
 
 impl ClientEntitiesContainer
 {
-    pub fn new(infos: DataInfos, player_entity: Entity) -> Self
+    pub fn new(
+        infos: DataInfos,
+        player_entity: Entity,
+        reflections: bool,
+        physics_rate: u32,
+        light_occlusion: ShaderId
+    ) -> Self
     {
         let mut entities = Entities::new(infos);
 
@@ -165,6 +252,15 @@ impl ClientEntitiesContainer
             player_entity,
             visible_renders: Vec::new(),
             shaded_renders: Vec::new(),
+            light_occlusion: LightOcclusion::new(light_occlusion),
+            decals: Decals::new(),
+            weather: WeatherEffects::new(),
+            background: Background::parse("backgrounds/backgrounds.json"),
+            reflections: Reflections::new(reflections),
+            projectiles: RefCell::new(Projectiles::new()),
+            tracers: Tracers::new(),
+            physics: PhysicsStepper::new(physics_rate),
+            doors: HashMap::new(),
             animation: 0.0
         }
     }
@@ -189,26 +285,60 @@ impl ClientEntitiesContainer
         &mut self,
         world: &World,
         passer: &mut impl EntityPasser,
-        damage_info: TextureId,
-        _is_trusted: bool,
+        particle_effects: &ParticleEffects,
+        is_trusted: bool,
         dt: f32
     )
     {
-        let mut space = SpatialGrid::new();
-        self.entities.build_space(&mut space);
+        let steps = self.physics.steps(dt);
+        if steps > 0
+        {
+            self.entities.reset_colliders();
+        }
+
+        // raycasts go through the space too so keep it fresh even without physics steps
+        self.entities.build_space();
+
+        let mut impacts = Vec::new();
+        (0..steps).for_each(|_|
+        {
+            self.physics.snapshot(&self.entities);
+
+            let step = self.physics.step();
+
+            self.entities.build_space();
+
+            self.entities.update_wind(step);
+            self.entities.update_physical(world, step);
+            impacts.extend(self.entities.update_colliders(world, step));
+        });
+
+        // doors get swung by the physics so only the trusted client lets enemies hear them
+        self.entities.update_doors(passer, &mut self.doors, is_trusted);
 
-        self.entities.update_physical(world, dt);
         self.entities.update_lazy(dt);
-        self.entities.update_enemy(passer, dt);
+        self.entities.update_enemy(passer, world, dt);
         self.entities.update_children();
 
-        self.entities.update_damaging(passer, damage_info);
+        self.entities.update_damaging(passer);
+
+        // everyone simulates the same physics, only one client gets to hurt things with it
+        if is_trusted
+        {
+            self.entities.update_impacts(passer, impacts);
+        }
 
         self.entities.update_lazy_mix(dt);
+        self.entities.update_anatomy(dt);
+        self.entities.update_corpses(dt);
+        self.entities.update_containers(dt);
+        self.entities.update_fires(particle_effects, dt);
 
         self.entities.update_outlineable(dt);
 
-        self.entities.update_colliders(world, &space, dt);
+        self.projectiles.get_mut().update(dt);
+
+        self.decals.update(dt);
 
         self.animation = (self.animation + dt) % (f32::consts::PI * 2.0);
     }
@@ -248,7 +378,9 @@ impl ClientEntitiesContainer
 
     fn update_buffers(
         &mut self,
+        world: &World,
         visibility: &VisibilityChecker,
+        sprite_batches: &mut SpriteBatches,
         info: &mut UpdateBuffersInfo,
         caster: &OccludingCaster
     )
@@ -286,18 +418,86 @@ impl ClientEntitiesContainer
 
         self.visible_renders = visible_renders.into_values().collect();
 
+        // keeps sprites from the same atlas page next to each other so they can get batched
+        self.visible_renders.iter_mut().for_each(|renders|
+        {
+            renders.sort_by_cached_key(|entity| sprite_batches.sort_key(&self.entities, *entity));
+        });
+
         render_system::update_buffers(
             &self.entities,
             self.visible_renders.iter().flatten().copied(),
+            &self.physics,
             info,
             caster
         );
+
+        sprite_batches.update_buffers(&self.entities, self.visible_renders.iter().flatten().copied(), info);
+
+        self.update_light_occlusion(world, visibility, info);
+
+        self.reflections.update_buffers(
+            &self.entities,
+            world,
+            self.visible_renders.iter().flatten().copied(),
+            info
+        );
+
+        if let Some(height) = self.player_transform().map(|x| x.position.z)
+        {
+            self.decals.update_buffers(visibility, info, height);
+        }
+
+        self.tracers.update_buffers(info);
+
+        self.weather.update_buffers(info);
+        self.background.update_buffers(info);
+    }
+
+    fn update_light_occlusion(
+        &mut self,
+        world: &World,
+        visibility: &VisibilityChecker,
+        info: &mut UpdateBuffersInfo
+    )
+    {
+        let animation = self.animation.sin();
+
+        let mut lights = Vec::new();
+        for_each_component!(self.entities, light, |entity, light: &RefCell<Light>|
+        {
+            let position = some_or_return!(self.entities.transform(entity)).position;
+
+            let light = light.borrow();
+            if light.visible(visibility, position)
+            {
+                lights.push((entity, position, light.current_radius(animation)));
+            }
+        });
+
+        let center = visibility.position;
+
+        let mut occluders = world.occluders_near(center, LightOcclusion::range());
+
+        for_each_component!(self.entities, occluder, |_, occluder: &RefCell<ClientOccluder>|
+        {
+            let occluder = occluder.borrow();
+            if !occluder.visible_height(visibility)
+            {
+                return;
+            }
+
+            occluders.extend(occluder.transforms().cloned());
+        });
+
+        self.light_occlusion.update_buffers(info, center, occluders, lights);
     }
 }
 
 pub struct GameStateInfo<'a>
 {
     pub shaders: ProgramShaders,
+    pub targets: RenderTargets,
     pub camera: Arc<RwLock<Camera>>,
     pub object_info: ObjectCreateInfo<'a>,
     pub data_infos: DataInfos,
@@ -343,7 +543,18 @@ pub enum UserEvent
     Info{which: InventoryWhich, item: InventoryItem},
     Drop{which: InventoryWhich, item: InventoryItem},
     Wield(InventoryItem),
-    Take(InventoryItem)
+    Equip(InventoryItem),
+    Unequip(EquipSlot),
+    Take(InventoryItem),
+    PickPerk{id: PerkId, name: String},
+    DialogueChoice{speaker: Entity, choice: usize},
+    TradeOffer{vendor: Entity, offer: TradeOffer},
+    // the server sends these when a dialogue choice goes through
+    OpenTrade(Entity),
+    Script(String),
+    Consume(InventoryItem),
+    Apply(InventoryItem),
+    Operate(InventoryItem)
 }
 
 impl UserEvent
@@ -356,7 +567,29 @@ impl UserEvent
             Self::Info{..} => "info",
             Self::Drop{..} => "drop",
             Self::Wield(..) => "wield",
-            Self::Take(..) => "take"
+            Self::Equip(..) => "equip",
+            Self::Unequip(..) => "unequip",
+            Self::Take(..) => "take",
+            Self::PickPerk{name, ..} => name,
+            Self::DialogueChoice{..} => "choose",
+            Self::TradeOffer{..} => "trade",
+            Self::OpenTrade(..) => "trade",
+            Self::Script(..) => "script",
+            Self::Consume(..) => "consume",
+            Self::Apply(..) => "apply",
+            Self::Operate(..) => "operate"
+        }
+    }
+
+    // the mod script procedure that gets called after this happens
+    pub fn hook(&self) -> Option<String>
+    {
+        match self
+        {
+            Self::UiAction(..) => None,
+            // perk names r whatever the data says so they all share one
+            Self::PickPerk{..} => Some(format!("{HOOK_PREFIX}perk")),
+            x => Some(format!("{HOOK_PREFIX}{}", x.name()))
         }
     }
 }
@@ -388,29 +621,23 @@ impl UiReceiver
     }
 }
 
-pub struct CommonTextures
-{
-    pub dust: TextureId,
-    pub blood: TextureId
-}
-
-impl CommonTextures
-{
-    pub fn new(assets: &mut Assets) -> Self
-    {
-        Self{
-            dust: assets.texture_id("decals/dust.png"),
-            blood: assets.texture_id("decals/blood.png")
-        }
-    }
-}
-
 pub struct UiNotifications
 {
     ui: Rc<RefCell<Ui>>,
     pub stamina: Option<WindowType>,
+    pub visibility: Option<WindowType>,
+    pub hunger: Option<WindowType>,
+    pub thirst: Option<WindowType>,
     pub weapon_cooldown: Option<WindowType>,
-    pub tile_tooltip: Option<WindowType>
+    pub tile_tooltip: Option<WindowType>,
+    pub lock_indicator: Option<WindowType>,
+    pub saving_indicator: Option<WindowType>,
+    pub time_scale_indicator: Option<WindowType>,
+    pub crash_notice: Option<WindowType>,
+    pub hint: Option<WindowType>,
+    pub tutorial: Option<WindowType>,
+    pub achievement: Option<WindowType>,
+    pub barks: HashMap<Entity, Option<WindowType>>
 }
 
 impl UiNotifications
@@ -509,6 +736,48 @@ impl UiNotifications
         })
     }
 
+    pub fn set_visibility_bar(
+        &mut self,
+        entities: &mut ClientEntities,
+        owner: Entity,
+        lifetime: f32,
+        amount: f32
+    )
+    {
+        Self::set_bar(&mut self.visibility, entities, &self.ui, owner, lifetime, amount, ||
+        {
+            NotificationCreateInfo::Bar{name: "VISIBLE".to_owned(), color: [0.839, 0.839, 0.761], amount}
+        })
+    }
+
+    pub fn set_hunger_bar(
+        &mut self,
+        entities: &mut ClientEntities,
+        owner: Entity,
+        lifetime: f32,
+        amount: f32
+    )
+    {
+        Self::set_bar(&mut self.hunger, entities, &self.ui, owner, lifetime, amount, ||
+        {
+            NotificationCreateInfo::Bar{name: "HUNGER".to_owned(), color: [0.839, 0.529, 0.318], amount}
+        })
+    }
+
+    pub fn set_thirst_bar(
+        &mut self,
+        entities: &mut ClientEntities,
+        owner: Entity,
+        lifetime: f32,
+        amount: f32
+    )
+    {
+        Self::set_bar(&mut self.thirst, entities, &self.ui, owner, lifetime, amount, ||
+        {
+            NotificationCreateInfo::Bar{name: "THIRST".to_owned(), color: [0.408, 0.808, 0.871], amount}
+        })
+    }
+
     pub fn set_weapon_cooldown_bar(
         &mut self,
         entities: &mut ClientEntities,
@@ -536,6 +805,130 @@ impl UiNotifications
             NotificationCreateInfo::Text{severity: NotificationSeverity::Normal, text}
         })
     }
+
+    pub fn set_bark(
+        &mut self,
+        entities: &mut ClientEntities,
+        owner: Entity,
+        lifetime: f32,
+        text: String
+    )
+    {
+        self.barks.retain(|_, window| window.as_ref().and_then(|x| x.upgrade()).is_some());
+
+        let bark = self.barks.entry(owner).or_default();
+        Self::set_text(bark, entities, &self.ui, owner, lifetime, text, |text|
+        {
+            NotificationCreateInfo::Text{severity: NotificationSeverity::Normal, text}
+        })
+    }
+
+    pub fn set_lock_indicator(
+        &mut self,
+        entities: &mut ClientEntities,
+        owner: Entity,
+        lifetime: f32
+    )
+    {
+        let text = "LOCKED".to_owned();
+        Self::set_text(&mut self.lock_indicator, entities, &self.ui, owner, lifetime, text, |text|
+        {
+            NotificationCreateInfo::Text{severity: NotificationSeverity::Damage, text}
+        })
+    }
+
+    pub fn set_saving_indicator(
+        &mut self,
+        entities: &mut ClientEntities,
+        owner: Entity,
+        lifetime: f32
+    )
+    {
+        let text = "SAVING".to_owned();
+        Self::set_text(&mut self.saving_indicator, entities, &self.ui, owner, lifetime, text, |text|
+        {
+            NotificationCreateInfo::Text{severity: NotificationSeverity::Normal, text}
+        })
+    }
+
+    pub fn set_time_scale_indicator(
+        &mut self,
+        entities: &mut ClientEntities,
+        owner: Entity,
+        lifetime: f32,
+        scale: f32
+    )
+    {
+        let text = if scale == 0.0
+        {
+            "PAUSED".to_owned()
+        } else
+        {
+            format!("SPEED x{scale}")
+        };
+
+        Self::set_text(&mut self.time_scale_indicator, entities, &self.ui, owner, lifetime, text, |text|
+        {
+            NotificationCreateInfo::Text{severity: NotificationSeverity::Normal, text}
+        })
+    }
+
+    pub fn set_hint(
+        &mut self,
+        entities: &mut ClientEntities,
+        owner: Entity,
+        lifetime: f32,
+        text: String
+    )
+    {
+        Self::set_text(&mut self.hint, entities, &self.ui, owner, lifetime, text, |text|
+        {
+            NotificationCreateInfo::Text{severity: NotificationSeverity::Hint, text}
+        })
+    }
+
+    pub fn set_tutorial(
+        &mut self,
+        entities: &mut ClientEntities,
+        owner: Entity,
+        lifetime: f32,
+        text: String
+    )
+    {
+        Self::set_text(&mut self.tutorial, entities, &self.ui, owner, lifetime, text, |text|
+        {
+            NotificationCreateInfo::Text{severity: NotificationSeverity::Hint, text}
+        })
+    }
+
+    pub fn set_achievement(
+        &mut self,
+        entities: &mut ClientEntities,
+        owner: Entity,
+        lifetime: f32,
+        text: String
+    )
+    {
+        Self::set_text(&mut self.achievement, entities, &self.ui, owner, lifetime, text, |text|
+        {
+            NotificationCreateInfo::Text{severity: NotificationSeverity::Achievement, text}
+        })
+    }
+
+    pub fn set_crash_notice(
+        &mut self,
+        entities: &mut ClientEntities,
+        owner: Entity,
+        lifetime: f32,
+        path: &Path
+    )
+    {
+        let text = format!("the game crashed last time, details are in {}", path.display());
+        Self::set_text(&mut self.crash_notice, entities, &self.ui, owner, lifetime, text, |text|
+        {
+            NotificationCreateInfo::Text{severity: NotificationSeverity::Damage, text}
+        })
+    }
 }
 
 type DebugVisibility = <DebugConfig as DebugConfigTrait>::DebugVisibility;
@@ -652,24 +1045,44 @@ pub struct GameState
     pub ui_notifications: UiNotifications,
     pub entities: ClientEntitiesContainer,
     pub controls: ControlsController,
+    gamepad: Gamepad,
     pub running: bool,
     pub debug_mode: bool,
     pub tilemap: Arc<TileMap>,
     pub items_info: Arc<ItemsInfo>,
+    pub enemies_info: Arc<EnemiesInfo>,
     pub characters_info: Arc<CharactersInfo>,
+    pub perks_info: Arc<PerksInfo>,
+    pub barks_info: Arc<BarksInfo>,
+    pub particle_effects: Arc<ParticleEffects>,
+    pub mods_info: Arc<ModsInfo>,
     pub user_receiver: Rc<RefCell<UiReceiver>>,
     pub ui: Rc<RefCell<Ui>>,
-    pub common_textures: CommonTextures,
+    sprite_batches: SpriteBatches,
+    post_effects: PostEffects,
+    color_grading: ColorGrading,
+    targets: RenderTargets,
     pub connected_and_ready: bool,
     pub world: World,
     ui_camera: Camera,
     shaders: ProgramShaders,
     host: bool,
     is_trusted: bool,
-    camera_scale: f32,
+    camera_controller: CameraController,
+    pub camera_shake: CameraShake,
+    // set by the server, only ever not 1 in single player
+    time_scale: f32,
+    // seconds left before leaving a world that ended
+    world_ended: Option<f32>,
+    pub audio: Audio,
+    sound_cues: SoundCues,
+    pub lifetime_stats: LifetimeStats,
+    telemetry: Telemetry,
     rare_timer: f32,
     debug_visibility: <DebugVisibility as DebugVisibilityTrait>::State,
     connections_handler: Arc<RwLock<ConnectionsHandler>>,
+    snapshots: SnapshotDecoder,
+    prediction: Prediction,
     receiver_handle: Option<JoinHandle<()>>,
     receiver: Receiver<Message>
 }
@@ -723,12 +1136,17 @@ impl GameState
 
         let player_entity = Self::connect_to_server(
             connections_handler.clone(),
-            &info.client_info.name
+            &info.client_info.name,
+            info.client_info.character.clone(),
+            info.data_infos.mods_info.names()
         );
 
         let mut entities = ClientEntitiesContainer::new(
             info.data_infos.clone(),
-            player_entity
+            player_entity,
+            info.client_info.graphics.reflections,
+            info.client_info.physics_rate,
+            info.shaders.light_occlusion
         );
 
         let _sender_handle = sender_loop(connections_handler.clone());
@@ -764,18 +1182,51 @@ impl GameState
 
         let builder_wrapper = &mut info.object_info.partial.builder_wrapper;
         let anatomy_locations = {
-            let base_image = image::open("textures/special/anatomy_areas.png")
+            let base_image = image::open(overridden("textures/special/anatomy_areas.png"))
                 .expect("anatomy_areas.png must exist");
 
             let mut assets = assets.lock();
 
-            let part_creator = PartCreator{
+            let mut part_creator = PartCreator{
                 assets: &mut assets,
                 resource_uploader: builder_wrapper.resource_uploader(),
                 shader: info.shaders.ui
             };
 
-            UiAnatomyLocations::new(part_creator, base_image)
+            let human = UiAnatomyLocations::new(&mut part_creator, base_image);
+
+            let plans = info.data_infos.body_plans.iter().map(|plan|
+            {
+                let path = Path::new("textures").join(&plan.overlay);
+                let base_image = image::open(overridden(&path))
+                    .unwrap_or_else(|err| panic!("{} must exist: {err}", path.display()));
+
+                let locations = UiAnatomyLocations::new_plan(&mut part_creator, base_image, plan);
+
+                (plan.name.clone(), locations)
+            }).collect();
+
+            UiAnatomyOverlays{human, plans}
+        };
+
+        let sprite_batches = SpriteBatches::new(Rc::new(SpriteAtlas::new(
+            builder_wrapper.resource_uploader(),
+            &assets.lock(),
+            info.shaders.default,
+            "textures/normal"
+        )));
+
+        let post_effects = {
+            let assets = assets.lock();
+
+            PostEffects::new(
+                info.client_info.graphics,
+                info.targets.clone(),
+                &info.object_info.partial.object_factory,
+                builder_wrapper.resource_uploader(),
+                assets.model(assets.default_model(DefaultModel::Square)).clone(),
+                info.shaders.post
+            )
         };
 
         let ui_mouse_entity = entities.ui_mouse_entity;
@@ -788,8 +1239,6 @@ impl GameState
             user_receiver.clone()
         );
 
-        let common_textures = CommonTextures::new(&mut assets.lock());
-
         let debug_visibility = <DebugVisibility as DebugVisibilityTrait>::State::new(
             &info.camera.read()
         );
@@ -797,8 +1246,19 @@ impl GameState
         let ui_notifications = UiNotifications{
             ui: ui.clone(),
             stamina: None,
+            visibility: None,
+            hunger: None,
+            thirst: None,
             weapon_cooldown: None,
-            tile_tooltip: None
+            tile_tooltip: None,
+            lock_indicator: None,
+            saving_indicator: None,
+            time_scale_indicator: None,
+            crash_notice: None,
+            hint: None,
+            tutorial: None,
+            achievement: None,
+            barks: HashMap::new()
         };
 
         let ui_camera = Camera::new(1.0, -1.0..1.0);
@@ -812,24 +1272,42 @@ impl GameState
             ui_notifications,
             entities,
             items_info: info.data_infos.items_info,
+            enemies_info: info.data_infos.enemies_info,
             characters_info: info.data_infos.characters_info,
+            perks_info: info.data_infos.perks_info,
+            barks_info: info.data_infos.barks_info,
+            particle_effects: info.data_infos.particle_effects,
+            mods_info: info.data_infos.mods_info,
             controls,
+            gamepad: Gamepad::new(),
             running: true,
             ui_camera,
             shaders: info.shaders,
             world,
             debug_mode: info.client_info.debug,
             tilemap,
-            camera_scale: 1.0,
+            camera_controller: CameraController::new(info.client_info.camera),
+            camera_shake: CameraShake::new(&info.client_info.camera),
+            time_scale: 1.0,
+            world_ended: None,
+            audio: Audio::new(info.client_info.audio),
+            sound_cues: SoundCues::new(info.client_info.sound_cues),
+            lifetime_stats: LifetimeStats::load(&info.client_info.name),
+            telemetry: Telemetry::new(info.client_info.telemetry.clone()),
             rare_timer: 0.0,
             ui,
-            common_textures,
+            sprite_batches,
+            post_effects,
+            color_grading: ColorGrading::parse("grading/grading.json"),
+            targets: info.targets,
             connected_and_ready: false,
             host: info.host,
             is_trusted: false,
             user_receiver,
             debug_visibility,
             connections_handler,
+            snapshots: SnapshotDecoder::new(),
+            prediction: Prediction::new(),
             receiver_handle,
             receiver
         };
@@ -837,7 +1315,7 @@ impl GameState
         {
             let aspect = this.camera.read().aspect();
 
-            this.set_camera_scale(DEFAULT_ZOOM);
+            this.set_camera_scale(this.camera_controller.scale());
 
             this.resize(aspect);
             this.camera_resized();
@@ -846,15 +1324,24 @@ impl GameState
         Rc::new(RefCell::new(this))
     }
 
-    pub fn sync_character(&mut self, entity: Entity)
+    pub fn sync_character(&mut self, entity: Entity, dt: f32)
     {
-        let entities = self.entities();
-        if let Some(target) = entities.target_ref(entity)
+        let correction = self.prediction.update(dt);
+
+        let target = self.entities().target(entity).map(|mut target|
+        {
+            target.position += correction;
+
+            target.clone()
+        });
+
+        if let Some(target) = target
         {
-            self.send_message(Message::SetTarget{entity, target: target.clone()});
+            let sequence = self.prediction.push(target.position);
+            self.send_message(Message::PlayerMove{entity, sequence, target});
         }
 
-        if let Some(character) = entities.character(entity)
+        if let Some(character) = self.entities().character(entity)
         {
             self.send_message(Message::SyncCharacter{entity, info: character.get_sync_info()});
         }
@@ -862,12 +1349,14 @@ impl GameState
 
     fn connect_to_server(
         handler: Arc<RwLock<ConnectionsHandler>>,
-        name: &str
+        name: &str,
+        creation: CharacterCreation,
+        mods: Vec<String>
     ) -> Entity
     {
         let mut handler = handler.write();
 
-        let message = Message::PlayerConnect{name: name.to_owned()};
+        let message = Message::PlayerConnect{name: name.to_owned(), creation, mods};
         if let Err(x) = handler.send_blocking(&message)
         {
             panic!("error connecting to server: {x}");
@@ -879,15 +1368,14 @@ impl GameState
             {
                 player_entity
             },
+            Ok(Some(Message::ModsMismatch{mods})) =>
+            {
+                panic!("server has different mods loaded: {mods:?}")
+            },
             x => panic!("received wrong message on connect: {x:?}")
         }
     }
 
-    fn damage_info(&self) -> TextureId
-    {
-        self.common_textures.blood
-    }
-
     pub fn entities(&self) -> &ClientEntities
     {
         &self.entities.entities
@@ -937,9 +1425,79 @@ impl GameState
         }
     }
 
+    fn held_item(&self) -> Option<(InventoryItem, ItemId)>
+    {
+        let player = self.player();
+        let entities = &self.entities.entities;
+
+        let holding = entities.character(player)?.holding()?;
+
+        entities.inventory(player)?.get(holding).map(|item| (holding, item.id))
+    }
+
+    fn rehold(&mut self, (holding, id): (InventoryItem, ItemId))
+    {
+        let player = self.player();
+        let entities = &self.entities.entities;
+
+        let moved = {
+            let inventory = some_or_return!(entities.inventory(player));
+
+            let same = inventory.get(holding).map(|item| item.id == id).unwrap_or(false);
+            if same
+            {
+                return;
+            }
+
+            inventory.find(id)
+        };
+
+        some_or_return!(entities.character_mut(player)).set_holding(moved);
+    }
+
     fn process_message_inner(&mut self, create_info: &mut RenderCreateInfo, message: Message)
     {
-        let message = some_or_return!{self.entities.handle_message(create_info, message)};
+        if let Message::Snapshot{sequence, base} = message
+        {
+            let ack = self.snapshots.start(sequence, base);
+            self.send_message(ack);
+
+            return;
+        }
+
+        let message = some_or_return!(self.snapshots.decode(message));
+
+        if let Message::EntityDamaged{entity, source, damage} = message
+        {
+            damaging_system::entity_damaged(&self.entities.entities, &self.particle_effects, entity, source, damage);
+
+            return;
+        }
+
+        // the server owns the inventory, whatever was held stays held if its still there
+        let held = match &message
+        {
+            Message::SetInventory{entity, ..} if *entity == self.player() => self.held_item(),
+            _ => None
+        };
+
+        // only a respawn moves the player from the server side
+        if let Message::SetTarget{entity, ..} = &message
+        {
+            if *entity == self.player()
+            {
+                self.prediction.reset();
+            }
+        }
+
+        let message = self.entities.handle_message(create_info, message);
+
+        if let Some(held) = held
+        {
+            self.rehold(held);
+        }
+
+        let message = some_or_return!(message);
         let message = some_or_return!{self.world.handle_message(message)};
 
         match message
@@ -952,6 +1510,92 @@ impl GameState
             {
                 self.is_trusted = true;
             },
+            Message::PlayerMoveAck{sequence, position} =>
+            {
+                let player = self.player();
+                let current = some_or_return!(self.entities().target_ref(player)).position;
+
+                self.prediction.acked(sequence, position, current);
+            },
+            Message::SpawnDecal{decal} =>
+            {
+                self.entities.decals.push(create_info, decal);
+            },
+            Message::SpawnTracer{tracer} =>
+            {
+                self.entities.projectiles.get_mut().push_remote_tracer(&self.entities.entities, &self.particle_effects, tracer);
+            },
+            Message::SpawnImpact{impact, position, direction} =>
+            {
+                Projectiles::remote_impact(&self.entities.entities, &self.particle_effects, &impact, position, direction);
+            },
+            Message::Explode{position, info} =>
+            {
+                projectile::spawn_explosion(&self.entities.entities, &self.particle_effects, position);
+
+                info.push(position, &self.entities.entities, &self.world);
+            },
+            Message::SetWeather{weather} =>
+            {
+                self.entities.weather.set_weather(weather);
+                self.entities.entities.set_weather(weather);
+            },
+            Message::SetDifficulty{difficulty} =>
+            {
+                self.entities.entities.set_difficulty(difficulty);
+            },
+            Message::SetTimeScale{scale} =>
+            {
+                self.time_scale = scale;
+            },
+            Message::SpawnShake{shake} =>
+            {
+                let listener = self.camera.read().position().coords;
+
+                self.camera_shake.add(&shake, listener);
+            },
+            Message::PlaySound{sound} =>
+            {
+                self.audio.play(sound);
+            },
+            Message::TradeResponse{entity, response} =>
+            {
+                let text = match response
+                {
+                    BarterResponse::Accept => "deal".to_owned(),
+                    BarterResponse::Counter{missing} => format!("thats {missing:.0} short"),
+                    BarterResponse::Refuse => "not trading with u".to_owned()
+                };
+
+                self.ui_notifications.set_bark(&mut self.entities.entities, entity, 2.0, text);
+            },
+            Message::OpenTrade{entity} =>
+            {
+                self.user_receiver.borrow_mut().push(UserEvent::OpenTrade(entity));
+            },
+            Message::RunScript{script} =>
+            {
+                self.user_receiver.borrow_mut().push(UserEvent::Script(script));
+            },
+            Message::Autosaving =>
+            {
+                let player = self.player();
+                self.ui_notifications.set_saving_indicator(&mut self.entities.entities, player, 1.5);
+            },
+            Message::WorldEnded =>
+            {
+                const LEAVE_DELAY: f32 = 5.0;
+
+                let player = self.player();
+                self.ui_notifications.set_bark(
+                    &mut self.entities.entities,
+                    player,
+                    LEAVE_DELAY,
+                    "the world is over".to_owned()
+                );
+
+                self.world_ended = Some(LEAVE_DELAY);
+            },
             x => panic!("unhandled message: {x:?}")
         }
     }
@@ -960,35 +1604,45 @@ impl GameState
     {
         const ZOOM_SPEED: f32 = 2.0;
 
+        let limited = !self.debug_mode;
+
         if self.pressed(Control::ZoomIn)
         {
-            self.resize_camera(1.0 - dt * ZOOM_SPEED);
+            self.camera_controller.zoom(1.0 - dt * ZOOM_SPEED, limited);
         } else if self.pressed(Control::ZoomOut)
         {
-            self.resize_camera(1.0 + dt * ZOOM_SPEED);
+            self.camera_controller.zoom(1.0 + dt * ZOOM_SPEED, limited);
         } else if self.pressed(Control::ZoomReset)
         {
-            self.set_camera_scale(DEFAULT_ZOOM);
+            self.camera_controller.reset();
+        }
+
+        if self.camera_controller.update(dt)
+        {
+            self.set_camera_scale(self.camera_controller.scale());
         }
     }
 
-    fn resize_camera(&mut self, factor: f32)
+    pub fn camera_shake_offset(&self) -> Vector3<f32>
     {
-        let _max_scale = World::zoom_limit(); // maybe i would wanna use this??
+        self.camera_shake.offset(self.camera.read().size())
+    }
 
-        self.camera_scale *= factor;
-        if !self.debug_mode
-        {
-            self.camera_scale = self.camera_scale.clamp(0.2, DEFAULT_ZOOM);
-        }
+    pub fn camera_follow_position(
+        &self,
+        current: Vector3<f32>,
+        player: Vector3<f32>,
+        mouse: Vector3<f32>
+    ) -> Vector3<f32>
+    {
+        let size = self.camera.read().size();
 
-        self.set_camera_scale(self.camera_scale);
+        self.camera_controller.follow_position(current, player, mouse, size)
     }
 
     fn set_camera_scale(&mut self, scale: f32)
     {
         {
-            self.camera_scale = scale;
             let mut camera = self.camera.write();
 
             camera.rescale(scale);
@@ -1016,11 +1670,39 @@ impl GameState
         self.send_message(message);
     }
 
+    pub fn time_scale(&self) -> f32
+    {
+        self.time_scale
+    }
+
+    pub fn is_paused(&self) -> bool
+    {
+        self.time_scale == 0.0
+    }
+
+    fn world_time_scale(&self) -> f32
+    {
+        self.time_scale * self.camera_shake.time_scale()
+    }
+
+    // the server ignores this if anyone else is connected
+    pub fn request_time_scale(&self, scale: f32)
+    {
+        self.send_message(Message::SetTimeScale{scale});
+    }
+
     pub fn send_message(&self, message: Message)
     {
         self.connections_handler.write().send_message(message);
     }
 
+    pub fn make_noise(&self, noise: Noise)
+    {
+        let mut passer = self.connections_handler.write();
+
+        self.entities().make_noise(&mut *passer, noise);
+    }
+
     pub fn create_popup(&mut self, responses: Vec<UserEvent>)
     {
         let popup_position = self.ui_mouse_position();
@@ -1063,6 +1745,25 @@ impl GameState
         self.world.tile(index)
     }
 
+    fn is_interior(&self) -> bool
+    {
+        let position = some_or_false!(self.entities.player_transform()).position;
+
+        self.world.under_roof(position)
+    }
+
+    // names match the world generation rule files
+    fn biome(&self) -> &'static str
+    {
+        if self.camera.read().position().z < 0.0
+        {
+            "underground"
+        } else
+        {
+            "surface"
+        }
+    }
+
     pub fn tile_of(&self, position: Pos3<f32>) -> TilePos
     {
         self.world.tile_of(position)
@@ -1104,11 +1805,20 @@ impl GameState
 
         self.entities.entities.create_render_queued(&mut create_info);
 
-        self.entities.update_buffers(&visibility, info, &caster);
+        self.entities.update_buffers(&self.world, &visibility, &mut self.sprite_batches, info, &caster);
 
         info.update_camera(&self.ui_camera);
+
+        self.post_effects.update_buffers(info, self.ui_camera.size());
+        self.sound_cues.update_buffers(info);
         let normal_camera = self.camera.read();
 
+        // size of a screen pixel in ui units
+        let pixel = self.targets.size().map(|[width, height]|
+        {
+            self.ui_camera.size().component_div(&Vector2::new(width as f32, height as f32))
+        });
+
         let entities = &self.entities.entities;
         self.ui.borrow().in_render_order(|entity|
         {
@@ -1118,10 +1828,15 @@ impl GameState
                 info.update_camera(&normal_camera);
             }
 
-            let transform = some_or_return!(entities.transform(entity));
+            let mut transform = some_or_return!(entities.transform(entity)).clone();
             let mut render = some_or_return!(entities.render_mut(entity));
 
-            render.set_transform(transform.clone());
+            if let Some(pixel) = pixel.filter(|_| !is_world)
+            {
+                transform.position = Self::snap_to_pixels(&transform, pixel);
+            }
+
+            render.set_transform(transform);
             render.update_buffers(info);
 
             if is_world
@@ -1133,13 +1848,69 @@ impl GameState
         self.entities.entities.handle_on_change();
     }
 
+    // the corner lands on a whole pixel so text and borders dont get smeared between two
+    fn snap_to_pixels(transform: &Transform, pixel: Vector2<f32>) -> Vector3<f32>
+    {
+        let half = transform.scale.xy() * 0.5;
+
+        let corner = (transform.position.xy() - half).component_div(&pixel);
+        let snapped = corner.map(|x| x.round()).component_mul(&pixel) + half;
+
+        Vector3::new(snapped.x, snapped.y, transform.position.z)
+    }
+
     pub fn draw(&self, info: &mut DrawInfo)
     {
-        if !self.entities.player_exists()
+        let player_exists = self.entities.player_exists();
+
+        if player_exists
+        {
+            let viewport = self.post_effects.viewport();
+
+            if let Some(viewport) = viewport.clone()
+            {
+                info.set_viewport(viewport);
+            }
+
+            self.draw_world(info);
+
+            if viewport.is_some()
+            {
+                info.reset_viewport();
+            }
+        }
+
+        info.next_subpass();
+
+        info.bind_pipeline(self.shaders.post);
+
+        self.post_effects.draw(info, &self.color_grading.lut());
+
+        // the world might be drawn at a lower resolution, the ui never is
+        info.next_subpass();
+
+        if !player_exists
         {
             return;
         }
 
+        info.bind_pipeline(self.shaders.ui);
+
+        self.sound_cues.draw(info);
+
+        let entities = &self.entities.entities;
+        self.ui.borrow().in_render_order(|entity|
+        {
+            let render = some_or_return!(entities.render(entity));
+
+            let outline = UiOutlinedInfo::new(render.mix);
+
+            render.draw(info, outline);
+        });
+    }
+
+    fn draw_world(&self, info: &mut DrawInfo)
+    {
         let visibility = self.visibility_checker();
 
         let animation = self.entities.animation.sin();
@@ -1147,6 +1918,16 @@ impl GameState
         let draw_entities = render_system::DrawEntities{
             renders: &self.entities.visible_renders,
             shaded_renders: &self.entities.shaded_renders,
+            light_occlusion: &self.entities.light_occlusion,
+            decals: &self.entities.decals,
+            weather: &self.entities.weather,
+            background: &self.entities.background,
+            reflections: &self.entities.reflections,
+            tracers: &self.entities.tracers,
+            batches: &self.sprite_batches,
+            height: self.entities.player_transform().map(|x| x.position.z).unwrap_or_default(),
+            // the whole camera spans 2 units on the screen
+            tile_step: Vector2::repeat(TILE_SIZE * 2.0).component_div(&self.camera.read().size()),
             world: &self.world
         };
 
@@ -1158,18 +1939,6 @@ impl GameState
             info,
             animation
         );
-
-        info.bind_pipeline(self.shaders.ui);
-
-        let entities = &self.entities.entities;
-        self.ui.borrow().in_render_order(|entity|
-        {
-            let render = some_or_return!(entities.render(entity));
-
-            let outline = UiOutlinedInfo::new(render.mix);
-
-            render.draw(info, outline);
-        });
     }
 
     fn visibility_checker(&self) -> VisibilityChecker
@@ -1204,12 +1973,99 @@ impl GameState
     pub fn on_player_connected(&mut self)
     {
         self.connected_and_ready = true;
+
+        if let Some(path) = crash_report::take_unseen()
+        {
+            println!("the game crashed last time, the crash report is in {}", path.display());
+
+            let player = self.player();
+            self.ui_notifications.set_crash_notice(&mut self.entities.entities, player, 8.0, &path);
+        }
+    }
+
+    fn death_cause(&self, kills: &[(Entity, Entity)]) -> String
+    {
+        let player = self.player();
+        let entities = &self.entities.entities;
+
+        // nothing killed the player directly, so bleeding out or something like that
+        let killer = some_or_value!(
+            kills.iter().find(|(_, killed)| *killed == player).map(|(killer, _)| *killer),
+            "injuries".to_owned()
+        );
+
+        if let Some(enemy) = entities.enemy(killer)
+        {
+            enemy.info(&self.enemies_info).name.clone()
+        } else if entities.player_exists(killer)
+        {
+            "player".to_owned()
+        } else
+        {
+            "unknown".to_owned()
+        }
+    }
+
+    fn update_stats(&mut self, alive: bool, dt: f32)
+    {
+        let player = self.player();
+
+        let kills = self.entities.entities.take_kills();
+
+        self.telemetry.update(dt);
+
+        if self.telemetry.died(alive)
+        {
+            let cause = self.death_cause(&kills);
+            self.telemetry.death(cause);
+        }
+
+        let player_kills = kills.iter().filter(|(killer, _)| *killer == player).count();
+        self.lifetime_stats.add_kills(player_kills as u32);
+
+        let position = self.player_transform().map(|x| x.position);
+
+        let unlocked = self.lifetime_stats.update(position, alive, dt);
+
+        if !unlocked.is_empty()
+        {
+            let text = unlocked.into_iter().map(|x| format!("achievement unlocked: {}", x.title()))
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            self.ui_notifications.set_achievement(&mut self.entities.entities, player, 5.0, text);
+        }
+    }
+
+    // aiming with the right stick moves the mouse around the player
+    fn gamepad_input(&mut self)
+    {
+        for (state, key) in self.gamepad.poll()
+        {
+            self.controls.handle_key(state, key);
+        }
+
+        let aim = some_or_return!(self.gamepad.aim());
+
+        let player_offset = {
+            let camera = self.camera.read();
+            let position = some_or_return!(self.entities.entities.transform(self.player())).position;
+
+            (position.xy() - camera.position().xy().coords).component_div(&camera.size())
+        };
+
+        self.controls.set_source(InputSource::Gamepad);
+        self.mouse_position = Vector2::repeat(0.5) + player_offset + aim;
     }
 
     pub fn update_pre(&mut self, dt: f32)
     {
+        self.gamepad_input();
+
         self.check_resize_camera(dt);
 
+        self.camera_shake.update(dt);
+
         self.entities.update_mouse(self.ui_mouse_position());
 
         self.world.update(dt);
@@ -1226,13 +2082,43 @@ impl GameState
             self.entities.update(
                 &self.world,
                 &mut *passer,
-                self.damage_info(),
+                &self.particle_effects,
                 self.is_trusted,
-                dt
+                dt * self.world_time_scale()
             );
+
+            self.update_barks();
         }
     }
 
+    fn update_barks(&mut self)
+    {
+        let entities = &mut self.entities.entities;
+
+        let mut barks = Vec::new();
+        for_each_component!(entities, enemy, |entity, enemy: &RefCell<Enemy>|
+        {
+            let mut enemy = enemy.borrow_mut();
+
+            if let Some((table, kind)) = enemy.take_bark()
+            {
+                if let Some(table) = self.barks_info.get(table)
+                {
+                    if let Some(line) = table.pick(kind)
+                    {
+                        barks.push((entity, line.to_owned()));
+                        enemy.set_bark_cooldown(table.cooldown);
+                    }
+                }
+            }
+        });
+
+        barks.into_iter().for_each(|(entity, line)|
+        {
+            self.ui_notifications.set_bark(entities, entity, 2.0, line);
+        });
+    }
+
     pub fn ui_input(&mut self, event: UiEvent) -> bool
     {
         let entities = &self.entities.entities;
@@ -1304,21 +2190,98 @@ impl GameState
         let partial = PartialCombinedInfo{
             assets: &assets,
             passer: &self.connections_handler,
-            common_textures: &self.common_textures,
+            particle_effects: &self.particle_effects,
+            projectiles: &self.entities.projectiles,
+            world: &self.world,
             characters_info: &self.characters_info,
             items_info: &self.items_info
         };
 
+        // hit stop and pausing slow down everything in the world but not the ui
+        let world_dt = dt * self.world_time_scale();
+
         self.entities.entities.update_characters(
             partial,
             &mut create_info,
-            dt
+            world_dt
         );
 
-        self.entities.entities.update_watchers(dt);
+        self.entities.entities.update_watchers(world_dt);
+
+        self.entities.tracers.update(&mut create_info, &self.entities.projectiles.borrow(), &self.entities.entities);
+
+        if let Some(position) = self.entities.player_transform().map(|x| x.position)
+        {
+            let player = self.player();
+
+            self.sound_cues.update(
+                &mut create_info,
+                self.entities.entities.take_noises(),
+                player,
+                position,
+                self.ui_camera.size(),
+                dt
+            );
+        }
 
         self.entities.entities.create_queued(&mut create_info);
 
+        {
+            let biome = self.biome();
+            let camera = self.camera.read();
+
+            self.entities.weather.update(
+                &mut create_info,
+                camera.position().coords,
+                camera.size(),
+                dt
+            );
+
+            self.entities.background.update(
+                &mut create_info,
+                biome,
+                camera.position().coords,
+                camera.size(),
+                self.color_grading.daylight()
+            );
+
+            self.audio.update(&self.entities.entities, &self.world, camera.position().coords);
+        }
+
+        {
+            let anatomy = self.entities.entities.anatomy(self.player());
+
+            let damage = self.post_effects.update(anatomy.as_deref(), dt);
+
+            if damage > 0.0
+            {
+                self.gamepad.rumble(damage);
+            }
+
+            let alive = anatomy.map(|x| x.speed().is_some()).unwrap_or(false);
+
+            self.update_stats(alive, dt);
+        }
+
+        if self.time_scale != 1.0
+        {
+            let player = self.player();
+            self.ui_notifications.set_time_scale_indicator(&mut self.entities.entities, player, 0.1, self.time_scale);
+        }
+
+        let interior = self.is_interior();
+        self.color_grading.update(interior, dt);
+
+        if let Some(timer) = self.world_ended.as_mut()
+        {
+            *timer -= dt;
+
+            if *timer <= 0.0
+            {
+                self.running = false;
+            }
+        }
+
         if self.rare_timer <= 0.0
         {
             self.rare();
@@ -1344,6 +2307,13 @@ impl GameState
     {
         if self.debug_visibility.input(&control) { return true; };
 
+        if let yanyaengine::Control::Scroll{y, ..} = control
+        {
+            self.camera_controller.scroll(y as f32, !self.debug_mode);
+
+            return true;
+        }
+
         self.controls.handle_input(control).is_some()
     }
 
@@ -1354,6 +2324,8 @@ impl GameState
 
     pub fn mouse_moved(&mut self, position: Vector2<f32>)
     {
+        self.controls.set_source(InputSource::KeyboardMouse);
+
         self.mouse_position = position;
     }
 
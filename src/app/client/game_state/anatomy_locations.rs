@@ -9,10 +9,23 @@ use yanyaengine::TextureId;
 use super::PartCreator;
 use crate::{
     client::ui_element::*,
-    common::{anatomy::HumanPartId, Side1d}
+    common::{
+        anatomy::{AnatomyPartId, HumanPartId, BodyPlanInfo},
+        Side1d
+    }
 };
 
 
+fn hex_color(value: u32) -> Rgba<u8>
+{
+    let r = (value >> (8 * 2)) & 0xff;
+    let g = (value >> 8) & 0xff;
+    let b = value & 0xff;
+
+    Rgba([r as u8, g as u8, b as u8, u8::MAX])
+}
+
+
 pub struct UiAnatomyLocation
 {
     pub id: TextureId,
@@ -58,19 +71,34 @@ impl UiAnatomyLocation
 pub struct UiAnatomyLocations
 {
     pub aspect: f32,
-    pub locations: HashMap<HumanPartId, UiAnatomyLocation>
+    pub locations: HashMap<AnatomyPartId, UiAnatomyLocation>
 }
 
 impl UiAnatomyLocations
 {
-    pub fn new(
-        mut part_creator: PartCreator,
-        base_image: DynamicImage
+    fn from_colors(
+        part_creator: &mut PartCreator,
+        base_image: DynamicImage,
+        colors: impl Iterator<Item=(AnatomyPartId, Rgba<u8>)>
     ) -> Self
     {
         let base_image = base_image.into_rgba8();
         let aspect = base_image.width() as f32 / base_image.height() as f32;
 
+        let locations = colors.map(|(id, color)|
+        {
+            (id, UiAnatomyLocation::from_color(part_creator, &base_image, color))
+        }).collect();
+
+        Self{aspect, locations}
+    }
+
+    pub fn new(
+        part_creator: &mut PartCreator,
+        base_image: DynamicImage
+    ) -> Self
+    {
+
         let color_pairs: HashMap<HumanPartId, Rgba<u8>> = [
             (HumanPartId::Head, 0xff0000),
             (HumanPartId::Spine, 0xdda0dd),
@@ -92,26 +120,39 @@ impl UiAnatomyLocations
             (HumanPartId::Foot(Side1d::Left), 0xf0e68c)
         ].into_iter().map(|(key, value): (_, u32)|
         {
-            let r = (value >> (8 * 2)) & 0xff;
-            let g = (value >> 8) & 0xff;
-            let b = value & 0xff;
+            (key, hex_color(value))
+        }).collect();
 
-            let color = Rgba([r as u8, g as u8, b as u8, u8::MAX]);
+        let colors = HumanPartId::iter().map(|id| (AnatomyPartId::Human(id), color_pairs[&id]));
 
-            (key, color)
-        }).collect();
+        Self::from_colors(part_creator, base_image, colors)
+    }
 
-        let locations: HashMap<_, _> = HumanPartId::iter().map(|id|
+    pub fn new_plan(
+        part_creator: &mut PartCreator,
+        base_image: DynamicImage,
+        plan: &BodyPlanInfo
+    ) -> Self
+    {
+        let colors = plan.parts.iter().enumerate().map(|(index, part)|
         {
-            let location = UiAnatomyLocation::from_color(
-                &mut part_creator,
-                &base_image,
-                color_pairs[&id]
-            );
+            (AnatomyPartId::Plan(index), hex_color(part.color()))
+        });
 
-            (id, location)
-        }).collect();
+        Self::from_colors(part_creator, base_image, colors)
+    }
+}
 
-        Self{aspect, locations}
+pub struct UiAnatomyOverlays
+{
+    pub human: UiAnatomyLocations,
+    pub plans: HashMap<String, UiAnatomyLocations>
+}
+
+impl UiAnatomyOverlays
+{
+    pub fn get(&self, plan: Option<&str>) -> &UiAnatomyLocations
+    {
+        plan.and_then(|plan| self.plans.get(plan)).unwrap_or(&self.human)
     }
 }
@@ -0,0 +1,487 @@
+use std::{
+    fs::{self, File},
+    io::Cursor,
+    mem,
+    path::Path,
+    cell::RefCell,
+    time::Duration,
+    sync::{Arc, atomic::{AtomicU32, Ordering}},
+    collections::HashMap
+};
+
+use serde::Deserialize;
+
+use nalgebra::{Vector2, Vector3};
+
+use rodio::{
+    Decoder,
+    OutputStream,
+    OutputStreamHandle,
+    Sink,
+    SpatialSink,
+    Source,
+    source::Buffered
+};
+
+use crate::common::{
+    some_or_value,
+    some_or_return,
+    raycast::*,
+    collider::ColliderLayer,
+    overrides::overridden,
+    rng::{self, RngStream},
+    AnyEntities,
+    Entity,
+    Occluder,
+    Sound,
+    SoundEmitter,
+    entity::{for_each_component, ClientEntities},
+    world::{World, TILE_SIZE}
+};
+
+
+// how far apart the ears are, the emitter always sits at unit distance so this only changes the panning
+const EAR_DISTANCE: f32 = 0.3;
+
+// anything past this many playing at once gets skipped
+const MAX_SOUNDS: usize = 64;
+
+// low pass cutoff in hz with nothing in the way
+const OPEN_CUTOFF: u32 = 20000;
+
+// every wall or closed door in the way divides the cutoff by how many there are and takes off some volume
+const MUFFLED_CUTOFF: u32 = 700;
+const MIN_CUTOFF: u32 = 150;
+const OCCLUDED_VOLUME: f32 = 0.45;
+
+// how often the filter picks up a new cutoff
+const FILTER_PERIOD: Duration = Duration::from_millis(20);
+
+type SoundSource = Buffered<Decoder<Cursor<Vec<u8>>>>;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SoundCategory
+{
+    #[default]
+    Sfx,
+    Music,
+    Ui
+}
+
+// every category gets multiplied by the master volume
+#[derive(Debug, Clone, Copy)]
+pub struct AudioSettings
+{
+    pub master: f32,
+    pub sfx: f32,
+    pub music: f32,
+    pub ui: f32
+}
+
+impl Default for AudioSettings
+{
+    fn default() -> Self
+    {
+        Self{master: 1.0, sfx: 1.0, music: 1.0, ui: 1.0}
+    }
+}
+
+impl AudioSettings
+{
+    pub fn volume(&self, category: SoundCategory) -> f32
+    {
+        let volume = match category
+        {
+            SoundCategory::Sfx => self.sfx,
+            SoundCategory::Music => self.music,
+            SoundCategory::Ui => self.ui
+        };
+
+        volume * self.master
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SoundInfoRaw
+{
+    // picks a random one each time it plays
+    files: Vec<String>,
+    #[serde(default = "default_volume")]
+    volume: f32,
+    // in tiles, past this it cant be heard at all
+    range: f32,
+    #[serde(default)]
+    category: SoundCategory
+}
+
+fn default_volume() -> f32 { 1.0 }
+
+fn cutoff_for(occlusion: u32) -> u32
+{
+    if occlusion == 0
+    {
+        OPEN_CUTOFF
+    } else
+    {
+        (MUFFLED_CUTOFF / occlusion).max(MIN_CUTOFF)
+    }
+}
+
+fn filtered(
+    source: impl Source<Item=i16> + Send + 'static,
+    cutoff: Arc<AtomicU32>
+) -> impl Source<Item=f32> + Send + 'static
+{
+    source.convert_samples::<f32>().low_pass(OPEN_CUTOFF).periodic_access(FILTER_PERIOD, move |filter|
+    {
+        filter.to_low_pass(cutoff.load(Ordering::Relaxed));
+    })
+}
+
+struct SoundInfo
+{
+    sources: Vec<SoundSource>,
+    volume: f32,
+    range: f32,
+    category: SoundCategory
+}
+
+impl SoundInfo
+{
+    fn from_raw(directory: &Path, name: &str, raw: SoundInfoRaw) -> Self
+    {
+        let sources = raw.files.into_iter().filter_map(|file|
+        {
+            let path = overridden(directory.join(&file));
+
+            let bytes = fs::read(&path).map_err(|err|
+            {
+                eprintln!("error loading sound {name} ({}): {err}", path.display())
+            }).ok()?;
+
+            Decoder::new(Cursor::new(bytes)).map(|x| x.buffered()).map_err(|err|
+            {
+                eprintln!("error decoding sound {name} ({}): {err}", path.display())
+            }).ok()
+        }).collect();
+
+        Self{
+            sources,
+            volume: raw.volume,
+            range: raw.range * TILE_SIZE,
+            category: raw.category
+        }
+    }
+
+    fn pick(&self) -> Option<SoundSource>
+    {
+        if self.sources.is_empty()
+        {
+            return None;
+        }
+
+        Some(self.sources[rng::usize(RngStream::Cosmetic, 0..self.sources.len())].clone())
+    }
+
+    fn attenuation(&self, distance: f32) -> f32
+    {
+        (1.0 - distance / self.range).clamp(0.0, 1.0)
+    }
+}
+
+pub struct SoundsInfo
+{
+    sounds: HashMap<String, SoundInfo>
+}
+
+impl SoundsInfo
+{
+    pub fn parse(directory: impl AsRef<Path>, info: impl AsRef<Path>) -> Self
+    {
+        let info = File::open(overridden(info)).unwrap();
+
+        let raws: HashMap<String, SoundInfoRaw> = serde_json::from_reader(info).unwrap();
+
+        let sounds = raws.into_iter().map(|(name, raw)|
+        {
+            let info = SoundInfo::from_raw(directory.as_ref(), &name, raw);
+
+            (name, info)
+        }).collect();
+
+        Self{sounds}
+    }
+
+    fn get(&self, name: &str) -> Option<&SoundInfo>
+    {
+        let info = self.sounds.get(name);
+
+        if info.is_none()
+        {
+            eprintln!("sound named {name} doesnt exist");
+        }
+
+        info
+    }
+}
+
+struct Playing
+{
+    sink: SpatialSink,
+    // shared with the filter on the audio thread
+    cutoff: Arc<AtomicU32>,
+    looping: bool,
+    name: String,
+    position: Vector3<f32>,
+    volume: f32
+}
+
+struct Output
+{
+    // has to stay alive for anything to play
+    _stream: OutputStream,
+    handle: OutputStreamHandle
+}
+
+pub struct Audio
+{
+    output: Option<Output>,
+    settings: AudioSettings,
+    sounds: SoundsInfo,
+    listener: Vector3<f32>,
+    emitters: HashMap<Entity, Playing>,
+    oneshots: Vec<Playing>,
+    // waiting for the next update to know whats in the way
+    pending: Vec<Sound>
+}
+
+impl Audio
+{
+    pub fn new(settings: AudioSettings) -> Self
+    {
+        // no sound device shouldnt stop the game from running
+        let output = OutputStream::try_default().map(|(stream, handle)|
+        {
+            Output{_stream: stream, handle}
+        }).map_err(|err|
+        {
+            eprintln!("error opening audio output, running without sound: {err}")
+        }).ok();
+
+        Self{
+            output,
+            settings,
+            sounds: SoundsInfo::parse("sounds", "sounds/sounds.json"),
+            listener: Vector3::zeros(),
+            emitters: HashMap::new(),
+            oneshots: Vec::new(),
+            pending: Vec::new()
+        }
+    }
+
+    // emitter always sits at unit distance from the listener so rodio doesnt attenuate it on its own
+    fn emitter_position(&self, position: Vector3<f32>) -> [f32; 3]
+    {
+        let offset = position.xy() - self.listener.xy();
+
+        // right on top of the listener its right between the ears
+        let direction = offset.try_normalize(f32::EPSILON).unwrap_or_else(Vector2::zeros);
+
+        [direction.x, direction.y, 0.0]
+    }
+
+    // how many walls and closed doors are between the sound and the listener
+    fn occlusion(&self, entities: &ClientEntities, world: &World, position: Vector3<f32>) -> u32
+    {
+        // the camera is way above everything so check at the height of the sound
+        let listener = Vector3::new(self.listener.x, self.listener.y, position.z);
+
+        if listener == position
+        {
+            return 0;
+        }
+
+        let walls = world.tile_hit(position, listener).is_some() as u32;
+
+        let info = RaycastInfo{
+            pierce: Some(f32::INFINITY),
+            layer: ColliderLayer::DAMAGE,
+            ignore_entity: None,
+            ignore_end: false
+        };
+
+        let doors = entities.raycast(info, &position, &listener).hits.into_iter().filter(|hit|
+        {
+            if let RaycastHitId::Entity(entity) = hit.id
+            {
+                entities.occluder(entity).map(|x| matches!(*x, Occluder::Door)).unwrap_or(false)
+            } else
+            {
+                false
+            }
+        }).count() as u32;
+
+        walls + doors
+    }
+
+    fn volume_at(&self, name: &str, position: Vector3<f32>, volume: f32, occlusion: u32) -> f32
+    {
+        let info = some_or_value!(self.sounds.sounds.get(name), 0.0);
+
+        let distance = (position.xy() - self.listener.xy()).magnitude();
+
+        let occluded = OCCLUDED_VOLUME.powi(occlusion as i32);
+
+        info.attenuation(distance) * occluded * info.volume * volume * self.settings.volume(info.category)
+    }
+
+    fn start(
+        &self,
+        name: &str,
+        position: Vector3<f32>,
+        volume: f32,
+        looping: bool,
+        occlusion: u32
+    ) -> Option<Playing>
+    {
+        let output = self.output.as_ref()?;
+
+        if self.emitters.len() + self.oneshots.len() >= MAX_SOUNDS
+        {
+            return None;
+        }
+
+        let gain = self.volume_at(name, position, volume, occlusion);
+        if gain <= 0.0 && !looping
+        {
+            return None;
+        }
+
+        let source = self.sounds.get(name)?.pick()?;
+
+        let sink = SpatialSink::try_new(
+            &output.handle,
+            self.emitter_position(position),
+            [-EAR_DISTANCE, 0.0, 0.0],
+            [EAR_DISTANCE, 0.0, 0.0]
+        ).map_err(|err| eprintln!("error playing sound {name}: {err}")).ok()?;
+
+        sink.set_volume(gain);
+
+        let cutoff = Arc::new(AtomicU32::new(cutoff_for(occlusion)));
+
+        if looping
+        {
+            sink.append(filtered(source.repeat_infinite(), cutoff.clone()));
+        } else
+        {
+            sink.append(filtered(source, cutoff.clone()));
+        }
+
+        Some(Playing{sink, cutoff, looping, name: name.to_owned(), position, volume})
+    }
+
+    pub fn play(&mut self, sound: Sound)
+    {
+        self.pending.push(sound);
+    }
+
+    // doesnt care where the listener is, for menus and stuff
+    pub fn play_ui(&self, name: &str)
+    {
+        let output = some_or_return!(self.output.as_ref());
+        let info = some_or_return!(self.sounds.get(name));
+        let source = some_or_return!(info.pick());
+
+        match Sink::try_new(&output.handle)
+        {
+            Ok(sink) =>
+            {
+                sink.set_volume(info.volume * self.settings.volume(SoundCategory::Ui));
+                sink.append(source);
+                sink.detach();
+            },
+            Err(err) => eprintln!("error playing sound {name}: {err}")
+        }
+    }
+
+    fn update_playing(&self, entities: &ClientEntities, world: &World, playing: &Playing)
+    {
+        let occlusion = self.occlusion(entities, world, playing.position);
+
+        playing.cutoff.store(cutoff_for(occlusion), Ordering::Relaxed);
+
+        playing.sink.set_emitter_position(self.emitter_position(playing.position));
+        playing.sink.set_volume(self.volume_at(&playing.name, playing.position, playing.volume, occlusion));
+    }
+
+    pub fn update(&mut self, entities: &ClientEntities, world: &World, listener: Vector3<f32>)
+    {
+        self.listener = listener;
+
+        let occlusion = |this: &Self, position| this.occlusion(entities, world, position);
+
+        for sound in mem::take(&mut self.pending)
+        {
+            let occlusion = occlusion(self, sound.position);
+
+            if let Some(playing) = self.start(&sound.name, sound.position, sound.volume, false, occlusion)
+            {
+                self.oneshots.push(playing);
+            }
+        }
+
+        let mut added = Vec::new();
+        for_each_component!(entities, sound, |entity, emitter: &RefCell<SoundEmitter>|
+        {
+            let position = some_or_return!(entities.transform(entity)).position;
+
+            if let Some(playing) = self.emitters.get_mut(&entity)
+            {
+                playing.position = position;
+            } else
+            {
+                let emitter = emitter.borrow();
+                added.push((entity, emitter.clone(), position));
+            }
+        });
+
+        for (entity, emitter, position) in added
+        {
+            let occlusion = occlusion(self, position);
+
+            if let Some(playing) = self.start(&emitter.name, position, emitter.volume, emitter.looping, occlusion)
+            {
+                self.emitters.insert(entity, playing);
+            }
+        }
+
+        let removed: Vec<Entity> = self.emitters.keys().copied().filter(|entity|
+        {
+            !entities.sound_exists(*entity)
+        }).collect();
+
+        // looping sounds stop with their entity, the rest get to finish
+        removed.into_iter().for_each(|entity|
+        {
+            let playing = self.emitters.remove(&entity).unwrap();
+
+            if playing.looping
+            {
+                playing.sink.stop();
+            } else
+            {
+                self.oneshots.push(playing);
+            }
+        });
+
+        self.oneshots.retain(|playing| !playing.sink.empty());
+
+        self.emitters.values().chain(self.oneshots.iter()).for_each(|playing|
+        {
+            self.update_playing(entities, world, playing);
+        });
+    }
+}
@@ -0,0 +1,180 @@
+use std::{
+    fs::File,
+    path::Path,
+    collections::HashMap
+};
+
+use serde::Deserialize;
+
+use nalgebra::{Vector2, Vector3};
+
+use yanyaengine::{
+    Object,
+    Transform,
+    TransformContainer,
+    game_object::*
+};
+
+use crate::{
+    client::RenderCreateInfo,
+    common::{
+        render_info::*,
+        overrides::overridden
+    }
+};
+
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LayerInfo
+{
+    texture: String,
+    // 0 stays still on screen, 1 moves with the world
+    scroll: f32,
+    // relative to the bigger screen side, has to be at least 1 to cover it
+    #[serde(default = "default_size")]
+    size: f32,
+    day: [f32; 3],
+    night: [f32; 3]
+}
+
+fn default_size() -> f32
+{
+    1.0
+}
+
+impl LayerInfo
+{
+    fn tint(&self, daylight: f32) -> [f32; 3]
+    {
+        std::array::from_fn(|i| self.night[i] * (1.0 - daylight) + self.day[i] * daylight)
+    }
+}
+
+struct Layer
+{
+    info: LayerInfo,
+    // 3x3 grid so the screen stays covered while it wraps around
+    objects: Vec<Object>
+}
+
+fn wrap(x: f32, size: f32) -> f32
+{
+    let half = size / 2.0;
+
+    (x + half).rem_euclid(size) - half
+}
+
+// layers are ordered from the farthest to the closest
+pub struct Background
+{
+    biomes: HashMap<String, Vec<LayerInfo>>,
+    biome: Option<String>,
+    layers: Vec<Layer>,
+    center: Vector3<f32>,
+    size: Vector2<f32>,
+    daylight: f32
+}
+
+impl Background
+{
+    pub fn parse(info: impl AsRef<Path>) -> Self
+    {
+        let info = File::open(overridden(info)).unwrap();
+
+        let biomes = serde_json::from_reader(info).unwrap();
+
+        Self{
+            biomes,
+            biome: None,
+            layers: Vec::new(),
+            center: Vector3::zeros(),
+            size: Vector2::zeros(),
+            daylight: 1.0
+        }
+    }
+
+    fn set_biome(&mut self, create_info: &mut RenderCreateInfo, biome: &str)
+    {
+        if self.biome.as_deref() == Some(biome)
+        {
+            return;
+        }
+
+        self.biome = Some(biome.to_owned());
+
+        let infos = self.biomes.get(biome).cloned().unwrap_or_default();
+
+        self.layers = infos.into_iter().map(|info|
+        {
+            let id = create_info.object_info.partial.assets.lock().texture_id(&info.texture);
+
+            let objects = (0..9).map(|_| create_info.create_object(id, Transform::default())).collect();
+
+            Layer{info, objects}
+        }).collect();
+    }
+
+    pub fn update(
+        &mut self,
+        create_info: &mut RenderCreateInfo,
+        biome: &str,
+        center: Vector3<f32>,
+        size: Vector2<f32>,
+        daylight: f32
+    )
+    {
+        self.set_biome(create_info, biome);
+
+        self.center = center;
+        self.size = size;
+        self.daylight = daylight;
+    }
+
+    fn layer_transforms(&self, layer: &LayerInfo) -> impl Iterator<Item=Transform>
+    {
+        let tile = self.size.max() * layer.size.max(1.0);
+
+        let center = self.center;
+        let shift = (-center.xy() * layer.scroll).map(|x| wrap(x, tile));
+
+        (0..9).map(move |index|
+        {
+            let cell = Vector2::new((index % 3) as f32 - 1.0, (index / 3) as f32 - 1.0);
+            let offset = shift + cell * tile;
+
+            Transform{
+                position: center + Vector3::new(offset.x, offset.y, 0.0),
+                scale: Vector3::new(tile, tile, 1.0),
+                ..Default::default()
+            }
+        })
+    }
+
+    pub fn update_buffers(&mut self, info: &mut UpdateBuffersInfo)
+    {
+        let transforms: Vec<Vec<_>> = self.layers.iter().map(|layer|
+        {
+            self.layer_transforms(&layer.info).collect()
+        }).collect();
+
+        self.layers.iter_mut().zip(transforms).for_each(|(layer, transforms)|
+        {
+            layer.objects.iter_mut().zip(transforms).for_each(|(object, transform)|
+            {
+                object.set_transform(transform);
+                object.update_buffers(info);
+            });
+        });
+    }
+
+    pub fn draw(&self, info: &mut DrawInfo)
+    {
+        self.layers.iter().for_each(|layer|
+        {
+            info.push_constants(WorldInfo::new(layer.info.tint(self.daylight), 1.0));
+
+            layer.objects.iter().for_each(|object| object.draw(info));
+        });
+    }
+}
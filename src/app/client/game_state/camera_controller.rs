@@ -0,0 +1,158 @@
+use nalgebra::{Vector2, Vector3};
+
+use crate::{
+    app::CameraFollow,
+    common::{
+        EaseOut,
+        world::CHUNK_VISUAL_SIZE
+    }
+};
+
+
+const DEFAULT_ZOOM: f32 = 3.0;
+
+const ZOOM_DECAY: f32 = 12.0;
+const SCROLL_STEP: f32 = 0.1;
+
+// fraction of the mouse offset the camera leans towards
+const LOOK_AHEAD: f32 = 0.2;
+
+// fraction of the screen the player can move in before the camera follows
+const DEAD_ZONE: f32 = 0.15;
+
+#[derive(Debug, Clone, Copy)]
+pub struct CameraSettings
+{
+    pub follow: CameraFollow,
+    // smaller scale means more zoomed in
+    pub min_scale: f32,
+    pub max_scale: f32,
+    // multiplier for screen shake, 0 turns it off
+    pub shake: f32,
+    pub hit_stop: bool
+}
+
+impl Default for CameraSettings
+{
+    fn default() -> Self
+    {
+        Self{
+            follow: CameraFollow::LookAhead,
+            min_scale: 0.2,
+            max_scale: DEFAULT_ZOOM,
+            shake: 1.0,
+            hit_stop: true
+        }
+    }
+}
+
+pub struct CameraController
+{
+    settings: CameraSettings,
+    scale: f32,
+    target_scale: f32
+}
+
+impl CameraController
+{
+    pub fn new(settings: CameraSettings) -> Self
+    {
+        let scale = DEFAULT_ZOOM.clamp(settings.min_scale, settings.max_scale);
+
+        Self{
+            settings,
+            scale,
+            target_scale: scale
+        }
+    }
+
+    pub fn scale(&self) -> f32
+    {
+        self.scale
+    }
+
+    pub fn zoom(&mut self, factor: f32, limited: bool)
+    {
+        self.target_scale *= factor;
+
+        if limited
+        {
+            self.target_scale = self.target_scale.clamp(self.settings.min_scale, self.settings.max_scale);
+        }
+    }
+
+    pub fn scroll(&mut self, amount: f32, limited: bool)
+    {
+        self.zoom(1.0 - amount * SCROLL_STEP, limited);
+    }
+
+    pub fn reset(&mut self)
+    {
+        self.target_scale = DEFAULT_ZOOM.clamp(self.settings.min_scale, self.settings.max_scale);
+    }
+
+    // returns whether the scale changed
+    pub fn update(&mut self, dt: f32) -> bool
+    {
+        if self.scale == self.target_scale
+        {
+            return false;
+        }
+
+        self.scale = self.scale.ease_out(self.target_scale, ZOOM_DECAY, dt);
+
+        if (self.scale - self.target_scale).abs() < 0.001
+        {
+            self.scale = self.target_scale;
+        }
+
+        true
+    }
+
+    pub fn follow_position(
+        &self,
+        current: Vector3<f32>,
+        player: Vector3<f32>,
+        mouse: Vector3<f32>,
+        size: Vector2<f32>
+    ) -> Vector3<f32>
+    {
+        match self.settings.follow
+        {
+            CameraFollow::Locked => player,
+            CameraFollow::LookAhead =>
+            {
+                if mouse.magnitude() > CHUNK_VISUAL_SIZE * 2.0
+                {
+                    player
+                } else
+                {
+                    player + mouse * LOOK_AHEAD
+                }
+            },
+            CameraFollow::DeadZone =>
+            {
+                let zone = size * DEAD_ZONE;
+
+                let follow_axis = |current: f32, player: f32, zone: f32|
+                {
+                    let distance = player - current;
+
+                    if distance.abs() > zone
+                    {
+                        player - zone * distance.signum()
+                    } else
+                    {
+                        current
+                    }
+                };
+
+                Vector3::new(
+                    follow_axis(current.x, player.x, zone.x),
+                    follow_axis(current.y, player.y, zone.y),
+                    player.z
+                )
+            }
+        }
+    }
+}
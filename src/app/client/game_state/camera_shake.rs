@@ -0,0 +1,109 @@
+use nalgebra::{Vector2, Vector3};
+
+use crate::common::Shake;
+
+use super::CameraSettings;
+
+
+// trauma lost per second
+const TRAUMA_DECAY: f32 = 1.5;
+
+// fraction of the smaller screen side at full trauma
+const MAX_OFFSET: f32 = 0.03;
+
+// how fast the game runs during a hit stop
+const HIT_STOP_SCALE: f32 = 0.05;
+const MAX_HIT_STOP: f32 = 0.2;
+
+pub struct CameraShake
+{
+    // multiplier, 0 turns it off
+    strength: f32,
+    hit_stop_enabled: bool,
+    trauma: f32,
+    hit_stop: f32,
+    time: f32
+}
+
+impl CameraShake
+{
+    pub fn new(settings: &CameraSettings) -> Self
+    {
+        Self{
+            strength: settings.shake,
+            hit_stop_enabled: settings.hit_stop,
+            trauma: 0.0,
+            hit_stop: 0.0,
+            time: 0.0
+        }
+    }
+
+    pub fn add_trauma(&mut self, amount: f32)
+    {
+        self.trauma = (self.trauma + amount * self.strength).min(1.0);
+    }
+
+    pub fn hit_stop(&mut self, duration: f32)
+    {
+        if !self.hit_stop_enabled
+        {
+            return;
+        }
+
+        self.hit_stop = self.hit_stop.max(duration).min(MAX_HIT_STOP);
+    }
+
+    // listener is wherever the camera is
+    pub fn add(&mut self, shake: &Shake, listener: Vector3<f32>)
+    {
+        let falloff = shake.falloff(listener);
+
+        if falloff <= 0.0
+        {
+            return;
+        }
+
+        self.add_trauma(shake.trauma * falloff);
+
+        if shake.hit_stop > 0.0
+        {
+            self.hit_stop(shake.hit_stop * falloff);
+        }
+    }
+
+    // multiplier for the simulation timestep
+    pub fn time_scale(&self) -> f32
+    {
+        if self.hit_stop > 0.0
+        {
+            HIT_STOP_SCALE
+        } else
+        {
+            1.0
+        }
+    }
+
+    pub fn update(&mut self, dt: f32)
+    {
+        self.time += dt;
+
+        self.trauma = (self.trauma - TRAUMA_DECAY * dt).max(0.0);
+        self.hit_stop = (self.hit_stop - dt).max(0.0);
+    }
+
+    pub fn offset(&self, size: Vector2<f32>) -> Vector3<f32>
+    {
+        if self.trauma <= 0.0
+        {
+            return Vector3::zeros();
+        }
+
+        // squared so small hits barely move it
+        let amount = self.trauma * self.trauma * size.min() * MAX_OFFSET;
+
+        // a few sines at odd frequencies look random enough without jittering every frame
+        let wave = |a: f32, b: f32| (self.time * a).sin() * 0.6 + (self.time * b).sin() * 0.4;
+
+        Vector3::new(wave(37.0, 59.0), wave(43.0, 71.0), 0.0) * amount
+    }
+}
@@ -0,0 +1,139 @@
+use std::{
+    f32,
+    fs::File,
+    path::Path,
+    collections::HashMap
+};
+
+use serde::Deserialize;
+
+use crate::common::{
+    overrides::overridden,
+    EaseOut
+};
+
+
+// seconds for a full day and night cycle
+const DAY_LENGTH: f32 = 1200.0;
+
+const INTERIOR_DECAY: f32 = 3.0;
+
+// 2x2x2 lut, corner index is red + green * 2 + blue * 4
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(transparent)]
+pub struct Lut([[f32; 3]; 8]);
+
+impl Lut
+{
+    fn lerp(&self, other: &Self, amount: f32) -> Self
+    {
+        Self(std::array::from_fn(|i|
+        {
+            std::array::from_fn(|c| self.0[i][c] * (1.0 - amount) + other.0[i][c] * amount)
+        }))
+    }
+
+    // every corner channel as a byte, 4 to a u32 so the whole lut fits in the post push constants
+    pub fn packed(&self) -> [u32; 6]
+    {
+        let values: Vec<u8> = self.0.iter().flatten().map(|value|
+        {
+            (value.clamp(0.0, 1.0) * 255.0).round() as u8
+        }).collect();
+
+        std::array::from_fn(|i|
+        {
+            u32::from_le_bytes(std::array::from_fn(|byte| values[i * 4 + byte]))
+        })
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ZoneLuts
+{
+    day: String,
+    night: String
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GradingInfo
+{
+    luts: HashMap<String, Lut>,
+    exterior: ZoneLuts,
+    interior: ZoneLuts
+}
+
+pub struct ColorGrading
+{
+    luts: HashMap<String, Lut>,
+    exterior: ZoneLuts,
+    interior: ZoneLuts,
+    time: f32,
+    inside: f32
+}
+
+impl ColorGrading
+{
+    pub fn parse(info: impl AsRef<Path>) -> Self
+    {
+        let info = File::open(overridden(info)).unwrap();
+
+        let GradingInfo{luts, exterior, interior} = serde_json::from_reader(info).unwrap();
+
+        [&exterior, &interior].into_iter().flat_map(|x| [&x.day, &x.night]).for_each(|name|
+        {
+            if !luts.contains_key(name)
+            {
+                panic!("lut named {name} doesnt exist");
+            }
+        });
+
+        Self{
+            luts,
+            exterior,
+            interior,
+            // start at noon
+            time: DAY_LENGTH * 0.5,
+            inside: 0.0
+        }
+    }
+
+    // 0 at midnight, 1 at noon
+    pub fn daylight(&self) -> f32
+    {
+        0.5 - (self.time / DAY_LENGTH * f32::consts::PI * 2.0).cos() * 0.5
+    }
+
+    pub fn update(&mut self, interior: bool, dt: f32)
+    {
+        self.time = (self.time + dt) % DAY_LENGTH;
+
+        let target = if interior
+        {
+            1.0
+        } else
+        {
+            0.0
+        };
+
+        self.inside = self.inside.ease_out(target, INTERIOR_DECAY, dt);
+    }
+
+    fn zone_lut(&self, zone: &ZoneLuts) -> Lut
+    {
+        let night = &self.luts[&zone.night];
+        let day = &self.luts[&zone.day];
+
+        night.lerp(day, self.daylight())
+    }
+
+    pub fn lut(&self) -> Lut
+    {
+        let exterior = self.zone_lut(&self.exterior);
+        let interior = self.zone_lut(&self.interior);
+
+        exterior.lerp(&interior, self.inside)
+    }
+}
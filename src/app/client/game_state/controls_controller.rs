@@ -6,6 +6,8 @@ use std::{
 
 use yanyaengine::{ElementState, PhysicalKey, KeyCode, KeyCodeNamed, MouseButton};
 
+use gilrs::{Button, Axis};
+
 use strum::EnumCount;
 
 use clipboard::{ClipboardProvider, ClipboardContext};
@@ -26,13 +28,21 @@ pub enum Control
     Jump,
     Crawl,
     Sprint,
+    Crouch,
     Poke,
+    Block,
+    Dodge,
+    LockOn,
+    CycleLock,
     Shoot,
     Throw,
     Inventory,
+    Perks,
     ZoomIn,
     ZoomOut,
-    ZoomReset
+    ZoomReset,
+    Pause,
+    FastForward
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -76,11 +86,21 @@ impl ControlState
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputSource
+{
+    KeyboardMouse,
+    Gamepad
+}
+
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
 pub enum KeyMapping
 {
     Keyboard(KeyCode),
-    Mouse(MouseButton)
+    Mouse(MouseButton),
+    Gamepad(Button),
+    // pushed far enough in the positive or negative direction of the axis
+    Stick(Axis, bool)
 }
 
 impl Display for KeyMapping
@@ -102,6 +122,44 @@ impl Display for KeyMapping
                     MouseButton::Middle => "Middle mouse",
                     _ => "unknown"
                 })
+            },
+            Self::Gamepad(button) =>
+            {
+                write!(f, "{}", match button
+                {
+                    Button::South => "A",
+                    Button::East => "B",
+                    Button::West => "X",
+                    Button::North => "Y",
+                    Button::LeftTrigger => "LB",
+                    Button::LeftTrigger2 => "LT",
+                    Button::RightTrigger => "RB",
+                    Button::RightTrigger2 => "RT",
+                    Button::LeftThumb => "LS",
+                    Button::RightThumb => "RS",
+                    Button::Select => "Select",
+                    Button::Start => "Start",
+                    Button::DPadUp => "Dpad up",
+                    Button::DPadDown => "Dpad down",
+                    Button::DPadLeft => "Dpad left",
+                    Button::DPadRight => "Dpad right",
+                    _ => "unknown"
+                })
+            },
+            Self::Stick(axis, positive) =>
+            {
+                write!(f, "{}", match (axis, positive)
+                {
+                    (Axis::LeftStickX, false) => "Left stick left",
+                    (Axis::LeftStickX, true) => "Left stick right",
+                    (Axis::LeftStickY, false) => "Left stick down",
+                    (Axis::LeftStickY, true) => "Left stick up",
+                    (Axis::RightStickX, false) => "Right stick left",
+                    (Axis::RightStickX, true) => "Right stick right",
+                    (Axis::RightStickY, false) => "Right stick down",
+                    (Axis::RightStickY, true) => "Right stick up",
+                    _ => "unknown"
+                })
             }
         }
     }
@@ -109,6 +167,15 @@ impl Display for KeyMapping
 
 impl KeyMapping
 {
+    pub fn source(&self) -> InputSource
+    {
+        match self
+        {
+            Self::Keyboard(_) | Self::Mouse(_) => InputSource::KeyboardMouse,
+            Self::Gamepad(_) | Self::Stick(..) => InputSource::Gamepad
+        }
+    }
+
     pub fn from_control(value: yanyaengine::Control) -> Option<Self>
     {
         match value
@@ -128,6 +195,9 @@ pub struct ControlsController
 {
     clipboard: Option<ClipboardContext>,
     key_mapping: BiMap<KeyMapping, Control>,
+    gamepad_mapping: BiMap<KeyMapping, Control>,
+    // whatever was used last, prompts show the keys for it
+    source: InputSource,
     keys: [ControlState; Control::COUNT],
     changed: Vec<(ControlState, Control)>
 }
@@ -149,13 +219,44 @@ impl ControlsController
             (KeyMapping::Keyboard(KeyCode::Space), Control::Jump),
             (KeyMapping::Keyboard(KeyCode::ControlLeft), Control::Crawl),
             (KeyMapping::Keyboard(KeyCode::ShiftLeft), Control::Sprint),
+            (KeyMapping::Keyboard(KeyCode::KeyX), Control::Crouch),
             (KeyMapping::Keyboard(KeyCode::KeyF), Control::Shoot),
             (KeyMapping::Keyboard(KeyCode::KeyG), Control::Poke),
+            (KeyMapping::Keyboard(KeyCode::KeyB), Control::Block),
+            (KeyMapping::Keyboard(KeyCode::KeyQ), Control::Dodge),
+            (KeyMapping::Keyboard(KeyCode::KeyR), Control::LockOn),
+            (KeyMapping::Keyboard(KeyCode::Tab), Control::CycleLock),
             (KeyMapping::Keyboard(KeyCode::KeyI), Control::Inventory),
+            (KeyMapping::Keyboard(KeyCode::KeyP), Control::Perks),
             (KeyMapping::Keyboard(KeyCode::KeyT), Control::Throw),
             (KeyMapping::Keyboard(KeyCode::Equal), Control::ZoomIn),
             (KeyMapping::Keyboard(KeyCode::Minus), Control::ZoomOut),
-            (KeyMapping::Keyboard(KeyCode::Digit0), Control::ZoomReset)
+            (KeyMapping::Keyboard(KeyCode::Digit0), Control::ZoomReset),
+            (KeyMapping::Keyboard(KeyCode::Escape), Control::Pause),
+            (KeyMapping::Keyboard(KeyCode::KeyZ), Control::FastForward)
+        ].into_iter().collect();
+
+        let gamepad_mapping = [
+            (KeyMapping::Stick(Axis::LeftStickX, true), Control::MoveRight),
+            (KeyMapping::Stick(Axis::LeftStickX, false), Control::MoveLeft),
+            (KeyMapping::Stick(Axis::LeftStickY, false), Control::MoveDown),
+            (KeyMapping::Stick(Axis::LeftStickY, true), Control::MoveUp),
+            (KeyMapping::Gamepad(Button::RightTrigger2), Control::MainAction),
+            (KeyMapping::Gamepad(Button::LeftTrigger2), Control::SecondaryAction),
+            (KeyMapping::Gamepad(Button::West), Control::Interact),
+            (KeyMapping::Gamepad(Button::South), Control::Jump),
+            (KeyMapping::Gamepad(Button::DPadDown), Control::Crawl),
+            (KeyMapping::Gamepad(Button::LeftThumb), Control::Sprint),
+            (KeyMapping::Gamepad(Button::DPadLeft), Control::Shoot),
+            (KeyMapping::Gamepad(Button::RightTrigger), Control::Poke),
+            (KeyMapping::Gamepad(Button::LeftTrigger), Control::Block),
+            (KeyMapping::Gamepad(Button::East), Control::Dodge),
+            (KeyMapping::Gamepad(Button::RightThumb), Control::LockOn),
+            (KeyMapping::Gamepad(Button::DPadRight), Control::CycleLock),
+            (KeyMapping::Gamepad(Button::North), Control::Inventory),
+            (KeyMapping::Gamepad(Button::Select), Control::Perks),
+            (KeyMapping::Gamepad(Button::DPadUp), Control::Throw),
+            (KeyMapping::Gamepad(Button::Start), Control::Pause)
         ].into_iter().collect();
 
         let clipboard = match ClipboardProvider::new()
@@ -172,6 +273,8 @@ impl ControlsController
         Self{
             clipboard,
             key_mapping,
+            gamepad_mapping,
+            source: InputSource::KeyboardMouse,
             keys: [ControlState::Released; Control::COUNT],
             changed: Vec::new()
         }
@@ -212,25 +315,48 @@ impl ControlsController
         
         if let Some(this_key) = this_key
         {
-            let matched = self.key_mapping.get(&this_key);
+            return self.handle_key(state, this_key);
+        }
 
-            if let Some(matched) = matched
-            {
-                self.keys[*matched as usize] = state;
+        None
+    }
 
-                let pair = (state, *matched);
-                self.changed.push(pair);
+    pub fn handle_key(&mut self, state: ControlState, key: KeyMapping) -> Option<(ControlState, Control)>
+    {
+        self.source = key.source();
 
-                return Some(pair);
-            }
+        let matched = match self.source
+        {
+            InputSource::KeyboardMouse => self.key_mapping.get(&key),
+            InputSource::Gamepad => self.gamepad_mapping.get(&key)
+        };
+
+        if let Some(matched) = matched
+        {
+            self.keys[*matched as usize] = state;
+
+            let pair = (state, *matched);
+            self.changed.push(pair);
+
+            return Some(pair);
         }
 
         None
     }
 
+    pub fn set_source(&mut self, source: InputSource)
+    {
+        self.source = source;
+    }
+
     pub fn key_for(&self, control: &Control) -> Option<&KeyMapping>
     {
-        self.key_mapping.get_back(control)
+        match self.source
+        {
+            InputSource::KeyboardMouse => self.key_mapping.get_back(control),
+            InputSource::Gamepad => self.gamepad_mapping.get_back(control)
+                .or_else(|| self.key_mapping.get_back(control))
+        }
     }
 
     pub fn changed_this_frame(&mut self) -> Vec<(ControlState, Control)>
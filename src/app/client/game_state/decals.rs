@@ -0,0 +1,184 @@
+use std::{
+    f32,
+    collections::{HashMap, VecDeque}
+};
+
+use nalgebra::Vector3;
+
+use yanyaengine::{
+    Object,
+    Transform,
+    TransformContainer,
+    game_object::*
+};
+
+use crate::{
+    client::{RenderCreateInfo, VisibilityChecker},
+    common::{
+        render_info::*,
+        Decal,
+        DecalKind,
+        Pos3,
+        world::{CHUNK_VISUAL_SIZE, TILE_SIZE, GlobalPos}
+    }
+};
+
+
+// oldest ones get removed first when a chunk goes over this
+const MAX_PER_CHUNK: usize = 64;
+
+const FADE_TIME: f32 = 5.0;
+
+fn floor_of(z: f32) -> i32
+{
+    (z / TILE_SIZE).floor() as i32
+}
+
+struct ClientDecal
+{
+    decal: Decal,
+    age: f32,
+    object: Object
+}
+
+impl ClientDecal
+{
+    fn new(create_info: &mut RenderCreateInfo, decal: Decal) -> Self
+    {
+        let id = create_info.object_info.partial.assets.lock().texture_id(decal.kind.texture());
+
+        let object = create_info.create_object(id, Self::transform(&decal, 1.0));
+
+        Self{decal, age: 0.0, object}
+    }
+
+    fn transform(decal: &Decal, fade: f32) -> Transform
+    {
+        let scale = decal.scale * fade;
+
+        Transform{
+            position: decal.position,
+            rotation: decal.rotation,
+            scale: Vector3::new(scale, scale, 1.0),
+            ..Default::default()
+        }
+    }
+
+    fn fade(&self) -> f32
+    {
+        let past = self.age - self.decal.kind.lifetime();
+
+        (1.0 - past / FADE_TIME).clamp(0.0, 1.0)
+    }
+
+    fn expired(&self) -> bool
+    {
+        self.age > self.decal.kind.lifetime() + FADE_TIME
+    }
+
+    // blood dries darker
+    fn mix(&self) -> Option<MixColor>
+    {
+        if self.decal.kind != DecalKind::Blood
+        {
+            return None;
+        }
+
+        let dried = (self.age / self.decal.kind.lifetime()).min(1.0);
+
+        Some(MixColor{color: [0.2, 0.05, 0.03], amount: dried * 0.5, keep_transparency: true})
+    }
+}
+
+pub struct Decals
+{
+    chunks: HashMap<GlobalPos, VecDeque<ClientDecal>>,
+    visible: Vec<GlobalPos>
+}
+
+impl Decals
+{
+    pub fn new() -> Self
+    {
+        Self{
+            chunks: HashMap::new(),
+            visible: Vec::new()
+        }
+    }
+
+    pub fn push(&mut self, create_info: &mut RenderCreateInfo, decal: Decal)
+    {
+        let chunk = Pos3::from(decal.position).rounded();
+
+        let decals = self.chunks.entry(chunk).or_default();
+
+        if decals.len() >= MAX_PER_CHUNK
+        {
+            decals.pop_front();
+        }
+
+        decals.push_back(ClientDecal::new(create_info, decal));
+    }
+
+    pub fn update(&mut self, dt: f32)
+    {
+        self.chunks.retain(|_, decals|
+        {
+            decals.iter_mut().for_each(|decal| decal.age += dt);
+            decals.retain(|decal| !decal.expired());
+
+            !decals.is_empty()
+        });
+    }
+
+    pub fn update_buffers(
+        &mut self,
+        visibility: &VisibilityChecker,
+        info: &mut UpdateBuffersInfo,
+        height: f32
+    )
+    {
+        let floor = floor_of(height);
+
+        // a sphere around the whole chunk
+        let chunk_radius = CHUNK_VISUAL_SIZE * 3.0_f32.sqrt() / 2.0;
+
+        self.visible = self.chunks.keys().copied().filter(|chunk|
+        {
+            let center = chunk.0.map(|x| (x as f32 + 0.5) * CHUNK_VISUAL_SIZE);
+
+            visibility.visible_sphere_radius(Vector3::from(center), chunk_radius)
+        }).collect();
+
+        self.visible.iter().for_each(|chunk|
+        {
+            self.chunks.get_mut(chunk).unwrap().iter_mut().for_each(|decal|
+            {
+                if floor_of(decal.decal.position.z) != floor
+                {
+                    return;
+                }
+
+                decal.object.set_transform(ClientDecal::transform(&decal.decal, decal.fade()));
+                decal.object.update_buffers(info);
+            });
+        });
+    }
+
+    pub fn draw(&self, info: &mut DrawInfo, height: f32, animation: f32)
+    {
+        let floor = floor_of(height);
+
+        self.visible.iter().filter_map(|chunk| self.chunks.get(chunk)).flatten().for_each(|decal|
+        {
+            if floor_of(decal.decal.position.z) != floor
+            {
+                return;
+            }
+
+            info.push_constants(OutlinedInfo::new(decal.mix(), 0.0, animation));
+
+            decal.object.draw(info);
+        });
+    }
+}
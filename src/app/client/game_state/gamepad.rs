@@ -0,0 +1,178 @@
+use gilrs::{
+    Gilrs,
+    Axis,
+    EventType,
+    GamepadId,
+    ff::{Effect, EffectBuilder, BaseEffect, BaseEffectType, Replay, Ticks}
+};
+
+use nalgebra::Vector2;
+
+use crate::common::some_or_return;
+
+use super::controls_controller::{ControlState, KeyMapping};
+
+
+// how far a stick has to be pushed to count as a press, has to come back past the release point to let go
+const STICK_PRESS: f32 = 0.5;
+const STICK_RELEASE: f32 = 0.3;
+
+// smaller right stick movements dont move the aim so it doesnt jitter around
+const AIM_DEADZONE: f32 = 0.25;
+
+// how far from the player the aim goes with the stick pushed all the way, in screen sizes
+const AIM_DISTANCE: f32 = 0.3;
+
+const RUMBLE_DURATION: u32 = 200;
+
+// health fraction lost that rumbles at full strength
+const RUMBLE_FULL_DAMAGE: f32 = 0.2;
+
+pub struct Gamepad
+{
+    gilrs: Option<Gilrs>,
+    active: Option<GamepadId>,
+    // up, down, left, right on the left stick
+    stick: [bool; 4],
+    // stops playing when dropped
+    rumble: Option<Effect>
+}
+
+impl Gamepad
+{
+    pub fn new() -> Self
+    {
+        // no gamepad support shouldnt stop the game from running
+        let gilrs = Gilrs::new().map_err(|err|
+        {
+            eprintln!("error initializing gamepads: {err}")
+        }).ok();
+
+        Self{
+            gilrs,
+            active: None,
+            stick: [false; 4],
+            rumble: None
+        }
+    }
+
+    fn stick_mapping(index: usize) -> KeyMapping
+    {
+        match index
+        {
+            0 => KeyMapping::Stick(Axis::LeftStickY, true),
+            1 => KeyMapping::Stick(Axis::LeftStickY, false),
+            2 => KeyMapping::Stick(Axis::LeftStickX, false),
+            3 => KeyMapping::Stick(Axis::LeftStickX, true),
+            x => unreachable!("{x}")
+        }
+    }
+
+    fn on_stick(&mut self, changes: &mut Vec<(ControlState, KeyMapping)>, axis: Axis, value: f32)
+    {
+        let indices = match axis
+        {
+            Axis::LeftStickY => [(0, value), (1, -value)],
+            Axis::LeftStickX => [(2, -value), (3, value)],
+            _ => return
+        };
+
+        for (index, value) in indices
+        {
+            let pressed = if self.stick[index] { value > STICK_RELEASE } else { value > STICK_PRESS };
+
+            if pressed != self.stick[index]
+            {
+                self.stick[index] = pressed;
+
+                let state = if pressed { ControlState::Pressed } else { ControlState::Released };
+                changes.push((state, Self::stick_mapping(index)));
+            }
+        }
+    }
+
+    // everything pressed or released since last time, the gamepad that was used last becomes the active one
+    pub fn poll(&mut self) -> Vec<(ControlState, KeyMapping)>
+    {
+        let mut changes = Vec::new();
+
+        while let Some(event) = self.gilrs.as_mut().and_then(|x| x.next_event())
+        {
+            match event.event
+            {
+                EventType::ButtonPressed(button, _) =>
+                {
+                    changes.push((ControlState::Pressed, KeyMapping::Gamepad(button)));
+                },
+                EventType::ButtonReleased(button, _) =>
+                {
+                    changes.push((ControlState::Released, KeyMapping::Gamepad(button)));
+                },
+                EventType::AxisChanged(axis, value, _) =>
+                {
+                    self.on_stick(&mut changes, axis, value);
+                },
+                EventType::Disconnected =>
+                {
+                    if self.active == Some(event.id)
+                    {
+                        self.active = None;
+                        self.rumble = None;
+
+                        // let go of everything the stick was holding
+                        self.on_stick(&mut changes, Axis::LeftStickY, 0.0);
+                        self.on_stick(&mut changes, Axis::LeftStickX, 0.0);
+                    }
+
+                    continue;
+                },
+                _ => continue
+            }
+
+            self.active = Some(event.id);
+        }
+
+        changes
+    }
+
+    // offset from the player on the screen with y pointing down
+    pub fn aim(&self) -> Option<Vector2<f32>>
+    {
+        let gamepad = self.gilrs.as_ref()?.gamepad(self.active?);
+
+        let aim = Vector2::new(gamepad.value(Axis::RightStickX), -gamepad.value(Axis::RightStickY));
+
+        (aim.magnitude() > AIM_DEADZONE).then_some(aim * AIM_DISTANCE)
+    }
+
+    pub fn rumble(&mut self, damage: f32)
+    {
+        let id = some_or_return!(self.active);
+        let gilrs = some_or_return!(self.gilrs.as_mut());
+
+        if !gilrs.gamepad(id).is_ff_supported()
+        {
+            return;
+        }
+
+        let strength = (damage / RUMBLE_FULL_DAMAGE).clamp(0.0, 1.0);
+
+        let effect = BaseEffect{
+            kind: BaseEffectType::Strong{magnitude: (strength * u16::MAX as f32) as u16},
+            scheduling: Replay{play_for: Ticks::from_ms(RUMBLE_DURATION), ..Default::default()},
+            ..Default::default()
+        };
+
+        let result = EffectBuilder::new()
+            .add_effect(effect)
+            .gamepads(&[id])
+            .finish(gilrs)
+            .and_then(|effect| effect.play().map(|_| effect));
+
+        match result
+        {
+            Ok(effect) => self.rumble = Some(effect),
+            Err(err) => eprintln!("error playing rumble: {err}")
+        }
+    }
+}
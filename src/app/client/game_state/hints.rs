@@ -0,0 +1,81 @@
+use super::controls_controller::{Control, ControlsController};
+
+
+// shown one after another until each one gets done
+pub const TUTORIAL: [Hint; 4] = [Hint::Move, Hint::Attack, Hint::Interact, Hint::Inventory];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hint
+{
+    Move,
+    Attack,
+    Interact,
+    Inventory,
+    FirstLoot,
+    FirstFight,
+    LowStamina
+}
+
+impl Hint
+{
+    // what gets saved in the player so it only shows up once
+    pub fn name(self) -> &'static str
+    {
+        match self
+        {
+            Self::Move => "tutorial_move",
+            Self::Attack => "tutorial_attack",
+            Self::Interact => "tutorial_interact",
+            Self::Inventory => "tutorial_inventory",
+            Self::FirstLoot => "first_loot",
+            Self::FirstFight => "first_fight",
+            Self::LowStamina => "low_stamina"
+        }
+    }
+
+    // tutorial steps are done once the player presses any of these
+    pub fn completed_by(self, control: Control) -> bool
+    {
+        match self
+        {
+            Self::Move => matches!(control, Control::MoveUp | Control::MoveDown | Control::MoveLeft | Control::MoveRight),
+            Self::Attack => control == Control::MainAction,
+            Self::Interact => control == Control::Interact,
+            Self::Inventory => control == Control::Inventory,
+            _ => false
+        }
+    }
+
+    pub fn text(self, controls: &ControlsController) -> String
+    {
+        let key = |control: Control|
+        {
+            controls.key_for(&control).map(ToString::to_string).unwrap_or_else(|| "unassigned".to_owned())
+        };
+
+        match self
+        {
+            Self::Move =>
+            {
+                format!(
+                    "move around with {} {} {} {}",
+                    key(Control::MoveUp),
+                    key(Control::MoveLeft),
+                    key(Control::MoveDown),
+                    key(Control::MoveRight)
+                )
+            },
+            Self::Attack => format!("press {} to attack", key(Control::MainAction)),
+            Self::Interact => format!("press {} to use stairs and doors", key(Control::Interact)),
+            Self::Inventory => format!("press {} to open your inventory", key(Control::Inventory)),
+            Self::FirstLoot => "click on items to take them, bodies and containers can be looted".to_owned(),
+            Self::FirstFight => format!(
+                "press {} to lock on, {} to block and {} to dodge",
+                key(Control::LockOn),
+                key(Control::Block),
+                key(Control::Dodge)
+            ),
+            Self::LowStamina => "low on stamina, stop sprinting and attacking to catch your breath".to_owned()
+        }
+    }
+}
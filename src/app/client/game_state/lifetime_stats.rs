@@ -0,0 +1,239 @@
+use std::{
+    fs,
+    path::PathBuf
+};
+
+use serde::{Serialize, Deserialize};
+
+use strum::{IntoEnumIterator, EnumIter};
+
+use nalgebra::Vector3;
+
+use crate::common::{
+    save_file,
+    world::TILE_SIZE
+};
+
+
+const PROFILES_PATH: &str = "profiles";
+
+const SAVE_INTERVAL: f32 = 60.0;
+
+// anything moving further than this in one frame got teleported (like going up stairs)
+const MAX_FRAME_DISTANCE: f32 = TILE_SIZE * 2.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter)]
+pub enum Achievement
+{
+    FirstBlood,
+    Hunter,
+    Butcher,
+    Wanderer,
+    Explorer,
+    FirstDeath,
+    Scavenger,
+    Survivor
+}
+
+impl Achievement
+{
+    pub fn name(self) -> &'static str
+    {
+        match self
+        {
+            Self::FirstBlood => "first_blood",
+            Self::Hunter => "hunter",
+            Self::Butcher => "butcher",
+            Self::Wanderer => "wanderer",
+            Self::Explorer => "explorer",
+            Self::FirstDeath => "first_death",
+            Self::Scavenger => "scavenger",
+            Self::Survivor => "survivor"
+        }
+    }
+
+    pub fn title(self) -> &'static str
+    {
+        match self
+        {
+            Self::FirstBlood => "first blood",
+            Self::Hunter => "hunter",
+            Self::Butcher => "butcher",
+            Self::Wanderer => "wanderer",
+            Self::Explorer => "explorer",
+            Self::FirstDeath => "it happens",
+            Self::Scavenger => "scavenger",
+            Self::Survivor => "survivor"
+        }
+    }
+
+    fn unlocked(self, stats: &Stats) -> bool
+    {
+        match self
+        {
+            Self::FirstBlood => stats.kills >= 1,
+            Self::Hunter => stats.kills >= 25,
+            Self::Butcher => stats.kills >= 100,
+            Self::Wanderer => stats.distance >= 1000.0,
+            Self::Explorer => stats.distance >= 10000.0,
+            Self::FirstDeath => stats.deaths >= 1,
+            Self::Scavenger => stats.looted >= 50,
+            Self::Survivor => stats.play_time >= 60.0 * 60.0
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct Stats
+{
+    kills: u32,
+    deaths: u32,
+    // in tiles
+    distance: f32,
+    looted: u32,
+    // in seconds
+    play_time: f64,
+    achievements: Vec<String>
+}
+
+// kept across every world for the same player name
+pub struct LifetimeStats
+{
+    path: PathBuf,
+    stats: Stats,
+    save_timer: f32,
+    previous_position: Option<Vector3<f32>>,
+    previous_alive: Option<bool>
+}
+
+impl Drop for LifetimeStats
+{
+    fn drop(&mut self)
+    {
+        self.save();
+    }
+}
+
+impl LifetimeStats
+{
+    pub fn load(profile: &str) -> Self
+    {
+        let path = PathBuf::from(PROFILES_PATH).join(profile).join("stats");
+
+        let stats = save_file::read(&path).and_then(|bytes|
+        {
+            bytes.map(|bytes| save_file::deserialize(&bytes)).transpose()
+        }).unwrap_or_else(|err|
+        {
+            eprintln!("error loading stats for {profile}: {err}");
+
+            None
+        }).unwrap_or_default();
+
+        Self{
+            path,
+            stats,
+            save_timer: SAVE_INTERVAL,
+            previous_position: None,
+            previous_alive: None
+        }
+    }
+
+    pub fn save(&mut self)
+    {
+        self.save_timer = SAVE_INTERVAL;
+
+        let result = fs::create_dir_all(self.path.parent().unwrap()).map_err(|err| err.to_string())
+            .and_then(|_|
+            {
+                let bytes = save_file::serialize(&self.stats).map_err(|err| err.to_string())?;
+
+                save_file::write(&self.path, &bytes).map_err(|err| err.to_string())
+            });
+
+        if let Err(err) = result
+        {
+            eprintln!("error saving stats: {err}");
+        }
+    }
+
+    pub fn add_kills(&mut self, amount: u32)
+    {
+        self.stats.kills += amount;
+    }
+
+    pub fn add_looted(&mut self)
+    {
+        self.stats.looted += 1;
+    }
+
+    // returns whatever got unlocked this update
+    pub fn update(&mut self, position: Option<Vector3<f32>>, alive: bool, dt: f32) -> Vec<Achievement>
+    {
+        self.stats.play_time += dt as f64;
+
+        if let (Some(previous), Some(current)) = (self.previous_position, position)
+        {
+            let distance = previous.xy().metric_distance(&current.xy());
+
+            if previous.z == current.z && distance < MAX_FRAME_DISTANCE
+            {
+                self.stats.distance += distance / TILE_SIZE;
+            }
+        }
+
+        self.previous_position = position;
+
+        if self.previous_alive == Some(true) && !alive
+        {
+            self.stats.deaths += 1;
+        }
+
+        self.previous_alive = Some(alive);
+
+        let unlocked: Vec<_> = Achievement::iter().filter(|achievement|
+        {
+            !self.stats.achievements.iter().any(|x| x == achievement.name()) && achievement.unlocked(&self.stats)
+        }).collect();
+
+        self.stats.achievements.extend(unlocked.iter().map(|x| x.name().to_owned()));
+
+        self.save_timer -= dt;
+        if !unlocked.is_empty() || self.save_timer <= 0.0
+        {
+            self.save();
+        }
+
+        unlocked
+    }
+
+    pub fn description(&self) -> String
+    {
+        let stats = &self.stats;
+
+        let achievements = Achievement::iter().filter(|achievement|
+        {
+            stats.achievements.iter().any(|x| x == achievement.name())
+        }).map(Achievement::title).collect::<Vec<_>>();
+
+        let achievements = if achievements.is_empty()
+        {
+            "none".to_owned()
+        } else
+        {
+            achievements.join(", ")
+        };
+
+        format!(
+            "lifetime\nkills: {}\ndeaths: {}\ndistance: {:.0} tiles\nlooted: {}\nplayed: {:.1} hours\nachievements ({}/{}): {achievements}",
+            stats.kills,
+            stats.deaths,
+            stats.distance,
+            stats.looted,
+            stats.play_time / (60.0 * 60.0),
+            stats.achievements.len(),
+            Achievement::iter().count()
+        )
+    }
+}
@@ -0,0 +1,284 @@
+use std::{
+    mem,
+    sync::Arc,
+    collections::HashMap
+};
+
+use parking_lot::RwLock;
+
+use nalgebra::{Vector2, Vector3};
+
+use yanyaengine::{
+    Object,
+    ObjectInfo,
+    DefaultModel,
+    Transform,
+    TransformContainer,
+    UniformLocation,
+    ShaderId,
+    object::{
+        Texture,
+        texture::{Color, SimpleImage}
+    },
+    game_object::*
+};
+
+use crate::common::{
+    some_or_return,
+    render_info::*,
+    Entity,
+    world::TILE_SIZE
+};
+
+
+// the distance field covers this many tiles around the camera
+const FIELD_TILES: usize = 64;
+const TEXELS_PER_TILE: usize = 2;
+
+const FIELD_SIZE: usize = FIELD_TILES * TEXELS_PER_TILE;
+
+const TEXEL_SIZE: f32 = TILE_SIZE / TEXELS_PER_TILE as f32;
+
+// distances are stored in texels in a single byte
+const MAX_DISTANCE: f32 = 255.0;
+
+struct LightQuad
+{
+    area: [f32; 4],
+    object: Object
+}
+
+// walls get rasterized into a grid, turned into a distance field and uploaded as a texture
+// then every light is a single quad that marches through it on the gpu
+pub struct LightOcclusion
+{
+    shader: ShaderId,
+    origin: Vector2<f32>,
+    occupied: Vec<bool>,
+    texture: Option<Arc<RwLock<Texture>>>,
+    lights: HashMap<Entity, LightQuad>
+}
+
+impl LightOcclusion
+{
+    pub fn new(shader: ShaderId) -> Self
+    {
+        Self{
+            shader,
+            origin: Vector2::zeros(),
+            occupied: Vec::new(),
+            texture: None,
+            lights: HashMap::new()
+        }
+    }
+
+    pub fn range() -> f32
+    {
+        FIELD_TILES as f32 * TILE_SIZE
+    }
+
+    fn texel_of(&self, position: Vector2<f32>) -> Option<usize>
+    {
+        let texel = (position - self.origin) / TEXEL_SIZE;
+
+        if texel.x < 0.0 || texel.y < 0.0
+        {
+            return None;
+        }
+
+        let (x, y) = (texel.x as usize, texel.y as usize);
+
+        (x < FIELD_SIZE && y < FIELD_SIZE).then_some(y * FIELD_SIZE + x)
+    }
+
+    fn rasterize(&self, occluders: &[Transform]) -> Vec<bool>
+    {
+        let mut occupied = vec![false; FIELD_SIZE * FIELD_SIZE];
+
+        occluders.iter().for_each(|transform|
+        {
+            let length = transform.scale.x;
+            let direction = Vector2::new(transform.rotation.cos(), transform.rotation.sin());
+
+            let start = transform.position.xy() - direction * (length / 2.0);
+
+            // half texel steps so diagonal planes dont leave holes
+            let steps = (length / (TEXEL_SIZE * 0.5)).ceil() as usize;
+            (0..=steps).for_each(|step|
+            {
+                let position = start + direction * (length * step as f32 / steps.max(1) as f32);
+
+                if let Some(index) = self.texel_of(position)
+                {
+                    occupied[index] = true;
+                }
+            });
+        });
+
+        occupied
+    }
+
+    // two pass chamfer distance, close enough to euclidean for marching
+    fn distance_field(occupied: &[bool]) -> Vec<f32>
+    {
+        let mut distances: Vec<f32> = occupied.iter().map(|occupied|
+        {
+            if *occupied
+            {
+                0.0
+            } else
+            {
+                MAX_DISTANCE
+            }
+        }).collect();
+
+        let diagonal = 2.0_f32.sqrt();
+
+        let relax = |distances: &mut Vec<f32>, x: usize, y: usize, offsets: &[(i32, i32, f32)]|
+        {
+            let index = y * FIELD_SIZE + x;
+
+            offsets.iter().for_each(|&(dx, dy, cost)|
+            {
+                let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+                if nx < 0 || ny < 0 || nx >= FIELD_SIZE as i32 || ny >= FIELD_SIZE as i32
+                {
+                    return;
+                }
+
+                let other = distances[ny as usize * FIELD_SIZE + nx as usize] + cost;
+                if other < distances[index]
+                {
+                    distances[index] = other;
+                }
+            });
+        };
+
+        let forward = [(-1, 0, 1.0), (0, -1, 1.0), (-1, -1, diagonal), (1, -1, diagonal)];
+        let backward = [(1, 0, 1.0), (0, 1, 1.0), (1, 1, diagonal), (-1, 1, diagonal)];
+
+        for y in 0..FIELD_SIZE
+        {
+            for x in 0..FIELD_SIZE
+            {
+                relax(&mut distances, x, y, &forward);
+            }
+        }
+
+        for y in (0..FIELD_SIZE).rev()
+        {
+            for x in (0..FIELD_SIZE).rev()
+            {
+                relax(&mut distances, x, y, &backward);
+            }
+        }
+
+        distances
+    }
+
+    fn rebuild(&mut self, info: &mut UpdateBuffersInfo)
+    {
+        let colors = Self::distance_field(&self.occupied).into_iter().map(|distance|
+        {
+            let value = distance.min(MAX_DISTANCE) as u8;
+
+            Color::new(value, value, value, 255)
+        }).collect();
+
+        let texture = Texture::new(
+            info.partial.builder_wrapper.resource_uploader(),
+            SimpleImage::new(colors, FIELD_SIZE, FIELD_SIZE).into(),
+            UniformLocation{set: 0, binding: 0},
+            self.shader
+        );
+
+        let texture = Arc::new(RwLock::new(texture));
+
+        self.lights.values_mut().for_each(|light| light.object.set_texture(texture.clone()));
+
+        self.texture = Some(texture);
+    }
+
+    // where the light quad sits inside the field, in its uvs
+    fn area(origin: Vector2<f32>, position: Vector3<f32>, radius: f32) -> [f32; 4]
+    {
+        let size = FIELD_SIZE as f32 * TEXEL_SIZE;
+
+        let start = (position.xy() - Vector2::repeat(radius) - origin) / size;
+        let scale = radius * 2.0 / size;
+
+        [start.x, start.y, scale, scale]
+    }
+
+    pub fn update_buffers(
+        &mut self,
+        info: &mut UpdateBuffersInfo,
+        center: Vector3<f32>,
+        occluders: Vec<Transform>,
+        lights: Vec<(Entity, Vector3<f32>, f32)>
+    )
+    {
+        // snapped to tiles so the field doesnt get rebuilt every time the camera moves a bit
+        let half = Vector2::repeat(Self::range() / 2.0);
+        let origin = ((center.xy() - half) / TILE_SIZE).map(|x| x.floor()) * TILE_SIZE;
+
+        let moved = origin != self.origin;
+        self.origin = origin;
+
+        let occupied = self.rasterize(&occluders);
+
+        if moved || occupied != self.occupied || self.texture.is_none()
+        {
+            self.occupied = occupied;
+            self.rebuild(info);
+        }
+
+        let texture = some_or_return!(self.texture.clone());
+
+        let origin = self.origin;
+        let mut previous = mem::take(&mut self.lights);
+
+        lights.into_iter().for_each(|(entity, position, radius)|
+        {
+            let transform = Transform{
+                position,
+                scale: Vector3::new(radius * 2.0, radius * 2.0, 1.0),
+                ..Default::default()
+            };
+
+            let mut light = previous.remove(&entity).unwrap_or_else(||
+            {
+                let model = {
+                    let assets = info.partial.assets.lock();
+
+                    assets.model(assets.default_model(DefaultModel::Square)).clone()
+                };
+
+                let object = info.partial.object_factory.create(ObjectInfo{
+                    model,
+                    texture: texture.clone(),
+                    transform: transform.clone()
+                });
+
+                LightQuad{area: [0.0; 4], object}
+            });
+
+            light.area = Self::area(origin, position, radius);
+
+            light.object.set_transform(transform);
+            light.object.update_buffers(info);
+
+            self.lights.insert(entity, light);
+        });
+    }
+
+    pub fn draw(&self, info: &mut DrawInfo)
+    {
+        self.lights.values().for_each(|light|
+        {
+            info.push_constants(LightOcclusionInfo{area: light.area});
+
+            light.object.draw(info);
+        });
+    }
+}
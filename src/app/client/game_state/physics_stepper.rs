@@ -0,0 +1,128 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap
+};
+
+use nalgebra::Vector3;
+
+use yanyaengine::Transform;
+
+use crate::{
+    LONGEST_FRAME,
+    common::{
+        some_or_return,
+        short_rotation,
+        AnyEntities,
+        Entity,
+        Physical,
+        entity::{for_each_component, ClientEntities}
+    }
+};
+
+
+pub const DEFAULT_PHYSICS_RATE: u32 = 120;
+
+const NANOS_PER_SECOND: f64 = 1_000_000_000.0;
+
+#[derive(Debug, Clone, Copy)]
+struct PreviousState
+{
+    position: Vector3<f32>,
+    rotation: f32
+}
+
+// physics runs in fixed steps no matter the framerate, rendering gets interpolated between the last two
+// time is kept in whole nanoseconds so the steps only depend on the total time and not on how frames split it up
+pub struct PhysicsStepper
+{
+    step: u64,
+    accumulator: u64,
+    previous: HashMap<Entity, PreviousState>
+}
+
+impl PhysicsStepper
+{
+    pub fn new(rate: u32) -> Self
+    {
+        Self{
+            step: (NANOS_PER_SECOND / rate.max(1) as f64).round() as u64,
+            accumulator: 0,
+            previous: HashMap::new()
+        }
+    }
+
+    fn nanos(seconds: f64) -> u64
+    {
+        (seconds * NANOS_PER_SECOND).round() as u64
+    }
+
+    pub fn step(&self) -> f32
+    {
+        (self.step as f64 / NANOS_PER_SECOND) as f32
+    }
+
+    // how many steps to run this frame, anything past the longest frame gets dropped instead of spiraling
+    pub fn steps(&mut self, dt: f32) -> u32
+    {
+        self.accumulator = (self.accumulator + Self::nanos(dt as f64)).min(Self::nanos(LONGEST_FRAME) + self.step);
+
+        let steps = self.accumulator / self.step;
+
+        self.accumulator %= self.step;
+
+        steps as u32
+    }
+
+    pub fn snapshot(&mut self, entities: &ClientEntities)
+    {
+        self.previous.clear();
+
+        for_each_component!(entities, physical, |entity, physical: &RefCell<Physical>|
+        {
+            if physical.borrow().sleeping()
+            {
+                return;
+            }
+
+            let transform = some_or_return!(entities.transform(entity));
+
+            self.previous.insert(entity, PreviousState{position: transform.position, rotation: transform.rotation});
+        });
+    }
+
+    pub fn interpolated(&self, entity: Entity, transform: &Transform) -> Transform
+    {
+        let mut transform = transform.clone();
+
+        if let Some(previous) = self.previous.get(&entity)
+        {
+            let a = (self.accumulator as f64 / self.step as f64) as f32;
+
+            transform.position = previous.position.lerp(&transform.position, a);
+            transform.rotation = previous.rotation + short_rotation(transform.rotation - previous.rotation) * a;
+        }
+
+        transform
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+
+    #[test]
+    fn frame_split()
+    {
+        let total_steps = |frame: f32, frames: u32|
+        {
+            let mut stepper = PhysicsStepper::new(DEFAULT_PHYSICS_RATE);
+
+            (0..frames).map(|_| stepper.steps(frame)).sum::<u32>()
+        };
+
+        assert_eq!(total_steps(0.01, 50), total_steps(0.025, 20));
+        assert_eq!(total_steps(0.005, 100), total_steps(0.025, 20));
+    }
+}
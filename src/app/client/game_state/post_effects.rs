@@ -0,0 +1,335 @@
+use std::{f32, sync::Arc};
+
+use parking_lot::RwLock;
+
+use vulkano::{
+    buffer::BufferContents,
+    image::view::ImageView,
+    pipeline::graphics::viewport::Viewport
+};
+
+use nalgebra::{Vector2, Vector3};
+
+use yanyaengine::{
+    Object,
+    ObjectInfo,
+    ResourceUploader,
+    ObjectFactory,
+    Transform,
+    TransformContainer,
+    UniformLocation,
+    ShaderId,
+    object::{
+        Model,
+        Texture,
+        texture::{Color, SimpleImage}
+    },
+    game_object::*
+};
+
+use crate::{
+    rendering::RenderTargets,
+    common::{some_or_return, EaseOut, Anatomy}
+};
+
+use super::{
+    color_grading::Lut,
+    render_scale::RenderScale
+};
+
+
+const VIGNETTE: f32 = 0.25;
+const LOW_HEALTH: f32 = 0.4;
+
+const DAMAGE_FLASH_SCALE: f32 = 4.0;
+const DAMAGE_FLASH_DECAY: f32 = 3.0;
+
+const BLOOM: f32 = 0.6;
+
+// how far apart the color channels get pulled when hit, in pixels
+const ABERRATION: f32 = 6.0;
+
+// how grey everything gets when about to die
+const DESATURATION: f32 = 0.85;
+
+const CRITICAL_HEALTH: f32 = 0.2;
+
+const BLOOD_DECAY: f32 = 2.0;
+
+// how much blood has to be gone before the head starts spinning
+const DIZZY_BLOOD_LOSS: f32 = 0.3;
+const DIZZY_DECAY: f32 = 1.0;
+const SWAY_SPEED: f32 = 0.7;
+
+// beats per minute when healthy and when about to die
+const HEART_RATE: (f32, f32) = (60.0, 140.0);
+
+#[derive(Debug, Clone, Copy)]
+pub struct GraphicsSettings
+{
+    pub vignette: bool,
+    pub bloom: bool,
+    pub aberration: bool,
+    pub desaturation: bool,
+    pub reflections: bool,
+    pub render_scale: f32,
+    pub adaptive_scale: bool
+}
+
+impl Default for GraphicsSettings
+{
+    fn default() -> Self
+    {
+        Self{
+            vignette: true,
+            bloom: true,
+            aberration: true,
+            desaturation: true,
+            reflections: true,
+            render_scale: 1.0,
+            adaptive_scale: false
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(BufferContents)]
+pub struct PostInfo
+{
+    bloom: f32,
+    vignette: f32,
+    aberration: f32,
+    saturation: f32,
+    blood: f32,
+    pulse: f32,
+    dizziness: f32,
+    sway: f32,
+    scale: f32,
+    lut: [u32; 6]
+}
+
+// a screen sized quad in the second subpass that samples the world image and draws the result to the screen
+pub struct PostEffects
+{
+    settings: GraphicsSettings,
+    render_scale: RenderScale,
+    targets: RenderTargets,
+    shader: ShaderId,
+    world: Option<Arc<ImageView>>,
+    object: Object,
+    health: Option<f32>,
+    damage_flash: f32,
+    blood: f32,
+    dizziness: f32,
+    sway: f32,
+    heartbeat: f32
+}
+
+impl PostEffects
+{
+    pub fn new(
+        settings: GraphicsSettings,
+        targets: RenderTargets,
+        object_factory: &ObjectFactory,
+        resource_uploader: &mut ResourceUploader,
+        model: Arc<RwLock<Model>>,
+        shader: ShaderId
+    ) -> Self
+    {
+        // just until the world image exists
+        let texture = Texture::new(
+            resource_uploader,
+            SimpleImage::new(vec![Color::new(0, 0, 0, 0)], 1, 1).into(),
+            UniformLocation{set: 0, binding: 0},
+            shader
+        );
+
+        let object = object_factory.create(ObjectInfo{
+            model,
+            texture: Arc::new(RwLock::new(texture)),
+            transform: Transform::default()
+        });
+
+        Self{
+            settings,
+            render_scale: RenderScale::new(&settings),
+            targets,
+            shader,
+            world: None,
+            object,
+            health: None,
+            damage_flash: 0.0,
+            blood: 0.0,
+            dizziness: 0.0,
+            sway: 0.0,
+            heartbeat: 0.0
+        }
+    }
+
+    // returns how much of the health got lost since last update
+    pub fn update(&mut self, anatomy: Option<&Anatomy>, dt: f32) -> f32
+    {
+        let health = anatomy.map(|anatomy| anatomy.health_fraction());
+
+        let mut lost = 0.0;
+        if let (Some(previous), Some(current)) = (self.health, health)
+        {
+            lost = (previous - current).max(0.0);
+
+            if lost > 0.0
+            {
+                self.damage_flash = (self.damage_flash + lost * DAMAGE_FLASH_SCALE).min(1.0);
+            }
+        }
+
+        self.health = health;
+
+        self.damage_flash = self.damage_flash.ease_out(0.0, DAMAGE_FLASH_DECAY, dt);
+
+        let target_blood = anatomy.map(|anatomy|
+        {
+            let blood_loss = 1.0 - anatomy.blood_fraction();
+
+            blood_loss.max(anatomy.pain()).clamp(0.0, 1.0)
+        }).unwrap_or(0.0);
+
+        self.blood = self.blood.ease_out(target_blood, BLOOD_DECAY, dt);
+
+        let target_dizziness = anatomy.map(|anatomy|
+        {
+            let blood_loss = 1.0 - anatomy.blood_fraction();
+
+            ((blood_loss - DIZZY_BLOOD_LOSS) / (1.0 - DIZZY_BLOOD_LOSS)).clamp(0.0, 1.0)
+        }).unwrap_or(0.0);
+
+        self.dizziness = self.dizziness.ease_out(target_dizziness, DIZZY_DECAY, dt);
+
+        self.sway = (self.sway + dt * SWAY_SPEED).fract();
+
+        let (calm_rate, panic_rate) = HEART_RATE;
+        let heart_rate = calm_rate + (panic_rate - calm_rate) * self.critical();
+
+        self.heartbeat = (self.heartbeat + dt * heart_rate / 60.0).fract();
+
+        self.render_scale.update(dt);
+
+        lost
+    }
+
+    // a resize makes a new world image so the texture has to follow it
+    fn update_world(&mut self, info: &mut UpdateBuffersInfo)
+    {
+        let world = some_or_return!(self.targets.world());
+
+        let same = self.world.as_ref().map(|current| Arc::ptr_eq(current, &world)).unwrap_or(false);
+        if same
+        {
+            return;
+        }
+
+        let texture = Texture::new(
+            info.partial.builder_wrapper.resource_uploader(),
+            world.clone().into(),
+            UniformLocation{set: 0, binding: 0},
+            self.shader
+        );
+
+        self.object.set_texture(Arc::new(RwLock::new(texture)));
+
+        self.world = Some(world);
+    }
+
+    pub fn update_buffers(&mut self, info: &mut UpdateBuffersInfo, size: Vector2<f32>)
+    {
+        self.update_world(info);
+
+        self.object.set_transform(Transform{
+            scale: Vector3::new(size.x, size.y, 1.0),
+            ..Default::default()
+        });
+
+        self.object.update_buffers(info);
+    }
+
+    // the corner of the world image the world gets drawn into, none if its drawn at full size
+    pub fn viewport(&self) -> Option<Viewport>
+    {
+        let scale = self.render_scale.scale();
+
+        if scale == 1.0
+        {
+            return None;
+        }
+
+        let [width, height, _] = self.world.as_ref()?.image().extent();
+
+        Some(Viewport{
+            offset: [0.0, 0.0],
+            extent: [(width as f32 * scale).round(), (height as f32 * scale).round()],
+            depth_range: 0.0..=1.0
+        })
+    }
+
+    pub fn draw(&self, info: &mut DrawInfo, lut: &Lut)
+    {
+        info.push_constants(self.info(lut));
+
+        self.object.draw(info);
+    }
+
+    fn critical(&self) -> f32
+    {
+        let health = self.health.unwrap_or(1.0);
+
+        (1.0 - health / CRITICAL_HEALTH).clamp(0.0, 1.0)
+    }
+
+    // lub dub, the second beat is a bit weaker
+    fn beat(&self) -> f32
+    {
+        let spike = |offset: f32|
+        {
+            let x = (self.heartbeat - offset) * 12.0;
+
+            (-x * x).exp()
+        };
+
+        spike(0.1) + spike(0.35) * 0.6
+    }
+
+    fn low_health(&self) -> f32
+    {
+        let health = self.health.unwrap_or(1.0);
+
+        (1.0 - health / LOW_HEALTH).clamp(0.0, 1.0)
+    }
+
+    fn info(&self, lut: &Lut) -> PostInfo
+    {
+        let enabled = |state: bool, value: f32|
+        {
+            if state
+            {
+                value
+            } else
+            {
+                0.0
+            }
+        };
+
+        let low_health = self.low_health();
+
+        PostInfo{
+            bloom: enabled(self.settings.bloom, BLOOM),
+            vignette: enabled(self.settings.vignette, VIGNETTE + low_health * (1.0 - VIGNETTE)),
+            aberration: enabled(self.settings.aberration, self.damage_flash * ABERRATION),
+            saturation: 1.0 - enabled(self.settings.desaturation, low_health * DESATURATION),
+            blood: self.blood * (1.0 + self.beat() * 0.3 * self.critical()),
+            pulse: self.beat() * self.critical(),
+            dizziness: self.dizziness,
+            sway: self.sway * f32::consts::TAU,
+            scale: self.render_scale.scale(),
+            lut: lut.packed()
+        }
+    }
+}
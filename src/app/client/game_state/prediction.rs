@@ -0,0 +1,177 @@
+use std::collections::VecDeque;
+
+use nalgebra::Vector3;
+
+use crate::common::{
+    EaseOut,
+    world::TILE_SIZE
+};
+
+
+// further off than this is a teleport, no point sliding all the way there
+const SNAP_DISTANCE: f32 = TILE_SIZE * 4.0;
+
+// closer than this is just float error
+const IGNORE_DISTANCE: f32 = TILE_SIZE * 0.01;
+
+const CORRECTION_DECAY: f32 = 12.0;
+
+// if the server stops answering the oldest ones get thrown out
+const MAX_INPUTS: usize = 256;
+
+struct Input
+{
+    sequence: u32,
+    // how far the player moved locally since the last one
+    movement: Vector3<f32>
+}
+
+// the player moves right away and gets pulled towards whatever the server agreed to afterwards
+pub struct Prediction
+{
+    sequence: u32,
+    // acks from before a teleport dont mean anything anymore
+    reset_sequence: u32,
+    last_position: Option<Vector3<f32>>,
+    inputs: VecDeque<Input>,
+    // whats left to slide towards
+    correction: Vector3<f32>
+}
+
+impl Prediction
+{
+    pub fn new() -> Self
+    {
+        Self{
+            sequence: 0,
+            reset_sequence: 0,
+            last_position: None,
+            inputs: VecDeque::new(),
+            correction: Vector3::zeros()
+        }
+    }
+
+    // every frame after moving, the move gets sent with the sequence this returns
+    pub fn push(&mut self, position: Vector3<f32>) -> u32
+    {
+        let movement = self.last_position.map(|last| position - last).unwrap_or_else(Vector3::zeros);
+        self.last_position = Some(position);
+
+        self.sequence = self.sequence.wrapping_add(1);
+
+        self.inputs.push_back(Input{sequence: self.sequence, movement});
+
+        if self.inputs.len() > MAX_INPUTS
+        {
+            self.inputs.pop_front();
+        }
+
+        self.sequence
+    }
+
+    // the server moved the player itself
+    pub fn reset(&mut self)
+    {
+        self.reset_sequence = self.sequence;
+        self.last_position = None;
+        self.inputs.clear();
+        self.correction = Vector3::zeros();
+    }
+
+    // replays everything the server hasnt seen yet on top of where it says the player is
+    pub fn acked(&mut self, sequence: u32, position: Vector3<f32>, current: Vector3<f32>)
+    {
+        if !is_newer(sequence, self.reset_sequence)
+        {
+            return;
+        }
+
+        while self.inputs.front().map(|input| !is_newer(input.sequence, sequence)).unwrap_or(false)
+        {
+            self.inputs.pop_front();
+        }
+
+        let predicted = self.inputs.iter().fold(position, |position, input| position + input.movement);
+
+        // replaces whatever was left since thats already part of the current position
+        let error = predicted - current;
+
+        self.correction = if error.magnitude() < IGNORE_DISTANCE
+        {
+            Vector3::zeros()
+        } else
+        {
+            error
+        };
+    }
+
+    // how much to move the player by this frame
+    pub fn update(&mut self, dt: f32) -> Vector3<f32>
+    {
+        if self.correction.magnitude() > SNAP_DISTANCE
+        {
+            return std::mem::replace(&mut self.correction, Vector3::zeros());
+        }
+
+        let left = self.correction.ease_out(Vector3::zeros(), CORRECTION_DECAY, dt);
+        let moved = self.correction - left;
+
+        self.correction = left;
+
+        moved
+    }
+}
+
+// handles the sequence wrapping around
+fn is_newer(sequence: u32, other: u32) -> bool
+{
+    (sequence.wrapping_sub(other) as i32) > 0
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+
+    #[test]
+    fn accepted_moves()
+    {
+        let mut prediction = Prediction::new();
+
+        let positions = (0..5).map(|i| Vector3::new(i as f32 * TILE_SIZE * 0.1, 0.0, 0.0)).collect::<Vec<_>>();
+        let sequences = positions.iter().map(|position| prediction.push(*position)).collect::<Vec<_>>();
+
+        let current = *positions.last().unwrap();
+        prediction.acked(sequences[2], positions[2], current);
+
+        assert_eq!(prediction.inputs.len(), 2);
+        assert_eq!(prediction.update(0.1), Vector3::zeros());
+    }
+
+    #[test]
+    fn rejected_moves()
+    {
+        let mut prediction = Prediction::new();
+
+        let start = prediction.push(Vector3::zeros());
+        let moved = prediction.push(Vector3::new(TILE_SIZE, 0.0, 0.0));
+        prediction.push(Vector3::new(TILE_SIZE * 1.5, 0.0, 0.0));
+
+        // the server never took the first step so everything after it should be that much behind
+        prediction.acked(start, Vector3::zeros(), Vector3::new(TILE_SIZE * 1.5, 0.0, 0.0));
+        prediction.acked(moved, Vector3::zeros(), Vector3::new(TILE_SIZE * 1.5, 0.0, 0.0));
+
+        let total = (0..200).map(|_| prediction.update(0.016)).fold(Vector3::zeros(), |total, x| total + x);
+
+        assert!((total - Vector3::new(-TILE_SIZE, 0.0, 0.0)).magnitude() < IGNORE_DISTANCE);
+    }
+
+    #[test]
+    fn wrapping()
+    {
+        assert!(is_newer(0, u32::MAX));
+        assert!(!is_newer(u32::MAX, 0));
+        assert!(!is_newer(5, 5));
+    }
+}
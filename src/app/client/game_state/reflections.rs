@@ -0,0 +1,151 @@
+use std::{
+    mem,
+    sync::Arc,
+    collections::HashMap
+};
+
+use parking_lot::RwLock;
+
+use yanyaengine::{
+    Object,
+    ObjectInfo,
+    DefaultModel,
+    Transform,
+    TransformContainer,
+    object::Texture,
+    game_object::*
+};
+
+use crate::common::{
+    some_or_return,
+    render_info::*,
+    Entity,
+    Pos3,
+    entity::ClientEntities,
+    world::World
+};
+
+
+// how far below the sprite the reflection sits, relative to its size
+const REFLECTION_OFFSET: f32 = 0.6;
+
+const REFLECTION_TINT: [f32; 3] = [0.55, 0.7, 0.85];
+
+struct Reflection
+{
+    texture: Arc<RwLock<Texture>>,
+    strength: f32,
+    object: Object
+}
+
+fn reflected_transform(transform: &Transform) -> Transform
+{
+    let mut transform = transform.clone();
+    transform.position.y += transform.scale.y * REFLECTION_OFFSET;
+
+    transform
+}
+
+// reflective floors under it or reflective walls (windows) right next to it
+fn strength_at(world: &World, transform: &Transform) -> f32
+{
+    let tile = world.tile_of(Pos3::from(transform.position));
+
+    [tile, tile.offset(Pos3::new(0, 0, -1))].into_iter().filter_map(|pos| world.tile(pos)).map(|tile|
+    {
+        world.tile_info(*tile).reflective
+    }).fold(0.0, f32::max)
+}
+
+pub struct Reflections
+{
+    enabled: bool,
+    reflections: HashMap<Entity, Reflection>
+}
+
+impl Reflections
+{
+    pub fn new(enabled: bool) -> Self
+    {
+        Self{enabled, reflections: HashMap::new()}
+    }
+
+    pub fn update_buffers(
+        &mut self,
+        entities: &ClientEntities,
+        world: &World,
+        renders: impl Iterator<Item=Entity>,
+        info: &mut UpdateBuffersInfo
+    )
+    {
+        if !self.enabled
+        {
+            return;
+        }
+
+        let mut previous = mem::take(&mut self.reflections);
+
+        // only characters so particles and furniture dont clutter it
+        renders.filter(|entity| entities.character_exists(*entity)).for_each(|entity|
+        {
+            let transform = reflected_transform(&some_or_return!(entities.transform(entity)));
+
+            let strength = strength_at(world, &transform);
+            if strength <= 0.0
+            {
+                return;
+            }
+
+            let render = some_or_return!(entities.render(entity));
+            if !render.visible
+            {
+                return;
+            }
+
+            let texture = some_or_return!(render.texture()).clone();
+
+            let mut reflection = if let Some(mut reflection) = previous.remove(&entity)
+            {
+                if !Arc::ptr_eq(&reflection.texture, &texture)
+                {
+                    reflection.object.set_texture(texture.clone());
+                    reflection.texture = texture;
+                }
+
+                reflection
+            } else
+            {
+                let model = {
+                    let assets = info.partial.assets.lock();
+
+                    assets.model(assets.default_model(DefaultModel::Square)).clone()
+                };
+
+                let object = info.partial.object_factory.create(ObjectInfo{
+                    model,
+                    texture: texture.clone(),
+                    transform: transform.clone()
+                });
+
+                Reflection{texture, strength, object}
+            };
+
+            reflection.strength = strength;
+
+            reflection.object.set_transform(transform);
+            reflection.object.update_buffers(info);
+
+            self.reflections.insert(entity, reflection);
+        });
+    }
+
+    pub fn draw(&self, info: &mut DrawInfo, animation: f32)
+    {
+        self.reflections.values().for_each(|reflection|
+        {
+            info.push_constants(ReflectionInfo::new(REFLECTION_TINT, reflection.strength, animation));
+
+            reflection.object.draw(info);
+        });
+    }
+}
@@ -0,0 +1,90 @@
+use crate::{
+    LONGEST_FRAME,
+    common::EaseOut
+};
+
+use super::GraphicsSettings;
+
+
+// the adaptive scale never goes below this
+const MIN_SCALE: f32 = 0.5;
+
+const SCALE_STEP: f32 = 0.05;
+
+// seconds between adjustments so it doesnt keep jumping between sizes
+const ADJUST_COOLDOWN: f32 = 0.5;
+
+const FRAME_TIME_DECAY: f32 = 2.0;
+
+// fractions of LONGEST_FRAME, slower than the first lowers the scale and faster than the second raises it back
+const SLOW_FRAME: f32 = 0.6;
+const FAST_FRAME: f32 = 0.3;
+
+// fraction of the window resolution the world gets drawn at, the ui always stays at full resolution
+pub struct RenderScale
+{
+    // the most it ever goes up to
+    limit: f32,
+    adaptive: bool,
+    scale: f32,
+    frame_time: f32,
+    cooldown: f32
+}
+
+impl RenderScale
+{
+    pub fn new(settings: &GraphicsSettings) -> Self
+    {
+        let limit = settings.render_scale.clamp(0.1, 1.0);
+
+        Self{
+            limit,
+            adaptive: settings.adaptive_scale,
+            scale: limit,
+            frame_time: 0.0,
+            cooldown: ADJUST_COOLDOWN
+        }
+    }
+
+    pub fn scale(&self) -> f32
+    {
+        self.scale
+    }
+
+    pub fn update(&mut self, dt: f32)
+    {
+        if !self.adaptive
+        {
+            return;
+        }
+
+        self.frame_time = self.frame_time.ease_out(dt, FRAME_TIME_DECAY, dt);
+
+        self.cooldown -= dt;
+        if self.cooldown > 0.0
+        {
+            return;
+        }
+
+        let longest = LONGEST_FRAME as f32;
+
+        let scale = if self.frame_time > longest * SLOW_FRAME
+        {
+            self.scale - SCALE_STEP
+        } else if self.frame_time < longest * FAST_FRAME
+        {
+            self.scale + SCALE_STEP
+        } else
+        {
+            return;
+        };
+
+        let scale = scale.clamp(MIN_SCALE.min(self.limit), self.limit);
+
+        if scale != self.scale
+        {
+            self.scale = scale;
+            self.cooldown = ADJUST_COOLDOWN;
+        }
+    }
+}
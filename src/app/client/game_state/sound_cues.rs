@@ -0,0 +1,174 @@
+use nalgebra::{Vector2, Vector3};
+
+use yanyaengine::{
+    Object,
+    Transform,
+    TransformContainer,
+    game_object::*
+};
+
+use crate::{
+    client::RenderCreateInfo,
+    common::{
+        render_info::*,
+        Entity,
+        Noise,
+        NoiseKind,
+        world::TILE_SIZE
+    }
+};
+
+
+const CUE_LIFETIME: f32 = 1.5;
+
+// noises of the same kind this close together refresh the same cue instead of making a new one
+const MERGE_DISTANCE: f32 = TILE_SIZE * 2.0;
+
+const MAX_CUES: usize = 16;
+
+// in ui units
+const CUE_SIZE: f32 = 0.04;
+const EDGE_MARGIN: f32 = 0.05;
+
+fn kind_color(kind: NoiseKind) -> [f32; 3]
+{
+    match kind
+    {
+        NoiseKind::Gunshot => [1.0, 0.2, 0.2],
+        NoiseKind::Footstep => [0.9, 0.9, 0.9],
+        NoiseKind::Door => [1.0, 0.8, 0.3],
+        NoiseKind::Container => [0.4, 0.8, 1.0]
+    }
+}
+
+struct SoundCue
+{
+    kind: NoiseKind,
+    position: Vector3<f32>,
+    lifetime: f32
+}
+
+// arrows around the edge of the screen pointing at noises, for when the sounds cant be heard
+pub struct SoundCues
+{
+    enabled: bool,
+    cues: Vec<SoundCue>,
+    transforms: Vec<(Transform, NoiseKind)>,
+    objects: Vec<Object>
+}
+
+impl SoundCues
+{
+    pub fn new(enabled: bool) -> Self
+    {
+        Self{
+            enabled,
+            cues: Vec::new(),
+            transforms: Vec::new(),
+            objects: Vec::new()
+        }
+    }
+
+    fn add(&mut self, noise: Noise)
+    {
+        let existing = self.cues.iter_mut().find(|cue|
+        {
+            cue.kind == noise.kind && cue.position.metric_distance(&noise.position) < MERGE_DISTANCE
+        });
+
+        if let Some(cue) = existing
+        {
+            cue.position = noise.position;
+            cue.lifetime = CUE_LIFETIME;
+
+            return;
+        }
+
+        if self.cues.len() >= MAX_CUES
+        {
+            self.cues.remove(0);
+        }
+
+        self.cues.push(SoundCue{kind: noise.kind, position: noise.position, lifetime: CUE_LIFETIME});
+    }
+
+    // puts the cue on the edge of the screen in the direction of the noise
+    fn edge_transform(listener: Vector3<f32>, screen_size: Vector2<f32>, cue: &SoundCue) -> Option<Transform>
+    {
+        let direction = (cue.position - listener).xy().try_normalize(f32::EPSILON)?;
+
+        let half = (screen_size / 2.0).add_scalar(-EDGE_MARGIN);
+
+        let scale = (direction.x.abs() / half.x).max(direction.y.abs() / half.y);
+
+        let position = direction / scale;
+
+        let size = CUE_SIZE * (cue.lifetime / CUE_LIFETIME).sqrt();
+
+        Some(Transform{
+            position: Vector3::new(position.x, position.y, 0.0),
+            rotation: direction.y.atan2(direction.x),
+            scale: Vector3::repeat(size),
+            ..Default::default()
+        })
+    }
+
+    pub fn update(
+        &mut self,
+        create_info: &mut RenderCreateInfo,
+        noises: Vec<Noise>,
+        listener: Entity,
+        listener_position: Vector3<f32>,
+        screen_size: Vector2<f32>,
+        dt: f32
+    )
+    {
+        if !self.enabled
+        {
+            return;
+        }
+
+        // the player knows about their own noises
+        noises.into_iter().filter(|noise|
+        {
+            noise.source != Some(listener)
+                && noise.position.metric_distance(&listener_position) < noise.radius
+        }).for_each(|noise| self.add(noise));
+
+        self.cues.iter_mut().for_each(|cue| cue.lifetime -= dt);
+        self.cues.retain(|cue| cue.lifetime > 0.0);
+
+        self.transforms = self.cues.iter().filter_map(|cue|
+        {
+            Self::edge_transform(listener_position, screen_size, cue).map(|transform| (transform, cue.kind))
+        }).collect();
+
+        while self.objects.len() < self.transforms.len()
+        {
+            let id = create_info.object_info.partial.assets.lock().texture_id("arrow.png");
+
+            self.objects.push(create_info.create_object(id, Transform::default()));
+        }
+    }
+
+    pub fn update_buffers(&mut self, info: &mut UpdateBuffersInfo)
+    {
+        self.objects.iter_mut().zip(self.transforms.iter()).for_each(|(object, (transform, _))|
+        {
+            object.set_transform(transform.clone());
+            object.update_buffers(info);
+        });
+    }
+
+    pub fn draw(&self, info: &mut DrawInfo)
+    {
+        self.objects.iter().zip(self.transforms.iter()).for_each(|(object, (_, kind))|
+        {
+            let mix = MixColor{color: kind_color(*kind), amount: 1.0, keep_transparency: true};
+
+            info.push_constants(UiOutlinedInfo::new(Some(mix)));
+
+            object.draw(info);
+        });
+    }
+}
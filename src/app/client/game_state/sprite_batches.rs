@@ -0,0 +1,244 @@
+use std::{
+    mem,
+    rc::Rc,
+    sync::Arc,
+    collections::HashMap
+};
+
+use parking_lot::RwLock;
+
+use nalgebra::Vector3;
+
+use yanyaengine::{
+    Object,
+    ObjectInfo,
+    Transform,
+    object::Model,
+    game_object::*
+};
+
+use crate::{
+    client::{SpriteAtlas, sprite_atlas::AtlasRegion},
+    common::{
+        some_or_false,
+        project_onto,
+        render_info::*,
+        Entity,
+        entity::ClientEntities
+    }
+};
+
+
+// same corners as the default square model
+const CORNERS: [[f32; 2]; 6] = [
+    [-0.5, -0.5],
+    [-0.5, 0.5],
+    [0.5, -0.5],
+    [-0.5, 0.5],
+    [0.5, 0.5],
+    [0.5, -0.5]
+];
+
+struct SpriteBatch
+{
+    // the batch gets drawn in this ones place, the rest of them right after it
+    first: Entity,
+    page: usize,
+    vertices: Vec<[f32; 3]>,
+    uvs: Vec<[f32; 2]>,
+    object: Option<Object>
+}
+
+impl SpriteBatch
+{
+    fn new(first: Entity, page: usize) -> Self
+    {
+        Self{first, page, vertices: Vec::new(), uvs: Vec::new(), object: None}
+    }
+
+    fn push(&mut self, transform: &Transform, region: AtlasRegion)
+    {
+        let [x, y] = region.start;
+        let [x_end, y_end] = region.end;
+
+        self.vertices.extend(CORNERS.iter().map(|[corner_x, corner_y]|
+        {
+            project_onto(transform, &Vector3::new(*corner_x, *corner_y, 0.0)).into()
+        }));
+
+        self.uvs.extend([
+            [x, y],
+            [x, y_end],
+            [x_end, y],
+            [x, y_end],
+            [x_end, y_end],
+            [x_end, y]
+        ]);
+    }
+
+    fn same_as(&self, other: &Self) -> bool
+    {
+        self.page == other.page && self.vertices == other.vertices && self.uvs == other.uvs
+    }
+}
+
+// consecutive plain sprites on the same atlas page get merged into one model and drawn at once
+pub struct SpriteBatches
+{
+    atlas: Rc<SpriteAtlas>,
+    batched: HashMap<Entity, usize>,
+    batches: Vec<SpriteBatch>
+}
+
+impl SpriteBatches
+{
+    pub fn new(atlas: Rc<SpriteAtlas>) -> Self
+    {
+        Self{
+            atlas,
+            batched: HashMap::new(),
+            batches: Vec::new()
+        }
+    }
+
+    // the draw order, same atlas page sprites end up next to each other and the entity keeps it stable
+    pub fn sort_key(&self, entities: &ClientEntities, entity: Entity) -> (ZLevel, Option<usize>, Entity)
+    {
+        let render = entities.render(entity).unwrap();
+
+        let page = render.sprite().and_then(|(_, texture)| self.atlas.region(texture)).map(|x| x.page);
+
+        (render.z_level(), page, entity)
+    }
+
+    // anything that changes how a single sprite gets drawn has to go through the normal path
+    fn batchable(&self, entities: &ClientEntities, entity: Entity) -> Option<(Transform, AtlasRegion)>
+    {
+        let outlined = entities.outlineable(entity).and_then(|x| x.current()).is_some();
+        if outlined
+        {
+            return None;
+        }
+
+        let render = entities.render(entity)?;
+
+        if render.mix.is_some() || render.emissive.is_some() || render.dissolve > 0.0
+        {
+            return None;
+        }
+
+        let (transform, texture) = render.sprite()?;
+
+        Some((transform.clone(), self.atlas.region(texture)?))
+    }
+
+    pub fn update_buffers(
+        &mut self,
+        entities: &ClientEntities,
+        renders: impl Iterator<Item=Entity>,
+        info: &mut UpdateBuffersInfo
+    )
+    {
+        let mut previous: Vec<_> = mem::take(&mut self.batches).into_iter().map(Some).collect();
+
+        self.batched.clear();
+
+        let mut current: Option<(SpriteBatch, Vec<Entity>)> = None;
+        let mut finished = Vec::new();
+
+        renders.for_each(|entity|
+        {
+            let batchable = self.batchable(entities, entity);
+
+            let continues = match (&current, &batchable)
+            {
+                (Some((batch, _)), Some((_, region))) => batch.page == region.page,
+                _ => false
+            };
+
+            if !continues
+            {
+                finished.extend(current.take());
+            }
+
+            if let Some((transform, region)) = batchable
+            {
+                let (batch, members) = current.get_or_insert_with(||
+                {
+                    (SpriteBatch::new(entity, region.page), Vec::new())
+                });
+
+                batch.push(&transform, region);
+                members.push(entity);
+            }
+        });
+
+        finished.extend(current.take());
+
+        finished.into_iter().for_each(|(mut batch, members)|
+        {
+            // a lone sprite is just a normal draw
+            if members.len() < 2
+            {
+                return;
+            }
+
+            let index = self.batches.len();
+            self.batched.extend(members.into_iter().map(|entity| (entity, index)));
+
+            // static stuff like furniture keeps the same model so theres no need to rebuild it
+            batch.object = previous.iter_mut().find_map(|old|
+            {
+                old.as_ref().filter(|old| old.same_as(&batch))?;
+
+                old.take().and_then(|old| old.object)
+            });
+
+            if batch.object.is_none()
+            {
+                let mut model = Model::new();
+
+                model.vertices = batch.vertices.clone();
+                model.uvs = batch.uvs.clone();
+
+                let object_info = ObjectInfo{
+                    model: Arc::new(RwLock::new(model)),
+                    texture: self.atlas.page(batch.page).clone(),
+                    transform: Transform::default()
+                };
+
+                batch.object = Some(info.partial.object_factory.create(object_info));
+            }
+
+            if let Some(object) = batch.object.as_mut()
+            {
+                object.update_buffers(info);
+            }
+
+            self.batches.push(batch);
+        });
+    }
+
+    // true if this entity is part of a batch, the whole batch gets drawn on its first entity
+    pub fn draw(
+        &self,
+        info: &mut DrawInfo,
+        entity: Entity,
+        animation: f32
+    ) -> bool
+    {
+        let batch = some_or_false!(self.batched.get(&entity).map(|index| &self.batches[*index]));
+
+        if batch.first == entity
+        {
+            if let Some(object) = batch.object.as_ref()
+            {
+                info.push_constants(OutlinedInfo::new(None, 0.0, animation));
+
+                object.draw(info);
+            }
+        }
+
+        true
+    }
+}
@@ -0,0 +1,189 @@
+use std::{
+    fs,
+    mem,
+    path::PathBuf,
+    collections::VecDeque,
+    time::{Duration, SystemTime, UNIX_EPOCH}
+};
+
+use serde::Serialize;
+
+
+const TELEMETRY_PATH: &str = "telemetry";
+
+// keeps the last this many frames for the percentiles
+const MAX_FRAMES: usize = 100_000;
+
+const POST_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Default)]
+pub struct TelemetrySettings
+{
+    pub enabled: bool,
+    pub endpoint: Option<String>
+}
+
+#[derive(Debug, Serialize)]
+struct Death
+{
+    cause: String,
+    // seconds into the session
+    time: f64
+}
+
+#[derive(Debug, Serialize)]
+struct FrameTimes
+{
+    p50: f32,
+    p95: f32,
+    p99: f32,
+    max: f32
+}
+
+// nothing in here says who played
+#[derive(Debug, Serialize)]
+struct Session
+{
+    version: &'static str,
+    session_length: f64,
+    deaths: Vec<Death>,
+    frame_times: Option<FrameTimes>
+}
+
+pub struct Telemetry
+{
+    settings: TelemetrySettings,
+    started: SystemTime,
+    time: f64,
+    deaths: Vec<Death>,
+    frames: VecDeque<f32>,
+    previous_alive: Option<bool>
+}
+
+impl Drop for Telemetry
+{
+    fn drop(&mut self)
+    {
+        self.finish();
+    }
+}
+
+impl Telemetry
+{
+    pub fn new(settings: TelemetrySettings) -> Self
+    {
+        Self{
+            settings,
+            started: SystemTime::now(),
+            time: 0.0,
+            deaths: Vec::new(),
+            frames: VecDeque::new(),
+            previous_alive: None
+        }
+    }
+
+    // true if the player just died and the cause should get recorded
+    pub fn died(&mut self, alive: bool) -> bool
+    {
+        let died = self.previous_alive == Some(true) && !alive;
+
+        self.previous_alive = Some(alive);
+
+        self.settings.enabled && died
+    }
+
+    pub fn death(&mut self, cause: String)
+    {
+        self.deaths.push(Death{cause, time: self.time});
+    }
+
+    pub fn update(&mut self, dt: f32)
+    {
+        if !self.settings.enabled
+        {
+            return;
+        }
+
+        self.time += dt as f64;
+
+        if self.frames.len() >= MAX_FRAMES
+        {
+            self.frames.pop_front();
+        }
+
+        self.frames.push_back(dt);
+    }
+
+    fn frame_times(&self) -> Option<FrameTimes>
+    {
+        if self.frames.is_empty()
+        {
+            return None;
+        }
+
+        let mut frames: Vec<f32> = self.frames.iter().copied().collect();
+        frames.sort_unstable_by(|a, b| a.total_cmp(b));
+
+        let percentile = |p: f32|
+        {
+            let index = ((frames.len() - 1) as f32 * p).round() as usize;
+
+            frames[index] * 1000.0
+        };
+
+        Some(FrameTimes{
+            p50: percentile(0.5),
+            p95: percentile(0.95),
+            p99: percentile(0.99),
+            max: percentile(1.0)
+        })
+    }
+
+    fn finish(&mut self)
+    {
+        if !self.settings.enabled
+        {
+            return;
+        }
+
+        let session = Session{
+            version: env!("CARGO_PKG_VERSION"),
+            session_length: self.time,
+            deaths: mem::take(&mut self.deaths),
+            frame_times: self.frame_times()
+        };
+
+        let json = match serde_json::to_string_pretty(&session)
+        {
+            Ok(x) => x,
+            Err(err) =>
+            {
+                eprintln!("error serializing telemetry: {err}");
+                return;
+            }
+        };
+
+        let started = self.started.duration_since(UNIX_EPOCH).map(|x| x.as_secs()).unwrap_or(0);
+        let path = PathBuf::from(TELEMETRY_PATH).join(format!("{started}.json"));
+
+        let result = fs::create_dir_all(TELEMETRY_PATH).and_then(|_| fs::write(&path, &json));
+        if let Err(err) = result
+        {
+            eprintln!("error saving telemetry to {}: {err}", path.display());
+        }
+
+        if let Some(endpoint) = self.settings.endpoint.as_ref()
+        {
+            // the game is closing so dont hang on a slow server forever
+            let result = ureq::post(endpoint)
+                .timeout(POST_TIMEOUT)
+                .set("Content-Type", "application/json")
+                .send_string(&json);
+
+            if let Err(err) = result
+            {
+                eprintln!("error sending telemetry to {endpoint}: {err}");
+            }
+        }
+    }
+}
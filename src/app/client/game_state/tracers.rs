@@ -0,0 +1,110 @@
+use std::cell::RefCell;
+
+use nalgebra::Vector3;
+
+use yanyaengine::{
+    Object,
+    Transform,
+    TransformContainer,
+    game_object::*
+};
+
+use crate::{
+    client::RenderCreateInfo,
+    common::{
+        some_or_return,
+        render_info::*,
+        AnyEntities,
+        Projectile,
+        Projectiles,
+        entity::{for_each_component, ClientEntities},
+        world::TILE_SIZE
+    }
+};
+
+
+const TRACER_WIDTH: f32 = TILE_SIZE * 0.05;
+
+// how far back the streak behind a flying projectile goes, in seconds of its velocity
+const STREAK_TIME: f32 = 0.02;
+
+const TRACER_EMISSIVE: f32 = 1.5;
+
+fn segment_transform(start: Vector3<f32>, end: Vector3<f32>, width: f32) -> Transform
+{
+    let difference = end - start;
+
+    Transform{
+        position: (start + end) / 2.0,
+        rotation: difference.y.atan2(difference.x),
+        scale: Vector3::new(difference.xy().magnitude(), width, 1.0),
+        ..Default::default()
+    }
+}
+
+// visuals for the projectiles, objects get reused between frames
+pub struct Tracers
+{
+    transforms: Vec<Transform>,
+    objects: Vec<Object>
+}
+
+impl Tracers
+{
+    pub fn new() -> Self
+    {
+        Self{
+            transforms: Vec::new(),
+            objects: Vec::new()
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        create_info: &mut RenderCreateInfo,
+        projectiles: &Projectiles,
+        entities: &ClientEntities
+    )
+    {
+        self.transforms = projectiles.tracers().iter().map(|tracer|
+        {
+            segment_transform(tracer.start, tracer.end, TRACER_WIDTH * tracer.fade())
+        }).collect();
+
+        for_each_component!(entities, projectile, |entity, _projectile: &RefCell<Projectile>|
+        {
+            let position = some_or_return!(entities.transform(entity)).position;
+            let velocity = *some_or_return!(entities.physical(entity)).velocity();
+
+            let start = position - velocity * STREAK_TIME;
+
+            self.transforms.push(segment_transform(start, position, TRACER_WIDTH));
+        });
+
+        while self.objects.len() < self.transforms.len()
+        {
+            let id = create_info.object_info.partial.assets.lock().texture_id("tracer.png");
+
+            self.objects.push(create_info.create_object(id, Transform::default()));
+        }
+    }
+
+    pub fn update_buffers(&mut self, info: &mut UpdateBuffersInfo)
+    {
+        self.objects.iter_mut().zip(self.transforms.iter()).for_each(|(object, transform)|
+        {
+            object.set_transform(transform.clone());
+            object.update_buffers(info);
+        });
+    }
+
+    pub fn draw(&self, info: &mut DrawInfo, animation: f32)
+    {
+        self.objects.iter().take(self.transforms.len()).for_each(|object|
+        {
+            info.push_constants(OutlinedInfo::new(None, 0.0, animation).with_emissive(TRACER_EMISSIVE));
+
+            object.draw(info);
+        });
+    }
+}
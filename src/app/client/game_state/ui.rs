@@ -1,19 +1,22 @@
 use std::{
+    f32,
     rc::{Weak, Rc},
     cell::RefCell,
     sync::Arc,
-    collections::{HashMap, VecDeque}
+    collections::{HashMap, HashSet, VecDeque}
 };
 
 use nalgebra::{Vector2, Vector3};
 
+use strum::IntoEnumIterator;
+
 use yanyaengine::{Transform, FontsContainer, TextInfo, camera::Camera};
 
 use crate::{
     LONGEST_FRAME,
     client::{
         ui_element::*,
-        game_state::{UiAnatomyLocations, GameState, EntityCreator, UserEvent, UiReceiver}
+        game_state::{UiAnatomyOverlays, GameState, EntityCreator, UserEvent, UiReceiver}
     },
     common::{
         lerp,
@@ -30,11 +33,15 @@ use crate::{
         Item,
         InventoryItem,
         InventorySorter,
+        TradeOffer,
+        Haggler,
+        items_info::ItemId,
+        EquipSlot,
         Parent,
         Entity,
         ItemsInfo,
         EntityInfo,
-        entity::ClientEntities
+        entity::{ClientEntities, COMPONENT_NAMES}
     }
 };
 
@@ -58,6 +65,10 @@ const CLOSED_LIFETIME: f32 = 1.0;
 
 const DEFAULT_COLOR: [f32; 3] = [0.165, 0.161, 0.192];
 
+const ANATOMY_COLOR: [f32; 3] = [0.4; 3];
+const BLEEDING_COLOR: [f32; 3] = [0.85, 0.05, 0.05];
+const INFECTED_COLOR: [f32; 3] = [0.35, 0.7, 0.2];
+
 pub type WindowType = Weak<RefCell<UiSpecializedWindow>>;
 
 #[derive(Debug, Clone)]
@@ -867,15 +878,35 @@ impl UiInventory
             });
         }
 
+        if info.creator.entities.equipment_exists(owner)
+        {
+            custom_buttons.push(CustomButton{
+                texture: "ui/equipment_button.png",
+                on_click: Rc::new(move |game_state|
+                {
+                    game_state.add_window(WindowCreateInfo::Equipment{
+                        spawn_position: game_state.ui_mouse_position(),
+                        entity: owner
+                    });
+                })
+            });
+        }
+
         if info.creator.entities.player_exists(owner)
         {
             custom_buttons.push(CustomButton{
                 texture: "ui/stats_button.png",
                 on_click: Rc::new(move |game_state|
                 {
+                    let lifetime = (owner == game_state.player()).then(||
+                    {
+                        game_state.lifetime_stats.description()
+                    });
+
                     game_state.add_window(WindowCreateInfo::Stats{
                         spawn_position: game_state.ui_mouse_position(),
-                        entity: owner
+                        entity: owner,
+                        lifetime
                     });
                 })
             });
@@ -978,11 +1009,605 @@ impl UiInventory
     }
 }
 
+#[derive(Clone)]
+pub struct UiEquipment
+{
+    items_info: Arc<ItemsInfo>,
+    owner: Entity,
+    names: Vec<String>,
+    list: UiList,
+    window: UiWindow
+}
+
+impl UiEquipment
+{
+    fn new(
+        info: &mut CommonWindowInfo,
+        owner: Entity,
+        spawn_position: Vector2<f32>
+    ) -> Self
+    {
+        let items_info = info.ui.borrow().items_info.clone();
+
+        let window_info = UiWindowInfo{
+            name: "equipment".to_owned(),
+            spawn_position,
+            ..Default::default()
+        };
+
+        let window = UiWindow::new(info, window_info);
+
+        // every slot gets a row in the same order so the index is the slot
+        let on_change = {
+            let user_receiver = info.user_receiver.clone();
+            Rc::new(RefCell::new(move |_entity, index|
+            {
+                if let Some(slot) = EquipSlot::iter().nth(index)
+                {
+                    user_receiver.borrow_mut().push(UserEvent::Unequip(slot));
+                }
+            }))
+        };
+
+        let mut this = Self{
+            items_info,
+            owner,
+            names: Vec::new(),
+            list: UiList::new(info.creator, window.panel, 1.0 - window.button_width, on_change),
+            window
+        };
+
+        this.update_equipment(info.creator);
+
+        this
+    }
+
+    pub fn body(&self) -> Entity
+    {
+        self.window.body
+    }
+
+    fn in_render_order(&self, mut f: impl FnMut(Entity))
+    {
+        self.window.in_render_order(&mut f);
+        self.list.in_render_order(f);
+    }
+
+    fn update_equipment(&mut self, creator: &EntityCreator)
+    {
+        let names: Vec<String> = {
+            let equipment = some_or_return!(creator.entities.equipment(self.owner));
+
+            EquipSlot::iter().map(|slot|
+            {
+                let worn = equipment.get(slot).map(|item|
+                {
+                    self.items_info.get(item.id).name.as_str()
+                }).unwrap_or("nothing");
+
+                format!("{}: {worn}", slot.name())
+            }).collect()
+        };
+
+        if names != self.names
+        {
+            self.names = names.clone();
+            self.list.set_items(creator, names);
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        creator: &EntityCreator,
+        camera: &Camera,
+        dt: f32
+    )
+    {
+        self.update_equipment(creator);
+        self.list.update(creator, camera, dt);
+    }
+}
+
+#[derive(Clone)]
+pub struct UiEntityInspector
+{
+    entity: Entity,
+    // which component each row is the header of, none for the rows with values
+    headers: Rc<RefCell<Vec<Option<&'static str>>>>,
+    expanded: Rc<RefCell<HashSet<&'static str>>>,
+    rows: Vec<String>,
+    list: UiList,
+    window: UiWindow
+}
+
+impl UiEntityInspector
+{
+    fn new(
+        info: &mut CommonWindowInfo,
+        entity: Entity,
+        spawn_position: Vector2<f32>
+    ) -> Self
+    {
+        let window_info = UiWindowInfo{
+            name: format!("inspector - {entity:?}"),
+            spawn_position,
+            ..Default::default()
+        };
+
+        let window = UiWindow::new(info, window_info);
+
+        let headers: Rc<RefCell<Vec<Option<&'static str>>>> = Rc::new(RefCell::new(Vec::new()));
+        let expanded = Rc::new(RefCell::new(HashSet::new()));
+
+        // clicking a header opens or closes that section
+        let on_change = {
+            let headers = headers.clone();
+            let expanded = expanded.clone();
+
+            Rc::new(RefCell::new(move |_entity, index: usize|
+            {
+                if let Some(Some(name)) = headers.borrow().get(index)
+                {
+                    let mut expanded = expanded.borrow_mut();
+                    if !expanded.remove(name)
+                    {
+                        expanded.insert(*name);
+                    }
+                }
+            }))
+        };
+
+        let mut this = Self{
+            entity,
+            headers,
+            expanded,
+            rows: Vec::new(),
+            list: UiList::new(info.creator, window.panel, 1.0 - window.button_width, on_change),
+            window
+        };
+
+        this.update_rows(info.creator);
+
+        this
+    }
+
+    pub fn body(&self) -> Entity
+    {
+        self.window.body
+    }
+
+    fn in_render_order(&self, mut f: impl FnMut(Entity))
+    {
+        self.window.in_render_order(&mut f);
+        self.list.in_render_order(f);
+    }
+
+    fn update_rows(&mut self, creator: &EntityCreator)
+    {
+        let mut headers = Vec::new();
+        let mut rows = Vec::new();
+
+        {
+            let expanded = self.expanded.borrow();
+
+            COMPONENT_NAMES.iter().for_each(|name|
+            {
+                let value = some_or_return!(creator.entities.component_info(self.entity, name));
+
+                let is_expanded = expanded.contains(name);
+
+                headers.push(Some(*name));
+                rows.push(format!("{} {name}", if is_expanded { "-" } else { "+" }));
+
+                if is_expanded
+                {
+                    value.lines().for_each(|line|
+                    {
+                        headers.push(None);
+                        rows.push(format!("  {line}"));
+                    });
+                }
+            });
+        }
+
+        *self.headers.borrow_mut() = headers;
+
+        if rows != self.rows
+        {
+            self.rows = rows.clone();
+            self.list.set_items(creator, rows);
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        creator: &EntityCreator,
+        camera: &Camera,
+        dt: f32
+    )
+    {
+        self.update_rows(creator);
+        self.list.update(creator, camera, dt);
+    }
+}
+
+#[derive(Clone)]
+pub struct UiDialogue
+{
+    speaker: Entity,
+    player: Entity,
+    // which choice each row picks, none for the rows with the speakers text
+    choice_indices: Rc<RefCell<Vec<Option<usize>>>>,
+    rows: Vec<String>,
+    list: UiList,
+    window: UiWindow
+}
+
+impl UiDialogue
+{
+    fn new(
+        info: &mut CommonWindowInfo,
+        player: Entity,
+        speaker: Entity,
+        spawn_position: Vector2<f32>
+    ) -> Self
+    {
+        let name = info.creator.entities.named(speaker)
+            .map(|name| name.clone())
+            .unwrap_or_else(|| "someone".to_owned());
+
+        let window_info = UiWindowInfo{
+            name,
+            spawn_position,
+            ..Default::default()
+        };
+
+        let window = UiWindow::new(info, window_info);
+
+        let choice_indices: Rc<RefCell<Vec<Option<usize>>>> = Rc::new(RefCell::new(Vec::new()));
+
+        let on_change = {
+            let user_receiver = info.user_receiver.clone();
+            let choice_indices = choice_indices.clone();
+
+            Rc::new(RefCell::new(move |_entity, index: usize|
+            {
+                if let Some(Some(choice)) = choice_indices.borrow().get(index)
+                {
+                    user_receiver.borrow_mut().push(UserEvent::DialogueChoice{speaker, choice: *choice});
+                }
+            }))
+        };
+
+        let mut this = Self{
+            speaker,
+            player,
+            choice_indices,
+            rows: Vec::new(),
+            list: UiList::new(info.creator, window.panel, 1.0 - window.button_width, on_change),
+            window
+        };
+
+        this.update_rows(info.creator);
+
+        this
+    }
+
+    pub fn body(&self) -> Entity
+    {
+        self.window.body
+    }
+
+    fn in_render_order(&self, mut f: impl FnMut(Entity))
+    {
+        self.window.in_render_order(&mut f);
+        self.list.in_render_order(f);
+    }
+
+    fn update_rows(&mut self, creator: &EntityCreator)
+    {
+        let entities = &*creator.entities;
+
+        let mut choice_indices = Vec::new();
+        let mut rows = Vec::new();
+
+        let node = entities.dialogue(self.speaker).and_then(|dialogue|
+        {
+            dialogue.talking_to(self.player).then(||
+            {
+                dialogue.current_node(&entities.infos().dialogues_info).cloned()
+            }).flatten()
+        });
+
+        if let Some(node) = node
+        {
+            node.text.lines().for_each(|line|
+            {
+                choice_indices.push(None);
+                rows.push(line.to_owned());
+            });
+
+            node.choices.iter().enumerate().filter(|(_, choice)|
+            {
+                choice.available(entities, self.player, self.speaker)
+            }).enumerate().for_each(|(shown, (index, choice))|
+            {
+                choice_indices.push(Some(index));
+                rows.push(format!("{}. {}", shown + 1, choice.text));
+            });
+        } else
+        {
+            choice_indices.push(None);
+            rows.push("...".to_owned());
+        }
+
+        *self.choice_indices.borrow_mut() = choice_indices;
+
+        if rows != self.rows
+        {
+            self.rows = rows.clone();
+            self.list.set_items(creator, rows);
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        creator: &EntityCreator,
+        camera: &Camera,
+        dt: f32
+    )
+    {
+        self.update_rows(creator);
+        self.list.update(creator, camera, dt);
+    }
+}
+
+#[derive(Clone)]
+pub struct UiTrade
+{
+    items_info: Arc<ItemsInfo>,
+    player: Entity,
+    vendor: Entity,
+    offer: Rc<RefCell<TradeOffer>>,
+    // which item each row is, none for the header
+    player_items: Rc<RefCell<Vec<Option<InventoryItem>>>>,
+    vendor_items: Rc<RefCell<Vec<Option<InventoryItem>>>>,
+    inventories: (Vec<(InventoryItem, ItemId)>, Vec<(InventoryItem, ItemId)>),
+    rows: (Vec<String>, Vec<String>),
+    player_list: UiList,
+    vendor_list: UiList,
+    window: UiWindow
+}
+
+impl UiTrade
+{
+    fn new(
+        info: &mut CommonWindowInfo,
+        player: Entity,
+        vendor: Entity,
+        spawn_position: Vector2<f32>
+    ) -> Self
+    {
+        let items_info = info.ui.borrow().items_info.clone();
+
+        let name = info.creator.entities.named(vendor)
+            .map(|name| format!("trading with {}", *name))
+            .unwrap_or_else(|| "trading".to_owned());
+
+        let window_info = UiWindowInfo{
+            name,
+            spawn_position,
+            size: Vector2::new(WINDOW_WIDTH * 2.0, WINDOW_HEIGHT),
+            ..Default::default()
+        };
+
+        let window = UiWindow::new(info, window_info);
+
+        // ur stuff on the left, theirs on the right
+        let mut half = |side: Vector3<f32>|
+        {
+            let scale = Vector3::new(0.5, 1.0, 1.0);
+
+            info.creator.push(
+                EntityInfo{
+                    lazy_transform: Some(LazyTransformInfo{
+                        transform: Transform{
+                            position: Ui::ui_position(scale, side),
+                            scale,
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    }.into()),
+                    parent: Some(Parent::new(window.panel, true)),
+                    ..Default::default()
+                },
+                None
+            )
+        };
+
+        let player_panel = half(Vector3::zeros());
+        let vendor_panel = half(Vector3::x());
+
+        let offer = Rc::new(RefCell::new(TradeOffer::default()));
+
+        let player_items: Rc<RefCell<Vec<Option<InventoryItem>>>> = Rc::new(RefCell::new(Vec::new()));
+        let vendor_items: Rc<RefCell<Vec<Option<InventoryItem>>>> = Rc::new(RefCell::new(Vec::new()));
+
+        let on_player_change = {
+            let offer = offer.clone();
+            let player_items = player_items.clone();
+
+            Rc::new(RefCell::new(move |_entity, index: usize|
+            {
+                if let Some(Some(item)) = player_items.borrow().get(index)
+                {
+                    offer.borrow_mut().toggle_given(*item);
+                }
+            }))
+        };
+
+        // the header on their side sends the offer
+        let on_vendor_change = {
+            let user_receiver = info.user_receiver.clone();
+            let offer = offer.clone();
+            let vendor_items = vendor_items.clone();
+
+            Rc::new(RefCell::new(move |_entity, index: usize|
+            {
+                match vendor_items.borrow().get(index)
+                {
+                    Some(Some(item)) => offer.borrow_mut().toggle_taken(*item),
+                    Some(None) =>
+                    {
+                        let offer = offer.borrow().clone();
+                        if !offer.is_empty()
+                        {
+                            user_receiver.borrow_mut().push(UserEvent::TradeOffer{vendor, offer});
+                        }
+                    },
+                    None => ()
+                }
+            }))
+        };
+
+        let list_width = 1.0 - window.button_width * 2.0;
+
+        let mut this = Self{
+            items_info,
+            player,
+            vendor,
+            offer,
+            player_items,
+            vendor_items,
+            inventories: (Vec::new(), Vec::new()),
+            rows: (Vec::new(), Vec::new()),
+            player_list: UiList::new(info.creator, player_panel, list_width, on_player_change),
+            vendor_list: UiList::new(info.creator, vendor_panel, list_width, on_vendor_change),
+            window
+        };
+
+        this.update_rows(info.creator);
+
+        this
+    }
+
+    pub fn body(&self) -> Entity
+    {
+        self.window.body
+    }
+
+    fn in_render_order(&self, mut f: impl FnMut(Entity))
+    {
+        self.window.in_render_order(&mut f);
+        self.player_list.in_render_order(&mut f);
+        self.vendor_list.in_render_order(f);
+    }
+
+    fn update_rows(&mut self, creator: &EntityCreator)
+    {
+        let entities = &*creator.entities;
+
+        let inventory = |entity| -> Vec<(InventoryItem, ItemId)>
+        {
+            entities.inventory(entity).map(|inventory|
+            {
+                inventory.items_ids().map(|(index, item)| (index, item.id)).collect()
+            }).unwrap_or_default()
+        };
+
+        let inventories = (inventory(self.player), inventory(self.vendor));
+
+        // anything moving around makes the picked indices point at the wrong stuff
+        if inventories != self.inventories
+        {
+            self.inventories = inventories;
+            *self.offer.borrow_mut() = TradeOffer::default();
+        }
+
+        let offer = self.offer.borrow().clone();
+
+        let (offered, asked) = {
+            let haggler = entities.player(self.player).and_then(|player|
+            {
+                entities.character(self.vendor).map(|character| Haggler::new(&player, character.faction))
+            });
+
+            let infos = |items: &[(InventoryItem, ItemId)], picked: &[InventoryItem]|
+            {
+                items.iter().filter(|(index, _)| picked.contains(index))
+                    .map(|(_, id)| self.items_info.get(*id))
+                    .collect::<Vec<_>>()
+            };
+
+            let given = infos(&self.inventories.0, &offer.given);
+            let taken = infos(&self.inventories.1, &offer.taken);
+
+            entities.enemy(self.vendor).and_then(|enemy|
+            {
+                let vendor = enemy.vendor()?;
+
+                Some((
+                    vendor.offered_value(given.into_iter()),
+                    vendor.asking_price(&haggler?, taken.into_iter())
+                ))
+            }).unwrap_or((0.0, 0.0))
+        };
+
+        let side = |items: &[(InventoryItem, ItemId)], picked: &[InventoryItem], header: String|
+        {
+            let mut indices = vec![None];
+            let mut rows = vec![header];
+
+            items.iter().for_each(|(index, id)|
+            {
+                let marker = if picked.contains(index) { "> " } else { "" };
+
+                indices.push(Some(*index));
+                rows.push(format!("{marker}{}", self.items_info.get(*id).name));
+            });
+
+            (indices, rows)
+        };
+
+        let (player_items, player_rows) = side(&self.inventories.0, &offer.given, format!("offering {offered:.0}"));
+        let (vendor_items, vendor_rows) = side(&self.inventories.1, &offer.taken, format!("trade for {asked:.0}"));
+
+        *self.player_items.borrow_mut() = player_items;
+        *self.vendor_items.borrow_mut() = vendor_items;
+
+        if player_rows != self.rows.0
+        {
+            self.rows.0 = player_rows.clone();
+            self.player_list.set_items(creator, player_rows);
+        }
+
+        if vendor_rows != self.rows.1
+        {
+            self.rows.1 = vendor_rows.clone();
+            self.vendor_list.set_items(creator, vendor_rows);
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        creator: &EntityCreator,
+        camera: &Camera,
+        dt: f32
+    )
+    {
+        self.update_rows(creator);
+        self.player_list.update(creator, camera, dt);
+        self.vendor_list.update(creator, camera, dt);
+    }
+}
+
 #[derive(Clone)]
 pub struct UiAnatomy
 {
     window: UiWindow,
-    anatomy_entities: Vec<Entity>
+    entity: Entity,
+    anatomy_entities: Vec<(AnatomyPartId, Entity)>,
+    pulse: f32
 }
 
 impl UiAnatomy
@@ -993,8 +1618,17 @@ impl UiAnatomy
         entity: Entity
     ) -> Self
     {
+        let (plan, parts) = {
+            let anatomy = common_info.creator.entities.anatomy(entity);
+
+            let plan = anatomy.as_ref().and_then(|x| x.plan_name().map(ToOwned::to_owned));
+            let parts = anatomy.map(|x| x.parts()).unwrap_or_default();
+
+            (plan, parts)
+        };
+
         let ui = common_info.ui.borrow();
-        let anatomy_locations = &ui.anatomy_locations;
+        let anatomy_locations = ui.anatomy_locations.get(plan.as_deref());
 
         let window_info = UiWindowInfo{
             name: "anatomy".to_owned(),
@@ -1009,18 +1643,18 @@ impl UiAnatomy
 
         let ui = &common_info.ui;
         let ui_ref = ui.borrow();
-        let anatomy_locations = &ui_ref.anatomy_locations;
+        let anatomy_locations = ui_ref.anatomy_locations.get(plan.as_deref());
 
-        let anatomy_entities = HumanPartId::iter().map(|id|
+        let anatomy_entities = parts.into_iter().filter_map(|id|
         {
-            (id, &anatomy_locations.locations[&id])
+            anatomy_locations.locations.get(&id).map(|location| (id, location))
         }).map(|(id, location)|
         {
             let ui = ui.clone();
-            let mut lazy_mix = LazyMix::ui_color([0.4; 3]);
+            let mut lazy_mix = LazyMix::ui_color(ANATOMY_COLOR);
             lazy_mix.target.keep_transparency = true;
 
-            common_info.creator.push(
+            let part = common_info.creator.push(
                 EntityInfo{
                     lazy_transform: Some(LazyTransformInfo{
                         transform: Transform{
@@ -1052,19 +1686,57 @@ impl UiAnatomy
                     z_level: ZLevel::Ui,
                     ..Default::default()
                 }
-            )
+            );
+
+            (id, part)
         }).collect::<Vec<_>>();
 
         Self{
             window,
-            anatomy_entities
+            entity,
+            anatomy_entities,
+            pulse: 0.0
         }
     }
 
+    fn update(&mut self, creator: &mut EntityCreator, dt: f32)
+    {
+        self.pulse = (self.pulse + dt * 1.5).fract();
+
+        let entities = &*creator.entities;
+        let anatomy = some_or_return!(entities.anatomy(self.entity));
+
+        let pulse = (self.pulse * f32::consts::TAU).sin() * 0.5 + 0.5;
+
+        self.anatomy_entities.iter().for_each(|(id, part)|
+        {
+            let (bleeding, infection) = anatomy.part_status(*id)
+                .map(|x| (x.bleeding > 0.0, x.infection))
+                .unwrap_or((false, 0.0));
+
+            let mut lazy_mix = some_or_return!(entities.lazy_mix_mut(*part));
+
+            if bleeding
+            {
+                lazy_mix.target.color = BLEEDING_COLOR;
+                lazy_mix.target.amount = 0.3 + pulse * 0.5;
+            } else if infection > 0.0
+            {
+                lazy_mix.target.color = INFECTED_COLOR;
+                lazy_mix.target.amount = 0.15 + infection * 0.2;
+            } else if lazy_mix.target.color != ANATOMY_COLOR
+            {
+                // healed up, hovering takes over again
+                lazy_mix.target.color = ANATOMY_COLOR;
+                lazy_mix.target.amount = 0.0;
+            }
+        });
+    }
+
     fn in_render_order(&self, mut f: impl FnMut(Entity))
     {
         self.window.in_render_order(&mut f);
-        self.anatomy_entities.iter().copied().for_each(f);
+        self.anatomy_entities.iter().map(|(_, entity)| *entity).for_each(f);
     }
 
     pub fn body(&self) -> Entity
@@ -1085,7 +1757,8 @@ impl UiStats
     fn new(
         common_info: &mut CommonWindowInfo,
         spawn_position: Vector2<f32>,
-        entity: Entity
+        entity: Entity,
+        lifetime: Option<String>
     ) -> Self
     {
         let window_info = UiWindowInfo{
@@ -1098,6 +1771,35 @@ impl UiStats
 
         let padding = 0.05;
 
+        let text = common_info.creator.entities.player(entity).map(|player|
+        {
+            let perks = if player.perks.is_empty()
+            {
+                "none".to_owned()
+            } else
+            {
+                player.perks.join(", ")
+            };
+
+            format!(
+                "level {} ({:.0}/{:.0})\nperk points: {}\nperks: {perks}\ncharisma: {:.1}\n\nreputation\n{}",
+                player.level,
+                player.experience,
+                player.experience_needed(),
+                player.perk_points,
+                player.charisma,
+                player.reputation.description()
+            )
+        }).unwrap_or_default();
+
+        let text = if let Some(lifetime) = lifetime
+        {
+            format!("{text}\n\n{lifetime}")
+        } else
+        {
+            text
+        };
+
         let temp = common_info.creator.push(
             EntityInfo{
                 lazy_transform: Some(LazyTransformInfo{
@@ -1112,7 +1814,7 @@ impl UiStats
             },
             RenderInfo{
                 object: Some(RenderObjectKind::Text{
-                    text: "this will have stats later!!!!!!!".to_owned(),
+                    text,
                     font_size: 15,
                     font: FontStyle::Bold,
                     align: TextAlign::default()
@@ -1454,7 +2156,9 @@ pub enum NotificationSeverity
     Normal,
     DamageMinor,
     Damage,
-    DamageMajor
+    DamageMajor,
+    Hint,
+    Achievement
 }
 
 impl NotificationSeverity
@@ -1466,7 +2170,9 @@ impl NotificationSeverity
             Self::Normal => DEFAULT_COLOR,
             Self::DamageMinor => [1.0, 0.727, 0.349], // wysi
             Self::Damage => [0.995, 0.367, 0.367],
-            Self::DamageMajor => [0.765, 0.0, 0.423]
+            Self::DamageMajor => [0.765, 0.0, 0.423],
+            Self::Hint => [0.55, 0.8, 1.0],
+            Self::Achievement => [1.0, 0.85, 0.3]
         }
     }
 }
@@ -1701,7 +2407,7 @@ impl Notification
 #[derive(Clone)]
 pub struct AnatomyTooltip
 {
-    current: HumanPartId,
+    current: AnatomyPartId,
     body: Entity,
     top_panel: Entity,
     name_entity: Entity,
@@ -1716,14 +2422,22 @@ impl AnatomyTooltip
         previous_size: Option<Vector2<f32>>,
         mouse: Entity,
         entity: Entity,
-        id: HumanPartId
+        id: AnatomyPartId
     ) -> Self
     {
         let padding = 0.2;
 
         let fit = 3;
 
-        let bars = if let HumanPartId::Eye(_) = id
+        let (name, status) = info.creator.entities.anatomy(entity).map(|anatomy|
+        {
+            (anatomy.part_name(id), anatomy.part_status(id))
+        }).unwrap_or_default();
+
+        // eyes and such are just one squishy layer
+        let bare = status.map(|x| x.skin.is_none() && x.muscle.is_none()).unwrap_or(false);
+
+        let bars = if bare
         {
             vec!["eye"]
         } else
@@ -1846,7 +2560,7 @@ impl AnatomyTooltip
             },
             RenderInfo{
                 object: Some(RenderObjectKind::Text{
-                    text: id.to_string(),
+                    text: name.unwrap_or_default(),
                     font_size: 20,
                     font: FontStyle::Bold,
                     align: TextAlign::centered()
@@ -1920,30 +2634,22 @@ impl AnatomyTooltip
         &self,
         entities: &ClientEntities,
         entity: Entity,
-        id: HumanPartId
+        id: AnatomyPartId
     )
     {
-        let anatomy = entities.anatomy(entity);
-        let part = anatomy.as_ref().and_then(|x| x.get_human(id).unwrap());
-
-        let hp_of = |part: Option<Health>| -> f32
-        {
-            part.map(|x| x.fraction()).unwrap_or_default()
-        };
+        let status = entities.anatomy(entity).and_then(|x| x.part_status(id));
 
         self.bars.iter().enumerate().for_each(|(index, bar)|
         {
-            let part = match index
+            let hp = match index
             {
-                0 => part.map(|x| *x.bone),
-                1 => part.and_then(|x| *x.muscle),
-                2 => part.and_then(|x| *x.skin),
+                0 => status.map(|x| x.bone),
+                1 => status.and_then(|x| x.muscle),
+                2 => status.and_then(|x| x.skin),
                 _ => unreachable!()
             };
 
-            let hp = hp_of(part);
-
-            bar.set_amount(entities, hp);
+            bar.set_amount(entities, hp.unwrap_or_default());
         });
     }
 
@@ -1952,7 +2658,7 @@ impl AnatomyTooltip
         self.bars.iter().for_each(|bar| bar.update(entities));
     }
 
-    pub fn current(&self) -> HumanPartId
+    pub fn current(&self) -> AnatomyPartId
     {
         self.current
     }
@@ -2252,8 +2958,12 @@ pub enum WindowCreateInfo
     Notification{owner: Entity, lifetime: f32, info: NotificationCreateInfo},
     Tooltip{closing_animation: bool, previous_size: Option<Vector2<f32>>, info: TooltipCreateInfo},
     Anatomy{spawn_position: Vector2<f32>, entity: Entity},
-    Stats{spawn_position: Vector2<f32>, entity: Entity},
+    Stats{spawn_position: Vector2<f32>, entity: Entity, lifetime: Option<String>},
     ItemInfo{spawn_position: Vector2<f32>, item: Item},
+    Equipment{spawn_position: Vector2<f32>, entity: Entity},
+    EntityInspector{spawn_position: Vector2<f32>, entity: Entity},
+    Dialogue{spawn_position: Vector2<f32>, player: Entity, speaker: Entity},
+    Trade{spawn_position: Vector2<f32>, player: Entity, vendor: Entity},
     Inventory{
         spawn_position: Vector2<f32>,
         entity: Entity,
@@ -2264,7 +2974,7 @@ pub enum WindowCreateInfo
 #[derive(Debug, Clone)]
 pub enum TooltipCreateInfo
 {
-    Anatomy{entity: Entity, id: HumanPartId}
+    Anatomy{entity: Entity, id: AnatomyPartId}
 }
 
 #[derive(Clone)]
@@ -2276,6 +2986,10 @@ pub enum UiSpecializedWindow
     Anatomy(UiAnatomy),
     Stats(UiStats),
     ItemInfo(UiItemInfo),
+    Equipment(UiEquipment),
+    EntityInspector(UiEntityInspector),
+    Dialogue(UiDialogue),
+    Trade(UiTrade),
     Inventory(UiInventory)
 }
 
@@ -2297,6 +3011,10 @@ impl UiSpecializedWindow
             Self::Anatomy(x) => x.body(),
             Self::Stats(x) => x.body(),
             Self::ItemInfo(x) => x.body(),
+            Self::Equipment(x) => x.body(),
+            Self::EntityInspector(x) => x.body(),
+            Self::Dialogue(x) => x.body(),
+            Self::Trade(x) => x.body(),
             Self::Inventory(x) => x.body()
         }
     }
@@ -2311,6 +3029,10 @@ impl UiSpecializedWindow
             Self::Anatomy(x) => x.in_render_order(f),
             Self::Stats(x) => x.in_render_order(f),
             Self::ItemInfo(x) => x.in_render_order(f),
+            Self::Equipment(x) => x.in_render_order(f),
+            Self::EntityInspector(x) => x.in_render_order(f),
+            Self::Dialogue(x) => x.in_render_order(f),
+            Self::Trade(x) => x.in_render_order(f),
             Self::Inventory(x) => x.in_render_order(f)
         }
     }
@@ -2327,9 +3049,13 @@ impl UiSpecializedWindow
             Self::ActionsList(_) => (),
             Self::Notification(x) => x.kind.update(creator.entities),
             Self::Tooltip(x) => x.update(creator.entities),
-            Self::Anatomy(_) => (),
+            Self::Anatomy(x) => x.update(creator, dt),
             Self::Stats(_) => (),
             Self::ItemInfo(_) => (),
+            Self::Equipment(x) => x.update(creator, camera, dt),
+            Self::EntityInspector(x) => x.update(creator, camera, dt),
+            Self::Dialogue(x) => x.update(creator, camera, dt),
+            Self::Trade(x) => x.update(creator, camera, dt),
             Self::Inventory(x) => x.update(creator, camera, dt)
         }
     }
@@ -2347,7 +3073,7 @@ pub struct Ui
     fonts: Rc<FontsContainer>,
     mouse: Entity,
     console: Entity,
-    anatomy_locations: UiAnatomyLocations,
+    anatomy_locations: UiAnatomyOverlays,
     user_receiver: Rc<RefCell<UiReceiver>>,
     notifications: HashMap<Entity, Vec<UiWindowId>>,
     active_popup: Option<UiWindowId>,
@@ -2364,7 +3090,7 @@ impl Ui
         fonts: Rc<FontsContainer>,
         entities: &mut ClientEntities,
         mouse: Entity,
-        anatomy_locations: UiAnatomyLocations,
+        anatomy_locations: UiAnatomyOverlays,
         user_receiver: Rc<RefCell<UiReceiver>>
     ) -> Rc<RefCell<Self>>
     {
@@ -2631,6 +3357,10 @@ impl Ui
                     UiSpecializedWindow::Anatomy(_) => (),
                     UiSpecializedWindow::Stats(_) => (),
                     UiSpecializedWindow::ItemInfo(_) => (),
+                    UiSpecializedWindow::Equipment(_) => (),
+                    UiSpecializedWindow::EntityInspector(_) => (),
+                    UiSpecializedWindow::Dialogue(_) => (),
+                    UiSpecializedWindow::Trade(_) => (),
                     UiSpecializedWindow::Inventory(_) => ()
                 }
 
@@ -2710,12 +3440,13 @@ impl Ui
                     entity
                 ))
             },
-            WindowCreateInfo::Stats{spawn_position, entity} =>
+            WindowCreateInfo::Stats{spawn_position, entity, lifetime} =>
             {
                 UiSpecializedWindow::Stats(UiStats::new(
                     &mut window_info,
                     spawn_position,
-                    entity
+                    entity,
+                    lifetime
                 ))
             },
             WindowCreateInfo::ItemInfo{spawn_position, item} =>
@@ -2726,6 +3457,40 @@ impl Ui
                     item
                 ))
             },
+            WindowCreateInfo::Equipment{spawn_position, entity} =>
+            {
+                UiSpecializedWindow::Equipment(UiEquipment::new(
+                    &mut window_info,
+                    entity,
+                    spawn_position
+                ))
+            },
+            WindowCreateInfo::EntityInspector{spawn_position, entity} =>
+            {
+                UiSpecializedWindow::EntityInspector(UiEntityInspector::new(
+                    &mut window_info,
+                    entity,
+                    spawn_position
+                ))
+            },
+            WindowCreateInfo::Dialogue{spawn_position, player, speaker} =>
+            {
+                UiSpecializedWindow::Dialogue(UiDialogue::new(
+                    &mut window_info,
+                    player,
+                    speaker,
+                    spawn_position
+                ))
+            },
+            WindowCreateInfo::Trade{spawn_position, player, vendor} =>
+            {
+                UiSpecializedWindow::Trade(UiTrade::new(
+                    &mut window_info,
+                    player,
+                    vendor,
+                    spawn_position
+                ))
+            },
             WindowCreateInfo::Inventory{spawn_position, entity, mut on_click} =>
             {
                 let urx = window_info.user_receiver.clone();
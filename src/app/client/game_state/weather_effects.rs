@@ -0,0 +1,306 @@
+use std::f32;
+
+use nalgebra::{Vector2, Vector3};
+
+use yanyaengine::{
+    Object,
+    Transform,
+    TransformContainer,
+    game_object::*
+};
+
+use crate::{
+    client::RenderCreateInfo,
+    common::{
+        some_or_return,
+        render_info::*,
+        rng::{self, RngStream},
+        EaseOut,
+        Weather,
+        WeatherKind,
+        world::TILE_SIZE
+    }
+};
+
+
+const MAX_DROPS: usize = 400;
+// fog is a few big blobs instead of lots of little drops
+const MAX_FOG_CLOUDS: usize = 40;
+const MAX_PUDDLES: usize = 24;
+
+const WETNESS_DECAY: f32 = 0.1;
+
+// how far from the camera (in screen sizes) puddles stay around
+const PUDDLE_RANGE: f32 = 1.5;
+
+const WET_TINT: [f32; 3] = [0.2, 0.25, 0.35];
+
+fn random_in(size: Vector2<f32>) -> Vector2<f32>
+{
+    Vector2::new(rng::f32(RngStream::Cosmetic) - 0.5, rng::f32(RngStream::Cosmetic) - 0.5).component_mul(&size)
+}
+
+fn drop_velocity(weather: &Weather, speed: f32) -> Vector2<f32>
+{
+    let (fall, wind) = match weather.kind
+    {
+        WeatherKind::Snow => (0.3, 0.6),
+        WeatherKind::Fog => (0.0, 1.0),
+        _ => (1.0, 0.4)
+    };
+
+    (Vector2::new(0.0, fall) + weather.wind * wind) * speed
+}
+
+fn wrap(x: f32, size: f32) -> f32
+{
+    let half = size / 2.0;
+
+    (x + half).rem_euclid(size) - half
+}
+
+struct WeatherDrop
+{
+    // relative to the camera so they stay on screen
+    offset: Vector2<f32>,
+    speed: f32,
+    object: Object
+}
+
+struct Puddle
+{
+    position: Vector3<f32>,
+    rotation: f32,
+    scale: f32,
+    object: Object
+}
+
+pub struct WeatherEffects
+{
+    weather: Weather,
+    wetness: f32,
+    center: Vector3<f32>,
+    drops: Vec<WeatherDrop>,
+    puddles: Vec<Puddle>
+}
+
+impl WeatherEffects
+{
+    pub fn new() -> Self
+    {
+        Self{
+            weather: Weather::default(),
+            wetness: 0.0,
+            center: Vector3::zeros(),
+            drops: Vec::new(),
+            puddles: Vec::new()
+        }
+    }
+
+    pub fn set_weather(&mut self, weather: Weather)
+    {
+        if weather.kind != self.weather.kind
+        {
+            self.drops.clear();
+        }
+
+        self.weather = weather;
+    }
+
+    pub fn world_info(&self) -> WorldInfo
+    {
+        WorldInfo::new(WET_TINT, self.wetness * 0.3)
+    }
+
+    fn texture(&self) -> Option<&'static str>
+    {
+        match self.weather.kind
+        {
+            WeatherKind::Clear => None,
+            WeatherKind::Rain => Some("weather/rain.png"),
+            WeatherKind::Snow => Some("weather/snow.png"),
+            WeatherKind::Fog => Some("weather/fog.png")
+        }
+    }
+
+    fn drop_transform(&self, drop: &WeatherDrop) -> Transform
+    {
+        let scale = match self.weather.kind
+        {
+            WeatherKind::Rain => Vector3::new(TILE_SIZE * 0.02, TILE_SIZE * 0.3, 1.0),
+            WeatherKind::Fog => Vector3::repeat(TILE_SIZE * 4.0),
+            _ => Vector3::repeat(TILE_SIZE * 0.08)
+        };
+
+        let velocity = drop_velocity(&self.weather, drop.speed);
+
+        // the rain texture points down, snow is round so it doesnt matter
+        let rotation = velocity.y.atan2(velocity.x) - f32::consts::FRAC_PI_2;
+
+        Transform{
+            position: self.center + Vector3::new(drop.offset.x, drop.offset.y, 0.0),
+            rotation,
+            scale,
+            ..Default::default()
+        }
+    }
+
+    fn puddle_transform(&self, puddle: &Puddle) -> Transform
+    {
+        let scale = puddle.scale * self.wetness;
+
+        Transform{
+            position: puddle.position,
+            rotation: puddle.rotation,
+            scale: Vector3::new(scale, scale * 0.6, 1.0),
+            ..Default::default()
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        create_info: &mut RenderCreateInfo,
+        center: Vector3<f32>,
+        size: Vector2<f32>,
+        dt: f32
+    )
+    {
+        self.center = center;
+
+        let target_wetness = if self.weather.kind == WeatherKind::Rain
+        {
+            self.weather.intensity
+        } else
+        {
+            0.0
+        };
+
+        self.wetness = self.wetness.ease_out(target_wetness, WETNESS_DECAY, dt);
+
+        self.update_drops(create_info, size, dt);
+        self.update_puddles(create_info, size);
+    }
+
+    fn update_drops(&mut self, create_info: &mut RenderCreateInfo, size: Vector2<f32>, dt: f32)
+    {
+        let max_amount = if self.weather.kind == WeatherKind::Fog { MAX_FOG_CLOUDS } else { MAX_DROPS };
+
+        let amount = (max_amount as f32 * self.weather.intensity) as usize;
+
+        self.drops.truncate(amount);
+
+        let texture = some_or_return!(self.texture());
+
+        let base_speed = match self.weather.kind
+        {
+            WeatherKind::Snow => TILE_SIZE * 1.5,
+            WeatherKind::Fog => TILE_SIZE * 0.3,
+            _ => TILE_SIZE * 12.0
+        };
+
+        while self.drops.len() < amount
+        {
+            let id = create_info.object_info.partial.assets.lock().texture_id(texture);
+
+            self.drops.push(WeatherDrop{
+                offset: random_in(size),
+                speed: base_speed * (0.7 + rng::f32(RngStream::Cosmetic) * 0.6),
+                object: create_info.create_object(id, Transform::default())
+            });
+        }
+
+        let weather = self.weather;
+        self.drops.iter_mut().for_each(|drop|
+        {
+            let mut velocity = drop_velocity(&weather, drop.speed);
+
+            if weather.kind == WeatherKind::Snow
+            {
+                // drifts side to side a bit
+                velocity.x += (drop.offset.y / TILE_SIZE + drop.speed).sin() * TILE_SIZE * 0.3;
+            }
+
+            drop.offset += velocity * dt;
+
+            drop.offset = Vector2::new(wrap(drop.offset.x, size.x), wrap(drop.offset.y, size.y));
+        });
+    }
+
+    fn update_puddles(&mut self, create_info: &mut RenderCreateInfo, size: Vector2<f32>)
+    {
+        let range = size * PUDDLE_RANGE;
+
+        let center = self.center;
+        self.puddles.retain(|puddle|
+        {
+            let distance = (puddle.position - center).xy().abs();
+
+            let same_floor = (puddle.position.z / TILE_SIZE).floor() == (center.z / TILE_SIZE).floor();
+
+            same_floor && distance.x < range.x && distance.y < range.y
+        });
+
+        if self.wetness < 0.01
+        {
+            self.puddles.clear();
+
+            return;
+        }
+
+        let amount = (MAX_PUDDLES as f32 * self.wetness).ceil() as usize;
+
+        while self.puddles.len() < amount
+        {
+            let id = create_info.object_info.partial.assets.lock().texture_id("weather/puddle.png");
+
+            let offset = random_in(range * 2.0);
+
+            let puddle = Puddle{
+                position: center + Vector3::new(offset.x, offset.y, 0.0),
+                rotation: rng::f32(RngStream::Cosmetic) * (f32::consts::PI * 2.0),
+                scale: TILE_SIZE * (0.5 + rng::f32(RngStream::Cosmetic)),
+                object: create_info.create_object(id, Transform::default())
+            };
+
+            self.puddles.push(puddle);
+        }
+    }
+
+    pub fn update_buffers(&mut self, info: &mut UpdateBuffersInfo)
+    {
+        let transforms: Vec<_> = self.drops.iter().map(|drop| self.drop_transform(drop)).collect();
+        self.drops.iter_mut().zip(transforms).for_each(|(drop, transform)|
+        {
+            drop.object.set_transform(transform);
+            drop.object.update_buffers(info);
+        });
+
+        let transforms: Vec<_> = self.puddles.iter().map(|puddle| self.puddle_transform(puddle)).collect();
+        self.puddles.iter_mut().zip(transforms).for_each(|(puddle, transform)|
+        {
+            puddle.object.set_transform(transform);
+            puddle.object.update_buffers(info);
+        });
+    }
+
+    // puddles sit on the floor under everything else
+    pub fn draw_ground(&self, info: &mut DrawInfo, animation: f32)
+    {
+        self.puddles.iter().for_each(|puddle|
+        {
+            info.push_constants(OutlinedInfo::new(None, 0.0, animation));
+
+            puddle.object.draw(info);
+        });
+    }
+
+    pub fn draw(&self, info: &mut DrawInfo, animation: f32)
+    {
+        self.drops.iter().for_each(|drop|
+        {
+            info.push_constants(OutlinedInfo::new(None, 0.0, animation));
+
+            drop.object.draw(info);
+        });
+    }
+}
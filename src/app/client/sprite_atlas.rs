@@ -0,0 +1,248 @@
+use std::{
+    fs,
+    cmp::Reverse,
+    sync::Arc,
+    collections::HashMap,
+    path::Path
+};
+
+use parking_lot::RwLock;
+
+use image::DynamicImage;
+
+use yanyaengine::{
+    Assets,
+    ResourceUploader,
+    ShaderId,
+    UniformLocation,
+    object::{
+        Texture,
+        texture::{Color, SimpleImage}
+    }
+};
+
+use crate::common::overrides::overridden;
+
+
+const ATLAS_WIDTH: u32 = 2048;
+const PADDING: u32 = 2;
+
+// the ui gets drawn on its own and the backgrounds r way too big to be worth it
+const ATLAS_FOLDERS: [&str; 6] = ["decals", "enemy", "furniture", "items", "player", "weather"];
+
+// where a texture ended up, the sprites themselves keep their own textures and only batches use these
+#[derive(Debug, Clone, Copy)]
+pub struct AtlasRegion
+{
+    pub page: usize,
+    pub start: [f32; 2],
+    pub end: [f32; 2]
+}
+
+struct PackedImage
+{
+    name: String,
+    image: DynamicImage,
+    x: u32,
+    y: u32
+}
+
+#[derive(Default)]
+struct AtlasPage
+{
+    images: Vec<PackedImage>,
+    height: u32
+}
+
+#[derive(Debug)]
+pub struct SpriteAtlas
+{
+    pages: Vec<Arc<RwLock<Texture>>>,
+    // keyed by the address of the texture the sprite was loaded with
+    regions: HashMap<usize, AtlasRegion>
+}
+
+impl SpriteAtlas
+{
+    pub fn new(
+        resource_uploader: &mut ResourceUploader,
+        assets: &Assets,
+        shader: ShaderId,
+        textures_root: impl AsRef<Path>
+    ) -> Self
+    {
+        let textures_root = textures_root.as_ref();
+
+        let mut images = Vec::new();
+        ATLAS_FOLDERS.iter().for_each(|folder|
+        {
+            Self::load_images(&mut images, textures_root, Path::new(folder));
+        });
+
+        let mut pages = Vec::new();
+        let mut regions = HashMap::new();
+
+        Self::pack(images).into_iter().enumerate().for_each(|(index, page)|
+        {
+            let (texture, page_regions) = Self::create_page(resource_uploader, shader, index, page);
+
+            pages.push(texture);
+
+            regions.extend(page_regions.into_iter().map(|(name, region)|
+            {
+                (Self::texture_key(assets.texture(assets.texture_id(&name))), region)
+            }));
+        });
+
+        Self{pages, regions}
+    }
+
+    fn texture_key(texture: &Arc<RwLock<Texture>>) -> usize
+    {
+        Arc::as_ptr(texture) as usize
+    }
+
+    pub fn region(&self, texture: &Arc<RwLock<Texture>>) -> Option<AtlasRegion>
+    {
+        self.regions.get(&Self::texture_key(texture)).copied()
+    }
+
+    pub fn page(&self, index: usize) -> &Arc<RwLock<Texture>>
+    {
+        &self.pages[index]
+    }
+
+    fn load_images(
+        images: &mut Vec<(String, DynamicImage)>,
+        textures_root: &Path,
+        folder: &Path
+    )
+    {
+        let entries = match fs::read_dir(textures_root.join(folder))
+        {
+            Ok(x) => x,
+            Err(err) =>
+            {
+                eprintln!("error reading {}: {err}", folder.display());
+                return;
+            }
+        };
+
+        entries.filter_map(|entry| entry.ok()).for_each(|entry|
+        {
+            let path = folder.join(entry.file_name());
+
+            if entry.file_type().map(|x| x.is_dir()).unwrap_or(false)
+            {
+                Self::load_images(images, textures_root, &path);
+                return;
+            }
+
+            let is_png = path.extension().map(|x| x == "png").unwrap_or(false);
+            if !is_png
+            {
+                return;
+            }
+
+            // only the atlas picks up overrides, textures the engine loads by itself dont
+            match image::open(overridden(textures_root.join(&path)))
+            {
+                Ok(image) =>
+                {
+                    if image.width() + PADDING * 2 > ATLAS_WIDTH
+                    {
+                        return;
+                    }
+
+                    let name = path.to_string_lossy().replace('\\', "/");
+
+                    images.push((name, image));
+                },
+                Err(err) => eprintln!("error loading {}: {err}", path.display())
+            }
+        });
+    }
+
+    // simple shelf packing, tallest images first so the rows waste less space
+    fn pack(mut images: Vec<(String, DynamicImage)>) -> Vec<AtlasPage>
+    {
+        images.sort_by_key(|(_, image)| Reverse(image.height()));
+
+        let mut pages = vec![AtlasPage::default()];
+
+        let mut x = 0;
+        let mut y = 0;
+        let mut row_height = 0;
+
+        images.into_iter().for_each(|(name, image)|
+        {
+            let width = image.width() + PADDING * 2;
+            let height = image.height() + PADDING * 2;
+
+            if x + width > ATLAS_WIDTH
+            {
+                x = 0;
+                y += row_height;
+                row_height = 0;
+            }
+
+            if y + height > ATLAS_WIDTH
+            {
+                pages.push(AtlasPage::default());
+
+                x = 0;
+                y = 0;
+                row_height = 0;
+            }
+
+            let page = pages.last_mut().unwrap();
+
+            page.images.push(PackedImage{name, image, x: x + PADDING, y: y + PADDING});
+            page.height = page.height.max(y + height);
+
+            x += width;
+            row_height = row_height.max(height);
+        });
+
+        pages.retain(|page| !page.images.is_empty());
+
+        pages
+    }
+
+    fn create_page(
+        resource_uploader: &mut ResourceUploader,
+        shader: ShaderId,
+        index: usize,
+        page: AtlasPage
+    ) -> (Arc<RwLock<Texture>>, Vec<(String, AtlasRegion)>)
+    {
+        let width = ATLAS_WIDTH as usize;
+        let height = page.height as usize;
+
+        let mut atlas = SimpleImage::new(vec![Color::new(0, 0, 0, 0); width * height], width, height);
+
+        let regions: Vec<_> = page.images.into_iter().map(|packed|
+        {
+            let to_uv = |value: u32, size: usize| value as f32 / size as f32;
+
+            let start = [to_uv(packed.x, width), to_uv(packed.y, height)];
+            let end = [
+                to_uv(packed.x + packed.image.width(), width),
+                to_uv(packed.y + packed.image.height(), height)
+            ];
+
+            atlas.blit(&SimpleImage::from(packed.image), packed.x as usize, packed.y as usize);
+
+            (packed.name, AtlasRegion{page: index, start, end})
+        }).collect();
+
+        let texture = Arc::new(RwLock::new(Texture::new(
+            resource_uploader,
+            atlas.into(),
+            UniformLocation{set: 0, binding: 0},
+            shader
+        )));
+
+        (texture, regions)
+    }
+}
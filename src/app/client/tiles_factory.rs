@@ -8,6 +8,22 @@ use image::error::ImageError;
 
 use parking_lot::RwLock;
 
+use vulkano::{
+    buffer::{
+        Buffer,
+        BufferContents,
+        BufferCreateInfo,
+        BufferUsage,
+        Subbuffer
+    },
+    memory::allocator::{
+        StandardMemoryAllocator,
+        AllocationCreateInfo,
+        MemoryTypeFilter
+    },
+    pipeline::graphics::vertex_input::Vertex
+};
+
 use nalgebra::Vector3;
 
 use yanyaengine::{
@@ -26,18 +42,19 @@ use yanyaengine::{
 };
 
 use crate::common::{
+    some_or_return,
     OccludingPlane,
     TileMap,
     TileMapWithTextures,
-    tilemap::{PADDING, TileInfo},
+    tilemap::{PADDING, TileInfo, rotation_facing},
     world::{
         CHUNK_SIZE,
         TILE_SIZE,
         Chunk,
         GlobalPos,
-        Pos3,
         Tile,
         TileRotation,
+        MaybeGroup,
         chunk::ChunkLocal,
     }
 };
@@ -60,16 +77,51 @@ pub struct VerticalOccluder
     pub size: Vector3<f32>
 }
 
+// everything the vertex shader needs to place a single tile of a chunk slice
+#[repr(C)]
+#[derive(Debug, Clone, Copy, BufferContents, Vertex)]
+pub struct TileInstance
+{
+    #[format(R32G32_SFLOAT)]
+    tile: [f32; 2],
+    // the 4 corners in the same order as the quad vertices
+    #[format(R32G32B32A32_SFLOAT)]
+    uvs_low: [f32; 4],
+    #[format(R32G32B32A32_SFLOAT)]
+    uvs_high: [f32; 4]
+}
+
 #[derive(Debug)]
 pub struct ChunkInfo
 {
-    model: Arc<RwLock<Model>>,
+    instances: Vec<TileInstance>,
     transform: Transform
 }
 
+// a single tile sized quad drawn once for every tile on a chunk slice
+#[derive(Debug)]
+pub struct TileObject
+{
+    object: Object,
+    instances: Subbuffer<[TileInstance]>
+}
+
+impl TileObject
+{
+    pub fn update_buffers(&mut self, info: &mut UpdateBuffersInfo)
+    {
+        self.object.update_buffers(info);
+    }
+
+    pub fn draw(&self, info: &mut DrawInfo)
+    {
+        self.object.draw_instanced(info, self.instances.clone());
+    }
+}
+
 pub struct ChunkModelBuilder
 {
-    model: ChunkSlice<Model>,
+    instances: ChunkSlice<Vec<TileInstance>>,
     tilemap: Arc<TileMap>
 }
 
@@ -79,50 +131,84 @@ impl ChunkModelBuilder
         tilemap: Arc<TileMap>
     ) -> Self
     {
-        let model = (0..CHUNK_SIZE).map(|_|
+        let instances = (0..CHUNK_SIZE).map(|_|
         {
-            Model::new()
+            Vec::new()
         }).collect::<Vec<_>>().try_into().unwrap();
 
-        Self{model, tilemap}
+        Self{instances, tilemap}
     }
 
-    pub fn create(&mut self, pos: ChunkLocal, tile: Tile)
+    pub fn create(&mut self, pos: ChunkLocal, tiles: MaybeGroup<Tile>)
     {
-        self.create_inner(pos, tile);
+        let tilemap = self.tilemap.clone();
+        let info = &tilemap[tiles.this];
+
+        let autotiled = info.autotile.as_ref().and_then(|autotile|
+        {
+            // missing neighbors count as connected so chunk borders dont get edges
+            let connected = tiles.other.map(|_, tile|
+            {
+                tile.map(|tile| autotile.connects(tile)).unwrap_or(true)
+            });
+
+            autotile.pick(connected)
+        });
+
+        let (texture, rotation) = autotiled.unwrap_or((tiles.this.id() - 1, tiles.this.rotation));
+
+        self.create_inner(pos, texture, rotation);
+
+        if info.blend.is_none()
+        {
+            return;
+        }
+
+        tiles.other.for_each(|direction, other|
+        {
+            let other = some_or_return!(other);
+
+            if other.id() == tiles.this.id()
+            {
+                return;
+            }
+
+            if let Some(texture) = tilemap[other].blend
+            {
+                self.create_inner(pos, texture, rotation_facing(direction));
+            }
+        });
     }
 
     fn create_inner(
         &mut self,
         chunk_pos: ChunkLocal,
-        tile: Tile
+        texture: usize,
+        rotation: TileRotation
     )
     {
-        let mut pos = Pos3::<f32>::from(*chunk_pos.pos()) * TILE_SIZE;
-        pos.z += TILE_SIZE;
+        let pos = chunk_pos.pos();
 
-        let chunk_height = chunk_pos.pos().z;
+        let [a, b, c, d] = self.tile_uvs(texture, rotation, false);
 
-        {
-            let uvs = self.tile_uvs(tile, false);
-
-            self.model[chunk_height].uvs.extend(uvs);
-        }
-
-        {
-            let vertices = self.tile_vertices(pos);
-
-            self.model[chunk_height].vertices.extend(vertices);
-        }
+        self.instances[pos.z].push(TileInstance{
+            tile: [pos.x as f32, pos.y as f32],
+            uvs_low: [a[0], a[1], b[0], b[1]],
+            uvs_high: [c[0], c[1], d[0], d[1]]
+        });
     }
 
-    fn tile_uvs(&self, tile: Tile, flip_xy: bool) -> impl Iterator<Item=[f32; 2]>
+    fn tile_uvs(
+        &self,
+        texture: usize,
+        rotation: TileRotation,
+        flip_xy: bool
+    ) -> [[f32; 2]; 4]
     {
         let side = self.tilemap.texture_row_size();
 
-        let id = tile.id() - 1;
-        let x = id % side;
-        let y = id / side;
+        let x = texture % side;
+        let y = texture / side;
 
         let to_uv = |value|
         {
@@ -142,7 +228,7 @@ impl ChunkModelBuilder
         let mut c = [x_end, y];
         let mut d = [x_end, y_end];
 
-        match tile.rotation
+        match rotation
         {
             TileRotation::Up => (),
             TileRotation::Down =>
@@ -164,22 +250,7 @@ impl ChunkModelBuilder
             (b, c) = (c, b);
         }
 
-        [a, b, c, b, d, c].into_iter()
-    }
-
-    fn tile_vertices(&self, pos: Pos3<f32>) -> impl Iterator<Item=[f32; 3]>
-    {
-        let (x, y, z) = (pos.x, pos.y, pos.z - TILE_SIZE);
-        let (x_end, y_end) = (pos.x + TILE_SIZE, pos.y + TILE_SIZE);
-
-        vec![
-            [x, y, z],
-            [x, y_end, z],
-            [x_end, y, z],
-            [x, y_end, z],
-            [x_end, y_end, z],
-            [x_end, y, z]
-        ].into_iter()
+        [a, b, c, d]
     }
 
     pub fn build(
@@ -187,16 +258,17 @@ impl ChunkModelBuilder
         pos: GlobalPos
     ) -> ChunkSlice<Option<ChunkInfo>>
     {
-        let transform = Chunk::transform_of_chunk(pos);
-
-        self.model.map(|model|
+        let mut z = 0;
+        self.instances.map(|instances|
         {
-            (!model.vertices.is_empty()).then(||
+            let mut transform = Chunk::transform_of_chunk(pos);
+            transform.position.z += z as f32 * TILE_SIZE;
+
+            z += 1;
+
+            (!instances.is_empty()).then(||
             {
-                ChunkInfo{
-                    model: Arc::new(RwLock::new(model)),
-                    transform: transform.clone()
-                }
+                ChunkInfo{instances, transform}
             })
         })
     }
@@ -206,7 +278,9 @@ impl ChunkModelBuilder
 pub struct TilesFactory
 {
     object_factory: Rc<ObjectFactory>,
+    allocator: Arc<StandardMemoryAllocator>,
     square: Arc<RwLock<Model>>,
+    tile: Arc<RwLock<Model>>,
     tilemap: Arc<TileMap>,
     texture: Arc<RwLock<Texture>>
 }
@@ -249,28 +323,70 @@ impl TilesFactory
 
         Ok(Self{
             object_factory: init_info.partial.object_factory.clone(),
+            allocator: init_info.partial.builder_wrapper.resource_uploader().allocator.clone(),
             square,
+            tile: Arc::new(RwLock::new(Self::tile_model())),
             tilemap,
             texture
         })
     }
 
+    // 2 triangles, a b c and b d c, the uvs come from the instances
+    fn tile_model() -> Model
+    {
+        let mut model = Model::new();
+
+        let (x, y) = (0.0, 0.0);
+        let (x_end, y_end) = (TILE_SIZE, TILE_SIZE);
+
+        model.vertices = vec![
+            [x, y, 0.0],
+            [x, y_end, 0.0],
+            [x_end, y, 0.0],
+            [x, y_end, 0.0],
+            [x_end, y_end, 0.0],
+            [x_end, y, 0.0]
+        ];
+
+        model.uvs = vec![[0.0; 2]; model.vertices.len()];
+
+        model
+    }
+
+    // only happens when a chunk gets (re)generated, the instances dont change after that
     pub fn build(
         &mut self,
         chunk_info: ChunkSlice<Option<ChunkInfo>>
-    ) -> ChunkSlice<Option<Object>>
+    ) -> ChunkSlice<Option<TileObject>>
     {
         chunk_info.map(|chunk_info|
         {
-            chunk_info.map(|ChunkInfo{model, transform}|
+            chunk_info.map(|ChunkInfo{instances, transform}|
             {
                 let object_info = ObjectInfo{
-                    model,
+                    model: self.tile.clone(),
                     texture: self.texture.clone(),
                     transform
                 };
 
-                self.object_factory.create(object_info)
+                let instances = Buffer::from_iter(
+                    self.allocator.clone(),
+                    BufferCreateInfo{
+                        usage: BufferUsage::VERTEX_BUFFER,
+                        ..Default::default()
+                    },
+                    AllocationCreateInfo{
+                        memory_type_filter: MemoryTypeFilter::PREFER_DEVICE
+                            | MemoryTypeFilter::HOST_SEQUENTIAL_WRITE,
+                        ..Default::default()
+                    },
+                    instances
+                ).unwrap();
+
+                TileObject{
+                    object: self.object_factory.create(object_info),
+                    instances
+                }
             })
         })
     }
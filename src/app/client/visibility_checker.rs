@@ -17,7 +17,7 @@ pub struct VisibilityChecker
 
 impl VisibilityChecker
 {
-    fn visible_sphere_radius(&self, position: Vector3<f32>, radius: f32) -> bool
+    pub fn visible_sphere_radius(&self, position: Vector3<f32>, radius: f32) -> bool
     {
         let offset = position - self.position;
 
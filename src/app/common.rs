@@ -21,10 +21,11 @@ pub use tilemap::{
     TileInfo,
     SpecialTile,
     SpawnerTile,
+    TileMaterial,
     TileMapWithTextures
 };
 
-pub use outlineable::Outlineable;
+pub use outlineable::{Outlineable, OutlineStyle, OutlinePulse};
 
 pub use lazy_mix::LazyMix;
 pub use lazy_transform::{
@@ -55,16 +56,41 @@ pub use item::Item;
 pub use items_info::{ItemInfo, ItemsInfo, Ranged};
 
 pub use inventory::{InventorySorter, InventoryItem, Inventory};
+pub use equipment::{EquipSlot, Wearable, Equipment};
 
 pub use character::{CharacterSyncInfo, Character, Faction};
+pub use corpse::{CorpseStage, Corpse, DeathEffect};
+pub use light::Light;
+pub use decal::{DecalKind, Decal};
+pub use explosion::{ExplosionInfo, Explosive, Explosion};
+pub use projectile::{ProjectileKind, ProjectileInfo, Tracer, Projectile, Projectiles};
+pub use shake::Shake;
+pub use sound::{Sound, SoundEmitter, Noise, NoiseKind};
+pub use weather::{WeatherKind, Weather, WeatherState};
+pub use difficulty::{DeathPolicy, Difficulty};
 pub use characters_info::{Hairstyle, CharacterId, CharactersInfo, CharacterInfo};
 
 pub use player::Player;
-
-pub use enemy::{EnemyBehavior, Enemy};
+pub use character_creation::CharacterCreation;
+pub use perks_info::{PerkId, PerkEffect, PerkInfo, PerksInfo};
+pub use reputation::{ReputationEvent, Reputation};
+pub use barks_info::{BarkKind, BarkTable, BarksInfo};
+pub use mods_info::ModsInfo;
+pub use recipes_info::{RecipeId, RecipeInfo, RecipesInfo};
+pub use collision_layers::CollisionLayers;
+pub use factions_info::{FactionRelation, FactionsInfo};
+pub use dialogue::{Dialogue, DialoguesInfo};
+pub use container::{Container, ContainersInfo, Unlock};
+pub use needs::{Food, Needs};
+pub use fire::Fire;
+pub use particle_effects::{ParticleEffectInfo, ParticleEffects};
+pub use curve::Curve;
+pub use barter::{VendorInfo, Vendor, Haggler, BarterResponse, TradeOffer};
+
+pub use enemy::{EnemyBehavior, BehaviorTimings, CompanionOrder, Companion, Enemy};
 pub use enemy_builder::EnemyBuilder;
 pub use furniture_builder::FurnitureBuilder;
-pub use enemies_info::{EnemyId, EnemyInfo, EnemiesInfo};
+pub use enemies_info::{EnemyId, EnemyLootInfo, EnemyInfo, EnemiesInfo};
 
 pub use chunk_saver::{SaveLoad, WorldChunksBlock, WorldChunkSaver, ChunkSaver, EntitiesSaver};
 
@@ -79,7 +105,7 @@ pub use render_info::RenderInfo;
 
 pub use saveable::Saveable;
 
-pub use anatomy::{Anatomy, HumanAnatomy, HumanAnatomyInfo};
+pub use anatomy::{Anatomy, Bandage, Prosthetic, HumanAnatomyInfo, PlanAnatomy, BodyPlansInfo};
 pub use damage::{Damageable, Damage, DamageType, DamageDirection, DamageHeight, DamagePartial};
 
 pub use spatial::{SpatialInfo, SpatialGrid};
@@ -89,6 +115,7 @@ pub use physics::{Physical, PhysicalProperties, PhysicalFixed};
 pub use world::{PosDirection, Pos3, Axis, FlatChunksContainer, ChunksContainer};
 
 pub use joint::Joint;
+pub use wind::WindZone;
 
 pub mod utility;
 
@@ -100,6 +127,14 @@ pub mod raycast;
 
 pub mod render_info;
 pub mod occluding_plane;
+pub mod light;
+pub mod decal;
+pub mod explosion;
+pub mod projectile;
+pub mod shake;
+pub mod sound;
+pub mod weather;
+pub mod difficulty;
 
 pub mod saveable;
 
@@ -109,6 +144,8 @@ pub mod anatomy;
 
 pub mod character;
 pub mod characters_info;
+pub mod corpse;
+pub mod ragdoll;
 
 pub mod outlineable;
 
@@ -125,31 +162,55 @@ pub mod item;
 pub mod items_info;
 
 pub mod inventory;
+pub mod equipment;
 
 pub mod player;
-
+pub mod needs;
+pub mod fire;
+pub mod character_creation;
+pub mod perks_info;
+pub mod reputation;
+pub mod barter;
+pub mod barks_info;
+pub mod mods_info;
+pub mod recipes_info;
+pub mod overrides;
+pub mod rng;
+pub mod crash_report;
+pub mod collision_layers;
+pub mod factions_info;
+pub mod dialogue;
+pub mod container;
+
+pub mod curve;
 pub mod particle_creator;
+pub mod particle_effects;
 pub mod furniture_builder;
 
 pub mod enemy;
+pub mod behavior;
 pub mod enemy_builder;
 pub mod enemies_info;
 
 pub mod message;
+pub mod snapshot;
 
 pub mod sender_loop;
 pub mod receiver_loop;
 
 pub mod tilemap;
 
+pub mod save_file;
 pub mod chunk_saver;
 pub mod world;
 
 pub mod spatial;
+pub mod pathfind;
 pub mod collider;
 pub mod physics;
 
 pub mod joint;
+pub mod wind;
 
 
 pub type MessageError = bincode::Error;
@@ -181,6 +242,16 @@ pub struct DataInfos
     pub items_info: Arc<ItemsInfo>,
     pub enemies_info: Arc<EnemiesInfo>,
     pub characters_info: Arc<CharactersInfo>,
+    pub perks_info: Arc<PerksInfo>,
+    pub barks_info: Arc<BarksInfo>,
+    pub body_plans: Arc<BodyPlansInfo>,
+    pub collision_layers: Arc<CollisionLayers>,
+    pub factions_info: Arc<FactionsInfo>,
+    pub dialogues_info: Arc<DialoguesInfo>,
+    pub containers_info: Arc<ContainersInfo>,
+    pub recipes_info: Arc<RecipesInfo>,
+    pub particle_effects: Arc<ParticleEffects>,
+    pub mods_info: Arc<ModsInfo>,
     pub player_character: CharacterId
 }
 
@@ -24,13 +24,68 @@ use crate::{
         Side1d,
         Side2d,
         Damageable,
+        Drug,
         world::TILE_SIZE
     }
 };
 
+pub use plan::{PlanAnatomy, BodyPlansInfo, BodyPlanInfo, HUMAN_PLAN};
+
+mod plan;
+
 
 type DebugName = <DebugConfig as DebugConfigTrait>::DebugName;
 
+// liters per second of bleeding for each point of cutting damage that got through the skin
+const BLEED_PER_DAMAGE: f32 = 0.0005;
+const MAX_BLEED: f32 = 0.1;
+
+// how fast wounds close up on their own, in liters per second per second
+const CLOT_RATE: f32 = 0.0002;
+
+// chance for a cut to get infected if nobody cleans it
+const INFECTION_CHANCE: f32 = 0.15;
+const INFECTION_START: f32 = 0.01;
+// about half an hour from a fresh infection to the worst it gets
+const INFECTION_GROWTH: f32 = 1.0 / 1800.0;
+// antibiotics fight it off a bit faster than it grows
+const INFECTION_RECOVERY: f32 = 1.0 / 900.0;
+// past this it starts eating the part and whatever is inside of it
+const INFECTION_HARMFUL: f32 = 0.4;
+// fraction of max health lost per second at the worst infection
+const ROT_RATE: f32 = 1.0 / 3600.0;
+
+// fraction of heat that makes it past each layer
+const HEAT_PASS: f32 = 0.3;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Bandage
+{
+    // fraction of the bleeding it stops
+    pub stops: f32,
+    // tourniquets only go on arms and legs
+    #[serde(default)]
+    pub limbs_only: bool
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProstheticPart
+{
+    Arm,
+    Leg,
+    Lung
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Prosthetic
+{
+    pub part: ProstheticPart,
+    // how close to the real thing it works
+    pub quality: f32
+}
+
 macro_rules! simple_getter
 {
     ($name:ident) =>
@@ -39,16 +94,38 @@ macro_rules! simple_getter
         {
             match self
             {
-                Self::Human(x) => x.$name()
+                Self::Human(x) => x.$name(),
+                Self::Plan(x) => x.$name()
             }
         }
     }
 }
 
+// which part of an anatomy, plans just number their parts in the order theyre listed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AnatomyPartId
+{
+    Human(HumanPartId),
+    Plan(usize)
+}
+
+// everything the health panels need to know about a part without caring whose body it is
+#[derive(Debug, Clone, Copy)]
+pub struct PartStatus
+{
+    pub bone: f32,
+    pub muscle: Option<f32>,
+    pub skin: Option<f32>,
+    pub bleeding: f32,
+    pub infection: f32
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Anatomy
 {
-    Human(HumanAnatomy)
+    // everyone gets made from body plans now, this is only here so old saves still load
+    Human(HumanAnatomy),
+    Plan(PlanAnatomy)
 }
 
 impl Anatomy
@@ -57,48 +134,179 @@ impl Anatomy
     simple_getter!(strength);
     simple_getter!(stamina);
     simple_getter!(max_stamina);
+    simple_getter!(hearing);
     simple_getter!(vision);
 
-    pub fn get_human(&self, id: HumanPartId) -> Option<Option<&HumanPart>>
+    pub fn override_crawling(&mut self, state: bool)
     {
-        #[allow(irrefutable_let_patterns)]
-        if let Self::Human(x) = self
+        match self
         {
-            Some(x.body.get(id))
-        } else
+            Self::Human(x) => x.override_crawling(state),
+            Self::Plan(x) => x.override_crawling(state)
+        }
+    }
+
+    pub fn is_crawling(&self) -> bool
+    {
+        match self
         {
-            None
+            Self::Human(x) => x.is_crawling(),
+            Self::Plan(x) => x.is_crawling()
         }
     }
 
-    pub fn override_crawling(&mut self, state: bool)
+    pub fn set_speed(&mut self, speed: f32)
     {
         match self
         {
-            Self::Human(x) => x.override_crawling(state)
+            Self::Human(x) => x.set_speed(speed),
+            Self::Plan(x) => x.set_speed(speed)
         }
     }
 
-    pub fn is_crawling(&self) -> bool
+    pub fn set_strength(&mut self, strength: f32)
+    {
+        match self
+        {
+            Self::Human(x) => x.set_strength(strength),
+            Self::Plan(x) => x.set_strength(strength)
+        }
+    }
+
+    pub fn health_fraction(&self) -> f32
+    {
+        match self
+        {
+            Self::Human(x) => x.health_fraction(),
+            Self::Plan(x) => x.health_fraction()
+        }
+    }
+
+    pub fn blood_fraction(&self) -> f32
+    {
+        match self
+        {
+            Self::Human(x) => x.blood_fraction(),
+            Self::Plan(x) => x.blood_fraction()
+        }
+    }
+
+    pub fn pain(&self) -> f32
+    {
+        match self
+        {
+            Self::Human(x) => x.pain(),
+            Self::Plan(x) => x.pain()
+        }
+    }
+
+    // returns false if there was nothing it could be used on
+    pub fn bandage(&mut self, bandage: &Bandage) -> bool
+    {
+        match self
+        {
+            Self::Human(x) => x.bandage(bandage),
+            Self::Plan(x) => x.bandage(bandage)
+        }
+    }
+
+    pub fn apply_drug(&mut self, drug: &Drug) -> bool
+    {
+        match self
+        {
+            Self::Human(x) => x.apply_drug(drug),
+            Self::Plan(x) => x.apply_drug(drug)
+        }
+    }
+
+    // returns false if there was nothing destroyed for it to replace
+    pub fn replace_part(&mut self, prosthetic: &Prosthetic) -> bool
+    {
+        match self
+        {
+            Self::Human(x) => x.replace_part(prosthetic),
+            Self::Plan(x) => x.replace_part(prosthetic)
+        }
+    }
+
+    pub fn update(&mut self, dt: f32)
+    {
+        match self
+        {
+            Self::Human(x) => x.update(dt),
+            Self::Plan(x) => x.update(dt)
+        }
+    }
+
+    pub fn parts(&self) -> Vec<AnatomyPartId>
+    {
+        match self
+        {
+            Self::Human(x) => HumanPartId::iter().filter(|id| x.body.get(*id).is_some())
+                .map(AnatomyPartId::Human)
+                .collect(),
+            Self::Plan(x) => (0..x.parts.len()).map(AnatomyPartId::Plan).collect()
+        }
+    }
+
+    pub fn part_name(&self, id: AnatomyPartId) -> Option<String>
+    {
+        match (self, id)
+        {
+            (Self::Human(_), AnatomyPartId::Human(id)) => Some(id.to_string()),
+            (Self::Plan(x), AnatomyPartId::Plan(id)) => x.parts.get(id).map(|part| part.name.clone()),
+            _ => None
+        }
+    }
+
+    pub fn part_status(&self, id: AnatomyPartId) -> Option<PartStatus>
+    {
+        match (self, id)
+        {
+            (Self::Human(x), AnatomyPartId::Human(id)) => x.body.get(id).map(|part| part.status()),
+            (Self::Plan(x), AnatomyPartId::Plan(id)) => x.parts.get(id).map(|part| part.part.status()),
+            _ => None
+        }
+    }
+
+    // the name of the body plan for plan anatomies, decides which overlay the ui uses
+    pub fn plan_name(&self) -> Option<&str>
     {
         match self
         {
-            Self::Human(x) => x.is_crawling()
+            Self::Human(_) => None,
+            Self::Plan(x) => Some(&x.plan)
         }
     }
 
-    pub fn sees(&self, this_position: &Vector3<f32>, other_position: &Vector3<f32>) -> bool
+    // sight_scale is for stuff like fog making everything harder to see
+    pub fn sees(
+        &self,
+        this_position: &Vector3<f32>,
+        other_position: &Vector3<f32>,
+        sight_scale: f32
+    ) -> bool
     {
         let distance = this_position.metric_distance(other_position);
 
-        self.vision().unwrap_or(0.0) >= distance
+        self.vision().unwrap_or(0.0) * sight_scale >= distance
     }
 
-    pub fn set_speed(&mut self, speed: f32)
+    pub fn base_speed(&self) -> f32
     {
         match self
         {
-            Self::Human(x) => x.set_speed(speed)
+            Self::Human(x) => x.base_speed,
+            Self::Plan(x) => x.base_speed
+        }
+    }
+
+    pub fn base_strength(&self) -> f32
+    {
+        match self
+        {
+            Self::Human(x) => x.base_strength,
+            Self::Plan(x) => x.base_strength
         }
     }
 
@@ -106,7 +314,8 @@ impl Anatomy
     {
         match self
         {
-            Self::Human(x) => x.for_broken_parts(f)
+            Self::Human(x) => x.for_broken_parts(f),
+            Self::Plan(x) => x.for_broken_parts(f)
         }
     }
 }
@@ -117,7 +326,8 @@ impl Damageable for Anatomy
     {
         match self
         {
-            Self::Human(x) => x.damage(damage)
+            Self::Human(x) => x.damage(damage),
+            Self::Plan(x) => x.damage(damage)
         }
     }
 }
@@ -141,41 +351,52 @@ pub enum BrokenKind
 
 pub struct BrokenPart
 {
-    pub id: HumanPartId,
-    pub kind: BrokenKind
+    pub kind: BrokenKind,
+    name: String,
+    // what gets said when the bone goes
+    bone_broken: String
 }
 
 impl Display for BrokenPart
 {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
     {
-        let s = match self.kind
+        match self.kind
         {
-            BrokenKind::Bone =>
-            {
-                let side = self.id.side_name();
-                if let HumanPartId::Eye(_) = self.id
-                {
-                    format!("{side}eye raptured")
-                } else
-                {
-                    format!("{side}{} fractured", self.id.bone_name())
-                }
-            },
-            BrokenKind::Muscle =>
-            {
-                format!("{} muscles torn", self.id)
-            },
-            BrokenKind::Skin =>
-            {
-                format!("{} skin ripped", self.id)
-            }
+            BrokenKind::Bone => write!(f, "{}", self.bone_broken),
+            BrokenKind::Muscle => write!(f, "{} muscles torn", self.name),
+            BrokenKind::Skin => write!(f, "{} skin ripped", self.name)
+        }
+    }
+}
+
+impl BrokenPart
+{
+    pub fn new(kind: BrokenKind, name: String, bone_broken: String) -> Self
+    {
+        Self{kind, name, bone_broken}
+    }
+
+    fn human(id: HumanPartId, kind: BrokenKind) -> Self
+    {
+        let side = id.side_name();
+        let bone_broken = if let HumanPartId::Eye(_) = id
+        {
+            format!("{side}eye raptured")
+        } else
+        {
+            format!("{side}{} fractured", id.bone_name())
         };
 
-        write!(f, "{s}")
+        Self::new(kind, id.to_string(), bone_broken)
     }
 }
 
+trait Rottable
+{
+    fn rot(&mut self, fraction: f32);
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct SimpleHealth
 {
@@ -264,6 +485,20 @@ impl Health
         self.health.current()
     }
 
+    fn rot(&mut self, fraction: f32)
+    {
+        self.health.subtract_hp(self.health.max * fraction);
+    }
+
+    // a fresh copy that only works as well as the quality allows
+    fn replaced(&self, quality: f32) -> Self
+    {
+        let mut health = SimpleHealth::new(self.health.max);
+        health.subtract_hp(self.health.max * (1.0 - quality));
+
+        Self{max_block: self.max_block, health}
+    }
+
     pub fn damage_pierce(&mut self, damage: DamageType) -> Option<DamageType>
     {
         match damage
@@ -282,6 +517,10 @@ impl Health
             DamageType::Bullet(damage) =>
             {
                 self.simple_pierce(damage).map(DamageType::Bullet)
+            },
+            DamageType::Heat(damage) =>
+            {
+                self.simple_pierce(damage).map(|damage| DamageType::Heat(damage * HEAT_PASS))
             }
         }
     }
@@ -426,6 +665,13 @@ pub struct BodyPart<Data>
     pub bone: BreakTracking<Health>,
     pub skin: BreakTracking<Option<Health>>,
     pub muscle: BreakTracking<Option<Health>>,
+    // liters per second leaking out
+    #[serde(default)]
+    bleed: f32,
+    #[serde(default)]
+    infection: f32,
+    #[serde(default)]
+    prosthetic: bool,
     size: f64,
     contents: Vec<Data>
 }
@@ -464,11 +710,121 @@ impl<Data> BodyPart<Data>
             bone: bone.into(),
             skin: skin.into(),
             muscle: muscle.into(),
+            bleed: 0.0,
+            infection: 0.0,
+            prosthetic: false,
             size,
             contents
         }
     }
 
+    pub fn health_fraction(&self) -> f32
+    {
+        let layers = [Some(*self.bone), *self.skin, *self.muscle];
+
+        let (amount, total) = layers.iter().flatten().fold((0, 0.0), |(amount, total), health|
+        {
+            (amount + 1, total + health.fraction())
+        });
+
+        total / amount as f32
+    }
+
+    pub fn bleeding(&self) -> f32
+    {
+        self.bleed
+    }
+
+    pub fn status(&self) -> PartStatus
+    {
+        PartStatus{
+            bone: self.bone.fraction(),
+            muscle: self.muscle.map(|x| x.fraction()),
+            skin: self.skin.map(|x| x.fraction()),
+            bleeding: self.bleed,
+            infection: self.infection
+        }
+    }
+
+    // returns how much blood leaked out and how much of it rotted away
+    fn update_wounds(&mut self, dt: f32, treated: bool) -> (f32, f32)
+    where
+        Data: Rottable
+    {
+        let rot = self.update_infection(dt, treated);
+
+        if rot > 0.0
+        {
+            self.contents.iter_mut().for_each(|organ| organ.rot(rot));
+        }
+
+        (self.update_bleed(dt), rot)
+    }
+
+    // returns how much it got eaten away this update
+    fn update_infection(&mut self, dt: f32, treated: bool) -> f32
+    {
+        if self.infection <= 0.0
+        {
+            return 0.0;
+        }
+
+        if treated
+        {
+            self.infection = (self.infection - INFECTION_RECOVERY * dt).max(0.0);
+        } else
+        {
+            self.infection = (self.infection + INFECTION_GROWTH * dt).min(1.0);
+        }
+
+        let harm = (self.infection - INFECTION_HARMFUL) / (1.0 - INFECTION_HARMFUL);
+        if harm <= 0.0
+        {
+            return 0.0;
+        }
+
+        let rot = harm * ROT_RATE * dt;
+
+        if let Some(muscle) = self.muscle.as_mut()
+        {
+            muscle.rot(rot);
+        }
+
+        rot
+    }
+
+    fn update_bleed(&mut self, dt: f32) -> f32
+    {
+        let bled = self.bleed * dt;
+
+        self.bleed = (self.bleed - CLOT_RATE * dt).max(0.0);
+
+        bled
+    }
+
+    fn replace(&mut self, quality: f32)
+    {
+        self.bone = self.bone.replaced(quality).into();
+        self.muscle = self.muscle.map(|x| x.replaced(quality)).into();
+        self.skin = None.into();
+        self.bleed = 0.0;
+        self.infection = 0.0;
+        self.prosthetic = true;
+    }
+
+    // the worst layer decides, a broken bone hurts even under healthy skin
+    pub fn pain(&self) -> f32
+    {
+        if self.prosthetic
+        {
+            return 0.0;
+        }
+
+        let layers = [Some(*self.bone), *self.skin, *self.muscle];
+
+        layers.iter().flatten().map(|health| 1.0 - health.fraction()).fold(0.0, f32::max)
+    }
+
     fn damage(&mut self, damage: Damage) -> Option<Damage>
     where
         Data: DamageReceiver + Debug
@@ -481,6 +837,25 @@ impl<Data> BodyPart<Data>
         let mut rng = damage.rng;
         let direction = damage.direction;
 
+        // blunt stuff bruises but doesnt open anything up, burns seal themselves
+        let cutting = match damage.data
+        {
+            DamageType::Blunt(_)
+            | DamageType::Heat(_) => 0.0,
+            DamageType::Sharp{sharpness, damage} => damage * sharpness.clamp(0.2, 1.0),
+            DamageType::Bullet(x) => x
+        };
+
+        if self.skin.is_some() && cutting > 0.0
+        {
+            self.bleed = (self.bleed + cutting * BLEED_PER_DAMAGE).min(MAX_BLEED);
+
+            if self.infection <= 0.0 && rng.next_f32() < INFECTION_CHANCE
+            {
+                self.infection = INFECTION_START;
+            }
+        }
+
         self.damage_inner(&mut rng, direction.side, damage.data).map(|damage|
         {
             Damage{rng, direction, data: damage}
@@ -507,7 +882,8 @@ impl<Data> BodyPart<Data>
                 {
                     x.damage_pierce(damage * (base_mult + sharpness).clamp(0.0, 1.0))
                 },
-                DamageType::Bullet(_) => x.damage_pierce(damage)
+                DamageType::Bullet(_)
+                | DamageType::Heat(_) => x.damage_pierce(damage)
             }
         }).unwrap_or(Some(damage))
         {
@@ -840,6 +1216,28 @@ pub enum HumanOrgan
     Lung(Lung)
 }
 
+impl Rottable for HumanOrgan
+{
+    fn rot(&mut self, fraction: f32)
+    {
+        match self
+        {
+            Self::Brain(brain) =>
+            {
+                [&mut brain.left, &mut brain.right].into_iter().for_each(|hemisphere|
+                {
+                    hemisphere.frontal.motor.arms.rot(fraction);
+                    hemisphere.frontal.motor.legs.rot(fraction);
+                    hemisphere.parietal.rot(fraction);
+                    hemisphere.temporal.rot(fraction);
+                    hemisphere.occipital.rot(fraction);
+                });
+            },
+            Self::Lung(lung) => lung.health.rot(fraction)
+        }
+    }
+}
+
 impl DamageReceiver for HumanOrgan
 {
     fn damage(
@@ -942,6 +1340,24 @@ impl Display for HumanPartId
 
 impl HumanPartId
 {
+    pub fn is_limb(&self) -> bool
+    {
+        match self
+        {
+            Self::Thigh(_)
+            | Self::Calf(_)
+            | Self::Arm(_)
+            | Self::Forearm(_)
+            | Self::Hand(_)
+            | Self::Foot(_) => true,
+            Self::Head
+            | Self::Torso
+            | Self::Spine
+            | Self::Pelvis
+            | Self::Eye(_) => false
+        }
+    }
+
     pub fn side(&self) -> Option<Side1d>
     {
         match self
@@ -1027,8 +1443,7 @@ pub struct CachedProps
     strength: Option<f32>,
     stamina: Option<f32>,
     max_stamina: Option<f32>,
-    vision: Option<f32>,
-    blood_change: f32
+    vision: Option<f32>
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -1231,6 +1646,9 @@ pub struct HumanAnatomy
     base_strength: f32,
     override_crawling: bool,
     blood: SimpleHealth,
+    // seconds of antibiotics left in the system
+    #[serde(default)]
+    antibiotics: f32,
     body: HumanBody,
     cached: CachedProps
 }
@@ -1346,6 +1764,7 @@ impl HumanAnatomy
             base_strength,
             override_crawling: false,
             blood: SimpleHealth::new(4.0),
+            antibiotics: 0.0,
             body,
             cached: Default::default()
         };
@@ -1380,6 +1799,17 @@ impl HumanAnatomy
         self.cached.vision
     }
 
+    // scales how far away noises can be heard from, the temporal lobes do the hearing
+    pub fn hearing(&self) -> Option<f32>
+    {
+        let brain = self.brain()?;
+
+        let hearing = brain.as_ref().map(|hemisphere| hemisphere.temporal.fraction())
+            .combine(|a, b| (a + b) / 2.0);
+
+        (hearing > 0.0).then_some(hearing)
+    }
+
     pub fn is_crawling(&self) -> bool
     {
         self.cached.is_crawling
@@ -1392,13 +1822,226 @@ impl HumanAnatomy
         self.update_cache();
     }
 
+    pub fn set_strength(&mut self, strength: f32)
+    {
+        self.base_strength = strength;
+
+        self.update_cache();
+    }
+
+    // rough overall condition, missing parts count as fully broken
+    pub fn health_fraction(&self) -> f32
+    {
+        if self.speed().is_none()
+        {
+            return 0.0;
+        }
+
+        let total_parts = HumanPartId::iter().count();
+
+        let parts: Vec<_> = HumanPartId::iter().filter_map(|id| self.body.get(id)).collect();
+
+        let size: f64 = parts.iter().map(|part| part.size).sum();
+        if size == 0.0
+        {
+            return 0.0;
+        }
+
+        let weighted: f64 = parts.iter().map(|part|
+        {
+            part.size * part.health_fraction() as f64
+        }).sum();
+
+        let existing = parts.len() as f32 / total_parts as f32;
+
+        (weighted / size) as f32 * existing
+    }
+
+    pub fn blood_fraction(&self) -> f32
+    {
+        self.blood.fraction()
+    }
+
+    pub fn pain(&self) -> f32
+    {
+        let parts: Vec<_> = HumanPartId::iter().filter_map(|id| self.body.get(id)).collect();
+
+        let size: f64 = parts.iter().map(|part| part.size).sum();
+        if size == 0.0
+        {
+            return 0.0;
+        }
+
+        let weighted: f64 = parts.iter().map(|part|
+        {
+            part.size * part.pain() as f64
+        }).sum();
+
+        (weighted / size) as f32
+    }
+
+    fn bandage(&mut self, bandage: &Bandage) -> bool
+    {
+        let worst = HumanPartId::iter()
+            .filter(|id| !bandage.limbs_only || id.is_limb())
+            .filter_map(|id| self.body.get(id).map(|part| (id, part.bleeding())))
+            .filter(|(_, bleeding)| *bleeding > 0.0)
+            .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap());
+
+        let (id, _) = some_or_value!(worst, false);
+
+        let part = self.body.get_mut(id).unwrap();
+        part.bleed *= 1.0 - bandage.stops.clamp(0.0, 1.0);
+
+        true
+    }
+
+    fn apply_drug(&mut self, drug: &Drug) -> bool
+    {
+        match drug
+        {
+            Drug::Heal{..} => false,
+            Drug::Antibiotics{duration} =>
+            {
+                self.antibiotics = self.antibiotics.max(*duration);
+
+                true
+            },
+            Drug::Disinfectant{amount} =>
+            {
+                let worst = HumanPartId::iter()
+                    .filter_map(|id| self.body.get(id).map(|part| (id, part.infection)))
+                    .filter(|(_, infection)| *infection > 0.0)
+                    .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap());
+
+                let (id, _) = some_or_value!(worst, false);
+
+                let part = self.body.get_mut(id).unwrap();
+                part.infection = (part.infection - amount).max(0.0);
+
+                true
+            }
+        }
+    }
+
+    fn replace_part(&mut self, prosthetic: &Prosthetic) -> bool
+    {
+        let quality = prosthetic.quality.clamp(0.05, 1.0);
+
+        let replaced = match prosthetic.part
+        {
+            ProstheticPart::Arm | ProstheticPart::Leg =>
+            {
+                let chain = |side|
+                {
+                    if prosthetic.part == ProstheticPart::Arm
+                    {
+                        [HumanPartId::Arm(side), HumanPartId::Forearm(side), HumanPartId::Hand(side)]
+                    } else
+                    {
+                        [HumanPartId::Thigh(side), HumanPartId::Calf(side), HumanPartId::Foot(side)]
+                    }
+                };
+
+                let is_destroyed = |this: &Self, id|
+                {
+                    this.body.get(id).map(|x| x.bone.is_zero()).unwrap_or(false)
+                };
+
+                let side = [Side1d::Left, Side1d::Right].into_iter().find(|side|
+                {
+                    chain(*side).into_iter().any(|id| is_destroyed(self, id))
+                });
+
+                let side = some_or_value!(side, false);
+
+                let destroyed: Vec<_> = chain(side).into_iter().filter(|id| is_destroyed(self, *id)).collect();
+
+                // the whole thing gets fitted at once, no point leaving the broken bits
+                destroyed.into_iter().for_each(|id|
+                {
+                    self.body.get_mut(id).unwrap().replace(quality);
+                });
+
+                true
+            },
+            ProstheticPart::Lung =>
+            {
+                let torso = &mut self.body.torso;
+
+                // nowhere to put it
+                if torso.bone.is_zero()
+                {
+                    return false;
+                }
+
+                let is_working = |contents: &[HumanOrgan], side|
+                {
+                    contents.iter().any(|organ|
+                    {
+                        matches!(organ, HumanOrgan::Lung(lung) if lung.side == side && !lung.health.is_zero())
+                    })
+                };
+
+                let side = [Side1d::Left, Side1d::Right].into_iter()
+                    .find(|side| !is_working(&torso.contents, *side));
+
+                let side = some_or_value!(side, false);
+
+                torso.contents.retain(|organ| !matches!(organ, HumanOrgan::Lung(lung) if lung.side == side));
+
+                let mut lung = Lung::new(side);
+                lung.health = lung.health.replaced(quality);
+
+                torso.contents.push(HumanOrgan::Lung(lung));
+
+                true
+            }
+        };
+
+        if replaced
+        {
+            self.update_cache();
+        }
+
+        replaced
+    }
+
+    pub fn update(&mut self, dt: f32)
+    {
+        let treated = self.antibiotics > 0.0;
+        self.antibiotics = (self.antibiotics - dt).max(0.0);
+
+        let (bled, rotted) = HumanPartId::iter().filter_map(|id|
+        {
+            self.body.get_mut(id).map(|part| part.update_wounds(dt, treated))
+        }).fold((0.0, 0.0), |(bled, rotted), (bleed, rot)| (bled + bleed, rotted + rot));
+
+        if rotted > 0.0
+        {
+            self.update_cache();
+        }
+
+        if bled > 0.0
+        {
+            let was_alive = self.blood.current() > 0.0;
+
+            self.blood.subtract_hp(bled);
+
+            if was_alive && self.blood.current() <= 0.0
+            {
+                self.update_cache();
+            }
+        }
+    }
+
     pub fn for_broken_parts(&mut self, mut f: impl FnMut(BrokenPart))
     {
         HumanPartId::iter().filter_map(|id|
         {
             self.body.get_mut(id).map(move |x|
             {
-                x.consume_broken().map(move |kind| BrokenPart{id, kind})
+                x.consume_broken().map(move |kind| BrokenPart::human(id, kind))
             })
         }).for_each(|part|
         {
@@ -1584,6 +2227,12 @@ impl HumanAnatomy
 
     fn updated_speed(&mut self) -> (bool, Option<f32>)
     {
+        // bled out
+        if self.blood.current() <= 0.0
+        {
+            return (false, None);
+        }
+
         let brain = some_or_value!(self.brain(), (false, None));
 
         let speeds = brain.as_ref().map(|hemisphere|
@@ -1634,9 +2283,20 @@ impl HumanAnatomy
         Some(0.5)
     }
 
+    // missing lungs count as not working at all
+    fn lungs_fraction(&self) -> f32
+    {
+        let total: f32 = self.body.torso.contents.iter().filter_map(|organ|
+        {
+            if let HumanOrgan::Lung(lung) = organ { Some(lung.health.fraction()) } else { None }
+        }).sum();
+
+        total / 2.0
+    }
+
     fn updated_max_stamina(&mut self) -> Option<f32>
     {
-        Some(10.0)
+        Some(10.0 * (self.lungs_fraction() * 0.8 + 0.2))
     }
 
     fn updated_vision(&mut self) -> Option<f32>
@@ -0,0 +1,755 @@
+use std::{
+    fs::File,
+    path::Path,
+    sync::Arc,
+    collections::HashMap
+};
+
+use serde::{Serialize, Deserialize};
+
+use crate::{
+    debug_config::*,
+    common::{
+        some_or_value,
+        overrides::overridden,
+        SeededRandom,
+        WeightedPicker,
+        Damage,
+        DamageHeight,
+        DamageType,
+        Side2d,
+        Damageable,
+        Drug,
+        world::TILE_SIZE
+    }
+};
+
+use super::{
+    DebugName,
+    DamageReceiver,
+    Rottable,
+    Bandage,
+    Prosthetic,
+    ProstheticPart,
+    BrokenPart,
+    BrokenKind,
+    BodyPart,
+    BodyPartInfo,
+    Health,
+    SimpleHealth,
+    CachedProps,
+    HumanAnatomyInfo
+};
+
+
+// below this fraction of the legs (or wings or whatever) working it gives up and drags itself
+const CRAWL_THRESHOLD: f32 = 0.4;
+
+// one good eye still sees most of the way
+const ONE_EYE_VISION: f32 = 0.6;
+
+// even without hands it can shove stuff around a bit
+const MIN_STRENGTH: f32 = 0.1;
+
+pub const HUMAN_PLAN: &str = "human";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrganRole
+{
+    // if all of these are gone its dead
+    Brain,
+    // these decide how much stamina it has
+    Lungs,
+    Other
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PlanOrganInfo
+{
+    pub health: f32,
+    pub role: OrganRole
+}
+
+fn default_true() -> bool
+{
+    true
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PlanPartInfo
+{
+    pub name: String,
+    // the color of this part in the overlay image, like 0xff0000
+    pub color: String,
+    pub bone: f32,
+    pub size: f64,
+    // names of the parts this one hangs off of, if those r destroyed this one stops doing anything
+    #[serde(default)]
+    pub connections: Vec<String>,
+    // how much this part helps with seeing
+    #[serde(default)]
+    pub sight: f32,
+    // how much this part helps with moving around
+    #[serde(default)]
+    pub moves: f32,
+    // how much this part helps with dragging itself around after the legs r gone
+    #[serde(default)]
+    pub crawls: f32,
+    // how much this part helps with holding and swinging stuff
+    #[serde(default)]
+    pub manipulates: f32,
+    #[serde(default)]
+    pub organs: Vec<PlanOrganInfo>,
+    #[serde(default="default_true")]
+    pub skin: bool,
+    #[serde(default="default_true")]
+    pub muscle: bool,
+    // tourniquets can go on these
+    #[serde(default)]
+    pub limb: bool,
+    // what kind of prosthetic can replace it after its destroyed
+    #[serde(default)]
+    pub prosthetic: Option<ProstheticPart>,
+    // which attacks can reach it, empty means all of them
+    #[serde(default)]
+    pub heights: Vec<DamageHeight>
+}
+
+impl PlanPartInfo
+{
+    pub fn color(&self) -> u32
+    {
+        u32::from_str_radix(self.color.trim_start_matches("0x"), 16)
+            .unwrap_or_else(|err| panic!("{} has an invalid color: {err}", self.name))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BodyPlanInfo
+{
+    #[serde(skip)]
+    pub name: String,
+    // relative to the textures folder
+    pub overlay: String,
+    pub speed: f32,
+    // in liters
+    pub blood: f32,
+    // in tiles
+    pub vision: f32,
+    pub hearing: f32,
+    pub parts: Vec<PlanPartInfo>
+}
+
+pub struct BodyPlansInfo
+{
+    plans: HashMap<String, Arc<BodyPlanInfo>>
+}
+
+impl BodyPlansInfo
+{
+    pub fn parse(info: impl AsRef<Path>) -> Self
+    {
+        let info = File::open(overridden(info)).unwrap();
+
+        let plans: HashMap<String, BodyPlanInfo> = serde_json::from_reader(info).unwrap();
+
+        let plans = plans.into_iter().map(|(name, mut plan)|
+        {
+            plan.parts.iter().flat_map(|part| part.connections.iter().map(move |x| (part, x))).for_each(|(part, connection)|
+            {
+                if !plan.parts.iter().any(|x| x.name == *connection)
+                {
+                    panic!("{} in body plan {name} is connected to {connection} which doesnt exist", part.name);
+                }
+            });
+
+            plan.name = name.clone();
+
+            (name, Arc::new(plan))
+        }).collect();
+
+        Self{plans}
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Arc<BodyPlanInfo>>
+    {
+        self.plans.get(name)
+    }
+
+    pub fn human(&self) -> &Arc<BodyPlanInfo>
+    {
+        self.get(HUMAN_PLAN).unwrap_or_else(|| panic!("body plan {HUMAN_PLAN} must exist"))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item=&Arc<BodyPlanInfo>>
+    {
+        self.plans.values()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanOrgan
+{
+    role: OrganRole,
+    health: Health
+}
+
+impl DamageReceiver for PlanOrgan
+{
+    fn damage(
+        &mut self,
+        _rng: &mut SeededRandom,
+        _side: Side2d,
+        damage: DamageType
+    ) -> Option<DamageType>
+    {
+        self.health.damage_pierce(damage)
+    }
+}
+
+impl Rottable for PlanOrgan
+{
+    fn rot(&mut self, fraction: f32)
+    {
+        self.health.rot(fraction);
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanPart
+{
+    pub name: String,
+    limb: bool,
+    // indices of the parts this one hangs off of
+    #[serde(default)]
+    connections: Vec<usize>,
+    #[serde(default)]
+    sight: f32,
+    moves: f32,
+    #[serde(default)]
+    crawls: f32,
+    #[serde(default)]
+    manipulates: f32,
+    #[serde(default)]
+    prosthetic: Option<ProstheticPart>,
+    heights: Vec<DamageHeight>,
+    pub part: BodyPart<PlanOrgan>
+}
+
+impl PlanPart
+{
+    fn reachable(&self, height: DamageHeight) -> bool
+    {
+        self.heights.is_empty() || self.heights.contains(&height)
+    }
+
+    // how well it works, without caring if its still attached to anything
+    fn working(&self) -> f32
+    {
+        let muscle_health = self.part.muscle.as_ref().map(|x| x.fraction()).unwrap_or(1.0);
+
+        (self.part.bone.fraction() * 0.9 + 0.1) * muscle_health
+    }
+
+    // destroyed and something like this prosthetic can go in its place
+    fn fits(&self, prosthetic: ProstheticPart) -> bool
+    {
+        self.part.bone.is_zero() && self.prosthetic == Some(prosthetic)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanAnatomy
+{
+    pub plan: String,
+    pub base_speed: f32,
+    pub base_strength: f32,
+    vision: f32,
+    hearing: f32,
+    override_crawling: bool,
+    blood: SimpleHealth,
+    antibiotics: f32,
+    pub parts: Vec<PlanPart>,
+    cached: CachedProps
+}
+
+impl PlanAnatomy
+{
+    pub fn new(plan: &BodyPlanInfo, mut info: HumanAnatomyInfo) -> Self
+    {
+        // same fudging as the human ones so the toughness numbers mean the same thing
+        info.bone_toughness *= 0.3;
+        info.muscle_toughness *= 0.6;
+        info.skin_toughness *= 0.6;
+
+        let bone_toughness = info.bone_toughness;
+        let base_speed = info.base_speed;
+        let base_strength = info.base_strength;
+        let part_info = BodyPartInfo::from(info);
+
+        let parts = plan.parts.iter().map(|part|
+        {
+            let organs = part.organs.iter().map(|organ|
+            {
+                PlanOrgan{role: organ.role, health: Health::new(organ.health * 0.15, organ.health)}
+            }).collect();
+
+            let mut body_part = BodyPart::new(
+                DebugName::new(part.name.clone()),
+                part_info.clone(),
+                bone_toughness * part.bone,
+                part.size,
+                organs
+            );
+
+            if !part.skin
+            {
+                body_part.skin = None.into();
+            }
+
+            if !part.muscle
+            {
+                body_part.muscle = None.into();
+            }
+
+            let connections = part.connections.iter().filter_map(|name|
+            {
+                plan.parts.iter().position(|x| x.name == *name)
+            }).collect();
+
+            PlanPart{
+                name: part.name.clone(),
+                limb: part.limb,
+                connections,
+                sight: part.sight,
+                moves: part.moves,
+                crawls: part.crawls,
+                manipulates: part.manipulates,
+                prosthetic: part.prosthetic,
+                heights: part.heights.clone(),
+                part: body_part
+            }
+        }).collect();
+
+        let mut this = Self{
+            plan: plan.name.clone(),
+            base_speed: base_speed * plan.speed,
+            base_strength,
+            vision: plan.vision,
+            hearing: plan.hearing,
+            override_crawling: false,
+            blood: SimpleHealth::new(plan.blood),
+            antibiotics: 0.0,
+            parts,
+            cached: Default::default()
+        };
+
+        this.update_cache();
+
+        this
+    }
+
+    pub fn speed(&self) -> Option<f32>
+    {
+        self.cached.speed
+    }
+
+    pub fn strength(&self) -> Option<f32>
+    {
+        self.cached.strength
+    }
+
+    pub fn stamina(&self) -> Option<f32>
+    {
+        self.cached.stamina
+    }
+
+    pub fn max_stamina(&self) -> Option<f32>
+    {
+        self.cached.max_stamina
+    }
+
+    pub fn vision(&self) -> Option<f32>
+    {
+        self.cached.vision
+    }
+
+    pub fn hearing(&self) -> Option<f32>
+    {
+        self.is_alive().then_some(self.hearing)
+    }
+
+    pub fn is_crawling(&self) -> bool
+    {
+        self.cached.is_crawling
+    }
+
+    pub fn set_speed(&mut self, speed: f32)
+    {
+        self.base_speed = speed;
+
+        self.update_cache();
+    }
+
+    pub fn set_strength(&mut self, strength: f32)
+    {
+        self.base_strength = strength;
+
+        self.update_cache();
+    }
+
+    pub fn health_fraction(&self) -> f32
+    {
+        if self.speed().is_none()
+        {
+            return 0.0;
+        }
+
+        self.size_weighted(|part| part.health_fraction())
+    }
+
+    pub fn blood_fraction(&self) -> f32
+    {
+        self.blood.fraction()
+    }
+
+    pub fn pain(&self) -> f32
+    {
+        self.size_weighted(|part| part.pain())
+    }
+
+    fn size_weighted(&self, f: impl Fn(&BodyPart<PlanOrgan>) -> f32) -> f32
+    {
+        let size: f64 = self.parts.iter().map(|x| x.part.size).sum();
+        if size == 0.0
+        {
+            return 0.0;
+        }
+
+        let weighted: f64 = self.parts.iter().map(|x|
+        {
+            x.part.size * f(&x.part) as f64
+        }).sum();
+
+        (weighted / size) as f32
+    }
+
+    pub(super) fn bandage(&mut self, bandage: &Bandage) -> bool
+    {
+        let worst = self.parts.iter_mut()
+            .filter(|x| !bandage.limbs_only || x.limb)
+            .filter(|x| x.part.bleed > 0.0)
+            .max_by(|a, b| a.part.bleed.partial_cmp(&b.part.bleed).unwrap());
+
+        let worst = some_or_value!(worst, false);
+
+        worst.part.bleed *= 1.0 - bandage.stops.clamp(0.0, 1.0);
+
+        true
+    }
+
+    pub(super) fn apply_drug(&mut self, drug: &Drug) -> bool
+    {
+        match drug
+        {
+            Drug::Heal{..} => false,
+            Drug::Antibiotics{duration} =>
+            {
+                self.antibiotics = self.antibiotics.max(*duration);
+
+                true
+            },
+            Drug::Disinfectant{amount} =>
+            {
+                let worst = self.parts.iter_mut()
+                    .filter(|x| x.part.infection > 0.0)
+                    .max_by(|a, b| a.part.infection.partial_cmp(&b.part.infection).unwrap());
+
+                let worst = some_or_value!(worst, false);
+
+                worst.part.infection = (worst.part.infection - amount).max(0.0);
+
+                true
+            }
+        }
+    }
+
+    // returns false if there was nothing destroyed for it to replace
+    pub(super) fn replace_part(&mut self, prosthetic: &Prosthetic) -> bool
+    {
+        let quality = prosthetic.quality.clamp(0.05, 1.0);
+
+        let replaced = match prosthetic.part
+        {
+            ProstheticPart::Arm | ProstheticPart::Leg =>
+            {
+                let kind = prosthetic.part;
+
+                // the destroyed one closest to the body
+                let root = (0..self.parts.len()).find(|id|
+                {
+                    let part = &self.parts[*id];
+
+                    part.fits(kind) && !part.connections.iter().any(|x| self.parts[*x].fits(kind))
+                });
+
+                let root = some_or_value!(root, false);
+
+                // the whole thing gets fitted at once, no point leaving the broken bits hanging off it
+                let mut fitted = vec![root];
+                while let Some(next) = (0..self.parts.len()).find(|id|
+                {
+                    let part = &self.parts[*id];
+
+                    !fitted.contains(id)
+                        && part.fits(kind)
+                        && part.connections.iter().any(|x| fitted.contains(x))
+                })
+                {
+                    fitted.push(next);
+                }
+
+                fitted.into_iter().for_each(|id| self.parts[id].part.replace(quality));
+
+                true
+            },
+            ProstheticPart::Lung =>
+            {
+                // nowhere to put it if the part its in is gone
+                let lung = self.parts.iter_mut()
+                    .filter(|x| !x.part.bone.is_zero())
+                    .flat_map(|x| x.part.contents.iter_mut())
+                    .find(|x| x.role == OrganRole::Lungs && x.health.is_zero());
+
+                let lung = some_or_value!(lung, false);
+
+                lung.health = lung.health.replaced(quality);
+
+                true
+            }
+        };
+
+        if replaced
+        {
+            self.update_cache();
+        }
+
+        replaced
+    }
+
+    pub fn update(&mut self, dt: f32)
+    {
+        let treated = self.antibiotics > 0.0;
+        self.antibiotics = (self.antibiotics - dt).max(0.0);
+
+        let (bled, rotted) = self.parts.iter_mut().map(|x| x.part.update_wounds(dt, treated))
+            .fold((0.0, 0.0), |(bled, rotted), (bleed, rot)| (bled + bleed, rotted + rot));
+
+        if rotted > 0.0
+        {
+            self.update_cache();
+        }
+
+        if bled > 0.0
+        {
+            let was_alive = self.blood.current() > 0.0;
+
+            self.blood.subtract_hp(bled);
+
+            if was_alive && self.blood.current() <= 0.0
+            {
+                self.update_cache();
+            }
+        }
+    }
+
+    pub fn for_broken_parts(&mut self, mut f: impl FnMut(BrokenPart))
+    {
+        self.parts.iter_mut().for_each(|x|
+        {
+            let name = &x.name;
+            x.part.consume_broken().for_each(|kind|
+            {
+                f(BrokenPart::new(kind, name.clone(), format!("{name} broken")));
+            });
+        });
+    }
+
+    fn damage_random_part(&mut self, mut damage: Damage) -> Option<Damage>
+    {
+        let height = damage.direction.height;
+
+        let reachable: Vec<usize> = (0..self.parts.len())
+            .filter(|id| self.parts[*id].reachable(height))
+            .collect();
+
+        // whatever it is must be hittable somehow
+        let ids = if reachable.is_empty() { (0..self.parts.len()).collect() } else { reachable };
+
+        let picked = WeightedPicker::pick_from(
+            damage.rng.next_f64(),
+            &ids,
+            |id| self.parts[*id].part.size
+        ).copied();
+
+        let pierce = picked.and_then(|id| self.parts[id].part.damage(damage));
+
+        self.update_cache();
+
+        pierce
+    }
+
+    fn organs(&self, role: OrganRole) -> impl Iterator<Item=&PlanOrgan>
+    {
+        self.parts.iter().flat_map(|x| x.part.contents.iter()).filter(move |x| x.role == role)
+    }
+
+    fn organs_fraction(&self, role: OrganRole) -> Option<f32>
+    {
+        let (amount, total) = self.organs(role).fold((0, 0.0), |(amount, total), organ|
+        {
+            (amount + 1, total + organ.health.fraction())
+        });
+
+        (amount > 0).then(|| total / amount as f32)
+    }
+
+    fn is_alive(&self) -> bool
+    {
+        if self.blood.current() <= 0.0
+        {
+            return false;
+        }
+
+        // a body without any brains is kept alive by something else
+        self.organs_fraction(OrganRole::Brain).map(|x| x > 0.0).unwrap_or(true)
+    }
+
+    // a part only does anything if everything between it and the body is still there
+    fn attached(&self) -> Vec<bool>
+    {
+        let mut attached: Vec<bool> = self.parts.iter().map(|x| !x.part.bone.is_zero()).collect();
+
+        // every pass pushes the cut off parts one step further out
+        (0..self.parts.len()).for_each(|_|
+        {
+            self.parts.iter().enumerate().for_each(|(id, part)|
+            {
+                if part.connections.iter().any(|x| !attached[*x])
+                {
+                    attached[id] = false;
+                }
+            });
+        });
+
+        attached
+    }
+
+    fn working_sum(&self, attached: &[bool], weight: impl Fn(&PlanPart) -> f32) -> f32
+    {
+        self.parts.iter().zip(attached).filter(|(_, attached)| **attached).map(|(x, _)|
+        {
+            weight(x) * x.working()
+        }).sum()
+    }
+
+    // none if nothing in the plan does this at all
+    fn working_fraction(&self, attached: &[bool], weight: impl Fn(&PlanPart) -> f32 + Copy) -> Option<f32>
+    {
+        let total: f32 = self.parts.iter().map(weight).sum();
+
+        (total > 0.0).then(|| self.working_sum(attached, weight) / total)
+    }
+
+    fn updated_speed(&mut self, attached: &[bool]) -> (bool, Option<f32>)
+    {
+        if !self.is_alive()
+        {
+            return (false, None);
+        }
+
+        let total: f32 = self.parts.iter().map(|x| x.moves).sum();
+        if total == 0.0
+        {
+            return (false, None);
+        }
+
+        let walking = self.working_sum(attached, |x| x.moves) / total;
+
+        let crawling = self.override_crawling || walking < CRAWL_THRESHOLD;
+
+        let can_crawl = self.parts.iter().any(|x| x.crawls > 0.0);
+
+        // crawling is measured against walking so its always slower
+        let speed_scale = if crawling && can_crawl
+        {
+            self.working_sum(attached, |x| x.crawls) / total
+        } else
+        {
+            walking
+        };
+
+        let speed = (speed_scale > 0.0).then(|| self.base_speed * speed_scale);
+
+        (crawling, speed)
+    }
+
+    fn updated_strength(&self, attached: &[bool]) -> f32
+    {
+        let manipulating = self.working_fraction(attached, |x| x.manipulates).unwrap_or(1.0);
+
+        self.base_strength * (MIN_STRENGTH + (1.0 - MIN_STRENGTH) * manipulating)
+    }
+
+    fn updated_vision(&self, attached: &[bool]) -> f32
+    {
+        let seeing = self.working_fraction(attached, |x| x.sight).unwrap_or(1.0);
+
+        let scale = if seeing > 0.0
+        {
+            ONE_EYE_VISION + (1.0 - ONE_EYE_VISION) * seeing
+        } else
+        {
+            0.0
+        };
+
+        TILE_SIZE * self.vision * scale
+    }
+
+    pub(super) fn override_crawling(&mut self, state: bool)
+    {
+        self.override_crawling = state;
+        self.update_cache();
+    }
+
+    fn update_cache(&mut self)
+    {
+        let alive = self.is_alive();
+        let attached = self.attached();
+
+        (self.cached.is_crawling, self.cached.speed) = self.updated_speed(&attached);
+        self.cached.strength = alive.then(|| self.updated_strength(&attached));
+        self.cached.stamina = alive.then_some(0.5);
+
+        let lungs = self.organs_fraction(OrganRole::Lungs).unwrap_or(1.0);
+        self.cached.max_stamina = alive.then_some(10.0 * (lungs * 0.8 + 0.2));
+
+        self.cached.vision = alive.then(|| self.updated_vision(&attached));
+    }
+}
+
+impl Damageable for PlanAnatomy
+{
+    fn damage(&mut self, mut damage: Damage) -> Option<Damage>
+    {
+        if self.is_crawling()
+        {
+            damage = damage * 2.0;
+        }
+
+        self.damage_random_part(damage)
+    }
+}
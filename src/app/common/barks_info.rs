@@ -0,0 +1,88 @@
+use std::{
+    fs::File,
+    path::Path,
+    collections::HashMap
+};
+
+use serde::{Serialize, Deserialize};
+
+use crate::common::{
+    overrides::overridden,
+    rng::{self, RngStream}
+};
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum BarkKind
+{
+    Spot,
+    Hurt,
+    Flee
+}
+
+fn default_cooldown() -> f32
+{
+    8.0
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BarkTable
+{
+    #[serde(default)]
+    pub spot: Vec<String>,
+    #[serde(default)]
+    pub hurt: Vec<String>,
+    #[serde(default)]
+    pub flee: Vec<String>,
+    // seconds before the same character can bark again
+    #[serde(default="default_cooldown")]
+    pub cooldown: f32
+}
+
+impl BarkTable
+{
+    pub fn lines(&self, kind: BarkKind) -> &[String]
+    {
+        match kind
+        {
+            BarkKind::Spot => &self.spot,
+            BarkKind::Hurt => &self.hurt,
+            BarkKind::Flee => &self.flee
+        }
+    }
+
+    pub fn pick(&self, kind: BarkKind) -> Option<&str>
+    {
+        let lines = self.lines(kind);
+
+        if lines.is_empty()
+        {
+            return None;
+        }
+
+        Some(&lines[rng::usize(RngStream::Ai, 0..lines.len())])
+    }
+}
+
+pub struct BarksInfo
+{
+    tables: HashMap<String, BarkTable>
+}
+
+impl BarksInfo
+{
+    pub fn parse(info: impl AsRef<Path>) -> Self
+    {
+        let info = File::open(overridden(info)).unwrap();
+
+        let tables = serde_json::from_reader(info).unwrap();
+
+        Self{tables}
+    }
+
+    pub fn get(&self, name: &str) -> Option<&BarkTable>
+    {
+        self.tables.get(name)
+    }
+}
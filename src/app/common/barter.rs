@@ -0,0 +1,214 @@
+use serde::{Serialize, Deserialize};
+
+use crate::common::{ItemInfo, InventoryItem, Faction, Player};
+
+
+// how close u have to stay to keep trading
+pub const TRADE_DISTANCE: f32 = 0.5;
+
+// how much patience a vendor gets back every second
+const PATIENCE_RECOVERY: f32 = 0.02;
+const HAGGLE_PATIENCE: f32 = 0.25;
+
+const LIKED_MULTIPLIER: f32 = 1.5;
+const DISLIKED_MULTIPLIER: f32 = 0.25;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct VendorInfo
+{
+    pub likes: Vec<String>,
+    pub dislikes: Vec<String>,
+    pub greed: f32,
+    // seconds until the stock gets topped up again
+    pub restock: f32
+}
+
+impl Default for VendorInfo
+{
+    fn default() -> Self
+    {
+        Self{
+            likes: Vec::new(),
+            dislikes: vec!["trash".to_owned()],
+            greed: 1.3,
+            restock: 300.0
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum BarterResponse
+{
+    Accept,
+    Counter{missing: f32},
+    Refuse
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Haggler
+{
+    pub price_multiplier: f32,
+    pub charisma: f32
+}
+
+impl Haggler
+{
+    pub fn new(player: &Player, faction: Faction) -> Self
+    {
+        Self{
+            price_multiplier: player.reputation.price_multiplier(faction),
+            charisma: player.charisma
+        }
+    }
+}
+
+// indices into the players and the vendors inventories
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TradeOffer
+{
+    pub given: Vec<InventoryItem>,
+    pub taken: Vec<InventoryItem>
+}
+
+impl TradeOffer
+{
+    pub fn is_empty(&self) -> bool
+    {
+        self.given.is_empty() && self.taken.is_empty()
+    }
+
+    pub fn toggle_given(&mut self, item: InventoryItem)
+    {
+        Self::toggle(&mut self.given, item);
+    }
+
+    pub fn toggle_taken(&mut self, item: InventoryItem)
+    {
+        Self::toggle(&mut self.taken, item);
+    }
+
+    fn toggle(items: &mut Vec<InventoryItem>, item: InventoryItem)
+    {
+        if let Some(index) = items.iter().position(|x| *x == item)
+        {
+            items.remove(index);
+        } else
+        {
+            items.push(item);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Vendor
+{
+    info: VendorInfo,
+    patience: f32,
+    #[serde(default)]
+    restock_timer: f32
+}
+
+impl From<VendorInfo> for Vendor
+{
+    fn from(info: VendorInfo) -> Self
+    {
+        let restock_timer = info.restock;
+
+        Self{info, patience: 1.0, restock_timer}
+    }
+}
+
+impl Vendor
+{
+    pub fn update(&mut self, dt: f32)
+    {
+        self.patience = (self.patience + PATIENCE_RECOVERY * dt).min(1.0);
+
+        if self.restock_timer > 0.0
+        {
+            self.restock_timer -= dt;
+        }
+    }
+
+    pub fn restock_due(&self) -> bool
+    {
+        self.restock_timer <= 0.0
+    }
+
+    pub fn restocked(&mut self)
+    {
+        self.restock_timer = self.info.restock;
+    }
+
+    pub fn patience(&self) -> f32
+    {
+        self.patience
+    }
+
+    // what the vendor thinks an item the player offers is worth
+    pub fn value_of(&self, info: &ItemInfo) -> f32
+    {
+        let has_group = |groups: &[String]|
+        {
+            info.groups.iter().any(|group| groups.contains(group))
+        };
+
+        let multiplier = if has_group(&self.info.dislikes)
+        {
+            DISLIKED_MULTIPLIER
+        } else if has_group(&self.info.likes)
+        {
+            LIKED_MULTIPLIER
+        } else
+        {
+            1.0
+        };
+
+        info.value * multiplier
+    }
+
+    pub fn asking_price<'a>(
+        &self,
+        haggler: &Haggler,
+        items: impl Iterator<Item=&'a ItemInfo>
+    ) -> f32
+    {
+        let value: f32 = items.map(|info| info.value).sum();
+
+        value * self.info.greed * haggler.price_multiplier
+    }
+
+    pub fn offered_value<'a>(&self, items: impl Iterator<Item=&'a ItemInfo>) -> f32
+    {
+        items.map(|info| self.value_of(info)).sum()
+    }
+
+    // offered is what the player gives (from offered_value), asked is the asking_price
+    pub fn respond(
+        &mut self,
+        haggler: &Haggler,
+        offered: f32,
+        asked: f32
+    ) -> BarterResponse
+    {
+        if self.patience <= 0.0
+        {
+            return BarterResponse::Refuse;
+        }
+
+        // a charming player can talk the price down, but less so the more annoyed the vendor is
+        let leeway = (haggler.charisma * 0.15 * self.patience).clamp(0.0, 0.3);
+        let threshold = asked * (1.0 - leeway);
+
+        if offered >= threshold
+        {
+            BarterResponse::Accept
+        } else
+        {
+            self.patience = (self.patience - HAGGLE_PATIENCE).max(0.0);
+
+            BarterResponse::Counter{missing: threshold - offered}
+        }
+    }
+}
@@ -0,0 +1,204 @@
+use std::f32;
+
+use serde::{Serialize, Deserialize};
+
+use nalgebra::{Unit, Vector3};
+
+use crate::common::{
+    rng::{self, RngStream},
+    enemy::BehaviorState,
+    Entity
+};
+
+
+// how far from home patrolling goes
+const PATROL_RADIUS: f32 = 1.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StateKind
+{
+    Wait,
+    MoveDirection,
+    Investigate,
+    Search,
+    Flee,
+    Attack
+}
+
+impl From<&BehaviorState> for StateKind
+{
+    fn from(state: &BehaviorState) -> Self
+    {
+        match state
+        {
+            BehaviorState::Wait => Self::Wait,
+            BehaviorState::MoveDirection(_) => Self::MoveDirection,
+            BehaviorState::Investigate(_) => Self::Investigate,
+            BehaviorState::Search{..} => Self::Search,
+            BehaviorState::Flee(_) => Self::Flee,
+            BehaviorState::Attack(_) => Self::Attack
+        }
+    }
+}
+
+// what the tree gets to look at when picking what to do next
+pub struct Blackboard<'a>
+{
+    // the state that just ended
+    pub previous: &'a BehaviorState,
+    pub companion: bool,
+    pub health: f32,
+    pub position: Vector3<f32>,
+    pub home: Vector3<f32>,
+    // whatever it last fought or ran from
+    pub enemy: Option<Entity>
+}
+
+// things the tree wants done that arent a state
+#[derive(Debug, Clone, Default)]
+pub struct BehaviorEffects
+{
+    pub call_for_help: Option<Entity>
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Status
+{
+    Failure,
+    Success,
+    Running(BehaviorState)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum BehaviorNode
+{
+    // runs children until one fails or picks a state
+    Sequence(Vec<BehaviorNode>),
+    // runs children until one doesnt fail
+    Selector(Vec<BehaviorNode>),
+    Invert(Box<BehaviorNode>),
+    Chance(f32, Box<BehaviorNode>),
+    Companion,
+    Was(StateKind),
+    // health fraction below this
+    Hurt(f32),
+    HasEnemy,
+    FarFromHome(f32),
+    Wait,
+    Wander,
+    Attack,
+    Flee,
+    GoHome,
+    Patrol,
+    CallForHelp
+}
+
+impl BehaviorNode
+{
+    // same as what enemies did before trees existed, stand around then walk somewhere
+    pub fn default_tree() -> Self
+    {
+        Self::Selector(vec![
+            Self::Sequence(vec![Self::Companion, Self::Wait]),
+            Self::Sequence(vec![Self::Was(StateKind::Wait), Self::Wander]),
+            Self::Wait
+        ])
+    }
+
+    pub fn decide(&self, board: &Blackboard, effects: &mut BehaviorEffects) -> Option<BehaviorState>
+    {
+        match self.tick(board, effects)
+        {
+            Status::Running(state) => Some(state),
+            _ => None
+        }
+    }
+
+    fn tick(&self, board: &Blackboard, effects: &mut BehaviorEffects) -> Status
+    {
+        let condition = |x: bool| if x { Status::Success } else { Status::Failure };
+
+        match self
+        {
+            Self::Sequence(nodes) =>
+            {
+                for node in nodes
+                {
+                    match node.tick(board, effects)
+                    {
+                        Status::Success => (),
+                        x => return x
+                    }
+                }
+
+                Status::Success
+            },
+            Self::Selector(nodes) =>
+            {
+                for node in nodes
+                {
+                    match node.tick(board, effects)
+                    {
+                        Status::Failure => (),
+                        x => return x
+                    }
+                }
+
+                Status::Failure
+            },
+            Self::Invert(node) =>
+            {
+                match node.tick(board, effects)
+                {
+                    Status::Failure => Status::Success,
+                    Status::Success => Status::Failure,
+                    x => x
+                }
+            },
+            Self::Chance(chance, node) =>
+            {
+                if rng::f32(RngStream::Ai) < *chance
+                {
+                    node.tick(board, effects)
+                } else
+                {
+                    Status::Failure
+                }
+            },
+            Self::Companion => condition(board.companion),
+            Self::Was(kind) => condition(StateKind::from(board.previous) == *kind),
+            Self::Hurt(fraction) => condition(board.health < *fraction),
+            Self::HasEnemy => condition(board.enemy.is_some()),
+            Self::FarFromHome(distance) =>
+            {
+                condition((board.home - board.position).xy().magnitude() > *distance)
+            },
+            Self::Wait => Status::Running(BehaviorState::Wait),
+            Self::Wander => Status::Running(BehaviorState::MoveDirection(random_direction())),
+            Self::Attack => board.enemy.map(|x| Status::Running(BehaviorState::Attack(x)))
+                .unwrap_or(Status::Failure),
+            Self::Flee => board.enemy.map(|x| Status::Running(BehaviorState::Flee(x)))
+                .unwrap_or(Status::Failure),
+            Self::GoHome => Status::Running(BehaviorState::Investigate(board.home)),
+            Self::Patrol =>
+            {
+                let offset = random_direction().into_inner() * (rng::f32(RngStream::Ai) * PATROL_RADIUS);
+
+                Status::Running(BehaviorState::Investigate(board.home + offset))
+            },
+            Self::CallForHelp =>
+            {
+                effects.call_for_help = board.enemy;
+
+                condition(board.enemy.is_some())
+            }
+        }
+    }
+}
+
+fn random_direction() -> Unit<Vector3<f32>>
+{
+    let angle = rng::f32(RngStream::Ai) * (f32::consts::PI * 2.0);
+
+    Unit::new_unchecked(Vector3::new(angle.cos(), angle.sin(), 0.0))
+}
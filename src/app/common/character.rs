@@ -1,7 +1,7 @@
 use std::{
     f32,
     mem,
-    cell::Ref,
+    cell::{Ref, RefCell},
     borrow::Cow,
     sync::Arc
 };
@@ -10,15 +10,14 @@ use parking_lot::{RwLock, Mutex};
 
 use serde::{Serialize, Deserialize};
 
+use strum::{EnumIter, IntoEnumIterator, IntoStaticStr};
+
 use nalgebra::{Unit, Vector3};
 
 use yanyaengine::{Assets, Transform, TextureId};
 
 use crate::{
-    client::{
-        CommonTextures,
-        ConnectionsHandler
-    },
+    client::ConnectionsHandler,
     common::{
         some_or_return,
         some_or_value,
@@ -29,26 +28,41 @@ use crate::{
         render_info::*,
         lazy_transform::*,
         collider::*,
+        raycast::*,
         watcher::*,
         damage::*,
         damaging::*,
-        particle_creator::*,
-        raycast::*,
         physics::*,
+        message::Message,
+        EntityPasser,
         Hairstyle,
         Side1d,
+        Side2d,
         AnyEntities,
         Entity,
         EntityInfo,
         CharacterId,
         CharactersInfo,
         ItemsInfo,
+        FactionsInfo,
         Item,
         InventoryItem,
+        EquipSlot,
         ItemInfo,
+        Ranged,
         Parent,
         Anatomy,
-        entity::{damaging_system, ClientEntities}
+        Needs,
+        joint::{Joint, TetherJoint},
+        ParticleEffects,
+        Projectiles,
+        Tracer,
+        Sound,
+        SoundEmitter,
+        Noise,
+        NoiseKind,
+        world::World,
+        entity::ClientEntities
     }
 };
 
@@ -118,22 +132,41 @@ impl<T> Stateful<T>
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter, IntoStaticStr, Serialize, Deserialize)]
 pub enum Faction
 {
     Player,
-    Zob
+    Zob,
+    Neutral,
+    Animal
 }
 
 impl Faction
 {
-    pub fn aggressive(&self, other: &Self) -> bool
+    // peaceful factions dont start fights but can still get hurt by players
+    pub fn can_damage(&self, other: &Self) -> bool
     {
         define_layers!{
             self, other,
             (Player, Player, false),
             (Zob, Zob, false),
-            (Player, Zob, true)
+            (Neutral, Neutral, false),
+            (Animal, Animal, false),
+            (Player, Zob, true),
+            (Player, Neutral, true),
+            (Player, Animal, true),
+            (Zob, Neutral, false),
+            (Zob, Animal, true),
+            (Neutral, Animal, true)
+        }
+    }
+
+    pub fn has_reputation(&self) -> bool
+    {
+        match self
+        {
+            Self::Player | Self::Animal => false,
+            Self::Zob | Self::Neutral => true
         }
     }
 }
@@ -144,12 +177,66 @@ pub enum CharacterAction
     Throw(Vector3<f32>),
     Poke{state: bool},
     Bash,
-    Ranged{state: bool, target: Vector3<f32>}
+    Ranged{state: bool, target: Vector3<f32>},
+    Block{state: bool},
+    Dodge(Vector3<f32>),
+    // none lets go of whatever its dragging
+    Drag(Option<Entity>)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockResult
+{
+    Hit,
+    Blocked,
+    Parried
 }
 
 pub const DEFAULT_HELD_DISTANCE: f32 = 0.1;
 pub const POKE_DISTANCE: f32 = 0.75;
 
+pub const GUNSHOT_NOISE: f32 = 3.0;
+
+// on concrete at a walk, the floor material and how fast its going scale it
+pub const FOOTSTEP_NOISE: f32 = 0.4;
+pub const SPRINT_STEP_LOUDNESS: f32 = 2.0;
+pub const CRAWL_STEP_LOUDNESS: f32 = 0.3;
+pub const CROUCH_STEP_LOUDNESS: f32 = 0.5;
+
+pub const CROUCH_SPEED: f32 = 0.5;
+
+// how much of the light still gives away someone lying low
+pub const CROUCH_VISIBILITY: f32 = 0.6;
+pub const CRAWL_VISIBILITY: f32 = 0.4;
+
+// blocking right before getting hit counts as a parry
+pub const PARRY_WINDOW: f32 = 0.2;
+pub const BLOCK_MITIGATION: f32 = 0.3;
+pub const BLOCK_STAMINA_COST: f32 = 0.5;
+pub const STAGGER_TIME: f32 = 0.8;
+
+pub const DODGE_STAMINA_COST: f32 = 2.0;
+// how long the character cant be hurt for after starting a dodge
+pub const DODGE_TIME: f32 = 0.3;
+pub const DODGE_COOLDOWN: f32 = 1.2;
+// relative to the walking speed
+pub const DODGE_SPEED: f32 = 3.0;
+
+// how heavy something has to be to halve the walking speed
+pub const DRAG_STRENGTH: f32 = 60.0;
+
+// lighter stuff gets picked up instead
+pub const DRAG_MIN_MASS: f32 = 10.0;
+
+// gets let go if it ends up further than this (stuck on something)
+pub const DRAG_BREAK_DISTANCE: f32 = 0.5;
+
+// anything lighter than this much of the characters own mass gets pulled in, otherwise the character gets pulled
+pub const GRAPPLE_PULL_RATIO: f32 = 0.8;
+
+// stops reeling in this close
+pub const GRAPPLE_MIN_LENGTH: f32 = ENTITY_SCALE;
+
 // hands r actually 0.1 meters in size but they look too small that way
 pub const HAND_SCALE: f32 = 0.3;
 
@@ -158,7 +245,9 @@ pub struct PartialCombinedInfo<'a>
 {
     pub passer: &'a Arc<RwLock<ConnectionsHandler>>,
     pub assets: &'a Arc<Mutex<Assets>>,
-    pub common_textures: &'a CommonTextures,
+    pub particle_effects: &'a ParticleEffects,
+    pub projectiles: &'a RefCell<Projectiles>,
+    pub world: &'a World,
     pub items_info: &'a ItemsInfo,
     pub characters_info: &'a CharactersInfo
 }
@@ -174,7 +263,9 @@ impl<'a> PartialCombinedInfo<'a>
             entities,
             assets: self.assets,
             passer: self.passer,
-            common_textures: self.common_textures,
+            particle_effects: self.particle_effects,
+            projectiles: self.projectiles,
+            world: self.world,
             items_info: self.items_info,
             characters_info: self.characters_info
         }
@@ -187,7 +278,9 @@ pub struct CombinedInfo<'a>
     pub passer: &'a Arc<RwLock<ConnectionsHandler>>,
     pub entities: &'a ClientEntities,
     pub assets: &'a Arc<Mutex<Assets>>,
-    pub common_textures: &'a CommonTextures,
+    pub particle_effects: &'a ParticleEffects,
+    pub projectiles: &'a RefCell<Projectiles>,
+    pub world: &'a World,
     pub items_info: &'a ItemsInfo,
     pub characters_info: &'a CharactersInfo
 }
@@ -203,7 +296,11 @@ impl CombinedInfo<'_>
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CharacterSyncInfo
 {
-    pub rotation: f32
+    pub rotation: f32,
+    pub blocking: Option<f32>,
+    pub dodging: f32,
+    // the server needs it to know if a block holds up
+    pub stamina: f32
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -213,7 +310,10 @@ pub struct AfterInfo
     hand_left: Entity,
     hand_right: Entity,
     holding: Entity,
-    hair: Vec<Entity>
+    hair: Vec<Entity>,
+    // one for every equipment slot in order
+    #[serde(default)]
+    worn: Vec<Entity>
 }
 
 #[derive(Default, Debug, Clone)]
@@ -222,6 +322,23 @@ struct CachedInfo
     pub bash_distance: Option<f32>
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct Dragging
+{
+    entity: Entity,
+    mass: f32
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct Grappling
+{
+    // whichever one has the joint
+    pulled: Entity,
+    // stuck in a wall, gets removed when letting go
+    hook: Option<Entity>,
+    speed: f32
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 enum AttackState
 {
@@ -236,6 +353,8 @@ pub struct Character
     pub id: CharacterId,
     pub faction: Faction,
     pub sprinting: bool,
+    #[serde(default)]
+    pub crouching: bool,
     pub rotation: f32,
     oversprint_cooldown: f32,
     stamina: f32,
@@ -249,6 +368,12 @@ pub struct Character
     held_update: bool,
     attack_cooldown: f32,
     bash_side: Side1d,
+    blocking: Option<f32>,
+    stagger: f32,
+    dodging: f32,
+    dodge_cooldown: f32,
+    dragging: Option<Dragging>,
+    grappling: Option<Grappling>,
     actions: Vec<CharacterAction>,
     sprite_state: Stateful<SpriteState>
 }
@@ -264,6 +389,7 @@ impl Character
             id,
             faction,
             sprinting: false,
+            crouching: false,
             rotation: 0.0,
             oversprint_cooldown: 0.0,
             stamina: f32::MAX,
@@ -276,6 +402,12 @@ impl Character
             held_update: true,
             attack_cooldown: 0.0,
             bash_side: Side1d::Left,
+            blocking: None,
+            stagger: 0.0,
+            dodging: 0.0,
+            dodge_cooldown: 0.0,
+            dragging: None,
+            grappling: None,
             actions: Vec::new(),
             sprite_state: SpriteState::Normal.into()
         }
@@ -284,13 +416,19 @@ impl Character
     pub fn get_sync_info(&self) -> CharacterSyncInfo
     {
         CharacterSyncInfo{
-            rotation: self.rotation
+            rotation: self.rotation,
+            blocking: self.blocking,
+            dodging: self.dodging,
+            stamina: self.stamina
         }
     }
 
     pub fn sync_info(&mut self, info: CharacterSyncInfo)
     {
         self.rotation = info.rotation;
+        self.blocking = info.blocking;
+        self.dodging = info.dodging;
+        self.stamina = info.stamina;
     }
 
     fn default_connection() -> Connection
@@ -449,13 +587,31 @@ impl Character
             }
         }
 
+        let worn = EquipSlot::iter().map(|slot|
+        {
+            inserter(EntityInfo{
+                lazy_transform: Some(LazyTransformInfo::default().into()),
+                parent: Some(Parent::new(entity, false)),
+                render: Some(RenderInfo{
+                    object: Some(RenderObjectKind::TextureId{
+                        id: character_info.hand
+                    }.into()),
+                    z_level: slot.z_level(),
+                    ..Default::default()
+                }),
+                watchers: Some(Default::default()),
+                ..Default::default()
+            })
+        }).collect();
+
         let hand_left = inserter(held_item(None, true));
         let info = AfterInfo{
             this: entity,
             hand_left,
             hand_right: inserter(held_item(None, false)),
             holding: inserter(held_item(Some(hand_left), false)),
-            hair
+            hair,
+            worn
         };
 
         self.info = Some(info);
@@ -474,12 +630,22 @@ impl Character
         self.actions.push(action);
     }
 
+    pub fn holding(&self) -> Option<InventoryItem>
+    {
+        self.holding
+    }
+
     pub fn set_holding(&mut self, holding: Option<InventoryItem>)
     {
         self.holding = holding;
         self.held_update = true;
     }
 
+    pub fn equipment_changed(&mut self)
+    {
+        self.held_update = true;
+    }
+
     pub fn dropped_item(&mut self, item: InventoryItem)
     {
         if Some(item) == self.holding
@@ -488,6 +654,20 @@ impl Character
         }
     }
 
+    // removed has to be sorted from the last item to the first
+    pub fn removed_items(&mut self, removed: &[InventoryItem])
+    {
+        let holding = self.holding.and_then(|holding|
+        {
+            removed.iter().try_fold(holding, |holding, removed| holding.after_removal(*removed))
+        });
+
+        if holding != self.holding
+        {
+            self.set_holding(holding);
+        }
+    }
+
     pub fn newtons(&self, combined_info: CombinedInfo) -> Option<f32>
     {
         self.anatomy(combined_info.entities).and_then(|x| x.strength().map(|strength| strength * 30.0))
@@ -499,6 +679,11 @@ impl Character
         self.stamina
     }
 
+    pub fn dodge_cooldown(&self) -> f32
+    {
+        self.dodge_cooldown
+    }
+
     pub fn attack_cooldown(&self) -> f32
     {
         self.attack_cooldown
@@ -511,12 +696,21 @@ impl Character
 
     pub fn stamina_speed(&self, combined_info: CombinedInfo) -> Option<f32>
     {
-        self.anatomy(combined_info.entities).and_then(|x| x.stamina())
+        let scale = self.needs_scale(combined_info.entities, Needs::stamina_regen_scale);
+
+        self.anatomy(combined_info.entities).and_then(|x| x.stamina()).map(|x| x * scale)
     }
 
     pub fn max_stamina(&self, entities: &ClientEntities) -> Option<f32>
     {
-        self.anatomy(entities).and_then(|x| x.max_stamina())
+        let scale = self.needs_scale(entities, Needs::max_stamina_scale);
+
+        self.anatomy(entities).and_then(|x| x.max_stamina()).map(|x| x * scale)
+    }
+
+    fn needs_scale(&self, entities: &ClientEntities, f: impl FnOnce(&Needs) -> f32) -> f32
+    {
+        self.info.as_ref().and_then(|info| entities.needs(info.this)).map(|needs| f(&needs)).unwrap_or(1.0)
     }
 
     fn held_attack_cooldown(&self, combined_info: CombinedInfo) -> Option<f32>
@@ -690,6 +884,8 @@ impl Character
         lazy_for(hand_left);
         lazy_for(hand_right);
 
+        self.update_worn(combined_info);
+
         if !holding_state
         {
             self.forward_point(combined_info);
@@ -698,6 +894,30 @@ impl Character
         self.held_update = false;
     }
 
+    fn update_worn(&self, combined_info: CombinedInfo)
+    {
+        let entities = &combined_info.entities;
+        let info = some_or_return!(self.info.as_ref());
+
+        let equipment = entities.equipment(info.this);
+
+        EquipSlot::iter().zip(info.worn.iter().copied()).for_each(|(slot, worn)|
+        {
+            let item = equipment.as_ref().and_then(|equipment| equipment.get(slot))
+                .map(|item| combined_info.items_info.get(item.id));
+
+            some_or_return!(entities.parent_mut(worn)).visible = item.is_some();
+
+            if let Some(item) = item
+            {
+                let texture = combined_info.assets.lock().texture(item.texture.unwrap()).clone();
+
+                some_or_return!(entities.lazy_transform_mut(worn)).target().scale = item.scale3();
+                some_or_return!(entities.render_mut(worn)).set_texture(texture);
+            }
+        });
+    }
+
     fn throw_held(
         &mut self,
         combined_info: CombinedInfo,
@@ -717,6 +937,18 @@ impl Character
         {
             let info = self.info.as_ref().unwrap();
 
+            if item_info.explosive.is_some()
+            {
+                // the server takes it out of the inventory too and simulates the throw
+                combined_info.passer.write().send_message(Message::FireProjectile{entity: info.this, item: held, target});
+
+                entities.inventory_mut(info.this).unwrap().remove(held);
+
+                self.held_update = true;
+
+                return;
+            }
+
             let entity_info = {
                 let holding_transform = entities.transform(info.holding).unwrap();
 
@@ -729,8 +961,10 @@ impl Character
                     Vector3::new(rotation.cos(), -rotation.sin(), 0.0)
                 };
 
+                // no real volumes so heavier stuff just counts as denser
                 let mut physical: Physical = PhysicalProperties{
                     inverse_mass: item_info.mass.recip(),
+                    density: 0.5 + item_info.mass,
                     ..Default::default()
                 }.into();
 
@@ -777,36 +1011,9 @@ impl Character
                     watchers: Some(Watchers::new(vec![
                         Watcher{
                             kind: WatcherType::Lifetime(2.5.into()),
-                            action: WatcherAction::Explode(Box::new(ExplodeInfo{
-                                keep: false,
-                                info: ParticlesInfo{
-                                    amount: 3..5,
-                                    speed: ParticleSpeed::Random(0.1),
-                                    decay: ParticleDecay::Random(3.5..=5.0),
-                                    position: ParticlePosition::Spread(1.0),
-                                    rotation: ParticleRotation::Random,
-                                    scale: ParticleScale::Spread{
-                                        scale: Vector3::repeat(ENTITY_SCALE * 0.4),
-                                        variation: 0.1
-                                    },
-                                    min_scale: ENTITY_SCALE * 0.02
-                                },
-                                prototype: EntityInfo{
-                                    physical: Some(PhysicalProperties{
-                                        inverse_mass: 0.01_f32.recip(),
-                                        floating: true,
-                                        ..Default::default()
-                                    }.into()),
-                                    render: Some(RenderInfo{
-                                        object: Some(RenderObjectKind::TextureId{
-                                            id: combined_info.common_textures.dust
-                                        }.into()),
-                                        z_level: ZLevel::BelowFeet,
-                                        ..Default::default()
-                                    }),
-                                    ..Default::default()
-                                }
-                            })),
+                            action: WatcherAction::Explode(Box::new(
+                                combined_info.particle_effects.get("dust").explode(false, Vector3::x_axis())
+                            )),
                             ..Default::default()
                         }
                     ])),
@@ -850,7 +1057,338 @@ impl Character
     {
         let state = *self.sprite_state.value();
 
-        state == SpriteState::Normal || state == SpriteState::Crawling
+        let busy = self.blocking.is_some() || self.is_staggered() || self.is_dodging();
+
+        (state == SpriteState::Normal || state == SpriteState::Crawling) && !busy
+    }
+
+    pub fn is_winding_up(&self) -> bool
+    {
+        self.attack_state != AttackState::None
+    }
+
+    pub fn is_blocking(&self) -> bool
+    {
+        self.blocking.is_some()
+    }
+
+    pub fn is_staggered(&self) -> bool
+    {
+        self.stagger > 0.0
+    }
+
+    pub fn is_dodging(&self) -> bool
+    {
+        self.dodging > 0.0
+    }
+
+    pub fn can_dodge(&self) -> bool
+    {
+        self.attackable_state() && self.dodge_cooldown <= 0.0 && self.stamina > 0.0
+    }
+
+    fn dodge(&mut self, combined_info: CombinedInfo, direction: Vector3<f32>)
+    {
+        if !self.can_dodge()
+        {
+            return;
+        }
+
+        let direction = some_or_return!(
+            Unit::try_new(Vector3::new(direction.x, direction.y, 0.0), 0.01)
+        );
+
+        let entity = some_or_return!(self.info.as_ref()).this;
+
+        let speed = some_or_return!(combined_info.entities.anatomy(entity).and_then(|x| x.speed()));
+        let mut physical = some_or_return!(combined_info.entities.physical_mut(entity));
+
+        let mut velocity = *direction * (speed * physical.inverse_mass * DODGE_SPEED);
+        velocity.z = physical.velocity().z;
+
+        physical.set_velocity_raw(velocity);
+
+        self.stamina -= DODGE_STAMINA_COST;
+
+        self.dodging = DODGE_TIME;
+        self.dodge_cooldown = DODGE_COOLDOWN;
+    }
+
+    pub fn dragging(&self) -> Option<Entity>
+    {
+        self.dragging.map(|dragging| dragging.entity)
+    }
+
+    // heavier stuff slows down more
+    fn drag_penalty(&self) -> f32
+    {
+        self.dragging.map(|dragging| DRAG_STRENGTH / (DRAG_STRENGTH + dragging.mass)).unwrap_or(1.0)
+    }
+
+    fn release_dragged(&mut self, entities: &ClientEntities)
+    {
+        if let Some(dragging) = self.dragging.take()
+        {
+            entities.lazy_setter.borrow_mut().set_joint(dragging.entity, None);
+        }
+    }
+
+    fn drag(&mut self, combined_info: CombinedInfo, target: Option<Entity>)
+    {
+        let entities = combined_info.entities;
+
+        self.release_dragged(entities);
+
+        let target = some_or_return!(target);
+
+        if !self.attackable_state()
+        {
+            return;
+        }
+
+        let entity = some_or_return!(self.info.as_ref()).this;
+
+        if !entities.within_interactable_distance(entity, target) || entities.joint_exists(target)
+        {
+            return;
+        }
+
+        let mass = some_or_return!(entities.physical(target)).inverse_mass.recip();
+
+        let length = {
+            let this = some_or_return!(entities.transform(entity)).position;
+            let other = some_or_return!(entities.transform(target)).position;
+
+            (this - other).xy().magnitude()
+        };
+
+        entities.lazy_setter.borrow_mut().set_joint(target, Some(Joint::Tether(TetherJoint{entity, length})));
+
+        self.dragging = Some(Dragging{entity: target, mass});
+    }
+
+    fn update_dragging(&mut self, combined_info: CombinedInfo)
+    {
+        let dragging = some_or_return!(self.dragging);
+        let entity = some_or_return!(self.info.as_ref()).this;
+
+        let entities = combined_info.entities;
+
+        let distance = entities.transform(dragging.entity).and_then(|other|
+        {
+            entities.transform(entity).map(|this| (this.position - other.position).xy().magnitude())
+        });
+
+        let broken = distance.map(|distance| distance > DRAG_BREAK_DISTANCE).unwrap_or(true);
+
+        if broken || !self.attackable_state()
+        {
+            self.release_dragged(entities);
+        }
+    }
+
+    fn release_grapple(&mut self, entities: &ClientEntities)
+    {
+        if let Some(grappling) = self.grappling.take()
+        {
+            entities.lazy_setter.borrow_mut().set_joint(grappling.pulled, None);
+
+            if let Some(hook) = grappling.hook
+            {
+                entities.remove_deferred(hook);
+            }
+        }
+    }
+
+    fn grapple(
+        &mut self,
+        combined_info: CombinedInfo,
+        target: Vector3<f32>,
+        range: f32,
+        speed: f32
+    ) -> bool
+    {
+        let entities = combined_info.entities;
+
+        self.release_grapple(entities);
+
+        let this = some_or_false!(self.info.as_ref()).this;
+        let this_mass = some_or_false!(entities.physical(this)).inverse_mass.recip();
+
+        let start = some_or_false!(entities.transform(this)).position;
+
+        let mut direction = target - start;
+        direction.z = 0.0;
+
+        let direction = some_or_false!(direction.try_normalize(0.0001));
+        let end = start + direction * range;
+
+        let info = RaycastInfo{
+            pierce: None,
+            layer: ColliderLayer::DAMAGE,
+            ignore_entity: Some(this),
+            ignore_end: false
+        };
+
+        let entity_hit = entities.raycast(info, &start, &end).hits.into_iter().find_map(|hit|
+        {
+            match hit.id
+            {
+                RaycastHitId::Entity(id) => Some((id, hit.result.distance.max(0.0))),
+                RaycastHitId::Tile => None
+            }
+        });
+
+        let wall_hit = combined_info.world.tile_hit(start, end).map(|hit| hit.distance);
+
+        let entity_hit = entity_hit.filter(|(_, distance)|
+        {
+            wall_hit.map(|wall| *distance < wall).unwrap_or(true)
+        });
+
+        let distance = entity_hit.map(|(_, distance)| distance).or(wall_hit);
+
+        let hit_position = start + direction * distance.unwrap_or(range);
+        combined_info.projectiles.borrow_mut().push_tracer(Tracer::new(start, hit_position));
+
+        // missed, still counts as an attack
+        let length = some_or_value!(distance, true);
+
+        let set_tether = |entity, anchor|
+        {
+            entities.lazy_setter.borrow_mut().set_joint(entity, Some(Joint::Tether(TetherJoint{entity: anchor, length})));
+        };
+
+        let grappling = if let Some((entity, _)) = entity_hit
+        {
+            let light = entities.physical(entity).map(|physical|
+            {
+                physical.inverse_mass.recip() < this_mass * GRAPPLE_PULL_RATIO
+            }).unwrap_or(false);
+
+            if light && !entities.joint_exists(entity)
+            {
+                set_tether(entity, this);
+
+                Grappling{pulled: entity, hook: None, speed}
+            } else
+            {
+                set_tether(this, entity);
+
+                Grappling{pulled: this, hook: None, speed}
+            }
+        } else
+        {
+            let hook = entities.push(true, EntityInfo{
+                transform: Some(Transform{
+                    position: hit_position,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            });
+
+            set_tether(this, hook);
+
+            Grappling{pulled: this, hook: Some(hook), speed}
+        };
+
+        self.grappling = Some(grappling);
+
+        true
+    }
+
+    fn update_grappling(&mut self, combined_info: CombinedInfo, dt: f32)
+    {
+        let grappling = some_or_return!(self.grappling);
+
+        let entities = combined_info.entities;
+
+        let done = if let Some(mut joint) = entities.joint_mut(grappling.pulled)
+        {
+            if let Joint::Tether(tether) = &mut *joint
+            {
+                tether.length = (tether.length - grappling.speed * dt).max(GRAPPLE_MIN_LENGTH);
+
+                tether.length <= GRAPPLE_MIN_LENGTH || !entities.exists(tether.entity)
+            } else
+            {
+                true
+            }
+        } else
+        {
+            // joints get set lazily so it might not be there yet
+            !entities.exists(grappling.pulled)
+        };
+
+        if done || !self.attackable_state()
+        {
+            self.release_grapple(entities);
+        }
+    }
+
+    fn block_start(&mut self, combined_info: CombinedInfo)
+    {
+        if !self.attackable_state() || self.attack_state != AttackState::None
+        {
+            return;
+        }
+
+        if self.stamina <= 0.0
+        {
+            return;
+        }
+
+        self.blocking = Some(0.0);
+
+        self.forward_point(combined_info);
+    }
+
+    fn block_end(&mut self, combined_info: CombinedInfo)
+    {
+        if self.blocking.take().is_some()
+        {
+            self.update_hands_rotation(combined_info);
+        }
+    }
+
+    // only melee hits from the front can be blocked
+    pub fn block_damage(&mut self, damage: &mut Damage) -> BlockResult
+    {
+        let held_for = some_or_value!(self.blocking, BlockResult::Hit);
+
+        let blockable = !matches!(damage.data, DamageType::Bullet(_) | DamageType::Heat(_))
+            && matches!(damage.direction.side, Side2d::Front);
+
+        if !blockable || self.stamina <= 0.0
+        {
+            return BlockResult::Hit;
+        }
+
+        if held_for <= PARRY_WINDOW
+        {
+            BlockResult::Parried
+        } else
+        {
+            self.stamina -= BLOCK_STAMINA_COST;
+
+            damage.data = damage.data * BLOCK_MITIGATION;
+
+            BlockResult::Blocked
+        }
+    }
+
+    // the server already decided this hit got blocked
+    pub fn blocked(&mut self)
+    {
+        self.stamina -= BLOCK_STAMINA_COST;
+    }
+
+    pub fn stagger(&mut self)
+    {
+        self.stagger = STAGGER_TIME;
+        self.blocking = None;
+
+        self.attack_cooldown = self.attack_cooldown.max(STAGGER_TIME);
     }
 
     pub fn can_ranged(&self) -> bool
@@ -972,6 +1510,11 @@ impl Character
 
         self.consume_attack_stamina(combined_info);
 
+        if let Some(transform) = self.info.as_ref().and_then(|info| combined_info.entities.transform(info.this))
+        {
+            Sound::new("swing", transform.position).spawn(&mut *combined_info.passer.write());
+        }
+
         self.bash_projectile(combined_info);
 
         self.update_hands_rotation(combined_info);
@@ -1169,59 +1712,52 @@ impl Character
 
         self.attack_cooldown = ranged.cooldown();
 
-        let info = some_or_false!(self.info.as_ref());
-
-        let start = &combined_info.entities.transform(info.this).unwrap().position;
-
-        let info = RaycastInfo{
-            pierce: None,
-            layer: ColliderLayer::Damage,
-            ignore_entity: Some(info.this),
-            ignore_end: true
-        };
+        if let Ranged::Grapple{range, speed, ..} = ranged
+        {
+            return self.grapple(combined_info, target, *range, *speed);
+        }
 
-        let hits = combined_info.entities.raycast(info, start, &target);
+        let projectile = some_or_false!(ranged.projectile().filter(|_| ranged.damage().is_some()));
 
-        let damage = ranged.damage();
+        let info = some_or_false!(self.info.as_ref());
 
-        let height = DamageHeight::random();
+        let this = info.this;
+        let start = combined_info.entities.transform(this).unwrap().position;
 
-        for hit in &hits.hits
+        // enemies dont carry ammo around so they never run out, the server is the one taking it
+        if let Some(ammo) = ranged.ammo().filter(|_| combined_info.is_player(this))
         {
-            #[allow(clippy::single_match)]
-            match hit.id
+            if !self.has_ammo(combined_info, ammo)
             {
-                RaycastHitId::Entity(id) =>
-                {
-                    let transform = combined_info.entities.transform(id)
-                        .unwrap();
-
-                    let hit_position = hits.hit_position(hit);
+                Sound::new("click", start).spawn(&mut *combined_info.passer.write());
 
-                    let angle = angle_between(hit_position, transform.position);
+                return true;
+            }
+        }
 
-                    let damage = DamagePartial{
-                        data: damage,
-                        height
-                    };
+        let mut passer = combined_info.passer.write();
 
-                    drop(transform);
+        combined_info.entities.make_noise(&mut *passer, Noise::new(NoiseKind::Gunshot, start, GUNSHOT_NOISE).with_source(this));
+        Sound::new("gunshot", start).spawn(&mut *passer);
 
-                    let mut passer = combined_info.passer.write();
+        Projectiles::muzzle(combined_info.entities, combined_info.particle_effects, projectile, start, target);
 
-                    damaging_system::entity_damager(
-                        combined_info.entities,
-                        &mut *passer,
-                        combined_info.common_textures.blood
-                    )(id, angle, self.faction, damage);
-                },
-                _ => ()
-            }
-        }
+        // whatever it hits and how far off it goes is up to the server
+        let held = some_or_false!(self.holding);
+        passer.send_message(Message::FireProjectile{entity: this, item: held, target});
 
         true
     }
 
+    fn has_ammo(&self, combined_info: CombinedInfo, ammo: &str) -> bool
+    {
+        let this = some_or_false!(self.info.as_ref()).this;
+
+        let ammo = some_or_false!(combined_info.items_info.get_id(ammo));
+
+        some_or_false!(combined_info.entities.inventory(this)).find(ammo).is_some()
+    }
+
     fn bash_projectile(&mut self, combined_info: CombinedInfo)
     {
         let info = some_or_return!(self.info.as_ref());
@@ -1252,7 +1788,7 @@ impl Character
                 parent: Some(Parent::new(info.this, true)),
                 collider: Some(ColliderInfo{
                     kind: ColliderType::Circle,
-                    layer: ColliderLayer::Damage,
+                    layer: ColliderLayer::DAMAGE,
                     ghost: true,
                     ..Default::default()
                 }.into()),
@@ -1315,7 +1851,7 @@ impl Character
                 parent: Some(Parent::new(info.holding, true)),
                 collider: Some(ColliderInfo{
                     kind: ColliderType::Rectangle,
-                    layer: ColliderLayer::Damage,
+                    layer: ColliderLayer::DAMAGE,
                     ghost: true,
                     ..Default::default()
                 }.into()),
@@ -1367,7 +1903,11 @@ impl Character
                 CharacterAction::Poke{state: true} => with_clear!(self.poke_attack(combined_info)),
                 CharacterAction::Ranged{state: false, ..} => self.aim_start(combined_info),
                 CharacterAction::Ranged{state: true, target} => with_clear!(self.ranged_attack(combined_info, target)),
-                CharacterAction::Bash => self.bash_attack(combined_info)
+                CharacterAction::Bash => self.bash_attack(combined_info),
+                CharacterAction::Block{state: false} => self.block_start(combined_info),
+                CharacterAction::Block{state: true} => self.block_end(combined_info),
+                CharacterAction::Dodge(direction) => self.dodge(combined_info, direction),
+                CharacterAction::Drag(target) => self.drag(combined_info, target)
             }
         });
     }
@@ -1445,6 +1985,14 @@ impl Character
     {
         Self::decrease_timer(&mut self.attack_cooldown, dt);
         Self::decrease_timer(&mut self.oversprint_cooldown, dt);
+        Self::decrease_timer(&mut self.stagger, dt);
+        Self::decrease_timer(&mut self.dodging, dt);
+        Self::decrease_timer(&mut self.dodge_cooldown, dt);
+
+        if let Some(blocking) = self.blocking.as_mut()
+        {
+            *blocking += dt;
+        }
     }
 
     pub fn scale_ratio(&self, combined_info: CombinedInfo) -> Option<f32>
@@ -1520,6 +2068,8 @@ impl Character
 
         self.update_jiggle(combined_info, dt);
         self.update_sprint(combined_info, dt);
+        self.update_dragging(combined_info);
+        self.update_grappling(combined_info, dt);
         self.update_attacks(dt);
 
         if !self.update_common(combined_info.characters_info, combined_info.entities)
@@ -1542,14 +2092,14 @@ impl Character
 
         let layer = if combined_info.is_player(entity)
         {
-            ColliderLayer::Player
+            ColliderLayer::PLAYER
         } else
         {
             match self.sprite_state.value()
             {
-                SpriteState::Normal => ColliderLayer::Normal,
-                SpriteState::Crawling
-                | SpriteState::Lying => ColliderLayer::Lying
+                SpriteState::Normal => ColliderLayer::NORMAL,
+                SpriteState::Crawling => ColliderLayer::LYING,
+                SpriteState::Lying => ColliderLayer::CORPSE
             }
         };
 
@@ -1619,6 +2169,7 @@ impl Character
             let set_visible = |entity| set_visible(entity, hair_visibility);
 
             info.hair.iter().copied().for_each(set_visible);
+            info.worn.iter().copied().for_each(set_visible);
         }
 
         self.update_held(combined_info);
@@ -1649,7 +2200,7 @@ impl Character
 
     fn is_sprinting(&self) -> bool
     {
-        if self.oversprint_cooldown <= 0.0
+        if self.oversprint_cooldown <= 0.0 && self.dragging.is_none() && !self.is_crouching()
         {
             self.sprinting
         } else
@@ -1658,14 +2209,45 @@ impl Character
         }
     }
 
+    pub fn is_crouching(&self) -> bool
+    {
+        self.crouching && *self.sprite_state.value() == SpriteState::Normal
+    }
+
+    // how much of the light actually makes this stand out
+    pub fn visibility_scale(&self) -> f32
+    {
+        if *self.sprite_state.value() != SpriteState::Normal
+        {
+            CRAWL_VISIBILITY
+        } else if self.is_crouching()
+        {
+            CROUCH_VISIBILITY
+        } else
+        {
+            1.0
+        }
+    }
+
     fn update_jiggle(&mut self, combined_info: CombinedInfo, dt: f32)
     {
         let info = some_or_return!(self.info.as_ref());
-        let physical = some_or_return!(combined_info.entities.physical(info.this));
-        let speed = physical.velocity().xy().magnitude() * 50.0;
+        let speed = {
+            let physical = some_or_return!(combined_info.entities.physical(info.this));
 
+            physical.velocity().xy().magnitude() * 50.0
+        };
+
+        let previous = self.jiggle;
         self.jiggle = (self.jiggle + dt * speed) % (2.0 * f32::consts::PI);
 
+        // every half of the jiggle is a step
+        let half_turn = |x: f32| (x / f32::consts::PI).floor();
+        if half_turn(previous) != half_turn(self.jiggle) && *self.sprite_state.value() != SpriteState::Lying
+        {
+            self.footstep(combined_info, info.this);
+        }
+
         let mut target = some_or_return!(combined_info.entities.target(info.this));
 
         target.rotation = if *self.sprite_state.value() == SpriteState::Crawling
@@ -1675,6 +2257,54 @@ impl Character
         {
             self.rotation
         };
+
+        if self.is_staggered()
+        {
+            // wobble around while recovering
+            target.rotation += (self.stagger * 30.0).sin() * 0.3 * (self.stagger / STAGGER_TIME);
+        }
+    }
+
+    fn footstep(&self, combined_info: CombinedInfo, entity: Entity)
+    {
+        let position = some_or_return!(combined_info.entities.transform(entity)).position;
+
+        let material = combined_info.world.material_at(position);
+
+        let loudness = if *self.sprite_state.value() == SpriteState::Crawling
+        {
+            CRAWL_STEP_LOUDNESS
+        } else if self.is_crouching()
+        {
+            CROUCH_STEP_LOUDNESS
+        } else if self.is_sprinting()
+        {
+            SPRINT_STEP_LOUDNESS
+        } else
+        {
+            1.0
+        } * material.loudness();
+
+        combined_info.entities.push(true, EntityInfo{
+            transform: Some(Transform{
+                position,
+                ..Default::default()
+            }),
+            sound: Some(SoundEmitter{volume: loudness, ..SoundEmitter::new(material.footstep())}),
+            watchers: Some(Watchers::simple_disappearing(0.5)),
+            ..Default::default()
+        });
+
+        let noise = Noise::new(NoiseKind::Footstep, position, FOOTSTEP_NOISE * loudness).with_source(entity);
+
+        // enemies dont notice each others steps
+        if combined_info.is_player(entity)
+        {
+            combined_info.entities.make_noise(&mut *combined_info.passer.write(), noise);
+        } else
+        {
+            combined_info.entities.push_noise(noise);
+        }
     }
 
     fn update_sprint(&mut self, combined_info: CombinedInfo, dt: f32)
@@ -1709,11 +2339,20 @@ impl Character
         dt: f32
     )
     {
-        let speed = some_or_return!(anatomy.speed());
+        // dont cancel out the dodge momentum
+        if self.is_dodging()
+        {
+            return;
+        }
+
+        let speed = some_or_return!(anatomy.speed()) * self.drag_penalty();
 
         let speed = if self.is_sprinting()
         {
             speed * 1.8
+        } else if self.is_crouching()
+        {
+            speed * CROUCH_SPEED
         } else
         {
             speed
@@ -1739,9 +2378,9 @@ impl Character
         physical.add_force(change_velocity);
     }
 
-    pub fn aggressive(&self, other: &Self) -> bool
+    pub fn aggressive(&self, factions_info: &FactionsInfo, other: &Self) -> bool
     {
-        self.faction.aggressive(&other.faction)
+        factions_info.aggressive(self.faction, other.faction)
     }
 
     fn set_sprite(&mut self, state: SpriteState)
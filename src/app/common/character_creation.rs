@@ -0,0 +1,50 @@
+use serde::{Serialize, Deserialize};
+
+use crate::common::{
+    ItemsInfo,
+    PerksInfo,
+    render_info::MixColor
+};
+
+
+// starting perks are free so dont let people pick all of them
+pub const MAX_TRAITS: usize = 1;
+
+pub const MAX_KIT_ITEMS: usize = 3;
+
+const TINT_AMOUNT: f32 = 0.4;
+
+// picked before joining, only used the first time a player joins a world
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CharacterCreation
+{
+    pub tint: Option<[f32; 3]>,
+    // names of perks the player starts with
+    pub traits: Vec<String>,
+    // names of items the player starts with
+    pub kit: Vec<String>
+}
+
+impl CharacterCreation
+{
+    // the client can send anything so throw out whatever doesnt exist or is over the limits
+    pub fn validated(mut self, perks_info: &PerksInfo, items_info: &ItemsInfo) -> Self
+    {
+        self.tint = self.tint.map(|color| color.map(|x| x.clamp(0.0, 1.0)));
+
+        self.traits.retain(|name| perks_info.get_id(name).is_some());
+        self.traits.dedup();
+        self.traits.truncate(MAX_TRAITS);
+
+        self.kit.retain(|name| items_info.get_id(name).is_some());
+        self.kit.truncate(MAX_KIT_ITEMS);
+
+        self
+    }
+
+    pub fn mix(&self) -> Option<MixColor>
+    {
+        self.tint.map(|color| MixColor{color, amount: TINT_AMOUNT, keep_transparency: true})
+    }
+}
@@ -2,10 +2,9 @@ use std::{
     thread,
     fmt::Debug,
     cmp::Ordering,
-    io,
     time::{Instant, Duration},
     path::{Path, PathBuf},
-    fs::{self, File},
+    fs,
     collections::{HashMap, BinaryHeap},
     sync::{
         Arc,
@@ -23,6 +22,7 @@ use crate::{
     server::world::world_generator::{CHUNK_RATIO, WorldChunk},
     common::{
         FullEntityInfo,
+        save_file::{self, SaveBytes, SaveFileError},
         world::{
             Chunk,
             GlobalPos,
@@ -53,6 +53,9 @@ pub trait SaveLoad<T>
 {
     fn save(&mut self, pos: GlobalPos, chunk: T);
     fn load(&mut self, pos: GlobalPos) -> Option<T>;
+
+    // blocks until everything saved so far is on disk
+    fn flush(&mut self) {}
 }
 
 // this shouldnt be public but sure, rust
@@ -325,26 +328,25 @@ impl<SaveT: Saveable> FileSaver<SaveT>
 
     fn load_with<F>(&mut self, pos: GlobalPos, load_fn: F) -> Option<SaveT>
     where
-        F: FnOnce(PathBuf, File) -> SaveT
+        F: FnOnce(SaveBytes) -> Result<SaveT, SaveFileError>
     {
         if self.is_unsaved(pos)
         {
             self.block_until(pos);
         }
 
-        let parent_path = self.parent_path(pos);
+        let path = Self::chunk_path(self.parent_path(pos), pos);
 
-        match File::open(Self::chunk_path(parent_path.clone(), pos))
+        match save_file::read(&path).and_then(|bytes| bytes.map(load_fn).transpose())
         {
-            Ok(file) =>
-            {
-                Some(load_fn(parent_path, file))
-            },
-            Err(ref err) if err.kind() == io::ErrorKind::NotFound =>
+            Ok(x) => x,
+            Err(err) =>
             {
+                // both the save and its backup are broken, better to lose the chunk than the world
+                eprintln!("error loading chunk from {}: {err}", path.display());
+
                 None
-            },
-            Err(err) => panic!("error loading chunk from file: {err}")
+            }
         }
     }
 
@@ -383,17 +385,23 @@ where
         Self::new_with_saver(parent_path, |path, pair|
         {
             let chunk_path = Self::chunk_path(path, pair.key);
-            let temp_path = chunk_path.with_extension("tmp");
 
-            let file = File::create(&temp_path).unwrap();
+            let result = (|| -> Result<(), SaveFileError>
+            {
+                let mut lzma_writer = LzmaWriter::new_compressor(Vec::new(), LZMA_PRESET)
+                    .map_err(|err| SaveFileError::Encode(err.to_string()))?;
 
-            let mut lzma_writer = LzmaWriter::new_compressor(file, LZMA_PRESET).unwrap();
+                save_file::serialize_into(&mut lzma_writer, &pair.value)?;
 
-            bincode::serialize_into(&mut lzma_writer, &pair.value).unwrap();
+                let bytes = lzma_writer.finish().map_err(|err| SaveFileError::Encode(err.to_string()))?;
 
-            lzma_writer.finish().unwrap();
+                save_file::write(&chunk_path, &bytes).map_err(SaveFileError::from)
+            })();
 
-            fs::rename(temp_path, chunk_path).unwrap();
+            if let Err(err) = result
+            {
+                eprintln!("error saving chunk to {}: {err}", chunk_path.display());
+            }
         })
     }
 
@@ -404,11 +412,14 @@ where
 
     fn load(&mut self, pos: GlobalPos) -> Option<Self::SaveItem>
     {
-        self.load_with(pos, |_parent_path, file|
+        self.load_with(pos, |bytes|
         {
-            let lzma_reader = LzmaReader::new_decompressor(file).unwrap();
+            let lzma_reader = LzmaReader::new_decompressor(&bytes.data[..]).map_err(|err|
+            {
+                SaveFileError::Decode{version: bytes.version, err: err.to_string()}
+            })?;
 
-            bincode::deserialize_from(lzma_reader).unwrap()
+            save_file::deserialize_from(bytes.version, lzma_reader)
         })
     }
 
@@ -517,6 +528,11 @@ where
 
         self.inner_save(pair);
     }
+
+    fn flush(&mut self)
+    {
+        self.file_saver.lock().flush();
+    }
 }
 
 #[cfg(test)]
@@ -12,10 +12,10 @@ use yanyaengine::Transform;
 
 use crate::common::{
     some_or_value,
-    define_layers,
     rectangle_points,
     Entity,
     Physical,
+    CollisionLayers,
     raycast::raycast_this,
     world::{
         TILE_SIZE,
@@ -131,60 +131,47 @@ impl ColliderType
     }
 }
 
+// the names and what collides with what r in collision/layers.json, the data can add its own layers too
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
-pub enum ColliderLayer
+pub struct ColliderLayer(u32);
+
+impl Default for ColliderLayer
 {
-    Normal,
-    Damage,
-    World,
-    Door,
-    Mouse,
-    Player,
-    Lying
+    fn default() -> Self
+    {
+        Self::NORMAL
+    }
 }
 
 impl ColliderLayer
 {
-    pub fn collides(&self, other: &Self) -> bool
+    // the ones the code itself puts stuff on, same order as they always were so old saves still load
+    pub const NORMAL: Self = Self(0);
+    pub const DAMAGE: Self = Self(1);
+    pub const WORLD: Self = Self(2);
+    pub const DOOR: Self = Self(3);
+    pub const MOUSE: Self = Self(4);
+    pub const PLAYER: Self = Self(5);
+    pub const LYING: Self = Self(6);
+    // dead bodies, only really get in the way of projectiles and the world
+    pub const CORPSE: Self = Self(7);
+
+    pub const BUILTIN: [(&'static str, Self); 8] = [
+        ("Normal", Self::NORMAL),
+        ("Damage", Self::DAMAGE),
+        ("World", Self::WORLD),
+        ("Door", Self::DOOR),
+        ("Mouse", Self::MOUSE),
+        ("Player", Self::PLAYER),
+        ("Lying", Self::LYING),
+        ("Corpse", Self::CORPSE)
+    ];
+
+    // the data ones get their id from the name so adding or removing a layer doesnt move any of the others
+    pub fn from_name(name: &str) -> Self
     {
-        define_layers!{
-            self, other,
-
-            (Normal, Normal, true),
-
-            (Damage, Damage, false),
-            (Damage, Normal, true),
-
-            (World, World, false),
-            (World, Normal, true),
-            (World, Damage, true),
-
-            (Mouse, Mouse, false),
-            (Mouse, Normal, true),
-            (Mouse, Damage, false),
-            (Mouse, World, false),
-
-            (Door, Door, false),
-            (Door, Normal, true),
-            (Door, Damage, true),
-            (Door, World, false),
-            (Door, Mouse, false),
-
-            (Player, Player, false),
-            (Player, Normal, true),
-            (Player, Damage, true),
-            (Player, World, true),
-            (Player, Mouse, true),
-            (Player, Door, true),
-
-            (Lying, Lying, true),
-            (Lying, Player, false),
-            (Lying, Normal, false),
-            (Lying, Damage, true),
-            (Lying, World, true),
-            (Lying, Mouse, true),
-            (Lying, Door, true)
-        }
+        // high bit keeps them away from the builtin ones
+        Self(crc32fast::hash(name.as_bytes()) | (1 << 31))
     }
 }
 
@@ -203,7 +190,7 @@ impl Default for ColliderInfo
     {
         Self{
             kind: ColliderType::Circle,
-            layer: ColliderLayer::Normal,
+            layer: ColliderLayer::NORMAL,
             ghost: false,
             scale: None
         }
@@ -266,6 +253,11 @@ impl Collider
 
     pub fn push_collided(&mut self, entity: Entity)
     {
+        if self.collided.contains(&entity)
+        {
+            return;
+        }
+
         self.collided.push(entity);
     }
 
@@ -1003,11 +995,6 @@ impl<'a> CollidingInfo<'a>
         mut add_contact: impl FnMut(Contact)
     ) -> bool
     {
-        if !self.collider.layer.collides(&other.collider.layer)
-        {
-            return false;
-        }
-
         let ignore_contacts = self.collider.ghost || other.collider.ghost;
 
         let add_contact = |contact: Contact|
@@ -1091,9 +1078,15 @@ impl<'a> CollidingInfo<'a>
     pub fn collide(
         &mut self,
         other: CollidingInfo,
+        layers: &CollisionLayers,
         add_contact: impl FnMut(Contact)
     ) -> bool
     {
+        if !layers.collides(self.collider.layer, other.collider.layer)
+        {
+            return false;
+        }
+
         let collided = self.collide_immutable(&other, add_contact);
 
         if collided
@@ -1115,10 +1108,11 @@ impl<'a> CollidingInfo<'a>
     pub fn collide_with_world(
         &mut self,
         world: &World,
+        layers: &CollisionLayers,
         contacts: &mut Vec<Contact>
     ) -> bool
     {
-        if !self.collider.layer.collides(&ColliderLayer::World)
+        if !layers.collides(self.collider.layer, ColliderLayer::WORLD)
         {
             return false;
         }
@@ -0,0 +1,165 @@
+use std::{
+    fs::File,
+    path::Path,
+    collections::{HashMap, BTreeMap}
+};
+
+use crate::common::{
+    collider::ColliderLayer,
+    overrides::overridden
+};
+
+
+// which layers collide with which, a pair collides if either of them lists the other
+#[derive(Debug, Clone)]
+pub struct CollisionLayers
+{
+    names: HashMap<String, ColliderLayer>,
+    // where in the matrix every layer is, only for this run so its never saved
+    indices: HashMap<ColliderLayer, usize>,
+    amount: usize,
+    // a row for every layer
+    matrix: Vec<bool>
+}
+
+impl CollisionLayers
+{
+    pub fn parse(info: impl AsRef<Path>) -> Self
+    {
+        let info = File::open(overridden(info)).unwrap();
+
+        let masks: BTreeMap<String, Vec<String>> = serde_json::from_reader(info).unwrap();
+
+        Self::from_masks(masks)
+    }
+
+    pub fn from_masks(masks: BTreeMap<String, Vec<String>>) -> Self
+    {
+        if let Some((name, _)) = ColliderLayer::BUILTIN.iter().find(|(name, _)| !masks.contains_key(*name))
+        {
+            panic!("collision layer {name} is missing from the matrix");
+        }
+
+        let mut names: HashMap<String, ColliderLayer> = ColliderLayer::BUILTIN.iter()
+            .map(|(name, layer)| (name.to_string(), *layer))
+            .collect();
+
+        masks.keys().for_each(|name|
+        {
+            if names.contains_key(name)
+            {
+                return;
+            }
+
+            let layer = ColliderLayer::from_name(name);
+
+            if let Some((other, _)) = names.iter().find(|(_, other)| **other == layer)
+            {
+                panic!("collision layers {name} and {other} got the same id, rename one of them");
+            }
+
+            names.insert(name.clone(), layer);
+        });
+
+        let indices: HashMap<ColliderLayer, usize> = names.values().enumerate()
+            .map(|(index, layer)| (*layer, index))
+            .collect();
+
+        let amount = names.len();
+        let mut matrix = vec![false; amount * amount];
+
+        masks.iter().for_each(|(name, mask)|
+        {
+            let layer = indices[&names[name]];
+
+            mask.iter().for_each(|other|
+            {
+                let other = names.get(other).map(|other| indices[other]).unwrap_or_else(||
+                {
+                    panic!("collision layer {name} collides with {other} which isnt in the matrix")
+                });
+
+                matrix[layer * amount + other] = true;
+                matrix[other * amount + layer] = true;
+            });
+        });
+
+        Self{names, indices, amount, matrix}
+    }
+
+    pub fn get(&self, name: &str) -> ColliderLayer
+    {
+        self.names.get(name).copied().unwrap_or_else(|| panic!("collision layer {name} isnt in the matrix"))
+    }
+
+    fn index(&self, layer: ColliderLayer) -> usize
+    {
+        self.indices.get(&layer).copied().unwrap_or_else(|| panic!("collision layer {layer:?} isnt in the matrix"))
+    }
+
+    pub fn collides(&self, a: ColliderLayer, b: ColliderLayer) -> bool
+    {
+        self.matrix[self.index(a) * self.amount + self.index(b)]
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+
+    fn masks(extra: &[(&str, &[&str])]) -> BTreeMap<String, Vec<String>>
+    {
+        ColliderLayer::BUILTIN.iter().map(|(name, _)| (*name, &[][..]))
+            .chain(extra.iter().copied())
+            .map(|(name, mask)| (name.to_owned(), mask.iter().map(|x| x.to_string()).collect()))
+            .collect()
+    }
+
+    #[test]
+    fn data_layers()
+    {
+        let layers = CollisionLayers::from_masks(masks(&[
+            ("Normal", &["Normal"]),
+            ("Vehicle", &["World", "Vehicle"])
+        ]));
+
+        let vehicle = layers.get("Vehicle");
+
+        assert!(layers.collides(vehicle, ColliderLayer::WORLD));
+        assert!(layers.collides(ColliderLayer::WORLD, vehicle));
+        assert!(layers.collides(vehicle, vehicle));
+        assert!(!layers.collides(vehicle, ColliderLayer::NORMAL));
+        assert!(layers.collides(ColliderLayer::NORMAL, ColliderLayer::NORMAL));
+    }
+
+    #[test]
+    fn stable_ids()
+    {
+        let vehicle = CollisionLayers::from_masks(masks(&[("Vehicle", &[])])).get("Vehicle");
+
+        let layers = CollisionLayers::from_masks(masks(&[("Boat", &[]), ("Vehicle", &["World"])]));
+
+        assert_eq!(layers.get("Vehicle"), vehicle);
+        assert!(layers.collides(vehicle, ColliderLayer::WORLD));
+        assert!(!layers.collides(layers.get("Boat"), ColliderLayer::WORLD));
+    }
+
+    #[test]
+    #[should_panic]
+    fn missing_builtin()
+    {
+        let mut masks = masks(&[]);
+        masks.remove("Corpse");
+
+        CollisionLayers::from_masks(masks);
+    }
+
+    #[test]
+    #[should_panic]
+    fn missing_other()
+    {
+        CollisionLayers::from_masks(masks(&[("Normal", &["Ghost"])]));
+    }
+}
@@ -0,0 +1,166 @@
+use std::{
+    f32,
+    fs::File,
+    path::Path,
+    collections::HashMap
+};
+
+use serde::{Serialize, Deserialize};
+
+use nalgebra::Vector2;
+
+use crate::common::{
+    pick_by_commonness,
+    rng::RngStream,
+    render_info::MixColor,
+    collider::ColliderLayer,
+    CollisionLayers
+};
+
+
+// how fast the lid wobble plays out
+const BOUNCE_SPEED: f32 = 5.0;
+const BOUNCE_SQUASH: f32 = 0.12;
+
+// the perk unlock that lets players pick locks without a tool
+pub const LOCKPICK_UNLOCK: &str = "lockpicking";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unlock
+{
+    Pry,
+    Pick
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ContainerInfo
+{
+    pub texture: String,
+    pub scale: f32,
+    #[serde(default)]
+    pub mix: Option<MixColor>,
+    pub loot: Vec<String>,
+    // inclusive on both ends
+    pub amount: (usize, usize),
+    #[serde(default)]
+    pub lock_chance: f32,
+    // holding any of these lets u pry it open when its locked
+    #[serde(default)]
+    pub pried_with: Vec<String>,
+    // seconds it burns for before its gone, doesnt catch fire if its missing
+    #[serde(default)]
+    pub flammable: Option<f32>,
+    // sleeping in it makes it where u come back after dying
+    #[serde(default)]
+    pub bed: bool,
+    // name from collision/layers.json, normal if missing
+    #[serde(default)]
+    layer: Option<String>,
+    #[serde(skip)]
+    pub collider_layer: ColliderLayer,
+    pub commonness: f32
+}
+
+pub struct ContainersInfo
+{
+    infos: HashMap<String, ContainerInfo>
+}
+
+impl ContainersInfo
+{
+    pub fn empty() -> Self
+    {
+        Self{infos: HashMap::new()}
+    }
+
+    // later files replace containers with the same name
+    pub fn parse(infos: impl IntoIterator<Item=impl AsRef<Path>>, layers: &CollisionLayers) -> Self
+    {
+        let mut infos: HashMap<String, ContainerInfo> = infos.into_iter().flat_map(|info|
+        {
+            let info = File::open(info.as_ref()).unwrap();
+
+            serde_json::from_reader::<_, HashMap<String, ContainerInfo>>(info).unwrap()
+        }).collect();
+
+        infos.values_mut().for_each(|info|
+        {
+            if let Some(layer) = info.layer.as_ref()
+            {
+                info.collider_layer = layers.get(layer);
+            }
+        });
+
+        Self{infos}
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ContainerInfo>
+    {
+        self.infos.get(name)
+    }
+
+    pub fn random(&self) -> Option<&str>
+    {
+        pick_by_commonness(RngStream::WorldGen, 1.0, self.infos.iter(), |(_, info)|
+        {
+            info.commonness as f64
+        }).map(|(name, _)| name.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Container
+{
+    // which entry in containers.json
+    pub name: String,
+    pub locked: bool,
+    open: bool,
+    #[serde(skip)]
+    bounce: f32
+}
+
+impl Container
+{
+    pub fn new(name: String, locked: bool) -> Self
+    {
+        Self{name, locked, open: false, bounce: 0.0}
+    }
+
+    pub fn is_open(&self) -> bool
+    {
+        self.open
+    }
+
+    pub fn set_open(&mut self, open: bool)
+    {
+        if self.open != open
+        {
+            self.open = open;
+            self.bounce = 1.0;
+        }
+    }
+
+    // whoever else opened it should see it wobble too
+    pub fn with_previous(&mut self, previous: Self)
+    {
+        self.bounce = if previous.open != self.open { 1.0 } else { previous.bounce };
+    }
+
+    pub fn update(&mut self, dt: f32)
+    {
+        self.bounce = (self.bounce - BOUNCE_SPEED * dt).max(0.0);
+    }
+
+    pub fn is_bouncing(&self) -> bool
+    {
+        self.bounce > 0.0
+    }
+
+    pub fn stretch(&self) -> (f32, Vector2<f32>)
+    {
+        let squash = 1.0 + (self.bounce * f32::consts::PI).sin() * BOUNCE_SQUASH;
+
+        (0.0, Vector2::new(squash, squash.recip()))
+    }
+}
@@ -0,0 +1,137 @@
+use std::mem;
+
+use serde::{Serialize, Deserialize};
+
+use crate::common::render_info::*;
+
+
+// seconds until a corpse gets cleaned up if the server doesnt say otherwise
+pub const DEFAULT_CORPSE_DURATION: f32 = 300.0;
+
+// fraction of the lifetime where each stage starts
+const ROTTING_START: f32 = 0.3;
+const SKELETAL_START: f32 = 0.75;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CorpseStage
+{
+    Fresh,
+    Rotting,
+    Skeletal
+}
+
+impl CorpseStage
+{
+    fn mix(&self) -> Option<MixColor>
+    {
+        let (color, amount) = match self
+        {
+            Self::Fresh => return None,
+            Self::Rotting => ([0.35, 0.45, 0.2], 0.35),
+            Self::Skeletal => ([0.85, 0.82, 0.75], 0.6)
+        };
+
+        Some(MixColor{color, amount, keep_transparency: true})
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DeathEffect
+{
+    // seconds at the end of the corpse lifetime spent dissolving, 0 just pops out
+    pub dissolve: f32,
+    // particle effect for when the killing blow is big enough
+    pub gibs: Option<String>,
+    pub gib_damage: f32
+}
+
+impl Default for DeathEffect
+{
+    fn default() -> Self
+    {
+        Self{dissolve: 3.0, gibs: None, gib_damage: 300.0}
+    }
+}
+
+impl DeathEffect
+{
+    pub fn gibs_for(&self, damage: f32) -> Option<&str>
+    {
+        self.gibs.as_deref().filter(|_| damage >= self.gib_damage)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Corpse
+{
+    age: f32,
+    duration: f32,
+    dissolve: f32,
+    #[serde(default)]
+    butchered: bool
+}
+
+impl Corpse
+{
+    pub fn new(duration: f32, dissolve: f32) -> Self
+    {
+        Self{age: 0.0, duration, dissolve: dissolve.min(duration), butchered: false}
+    }
+
+    pub fn is_butchered(&self) -> bool
+    {
+        self.butchered
+    }
+
+    // false if theres nothing left to cut up
+    pub fn butcher(&mut self) -> bool
+    {
+        !mem::replace(&mut self.butchered, true)
+    }
+
+    pub fn update(&mut self, dt: f32)
+    {
+        self.age += dt;
+    }
+
+    pub fn expired(&self) -> bool
+    {
+        self.age >= self.duration
+    }
+
+    pub fn stage(&self) -> CorpseStage
+    {
+        let fraction = self.age / self.duration;
+
+        if fraction < ROTTING_START
+        {
+            CorpseStage::Fresh
+        } else if fraction < SKELETAL_START
+        {
+            CorpseStage::Rotting
+        } else
+        {
+            CorpseStage::Skeletal
+        }
+    }
+
+    pub fn mix(&self) -> Option<MixColor>
+    {
+        self.stage().mix()
+    }
+
+    // 0 is fully there, 1 is fully gone
+    pub fn dissolved(&self) -> f32
+    {
+        if self.dissolve <= 0.0
+        {
+            return 0.0;
+        }
+
+        let left = self.duration - self.age;
+
+        (1.0 - left / self.dissolve).clamp(0.0, 1.0)
+    }
+}
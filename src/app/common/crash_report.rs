@@ -0,0 +1,153 @@
+use std::{
+    fs,
+    thread,
+    panic::{self, PanicHookInfo},
+    backtrace::Backtrace,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+    collections::VecDeque,
+    sync::{
+        LazyLock,
+        atomic::{AtomicBool, Ordering}
+    }
+};
+
+use parking_lot::Mutex;
+
+use crate::{
+    LOG_PATH,
+    common::copy_dir
+};
+
+
+const CRASHES_PATH: &str = "crashes";
+
+// points at the newest crash that nobody was told about yet
+const UNSEEN_PATH: &str = "crashes/unseen";
+
+const MAX_CONSOLE_LINES: usize = 50;
+
+static CONSOLE_LINES: LazyLock<Mutex<VecDeque<String>>> = LazyLock::new(|| Mutex::new(VecDeque::new()));
+
+static INCLUDE_AUTOSAVE: AtomicBool = AtomicBool::new(false);
+
+// only the first panic gets a report, the rest are usually caused by it
+static CRASHED: AtomicBool = AtomicBool::new(false);
+
+pub fn install()
+{
+    let default_hook = panic::take_hook();
+
+    panic::set_hook(Box::new(move |info|
+    {
+        default_hook(info);
+
+        if !CRASHED.swap(true, Ordering::Relaxed)
+        {
+            write_report(info);
+        }
+    }));
+}
+
+pub fn set_include_autosave(state: bool)
+{
+    INCLUDE_AUTOSAVE.store(state, Ordering::Relaxed);
+}
+
+pub fn push_console_line(line: impl Into<String>)
+{
+    let mut lines = CONSOLE_LINES.lock();
+
+    if lines.len() >= MAX_CONSOLE_LINES
+    {
+        lines.pop_front();
+    }
+
+    lines.push_back(line.into());
+}
+
+// the folder of a crash from a previous run, only returns it once
+pub fn take_unseen() -> Option<PathBuf>
+{
+    let path = fs::read_to_string(UNSEEN_PATH).ok()?;
+
+    if let Err(err) = fs::remove_file(UNSEEN_PATH)
+    {
+        eprintln!("error removing {UNSEEN_PATH}: {err}");
+    }
+
+    Some(PathBuf::from(path))
+}
+
+fn newest_autosave() -> Option<PathBuf>
+{
+    let modified = |path: &Path| fs::metadata(path).and_then(|x| x.modified()).ok();
+
+    fs::read_dir("worlds").ok()?.flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().ends_with("_autosaves"))
+        .flat_map(|entry| fs::read_dir(entry.path()).into_iter().flatten().flatten())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir() && path.extension().is_none())
+        .max_by_key(|path| modified(path))
+}
+
+fn write_report(info: &PanicHookInfo)
+{
+    let time = SystemTime::now().duration_since(UNIX_EPOCH).map(|x| x.as_secs()).unwrap_or(0);
+    let path = Path::new(CRASHES_PATH).join(time.to_string());
+
+    if let Err(err) = fs::create_dir_all(&path)
+    {
+        eprintln!("error creating crash report folder: {err}");
+        return;
+    }
+
+    let report = format!(
+        "version: {}\nthread: {}\n{info}\n\nbacktrace:\n{}",
+        env!("CARGO_PKG_VERSION"),
+        thread::current().name().unwrap_or("unnamed"),
+        Backtrace::force_capture()
+    );
+
+    let write = |name: &str, contents: &str|
+    {
+        if let Err(err) = fs::write(path.join(name), contents)
+        {
+            eprintln!("error writing {name} to the crash report: {err}");
+        }
+    };
+
+    write("crash.txt", &report);
+
+    // if the lock is held by whoever panicked theres just no console lines
+    if let Some(lines) = CONSOLE_LINES.try_lock()
+    {
+        write("console.txt", &lines.iter().fold(String::new(), |acc, x| acc + x + "\n"));
+    }
+
+    if Path::new(LOG_PATH).exists()
+    {
+        if let Err(err) = fs::copy(LOG_PATH, path.join(LOG_PATH))
+        {
+            eprintln!("error copying the log to the crash report: {err}");
+        }
+    }
+
+    if INCLUDE_AUTOSAVE.load(Ordering::Relaxed)
+    {
+        if let Some(autosave) = newest_autosave()
+        {
+            if let Err(err) = copy_dir(&autosave, &path.join("autosave"))
+            {
+                eprintln!("error copying the autosave to the crash report: {err}");
+            }
+        }
+    }
+
+    if let Err(err) = fs::write(UNSEEN_PATH, path.to_string_lossy().as_bytes())
+    {
+        eprintln!("error writing {UNSEEN_PATH}: {err}");
+    }
+
+    eprintln!("crash report written to {}", path.display());
+}
@@ -0,0 +1,46 @@
+use serde::{Serialize, Deserialize};
+
+use crate::common::Lerp;
+
+
+// keys r (time, value) pairs sorted by time, values get linearly interpolated between them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Curve<T>(Vec<(f32, T)>);
+
+impl<T: Lerp + Copy> Curve<T>
+{
+    pub fn new(mut keys: Vec<(f32, T)>) -> Self
+    {
+        assert!(!keys.is_empty(), "curve must have at least one key");
+
+        keys.sort_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap());
+
+        Self(keys)
+    }
+
+    pub fn sample(&self, time: f32) -> T
+    {
+        let after = self.0.iter().position(|(key_time, _)| *key_time > time);
+
+        match after
+        {
+            None => self.0.last().unwrap().1,
+            Some(0) => self.0[0].1,
+            Some(index) =>
+            {
+                let (start_time, start) = self.0[index - 1];
+                let (end_time, end) = self.0[index];
+
+                let amount = (time - start_time) / (end_time - start_time);
+
+                start.lerp(end, amount)
+            }
+        }
+    }
+
+    pub fn sorted(self) -> Self
+    {
+        Self::new(self.0)
+    }
+}
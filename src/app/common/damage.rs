@@ -6,7 +6,11 @@ use std::{
 
 use serde::{Serialize, Deserialize};
 
-use crate::common::{Side2d, SeededRandom};
+use crate::common::{
+    rng::{self, RngStream},
+    Side2d,
+    SeededRandom
+};
 
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -74,7 +78,9 @@ pub enum DamageType
 {
     Blunt(f32),
     Sharp{sharpness: f32, damage: f32},
-    Bullet(f32)
+    Bullet(f32),
+    // burns, mostly stays in the skin
+    Heat(f32)
 }
 
 impl Mul<f32> for DamageType
@@ -87,7 +93,8 @@ impl Mul<f32> for DamageType
         {
             Self::Blunt(x) => *x *= scale,
             Self::Sharp{damage, ..} => *damage *= scale,
-            Self::Bullet(x) => *x *= scale
+            Self::Bullet(x) => *x *= scale,
+            Self::Heat(x) => *x *= scale
         }
 
         self
@@ -102,7 +109,8 @@ impl DamageType
         {
             Self::Blunt(x) => x,
             Self::Sharp{damage, ..} => damage,
-            Self::Bullet(x) => x
+            Self::Bullet(x) => x,
+            Self::Heat(x) => x
         }
     }
 }
@@ -119,7 +127,7 @@ impl DamageHeight
 {
     pub fn random() -> Self
     {
-        match fastrand::u32(0..3)
+        match rng::u32(RngStream::Combat, 0..3)
         {
             0 => Self::Top,
             1 => Self::Middle,
@@ -0,0 +1,86 @@
+use std::f32;
+
+use serde::{Serialize, Deserialize};
+
+use nalgebra::Vector3;
+
+use crate::common::{
+    rng::{self, RngStream},
+    ENTITY_SCALE,
+    Message,
+    EntityPasser,
+    world::TILE_SIZE
+};
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DecalKind
+{
+    Blood,
+    Scorch,
+    BulletHole
+}
+
+impl DecalKind
+{
+    pub fn texture(&self) -> &'static str
+    {
+        match self
+        {
+            Self::Blood => "decals/blood.png",
+            Self::Scorch => "decals/scorch.png",
+            Self::BulletHole => "decals/bullet_hole.png"
+        }
+    }
+
+    // seconds before it starts fading
+    pub fn lifetime(&self) -> f32
+    {
+        match self
+        {
+            Self::Blood => 120.0,
+            Self::Scorch => 180.0,
+            Self::BulletHole => 240.0
+        }
+    }
+
+    fn scale(&self) -> f32
+    {
+        match self
+        {
+            Self::Blood => ENTITY_SCALE * 0.6,
+            Self::Scorch => TILE_SIZE * 0.4,
+            Self::BulletHole => TILE_SIZE * 0.1
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Decal
+{
+    pub kind: DecalKind,
+    pub position: Vector3<f32>,
+    pub rotation: f32,
+    pub scale: f32
+}
+
+impl Decal
+{
+    pub fn new(kind: DecalKind, position: Vector3<f32>) -> Self
+    {
+        Self{
+            kind,
+            position,
+            rotation: rng::f32(RngStream::Cosmetic) * (f32::consts::PI * 2.0),
+            scale: kind.scale() * (0.7 + rng::f32(RngStream::Cosmetic) * 0.6)
+        }
+    }
+
+    // goes through the server so every client (including this one) gets it
+    pub fn spawn(self, passer: &mut impl EntityPasser)
+    {
+        let message = Message::SpawnDecal{decal: self};
+
+        passer.send_message(Message::RepeatMessage{message: Box::new(message)});
+    }
+}
@@ -0,0 +1,203 @@
+use std::{
+    fs::File,
+    path::Path,
+    collections::HashMap
+};
+
+use serde::{Serialize, Deserialize};
+
+use crate::common::{
+    some_or_false,
+    some_or_value,
+    overrides::overridden,
+    AnyEntities,
+    Entity
+};
+
+
+// walking further than this from whoever ur talking to ends the conversation
+pub const DIALOGUE_DISTANCE: f32 = 0.5;
+
+#[derive(Debug, Clone, Deserialize)]
+pub enum DialogueCondition
+{
+    HasItem(String),
+    // standing with the speakers faction
+    MinReputation(f32),
+    MaxReputation(f32)
+}
+
+impl DialogueCondition
+{
+    pub fn holds(&self, entities: &impl AnyEntities, player: Entity, speaker: Entity) -> bool
+    {
+        let standing = ||
+        {
+            let faction = some_or_value!(entities.character(speaker), 0.0).faction;
+
+            entities.player(player).map(|player| player.reputation.standing(faction)).unwrap_or(0.0)
+        };
+
+        match self
+        {
+            Self::HasItem(name) =>
+            {
+                let id = some_or_false!(entities.infos().items_info.get_id(name));
+
+                entities.inventory(player).map(|inventory| inventory.find(id).is_some()).unwrap_or(false)
+            },
+            Self::MinReputation(x) => standing() >= *x,
+            Self::MaxReputation(x) => standing() <= *x
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub enum DialogueEffect
+{
+    GiveItem(String),
+    TakeItem(String),
+    Reputation(f32),
+    // opens the speakers stock
+    Trade,
+    // speaker starts following whoever picked it
+    Recruit,
+    // lisp code ran in the console of whoever picked it
+    Script(String)
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DialogueChoice
+{
+    pub text: String,
+    #[serde(default)]
+    pub conditions: Vec<DialogueCondition>,
+    #[serde(default)]
+    pub effects: Vec<DialogueEffect>,
+    // none ends the conversation
+    pub next: Option<String>
+}
+
+impl DialogueChoice
+{
+    pub fn available(&self, entities: &impl AnyEntities, player: Entity, speaker: Entity) -> bool
+    {
+        self.conditions.iter().all(|condition| condition.holds(entities, player, speaker))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DialogueNode
+{
+    pub text: String,
+    #[serde(default)]
+    pub choices: Vec<DialogueChoice>
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DialogueTree
+{
+    pub start: String,
+    pub nodes: HashMap<String, DialogueNode>
+}
+
+pub struct DialoguesInfo
+{
+    trees: HashMap<String, DialogueTree>
+}
+
+impl DialoguesInfo
+{
+    pub fn parse(info: impl AsRef<Path>) -> Self
+    {
+        let info = File::open(overridden(info)).unwrap();
+
+        let trees: HashMap<String, DialogueTree> = serde_json::from_reader(info).unwrap();
+
+        trees.iter().for_each(|(name, tree)|
+        {
+            let exists = |node: &str| tree.nodes.contains_key(node);
+
+            if !exists(&tree.start)
+            {
+                panic!("dialogue {name} starts at {} which doesnt exist", tree.start);
+            }
+
+            tree.nodes.values().flat_map(|node| node.choices.iter()).for_each(|choice|
+            {
+                if let Some(next) = choice.next.as_ref().filter(|next| !exists(next))
+                {
+                    panic!("dialogue {name} goes to {next} which doesnt exist");
+                }
+            });
+        });
+
+        Self{trees}
+    }
+
+    pub fn get(&self, name: &str) -> Option<&DialogueTree>
+    {
+        self.trees.get(name)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DialogueSession
+{
+    pub player: Entity,
+    pub node: String
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Dialogue
+{
+    // name of the tree in dialogues.json
+    pub name: String,
+    session: Option<DialogueSession>
+}
+
+impl Dialogue
+{
+    pub fn new(name: String) -> Self
+    {
+        Self{name, session: None}
+    }
+
+    pub fn session(&self) -> Option<&DialogueSession>
+    {
+        self.session.as_ref()
+    }
+
+    pub fn talking_to(&self, player: Entity) -> bool
+    {
+        self.session.as_ref().map(|session| session.player == player).unwrap_or(false)
+    }
+
+    pub fn start(&mut self, player: Entity, node: String)
+    {
+        self.session = Some(DialogueSession{player, node});
+    }
+
+    pub fn advance(&mut self, node: Option<String>)
+    {
+        self.session = node.and_then(|node|
+        {
+            self.session.take().map(|session| DialogueSession{node, ..session})
+        });
+    }
+
+    pub fn end(&mut self)
+    {
+        self.session = None;
+    }
+
+    pub fn current_node<'a>(&self, dialogues_info: &'a DialoguesInfo) -> Option<&'a DialogueNode>
+    {
+        let session = self.session.as_ref()?;
+
+        dialogues_info.get(&self.name)?.nodes.get(&session.node)
+    }
+}
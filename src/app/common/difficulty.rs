@@ -0,0 +1,91 @@
+use std::ops::Range;
+
+use serde::{Serialize, Deserialize};
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum DeathPolicy
+{
+    // back at the spawn point without anything that was carried
+    Respawn,
+    // back at the spawn point, everything carried is left in remains where the player died
+    #[default]
+    CorpseRun,
+    // the world is over and cant be loaded again
+    Permadeath
+}
+
+// picked when a world gets created and stays with it, the server sends it to everyone who connects
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Difficulty
+{
+    // multiplies the damage enemies do to players
+    pub enemy_damage: f32,
+    // multiplies how tough newly spawned enemies are
+    pub enemy_health: f32,
+    // multiplies how many items spawn in enemies and containers
+    pub loot: f32,
+    // multiplies how fast players get hungry and thirsty
+    pub hunger_rate: f32,
+    // fraction of the experience towards the next level lost on death
+    pub death_penalty: f32,
+    pub death: DeathPolicy
+}
+
+impl Default for Difficulty
+{
+    fn default() -> Self
+    {
+        Self::normal()
+    }
+}
+
+impl Difficulty
+{
+    pub fn easy() -> Self
+    {
+        Self{
+            enemy_damage: 0.6,
+            enemy_health: 0.75,
+            loot: 1.5,
+            hunger_rate: 0.75,
+            death_penalty: 0.0,
+            death: DeathPolicy::CorpseRun
+        }
+    }
+
+    pub fn normal() -> Self
+    {
+        Self{
+            enemy_damage: 1.0,
+            enemy_health: 1.0,
+            loot: 1.0,
+            hunger_rate: 1.0,
+            death_penalty: 0.0,
+            death: DeathPolicy::CorpseRun
+        }
+    }
+
+    pub fn hard() -> Self
+    {
+        Self{
+            enemy_damage: 1.5,
+            enemy_health: 1.3,
+            loot: 0.6,
+            hunger_rate: 1.4,
+            death_penalty: 0.5,
+            death: DeathPolicy::Respawn
+        }
+    }
+
+    // always leaves at least one possible amount so it can be picked from
+    pub fn loot_amount(&self, amount: Range<usize>) -> Range<usize>
+    {
+        let scale = |x: usize| (x as f32 * self.loot).round() as usize;
+
+        let start = scale(amount.start);
+
+        start..scale(amount.end).max(start + 1)
+    }
+}
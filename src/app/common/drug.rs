@@ -4,5 +4,8 @@ use serde::Deserialize;
 #[derive(Debug, Clone, Deserialize)]
 pub enum Drug
 {
-    Heal{amount: f32}
+    Heal{amount: f32},
+    // seconds it keeps fighting infections for
+    Antibiotics{duration: f32},
+    Disinfectant{amount: f32}
 }
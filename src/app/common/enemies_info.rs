@@ -1,6 +1,7 @@
 use std::{
     fs::File,
-    path::Path
+    path::Path,
+    sync::Arc
 };
 
 use serde::Deserialize;
@@ -10,18 +11,57 @@ use yanyaengine::Assets;
 use crate::common::{
     pick_by_commonness,
     normalize_path,
+    mods_info::merge_by_name,
+    rng::RngStream,
     ENTITY_SCALE,
     generic_info::*,
     Hairstyle,
     CharactersInfo,
     CharacterInfo,
     CharacterId,
-    anatomy::HumanAnatomyInfo,
-    enemy::EnemyBehavior
+    Faction,
+    VendorInfo,
+    OutlineStyle,
+    DeathEffect,
+    anatomy::{HumanAnatomyInfo, BodyPlansInfo, BodyPlanInfo, HUMAN_PLAN},
+    enemy::{EnemyBehavior, BehaviorTimings},
+    behavior::BehaviorNode
 };
 
 
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EnemyLootInfo
+{
+    pub groups: Vec<String>,
+    // inclusive on both ends
+    pub amount: (usize, usize),
+    pub holding_chance: f32
+}
+
+impl Default for EnemyLootInfo
+{
+    fn default() -> Self
+    {
+        Self{
+            groups: ["utility", "weapons", "animals"].into_iter().map(ToOwned::to_owned).collect(),
+            amount: (1, 3),
+            holding_chance: 0.1
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ButcherInfo
+{
+    pub item: String,
+    // inclusive on both ends
+    pub amount: (usize, usize)
+}
+
 #[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
 struct EnemyInfoRaw
 {
     name: String,
@@ -29,14 +69,32 @@ struct EnemyInfoRaw
     hairstyle: Hairstyle<String>,
     #[serde(default)]
     anatomy: HumanAnatomyInfo,
+    // name from body_plans.json, human if missing
+    body_plan: Option<String>,
     behavior: Option<EnemyBehavior>,
+    // decides what to do whenever the current state ends
+    tree: Option<BehaviorNode>,
+    #[serde(default)]
+    timings: BehaviorTimings,
+    faction: Option<Faction>,
     scale: Option<f32>,
+    mass: Option<f32>,
     normal: String,
     crawling: String,
     lying: String,
     hand: String,
     commonness: Option<f32>,
-    loot_commonness: Option<f32>
+    #[serde(default)]
+    loot: EnemyLootInfo,
+    loot_commonness: Option<f32>,
+    butcher: Option<ButcherInfo>,
+    vendor: Option<VendorInfo>,
+    dodge_chance: Option<f32>,
+    barks: Option<String>,
+    dialogue: Option<String>,
+    outline: Option<OutlineStyle>,
+    #[serde(default)]
+    death: DeathEffect
 }
 
 type EnemiesInfoRaw = Vec<EnemyInfoRaw>;
@@ -47,11 +105,28 @@ pub struct EnemyInfo
 {
     pub name: String,
     pub anatomy: HumanAnatomyInfo,
+    pub body_plan: Arc<BodyPlanInfo>,
     pub behavior: EnemyBehavior,
+    pub tree: BehaviorNode,
+    pub timings: BehaviorTimings,
+    pub faction: Faction,
     pub character: CharacterId,
     pub scale: f32,
+    pub mass: f32,
     pub commonness: f32,
-    pub loot_commonness: f32
+    pub loot: EnemyLootInfo,
+    pub loot_commonness: f32,
+    // what cutting up the corpse gives, cant be butchered if none
+    pub butcher: Option<ButcherInfo>,
+    pub vendor: Option<VendorInfo>,
+    // chance per second to dodge while the target is winding up an attack
+    pub dodge_chance: f32,
+    // name of the table in barks.json
+    pub barks: Option<String>,
+    // name of the tree in dialogues.json
+    pub dialogue: Option<String>,
+    pub outline: OutlineStyle,
+    pub death: DeathEffect
 }
 
 impl GenericItem for EnemyInfo
@@ -67,10 +142,17 @@ impl EnemyInfo
     fn from_raw(
         assets: &Assets,
         characters_info: &mut CharactersInfo,
+        body_plans: &BodyPlansInfo,
         textures_root: &Path,
         raw: EnemyInfoRaw
     ) -> Self
     {
+        let body_plan = {
+            let name = raw.body_plan.as_deref().unwrap_or(HUMAN_PLAN);
+
+            body_plans.get(name).unwrap_or_else(|| panic!("body plan {name} doesnt exist")).clone()
+        };
+
         let get_texture = |name|
         {
             let path = textures_root.join(name);
@@ -82,6 +164,18 @@ impl EnemyInfo
 
         let scale = raw.scale.unwrap_or(1.0) * ENTITY_SCALE;
 
+        let outline = raw.outline.unwrap_or_else(||
+        {
+            // vendors are worth going to
+            if raw.vendor.is_some()
+            {
+                OutlineStyle::quest()
+            } else
+            {
+                OutlineStyle::enemy()
+            }
+        });
+
         let character = characters_info.push(CharacterInfo{
             scale,
             hairstyle: raw.hairstyle.map(get_texture),
@@ -94,11 +188,24 @@ impl EnemyInfo
         Self{
             name: raw.name,
             anatomy: raw.anatomy,
+            body_plan,
             behavior: raw.behavior.unwrap_or(EnemyBehavior::Melee),
+            tree: raw.tree.unwrap_or_else(BehaviorNode::default_tree),
+            timings: raw.timings,
+            faction: raw.faction.unwrap_or(Faction::Zob),
             character,
             scale,
+            mass: raw.mass.unwrap_or_else(|| 50.0 * ENTITY_SCALE / scale),
             commonness: raw.commonness.unwrap_or(1.0),
-            loot_commonness: raw.loot_commonness.unwrap_or(1.0)
+            loot: raw.loot,
+            loot_commonness: raw.loot_commonness.unwrap_or(1.0),
+            butcher: raw.butcher,
+            vendor: raw.vendor,
+            dodge_chance: raw.dodge_chance.unwrap_or(0.0),
+            barks: raw.barks,
+            dialogue: raw.dialogue,
+            outline,
+            death: raw.death
         }
     }
 }
@@ -115,18 +222,26 @@ impl EnemiesInfo
     pub fn parse(
         assets: &Assets,
         characters_info: &mut CharactersInfo,
+        body_plans: &BodyPlansInfo,
         textures_root: impl AsRef<Path>,
-        info: impl AsRef<Path>
+        infos: impl IntoIterator<Item=impl AsRef<Path>>
     ) -> Self
     {
-        let info = File::open(info.as_ref()).unwrap();
+        let enemies = infos.into_iter().flat_map(|info|
+        {
+            let info = File::open(info.as_ref()).unwrap();
+
+            let enemies: EnemiesInfoRaw = serde_json::from_reader(info).unwrap();
+
+            enemies
+        });
 
-        let enemies: EnemiesInfoRaw = serde_json::from_reader(info).unwrap();
+        let enemies = merge_by_name(enemies, |x| &x.name);
 
         let textures_root = textures_root.as_ref();
         let mut enemies: Vec<_> = enemies.into_iter().map(|info_raw|
         {
-            EnemyInfo::from_raw(assets, characters_info, textures_root, info_raw)
+            EnemyInfo::from_raw(assets, characters_info, body_plans, textures_root, info_raw)
         }).collect();
 
         let commonnest = enemies.iter().map(|x| x.commonness).max_by(|a, b|
@@ -144,9 +259,19 @@ impl EnemiesInfo
 
     pub fn weighted_random(&self, commonness: f64) -> Option<EnemyId>
     {
-        let ids = (0..self.items().len()).map(EnemyId::from);
+        self.weighted_random_with(commonness, |_| true)
+    }
+
+    pub fn weighted_random_with(
+        &self,
+        commonness: f64,
+        predicate: impl Fn(&EnemyInfo) -> bool
+    ) -> Option<EnemyId>
+    {
+        let predicate = &predicate;
+        let ids = (0..self.items().len()).map(EnemyId::from).filter(|id| predicate(self.get(*id)));
 
-        pick_by_commonness(commonness, ids, |id|
+        pick_by_commonness(RngStream::WorldGen, commonness, ids, |id|
         {
             self.get(id).commonness as f64
         })
@@ -1,3 +1,5 @@
+use std::f32;
+
 use serde::{Serialize, Deserialize};
 
 use nalgebra::{Unit, Vector3};
@@ -12,15 +14,49 @@ use crate::common::{
     EnemiesInfo,
     EnemyInfo,
     EnemyId,
+    DeathEffect,
+    Vendor,
+    BarkKind,
     Physical,
-    Anatomy
+    Anatomy,
+    pathfind::PathCache,
+    behavior::{Blackboard, BehaviorEffects},
+    world::World
 };
 
 
+// how close a companion tries to stay to whatever its following
+const COMPANION_DISTANCE: f32 = 0.4;
+const GUARD_DISTANCE: f32 = 2.0;
+
+const SEARCH_RADIUS: f32 = 0.5;
+const INVESTIGATE_ARRIVE_DISTANCE: f32 = 0.15;
+
+// how far ahead (in seconds) to guess where a lost target went
+const PREDICT_TIME: f32 = 1.0;
+
+pub const FLEE_DISTANCE: f32 = 0.5;
+
+// how often it looks around for anything to attack
+const HOSTILE_INTERVAL: f32 = 0.5;
+
+// someone standing in full light gets noticed on the first look
+const SPOT_SPEED: f32 = 2.0;
+const SPOT_DECAY: f32 = 0.5;
+
+// allies this close come running when called for help
+pub const HELP_RADIUS: f32 = 2.0;
+
+// other characters closer than this get steered around
+pub const AVOID_DISTANCE: f32 = 0.25;
+pub const AVOID_LOOKAHEAD: f32 = 0.2;
+const AVOID_STRENGTH: f32 = 0.8;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum EnemyBehavior
 {
-    Melee
+    Melee,
+    Animal{cornered_attack: bool}
 }
 
 impl EnemyBehavior
@@ -29,34 +65,91 @@ impl EnemyBehavior
     {
         match self
         {
-            Self::Melee => BehaviorState::Wait
+            Self::Melee
+            | Self::Animal{..} => BehaviorState::Wait
         }
     }
 
-    pub fn duration_of(&self, rng: &mut SeededRandom, state: &BehaviorState) -> Option<f32>
+    pub fn is_skittish(&self) -> bool
     {
-        let range = match self
+        match self
         {
-            Self::Melee =>
+            Self::Melee => false,
+            Self::Animal{..} => true
+        }
+    }
+
+    fn cornered_attack(&self) -> bool
+    {
+        match self
+        {
+            Self::Melee => false,
+            Self::Animal{cornered_attack} => *cornered_attack
+        }
+    }
+
+    pub fn duration_of(
+        &self,
+        rng: &mut SeededRandom,
+        timings: &BehaviorTimings,
+        state: &BehaviorState
+    ) -> Option<f32>
+    {
+        let (low, high) = match self
+        {
+            Self::Melee
+            | Self::Animal{..} =>
             {
                 match state
                 {
-                    BehaviorState::Wait => 10.0..=20.0,
-                    BehaviorState::MoveDirection(_) => 0.8..=2.0,
+                    BehaviorState::Wait => timings.wait,
+                    BehaviorState::MoveDirection(_) => timings.move_direction,
+                    BehaviorState::Investigate(_) => timings.investigate,
+                    BehaviorState::Search{..} => timings.search,
+                    BehaviorState::Flee(_) => timings.flee,
                     BehaviorState::Attack(_) => return None
                 }
             }
         };
 
-        Some(rng.next_f32_between(range))
+        Some(rng.next_f32_between(low..=high))
     }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BehaviorTimings
+{
+    pub wait: (f32, f32),
+    pub move_direction: (f32, f32),
+    // how long to try getting to a noise before giving up
+    pub investigate: (f32, f32),
+    pub search: (f32, f32),
+    pub flee: (f32, f32)
+}
+
+impl Default for BehaviorTimings
+{
+    fn default() -> Self
+    {
+        Self{
+            wait: (10.0, 20.0),
+            move_direction: (0.8, 2.0),
+            investigate: (10.0, 15.0),
+            search: (5.0, 8.0),
+            flee: (3.0, 6.0)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum BehaviorState
 {
     Wait,
     MoveDirection(Unit<Vector3<f32>>),
+    Investigate(Vector3<f32>),
+    Search{center: Vector3<f32>, direction: Unit<Vector3<f32>>},
+    Flee(Entity),
     Attack(Entity)
 }
 
@@ -68,14 +161,145 @@ impl Default for BehaviorState
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CompanionOrder
+{
+    Follow,
+    Stay,
+    Guard
+}
+
+impl CompanionOrder
+{
+    pub fn next(self) -> Self
+    {
+        match self
+        {
+            Self::Follow => Self::Stay,
+            Self::Stay => Self::Guard,
+            Self::Guard => Self::Follow
+        }
+    }
+
+    pub fn name(&self) -> &'static str
+    {
+        match self
+        {
+            Self::Follow => "follow",
+            Self::Stay => "stay",
+            Self::Guard => "guard"
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Companion
+{
+    order: CompanionOrder,
+    // entities dont keep their ids across saves so the leader gets found again by name
+    leader_name: String,
+    leader: Option<Entity>,
+    anchor: Vector3<f32>
+}
+
+impl Companion
+{
+    pub fn new(leader_name: String, leader: Entity, anchor: Vector3<f32>) -> Self
+    {
+        Self{
+            order: CompanionOrder::Follow,
+            leader_name,
+            leader: Some(leader),
+            anchor
+        }
+    }
+
+    pub fn order(&self) -> CompanionOrder
+    {
+        self.order
+    }
+
+    pub fn set_order(&mut self, order: CompanionOrder, anchor: Vector3<f32>)
+    {
+        self.order = order;
+        self.anchor = anchor;
+    }
+
+    pub fn leader_name(&self) -> &str
+    {
+        &self.leader_name
+    }
+
+    pub fn leader(&self) -> Option<Entity>
+    {
+        self.leader
+    }
+
+    pub fn set_leader(&mut self, leader: Option<Entity>)
+    {
+        self.leader = leader;
+    }
+
+    fn destination(&self, entities: &impl AnyEntities) -> Option<Vector3<f32>>
+    {
+        match self.order
+        {
+            CompanionOrder::Follow =>
+            {
+                entities.transform(self.leader?).map(|transform| transform.position)
+            },
+            CompanionOrder::Stay => None,
+            CompanionOrder::Guard => Some(self.anchor)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TargetMemory
+{
+    position: Vector3<f32>,
+    velocity: Vector3<f32>
+}
+
+impl TargetMemory
+{
+    fn predicted(&self) -> Vector3<f32>
+    {
+        self.position + self.velocity * PREDICT_TIME
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Enemy
 {
     behavior: EnemyBehavior,
+    timings: BehaviorTimings,
     behavior_state: BehaviorState,
     current_state_left: Option<f32>,
     hostile_timer: f32,
+    // how sure it is that its seeing someone, attacks at 1
+    #[serde(default)]
+    seen_fraction: f32,
     reset_state: bool,
+    target_memory: Option<TargetMemory>,
+    // where it first showed up, patrols go around here
+    #[serde(default)]
+    home: Option<Vector3<f32>>,
+    #[serde(default)]
+    last_enemy: Option<Entity>,
+    #[serde(skip)]
+    help_call: Option<Entity>,
+    companion: Option<Companion>,
+    vendor: Option<Vendor>,
+    dodge_chance: f32,
+    barks: Option<String>,
+    #[serde(default)]
+    death: DeathEffect,
+    bark_cooldown: f32,
+    #[serde(skip)]
+    pending_bark: Option<BarkKind>,
+    #[serde(skip)]
+    path: PathCache,
     id: EnemyId,
     rng: SeededRandom
 }
@@ -84,44 +308,64 @@ impl Enemy
 {
     pub fn new(enemies_info: &EnemiesInfo, id: EnemyId) -> Self
     {
-        let behavior = enemies_info.get(id).behavior.clone();
+        let info = enemies_info.get(id);
+
+        let behavior = info.behavior.clone();
+        let timings = info.timings.clone();
 
         let mut rng = SeededRandom::new();
         let behavior_state = behavior.start_state();
 
         Self{
-            current_state_left: behavior.duration_of(&mut rng, &behavior_state),
+            current_state_left: behavior.duration_of(&mut rng, &timings, &behavior_state),
             behavior_state,
             behavior,
+            timings,
             hostile_timer: 0.0,
+            seen_fraction: 0.0,
             reset_state: false,
+            target_memory: None,
+            home: None,
+            last_enemy: None,
+            help_call: None,
+            companion: None,
+            vendor: info.vendor.clone().map(Vendor::from),
+            dodge_chance: info.dodge_chance,
+            barks: info.barks.clone(),
+            death: info.death.clone(),
+            bark_cooldown: 0.0,
+            pending_bark: None,
+            path: PathCache::default(),
             id,
             rng
         }
     }
 
-    fn next_state(&self) -> BehaviorState
+    fn next_state(&mut self, entities: &impl AnyEntities, entity: Entity) -> BehaviorState
     {
-        match &self.behavior
-        {
-            EnemyBehavior::Melee =>
-            {
-                match &self.behavior_state
-                {
-                    BehaviorState::Wait =>
-                    {
-                        let x = fastrand::f32() * 2.0 - 1.0;
-                        let y = fastrand::f32() * 2.0 - 1.0;
+        let position = entities.transform(entity).map(|x| x.position).unwrap_or_else(Vector3::zeros);
+        let health = entities.anatomy(entity).map(|x| x.health_fraction()).unwrap_or(1.0);
+
+        let board = Blackboard{
+            previous: &self.behavior_state,
+            companion: self.companion.is_some(),
+            health,
+            position,
+            home: self.home.unwrap_or(position),
+            enemy: self.last_enemy.filter(|x| entities.exists(*x))
+        };
 
-                        let direction = Unit::new_normalize(Vector3::new(x, y, 0.0));
+        let mut effects = BehaviorEffects::default();
 
-                        BehaviorState::MoveDirection(direction)
-                    },
-                    BehaviorState::MoveDirection(_) => BehaviorState::Wait,
-                    BehaviorState::Attack(_) => BehaviorState::Wait
-                }
-            }
+        let state = self.info(&entities.infos().enemies_info).tree.decide(&board, &mut effects)
+            .unwrap_or(BehaviorState::Wait);
+
+        if effects.call_for_help.is_some()
+        {
+            self.help_call = effects.call_for_help;
         }
+
+        state
     }
 
     pub fn info<'a>(&self, enemies_info: &'a EnemiesInfo) -> &'a EnemyInfo
@@ -132,7 +376,9 @@ impl Enemy
     fn do_behavior(
         &mut self,
         entities: &impl AnyEntities,
+        world: &World,
         entity: Entity,
+        avoidance: Vector3<f32>,
         dt: f32
     )
     {
@@ -143,10 +389,20 @@ impl Enemy
             return;
         }
 
+        let hostile_target = if let BehaviorState::Attack(other_entity) = &self.behavior_state
+        {
+            entities.is_hostile(entity, *other_entity)
+        } else
+        {
+            false
+        };
+
         let transform = some_or_return!(entities.target_ref(entity));
         let mut physical = some_or_return!(entities.physical_mut(entity));
         let mut character = some_or_return!(entities.character_mut(entity));
 
+        let mut new_state = None;
+
         match &self.behavior_state
         {
             BehaviorState::MoveDirection(direction) =>
@@ -155,46 +411,201 @@ impl Enemy
                     &mut physical,
                     &mut character,
                     &anatomy,
+                    avoidance,
                     *direction,
                     dt
                 );
             },
-            BehaviorState::Attack(other_entity) =>
+            BehaviorState::Investigate(position) =>
             {
-                let other_entity = *other_entity;
+                let offset = position - transform.position;
 
-                if entity == other_entity
+                if offset.xy().magnitude() < INVESTIGATE_ARRIVE_DISTANCE
                 {
-                    self.reset_state = true;
-                    return;
+                    new_state = Some(BehaviorState::Search{
+                        center: *position,
+                        direction: Self::random_direction(&mut self.rng)
+                    });
+                } else
+                {
+                    Self::move_direction(
+                        &mut physical,
+                        &mut character,
+                        &anatomy,
+                        avoidance,
+                        some_or_return!(
+                            self.path.direction(entities, world, transform.position, *position, dt)
+                        ),
+                        dt
+                    );
                 }
+            },
+            BehaviorState::Search{center, direction} =>
+            {
+                let offset = center - transform.position;
 
-                if let Some(other_transform) = entities.transform(other_entity)
+                if offset.xy().magnitude() > SEARCH_RADIUS
                 {
-                    let aggressive = character.aggressive(
-                        &entities.character(other_entity).unwrap()
+                    // wander back towards the middle from a slightly random angle
+                    let back = Unit::try_new(offset, 0.01).map(|back|
+                    {
+                        let jitter = Self::random_direction(&mut self.rng).into_inner() * 0.5;
+
+                        Unit::new_normalize(back.into_inner() + jitter)
+                    }).unwrap_or(*direction);
+
+                    new_state = Some(BehaviorState::Search{center: *center, direction: back});
+                } else
+                {
+                    Self::move_direction(
+                        &mut physical,
+                        &mut character,
+                        &anatomy,
+                        avoidance,
+                        *direction,
+                        dt
                     );
+                }
+            },
+            BehaviorState::Flee(threat) =>
+            {
+                if let Some(threat_transform) = entities.transform(*threat)
+                {
+                    let threat_position = threat_transform.position;
 
-                    let sees = anatomy.sees(&transform.position, &other_transform.position);
+                    let cornered = self.behavior.cornered_attack()
+                        && character.bash_reachable(&transform, &threat_position);
 
-                    if aggressive && sees
+                    if cornered
                     {
-                        let direction = other_transform.position - transform.position;
+                        let direction = some_or_return!(
+                            Unit::try_new(threat_position - transform.position, 0.01)
+                        );
 
+                        Self::look_direction(&mut character, direction);
+                        character.push_action(CharacterAction::Bash);
+                    } else
+                    {
                         Self::move_direction(
                             &mut physical,
                             &mut character,
                             &anatomy,
-                            some_or_return!(Unit::try_new(direction, 0.01)),
+                            avoidance,
+                            some_or_return!(
+                                Unit::try_new(transform.position - threat_position, 0.01)
+                            ),
                             dt
                         );
+                    }
+                } else
+                {
+                    self.reset_state = true;
+                }
+            },
+            BehaviorState::Attack(other_entity) =>
+            {
+                let other_entity = *other_entity;
+
+                if entity == other_entity
+                {
+                    self.reset_state = true;
+                    return;
+                }
+
+                if let Some(other_transform) = entities.transform(other_entity)
+                {
+                    let sees = anatomy.sees(
+                        &transform.position,
+                        &other_transform.position,
+                        entities.weather().sight_scale()
+                    );
+
+                    let order = self.companion.as_ref().map(|companion| companion.order);
+
+                    let too_far_from_post = order == Some(CompanionOrder::Guard)
+                        && self.companion.as_ref().map(|companion|
+                        {
+                            (companion.anchor - transform.position).magnitude() > GUARD_DISTANCE
+                        }).unwrap_or(false);
 
-                        if character.bash_reachable(&transform, &other_transform.position)
+                    if hostile_target && sees && !too_far_from_post
+                    {
+                        self.target_memory = Some(TargetMemory{
+                            position: other_transform.position,
+                            velocity: entities.physical(other_entity).map(|physical|
+                            {
+                                *physical.velocity()
+                            }).unwrap_or_else(Vector3::zeros)
+                        });
+
+                        let direction = some_or_return!(
+                            Unit::try_new(other_transform.position - transform.position, 0.01)
+                        );
+
+                        if order == Some(CompanionOrder::Stay)
+                        {
+                            Self::look_direction(&mut character, direction);
+                        } else
+                        {
+                            let walk = self.path.direction(
+                                entities,
+                                world,
+                                transform.position,
+                                other_transform.position,
+                                dt
+                            ).unwrap_or(direction);
+
+                            Self::move_direction(
+                                &mut physical,
+                                &mut character,
+                                &anatomy,
+                                avoidance,
+                                walk,
+                                dt
+                            );
+                        }
+
+                        let target_winding_up = entities.character(other_entity).map(|other|
+                        {
+                            other.is_winding_up()
+                        }).unwrap_or(false);
+
+                        let wants_dodge = target_winding_up
+                            && character.can_dodge()
+                            && self.rng.next_f32_between(0.0..=1.0) < self.dodge_chance * dt;
+
+                        if wants_dodge
+                        {
+                            // roll to the side of the incoming attack
+                            let side = Vector3::new(-direction.y, direction.x, 0.0);
+                            let side = if self.rng.next_bool()
+                            {
+                                side
+                            } else
+                            {
+                                -side
+                            };
+
+                            character.push_action(CharacterAction::Dodge(side));
+                        } else if character.bash_reachable(&transform, &other_transform.position)
                         {
                             character.push_action(CharacterAction::Bash);
                         }
+                    } else if hostile_target && !sees && self.companion.is_none()
+                    {
+                        // lost sight of them, go look where they were heading
+                        new_state = self.target_memory.take().map(|memory|
+                        {
+                            BehaviorState::Investigate(memory.predicted())
+                        });
+
+                        if new_state.is_none()
+                        {
+                            self.reset_state = true;
+                        }
                     } else
                     {
+                        self.target_memory = None;
                         self.reset_state = true;
                     }
                 } else
@@ -202,21 +613,63 @@ impl Enemy
                     self.reset_state = true;
                 }
             },
-            BehaviorState::Wait => ()
+            BehaviorState::Wait =>
+            {
+                let destination = self.companion.as_ref().and_then(|companion|
+                {
+                    companion.destination(entities)
+                });
+
+                if let Some(destination) = destination
+                {
+                    let direction = destination - transform.position;
+
+                    if direction.magnitude() > COMPANION_DISTANCE
+                    {
+                        Self::move_direction(
+                            &mut physical,
+                            &mut character,
+                            &anatomy,
+                            avoidance,
+                            some_or_return!(
+                                self.path.direction(entities, world, transform.position, destination, dt)
+                            ),
+                            dt
+                        );
+                    }
+                }
+            }
+        }
+
+        if let Some(state) = new_state
+        {
+            self.set_state(state);
         }
     }
 
+    fn random_direction(rng: &mut SeededRandom) -> Unit<Vector3<f32>>
+    {
+        let angle = rng.next_f32_between(0.0..=(f32::consts::PI * 2.0));
+
+        Unit::new_unchecked(Vector3::new(angle.cos(), angle.sin(), 0.0))
+    }
+
     fn move_direction(
         physical: &mut Physical,
         character: &mut Character,
         anatomy: &Anatomy,
+        avoidance: Vector3<f32>,
         direction: Unit<Vector3<f32>>,
         dt: f32
     )
     {
         Self::look_direction(character, direction);
 
-        character.walk(anatomy, physical, direction, dt);
+        // keep looking where its going but walk around whatever is in the way
+        let steered = Unit::try_new(*direction + avoidance * AVOID_STRENGTH, 0.01)
+            .unwrap_or(direction);
+
+        character.walk(anatomy, physical, steered, dt);
     }
 
     fn look_direction(
@@ -232,7 +685,9 @@ impl Enemy
     pub fn update(
         &mut self,
         entities: &impl AnyEntities,
+        world: &World,
         entity: Entity,
+        avoidance: Vector3<f32>,
         dt: f32
     ) -> bool
     {
@@ -243,9 +698,24 @@ impl Enemy
             return false;
         }
 
+        if let Some(vendor) = self.vendor.as_mut()
+        {
+            vendor.update(dt);
+        }
+
+        if self.home.is_none()
+        {
+            self.home = entities.transform(entity).map(|x| x.position);
+        }
+
+        if self.bark_cooldown > 0.0
+        {
+            self.bark_cooldown -= dt;
+        }
+
         if self.hostile_timer <= 0.0
         {
-            self.hostile_timer = 0.5;
+            self.hostile_timer = HOSTILE_INTERVAL;
         } else
         {
             self.hostile_timer -= dt;
@@ -258,7 +728,7 @@ impl Enemy
             let changed_state = *current_state_left <= 0.0;
             if changed_state
             {
-                self.set_next_state();
+                self.set_next_state(entities, entity);
             }
 
             changed_state
@@ -272,17 +742,21 @@ impl Enemy
             self.reset_state = false;
 
             changed = true;
-            self.set_next_state();
+            self.set_next_state(entities, entity);
         }
 
-        self.do_behavior(entities, entity, dt);
+        let previous_state = self.behavior_state.clone();
 
-        changed
+        self.do_behavior(entities, world, entity, avoidance, dt);
+
+        changed || (previous_state != self.behavior_state)
     }
 
-    fn set_next_state(&mut self)
+    fn set_next_state(&mut self, entities: &impl AnyEntities, entity: Entity)
     {
-        self.set_state(self.next_state());
+        let state = self.next_state(entities, entity);
+
+        self.set_state(state);
     }
 
     fn set_state(&mut self, state: BehaviorState)
@@ -291,15 +765,42 @@ impl Enemy
 
         self.current_state_left = self.behavior.duration_of(
             &mut self.rng,
+            &self.timings,
             &self.behavior_state
         );
     }
 
     pub fn set_attacking(&mut self, entity: Entity)
     {
+        if !self.is_attacking()
+        {
+            self.bark(BarkKind::Spot);
+        }
+
+        self.target_memory = None;
+        self.last_enemy = Some(entity);
+
         self.set_state(BehaviorState::Attack(entity));
     }
 
+    // true once its seen enough to be sure
+    pub fn spot(&mut self, visibility: f32) -> bool
+    {
+        self.seen_fraction = (self.seen_fraction + visibility * SPOT_SPEED * HOSTILE_INTERVAL).min(1.0);
+
+        self.seen_fraction >= 1.0
+    }
+
+    pub fn lose_sight(&mut self)
+    {
+        self.seen_fraction = (self.seen_fraction - SPOT_DECAY * HOSTILE_INTERVAL).max(0.0);
+    }
+
+    pub fn seen_fraction(&self) -> f32
+    {
+        self.seen_fraction
+    }
+
     pub fn is_attacking(&self) -> bool
     {
         match self.behavior_state
@@ -309,6 +810,111 @@ impl Enemy
         }
     }
 
+    pub fn hear_noise(&mut self, position: Vector3<f32>) -> bool
+    {
+        let busy = self.is_attacking() || self.companion.is_some();
+
+        if busy
+        {
+            return false;
+        }
+
+        self.set_state(BehaviorState::Investigate(position));
+
+        true
+    }
+
+    // whoever the tree wants the nearby allies to go after
+    pub fn take_help_call(&mut self) -> Option<Entity>
+    {
+        self.help_call.take()
+    }
+
+    pub fn recruit(&mut self, leader_name: String, leader: Entity, position: Vector3<f32>)
+    {
+        self.companion = Some(Companion::new(leader_name, leader, position));
+
+        self.set_state(BehaviorState::Wait);
+    }
+
+    pub fn bark(&mut self, kind: BarkKind)
+    {
+        if self.barks.is_some() && self.bark_cooldown <= 0.0 && self.pending_bark.is_none()
+        {
+            self.pending_bark = Some(kind);
+        }
+    }
+
+    // the table name gets returned so the caller can look up the lines
+    pub fn take_bark(&mut self) -> Option<(&str, BarkKind)>
+    {
+        let kind = self.pending_bark.take()?;
+
+        self.barks.as_deref().map(|table| (table, kind))
+    }
+
+    pub fn set_bark_cooldown(&mut self, cooldown: f32)
+    {
+        self.bark_cooldown = cooldown;
+    }
+
+    pub fn death(&self) -> &DeathEffect
+    {
+        &self.death
+    }
+
+    pub fn vendor(&self) -> Option<&Vendor>
+    {
+        self.vendor.as_ref()
+    }
+
+    pub fn vendor_mut(&mut self) -> Option<&mut Vendor>
+    {
+        self.vendor.as_mut()
+    }
+
+    pub fn companion(&self) -> Option<&Companion>
+    {
+        self.companion.as_ref()
+    }
+
+    pub fn home(&self) -> Option<Vector3<f32>>
+    {
+        self.home
+    }
+
+    pub fn companion_mut(&mut self) -> Option<&mut Companion>
+    {
+        self.companion.as_mut()
+    }
+
+    pub fn set_fleeing(&mut self, entity: Entity)
+    {
+        self.bark(BarkKind::Flee);
+
+        self.last_enemy = Some(entity);
+
+        self.set_state(BehaviorState::Flee(entity));
+    }
+
+    pub fn is_fleeing(&self) -> bool
+    {
+        match self.behavior_state
+        {
+            BehaviorState::Flee(_) => true,
+            _ => false
+        }
+    }
+
+    pub fn check_threats(&self) -> bool
+    {
+        self.behavior.is_skittish()
+            && self.companion.is_none()
+            && !self.is_fleeing()
+            && !self.is_attacking()
+            && (self.hostile_timer <= 0.0)
+    }
+
     pub fn check_hostiles(&self) -> bool
     {
         !self.is_attacking() && (self.hostile_timer <= 0.0)
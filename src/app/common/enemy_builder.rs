@@ -8,18 +8,20 @@ use crate::common::{
     random_rotation,
     render_info::*,
     physics::*,
-    ENTITY_SCALE,
+    rng::{self, RngStream},
     ItemsInfo,
     Loot,
     Inventory,
     Anatomy,
-    HumanAnatomy,
-    Faction,
+    PlanAnatomy,
     Character,
     Enemy,
+    Dialogue,
     EnemyId,
     EnemiesInfo,
     EntityInfo,
+    Difficulty,
+    Outlineable,
     lazy_transform::*
 };
 
@@ -28,6 +30,7 @@ pub struct EnemyBuilder<'a>
 {
     enemies_info: &'a EnemiesInfo,
     items_info: &'a ItemsInfo,
+    difficulty: &'a Difficulty,
     pos: Vector3<f32>,
     id: EnemyId
 }
@@ -37,11 +40,12 @@ impl<'a> EnemyBuilder<'a>
     pub fn new(
         enemies_info: &'a EnemiesInfo,
         items_info: &'a ItemsInfo,
+        difficulty: &'a Difficulty,
         id: EnemyId,
         pos: Vector3<f32>
     ) -> Self
     {
-        Self{enemies_info, items_info, pos, id}
+        Self{enemies_info, items_info, difficulty, pos, id}
     }
 
     pub fn build(self) -> EntityInfo
@@ -52,15 +56,25 @@ impl<'a> EnemyBuilder<'a>
 
         let mut loot = Loot::new(
             self.items_info,
-            vec!["utility", "weapons", "animals"],
+            info.loot.groups.iter().map(String::as_str).collect(),
             info.loot_commonness * 0.6
         );
 
-        loot.create_random(&mut inventory, 1..4);
+        let (low, high) = info.loot.amount;
+        loot.create_random(&mut inventory, self.difficulty.loot_amount(low..(high + 1)));
 
-        let mut character = Character::new(info.character, Faction::Zob);
+        let mut character = Character::new(info.character, info.faction);
 
-        if fastrand::f32() < 0.1
+        let mut anatomy = info.anatomy.clone();
+
+        let health = self.difficulty.enemy_health;
+        anatomy.bone_toughness *= health;
+        anatomy.muscle_toughness *= health;
+        anatomy.skin_toughness *= health;
+
+        let anatomy = Anatomy::Plan(PlanAnatomy::new(&info.body_plan, anatomy));
+
+        if !inventory.is_empty() && rng::f32(RngStream::Loot) < info.loot.holding_chance
         {
             character.set_holding(Some(inventory.random()));
         }
@@ -88,17 +102,20 @@ impl<'a> EnemyBuilder<'a>
                 ..Default::default()
             }),
             physical: Some(PhysicalProperties{
-                inverse_mass: (info.scale / ENTITY_SCALE) * 50.0_f32.recip(),
+                inverse_mass: info.mass.recip(),
                 static_friction: 0.9,
                 dynamic_friction: 0.8,
+                density: BODY_DENSITY,
                 fixed: PhysicalFixed{rotation: true, ..Default::default()},
                 ..Default::default()
             }.into()),
             inventory: Some(inventory),
-            anatomy: Some(Anatomy::Human(HumanAnatomy::new(info.anatomy.clone()))),
+            anatomy: Some(anatomy),
             character: Some(character),
             named: Some(self.enemies_info.get(self.id).name.clone()),
             enemy: Some(Enemy::new(self.enemies_info, self.id)),
+            dialogue: info.dialogue.clone().map(Dialogue::new),
+            outlineable: Some(Outlineable::new(info.outline)),
             ..Default::default()
         }
     }
@@ -3,14 +3,15 @@ use std::{
     mem,
     rc::Rc,
     fmt::{self, Debug},
-    cell::{Ref, RefMut, RefCell}
+    cell::{Ref, RefMut, RefCell},
+    collections::HashMap
 };
 
 use serde::{Serialize, Deserialize};
 
 use nalgebra::Vector3;
 
-use yanyaengine::{TextureId, Transform};
+use yanyaengine::Transform;
 
 use crate::{
     server,
@@ -20,6 +21,8 @@ use crate::{
     },
     common::{
         some_or_return,
+        some_or_value,
+        some_or_false,
         write_log,
         insertion_sort_with,
         render_info::*,
@@ -31,37 +34,77 @@ use crate::{
         SpatialGrid,
         SpatialInfo,
         Joint,
+        WindZone,
+        SoundEmitter,
+        Noise,
+        Difficulty,
+        Weather,
         Outlineable,
+        OutlineStyle,
         LazyMix,
         DataInfos,
         Occluder,
         ClientOccluder,
         Faction,
         Damage,
+        reputation::TERRITORY_RADIUS,
         EntityPasser,
         Inventory,
+        Equipment,
+        Item,
+        Haggler,
+        BarterResponse,
+        Loot,
         Anatomy,
         CharactersInfo,
         Character,
         Player,
         Enemy,
+        Corpse,
+        Dialogue,
+        Container,
+        Unlock,
+        container::LOCKPICK_UNLOCK,
+        Needs,
+        Fire,
+        Projectile,
+        Light,
+        light,
+        ParticleEffects,
         Physical,
         ObjectsStore,
+        enemy::{FLEE_DISTANCE, HELP_RADIUS, AVOID_DISTANCE, AVOID_LOOKAHEAD},
         Message,
         Saveable,
-        character::PartialCombinedInfo,
+        character::{PartialCombinedInfo, BlockResult},
         world::World
     }
 };
 
 pub use crate::{iterate_components_with, for_each_component};
 
+pub use collider_system::Impact;
+
 pub mod render_system;
 pub mod damaging_system;
 mod physical_system;
 mod collider_system;
+mod wind_system;
+mod door_system;
 mod raycast_system;
 
+#[cfg(test)]
+mod harness;
+
+
+// nothing takes the events on the server so dont let them pile up
+const MAX_EVENTS: usize = 64;
+
+// snapshots compare these instead of keeping copies of every component around
+fn component_hash<T: Serialize>(component: &T) -> u32
+{
+    crc32fast::hash(&bincode::serialize(component).unwrap())
+}
 
 // too many macros, the syntax is horrible, why r they so limiting? wuts up with that?
 
@@ -187,7 +230,7 @@ pub trait ServerToClient<T>
     ) -> T;
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct Entity
 {
     local: bool,
@@ -252,10 +295,18 @@ no_on_set!{
     Parent,
     Transform,
     Enemy,
+    Corpse,
+    Dialogue,
+    Light,
     Player,
+    Needs,
+    Fire,
+    Projectile,
     Collider,
     Physical,
     Joint,
+    WindZone,
+    SoundEmitter,
     Damaging,
     Watchers,
     Occluder,
@@ -278,6 +329,30 @@ impl OnSet<ClientEntities> for Character
 }
 
 no_on_set_for!{ServerEntities, Anatomy}
+no_on_set_for!{ServerEntities, Equipment}
+no_on_set_for!{ServerEntities, Container}
+
+impl OnSet<ClientEntities> for Container
+{
+    fn on_set(previous: Option<Self>, entities: &ClientEntities, entity: Entity)
+    {
+        if let Some(previous) = previous
+        {
+            entities.container_mut(entity).unwrap().with_previous(previous);
+        }
+    }
+}
+
+impl OnSet<ClientEntities> for Equipment
+{
+    fn on_set(_previous: Option<Self>, entities: &ClientEntities, entity: Entity)
+    {
+        if let Some(mut character) = entities.character_mut(entity)
+        {
+            character.equipment_changed();
+        }
+    }
+}
 
 impl OnSet<ClientEntities> for Anatomy
 {
@@ -423,9 +498,30 @@ macro_rules! impl_common_systems
         {
             match message
             {
-                Message::EntityDamage{entity, faction, damage} =>
+                Message::EntityDamage{entity, source, faction, damage} =>
+                {
+                    self.damage_entity_common(entity, source, faction, damage);
+
+                    None
+                },
+                Message::EntityDamaged{entity, damage, ..} =>
+                {
+                    self.apply_damage(entity, damage);
+
+                    None
+                },
+                Message::EntityBlocked{entity, source, parried} =>
                 {
-                    self.damage_entity_common(entity, faction, damage);
+                    if parried
+                    {
+                        if let Some(mut character) = source.and_then(|source| self.character_mut(source))
+                        {
+                            character.stagger();
+                        }
+                    } else if let Some(mut character) = self.character_mut(entity)
+                    {
+                        character.blocked();
+                    }
 
                     None
                 },
@@ -476,34 +572,88 @@ macro_rules! impl_common_systems
             }
         }
 
+        // none if the entity cant be damaged at all
         pub fn damage_entity_common(
             &self,
             entity: Entity,
+            source: Option<Entity>,
             faction: Faction,
             damage: Damage
-        ) -> bool
+        ) -> Option<BlockResult>
+        {
+            let (result, damage) = self.resolve_damage(entity, source, faction, damage)?;
+
+            if let Some(damage) = damage
+            {
+                self.apply_damage(entity, damage);
+            }
+
+            Some(result)
+        }
+
+        // blocks and parries happen here, the damage is none if nothing got through
+        pub fn resolve_damage(
+            &self,
+            entity: Entity,
+            source: Option<Entity>,
+            faction: Faction,
+            mut damage: Damage
+        ) -> Option<(BlockResult, Option<Damage>)>
         {
-            if let Some(other) = self.faction(entity)
+            let other = self.faction(entity)?;
+
+            // anything can hurt itself, like slamming into a wall
+            let self_inflicted = source == Some(entity);
+
+            if (!self_inflicted && !faction.can_damage(&other)) || !self.anatomy_exists(entity)
             {
-                if !faction.aggressive(&other)
+                return None;
+            }
+
+            // dodging gives a few frames of invulnerability
+            if self.character(entity).map(|character| character.is_dodging()).unwrap_or(false)
+            {
+                return None;
+            }
+
+            let result = self.character_mut(entity).map(|mut character|
+            {
+                character.block_damage(&mut damage)
+            }).unwrap_or(BlockResult::Hit);
+
+            if result == BlockResult::Parried
+            {
+                if let Some(mut character) = source.and_then(|source| self.character_mut(source))
                 {
-                    return false;
+                    character.stagger();
                 }
-            } else
-            {
-                return false;
+
+                return Some((result, None));
             }
 
-            if self.anatomy_exists(entity)
+            if let Some(equipment) = self.equipment(entity)
             {
-                damaging_system::damage(self, entity, damage);
+                let scale = equipment.damage_scale(&self.infos().items_info, damage.direction.height);
 
-                Anatomy::on_set(None, self, entity);
+                damage.data = damage.data * scale;
+            }
 
-                return true;
+            let from_enemy = source.map(|source| self.enemy_exists(source)).unwrap_or(false);
+            if from_enemy && self.player_exists(entity)
+            {
+                damage.data = damage.data * self.difficulty.enemy_damage;
             }
 
-            false
+            let absorbed = damage.data.as_flat() <= 0.0;
+
+            Some((result, (!absorbed).then_some(damage)))
+        }
+
+        pub fn apply_damage(&self, entity: Entity, damage: Damage)
+        {
+            damaging_system::damage(self, entity, damage);
+
+            Anatomy::on_set(None, self, entity);
         }
 
         pub fn faction(&self, entity: Entity) -> Option<Faction>
@@ -533,6 +683,15 @@ macro_rules! impl_common_systems
             });
         }
 
+        // only bleeding for now
+        pub fn update_anatomy(&mut self, dt: f32)
+        {
+            for_each_component!(self, anatomy, |_entity, anatomy: &RefCell<Anatomy>|
+            {
+                anatomy.borrow_mut().update(dt);
+            });
+        }
+
         fn for_every_child_inner(
             &self,
             entity: Entity,
@@ -690,7 +849,15 @@ macro_rules! entity_info_common
 
             if self.player.is_none() && self.inventory.is_some() && self.outlineable.is_none()
             {
-                self.outlineable = Some(Outlineable::default());
+                let style = if self.enemy.is_some()
+                {
+                    OutlineStyle::enemy()
+                } else
+                {
+                    OutlineStyle::loot()
+                };
+
+                self.outlineable = Some(Outlineable::new(style));
             }
 
             if self.outlineable.is_some() && self.watchers.is_none()
@@ -746,6 +913,19 @@ macro_rules! common_trait_impl
             Self::exists(self, entity)
         }
 
+        fn colliders(&self) -> Vec<Entity>
+        {
+            iterate_components_with!(self, collider, map, |entity, _collider: &RefCell<Collider>|
+            {
+                entity
+            }).collect()
+        }
+
+        fn weather(&self) -> Weather
+        {
+            Self::weather(self)
+        }
+
         fn lazy_target_ref(&self, entity: Entity) -> Option<Ref<Transform>>
         {
             Self::lazy_transform(self, entity).map(|lazy|
@@ -815,6 +995,14 @@ macro_rules! common_trait_impl
             })
         }
 
+        fn emissive_target(&self, entity: Entity) -> Option<RefMut<Option<Emissive>>>
+        {
+            self.render_mut(entity).map(|render|
+            {
+                RefMut::map(render, |x| &mut x.emissive)
+            })
+        }
+
         fn remove_deferred(&self, entity: Entity)
         {
             self.remove_queue.borrow_mut().push(entity);
@@ -982,6 +1170,8 @@ macro_rules! define_entities_both
 
         pub const COMPONENTS_COUNT: usize = count_components();
 
+        pub const COMPONENT_NAMES: [&str; COMPONENTS_COUNT] = [$(stringify!($name),)+];
+
         #[derive(Clone, Serialize, Deserialize)]
         pub struct EntityInfo<$($component_type=$default_type,)+>
         {
@@ -1038,12 +1228,99 @@ macro_rules! define_entities_both
                     .map(|lazy| lazy.target())
                     .or_else(|| self.transform.as_mut())
             }
+
+            pub fn component_hashes(&self) -> ComponentHashes
+            {
+                let mut hashes = [None; COMPONENTS_COUNT];
+
+                $(hashes[Component::$name as usize] = self.$name.as_ref().map(component_hash);)+
+
+                hashes
+            }
+
+            // only keeps whats different from the base, also gives back the components the base had and this doesnt
+            pub fn changed_since(mut self, base: &ComponentHashes) -> (Self, Vec<usize>)
+            {
+                let hashes = self.component_hashes();
+
+                let mut removed = Vec::new();
+
+                $({
+                    let index = Component::$name as usize;
+
+                    if hashes[index] == base[index]
+                    {
+                        self.$name = None;
+                    } else if hashes[index].is_none()
+                    {
+                        removed.push(index);
+                    }
+                })+
+
+                (self, removed)
+            }
+
+            // undoes changed_since on top of the same base
+            pub fn with_changes(mut self, changed: Self, removed: &[usize]) -> Self
+            {
+                $(if changed.$name.is_some()
+                {
+                    self.$name = changed.$name;
+                } else if removed.contains(&(Component::$name as usize))
+                {
+                    self.$name = None;
+                })+
+
+                self
+            }
+
+            // which component a single component set message changes and what it hashes to now
+            pub fn set_message_hash(message: &Message) -> Option<(Entity, usize, u32)>
+            {
+                #[allow(unreachable_patterns)]
+                match message
+                {
+                    $(Message::$message_name{entity, component} =>
+                    {
+                        Some((*entity, Component::$name as usize, component_hash(&**component)))
+                    },)+
+                    _ => None
+                }
+            }
+
+            pub fn set_message_entity(message: &Message) -> Option<Entity>
+            {
+                #[allow(unreachable_patterns)]
+                match message
+                {
+                    $(Message::$message_name{entity, ..} => Some(*entity),)+
+                    _ => None
+                }
+            }
+
+            // false if its not a single component set message
+            pub fn apply_set_message(&mut self, message: &Message) -> bool
+            {
+                #[allow(unreachable_patterns)]
+                match message
+                {
+                    $(Message::$message_name{component, ..} =>
+                    {
+                        self.$name = Some((**component).clone());
+
+                        true
+                    },)+
+                    _ => false
+                }
+            }
         }
 
         pub type OnComponentChange = Box<dyn FnMut(&mut ClientEntities, Entity)>;
 
         pub type ComponentsIndices = [Option<usize>; COMPONENTS_COUNT];
 
+        pub type ComponentHashes = [Option<u32>; COMPONENTS_COUNT];
+
         #[derive(Debug, Default)]
         struct ChangedEntities
         {
@@ -1102,6 +1379,13 @@ macro_rules! define_entities_both
             create_queue: RefCell<Vec<(Entity, EntityInfo)>>,
             create_render_queue: RefCell<Vec<(Entity, RenderComponent)>>,
             changed_entities: RefCell<ChangedEntities>,
+            noises: RefCell<Vec<Noise>>,
+            // killer and killed
+            kills: RefCell<Vec<(Entity, Entity)>>,
+            difficulty: Difficulty,
+            weather: Weather,
+            // where colliders were as of the last update, raycasts and the broadphase use it
+            space: RefCell<SpatialGrid>,
             $($on_name: Rc<RefCell<Vec<OnComponentChange>>>,)+
             $(pub $name: ObjectsStore<ComponentWrapper<$component_type>>,)+
         }
@@ -1123,6 +1407,11 @@ macro_rules! define_entities_both
                     create_queue: RefCell::new(Vec::new()),
                     create_render_queue: RefCell::new(Vec::new()),
                     changed_entities: RefCell::new(Default::default()),
+                    noises: RefCell::new(Vec::new()),
+                    kills: RefCell::new(Vec::new()),
+                    difficulty: Difficulty::default(),
+                    weather: Weather::default(),
+                    space: RefCell::new(SpatialGrid::new()),
                     $($on_name: Rc::new(RefCell::new(Vec::new())),)+
                     $($name: ObjectsStore::new(),)+
                 }
@@ -1133,6 +1422,26 @@ macro_rules! define_entities_both
                 components!(self, entity).borrow().get(entity.id).is_some()
             }
 
+            pub fn difficulty(&self) -> Difficulty
+            {
+                self.difficulty
+            }
+
+            pub fn set_difficulty(&mut self, difficulty: Difficulty)
+            {
+                self.difficulty = difficulty;
+            }
+
+            pub fn weather(&self) -> Weather
+            {
+                self.weather
+            }
+
+            pub fn set_weather(&mut self, weather: Weather)
+            {
+                self.weather = weather;
+            }
+
             pub fn for_each_entity(
                 &self,
                 mut f: impl FnMut(Entity)
@@ -1602,7 +1911,7 @@ macro_rules! define_entities_both
                 self.push_inner(true, info)
             }
 
-            pub fn build_space(&self, space: &mut SpatialGrid)
+            pub fn build_space(&self)
             {
                 let infos = iterate_components_with!(self, collider, map, |entity, collider: &RefCell<Collider>|
                 {
@@ -1628,7 +1937,7 @@ macro_rules! define_entities_both
                     }
                 });
 
-                space.build(infos);
+                self.space.borrow_mut().build(infos);
             }
 
             impl_common_systems!{ClientEntityInfo, $(($name, $set_func, $component_type),)+}
@@ -1658,16 +1967,6 @@ macro_rules! define_entities_both
                 )+
             }
 
-            pub fn raycast(
-                &self,
-                info: RaycastInfo,
-                start: &Vector3<f32>,
-                end: &Vector3<f32>
-            ) -> RaycastHits
-            {
-                raycast_system::raycast(self, info, start, end)
-            }
-
             pub fn create_render_queued(&mut self, create_info: &mut RenderCreateInfo)
             {
                 let render_queue = {
@@ -1782,13 +2081,14 @@ macro_rules! define_entities_both
                 });
             }
 
-            pub fn update_damaging(
-                &mut self,
-                passer: &mut impl EntityPasser,
-                blood_texture: TextureId
-            )
+            pub fn update_damaging(&mut self, passer: &mut impl EntityPasser)
             {
-                damaging_system::update(self, passer, blood_texture);
+                damaging_system::update(self, passer);
+            }
+
+            pub fn update_impacts(&mut self, passer: &mut impl EntityPasser, impacts: Vec<Impact>)
+            {
+                damaging_system::apply_impacts(self, passer, impacts);
             }
 
             pub fn update_children(&mut self)
@@ -1834,107 +2134,321 @@ macro_rules! define_entities_both
                     true
                 };
 
+                // containers open even when theres nothing left in them
+                let container = self.container(entity).map(|container| !container.locked);
+                if let Some(unlocked) = container
+                {
+                    return unlocked;
+                }
+
                 !is_player && has_inventory && maybe_anatomy
             }
 
-            pub fn within_interactable_distance(&self, a: Entity, b: Entity) -> bool
+            // how the entity could get a locked container open, none if it isnt locked or it cant
+            pub fn container_unlock(&self, entity: Entity, container: Entity) -> Option<Unlock>
             {
-                let interactable_distance = 0.3;
+                let pried_with = {
+                    let container = self.container(container)?;
 
-                let a = if let Some(x) = self.transform(a)
-                {
-                    x.position
-                } else
-                {
-                    return false;
+                    if !container.locked
+                    {
+                        return None;
+                    }
+
+                    self.infos().containers_info.get(&container.name)?.pried_with.clone()
                 };
 
-                let b = if let Some(x) = self.transform(b)
+                let can_pry = self.character(entity).and_then(|character| character.holding()).and_then(|held|
                 {
-                    x.position
-                } else
+                    let id = self.inventory(entity)?.get(held)?.id;
+
+                    Some(pried_with.contains(&self.infos().items_info.get(id).name))
+                }).unwrap_or(false);
+
+                if can_pry
                 {
-                    return false;
-                };
+                    return Some(Unlock::Pry);
+                }
 
-                a.metric_distance(&b) <= interactable_distance
+                let can_pick = self.player(entity).map(|player| player.has_unlock(LOCKPICK_UNLOCK)).unwrap_or(false);
+
+                can_pick.then_some(Unlock::Pick)
             }
 
-            pub fn update_mouse_highlight(&mut self, player: Entity, mouse: Entity)
+            pub fn make_noise(
+                &self,
+                passer: &mut impl EntityPasser,
+                noise: Noise
+            )
             {
-                let mouse_collider = self.collider(mouse).unwrap();
-                let mouse_collided = mouse_collider.collided().first().copied();
-
-                let mouse_collided = some_or_return!(mouse_collided);
+                let position = noise.position;
+                let radius = noise.radius * self.weather.hearing_scale();
 
-                if !self.within_interactable_distance(player, mouse_collided)
-                {
-                    return;
-                }
-
-                for_each_component!(self, outlineable, |entity, outlineable: &RefCell<Outlineable>|
+                for_each_component!(self, enemy, |entity, enemy: &RefCell<Enemy>|
                 {
-                    let overlapping = mouse_collided == entity;
+                    let hearing = self.anatomy(entity).and_then(|x| x.hearing()).unwrap_or(0.0);
 
-                    if !overlapping || !self.is_lootable(entity)
+                    let heard = self.transform(entity).map(|transform|
                     {
-                        return;
-                    }
+                        transform.position.metric_distance(&position) < radius * hearing
+                    }).unwrap_or(false);
 
-                    if let Some(mut watchers) = self.watchers_mut(entity)
+                    if heard && enemy.borrow_mut().hear_noise(position)
                     {
-                        outlineable.borrow_mut().enable();
-
-                        let kind = WatcherType::Lifetime(0.1.into());
-                        if let Some(found) = watchers.find(|watcher|
-                        {
-                            // comparison considered harmful
-                            if let WatcherAction::OutlineableDisable = watcher.action
-                            {
-                                true
-                            } else
-                            {
-                                false
-                            }
-                        })
-                        {
-                            found.kind = kind;
-                        } else
-                        {
-                            watchers.push(Watcher{
-                                kind,
-                                action: WatcherAction::OutlineableDisable,
-                                ..Default::default()
-                            });
-                        }
+                        passer.send_message(Message::SetEnemy{
+                            entity,
+                            component: enemy.borrow().clone().into()
+                        });
                     }
                 });
+
+                self.push_noise(noise);
             }
 
-            pub fn update_outlineable(&mut self, dt: f32)
+            // doesnt alert anyone, only shows up in the sound cues
+            pub fn push_noise(&self, noise: Noise)
             {
-                for_each_component!(self, outlineable, |_entity, outlineable: &RefCell<Outlineable>|
+                let mut noises = self.noises.borrow_mut();
+
+                if noises.len() >= MAX_EVENTS
                 {
-                    outlineable.borrow_mut().update(dt);
-                });
+                    noises.remove(0);
+                }
+
+                noises.push(noise);
             }
 
-            pub fn update_colliders(
-                &mut self,
-                world: &World,
-                space: &SpatialGrid,
-                dt: f32
-            )
+            pub fn take_noises(&self) -> Vec<Noise>
             {
-                collider_system::update(self, world, space, dt);
+                mem::take(&mut *self.noises.borrow_mut())
             }
 
-            pub fn update_lazy_one(
-                &self,
-                entity: Entity,
-                mut lazy: RefMut<LazyTransform>,
-                dt: f32
-            )
+            pub fn push_kill(&self, killer: Entity, killed: Entity)
+            {
+                let mut kills = self.kills.borrow_mut();
+
+                if kills.len() >= MAX_EVENTS
+                {
+                    kills.remove(0);
+                }
+
+                kills.push((killer, killed));
+            }
+
+            // everything killed since the last time
+            pub fn take_kills(&self) -> Vec<(Entity, Entity)>
+            {
+                mem::take(&mut *self.kills.borrow_mut())
+            }
+
+            pub fn is_companion_of(&self, entity: Entity, leader: Entity) -> bool
+            {
+                self.enemy(entity).map(|enemy|
+                {
+                    enemy.companion().and_then(|companion| companion.leader()) == Some(leader)
+                }).unwrap_or(false)
+            }
+
+            fn player_named(&self, name: &str) -> Option<Entity>
+            {
+                self.player.iter().map(|(_, x)| x.entity).find(|entity|
+                {
+                    self.named(*entity).map(|x| *x == name).unwrap_or(false)
+                })
+            }
+
+            // 0 is pitch black, 1 is as bright as it gets
+            pub fn light_level(&self, world: &World, position: Vector3<f32>) -> f32
+            {
+                let lights = self.light.iter().map(|(_, x)| x).filter_map(|x|
+                {
+                    let light_position = self.transform(x.entity)?.position;
+
+                    Some((light_position, x.component.borrow().radius))
+                });
+
+                light::light_level(world, lights, position)
+            }
+
+            // how easy this is to spot, 1 is standing around in full light
+            pub fn visibility(&self, world: &World, entity: Entity) -> f32
+            {
+                let position = some_or_value!(self.transform(entity), 0.0).position;
+
+                let scale = self.character(entity).map(|x| x.visibility_scale()).unwrap_or(1.0);
+
+                self.light_level(world, position) * scale
+            }
+
+            // alive hostiles the entity can see, closest first
+            pub fn lock_targets(&self, entity: Entity) -> Vec<Entity>
+            {
+                let anatomy = some_or_value!(self.anatomy(entity), Vec::new());
+                let position = some_or_value!(self.transform(entity), Vec::new()).position;
+
+                let mut targets: Vec<(f32, Entity)> = iterate_components_with!(self, character, filter_map, |other, _|
+                {
+                    if other == entity || !self.is_hostile(entity, other)
+                    {
+                        return None;
+                    }
+
+                    let alive = self.anatomy(other).map(|x| x.speed().is_some()).unwrap_or(false);
+                    if !alive
+                    {
+                        return None;
+                    }
+
+                    let other_position = self.transform(other)?.position;
+
+                    anatomy.sees(&position, &other_position, self.weather.sight_scale()).then(||
+                    {
+                        (position.metric_distance(&other_position), other)
+                    })
+                }).collect();
+
+                targets.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+
+                targets.into_iter().map(|(_, other)| other).collect()
+            }
+
+            pub fn within_interactable_distance(&self, a: Entity, b: Entity) -> bool
+            {
+                let interactable_distance = 0.3;
+
+                let a = if let Some(x) = self.transform(a)
+                {
+                    x.position
+                } else
+                {
+                    return false;
+                };
+
+                let b = if let Some(x) = self.transform(b)
+                {
+                    x.position
+                } else
+                {
+                    return false;
+                };
+
+                a.metric_distance(&b) <= interactable_distance
+            }
+
+            pub fn update_mouse_highlight(&mut self, player: Entity, mouse: Entity)
+            {
+                let mouse_collider = self.collider(mouse).unwrap();
+                let mouse_collided = mouse_collider.collided().first().copied();
+
+                let mouse_collided = some_or_return!(mouse_collided);
+
+                if !self.within_interactable_distance(player, mouse_collided)
+                {
+                    return;
+                }
+
+                for_each_component!(self, outlineable, |entity, outlineable: &RefCell<Outlineable>|
+                {
+                    let overlapping = mouse_collided == entity;
+
+                    if !overlapping || !self.is_lootable(entity)
+                    {
+                        return;
+                    }
+
+                    if let Some(mut watchers) = self.watchers_mut(entity)
+                    {
+                        outlineable.borrow_mut().enable();
+
+                        let kind = WatcherType::Lifetime(0.1.into());
+                        if let Some(found) = watchers.find(|watcher|
+                        {
+                            // comparison considered harmful
+                            if let WatcherAction::OutlineableDisable = watcher.action
+                            {
+                                true
+                            } else
+                            {
+                                false
+                            }
+                        })
+                        {
+                            found.kind = kind;
+                        } else
+                        {
+                            watchers.push(Watcher{
+                                kind,
+                                action: WatcherAction::OutlineableDisable,
+                                ..Default::default()
+                            });
+                        }
+                    }
+                });
+            }
+
+            pub fn update_containers(&mut self, dt: f32)
+            {
+                for_each_component!(self, container, |entity, container: &RefCell<Container>|
+                {
+                    let mut container = container.borrow_mut();
+
+                    if !container.is_bouncing()
+                    {
+                        return;
+                    }
+
+                    container.update(dt);
+
+                    if let Some(mut transform) = self.transform_mut(entity)
+                    {
+                        transform.stretch = container.stretch();
+                    }
+                });
+            }
+
+            pub fn update_outlineable(&mut self, dt: f32)
+            {
+                for_each_component!(self, outlineable, |_entity, outlineable: &RefCell<Outlineable>|
+                {
+                    outlineable.borrow_mut().update(dt);
+                });
+            }
+
+            pub fn update_colliders(
+                &mut self,
+                world: &World,
+                dt: f32
+            ) -> Vec<Impact>
+            {
+                collider_system::update(self, world, dt)
+            }
+
+            pub fn update_wind(&mut self, dt: f32)
+            {
+                wind_system::update(self, dt);
+            }
+
+            pub fn update_doors(
+                &self,
+                passer: &mut impl EntityPasser,
+                doors: &mut HashMap<Entity, bool>,
+                make_noise: bool
+            )
+            {
+                door_system::update(self, passer, doors, make_noise);
+            }
+
+            pub fn reset_colliders(&mut self)
+            {
+                collider_system::reset_frame(self);
+            }
+
+            pub fn update_lazy_one(
+                &self,
+                entity: Entity,
+                mut lazy: RefMut<LazyTransform>,
+                dt: f32
+            )
             {
                 if let Some(mut transform) = self.transform_mut(entity)
                 {
@@ -1974,7 +2488,109 @@ macro_rules! define_entities_both
                 });
             }
 
-            pub fn update_enemy(&mut self, passer: &mut impl EntityPasser, dt: f32)
+            // the server decides when corpses get removed, this only ages them for visuals
+            pub fn update_corpses(&mut self, dt: f32)
+            {
+                for_each_component!(self, corpse, |entity, corpse: &RefCell<Corpse>|
+                {
+                    let mut corpse = corpse.borrow_mut();
+                    corpse.update(dt);
+
+                    if let Some(mut render) = self.render_mut(entity)
+                    {
+                        if let Some(mix) = corpse.mix()
+                        {
+                            render.mix = Some(mix);
+                        }
+
+                        render.dissolve = corpse.dissolved();
+                    }
+                });
+            }
+
+            pub fn update_fires(&mut self, particle_effects: &ParticleEffects, dt: f32)
+            {
+                let mut flaring = Vec::new();
+                for_each_component!(self, fire, |entity, fire: &RefCell<Fire>|
+                {
+                    if fire.borrow_mut().flare(dt)
+                    {
+                        flaring.push(entity);
+                    }
+                });
+
+                flaring.into_iter().for_each(|entity|
+                {
+                    let info = particle_effects.get("fire").explode(true, Vector3::z_axis());
+
+                    WatcherAction::Explode(Box::new(info)).execute(self, entity);
+                });
+            }
+
+            // separation from nearby characters plus a sidestep around furniture in front
+            fn avoidance_force(&self, entity: Entity) -> Vector3<f32>
+            {
+                let position = some_or_value!(self.transform(entity), Vector3::zeros()).position;
+
+                let separation: Vector3<f32> = self.character.iter()
+                    .map(|(_, x)| x.entity)
+                    .filter(|other| *other != entity)
+                    .filter_map(|other|
+                    {
+                        let mut offset = position - self.transform(other)?.position;
+                        offset.z = 0.0;
+
+                        let distance = offset.magnitude();
+
+                        (distance > 0.0 && distance < AVOID_DISTANCE).then(||
+                        {
+                            (offset / distance) * (1.0 - distance / AVOID_DISTANCE)
+                        })
+                    })
+                    .sum();
+
+                let rotation = some_or_value!(self.character(entity), separation).rotation;
+                let forward = Vector3::new(rotation.cos(), rotation.sin(), 0.0);
+
+                let info = RaycastInfo{
+                    pierce: None,
+                    layer: ColliderLayer::NORMAL,
+                    ignore_entity: Some(entity),
+                    ignore_end: true
+                };
+
+                let hits = self.raycast(info, &position, &(position + forward * AVOID_LOOKAHEAD));
+
+                let obstacle = hits.hits.iter().find_map(|hit|
+                {
+                    if let RaycastHitId::Entity(other) = hit.id
+                    {
+                        (!self.character_exists(other)).then_some(other)
+                    } else
+                    {
+                        None
+                    }
+                });
+
+                let sidestep = obstacle.and_then(|obstacle|
+                {
+                    let offset = self.transform(obstacle)?.position - position;
+
+                    // go to whichever side the obstacle isnt on
+                    let side = Vector3::new(-forward.y, forward.x, 0.0);
+                    if side.dot(&offset) > 0.0
+                    {
+                        Some(-side)
+                    } else
+                    {
+                        Some(side)
+                    }
+                }).unwrap_or_else(Vector3::zeros);
+
+                separation + sidestep
+            }
+
+            pub fn update_enemy(&mut self, passer: &mut impl EntityPasser, world: &World, dt: f32)
             {
                 let mut on_state_change = |entity|
                 {
@@ -1994,16 +2610,31 @@ macro_rules! define_entities_both
 
                 for_each_component!(self, enemy, |entity, enemy: &RefCell<Enemy>|
                 {
+                    if let Some(companion) = enemy.borrow_mut().companion_mut()
+                    {
+                        let leader_valid = companion.leader().map(|leader|
+                        {
+                            self.player_exists(leader) && self.named(leader).map(|name|
+                            {
+                                *name == companion.leader_name()
+                            }).unwrap_or(false)
+                        }).unwrap_or(false);
+
+                        if !leader_valid
+                        {
+                            companion.set_leader(self.player_named(companion.leader_name()));
+                        }
+                    }
+
                     if enemy.borrow().check_hostiles()
                     {
-                        let character = self.character_mut(entity).unwrap();
-                        self.character.iter()
+                        // whoever stands out the most
+                        let spotted = self.character.iter()
                             .map(|(_, x)| x)
                             .filter(|x| x.entity != entity)
                             .filter(|x|
                             {
-                                let other_character = x.get();
-                                character.aggressive(&other_character)
+                                self.is_hostile(entity, x.entity)
                             })
                             .filter(|x|
                             {
@@ -2014,21 +2645,69 @@ macro_rules! define_entities_both
                                 let transform = self.transform(entity).unwrap();
                                 let other_transform = self.transform(other_entity).unwrap();
 
-                                anatomy.sees(&transform.position, &other_transform.position)
+                                anatomy.sees(
+                                    &transform.position,
+                                    &other_transform.position,
+                                    self.weather.sight_scale()
+                                )
                             })
-                            .for_each(|&ComponentWrapper{
-                                entity: other_entity,
-                                ..
-                            }|
+                            .map(|x| (x.entity, self.visibility(world, x.entity)))
+                            .max_by(|(_, a), (_, b)| a.total_cmp(b));
+
+                        if let Some((other_entity, visibility)) = spotted
+                        {
+                            let sure = enemy.borrow_mut().spot(visibility);
+                            if sure
                             {
                                 enemy.borrow_mut().set_attacking(other_entity);
                                 on_state_change(entity);
-                            });
+                            }
+                        } else
+                        {
+                            enemy.borrow_mut().lose_sight();
+                        }
+                    }
+
+                    if enemy.borrow().check_threats()
+                    {
+                        let faction = self.faction(entity);
+
+                        let threat = self.character.iter()
+                            .map(|(_, x)| x)
+                            .filter(|x| x.entity != entity)
+                            .filter(|x| Some(x.component.borrow().faction) != faction)
+                            .find(|x|
+                            {
+                                let anatomy = self.anatomy(entity).unwrap();
+
+                                let transform = self.transform(entity).unwrap();
+                                let other_transform = self.transform(x.entity).unwrap();
+
+                                let close = transform.position.metric_distance(&other_transform.position)
+                                    < FLEE_DISTANCE;
+
+                                close && anatomy.sees(
+                                    &transform.position,
+                                    &other_transform.position,
+                                    self.weather.sight_scale()
+                                )
+                            })
+                            .map(|x| x.entity);
+
+                        if let Some(threat) = threat
+                        {
+                            enemy.borrow_mut().set_fleeing(threat);
+                            on_state_change(entity);
+                        }
                     }
 
+                    let avoidance = self.avoidance_force(entity);
+
                     let state_changed = enemy.borrow_mut().update(
                         self,
+                        world,
                         entity,
+                        avoidance,
                         dt
                     );
 
@@ -2036,6 +2715,44 @@ macro_rules! define_entities_both
                     {
                         on_state_change(entity);
                     }
+
+                    let help_call = enemy.borrow_mut().take_help_call();
+                    if let Some(target) = help_call
+                    {
+                        let position = some_or_return!(self.transform(entity)).position;
+                        let faction = some_or_return!(self.faction(entity));
+                        let factions_info = &self.infos().factions_info;
+
+                        let helpers: Vec<Entity> = self.enemy.iter()
+                            .map(|(_, x)| x.entity)
+                            .filter(|other| *other != entity && *other != target)
+                            .filter(|other|
+                            {
+                                self.faction(*other).map(|other| factions_info.allied(faction, other))
+                                    .unwrap_or(false)
+                            })
+                            .filter(|other|
+                            {
+                                self.transform(*other).map(|transform|
+                                {
+                                    transform.position.metric_distance(&position) < HELP_RADIUS
+                                }).unwrap_or(false)
+                            })
+                            .collect();
+
+                        helpers.into_iter().for_each(|other|
+                        {
+                            let mut other_enemy = some_or_return!(self.enemy_mut(other));
+
+                            if !other_enemy.is_attacking() && other_enemy.companion().is_none()
+                            {
+                                other_enemy.set_attacking(target);
+                                drop(other_enemy);
+
+                                on_state_change(other);
+                            }
+                        });
+                    }
                 });
             }
 
@@ -2119,6 +2836,164 @@ macro_rules! define_entities_both
                 });
             }
 
+            // dead non players turn into corpses which get removed after a while
+            pub fn update_corpses(
+                &mut self,
+                writer: &mut server::ConnectionsHandler,
+                duration: f32,
+                dt: f32
+            )
+            {
+                let mut died = Vec::new();
+                for_each_component!(self, character, |entity, _|
+                {
+                    let dead = self.anatomy(entity).map(|anatomy| anatomy.speed().is_none()).unwrap_or(false);
+
+                    if dead && !self.corpse_exists(entity) && !self.player_exists(entity)
+                    {
+                        died.push(entity);
+                    }
+                });
+
+                died.into_iter().for_each(|entity|
+                {
+                    let dissolve = self.enemy(entity).map(|enemy| enemy.death().dissolve).unwrap_or_default();
+
+                    let corpse = Corpse::new(duration, dissolve);
+
+                    self.set_corpse(entity, Some(corpse.clone()));
+                    writer.send_message(Message::SetCorpse{entity, component: Box::new(corpse)});
+                });
+
+                let mut expired = Vec::new();
+                for_each_component!(self, corpse, |entity, corpse: &RefCell<Corpse>|
+                {
+                    let mut corpse = corpse.borrow_mut();
+                    corpse.update(dt);
+
+                    if corpse.expired()
+                    {
+                        expired.push(entity);
+                    }
+                });
+
+                expired.into_iter().for_each(|entity|
+                {
+                    let message = self.remove_message(entity);
+                    writer.send_message(message);
+                });
+            }
+
+            // None if the other side isnt a vendor
+            pub fn barter_offer(
+                &self,
+                writer: &mut server::ConnectionsHandler,
+                player: Entity,
+                vendor: Entity,
+                given: &[Item],
+                taken: &[Item]
+            ) -> Option<BarterResponse>
+            {
+                let items_info = &self.infos().items_info;
+
+                let faction = self.faction(vendor)?;
+                let haggler = Haggler::new(&*self.player(player)?, faction);
+
+                let mut enemy = self.enemy_mut(vendor)?;
+                let vendor_component = enemy.vendor_mut()?;
+
+                let infos = |items: &[Item]|
+                {
+                    items.iter().map(|item| items_info.get(item.id)).collect::<Vec<_>>()
+                };
+
+                let offered = vendor_component.offered_value(infos(given).into_iter());
+                let asked = vendor_component.asking_price(&haggler, infos(taken).into_iter());
+
+                let response = vendor_component.respond(&haggler, offered, asked);
+
+                writer.send_message(Message::SetEnemy{
+                    entity: vendor,
+                    component: enemy.clone().into()
+                });
+
+                Some(response)
+            }
+
+            // tops vendors back up to the most loot they could spawn with
+            pub fn restock_vendors(&mut self, writer: &mut server::ConnectionsHandler)
+            {
+                let mut due = Vec::new();
+                for_each_component!(self, enemy, |entity, enemy: &RefCell<Enemy>|
+                {
+                    let alive = self.anatomy(entity).map(|anatomy| anatomy.speed().is_some()).unwrap_or(false);
+
+                    if alive && enemy.borrow().vendor().map(|vendor| vendor.restock_due()).unwrap_or(false)
+                    {
+                        due.push(entity);
+                    }
+                });
+
+                due.into_iter().for_each(|entity|
+                {
+                    let infos = self.infos().clone();
+
+                    let enemy = {
+                        let mut enemy = some_or_return!(self.enemy_mut(entity));
+                        some_or_return!(enemy.vendor_mut()).restocked();
+
+                        enemy.clone()
+                    };
+
+                    let info = enemy.info(&infos.enemies_info);
+
+                    let inventory = {
+                        let mut inventory = some_or_return!(self.inventory_mut(entity));
+
+                        let missing = info.loot.amount.1.saturating_sub(inventory.items().len());
+
+                        let mut loot = Loot::new(
+                            &infos.items_info,
+                            info.loot.groups.iter().map(String::as_str).collect(),
+                            info.loot_commonness * 0.6
+                        );
+
+                        loot.create_random(&mut inventory, missing..(missing + 1));
+
+                        inventory.clone()
+                    };
+
+                    writer.send_message(Message::SetInventory{entity, component: inventory.into()});
+                    writer.send_message(Message::SetEnemy{entity, component: enemy.into()});
+                });
+            }
+
+            pub fn update_needs(&mut self, dt: f32)
+            {
+                let rate = self.difficulty.hunger_rate;
+
+                for_each_component!(self, needs, |entity, needs: &RefCell<Needs>|
+                {
+                    let alive = self.anatomy(entity).map(|anatomy| anatomy.speed().is_some()).unwrap_or(false);
+
+                    if alive
+                    {
+                        needs.borrow_mut().update(rate, dt);
+                    }
+                });
+            }
+
+            // needs change slowly so the owners only get told every once in a while
+            pub fn sync_needs(&self, writer: &mut server::ConnectionsHandler)
+            {
+                for_each_component!(self, needs, |entity, needs: &RefCell<Needs>|
+                {
+                    let component = needs.borrow().clone();
+
+                    writer.send_message_with_owner(Message::SetNeeds{entity, component: component.into()});
+                });
+            }
+
             pub fn update_sprites(
                 &mut self,
                 characters_info: &CharactersInfo
@@ -2217,6 +3092,11 @@ macro_rules! define_entities
         {
             common_trait_impl!{$(($name, $mut_func, $default_type),)+}
 
+            fn raycast(&self, info: RaycastInfo, start: &Vector3<f32>, end: &Vector3<f32>) -> RaycastHits
+            {
+                raycast_system::raycast_client(self, info, start, end)
+            }
+
             fn push_eager(
                 &mut self,
                 local: bool,
@@ -2252,6 +3132,12 @@ macro_rules! define_entities
         {
             common_trait_impl!{$(($name, $mut_func, $default_type),)+}
 
+            fn raycast(&self, info: RaycastInfo, start: &Vector3<f32>, end: &Vector3<f32>) -> RaycastHits
+            {
+                // the server doesnt keep a space around so it just checks everything
+                raycast_system::raycast(self, self.colliders(), info, start, end)
+            }
+
             fn push_eager(&mut self, local: bool, info: EntityInfo) -> Entity
             {
                 Self::push_inner(self, local, info)
@@ -2287,9 +3173,16 @@ macro_rules! define_entities
             fn is_visible(&self, entity: Entity) -> bool;
             fn visible_target(&self, entity: Entity) -> Option<RefMut<bool>>;
             fn mix_color_target(&self, entity: Entity) -> Option<RefMut<Option<MixColor>>>;
+            fn emissive_target(&self, entity: Entity) -> Option<RefMut<Option<Emissive>>>;
 
             fn exists(&self, entity: Entity) -> bool;
 
+            fn colliders(&self) -> Vec<Entity>;
+
+            fn raycast(&self, info: RaycastInfo, start: &Vector3<f32>, end: &Vector3<f32>) -> RaycastHits;
+
+            fn weather(&self) -> Weather;
+
             fn remove_deferred(&self, entity: Entity);
             fn remove(&mut self, entity: Entity);
 
@@ -2329,6 +3222,40 @@ macro_rules! define_entities
                 })
             }
 
+            fn is_hostile(&self, entity: Entity, other: Entity) -> bool
+            {
+                let faction = some_or_false!(self.character(entity)).faction;
+                let other_faction = some_or_false!(self.character(other)).faction;
+
+                let bad_reputation = |player: Entity, faction: Faction|
+                {
+                    self.player(player).map(|player|
+                    {
+                        player.reputation.hostile(faction)
+                    }).unwrap_or(false)
+                };
+
+                // unwelcome players get attacked when they come close to where a faction lives
+                let trespassing = |guard: Entity, player: Entity, faction: Faction|
+                {
+                    if some_or_false!(self.player(player)).reputation.welcome(faction)
+                    {
+                        return false;
+                    }
+
+                    let home = some_or_false!(self.enemy(guard).and_then(|enemy| enemy.home()));
+                    let position = some_or_false!(self.transform(player)).position;
+
+                    home.metric_distance(&position) < TERRITORY_RADIUS
+                };
+
+                self.infos().factions_info.aggressive(faction, other_faction)
+                    || bad_reputation(other, faction)
+                    || bad_reputation(entity, other_faction)
+                    || trespassing(entity, other, faction)
+                    || trespassing(other, entity, other_faction)
+            }
+
             fn check_guarantees(&mut self);
         }
 
@@ -2463,6 +3390,37 @@ macro_rules! define_entities
                     x => Some(x)
                 }
             }
+
+            // same as handle_message but skips anything that needs to be rendered, so tests dont need a window
+            #[cfg(test)]
+            pub fn handle_message_headless(&mut self, message: Message) -> Option<Message>
+            {
+                let message = self.handle_message_common(message)?;
+
+                #[allow(unreachable_patterns)]
+                match message
+                {
+                    Message::EntitySet{entity, info} =>
+                    {
+                        $(
+                            if info.$name.is_some()
+                            {
+                                self.$set_func(entity, info.$name);
+                            }
+                        )+
+
+                        None
+                    },
+                    $(Message::$side_message_name{..} => None,)+
+                    $(Message::$message_name{entity, component} =>
+                    {
+                        self.$set_func(entity, Some(*component));
+
+                        None
+                    },)+
+                    x => Some(x)
+                }
+            }
         }
     }
 }
@@ -2484,14 +3442,24 @@ define_entities!{
     (watchers, watchers_mut, set_watchers, on_watchers, resort_watchers, watchers_exists, SetWatchers, WatchersType, Watchers),
     (damaging, damaging_mut, set_damaging, on_damaging, resort_damaging, damaging_exists, SetDamaging, DamagingType, Damaging),
     (inventory, inventory_mut, set_inventory, on_inventory, resort_inventory, inventory_exists, SetInventory, InventoryType, Inventory),
+    (equipment, equipment_mut, set_equipment, on_equipment, resort_equipment, equipment_exists, SetEquipment, EquipmentType, Equipment),
     (named, named_mut, set_named, on_named, resort_named, named_exists, SetNamed, NamedType, String),
     (transform, transform_mut, set_transform, on_transform, resort_transform, transform_exists, SetTransform, TransformType, Transform),
     (character, character_mut, set_character, on_character, resort_character, character_exists, SetCharacter, CharacterType, Character),
     (enemy, enemy_mut, set_enemy, on_enemy, resort_enemy, enemy_exists, SetEnemy, EnemyType, Enemy),
+    (corpse, corpse_mut, set_corpse, on_corpse, resort_corpse, corpse_exists, SetCorpse, CorpseType, Corpse),
+    (dialogue, dialogue_mut, set_dialogue, on_dialogue, resort_dialogue, dialogue_exists, SetDialogue, DialogueType, Dialogue),
+    (container, container_mut, set_container, on_container, resort_container, container_exists, SetContainer, ContainerType, Container),
+    (light, light_mut, set_light, on_light, resort_light, light_exists, SetLight, LightType, Light),
     (player, player_mut, set_player, on_player, resort_player, player_exists, SetPlayer, PlayerType, Player),
+    (needs, needs_mut, set_needs, on_needs, resort_needs, needs_exists, SetNeeds, NeedsType, Needs),
+    (fire, fire_mut, set_fire, on_fire, resort_fire, fire_exists, SetFire, FireType, Fire),
+    (projectile, projectile_mut, set_projectile, on_projectile, resort_projectile, projectile_exists, SetProjectile, ProjectileType, Projectile),
     (collider, collider_mut, set_collider, on_collider, resort_collider, collider_exists, SetCollider, ColliderType, Collider),
     (physical, physical_mut, set_physical, on_physical, resort_physical, physical_exists, SetPhysical, PhysicalType, Physical),
     (anatomy, anatomy_mut, set_anatomy, on_anatomy, resort_anatomy, anatomy_exists, SetAnatomy, AnatomyType, Anatomy),
     (joint, joint_mut, set_joint, on_joint, resort_joint, joint_exists, SetJoint, JointType, Joint),
+    (wind, wind_mut, set_wind, on_wind, resort_wind, wind_exists, SetWind, WindType, WindZone),
+    (sound, sound_mut, set_sound, on_sound, resort_sound, sound_exists, SetSound, SoundType, SoundEmitter),
     (saveable, saveable_mut, set_saveable, on_saveable, resort_saveable, saveable_exists, SetNone, SaveableType, Saveable)
 }
@@ -1,4 +1,7 @@
-use std::cell::RefCell;
+use std::{
+    cell::RefCell,
+    collections::HashSet
+};
 
 use yanyaengine::Transform;
 
@@ -7,13 +10,17 @@ use nalgebra::Vector3;
 use crate::{
     debug_config::*,
     common::{
-        unique_pairs_no_self,
+        some_or_return,
+        line_info,
+        project_onto,
         collider::*,
         render_info::*,
         watcher::*,
         ENTITY_SCALE,
         Entity,
         SpatialGrid,
+        CollisionLayers,
+        Physical,
         Joint,
         EntityInfo,
         AnyEntities,
@@ -26,45 +33,20 @@ use crate::{
 };
 
 use resolver::ContactResolver;
+use islands::Islands;
+use narrowphase::{Body, BodyPair};
+
+pub use impacts::Impact;
 
 mod resolver;
+mod islands;
+mod narrowphase;
+mod impacts;
 
 
-pub fn update(
-    entities: &mut ClientEntities,
-    world: &World,
-    space: &SpatialGrid,
-    dt: f32
-)
+// collisions from all the physics steps in a frame get kept until the next frame
+pub fn reset_frame(entities: &mut ClientEntities)
 {
-    macro_rules! colliding_info
-    {
-        ($result_variable:expr, $entity:expr) =>
-        {
-            let mut collider = entities.collider_mut($entity).unwrap();
-            {
-                let mut transform = entities.transform($entity).unwrap().clone();
-
-                let kind = collider.kind;
-                if kind == ColliderType::Aabb
-                {
-                    transform.rotation = 0.0;
-                }
-
-                if let Some(scale) = collider.scale
-                {
-                    transform.scale = scale;
-                }
-
-                $result_variable = CollidingInfo{
-                    entity: Some($entity),
-                    transform,
-                    collider: &mut collider
-                };
-            }
-        }
-    }
-
     for_each_component!(entities, collider, |entity, collider: &RefCell<Collider>|
     {
         if DebugConfig::is_enabled(DebugTool::CollisionBounds)
@@ -104,24 +86,185 @@ pub fn update(
 
         collider.borrow_mut().reset_frame();
     });
+}
 
-    let mut contacts = Vec::new();
+fn display_space(entities: &ClientEntities, space: &SpatialGrid)
+{
+    let size = SpatialGrid::cell_size();
+
+    space.cells().for_each(|(cell, count)|
+    {
+        // redder the more stuff is crammed into one cell
+        let crowded = ((count as f32 - 1.0) / 4.0).clamp(0.0, 1.0);
+
+        entities.push(true, EntityInfo{
+            transform: Some(Transform{
+                position: (cell.cast::<f32>() + Vector3::repeat(0.5)) * size,
+                scale: Vector3::repeat(size),
+                ..Default::default()
+            }),
+            render: Some(RenderInfo{
+                object: Some(RenderObjectKind::Texture{
+                    name: "ui/background.png".to_owned()
+                }.into()),
+                z_level: ZLevel::highest_non_ui(),
+                mix: Some(MixColor{color: [crowded, 1.0 - crowded, 0.2], amount: 0.5, keep_transparency: true}),
+                ..Default::default()
+            }),
+            watchers: Some(Watchers::simple_one_frame()),
+            ..Default::default()
+        });
+    });
+}
 
-    space.possible_pairs(|possible|
+fn display_joint(entities: &ClientEntities, joint: &Joint, transform: &Transform, anchor: Vector3<f32>)
+{
+    let (start, color) = match joint
     {
-        let pairs_fn = |entity: Entity, other_entity: Entity|
+        Joint::Hinge(hinge) => (project_onto(transform, &hinge.origin), [0.2, 0.4, 1.0]),
+        Joint::Tether(tether) =>
         {
-            let mut this;
-            colliding_info!{this, entity};
+            let taut = (transform.position - anchor).magnitude() >= tether.length;
 
-            let other;
-            colliding_info!{other, other_entity};
+            (transform.position, if taut { [1.0, 0.3, 0.0] } else { [1.0, 1.0, 0.0] })
+        }
+    };
 
-            this.collide(other, |contact| contacts.push(contact));
-        };
+    entities.push(true, line_info(start, anchor, ENTITY_SCALE * 0.03, color));
+}
+
+// sleeping bodies dont move on their own so they only need checking against awake ones
+fn asleep(entities: &ClientEntities, entity: Entity) -> bool
+{
+    entities.physical(entity).map(|physical| physical.sleeping()).unwrap_or(false)
+}
 
-        unique_pairs_no_self(possible.iter().copied(), pairs_fn);
+// contacts between entities, the world and joints get added on top of these
+// also gives back which bodies r touching each other, including sleeping ones that never got narrowphased
+fn collide_bodies(
+    entities: &ClientEntities,
+    layers: &CollisionLayers
+) -> (Islands, Vec<Contact>, Vec<(Entity, Entity)>)
+{
+    let mut pairs = Vec::new();
+    let mut touching = Vec::new();
+    {
+        let space = entities.space.borrow();
+
+        if DebugConfig::is_enabled(DebugTool::Broadphase)
+        {
+            display_space(entities, &space);
+        }
+
+        space.possible_pairs(|entity, other_entity|
+        {
+            let mut pair = BodyPair{a: Body::new(entities, entity), b: Body::new(entities, other_entity)};
+
+            if asleep(entities, entity) && asleep(entities, other_entity)
+            {
+                if pair.bounds_touch(layers)
+                {
+                    touching.push((entity, other_entity));
+                }
+
+                return;
+            }
+
+            pairs.push(pair);
+        });
+    }
+
+    let mut islands = Islands::new(pairs.iter().map(|pair| (pair.a.entity(), pair.b.entity())));
+
+    let mut contacts = Vec::new();
+
+    narrowphase::collide_all(pairs, &mut islands, layers).into_iter().for_each(|pair|
+    {
+        entities.collider_mut(pair.a).unwrap().push_collided(pair.b);
+        entities.collider_mut(pair.b).unwrap().push_collided(pair.a);
+
+        touching.push((pair.a, pair.b));
+
+        contacts.extend(pair.contacts);
+    });
+
+    (islands, contacts, touching)
+}
+
+// islands dont touch each other so they get resolved separately, way less contacts to search through each time
+fn resolve(entities: &ClientEntities, mut islands: Islands, contacts: Vec<Contact>, dt: f32)
+{
+    islands.split(contacts, |contact| contact.a).into_iter().for_each(|contacts|
+    {
+        ContactResolver::resolve(entities, contacts, dt);
     });
+}
+
+// a pile only falls asleep once all of it is resting, anything awake touching it wakes the whole thing back up
+fn update_sleeping(entities: &ClientEntities, touching: Vec<(Entity, Entity)>)
+{
+    // stuff that cant move like walls would glue every pile touching them together
+    let movable = |entity|
+    {
+        entities.physical(entity).map(|physical| physical.inverse_mass != 0.0).unwrap_or(false)
+    };
+
+    let mut islands = Islands::new(touching.into_iter().filter(|(a, b)| movable(*a) && movable(*b)));
+
+    let mut restless = HashSet::new();
+    for_each_component!(entities, physical, |entity, physical: &RefCell<Physical>|
+    {
+        if !physical.borrow().resting()
+        {
+            restless.insert(islands.island(entity));
+        }
+    });
+
+    for_each_component!(entities, physical, |entity, physical: &RefCell<Physical>|
+    {
+        let awake = restless.contains(&islands.island(entity));
+
+        physical.borrow_mut().set_sleeping(!awake);
+    });
+}
+
+pub fn update(
+    entities: &mut ClientEntities,
+    world: &World,
+    dt: f32
+) -> Vec<Impact>
+{
+    macro_rules! colliding_info
+    {
+        ($result_variable:expr, $entity:expr) =>
+        {
+            let mut collider = entities.collider_mut($entity).unwrap();
+            {
+                let mut transform = entities.transform($entity).unwrap().clone();
+
+                let kind = collider.kind;
+                if kind == ColliderType::Aabb
+                {
+                    transform.rotation = 0.0;
+                }
+
+                if let Some(scale) = collider.scale
+                {
+                    transform.scale = scale;
+                }
+
+                $result_variable = CollidingInfo{
+                    entity: Some($entity),
+                    transform,
+                    collider: &mut collider
+                };
+            }
+        }
+    }
+
+    let layers = entities.infos().collision_layers.clone();
+
+    let (islands, mut contacts, mut touching) = collide_bodies(entities, &layers);
 
     for_each_component!(entities, collider, |entity, _collider|
     {
@@ -148,18 +291,198 @@ pub fn update(
             });
         }
 
-        this.collide_with_world(world, &mut contacts);
+        if asleep(entities, entity)
+        {
+            return;
+        }
+
+        this.collide_with_world(world, &layers, &mut contacts);
     });
 
+    // joints arent real collisions so they dont hurt
+    let impacts = impacts::impacts(entities, &contacts);
+
     for_each_component!(entities, joint, |entity, joint: &RefCell<Joint>|
     {
-        let parent = entities.parent(entity).unwrap();
+        let joint = joint.borrow();
+
+        let anchor = some_or_return!(joint.anchor().or_else(||
+        {
+            entities.parent(entity).map(|parent| parent.entity())
+        }));
+
         let transform = entities.transform(entity).unwrap();
 
-        let parent_position = entities.transform(parent.entity()).unwrap().position;
+        let anchor_position = some_or_return!(entities.transform(anchor)).position;
 
-        joint.borrow().add_contacts(&transform, entity, parent_position, &mut contacts);
+        if DebugConfig::is_enabled(DebugTool::Joints)
+        {
+            display_joint(entities, &joint, &transform, anchor_position);
+        }
+
+        joint.add_contacts(&transform, entity, anchor_position, &mut contacts);
+
+        touching.push((entity, anchor));
     });
 
-    ContactResolver::resolve(entities, contacts, dt);
+    resolve(entities, islands, contacts, dt);
+
+    update_sleeping(entities, touching);
+
+    impacts
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::f32;
+
+    use crate::common::physics::*;
+
+    use super::*;
+
+
+    fn step(entities: &mut ClientEntities, layers: &CollisionLayers, dt: f32)
+    {
+        entities.build_space();
+
+        for_each_component!(entities, physical, |entity, physical: &RefCell<Physical>|
+        {
+            let mut target = entities.target(entity).unwrap();
+
+            physical.borrow_mut().update(&mut target, |physical, transform|
+            {
+                entities.collider(entity).unwrap().inverse_inertia(physical, transform.clone())
+            }, dt);
+        });
+
+        let (islands, contacts, touching) = collide_bodies(entities, layers);
+
+        resolve(entities, islands, contacts, dt);
+
+        update_sleeping(entities, touching);
+    }
+
+    fn push_body(entities: &mut ClientEntities, position: Vector3<f32>, can_sleep: bool) -> Entity
+    {
+        entities.push_eager(true, EntityInfo{
+            transform: Some(Transform{
+                position,
+                scale: Vector3::repeat(ENTITY_SCALE),
+                ..Default::default()
+            }),
+            collider: Some(ColliderInfo{kind: ColliderType::Circle, ..Default::default()}.into()),
+            physical: Some(PhysicalProperties{
+                floating: true,
+                can_sleep,
+                ..Default::default()
+            }.into()),
+            ..Default::default()
+        })
+    }
+
+    fn simulate() -> Vec<(Vector3<f32>, f32, Vector3<f32>)>
+    {
+        let mut entities = ClientEntities::new(None);
+        let layers = CollisionLayers::parse("collision/layers.json");
+
+        // a ring of stuff all thrown into the middle at once
+        let amount = 12;
+        let bodies: Vec<Entity> = (0..amount).map(|index|
+        {
+            let angle = index as f32 / amount as f32 * f32::consts::TAU;
+            let direction = Vector3::new(angle.cos(), angle.sin(), 0.0);
+
+            let kind = if index % 2 == 0 { ColliderType::Circle } else { ColliderType::Rectangle };
+
+            let entity = entities.push_eager(true, EntityInfo{
+                transform: Some(Transform{
+                    position: direction * ENTITY_SCALE * 2.0,
+                    rotation: angle,
+                    scale: Vector3::repeat(ENTITY_SCALE),
+                    ..Default::default()
+                }),
+                collider: Some(ColliderInfo{kind, ..Default::default()}.into()),
+                physical: Some(PhysicalProperties{
+                    floating: true,
+                    can_sleep: false,
+                    ..Default::default()
+                }.into()),
+                ..Default::default()
+            });
+
+            entities.physical_mut(entity).unwrap().add_velocity_raw(-direction * ENTITY_SCALE * 10.0);
+
+            entity
+        }).collect();
+
+        (0..120).for_each(|_| step(&mut entities, &layers, 1.0 / 120.0));
+
+        bodies.into_iter().map(|entity|
+        {
+            let transform = entities.transform(entity).unwrap();
+
+            (transform.position, transform.rotation, *entities.physical(entity).unwrap().velocity())
+        }).collect()
+    }
+
+    #[test]
+    fn repeatable()
+    {
+        let first = simulate();
+
+        (0..3).for_each(|_| assert_eq!(simulate(), first));
+    }
+
+    #[test]
+    fn island_wakes()
+    {
+        let mut entities = ClientEntities::new(None);
+        let layers = CollisionLayers::parse("collision/layers.json");
+
+        let dt = 1.0 / 120.0;
+
+        // overlapping so little that the resolver leaves them alone
+        let spacing = ENTITY_SCALE - 0.0003;
+
+        // a row of stuff touching each other and another one off on its own
+        let row: Vec<Entity> = (0..5).map(|index|
+        {
+            push_body(&mut entities, Vector3::x() * spacing * index as f32, true)
+        }).collect();
+
+        let alone = push_body(&mut entities, Vector3::new(0.0, ENTITY_SCALE * 10.0, 0.0), true);
+
+        let thrown = push_body(&mut entities, -Vector3::x() * ENTITY_SCALE * 4.0, false);
+
+        let all_asleep = |entities: &ClientEntities|
+        {
+            row.iter().chain([&alone]).all(|entity| entities.physical(*entity).unwrap().sleeping())
+        };
+
+        let mut steps = 0;
+        while !all_asleep(&entities)
+        {
+            assert!(steps < 5000, "never fell asleep");
+
+            step(&mut entities, &layers, dt);
+            steps += 1;
+        }
+
+        entities.physical_mut(thrown).unwrap().add_velocity_raw(Vector3::x() * ENTITY_SCALE * 10.0);
+
+        let mut steps = 0;
+        while entities.physical(row[0]).unwrap().sleeping()
+        {
+            assert!(steps < 120, "never hit the row");
+
+            step(&mut entities, &layers, dt);
+            steps += 1;
+        }
+
+        // the far end wakes up in the same step even though nothing touched it directly
+        row.iter().for_each(|entity| assert!(!entities.physical(*entity).unwrap().sleeping()));
+
+        assert!(entities.physical(alone).unwrap().sleeping());
+    }
 }
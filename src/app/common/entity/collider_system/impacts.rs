@@ -0,0 +1,78 @@
+use std::collections::BTreeMap;
+
+use nalgebra::Vector3;
+
+use crate::common::{
+    angle_between,
+    ENTITY_SCALE,
+    AnyEntities,
+    Entity,
+    collider::Contact,
+    entity::ClientEntities
+};
+
+
+// anything slower than this is just a bump
+const MIN_SPEED: f32 = ENTITY_SCALE * 8.0;
+
+// damage for every kilogram going an entity length per second over the minimum
+const DAMAGE_SCALE: f32 = 0.5;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Impact
+{
+    pub entity: Entity,
+    pub angle: f32,
+    pub damage: f32
+}
+
+pub fn impacts(entities: &ClientEntities, contacts: &[Contact]) -> Vec<Impact>
+{
+    // a collision usually has a few contact points, only the fastest one counts
+    let mut fastest: BTreeMap<(Entity, Option<Entity>), (f32, Vector3<f32>)> = BTreeMap::new();
+
+    contacts.iter().for_each(|contact|
+    {
+        let speed = contact.closing_speed(entities);
+        if speed < MIN_SPEED
+        {
+            return;
+        }
+
+        let current = fastest.entry((contact.a, contact.b)).or_insert((speed, *contact.normal));
+        if speed > current.0
+        {
+            *current = (speed, *contact.normal);
+        }
+    });
+
+    // thrown stuff already does its own damage
+    let is_damaging = |entity: Option<Entity>|
+    {
+        entity.map(|entity| entities.damaging_exists(entity)).unwrap_or(false)
+    };
+
+    fastest.into_iter().flat_map(|((a, b), (speed, normal))|
+    {
+        if is_damaging(Some(a)) || is_damaging(b)
+        {
+            return Vec::new();
+        }
+
+        let inverse_mass = |entity: Entity| entities.physical(entity).map(|x| x.inverse_mass).unwrap_or(0.0);
+
+        let total_inverse_mass = inverse_mass(a) + b.map(inverse_mass).unwrap_or(0.0);
+        if total_inverse_mass <= 0.0
+        {
+            return Vec::new();
+        }
+
+        let damage = (speed - MIN_SPEED) / ENTITY_SCALE * total_inverse_mass.recip() * DAMAGE_SCALE;
+
+        let hit_a = Impact{entity: a, angle: angle_between(Vector3::zeros(), normal), damage};
+
+        let hit_b = b.map(|b| Impact{entity: b, angle: angle_between(Vector3::zeros(), -normal), damage});
+
+        Some(hit_a).into_iter().chain(hit_b).collect()
+    }).collect()
+}
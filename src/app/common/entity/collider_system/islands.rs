@@ -0,0 +1,90 @@
+use std::collections::HashMap;
+
+use crate::common::Entity;
+
+
+// groups of bodies touching each other, contacts in different islands cant affect each other
+pub struct Islands
+{
+    indices: HashMap<Entity, usize>,
+    parents: Vec<usize>
+}
+
+impl Islands
+{
+    pub fn new(pairs: impl Iterator<Item=(Entity, Entity)>) -> Self
+    {
+        let mut this = Self{
+            indices: HashMap::new(),
+            parents: Vec::new()
+        };
+
+        pairs.for_each(|(a, b)|
+        {
+            let a = this.index(a);
+            let b = this.index(b);
+
+            let (a, b) = (this.find(a), this.find(b));
+
+            // the lower one always wins so the roots dont depend on the pair order
+            this.parents[a.max(b)] = a.min(b);
+        });
+
+        this
+    }
+
+    fn index(&mut self, entity: Entity) -> usize
+    {
+        let next = self.parents.len();
+
+        let index = *self.indices.entry(entity).or_insert(next);
+
+        if index == next
+        {
+            self.parents.push(next);
+        }
+
+        index
+    }
+
+    fn find(&mut self, mut index: usize) -> usize
+    {
+        while self.parents[index] != index
+        {
+            self.parents[index] = self.parents[self.parents[index]];
+            index = self.parents[index];
+        }
+
+        index
+    }
+
+    pub fn island(&mut self, entity: Entity) -> usize
+    {
+        let index = self.index(entity);
+
+        self.find(index)
+    }
+
+    // keeps the order things came in, both for the islands and inside of them
+    pub fn split<T>(&mut self, items: impl IntoIterator<Item=T>, entity: impl Fn(&T) -> Entity) -> Vec<Vec<T>>
+    {
+        let mut groups: Vec<Vec<T>> = Vec::new();
+        let mut group_of = HashMap::new();
+
+        items.into_iter().for_each(|item|
+        {
+            let island = self.island(entity(&item));
+
+            let index = *group_of.entry(island).or_insert_with(||
+            {
+                groups.push(Vec::new());
+
+                groups.len() - 1
+            });
+
+            groups[index].push(item);
+        });
+
+        groups
+    }
+}
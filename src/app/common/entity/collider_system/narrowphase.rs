@@ -0,0 +1,168 @@
+use std::thread;
+
+use nalgebra::Vector3;
+
+use yanyaengine::Transform;
+
+use crate::common::{
+    collider::*,
+    AnyEntities,
+    Entity,
+    CollisionLayers,
+    entity::ClientEntities
+};
+
+use super::islands::Islands;
+
+
+// below this spinning up threads costs more than it saves
+const PARALLEL_MIN_PAIRS: usize = 64;
+
+// a copy of everything the narrowphase needs so it can leave the main thread
+pub struct Body
+{
+    entity: Entity,
+    transform: Transform,
+    collider: Collider
+}
+
+impl Body
+{
+    pub fn new(entities: &ClientEntities, entity: Entity) -> Self
+    {
+        let collider = entities.collider(entity).unwrap().clone();
+        let mut transform = entities.transform(entity).unwrap().clone();
+
+        if collider.kind == ColliderType::Aabb
+        {
+            transform.rotation = 0.0;
+        }
+
+        if let Some(scale) = collider.scale
+        {
+            transform.scale = scale;
+        }
+
+        Self{entity, transform, collider}
+    }
+
+    pub fn entity(&self) -> Entity
+    {
+        self.entity
+    }
+
+    fn bounds(&mut self) -> (Vector3<f32>, Vector3<f32>)
+    {
+        let half = CollidingInfo{
+            entity: Some(self.entity),
+            transform: self.transform.clone(),
+            collider: &mut self.collider
+        }.bounds();
+
+        (self.transform.position - half, self.transform.position + half)
+    }
+}
+
+pub struct BodyPair
+{
+    pub a: Body,
+    pub b: Body
+}
+
+impl BodyPair
+{
+    // way cheaper than a real collision, good enough to tell which resting stuff is piled together
+    pub fn bounds_touch(&mut self, layers: &CollisionLayers) -> bool
+    {
+        if !layers.collides(self.a.collider.layer, self.b.collider.layer)
+        {
+            return false;
+        }
+
+        let (a_start, a_end) = self.a.bounds();
+        let (b_start, b_end) = self.b.bounds();
+
+        (0..3).all(|i| a_start[i] <= b_end[i] && b_start[i] <= a_end[i])
+    }
+}
+
+pub struct PairContacts
+{
+    pub a: Entity,
+    pub b: Entity,
+    pub contacts: Vec<Contact>
+}
+
+fn collide_pair(pair: &mut BodyPair, layers: &CollisionLayers) -> Option<PairContacts>
+{
+    let mut contacts = Vec::new();
+
+    let mut this = CollidingInfo{
+        entity: Some(pair.a.entity),
+        transform: pair.a.transform.clone(),
+        collider: &mut pair.a.collider
+    };
+
+    let other = CollidingInfo{
+        entity: Some(pair.b.entity),
+        transform: pair.b.transform.clone(),
+        collider: &mut pair.b.collider
+    };
+
+    this.collide(other, layers, |contact| contacts.push(contact)).then(||
+    {
+        PairContacts{a: pair.a.entity, b: pair.b.entity, contacts}
+    })
+}
+
+// whole islands get handed out to worker threads, the results get put back in the original pair order
+// so the merge comes out the same no matter which thread finishes first
+pub fn collide_all(
+    pairs: Vec<BodyPair>,
+    islands: &mut Islands,
+    layers: &CollisionLayers
+) -> Vec<PairContacts>
+{
+    let workers = thread::available_parallelism().map(|x| x.get()).unwrap_or(1);
+
+    if pairs.len() < PARALLEL_MIN_PAIRS || workers < 2
+    {
+        return pairs.into_iter().filter_map(|mut pair| collide_pair(&mut pair, layers)).collect();
+    }
+
+    let total = pairs.len();
+
+    let mut groups: Vec<Vec<(usize, BodyPair)>> = (0..workers).map(|_| Vec::new()).collect();
+    islands.split(pairs.into_iter().enumerate(), |(_, pair)| pair.a.entity).into_iter().for_each(|island|
+    {
+        let least_busy = groups.iter_mut().min_by_key(|group| group.len()).unwrap();
+
+        least_busy.extend(island);
+    });
+
+    let mut results: Vec<Option<PairContacts>> = (0..total).map(|_| None).collect();
+
+    thread::scope(|scope|
+    {
+        let handles: Vec<_> = groups.into_iter().filter(|group| !group.is_empty()).map(|group|
+        {
+            scope.spawn(move ||
+            {
+                group.into_iter().filter_map(|(index, mut pair)|
+                {
+                    collide_pair(&mut pair, layers).map(|contacts| (index, contacts))
+                }).collect::<Vec<_>>()
+            })
+        }).collect();
+
+        handles.into_iter().for_each(|handle|
+        {
+            handle.join().unwrap().into_iter().for_each(|(index, contacts)|
+            {
+                results[index] = Some(contacts);
+            });
+        });
+    });
+
+    results.into_iter().flatten().collect()
+}
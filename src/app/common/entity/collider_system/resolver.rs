@@ -502,6 +502,26 @@ impl Contact
         to_world.transpose() * relative_velocity
     }
 
+    // how fast the bodies are going into each other at the contact point
+    pub fn closing_speed(&self, entities: &ClientEntities) -> f32
+    {
+        let point_velocity = |entity: Entity|
+        {
+            let physical = entities.physical(entity).unwrap();
+            let relative = self.point - entities.transform(entity).unwrap().position;
+
+            Self::velocity_from_angular(physical.angular_velocity(), relative) + physical.velocity()
+        };
+
+        let mut velocity = point_velocity(self.a);
+        if let Some(b) = self.b
+        {
+            velocity -= point_velocity(b);
+        }
+
+        -velocity.dot(&self.normal)
+    }
+
     fn restitution(&self, entities: &ClientEntities) -> f32
     {
         self.average_physical(entities, |x| x.restitution)
@@ -745,10 +765,7 @@ impl ContactResolver
         {
             if let Some(info) = contacts.iter_mut()
                 .filter_map(contact_selector(&compare, &epsilon))
-                .max_by(|(a, _), (b, _)|
-                {
-                    a.partial_cmp(b).unwrap_or(Ordering::Less)
-                })
+                .max_by(|(a, _), (b, _)| a.total_cmp(b))
             {
                 if let Some((moves, bodies)) = contact_handler(entities, &epsilon, &mut resolver, info)
                 {
@@ -801,12 +818,26 @@ impl ContactResolver
         }
     }
 
+    // the order contacts get found in depends on where things r stored, this one only depends on the contacts
+    fn contact_order(a: &Contact, b: &Contact) -> Ordering
+    {
+        a.a.cmp(&b.a).then_with(|| a.b.cmp(&b.b)).then_with(||
+        {
+            a.point.iter().zip(b.point.iter()).map(|(a, b)| a.total_cmp(b))
+                .find(|x| x.is_ne())
+                .unwrap_or(Ordering::Equal)
+        })
+    }
+
     pub fn resolve(
         entities: &ClientEntities,
-        contacts: Vec<Contact>,
+        mut contacts: Vec<Contact>,
         dt: f32
     )
     {
+        // every contact changes the ones after it so they always have to go in the same order
+        contacts.sort_by(Self::contact_order);
+
         if DebugConfig::is_enabled(DebugTool::Contacts)
         {
             contacts.iter().for_each(|contact| Self::display_contact(entities, contact));
@@ -6,116 +6,198 @@ use std::{
 use nalgebra::{Unit, Vector3};
 
 use crate::common::{
+    some_or_return,
     angle_between,
     short_rotation,
     damage::*,
     damaging::*,
     character::*,
-    render_info::*,
     watcher::*,
-    particle_creator::*,
-    ENTITY_SCALE,
-    EntityInfo,
-    PhysicalProperties,
     Message,
     Side2d,
+    BarkKind,
+    ParticleEffects,
+    ENTITY_SCALE,
+    ragdoll::spawn_ragdoll,
     AnyEntities,
     Entity,
     EntityPasser,
-    entity::{iterate_components_with, ClientEntities}
+    entity::{iterate_components_with, ClientEntities, Impact}
 };
 
-use yanyaengine::TextureId;
 
+const HEAVY_HIT_DAMAGE: f32 = 250.0;
+const HEAVY_HIT_TRAUMA: f32 = 0.3;
+const HEAVY_HIT_STOP: f32 = 0.05;
+
+const GIBS_TRAUMA: f32 = 0.6;
+const GIBS_HIT_STOP: f32 = 0.1;
+
+// limb speed from a killing hit of heavy hit damage
+const RAGDOLL_SPEED: f32 = ENTITY_SCALE * 4.0;
+const RAGDOLL_MAX_SPEED: f32 = ENTITY_SCALE * 10.0;
 
 pub fn entity_damager<'a>(
     entities: &'a ClientEntities,
-    passer: &'a mut impl EntityPasser,
-    blood_texture: TextureId
-) -> impl FnMut(Entity, f32, Faction, DamagePartial) + 'a
+    passer: &'a mut impl EntityPasser
+) -> impl FnMut(Entity, Option<Entity>, f32, Faction, DamagePartial) + 'a
+{
+    move |entity, source, angle, faction, damage|
+    {
+        let damage = damage.with_direction(some_or_return!(hit_side(entities, entity, angle)));
+
+        // blocks and parries get resolved once by the server, it sends back what got through
+        passer.send_message(Message::EntityDamage{entity, source, faction, damage});
+    }
+}
+
+// which side of the entity a hit coming in from this angle lands on
+pub fn hit_side(entities: &impl AnyEntities, entity: Entity, angle: f32) -> Option<Side2d>
+{
+    let entity_rotation = entities.transform(entity)?.rotation;
+
+    let relative_rotation = angle - (-entity_rotation);
+
+    Some(Side2d::from_angle(relative_rotation))
+}
+
+// how hard a hit shakes the screen and how long it stops time for
+pub fn hit_shake(gibbed: bool, damage_amount: f32) -> Option<(f32, f32)>
+{
+    if gibbed
+    {
+        Some((GIBS_TRAUMA, GIBS_HIT_STOP))
+    } else if damage_amount >= HEAVY_HIT_DAMAGE
+    {
+        Some((HEAVY_HIT_TRAUMA, HEAVY_HIT_STOP))
+    } else
+    {
+        None
+    }
+}
+
+// the server only says which side got hit, this is whichever way its away from the source
+fn hit_direction(entities: &ClientEntities, entity: Entity, source: Option<Entity>) -> Unit<Vector3<f32>>
+{
+    let position = |entity: Entity| entities.transform(entity).map(|transform| transform.position);
+
+    source.filter(|source| *source != entity).and_then(|source|
+    {
+        let offset = position(entity)? - position(source)?;
+
+        Unit::try_new(Vector3::new(offset.x, offset.y, 0.0), f32::EPSILON)
+    }).unwrap_or_else(||
+    {
+        let rotation = entities.character(entity).map(|character| character.rotation).unwrap_or_default();
+
+        Unit::new_normalize(-Vector3::new(rotation.cos(), rotation.sin(), 0.0))
+    })
+}
+
+// damage the server already resolved, everything that isnt synced like particles happens here
+pub fn entity_damaged(
+    entities: &ClientEntities,
+    particle_effects: &ParticleEffects,
+    entity: Entity,
+    source: Option<Entity>,
+    damage: Damage
+)
 {
-    move |entity, angle, faction, damage|
+    let is_alive = ||
+    {
+        entities.anatomy(entity).map(|anatomy| anatomy.speed().is_some()).unwrap_or(false)
+    };
+
+    let was_alive = is_alive();
+
+    let damage_amount = damage.data.as_flat();
+    entities.apply_damage(entity, damage);
+
+    let killed = was_alive && !is_alive();
+
+    if is_alive()
     {
-        let entity_rotation = if let Some(transform) = entities.transform(entity)
+        if let Some(mut enemy) = entities.enemy_mut(entity)
         {
-            transform.rotation
-        } else
+            enemy.bark(BarkKind::Hurt);
+        }
+    }
+
+    if let Some(source) = source.filter(|source| *source != entity)
+    {
+        if killed
         {
-            return;
-        };
+            entities.push_kill(source, entity);
+        }
+    }
+
+    let direction = hit_direction(entities, entity, source);
 
-        let relative_rotation = angle - (-entity_rotation);
-        let damage = damage.with_direction(Side2d::from_angle(relative_rotation));
+    // things without anatomy dont bleed
+    let effect = if entities.anatomy_exists(entity)
+    {
+        "blood"
+    } else
+    {
+        "hit_sparks"
+    };
 
-        let damaged = entities.damage_entity_common(entity, faction, damage.clone());
+    entities.watchers_mut(entity).unwrap().push(Watcher{
+        kind: WatcherType::Instant,
+        action: WatcherAction::Explode(Box::new(
+            particle_effects.get(effect).explode(true, direction)
+        )),
+        ..Default::default()
+    });
 
-        if damaged
+    let gibs = killed.then(||
+    {
+        entities.enemy(entity).and_then(|enemy|
         {
-            let direction = Unit::new_unchecked(
-                Vector3::new(-angle.cos(), angle.sin(), 0.0)
-            );
-
-            passer.send_message(Message::EntityDamage{entity, faction, damage});
-
-            let scale = Vector3::repeat(ENTITY_SCALE * 0.1)
-                .component_mul(&Vector3::new(4.0, 1.0, 1.0));
-
-            entities.watchers_mut(entity).unwrap().push(Watcher{
-                kind: WatcherType::Instant,
-                action: WatcherAction::Explode(Box::new(ExplodeInfo{
-                    keep: true,
-                    info: ParticlesInfo{
-                        amount: 2..4,
-                        speed: ParticleSpeed::DirectionSpread{
-                            direction,
-                            speed: 1.7..=2.0,
-                            spread: 0.2
-                        },
-                        decay: ParticleDecay::Random(7.0..=10.0),
-                        position: ParticlePosition::Spread(0.1),
-                        rotation: ParticleRotation::Exact(f32::consts::PI - angle),
-                        scale: ParticleScale::Spread{scale, variation: 0.1},
-                        min_scale: ENTITY_SCALE * 0.15
-                    },
-                    prototype: EntityInfo{
-                        physical: Some(PhysicalProperties{
-                            inverse_mass: 0.05_f32.recip(),
-                            floating: true,
-                            ..Default::default()
-                        }.into()),
-                        render: Some(RenderInfo{
-                            object: Some(RenderObjectKind::TextureId{
-                                id: blood_texture
-                            }.into()),
-                            z_level: ZLevel::Knee,
-                            ..Default::default()
-                        }),
-                        ..Default::default()
-                    }
-                })),
-                ..Default::default()
-            });
-        }
+            enemy.death().gibs_for(damage_amount).map(ToOwned::to_owned)
+        })
+    }).flatten();
+
+    if let Some(gibs) = gibs
+    {
+        entities.watchers_mut(entity).unwrap().push(Watcher{
+            kind: WatcherType::Instant,
+            action: WatcherAction::Explode(Box::new(
+                particle_effects.get(&gibs).explode(true, direction)
+            )),
+            ..Default::default()
+        });
+    } else if killed
+    {
+        let speed = (damage_amount / HEAVY_HIT_DAMAGE * RAGDOLL_SPEED).min(RAGDOLL_MAX_SPEED);
+
+        spawn_ragdoll(entities, entity, *direction * speed);
     }
 }
 
 pub fn update(
     entities: &mut ClientEntities,
-    passer: &mut impl EntityPasser,
-    blood_texture: TextureId
+    passer: &mut impl EntityPasser
 )
 {
     struct DamagingResult
     {
         collided: Entity,
+        source: Option<Entity>,
         angle: f32,
         faction: Faction,
         damage: DamagePartial
     }
 
     // "zero" "cost" "abstractions" "borrow" "checker"
-    let damage_entities = iterate_components_with!(entities, damaging, flat_map, |entity, damaging: &RefCell<Damaging>|
+    let damage_entities = iterate_components_with!(entities, damaging, flat_map, |entity: Entity, damaging: &RefCell<Damaging>|
     {
+        // clients can only make local ones, anything else is a projectile and the server does its hits
+        if !entity.local()
+        {
+            return Vec::new();
+        }
+
         let collider = entities.collider(entity).unwrap();
 
         collider.collided().iter().copied().filter_map(|collided|
@@ -171,7 +253,13 @@ pub fn update(
 
                 return damaging.damage.as_damage(collision_info).map(|(angle, damage)|
                 {
-                    DamagingResult{collided, angle, faction: damaging.faction, damage}
+                    DamagingResult{
+                        collided,
+                        source: damaging.source,
+                        angle,
+                        faction: damaging.faction,
+                        damage
+                    }
                 });
             }
 
@@ -179,15 +267,37 @@ pub fn update(
         }).collect::<Vec<_>>()
     }).collect::<Vec<_>>();
 
-    let mut damager = entity_damager(entities, passer, blood_texture);
+    let mut damager = entity_damager(entities, passer);
     damage_entities.into_iter().for_each(|DamagingResult{
         collided,
+        source,
         angle,
         faction,
         damage
     }|
     {
-        damager(collided, angle, faction, damage)
+        damager(collided, source, angle, faction, damage)
+    });
+}
+
+// impacts are self inflicted so they go through no matter the faction
+pub fn apply_impacts(
+    entities: &ClientEntities,
+    passer: &mut impl EntityPasser,
+    impacts: Vec<Impact>
+)
+{
+    let mut damager = entity_damager(entities, passer);
+    impacts.into_iter().for_each(|impact|
+    {
+        let faction = some_or_return!(entities.faction(impact.entity));
+
+        let damage = DamagePartial{
+            data: DamageType::Blunt(impact.damage),
+            height: DamageHeight::random()
+        };
+
+        damager(impact.entity, Some(impact.entity), impact.angle, faction, damage);
     });
 }
 
@@ -0,0 +1,97 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap
+};
+
+use yanyaengine::Transform;
+
+use crate::common::{
+    some_or_return,
+    short_rotation,
+    watcher::*,
+    AnyEntities,
+    Entity,
+    EntityInfo,
+    EntityPasser,
+    Joint,
+    Occluder,
+    SoundEmitter,
+    Noise,
+    NoiseKind,
+    entity::{for_each_component, ClientEntities}
+};
+
+
+// swung further than this from its frame counts as open, has to come back past the closed angle to close
+const OPEN_ANGLE: f32 = 0.3;
+const CLOSED_ANGLE: f32 = 0.1;
+
+const DOOR_NOISE: f32 = 0.6;
+
+// remembers which doors were open last frame
+pub fn update(
+    entities: &ClientEntities,
+    passer: &mut impl EntityPasser,
+    doors: &mut HashMap<Entity, bool>,
+    make_noise: bool
+)
+{
+    doors.retain(|entity, _| entities.exists(*entity));
+
+    for_each_component!(entities, joint, |entity, joint: &RefCell<Joint>|
+    {
+        if !entities.occluder(entity).map(|x| matches!(*x, Occluder::Door)).unwrap_or(false)
+        {
+            return;
+        }
+
+        let base = if let Joint::Hinge(hinge) = &*joint.borrow()
+        {
+            some_or_return!(hinge.angle_limit).base
+        } else
+        {
+            return;
+        };
+
+        let position = {
+            let transform = some_or_return!(entities.transform(entity));
+
+            let angle = short_rotation(transform.rotation - base).abs();
+
+            let was_open = *doors.entry(entity).or_insert(angle > OPEN_ANGLE);
+
+            let is_open = if was_open { angle > CLOSED_ANGLE } else { angle > OPEN_ANGLE };
+
+            if is_open == was_open
+            {
+                return;
+            }
+
+            doors.insert(entity, is_open);
+
+            transform.position
+        };
+
+        let name = if doors[&entity] { "door_open" } else { "door_close" };
+
+        entities.push(true, EntityInfo{
+            transform: Some(Transform{
+                position,
+                ..Default::default()
+            }),
+            sound: Some(SoundEmitter::new(name)),
+            watchers: Some(Watchers::simple_disappearing(1.0)),
+            ..Default::default()
+        });
+
+        let noise = Noise::new(NoiseKind::Door, position, DOOR_NOISE);
+
+        if make_noise
+        {
+            entities.make_noise(passer, noise);
+        } else
+        {
+            entities.push_noise(noise);
+        }
+    });
+}
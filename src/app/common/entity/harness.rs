@@ -0,0 +1,238 @@
+use std::mem;
+
+use yanyaengine::Transform;
+
+use nalgebra::Vector3;
+
+use crate::common::{
+    ConnectionId,
+    EntityPasser,
+    EntityInfo,
+    Entity,
+    Message,
+    Inventory,
+    Item,
+    Anatomy,
+    PlanAnatomy,
+    BodyPlansInfo,
+    Character,
+    CharacterId,
+    Faction,
+    DamagePartial,
+    DamageType,
+    DamageHeight,
+    Side2d,
+    items_info::ItemId,
+    entity::{ServerEntities, ClientEntities}
+};
+
+
+#[derive(Default)]
+struct QueuePasser
+{
+    messages: Vec<Message>
+}
+
+impl EntityPasser for QueuePasser
+{
+    fn send_single(&mut self, _id: ConnectionId, message: Message)
+    {
+        self.messages.push(message);
+    }
+
+    fn send_message(&mut self, message: Message)
+    {
+        self.messages.push(message);
+    }
+}
+
+// a server and a client passing messages through queues instead of sockets
+pub struct Harness
+{
+    pub server: ServerEntities,
+    pub client: ClientEntities,
+    to_client: QueuePasser,
+    to_server: QueuePasser
+}
+
+impl Harness
+{
+    pub fn new() -> Self
+    {
+        Self{
+            server: ServerEntities::new(None),
+            client: ClientEntities::new(None),
+            to_client: QueuePasser::default(),
+            to_server: QueuePasser::default()
+        }
+    }
+
+    pub fn spawn(&mut self, info: EntityInfo) -> Entity
+    {
+        let message = self.server.push_message(info);
+
+        let entity = message.entity().unwrap();
+        self.to_client.send_message(message);
+
+        entity
+    }
+
+    pub fn server_send(&mut self, message: Message)
+    {
+        self.to_client.send_message(message);
+    }
+
+    pub fn client_send(&mut self, message: Message)
+    {
+        self.to_server.send_message(message);
+    }
+
+    // delivers everything sent so far both ways, returns whatever nothing handled
+    pub fn sync(&mut self) -> Vec<Message>
+    {
+        let mut unhandled = Vec::new();
+
+        for message in mem::take(&mut self.to_client.messages)
+        {
+            unhandled.extend(self.client.handle_message_headless(message));
+        }
+
+        for message in mem::take(&mut self.to_server.messages)
+        {
+            unhandled.extend(self.server.handle_message(message));
+        }
+
+        unhandled
+    }
+}
+
+mod tests
+{
+    use super::*;
+
+
+    fn at(position: Vector3<f32>) -> Transform
+    {
+        Transform{position, ..Default::default()}
+    }
+
+    fn human() -> Anatomy
+    {
+        let plans = BodyPlansInfo::parse("anatomy/body_plans.json");
+
+        Anatomy::Plan(PlanAnatomy::new(plans.human(), Default::default()))
+    }
+
+    #[test]
+    fn replication()
+    {
+        let mut harness = Harness::new();
+
+        let position = Vector3::new(1.0, 2.0, 0.0);
+        let entity = harness.spawn(EntityInfo{
+            transform: Some(at(position)),
+            named: Some("thing".to_owned()),
+            ..Default::default()
+        });
+
+        assert!(harness.sync().is_empty());
+
+        assert_eq!(harness.client.transform(entity).unwrap().position, position);
+        assert_eq!(*harness.client.named(entity).unwrap(), "thing");
+
+        let moved = Vector3::new(5.0, -3.0, 0.0);
+        harness.client_send(Message::SyncPosition{entity, position: moved});
+        harness.sync();
+
+        assert_eq!(harness.server.transform(entity).unwrap().position, moved);
+
+        harness.server_send(Message::EntityDestroy{entity});
+        harness.sync();
+
+        assert!(!harness.client.exists(entity));
+    }
+
+    #[test]
+    fn inventory()
+    {
+        let mut harness = Harness::new();
+
+        let entity = harness.spawn(EntityInfo{
+            transform: Some(at(Vector3::zeros())),
+            inventory: Some(Inventory::new()),
+            ..Default::default()
+        });
+
+        harness.sync();
+
+        let mut inventory = harness.client.inventory(entity).unwrap().clone();
+        inventory.push(Item{id: ItemId::from(0)});
+
+        harness.client_send(Message::SetInventory{entity, component: Box::new(inventory)});
+        harness.sync();
+
+        let items = harness.server.inventory(entity).unwrap().items().len();
+        assert_eq!(items, 1);
+    }
+
+    #[test]
+    fn damage()
+    {
+        let mut harness = Harness::new();
+
+        let entity = harness.spawn(EntityInfo{
+            transform: Some(at(Vector3::zeros())),
+            character: Some(Character::new(CharacterId::from(0), Faction::Zob)),
+            anatomy: Some(human()),
+            ..Default::default()
+        });
+
+        harness.sync();
+
+        let before = harness.server.anatomy(entity).unwrap().health_fraction();
+
+        let damage = DamagePartial{
+            data: DamageType::Blunt(50.0),
+            height: DamageHeight::Middle
+        }.with_direction(Side2d::Back);
+
+        harness.client_send(Message::EntityDamage{entity, source: None, faction: Faction::Player, damage});
+        harness.sync();
+
+        let after = harness.server.anatomy(entity).unwrap().health_fraction();
+        assert!(after < before, "{after} should be less than {before}");
+    }
+
+    #[test]
+    fn resolved_damage()
+    {
+        let mut harness = Harness::new();
+
+        let entity = harness.spawn(EntityInfo{
+            transform: Some(at(Vector3::zeros())),
+            character: Some(Character::new(CharacterId::from(0), Faction::Zob)),
+            anatomy: Some(human()),
+            ..Default::default()
+        });
+
+        harness.sync();
+
+        let damage = DamagePartial{
+            data: DamageType::Blunt(50.0),
+            height: DamageHeight::Middle
+        }.with_direction(Side2d::Front);
+
+        let (_, damage) = harness.server.resolve_damage(entity, None, Faction::Player, damage).unwrap();
+        let damage = damage.unwrap();
+
+        harness.server.apply_damage(entity, damage.clone());
+
+        // the client doesnt get to block it again
+        harness.server_send(Message::EntityDamaged{entity, source: None, damage});
+        harness.sync();
+
+        let server = harness.server.anatomy(entity).unwrap().health_fraction();
+        let client = harness.client.anatomy(entity).unwrap().health_fraction();
+        assert_eq!(server, client);
+    }
+}
@@ -1,11 +1,17 @@
 use std::cell::RefCell;
 
+use nalgebra::Vector3;
+
 use crate::{
     debug_config::*,
     common::{
+        some_or_return,
         direction_arrow_info,
         physics::*,
+        collider::*,
+        raycast::*,
         AnyEntities,
+        Entity,
         world::World,
         entity::{
             for_each_component,
@@ -15,6 +21,84 @@ use crate::{
 };
 
 
+// fast things get swept so they dont tunnel through thin walls or characters between frames
+fn swept_position(
+    entities: &ClientEntities,
+    world: &World,
+    entity: Entity,
+    start: Vector3<f32>,
+    end: Vector3<f32>
+) -> Option<Vector3<f32>>
+{
+    let collider = entities.collider(entity)?;
+    if collider.ghost
+    {
+        return None;
+    }
+
+    let scale = collider.scale.unwrap_or(entities.transform(entity)?.scale);
+    let radius = scale.xy().min() / 2.0;
+
+    let movement = end - start;
+    let distance = movement.magnitude();
+
+    if distance <= radius
+    {
+        return None;
+    }
+
+    let direction = movement / distance;
+
+    let info = RaycastInfo{
+        pierce: Some(f32::INFINITY),
+        layer: collider.layer,
+        ignore_entity: Some(entity),
+        ignore_end: false
+    };
+
+    // already overlapping things get handled by the regular collisions
+    let entity_hit = entities.raycast(info, &start, &end).hits.into_iter()
+        .map(|hit| hit.result.distance)
+        .find(|distance| *distance > 0.0);
+
+    let hits_world = entities.infos().collision_layers.collides(collider.layer, ColliderLayer::WORLD);
+
+    let world_hit = if hits_world
+    {
+        world.tile_hit(start, end).map(|hit| hit.distance)
+    } else
+    {
+        None
+    };
+
+    let hit = entity_hit.into_iter().chain(world_hit).min_by(f32::total_cmp)?;
+
+    // stops a bit inside so the narrowphase still sees the contact
+    Some(start + direction * (hit - radius * 0.5).max(0.0))
+}
+
+// stops stuff from bobbing forever
+const LIQUID_DRAG: f32 = 3.0;
+
+// pushes up anything inside of a liquid tile depending on how dense it is compared to the liquid
+fn buoyancy(world: &World, physical: &mut Physical, position: Vector3<f32>)
+{
+    if physical.floating() || physical.sleeping() || physical.inverse_mass == 0.0
+    {
+        return;
+    }
+
+    let tile = some_or_return!(world.tile(world.tile_of(position.into())));
+    let liquid = some_or_return!(world.tile_info(*tile).liquid);
+
+    let mass = physical.inverse_mass.recip();
+
+    let lift = -GRAVITY * (liquid / physical.density.max(0.01)) * mass;
+    let drag = -*physical.velocity() * LIQUID_DRAG * mass;
+
+    physical.add_force(lift + drag);
+}
+
 pub fn update(entities: &mut ClientEntities, world: &World, dt: f32)
 {
     for_each_component!(entities, physical, |entity, physical: &RefCell<Physical>|
@@ -26,6 +110,10 @@ pub fn update(entities: &mut ClientEntities, world: &World, dt: f32)
                 return;
             }
 
+            let start = target.position;
+
+            buoyancy(world, &mut physical.borrow_mut(), start);
+
             physical.borrow_mut().update(
                 &mut target,
                 |physical, transform|
@@ -37,10 +125,16 @@ pub fn update(entities: &mut ClientEntities, world: &World, dt: f32)
                 dt
             );
 
-            if DebugConfig::is_enabled(DebugTool::Velocity)
+            let end = target.position;
+            drop(target);
+
+            if let Some(position) = swept_position(entities, world, entity, start, end)
             {
-                drop(target);
+                entities.target(entity).unwrap().position = position;
+            }
 
+            if DebugConfig::is_enabled(DebugTool::Velocity)
+            {
                 let velocity = *physical.borrow().velocity();
                 let magnitude = velocity.magnitude();
 
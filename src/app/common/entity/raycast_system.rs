@@ -1,40 +1,54 @@
-use std::{
-    cmp::Ordering,
-    cell::RefCell
-};
+use std::cmp::Ordering;
 
 use nalgebra::{Unit, Vector3};
 
 use crate::common::{
     collider::*,
     raycast::*,
-    entity::{
-        iterate_components_with,
-        ClientEntities
-    }
+    AnyEntities,
+    Entity,
+    entity::ClientEntities
 };
 
 
-pub fn raycast(
+pub fn raycast_client(
     entities: &ClientEntities,
     info: RaycastInfo,
     start: &Vector3<f32>,
     end: &Vector3<f32>
 ) -> RaycastHits
+{
+    let candidates: Vec<Entity> = if info.ignore_end
+    {
+        // keeps going past the end so theres no last cell to stop at
+        entities.colliders()
+    } else
+    {
+        entities.space.borrow().along_line(*start, *end)
+    };
+
+    raycast(entities, candidates, info, start, end)
+}
+
+pub fn raycast(
+    entities: &impl AnyEntities,
+    candidates: Vec<Entity>,
+    info: RaycastInfo,
+    start: &Vector3<f32>,
+    end: &Vector3<f32>
+) -> RaycastHits
 {
     let direction = end - start;
 
     let max_distance = direction.magnitude();
     let direction = Unit::new_unchecked(direction / max_distance);
 
-    let mut hits: Vec<_> = iterate_components_with!(
-        entities,
-        collider,
-        filter_map,
-        |entity, collider: &RefCell<Collider>|
+    let layers = &entities.infos().collision_layers;
+
+    let mut hits: Vec<_> = candidates.into_iter().filter_map(|entity|
         {
-            let collider = collider.borrow();
-            let collides = collider.layer.collides(&info.layer);
+            let collider = entities.collider(entity)?;
+            let collides = layers.collides(collider.layer, info.layer);
 
             (collides && !collider.ghost).then(|| (entity, collider.kind))
         })
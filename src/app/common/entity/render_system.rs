@@ -1,9 +1,23 @@
+use nalgebra::Vector2;
+
 use yanyaengine::game_object::*;
 
 use crate::{
     debug_config::*,
     ProgramShaders,
-    client::VisibilityChecker,
+    client::{
+        VisibilityChecker,
+        game_state::{
+            Decals,
+            WeatherEffects,
+            Background,
+            Reflections,
+            LightOcclusion,
+            Tracers,
+            PhysicsStepper,
+            SpriteBatches
+        }
+    },
     common::{
         render_info::*,
         Entity,
@@ -18,6 +32,7 @@ use crate::{
 pub fn update_buffers(
     entities: &ClientEntities,
     renderables: impl Iterator<Item=Entity>,
+    physics: &PhysicsStepper,
     info: &mut UpdateBuffersInfo,
     caster: &OccludingCaster
 )
@@ -43,7 +58,7 @@ pub fn update_buffers(
             }
         }
 
-        let transform = entities.transform(entity).unwrap();
+        let transform = physics.interpolated(entity, &entities.transform(entity).unwrap());
 
         let mut render = entities.render_mut(entity).unwrap();
         render.set_transform(transform.clone());
@@ -61,6 +76,15 @@ pub struct DrawEntities<'a>
 {
     pub renders: &'a [Vec<Entity>],
     pub shaded_renders: &'a [Entity],
+    pub light_occlusion: &'a LightOcclusion,
+    pub decals: &'a Decals,
+    pub weather: &'a WeatherEffects,
+    pub background: &'a Background,
+    pub reflections: &'a Reflections,
+    pub tracers: &'a Tracers,
+    pub batches: &'a SpriteBatches,
+    pub height: f32,
+    pub tile_step: Vector2<f32>,
     pub world: &'a World
 }
 
@@ -73,6 +97,10 @@ pub fn draw(
     animation: f32
 )
 {
+    info.bind_pipeline(shaders.background);
+
+    renderables.background.draw(info);
+
     info.bind_pipeline(shaders.shadow);
 
     renderables.world.draw_shadows(info, &visibility);
@@ -92,30 +120,77 @@ pub fn draw(
 
     info.bind_pipeline(shaders.world);
 
+    info.push_constants(TilesInfo::new(renderables.weather.world_info(), renderables.tile_step));
+
     renderables.world.draw(info);
 
     info.bind_pipeline(shaders.default);
 
+    renderables.decals.draw(info, renderables.height, animation);
+    renderables.weather.draw_ground(info, animation);
+
+    info.bind_pipeline(shaders.reflection);
+
+    renderables.reflections.draw(info, animation);
+
+    info.bind_pipeline(shaders.default);
+
     renderables.renders.iter().flatten().for_each(|&entity|
     {
-        let outline = entities.outlineable(entity).and_then(|outline|
+        if renderables.batches.draw(info, entity, animation)
+        {
+            return;
+        }
+
+        let outlineable = entities.outlineable(entity);
+
+        let style = outlineable.as_ref().map(|x| x.style).unwrap_or_default();
+        let outline = outlineable.and_then(|outline|
         {
             outline.current()
-        }).unwrap_or_default();
+        }).map(|x| x * style.pulse.amount(animation)).unwrap_or_default();
 
         let render = entities.render(entity).unwrap();
 
-        let outline = OutlinedInfo::new(
-            render.mix,
-            outline,
-            animation
-        );
+        let outline = OutlinedInfo::new(render.mix, outline, animation)
+            .with_emissive(render.emissive_intensity(animation))
+            .with_outline_style(&style)
+            .with_dissolve(render.dissolve);
 
         render.draw(info, outline);
     });
 
+    info.bind_pipeline(shaders.light_occlusion);
+
+    renderables.light_occlusion.draw(info);
+
+    info.bind_pipeline(shaders.default);
+
+    // drawn again so light shadows dont cover them
+    renderables.renders.iter().flatten().for_each(|&entity|
+    {
+        let render = entities.render(entity).unwrap();
+
+        if render.emissive.is_none()
+        {
+            return;
+        }
+
+        render.draw_glow(info, animation);
+
+        let outline = OutlinedInfo::new(render.mix, 0.0, animation)
+            .with_emissive(render.emissive_intensity(animation))
+            .with_dissolve(render.dissolve);
+
+        render.draw(info, outline);
+    });
+
+    renderables.tracers.draw(info, animation);
+
     info.bind_pipeline(shaders.world_shaded);
 
+    info.push_constants(TilesInfo::new(WorldInfo::default(), renderables.tile_step));
+
     renderables.world.draw(info);
 
     info.bind_pipeline(shaders.default_shaded);
@@ -124,10 +199,14 @@ pub fn draw(
     {
         let render = entities.render(entity).unwrap();
 
-        render.draw(info, OutlinedInfo::new(
-            render.mix,
-            Default::default(),
-            animation
-        ));
+        let outline = OutlinedInfo::new(render.mix, Default::default(), animation)
+            .with_emissive(render.emissive_intensity(animation))
+            .with_dissolve(render.dissolve);
+
+        render.draw(info, outline);
     });
+
+    info.bind_pipeline(shaders.default);
+
+    renderables.weather.draw(info, animation);
 }
@@ -0,0 +1,45 @@
+use std::cell::RefCell;
+
+use crate::common::{
+    some_or_return,
+    AnyEntities,
+    WindZone,
+    Physical,
+    entity::{for_each_component, ClientEntities}
+};
+
+
+pub fn update(entities: &mut ClientEntities, dt: f32)
+{
+    let mut zones = Vec::new();
+    for_each_component!(entities, wind, |entity, wind: &RefCell<WindZone>|
+    {
+        let transform = some_or_return!(entities.transform(entity)).clone();
+
+        let mut wind = wind.borrow_mut();
+        wind.update(dt);
+
+        zones.push((transform, wind.acceleration()));
+    });
+
+    if zones.is_empty()
+    {
+        return;
+    }
+
+    for_each_component!(entities, physical, |entity, physical: &RefCell<Physical>|
+    {
+        let position = some_or_return!(entities.transform(entity)).position;
+
+        zones.iter().filter(|(transform, _)| WindZone::contains(transform, position)).for_each(|(_, acceleration)|
+        {
+            let mut physical = physical.borrow_mut();
+
+            if physical.inverse_mass > 0.0
+            {
+                let mass = physical.inverse_mass.recip();
+                physical.add_force(acceleration * mass);
+            }
+        });
+    });
+}
@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+
+use serde::{Serialize, Deserialize};
+
+use strum::{EnumIter, IntoStaticStr};
+
+use crate::common::{
+    render_info::ZLevel,
+    DamageHeight,
+    ItemsInfo,
+    Item
+};
+
+
+// even the best stuff lets some of it through
+const MAX_PROTECTION: f32 = 0.8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter, IntoStaticStr, Serialize, Deserialize)]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum EquipSlot
+{
+    Head,
+    Torso,
+    Hands,
+    Legs,
+    Feet,
+    Back
+}
+
+impl EquipSlot
+{
+    pub fn name(&self) -> &'static str
+    {
+        self.into()
+    }
+
+    pub fn z_level(&self) -> ZLevel
+    {
+        match self
+        {
+            Self::Head => ZLevel::Hat,
+            Self::Torso => ZLevel::Shoulders,
+            Self::Hands => ZLevel::Elbow,
+            Self::Legs => ZLevel::Hips,
+            Self::Feet => ZLevel::Feet,
+            Self::Back => ZLevel::Waist
+        }
+    }
+
+    fn covers(&self, height: DamageHeight) -> bool
+    {
+        match height
+        {
+            DamageHeight::Top => *self == Self::Head,
+            DamageHeight::Middle => matches!(self, Self::Torso | Self::Hands | Self::Back),
+            DamageHeight::Bottom => matches!(self, Self::Legs | Self::Feet)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct Wearable
+{
+    pub slot: EquipSlot,
+    // fraction of the damage taken off anything hitting where its worn
+    #[serde(default)]
+    pub protection: f32
+}
+
+// worn items dont sit in the inventory
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Equipment
+{
+    slots: HashMap<EquipSlot, Item>
+}
+
+impl Equipment
+{
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    pub fn get(&self, slot: EquipSlot) -> Option<&Item>
+    {
+        self.slots.get(&slot)
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.slots.is_empty()
+    }
+
+    // gives back whatever was in that slot before
+    pub fn equip(&mut self, slot: EquipSlot, item: Item) -> Option<Item>
+    {
+        self.slots.insert(slot, item)
+    }
+
+    pub fn unequip(&mut self, slot: EquipSlot) -> Option<Item>
+    {
+        self.slots.remove(&slot)
+    }
+
+    pub fn take_all(&mut self) -> Vec<Item>
+    {
+        self.slots.drain().map(|(_, item)| item).collect()
+    }
+
+    // how much of a hit at this height gets through
+    pub fn damage_scale(&self, items_info: &ItemsInfo, height: DamageHeight) -> f32
+    {
+        let protection: f32 = self.slots.iter()
+            .filter(|(slot, _)| slot.covers(height))
+            .filter_map(|(_, item)| items_info.get(item.id).wearable)
+            .map(|wearable| wearable.protection)
+            .sum();
+
+        1.0 - protection.clamp(0.0, MAX_PROTECTION)
+    }
+}
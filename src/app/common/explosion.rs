@@ -0,0 +1,209 @@
+use std::f32;
+
+use serde::{Serialize, Deserialize};
+
+use nalgebra::Vector3;
+
+use crate::common::{
+    some_or_false,
+    damage::*,
+    raycast::*,
+    collider::*,
+    rng::{self, RngStream},
+    projectile::{ProjectileInfo, ProjectileKind, SimulatedInfo},
+    AnyEntities,
+    Entity,
+    Faction,
+    entity::ClientEntities,
+    world::{TILE_SIZE, World}
+};
+
+
+// how much gets through a wall
+const WALL_FACTOR: f32 = 0.15;
+
+// every thing standing in the way takes this much off
+const COVER_FACTOR: f32 = 0.5;
+
+// anything weaker than this doesnt do anything
+const MIN_STRENGTH: f32 = 0.01;
+
+const HEIGHTS: [DamageHeight; 3] = [DamageHeight::Top, DamageHeight::Middle, DamageHeight::Bottom];
+
+// no matter what the item data says, anything bigger goes through way too many tiles
+const MAX_RADIUS: f32 = TILE_SIZE * 16.0;
+
+// thrown explosives roll to a stop on their own
+const THROW_SPEED: f32 = TILE_SIZE * 30.0;
+const THROW_DRAG: f32 = 0.2;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExplosionInfo
+{
+    pub radius: f32,
+    pub damage: f32,
+    pub impulse: f32,
+    // sets whatever is around it on fire
+    #[serde(default)]
+    pub fire: bool
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Explosive
+{
+    // seconds after being thrown, goes off sooner if it stops or hits someone
+    pub fuse: f32,
+    pub explosion: ExplosionInfo
+}
+
+impl Explosive
+{
+    // bounces around until the fuse runs out
+    pub fn projectile(&self) -> ProjectileInfo
+    {
+        ProjectileInfo{
+            kind: ProjectileKind::Simulated(SimulatedInfo{
+                speed: THROW_SPEED,
+                drag: THROW_DRAG,
+                ricochets: 3,
+                ricochet_angle: f32::consts::FRAC_PI_2,
+                ricochet_speed: 0.4,
+                lifetime: self.fuse,
+                ..Default::default()
+            }),
+            impact: None,
+            muzzle: None,
+            explosion: Some(self.explosion)
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Explosion
+{
+    pub position: Vector3<f32>,
+    pub info: ExplosionInfo,
+    pub source: Option<Entity>,
+    pub faction: Faction
+}
+
+impl ExplosionInfo
+{
+    pub fn clamped(self) -> Self
+    {
+        Self{
+            radius: self.radius.clamp(0.0, MAX_RADIUS),
+            damage: self.damage.max(0.0),
+            impulse: self.impulse.max(0.0),
+            ..self
+        }
+    }
+
+    // 1 right at the center, falls off with distance and gets smaller behind cover
+    fn strength(
+        &self,
+        center: Vector3<f32>,
+        entities: &impl AnyEntities,
+        walled: &mut impl FnMut(Vector3<f32>, Vector3<f32>) -> bool,
+        entity: Entity,
+        position: Vector3<f32>
+    ) -> f32
+    {
+        let distance = (position - center).magnitude();
+
+        let mut strength = (1.0 - distance / self.radius).max(0.0).powi(2);
+
+        if strength < MIN_STRENGTH || distance == 0.0
+        {
+            return strength;
+        }
+
+        if walled(center, position)
+        {
+            strength *= WALL_FACTOR;
+        }
+
+        let info = RaycastInfo{
+            pierce: Some(f32::INFINITY),
+            layer: ColliderLayer::DAMAGE,
+            ignore_entity: Some(entity),
+            ignore_end: false
+        };
+
+        let cover = entities.raycast(info, &center, &position).hits.len();
+
+        strength * COVER_FACTOR.powi(cover as i32)
+    }
+
+    // everything that feels it with how strongly, walled checks if theres a wall between two points
+    pub fn caught(
+        &self,
+        center: Vector3<f32>,
+        entities: &impl AnyEntities,
+        mut walled: impl FnMut(Vector3<f32>, Vector3<f32>) -> bool
+    ) -> Vec<(Entity, Vector3<f32>, f32)>
+    {
+        let layers = &entities.infos().collision_layers;
+
+        entities.colliders().into_iter().filter(|entity|
+        {
+            let collider = some_or_false!(entities.collider(*entity));
+
+            !collider.ghost && layers.collides(collider.layer, ColliderLayer::DAMAGE)
+        }).filter_map(|entity|
+        {
+            let position = entities.transform(entity)?.position;
+
+            let strength = self.strength(center, entities, &mut walled, entity, position);
+
+            (strength >= MIN_STRENGTH).then_some((entity, position, strength))
+        }).collect()
+    }
+
+    // the server already hurt everyone, this just throws stuff around
+    pub fn push(&self, center: Vector3<f32>, entities: &ClientEntities, world: &World)
+    {
+        self.caught(center, entities, |start, end| world.tile_hit(start, end).is_some())
+            .into_iter()
+            .for_each(|(entity, position, strength)|
+            {
+                if let Some(mut physical) = entities.physical_mut(entity)
+                {
+                    let direction = (position - center).try_normalize(0.0001).unwrap_or_else(Vector3::z);
+
+                    let velocity = direction * (self.impulse * strength * physical.inverse_mass);
+
+                    physical.set_sleeping(false);
+                    physical.add_velocity_raw(velocity);
+                }
+            });
+    }
+}
+
+impl Explosion
+{
+    // every body part has a chance of getting caught in it, closer means more of them
+    pub fn damages(&self, bleeds: bool, strength: f32) -> Vec<DamagePartial>
+    {
+        let data = DamageType::Blunt(self.info.damage * strength);
+
+        if !bleeds
+        {
+            return vec![DamagePartial{data, height: DamageHeight::random()}];
+        }
+
+        let mut damages: Vec<_> = HEIGHTS.into_iter().filter(|_| rng::f32(RngStream::Combat) < strength.sqrt()).map(|height|
+        {
+            DamagePartial{data, height}
+        }).collect();
+
+        if damages.is_empty()
+        {
+            damages.push(DamagePartial{data, height: DamageHeight::random()});
+        }
+
+        damages
+    }
+}
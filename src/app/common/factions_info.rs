@@ -0,0 +1,70 @@
+use std::{
+    fs::File,
+    path::Path,
+    collections::HashMap
+};
+
+use serde::{Serialize, Deserialize};
+
+use crate::common::{
+    overrides::overridden,
+    Faction
+};
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum FactionRelation
+{
+    Hostile,
+    #[default]
+    Neutral,
+    Ally
+}
+
+// how factions feel about each other, a pair only needs to be listed from one side
+#[derive(Debug, Clone)]
+pub struct FactionsInfo
+{
+    relations: HashMap<(Faction, Faction), FactionRelation>
+}
+
+impl FactionsInfo
+{
+    pub fn parse(info: impl AsRef<Path>) -> Self
+    {
+        let info = File::open(overridden(info)).unwrap();
+
+        let relations: HashMap<Faction, HashMap<Faction, FactionRelation>> =
+            serde_json::from_reader(info).unwrap();
+
+        Self::from_relations(relations)
+    }
+
+    pub fn from_relations(relations: HashMap<Faction, HashMap<Faction, FactionRelation>>) -> Self
+    {
+        let relations = relations.into_iter().flat_map(|(faction, others)|
+        {
+            others.into_iter().flat_map(move |(other, relation)|
+            {
+                [((faction, other), relation), ((other, faction), relation)]
+            })
+        }).collect();
+
+        Self{relations}
+    }
+
+    pub fn relation(&self, a: Faction, b: Faction) -> FactionRelation
+    {
+        self.relations.get(&(a, b)).copied().unwrap_or_default()
+    }
+
+    pub fn aggressive(&self, a: Faction, b: Faction) -> bool
+    {
+        self.relation(a, b) == FactionRelation::Hostile
+    }
+
+    pub fn allied(&self, a: Faction, b: Faction) -> bool
+    {
+        self.relation(a, b) == FactionRelation::Ally
+    }
+}
@@ -0,0 +1,164 @@
+use serde::{Serialize, Deserialize};
+
+use nalgebra::Vector3;
+
+use yanyaengine::Transform;
+
+use crate::common::{
+    render_info::*,
+    lazy_transform::*,
+    Light,
+    Parent,
+    Entity,
+    EntityInfo,
+    world::TILE_SIZE
+};
+
+
+// how often a fire tries to catch whatever is next to it
+const SPREAD_INTERVAL: f32 = 2.0;
+
+// how often anything burning takes heat damage
+const HEAT_INTERVAL: f32 = 0.5;
+
+// how often the flames puff out particles
+const FLARE_INTERVAL: f32 = 0.2;
+
+const FIRE_COLOR: [f32; 3] = [1.0, 0.55, 0.1];
+
+// how long someone keeps burning after catching fire
+pub const BURNING_FUEL: f32 = 8.0;
+
+// spilled fuel on the ground burns for this long with nothing else feeding it
+pub const SPILL_FUEL: f32 = 6.0;
+
+// every heat tick
+pub const HEAT_DAMAGE: f32 = 15.0;
+
+// chance for every neighbor to catch on a spread tick
+pub const SPREAD_CHANCE: f32 = 0.3;
+pub const SPREAD_DISTANCE: f32 = TILE_SIZE * 1.5;
+
+// walking through fire has this chance to catch every heat tick
+pub const IGNITE_CHANCE: f32 = 0.5;
+pub const IGNITE_DISTANCE: f32 = TILE_SIZE * 0.8;
+
+// seconds of fuel gone every second of rolling around
+pub const ROLL_SMOTHER: f32 = 15.0;
+
+// rain burns through fuel this many times faster
+pub const RAIN_BURN: f32 = 3.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Fire
+{
+    // seconds until it burns out
+    pub fuel: f32,
+    #[serde(skip, default)]
+    spread: f32,
+    #[serde(skip, default)]
+    heat: f32,
+    #[serde(skip, default)]
+    flare: f32
+}
+
+impl Fire
+{
+    pub fn new(fuel: f32) -> Self
+    {
+        Self{fuel, spread: SPREAD_INTERVAL, heat: HEAT_INTERVAL, flare: 0.0}
+    }
+
+    // rate is how many seconds of fuel go every second
+    pub fn update(&mut self, rate: f32, dt: f32)
+    {
+        self.fuel -= rate * dt;
+
+        self.spread -= dt;
+        self.heat -= dt;
+    }
+
+    pub fn burnt_out(&self) -> bool
+    {
+        self.fuel <= 0.0
+    }
+
+    pub fn smother(&mut self, amount: f32)
+    {
+        self.fuel -= amount;
+    }
+
+    pub fn extinguish(&mut self)
+    {
+        self.fuel = 0.0;
+    }
+
+    pub fn consume_spread(&mut self) -> bool
+    {
+        Self::consume_timer(&mut self.spread, SPREAD_INTERVAL)
+    }
+
+    pub fn consume_heat(&mut self) -> bool
+    {
+        Self::consume_timer(&mut self.heat, HEAT_INTERVAL)
+    }
+
+    // only the clients care about this, its just for looks
+    pub fn flare(&mut self, dt: f32) -> bool
+    {
+        self.flare -= dt;
+
+        Self::consume_timer(&mut self.flare, FLARE_INTERVAL)
+    }
+
+    fn consume_timer(timer: &mut f32, interval: f32) -> bool
+    {
+        let ready = *timer <= 0.0;
+
+        if ready
+        {
+            *timer += interval;
+        }
+
+        ready
+    }
+
+    // a fire on the ground if theres no parent, otherwise stuck to whatever is burning
+    pub fn entity_info(self, position: Vector3<f32>, parent: Option<Entity>) -> EntityInfo
+    {
+        let transform = if parent.is_some()
+        {
+            Transform{
+                scale: Vector3::repeat(0.8),
+                ..Default::default()
+            }
+        } else
+        {
+            Transform{
+                position,
+                scale: Vector3::repeat(TILE_SIZE),
+                ..Default::default()
+            }
+        };
+
+        EntityInfo{
+            lazy_transform: Some(LazyTransformInfo{
+                transform,
+                ..Default::default()
+            }.into()),
+            parent: parent.map(|parent| Parent::new(parent, true)),
+            render: Some(RenderInfo{
+                object: Some(RenderObjectKind::Texture{
+                    name: "decals/dust.png".to_owned()
+                }.into()),
+                mix: Some(MixColor{color: FIRE_COLOR, amount: 0.8, keep_transparency: true}),
+                emissive: Some(Emissive{color: FIRE_COLOR, intensity: 1.0, flicker: 0.4}),
+                z_level: ZLevel::Hat,
+                ..Default::default()
+            }),
+            light: Some(Light{radius: TILE_SIZE * 6.0, flicker: 0.25}),
+            fire: Some(self),
+            ..Default::default()
+        }
+    }
+}
@@ -10,14 +10,19 @@ use crate::common::{
     PhysicalProperties,
     EntityInfo,
     ItemsInfo,
+    Difficulty,
+    rng::{self, RngStream},
     Loot,
-    Inventory
+    Inventory,
+    Container,
+    ContainersInfo
 };
 
 
 pub struct FurnitureBuilder<'a>
 {
     items_info: &'a ItemsInfo,
+    difficulty: &'a Difficulty,
     pos: Vector3<f32>
 }
 
@@ -25,32 +30,66 @@ impl<'a> FurnitureBuilder<'a>
 {
     pub fn new(
         items_info: &'a ItemsInfo,
+        difficulty: &'a Difficulty,
         pos: Vector3<f32>
     ) -> Self
     {
-        Self{items_info, pos}
+        Self{items_info, difficulty, pos}
     }
 
-    pub fn build(self) -> EntityInfo
+    // a random container from containers.json
+    pub fn build(self, containers_info: &ContainersInfo) -> Option<EntityInfo>
     {
+        let name = containers_info.random()?;
+        let info = containers_info.get(name)?;
+
         let mut inventory = Inventory::new();
 
-        let mut loot = Loot::new(self.items_info, vec!["trash", "utility"], 1.0);
-        loot.create_random(&mut inventory, 1..4);
+        let mut loot = Loot::new(self.items_info, info.loot.iter().map(String::as_str).collect(), 1.0);
+
+        let (low, high) = info.amount;
+        loot.create_random(&mut inventory, self.difficulty.loot_amount(low..(high + 1)));
+
+        let locked = rng::f32(RngStream::WorldGen) < info.lock_chance;
+
+        let mut entity_info = self.build_with(name, &info.texture, info.scale, inventory);
+
+        if let Some(render) = entity_info.render.as_mut()
+        {
+            render.mix = info.mix;
+        }
+
+        if let Some(collider) = entity_info.collider.as_mut()
+        {
+            collider.layer = info.collider_layer;
+        }
 
+        entity_info.container = Some(Container::new(name.to_owned(), locked));
+
+        Some(entity_info)
+    }
+
+    // whatever a dead player was carrying
+    pub fn build_remains(self, inventory: Inventory) -> EntityInfo
+    {
+        self.build_with("remains", "furniture/crate.png", 0.8, inventory)
+    }
+
+    fn build_with(self, name: &str, texture: &str, scale: f32, inventory: Inventory) -> EntityInfo
+    {
         EntityInfo{
             lazy_transform: Some(LazyTransformInfo{
                 transform: Transform{
                     position: self.pos,
-                    scale: Vector3::repeat(ENTITY_SCALE * 0.8),
+                    scale: Vector3::repeat(ENTITY_SCALE * scale),
                     ..Default::default()
                 },
                 ..Default::default()
             }.into()),
-            named: Some("crate".to_owned()),
+            named: Some(name.to_owned()),
             render: Some(RenderInfo{
                 object: Some(RenderObjectKind::Texture{
-                    name: "furniture/crate.png".to_owned()
+                    name: texture.to_owned()
                 }.into()),
                 shadow_visible: true,
                 z_level: ZLevel::Hips,
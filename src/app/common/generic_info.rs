@@ -2,6 +2,8 @@ use std::collections::HashMap;
 
 pub use crate::define_info_id;
 
+use crate::common::rng::{self, RngStream};
+
 
 #[macro_export]
 macro_rules! define_info_id
@@ -81,6 +83,6 @@ where
 
     pub fn random(&self) -> Id
     {
-        Id::from(fastrand::usize(0..self.items.len()))
+        Id::from(rng::usize(RngStream::Loot, 0..self.items.len()))
     }
 }
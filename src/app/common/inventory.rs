@@ -1,15 +1,37 @@
 use serde::{Serialize, Deserialize};
 
-use crate::common::Item;
+use crate::common::{
+    rng::{self, RngStream},
+    items_info::ItemId,
+    Item
+};
 
 pub use sorter::InventorySorter;
 
 mod sorter;
 
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct InventoryItem(usize);
 
+impl InventoryItem
+{
+    // everything after a removed item moves back by one
+    pub fn after_removal(self, removed: InventoryItem) -> Option<Self>
+    {
+        if self == removed
+        {
+            None
+        } else if self.0 > removed.0
+        {
+            Some(Self(self.0 - 1))
+        } else
+        {
+            Some(self)
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Inventory
 {
@@ -44,6 +66,11 @@ impl Inventory
         }
     }
 
+    pub fn find(&self, id: ItemId) -> Option<InventoryItem>
+    {
+        self.items.iter().position(|item| item.id == id).map(InventoryItem)
+    }
+
     pub fn is_empty(&self) -> bool
     {
         self.items.is_empty()
@@ -56,7 +83,7 @@ impl Inventory
 
     pub fn random(&self) -> InventoryItem
     {
-        let id = fastrand::usize(0..self.items.len());
+        let id = rng::usize(RngStream::Loot, 0..self.items.len());
 
         InventoryItem(id)
     }
@@ -13,10 +13,18 @@ use yanyaengine::{Assets, TextureId};
 use crate::common::{
     lerp,
     generic_info::*,
+    mods_info::merge_by_name,
+    rng::{self, RngStream},
     character::HAND_SCALE,
     Drug,
+    Food,
+    Bandage,
+    Prosthetic,
+    Explosive,
     DamageType,
-    Item
+    Item,
+    ProjectileInfo,
+    equipment::Wearable
 };
 
 
@@ -25,7 +33,18 @@ define_info_id!{ItemId}
 #[derive(Debug, Clone, Deserialize)]
 pub enum Ranged
 {
-    Pistol{cooldown: f32, damage: f32}
+    // spread is the biggest angle (in radians) a shot can stray by, ammo is the item used up every shot
+    Pistol{
+        cooldown: f32,
+        damage: f32,
+        #[serde(default)]
+        spread: f32,
+        ammo: Option<String>,
+        #[serde(default)]
+        projectile: ProjectileInfo
+    },
+    // speed is how fast the rope reels in
+    Grapple{cooldown: f32, range: f32, speed: f32}
 }
 
 impl Ranged
@@ -34,7 +53,8 @@ impl Ranged
     {
         match self
         {
-            Self::Pistol{..} => true
+            Self::Pistol{..} => true,
+            Self::Grapple{..} => false
         }
     }
 
@@ -42,17 +62,45 @@ impl Ranged
     {
         match self
         {
-            Self::Pistol{cooldown, ..} => *cooldown
+            Self::Pistol{cooldown, ..}
+            | Self::Grapple{cooldown, ..} => *cooldown
         }
     }
 
-    pub fn damage(&self) -> DamageType
+    pub fn spread(&self) -> f32
+    {
+        match self
+        {
+            Self::Pistol{spread, ..} => *spread,
+            Self::Grapple{..} => 0.0
+        }
+    }
+
+    pub fn ammo(&self) -> Option<&str>
+    {
+        match self
+        {
+            Self::Pistol{ammo, ..} => ammo.as_deref(),
+            Self::Grapple{..} => None
+        }
+    }
+
+    pub fn projectile(&self) -> Option<&ProjectileInfo>
+    {
+        match self
+        {
+            Self::Pistol{projectile, ..} => Some(projectile),
+            Self::Grapple{..} => None
+        }
+    }
+
+    pub fn damage(&self) -> Option<DamageType>
     {
         let with_base = |base, value|
         {
             let damage = base * value;
 
-            let spread = fastrand::f32() * damage * 0.05;
+            let spread = rng::f32(RngStream::Combat) * damage * 0.05;
 
             damage * spread
         };
@@ -61,8 +109,9 @@ impl Ranged
         {
             Self::Pistol{damage, ..} =>
             {
-                DamageType::Bullet(with_base(400.0, damage))
-            }
+                Some(DamageType::Bullet(with_base(400.0, damage)))
+            },
+            Self::Grapple{..} => None
         }
     }
 }
@@ -74,12 +123,20 @@ pub struct ItemInfoRaw
     name: String,
     ranged: Option<Ranged>,
     drug: Option<Drug>,
+    food: Option<Food>,
+    bandage: Option<Bandage>,
+    prosthetic: Option<Prosthetic>,
+    // needed to do any surgery
+    surgical: Option<bool>,
+    explosive: Option<Explosive>,
+    wearable: Option<Wearable>,
     comfort: Option<f32>,
     sharpness: Option<f32>,
     side_sharpness: Option<f32>,
     scale: Option<f32>,
     mass: Option<f32>,
     commonness: Option<f64>,
+    value: Option<f32>,
     groups: Vec<String>,
     texture: Option<String>
 }
@@ -92,6 +149,12 @@ pub struct ItemInfo
     pub name: String,
     pub ranged: Option<Ranged>,
     pub drug: Option<Drug>,
+    pub food: Option<Food>,
+    pub bandage: Option<Bandage>,
+    pub prosthetic: Option<Prosthetic>,
+    pub surgical: bool,
+    pub explosive: Option<Explosive>,
+    pub wearable: Option<Wearable>,
     pub comfort: f32,
     pub sharpness: f32,
     pub side_sharpness: f32,
@@ -99,6 +162,8 @@ pub struct ItemInfo
     pub aspect: Vector2<f32>,
     pub mass: f32,
     pub commonness: f64,
+    pub value: f32,
+    pub groups: Vec<String>,
     pub texture: Option<TextureId>
 }
 
@@ -143,10 +208,21 @@ impl ItemInfo
 
         let scale = raw.scale.unwrap_or(0.1) * 4.0;
 
+        let commonness = raw.commonness.unwrap_or(1.0);
+
+        // rarer stuff is worth more by default
+        let value = raw.value.unwrap_or_else(|| 5.0 / commonness.max(0.05) as f32);
+
         Self{
             name: raw.name,
             ranged: raw.ranged,
             drug: raw.drug,
+            food: raw.food,
+            bandage: raw.bandage,
+            prosthetic: raw.prosthetic,
+            surgical: raw.surgical.unwrap_or(false),
+            explosive: raw.explosive,
+            wearable: raw.wearable,
             comfort: raw.comfort.unwrap_or(1.0),
             sharpness: raw.sharpness.unwrap_or(0.0),
             side_sharpness: raw.side_sharpness.unwrap_or(0.0),
@@ -154,7 +230,9 @@ impl ItemInfo
             scale,
             aspect,
             mass: raw.mass.unwrap_or(1.0),
-            commonness: raw.commonness.unwrap_or(1.0),
+            commonness,
+            value,
+            groups: raw.groups,
             texture: Some(texture)
         }
     }
@@ -165,6 +243,12 @@ impl ItemInfo
             name: "hand".to_owned(),
             ranged: None,
             drug: None,
+            food: None,
+            bandage: None,
+            prosthetic: None,
+            surgical: false,
+            explosive: None,
+            wearable: None,
             comfort: 2.0,
             sharpness: 0.0,
             side_sharpness: 0.0,
@@ -172,6 +256,8 @@ impl ItemInfo
             aspect: Vector2::repeat(1.0),
             mass: 0.3,
             commonness: 1.0,
+            value: 0.0,
+            groups: Vec::new(),
             texture: None
         }
     }
@@ -230,12 +316,19 @@ impl ItemsInfo
     pub fn parse(
         assets: &Assets,
         textures_root: impl AsRef<Path>,
-        info: impl AsRef<Path>
+        infos: impl IntoIterator<Item=impl AsRef<Path>>
     ) -> Self
     {
-        let info = File::open(info.as_ref()).unwrap();
+        let items = infos.into_iter().flat_map(|info|
+        {
+            let info = File::open(info.as_ref()).unwrap();
+
+            let items: ItemsInfoRaw = serde_json::from_reader(info).unwrap();
+
+            items
+        });
 
-        let items: ItemsInfoRaw = serde_json::from_reader(info).unwrap();
+        let items = merge_by_name(items, |x| &x.name);
 
         let mut groups: HashMap<String, Vec<ItemId>> = HashMap::new();
 
@@ -306,7 +399,7 @@ impl ItemsInfo
 
     pub fn random(&self) -> Item
     {
-        let id = ItemId(fastrand::usize(0..self.generic_info.items().len()));
+        let id = ItemId(rng::usize(RngStream::Loot, 0..self.generic_info.items().len()));
 
         Item{id}
     }
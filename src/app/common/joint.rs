@@ -33,10 +33,19 @@ pub struct HingeJoint
     pub angle_limit: Option<HingeAngleLimit>
 }
 
+// keeps it within a distance of another entity, like a rope
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TetherJoint
+{
+    pub entity: Entity,
+    pub length: f32
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Joint
 {
-    Hinge(HingeJoint)
+    Hinge(HingeJoint),
+    Tether(TetherJoint)
 }
 
 fn hinge_contact(
@@ -102,8 +111,45 @@ fn hinge_contact(
     }
 }
 
+fn tether_contact(
+    this: &Transform,
+    entity: Entity,
+    base: Vector3<f32>,
+    joint: &TetherJoint,
+    contacts: &mut Vec<Contact>
+)
+{
+    let mut diff = this.position - base;
+    diff.z = 0.0;
+
+    let magnitude = diff.magnitude();
+
+    if magnitude > joint.length
+    {
+        let normal = -Unit::new_unchecked(diff / magnitude);
+
+        contacts.push(Contact{
+            a: entity,
+            b: None,
+            point: this.position,
+            penetration: magnitude - joint.length,
+            normal
+        });
+    }
+}
+
 impl Joint
 {
+    // hinges get pinned to their parent instead
+    pub fn anchor(&self) -> Option<Entity>
+    {
+        match self
+        {
+            Self::Hinge(_) => None,
+            Self::Tether(joint) => Some(joint.entity)
+        }
+    }
+
     pub fn add_contacts(
         &self,
         transform: &Transform,
@@ -119,7 +165,8 @@ impl Joint
 
         match self
         {
-            Self::Hinge(joint) => hinge_contact(transform, entity, base, joint, contacts)
+            Self::Hinge(joint) => hinge_contact(transform, entity, base, joint, contacts),
+            Self::Tether(joint) => tether_contact(transform, entity, base, joint, contacts)
         }
     }
 }
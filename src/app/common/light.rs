@@ -0,0 +1,72 @@
+use nalgebra::Vector3;
+
+use serde::{Serialize, Deserialize};
+
+use yanyaengine::{Transform, game_object::*};
+
+use crate::{
+    client::VisibilityChecker,
+    common::world::World
+};
+
+
+// how bright it is without any lights around
+const OUTSIDE_LIGHT: f32 = 1.0;
+const INSIDE_LIGHT: f32 = 0.35;
+const UNDERGROUND_LIGHT: f32 = 0.15;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Light
+{
+    pub radius: f32,
+    // how much the radius dips with the animation, 0 is steady
+    #[serde(default)]
+    pub flicker: f32
+}
+
+// does a light at this position reach far enough to get shadows from this plane
+pub fn light_reaches(position: Vector3<f32>, radius: f32, plane: &Transform) -> bool
+{
+    let distance = (plane.position.xy() - position.xy()).magnitude();
+
+    distance <= radius + plane.max_scale() / 2.0
+}
+
+// 0 is pitch black, 1 is as bright as it gets
+pub fn light_level(
+    world: &World,
+    lights: impl Iterator<Item=(Vector3<f32>, f32)>,
+    position: Vector3<f32>
+) -> f32
+{
+    let ambient = if position.z < 0.0
+    {
+        UNDERGROUND_LIGHT
+    } else if world.under_roof(position)
+    {
+        INSIDE_LIGHT
+    } else
+    {
+        OUTSIDE_LIGHT
+    };
+
+    let lit: f32 = lights.map(|(light_position, radius)|
+    {
+        (1.0 - light_position.metric_distance(&position) / radius).max(0.0)
+    }).sum();
+
+    (ambient + lit).min(1.0)
+}
+
+impl Light
+{
+    pub fn current_radius(&self, animation: f32) -> f32
+    {
+        self.radius * (1.0 - self.flicker * (animation * 0.5 + 0.5))
+    }
+
+    pub fn visible(&self, visibility: &VisibilityChecker, position: Vector3<f32>) -> bool
+    {
+        visibility.visible_sphere_radius(position, self.radius)
+    }
+}
@@ -9,7 +9,10 @@ use std::{
     ops::{RangeInclusive, Add, Sub, Mul, Div, Rem, Deref, Index, IndexMut}
 };
 
-use crate::debug_config::*;
+use crate::{
+    debug_config::*,
+    common::rng::{self, RngStream}
+};
 
 pub use super::{
     transfer_with_capacity,
@@ -712,7 +715,7 @@ impl Primitives
                 {
                     let limit = args.pop(memory).as_integer()?;
 
-                    memory.push_return(fastrand::i32(0..limit));
+                    memory.push_return(rng::i32(RngStream::WorldGen, 0..limit));
 
                     Ok(())
                 })),
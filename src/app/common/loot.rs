@@ -2,6 +2,7 @@ use std::ops::Range;
 
 use crate::common::{
     pick_by_commonness,
+    rng::{self, RngStream},
     Inventory,
     Item,
     ItemsInfo
@@ -11,7 +12,7 @@ use crate::common::{
 pub struct Loot<'a>
 {
     info: &'a ItemsInfo,
-    groups: Vec<&'static str>,
+    groups: Vec<&'a str>,
     commonness: f32
 }
 
@@ -19,7 +20,7 @@ impl<'a> Loot<'a>
 {
     pub fn new(
         info: &'a ItemsInfo,
-        groups: Vec<&'static str>,
+        groups: Vec<&'a str>,
         commonness: f32
     ) -> Self
     {
@@ -30,7 +31,7 @@ impl<'a> Loot<'a>
     {
         let possible = self.groups.iter().flat_map(|name| self.info.group(name));
 
-        let id = pick_by_commonness(self.commonness as f64, possible, |id|
+        let id = pick_by_commonness(RngStream::Loot, self.commonness as f64, possible, |id|
         {
             self.info.get(*id).commonness
         });
@@ -45,7 +46,7 @@ impl<'a> Loot<'a>
 
     pub fn create_random(&mut self, items: &mut Inventory, amount: Range<usize>)
     {
-        (0..fastrand::usize(amount)).filter_map(|_| self.create()).for_each(|item|
+        (0..rng::usize(RngStream::Loot, amount)).filter_map(|_| self.create()).for_each(|item|
         {
             items.push(item);
         });
@@ -57,7 +58,7 @@ mod tests
 {
     use std::{iter, collections::HashMap};
 
-    use crate::common::pick_by_commonness;
+    use crate::common::{pick_by_commonness, rng::RngStream};
 
 
     fn distribution(this_commonness: f64)
@@ -86,7 +87,7 @@ mod tests
 
         (0..trials).for_each(|_|
         {
-            let picked = pick_by_commonness(this_commonness, items.iter(), |(_, commonness)|
+            let picked = pick_by_commonness(RngStream::Loot, this_commonness, items.iter(), |(_, commonness)|
             {
                 *commonness
             });
@@ -1,4 +1,7 @@
-use std::mem;
+use std::{
+    mem::{self, Discriminant},
+    collections::HashMap
+};
 
 use serde::{Serialize, Deserialize};
 
@@ -12,6 +15,8 @@ use crate::common::{
     damaging::*,
     Occluder,
     Joint,
+    WindZone,
+    SoundEmitter,
     LazyMix,
     Outlineable,
     Transform,
@@ -19,6 +24,8 @@ use crate::common::{
     Collider,
     Physical,
     Inventory,
+    InventoryItem,
+    Equipment,
     Entity,
     EntityInfo,
     Character,
@@ -26,9 +33,30 @@ use crate::common::{
     Player,
     Parent,
     Enemy,
+    Corpse,
+    Dialogue,
+    Container,
+    Needs,
+    Fire,
+    Projectile,
+    TradeOffer,
+    BarterResponse,
+    PerkId,
+    RecipeId,
+    Light,
+    Decal,
+    Tracer,
+    Projectile,
+    ExplosionInfo,
+    Shake,
+    Sound,
+    Weather,
+    Difficulty,
+    CharacterCreation,
     Damage,
     Anatomy,
     RenderInfo,
+    snapshot::SyncDelta,
     world::{TilePos, Tile, Chunk, GlobalPos}
 };
 
@@ -45,27 +73,51 @@ pub enum Message
     SetFollowRotation{entity: Entity, component: Box<FollowRotation>},
     SetFollowPosition{entity: Entity, component: Box<FollowPosition>},
     SetInventory{entity: Entity, component: Box<Inventory>},
+    SetEquipment{entity: Entity, component: Box<Equipment>},
     SetRender{entity: Entity, component: Box<RenderInfo>},
     SetCollider{entity: Entity, component: Box<Collider>},
     SetPhysical{entity: Entity, component: Box<Physical>},
     SetJoint{entity: Entity, component: Box<Joint>},
+    SetWind{entity: Entity, component: Box<WindZone>},
+    SetSound{entity: Entity, component: Box<SoundEmitter>},
     SetWatchers{entity: Entity, component: Box<Watchers>},
     SetDamaging{entity: Entity, component: Box<Damaging>},
     SetAnatomy{entity: Entity, component: Box<Anatomy>},
     SetCharacter{entity: Entity, component: Box<Character>},
     SetPlayer{entity: Entity, component: Box<Player>},
     SetEnemy{entity: Entity, component: Box<Enemy>},
+    SetCorpse{entity: Entity, component: Box<Corpse>},
+    SetDialogue{entity: Entity, component: Box<Dialogue>},
+    SetContainer{entity: Entity, component: Box<Container>},
+    SetLight{entity: Entity, component: Box<Light>},
+    SetNeeds{entity: Entity, component: Box<Needs>},
+    SetFire{entity: Entity, component: Box<Fire>},
+    SetProjectile{entity: Entity, component: Box<Projectile>},
     SetNamed{entity: Entity, component: Box<String>},
     SetOccluder{entity: Entity, component: Box<Occluder>},
     SetNone{entity: Entity, component: Box<()>},
     SetTarget{entity: Entity, target: Transform},
+    // the client already moved its player, the server checks it and acks with where it ended up
+    PlayerMove{entity: Entity, sequence: u32, target: Transform},
+    PlayerMoveAck{sequence: u32, position: Vector3<f32>},
     SyncPosition{entity: Entity, position: Vector3<f32>},
     SyncPositionRotation{entity: Entity, position: Vector3<f32>, rotation: f32},
     SyncCharacter{entity: Entity, info: CharacterSyncInfo},
+    // only the components that changed since the base snapshot, removed r the indices of ones that r gone
+    EntitySetDelta{entity: Entity, info: Box<EntityInfo>, removed: Vec<usize>},
+    // the fields of a position sync that changed since the base snapshot
+    SyncDelta{entity: Entity, delta: SyncDelta},
     EntityDestroy{entity: Entity},
-    EntityDamage{entity: Entity, faction: Faction, damage: Damage},
-    PlayerConnect{name: String},
+    // a hit that the server still has to resolve
+    EntityDamage{entity: Entity, source: Option<Entity>, faction: Faction, damage: Damage},
+    // what got through after the server resolved it
+    EntityDamaged{entity: Entity, source: Option<Entity>, damage: Damage},
+    EntityBlocked{entity: Entity, source: Option<Entity>, parried: bool},
+    // mods r the names in load order
+    PlayerConnect{name: String, creation: CharacterCreation, mods: Vec<String>},
     PlayerOnConnect{player_entity: Entity},
+    // the server has different mods loaded, these r its own
+    ModsMismatch{mods: Vec<String>},
     PlayerFullyConnected,
     PlayerDisconnect{host: bool},
     PlayerDisconnectFinished,
@@ -73,6 +125,45 @@ pub enum Message
     ChunkRequest{pos: GlobalPos},
     ChunkSync{pos: GlobalPos, chunk: Chunk},
     SetTile{pos: TilePos, tile: Tile},
+    SpawnDecal{decal: Decal},
+    SpawnTracer{tracer: Tracer},
+    // where a projectile entity bounced off or stopped on something that doesnt bleed
+    SpawnImpact{impact: String, position: Vector3<f32>, direction: Vector3<f32>},
+    // the server figures out what the shot hits from the items own data
+    FireProjectile{entity: Entity, item: InventoryItem, target: Vector3<f32>},
+    // already resolved by the server, clients just push stuff around and draw it
+    Explode{position: Vector3<f32>, info: ExplosionInfo},
+    SpawnShake{shake: Shake},
+    PlaySound{sound: Sound},
+    SetWeather{weather: Weather},
+    SetDifficulty{difficulty: Difficulty},
+    SetTimeScale{scale: f32},
+    SpawnEnemy{name: String, position: Vector3<f32>},
+    DialogueStart{entity: Entity},
+    // none walks away from the conversation
+    DialogueChoose{entity: Entity, choice: Option<usize>},
+    TradeOffer{entity: Entity, offer: TradeOffer},
+    // only sent back to whoever made the offer
+    TradeResponse{entity: Entity, response: BarterResponse},
+    // dialogue effects the server already checked, only sent to whoever picked the choice
+    OpenTrade{entity: Entity},
+    RunScript{script: String},
+    ConsumeItem{item: InventoryItem},
+    Craft{recipe: RecipeId},
+    // cut up a dead animal with whatever sharp thing is held
+    Butcher{entity: Entity},
+    // the server owns the open and lock state, clients only ask
+    OpenContainer{entity: Entity, open: bool},
+    UnlockContainer{entity: Entity},
+    SetSpawn{bed: Entity},
+    // the server checks if the perk can be picked before giving it
+    PickPerk{perk: PerkId},
+    // permadeath, nothing happens in the world after this
+    WorldEnded,
+    Autosaving,
+    // starts everything the server sends in one go, deltas in it r against the base
+    Snapshot{sequence: u32, base: Option<u32>},
+    SnapshotAck{sequence: u32},
     RepeatMessage{message: Box<Message>}
 }
 
@@ -85,13 +176,54 @@ impl Message
             Message::ChunkRequest{..}
             | Message::PlayerConnect{..}
             | Message::PlayerOnConnect{..}
+            | Message::ModsMismatch{..}
             | Message::PlayerFullyConnected
             | Message::PlayerDisconnect{..}
-            | Message::PlayerDisconnectFinished => false,
+            | Message::PlayerDisconnectFinished
+            | Message::SetTimeScale{..}
+            | Message::SpawnEnemy{..}
+            | Message::SpawnTracer{..}
+            | Message::SpawnImpact{..}
+            | Message::Explode{..}
+            | Message::FireProjectile{..}
+            | Message::PlayerMove{..}
+            | Message::PlayerMoveAck{..}
+            | Message::EntityDamage{..}
+            | Message::DialogueStart{..}
+            | Message::DialogueChoose{..}
+            | Message::TradeOffer{..}
+            | Message::TradeResponse{..}
+            | Message::OpenTrade{..}
+            | Message::RunScript{..}
+            | Message::ConsumeItem{..}
+            | Message::Craft{..}
+            | Message::Butcher{..}
+            | Message::OpenContainer{..}
+            | Message::UnlockContainer{..}
+            | Message::SetSpawn{..}
+            | Message::PickPerk{..}
+            | Message::SyncDelta{..}
+            | Message::EntitySetDelta{..}
+            | Message::Snapshot{..}
+            | Message::SnapshotAck{..}
+            | Message::WorldEnded => false,
             _ => true
         }
     }
 
+    // completely overwrites whatever the last one of these set
+    pub fn is_full_state(&self) -> bool
+    {
+        match self
+        {
+            Message::SetTransform{..}
+            | Message::SetTarget{..}
+            | Message::SyncPosition{..}
+            | Message::SyncPositionRotation{..} => true,
+            _ => false
+        }
+    }
+
     pub fn entity(&self) -> Option<Entity>
     {
         match self
@@ -105,34 +237,79 @@ impl Message
             | Message::SetFollowRotation{entity, ..}
             | Message::SetFollowPosition{entity, ..}
             | Message::SetInventory{entity, ..}
+            | Message::SetEquipment{entity, ..}
             | Message::SetRender{entity, ..}
             | Message::SetCollider{entity, ..}
             | Message::SetPhysical{entity, ..}
             | Message::SetJoint{entity, ..}
+            | Message::SetWind{entity, ..}
+            | Message::SetSound{entity, ..}
             | Message::SetWatchers{entity, ..}
             | Message::SetDamaging{entity, ..}
             | Message::SetAnatomy{entity, ..}
             | Message::SetCharacter{entity, ..}
             | Message::SetPlayer{entity, ..}
             | Message::SetEnemy{entity, ..}
+            | Message::SetCorpse{entity, ..}
+            | Message::SetDialogue{entity, ..}
+            | Message::SetContainer{entity, ..}
+            | Message::SetLight{entity, ..}
+            | Message::SetNeeds{entity, ..}
+            | Message::SetFire{entity, ..}
+            | Message::SetProjectile{entity, ..}
             | Message::SetNamed{entity, ..}
             | Message::SetOccluder{entity, ..}
             | Message::SetNone{entity, ..}
             | Message::SetTarget{entity, ..}
+            | Message::PlayerMove{entity, ..}
             | Message::SyncPosition{entity, ..}
             | Message::SyncPositionRotation{entity, ..}
             | Message::SyncCharacter{entity, ..}
+            | Message::SyncDelta{entity, ..}
+            | Message::EntitySetDelta{entity, ..}
             | Message::EntityDestroy{entity, ..}
-            | Message::EntityDamage{entity, ..} => Some(*entity),
+            | Message::EntityDamage{entity, ..}
+            | Message::EntityDamaged{entity, ..}
+            | Message::EntityBlocked{entity, ..} => Some(*entity),
             Message::PlayerConnect{..}
             | Message::PlayerOnConnect{..}
+            | Message::ModsMismatch{..}
             | Message::PlayerFullyConnected
             | Message::PlayerDisconnect{..}
             | Message::PlayerDisconnectFinished
             | Message::SetTrusted
+            | Message::PlayerMoveAck{..}
             | Message::ChunkRequest{..}
             | Message::ChunkSync{..}
             | Message::SetTile{..}
+            | Message::SpawnDecal{..}
+            | Message::SpawnTracer{..}
+            | Message::SpawnImpact{..}
+            | Message::FireProjectile{..}
+            | Message::Explode{..}
+            | Message::SpawnShake{..}
+            | Message::PlaySound{..}
+            | Message::SetWeather{..}
+            | Message::SetDifficulty{..}
+            | Message::SetTimeScale{..}
+            | Message::SpawnEnemy{..}
+            | Message::DialogueStart{..}
+            | Message::DialogueChoose{..}
+            | Message::TradeOffer{..}
+            | Message::TradeResponse{..}
+            | Message::OpenTrade{..}
+            | Message::RunScript{..}
+            | Message::ConsumeItem{..}
+            | Message::Craft{..}
+            | Message::Butcher{..}
+            | Message::OpenContainer{..}
+            | Message::UnlockContainer{..}
+            | Message::SetSpawn{..}
+            | Message::PickPerk{..}
+            | Message::WorldEnded
+            | Message::Autosaving
+            | Message::Snapshot{..}
+            | Message::SnapshotAck{..}
             | Message::RepeatMessage{..} => None
         }
     }
@@ -141,28 +318,43 @@ impl Message
 #[derive(Debug, Clone)]
 pub struct MessageBuffer
 {
-    buffer: Vec<Message>
+    buffer: Vec<Option<Message>>,
+    latest: HashMap<(Entity, Discriminant<Message>), usize>
 }
 
 impl MessageBuffer
 {
     pub fn new() -> Self
     {
-        Self{buffer: Vec::new()}
+        Self{buffer: Vec::new(), latest: HashMap::new()}
     }
 
+    // a newer state for the same entity makes the older one pointless to send
     pub fn set_message(&mut self, message: Message)
     {
-        self.buffer.push(message);
+        if let Some(entity) = message.entity().filter(|_| message.is_full_state())
+        {
+            let key = (entity, mem::discriminant(&message));
+
+            if let Some(previous) = self.latest.insert(key, self.buffer.len())
+            {
+                self.buffer[previous] = None;
+            }
+        }
+
+        self.buffer.push(Some(message));
     }
 
     pub fn clear(&mut self)
     {
         self.buffer.clear();
+        self.latest.clear();
     }
 
     pub fn get_buffered(&mut self) -> Vec<Message>
     {
-        mem::take(&mut self.buffer)
+        self.latest.clear();
+
+        mem::take(&mut self.buffer).into_iter().flatten().collect()
     }
 }
@@ -0,0 +1,156 @@
+use std::{
+    fs::{self, File},
+    path::{Path, PathBuf},
+    collections::HashSet
+};
+
+use serde::Deserialize;
+
+use crate::common::overrides::overridden;
+
+
+// procedures in mod scripts starting with this get called after the event with the same name, like on-take
+pub const HOOK_PREFIX: &str = "on-";
+
+fn default_enabled() -> bool
+{
+    true
+}
+
+// mod.json in the mods folder
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ModInfoRaw
+{
+    name: String,
+    // names of mods that have to be loaded before this one
+    #[serde(default)]
+    dependencies: Vec<String>,
+    // lower loads first, ties get sorted by name
+    #[serde(default)]
+    priority: i32,
+    #[serde(default="default_enabled")]
+    enabled: bool
+}
+
+#[derive(Debug, Clone)]
+pub struct ModInfo
+{
+    pub name: String,
+    pub path: PathBuf
+}
+
+// enabled mods in the order they get loaded in
+#[derive(Debug, Clone)]
+pub struct ModsInfo
+{
+    mods: Vec<ModInfo>
+}
+
+impl ModsInfo
+{
+    pub fn empty() -> Self
+    {
+        Self{mods: Vec::new()}
+    }
+
+    pub fn parse(directory: impl AsRef<Path>) -> Self
+    {
+        let directory = directory.as_ref();
+
+        // no mods folder is the same as no mods
+        let entries = fs::read_dir(directory).into_iter().flatten();
+
+        let mut candidates: Vec<(ModInfoRaw, PathBuf)> = entries.filter_map(|entry|
+        {
+            let path = entry.ok()?.path();
+            let info_path = path.join("mod.json");
+
+            let file = File::open(&info_path).ok()?;
+
+            serde_json::from_reader::<_, ModInfoRaw>(file).map(|info| (info, path)).map_err(|err|
+            {
+                eprintln!("error parsing {}: {err}", info_path.display())
+            }).ok()
+        }).filter(|(info, _)| info.enabled).collect();
+
+        candidates.sort_by(|(a, _), (b, _)| a.priority.cmp(&b.priority).then_with(|| a.name.cmp(&b.name)));
+
+        let mut loaded = HashSet::new();
+        let mut mods = Vec::new();
+
+        // keeps picking the first mod that has everything it needs, whatever is left is broken
+        while let Some(index) = candidates.iter().position(|(info, _)|
+        {
+            info.dependencies.iter().all(|name| loaded.contains(name))
+        })
+        {
+            let (info, path) = candidates.remove(index);
+
+            loaded.insert(info.name.clone());
+            mods.push(ModInfo{name: info.name, path});
+        }
+
+        candidates.into_iter().for_each(|(info, _)|
+        {
+            let missing: Vec<_> = info.dependencies.iter().filter(|name| !loaded.contains(*name)).collect();
+
+            eprintln!("mod {} isnt loaded, missing or circular dependencies: {missing:?}", info.name);
+        });
+
+        mods.iter().for_each(|info| println!("loaded mod {}", info.name));
+
+        Self{mods}
+    }
+
+    pub fn mods(&self) -> &[ModInfo]
+    {
+        &self.mods
+    }
+
+    // the load order decides the item ids so the server and clients have to match exactly
+    pub fn names(&self) -> Vec<String>
+    {
+        self.mods.iter().map(|info| info.name.clone()).collect()
+    }
+
+    // the base file followed by every mods file with this name
+    pub fn data_files(&self, base: impl AsRef<Path>, name: &str) -> Vec<PathBuf>
+    {
+        let mut files = vec![overridden(base)];
+
+        files.extend(self.mods.iter().map(|info| info.path.join(name)).filter(|path| path.exists()));
+
+        files
+    }
+
+    // later mods get to override the earlier ones
+    pub fn override_directories(&self) -> impl Iterator<Item=PathBuf> + '_
+    {
+        self.mods.iter().rev().map(|info| info.path.join("overrides"))
+    }
+
+    pub fn scripts(&self) -> impl Iterator<Item=PathBuf> + '_
+    {
+        self.mods.iter().map(|info| info.path.join("script.scm")).filter(|path| path.exists())
+    }
+}
+
+// later entries with the same name replace earlier ones in place so mods can change existing stuff
+pub fn merge_by_name<T>(infos: impl IntoIterator<Item=T>, name: impl Fn(&T) -> &str) -> Vec<T>
+{
+    let mut merged: Vec<T> = Vec::new();
+
+    infos.into_iter().for_each(|info|
+    {
+        if let Some(index) = merged.iter().position(|x| name(x) == name(&info))
+        {
+            merged[index] = info;
+        } else
+        {
+            merged.push(info);
+        }
+    });
+
+    merged
+}
@@ -0,0 +1,85 @@
+use serde::{Serialize, Deserialize};
+
+
+// fraction lost per second, so about 40 minutes from full to empty
+const HUNGER_DECAY: f32 = 1.0 / 2400.0;
+// thirst goes faster than hunger
+const THIRST_DECAY: f32 = 1.0 / 1500.0;
+
+// below this the penalties start kicking in
+const STARVING: f32 = 0.2;
+
+// at completely empty
+const MIN_STAMINA_REGEN: f32 = 0.3;
+const MIN_MAX_STAMINA: f32 = 0.5;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Food
+{
+    pub hunger: f32,
+    pub thirst: f32
+}
+
+impl Default for Food
+{
+    fn default() -> Self
+    {
+        Self{hunger: 0.0, thirst: 0.0}
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Needs
+{
+    // 1.0 is full, 0.0 is starving to death
+    pub hunger: f32,
+    pub thirst: f32
+}
+
+impl Default for Needs
+{
+    fn default() -> Self
+    {
+        Self{hunger: 1.0, thirst: 1.0}
+    }
+}
+
+impl Needs
+{
+    // rate comes from the difficulty, 1 is normal
+    pub fn update(&mut self, rate: f32, dt: f32)
+    {
+        self.hunger = (self.hunger - HUNGER_DECAY * rate * dt).max(0.0);
+        self.thirst = (self.thirst - THIRST_DECAY * rate * dt).max(0.0);
+    }
+
+    pub fn consume(&mut self, food: &Food)
+    {
+        self.hunger = (self.hunger + food.hunger).clamp(0.0, 1.0);
+        self.thirst = (self.thirst + food.thirst).clamp(0.0, 1.0);
+    }
+
+    pub fn is_starving(&self) -> bool
+    {
+        self.hunger < STARVING || self.thirst < STARVING
+    }
+
+    // 0 when not starving, 1 when completely empty
+    fn starvation(&self) -> f32
+    {
+        let lowest = self.hunger.min(self.thirst);
+
+        (1.0 - lowest / STARVING).clamp(0.0, 1.0)
+    }
+
+    pub fn stamina_regen_scale(&self) -> f32
+    {
+        1.0 - self.starvation() * (1.0 - MIN_STAMINA_REGEN)
+    }
+
+    pub fn max_stamina_scale(&self) -> f32
+    {
+        1.0 - self.starvation() * (1.0 - MIN_MAX_STAMINA)
+    }
+}
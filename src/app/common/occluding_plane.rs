@@ -61,6 +61,14 @@ impl ClientOccluder
         [top, bottom, right]
     }
 
+    pub fn transforms(&self) -> impl Iterator<Item=&Transform>
+    {
+        match self
+        {
+            Self::Door(planes) => planes.iter().map(|x| x.transform())
+        }
+    }
+
     pub fn set_transform(&mut self, transform: Transform)
     {
         match self
@@ -175,6 +183,11 @@ impl OccludingPlane
         self.0.set_transform(transform);
     }
 
+    pub fn transform(&self) -> &Transform
+    {
+        self.0.transform_ref()
+    }
+
     pub fn visible(&self, visibility: &VisibilityChecker) -> bool
     {
         Self::visible_with(visibility, self.0.transform_ref())
@@ -3,9 +3,78 @@ use serde::{Serialize, Deserialize};
 use crate::common::EaseOut;
 
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OutlinePulse
+{
+    Steady,
+    Breathe,
+    Blink
+}
+
+impl OutlinePulse
+{
+    // animation goes from -1 to 1
+    pub fn amount(&self, animation: f32) -> f32
+    {
+        match self
+        {
+            Self::Steady => 1.0,
+            Self::Breathe => 0.7 + animation * 0.3,
+            Self::Blink =>
+            {
+                if animation > 0.0
+                {
+                    1.0
+                } else
+                {
+                    0.2
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OutlineStyle
+{
+    // none is the old rainbow one
+    pub color: Option<[f32; 3]>,
+    // in texture pixels
+    pub thickness: f32,
+    pub pulse: OutlinePulse
+}
+
+impl Default for OutlineStyle
+{
+    fn default() -> Self
+    {
+        Self{color: None, thickness: 1.0, pulse: OutlinePulse::Steady}
+    }
+}
+
+impl OutlineStyle
+{
+    pub fn loot() -> Self
+    {
+        Self{color: Some([1.0; 3]), ..Default::default()}
+    }
+
+    pub fn enemy() -> Self
+    {
+        Self{color: Some([0.9, 0.1, 0.1]), thickness: 1.5, pulse: OutlinePulse::Breathe}
+    }
+
+    pub fn quest() -> Self
+    {
+        Self{color: Some([1.0, 0.8, 0.2]), thickness: 2.0, pulse: OutlinePulse::Breathe}
+    }
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct Outlineable
 {
+    pub style: OutlineStyle,
     current: f32,
     target: f32
 }
@@ -14,12 +83,17 @@ impl Default for Outlineable
 {
     fn default() -> Self
     {
-        Self{current: 0.0, target: 0.0}
+        Self::new(OutlineStyle::default())
     }
 }
 
 impl Outlineable
 {
+    pub fn new(style: OutlineStyle) -> Self
+    {
+        Self{style, current: 0.0, target: 0.0}
+    }
+
     pub fn enable(&mut self)
     {
         self.target = 1.0;
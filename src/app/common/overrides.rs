@@ -0,0 +1,29 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::OnceLock
+};
+
+
+// mirrors the game folder, anything in here gets used instead of the built in file with the same path
+pub const USER_OVERRIDES: &str = "overrides";
+
+static OVERRIDE_DIRECTORIES: OnceLock<Vec<PathBuf>> = OnceLock::new();
+
+// earlier directories win
+pub fn init(directories: Vec<PathBuf>)
+{
+    if OVERRIDE_DIRECTORIES.set(directories).is_err()
+    {
+        eprintln!("override directories are already set");
+    }
+}
+
+pub fn overridden(path: impl AsRef<Path>) -> PathBuf
+{
+    let path = path.as_ref();
+
+    OVERRIDE_DIRECTORIES.get().into_iter().flatten().map(|directory|
+    {
+        directory.join(path)
+    }).find(|x| x.exists()).unwrap_or_else(|| path.to_owned())
+}
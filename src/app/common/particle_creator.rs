@@ -9,8 +9,11 @@ use yanyaengine::Transform;
 use crate::common::{
     random_rotation,
     random_f32,
+    render_info::*,
+    rng::{self, RngStream},
     lazy_transform::*,
     watcher::*,
+    Curve,
     AnyEntities,
     Entity,
     EntityInfo
@@ -106,6 +109,60 @@ impl ParticleScale
     }
 }
 
+// how many times the curves get sampled over a particles lifetime
+const CURVE_STEPS: usize = 8;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParticleCurves
+{
+    pub lifetime: RangeInclusive<f32>,
+    pub color: Option<Curve<[f32; 3]>>,
+    pub scale: Option<Curve<f32>>
+}
+
+impl ParticleCurves
+{
+    fn watchers(&self, scale: Vector3<f32>) -> Vec<Watcher>
+    {
+        let lifetime = random_f32(self.lifetime.clone());
+
+        let mut watchers: Vec<Watcher> = (0..=CURVE_STEPS).flat_map(|step|
+        {
+            let fraction = step as f32 / CURVE_STEPS as f32;
+
+            let kind = || WatcherType::Lifetime((lifetime * fraction).max(f32::EPSILON).into());
+
+            let color = self.color.as_ref().map(|color|
+            {
+                Watcher{
+                    kind: kind(),
+                    action: WatcherAction::SetMixColor(Some(MixColor{
+                        color: color.sample(fraction),
+                        amount: 1.0,
+                        keep_transparency: true
+                    })),
+                    ..Default::default()
+                }
+            });
+
+            let scale = self.scale.as_ref().map(|curve|
+            {
+                Watcher{
+                    kind: kind(),
+                    action: WatcherAction::SetTargetScale(scale * curve.sample(fraction)),
+                    ..Default::default()
+                }
+            });
+
+            color.into_iter().chain(scale)
+        }).collect();
+
+        watchers.push(Watcher::simple_disappearing(lifetime));
+
+        watchers
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ParticlesInfo
 {
@@ -115,7 +172,8 @@ pub struct ParticlesInfo
     pub position: ParticlePosition,
     pub rotation: ParticleRotation,
     pub scale: ParticleScale,
-    pub min_scale: f32
+    pub min_scale: f32,
+    pub curves: Option<ParticleCurves>
 }
 
 pub struct ParticleCreator
@@ -131,21 +189,30 @@ impl ParticleCreator
         mut prototype: EntityInfo
     )
     {
-        prototype.watchers = Some(Watchers::new(vec![
-            Watcher{
-                kind: WatcherType::Instant,
-                action: WatcherAction::SetTargetScale(Vector3::zeros()),
-                ..Default::default()
-            },
-            Watcher{
-                kind: WatcherType::ScaleDistance{
-                    from: Vector3::zeros(),
-                    near: info.min_scale
+        // particles without a scale curve just shrink until theyre gone
+        let shrinking = info.curves.as_ref().map(|x| x.scale.is_none()).unwrap_or(true);
+
+        let shrink_watchers = if shrinking
+        {
+            vec![
+                Watcher{
+                    kind: WatcherType::Instant,
+                    action: WatcherAction::SetTargetScale(Vector3::zeros()),
+                    ..Default::default()
                 },
-                action: WatcherAction::Remove,
-                ..Default::default()
-            }
-        ]));
+                Watcher{
+                    kind: WatcherType::ScaleDistance{
+                        from: Vector3::zeros(),
+                        near: info.min_scale
+                    },
+                    action: WatcherAction::Remove,
+                    ..Default::default()
+                }
+            ]
+        } else
+        {
+            Vec::new()
+        };
 
         let position;
         let scale;
@@ -158,14 +225,25 @@ impl ParticleCreator
 
         let parent_velocity = entities.physical(entity).map(|x| *x.velocity());
 
-        let amount = fastrand::usize(info.amount);
+        let amount = rng::usize(RngStream::Cosmetic, info.amount);
         (0..amount).for_each(|_|
         {
             let mut prototype = prototype.clone();
+
+            let particle_scale = info.scale.get();
+
+            let mut watchers = shrink_watchers.clone();
+            if let Some(curves) = info.curves.as_ref()
+            {
+                watchers.extend(curves.watchers(particle_scale));
+            }
+
+            prototype.watchers = Some(Watchers::new(watchers));
+
             prototype.lazy_transform = Some(LazyTransformInfo{
                 scaling: Scaling::EaseOut{decay: info.decay.get()},
                 transform: Transform{
-                    scale: info.scale.get(),
+                    scale: particle_scale,
                     ..Default::default()
                 },
                 ..Default::default()
@@ -182,7 +260,7 @@ impl ParticleCreator
                     {
                         let r = ||
                         {
-                            2.0 * fastrand::f32()
+                            2.0 * rng::f32(RngStream::Cosmetic)
                         };
 
                         let offset = scale - Vector3::new(scale.x * r(), scale.y * r(), 0.0);
@@ -0,0 +1,218 @@
+use std::{
+    fs::File,
+    path::Path,
+    collections::HashMap
+};
+
+use nalgebra::{Unit, Vector3};
+
+use serde::Deserialize;
+
+use yanyaengine::{Assets, TextureId};
+
+use crate::common::{
+    ENTITY_SCALE,
+    render_info::*,
+    particle_creator::*,
+    watcher::ExplodeInfo,
+    overrides::overridden,
+    Curve,
+    EntityInfo,
+    PhysicalProperties
+};
+
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub enum EmitterShape
+{
+    Point,
+    // spread over the emitting entity, scaled by the value
+    Area(f32)
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub enum EffectSpeed
+{
+    Random(f32),
+    // inclusive on both ends, direction comes from whoever spawns the effect
+    Directional{speed: (f32, f32), spread: f32}
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub enum EffectRotation
+{
+    Random,
+    Direction
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ParticleEffectInfoRaw
+{
+    pub texture: String,
+    pub z_level: ZLevel,
+    // inclusive on both ends
+    pub amount: (usize, usize),
+    pub emitter: EmitterShape,
+    pub speed: EffectSpeed,
+    pub rotation: EffectRotation,
+    pub decay: (f32, f32),
+    // relative to the entity scale
+    pub scale: [f32; 3],
+    #[serde(default)]
+    pub scale_variation: f32,
+    pub min_scale: f32,
+    pub mass: f32,
+    // only needed if theres any curves
+    pub lifetime: Option<(f32, f32)>,
+    pub color: Option<Curve<[f32; 3]>>,
+    pub scale_curve: Option<Curve<f32>>
+}
+
+#[derive(Debug, Clone)]
+pub struct ParticleEffectInfo
+{
+    pub texture: TextureId,
+    pub z_level: ZLevel,
+    pub amount: (usize, usize),
+    pub emitter: EmitterShape,
+    pub speed: EffectSpeed,
+    pub rotation: EffectRotation,
+    pub decay: (f32, f32),
+    pub scale: Vector3<f32>,
+    pub scale_variation: f32,
+    pub min_scale: f32,
+    pub mass: f32,
+    pub curves: Option<ParticleCurves>
+}
+
+impl ParticleEffectInfo
+{
+    fn from_raw(assets: &Assets, name: &str, raw: ParticleEffectInfoRaw) -> Self
+    {
+        let has_curves = raw.color.is_some() || raw.scale_curve.is_some();
+
+        let curves = has_curves.then(||
+        {
+            let (start, end) = raw.lifetime.unwrap_or_else(||
+            {
+                panic!("particle effect {name} has curves but no lifetime")
+            });
+
+            ParticleCurves{
+                lifetime: start..=end,
+                color: raw.color.map(Curve::sorted),
+                scale: raw.scale_curve.map(Curve::sorted)
+            }
+        });
+
+        Self{
+            texture: assets.texture_id(&raw.texture),
+            z_level: raw.z_level,
+            amount: raw.amount,
+            emitter: raw.emitter,
+            speed: raw.speed,
+            rotation: raw.rotation,
+            decay: raw.decay,
+            scale: Vector3::from(raw.scale) * ENTITY_SCALE,
+            scale_variation: raw.scale_variation,
+            min_scale: raw.min_scale * ENTITY_SCALE,
+            mass: raw.mass,
+            curves
+        }
+    }
+
+    pub fn particles(&self, direction: Unit<Vector3<f32>>) -> ParticlesInfo
+    {
+        let speed = match self.speed
+        {
+            EffectSpeed::Random(speed) => ParticleSpeed::Random(speed),
+            EffectSpeed::Directional{speed: (low, high), spread} =>
+            {
+                ParticleSpeed::DirectionSpread{direction, speed: low..=high, spread}
+            }
+        };
+
+        let position = match self.emitter
+        {
+            EmitterShape::Point => ParticlePosition::Exact,
+            EmitterShape::Area(mult) => ParticlePosition::Spread(mult)
+        };
+
+        let rotation = match self.rotation
+        {
+            EffectRotation::Random => ParticleRotation::Random,
+            EffectRotation::Direction => ParticleRotation::Exact(direction.y.atan2(direction.x))
+        };
+
+        let (decay_low, decay_high) = self.decay;
+
+        ParticlesInfo{
+            amount: self.amount.0..(self.amount.1 + 1),
+            speed,
+            decay: ParticleDecay::Random(decay_low..=decay_high),
+            position,
+            rotation,
+            scale: ParticleScale::Spread{scale: self.scale, variation: self.scale_variation},
+            min_scale: self.min_scale,
+            curves: self.curves.clone()
+        }
+    }
+
+    pub fn prototype(&self) -> EntityInfo
+    {
+        EntityInfo{
+            physical: Some(PhysicalProperties{
+                inverse_mass: self.mass.recip(),
+                floating: true,
+                ..Default::default()
+            }.into()),
+            render: Some(RenderInfo{
+                object: Some(RenderObjectKind::TextureId{
+                    id: self.texture
+                }.into()),
+                z_level: self.z_level,
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    pub fn explode(&self, keep: bool, direction: Unit<Vector3<f32>>) -> ExplodeInfo
+    {
+        ExplodeInfo{
+            keep,
+            info: self.particles(direction),
+            prototype: self.prototype()
+        }
+    }
+}
+
+pub struct ParticleEffects
+{
+    effects: HashMap<String, ParticleEffectInfo>
+}
+
+impl ParticleEffects
+{
+    pub fn parse(assets: &Assets, info: impl AsRef<Path>) -> Self
+    {
+        let info = File::open(overridden(info)).unwrap();
+
+        let effects: HashMap<String, ParticleEffectInfoRaw> = serde_json::from_reader(info).unwrap();
+
+        let effects = effects.into_iter().map(|(name, raw)|
+        {
+            let effect = ParticleEffectInfo::from_raw(assets, &name, raw);
+
+            (name, effect)
+        }).collect();
+
+        Self{effects}
+    }
+
+    pub fn get(&self, name: &str) -> &ParticleEffectInfo
+    {
+        self.effects.get(name).unwrap_or_else(|| panic!("particle effect named {name} doesnt exist"))
+    }
+}
@@ -0,0 +1,437 @@
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet, BinaryHeap}
+};
+
+use nalgebra::{Unit, Vector3};
+
+use crate::common::{
+    some_or_return,
+    collider::ColliderLayer,
+    AnyEntities,
+    world::{World, Pos3, TILE_SIZE}
+};
+
+
+// stops looking after this many tiles so unreachable goals dont eat the whole frame
+const MAX_SEARCHED: usize = 1024;
+
+const STRAIGHT_COST: u32 = 10;
+const DIAGONAL_COST: u32 = 14;
+
+// how often a path gets found again even if the goal didnt move, also how long a failed one is remembered
+const REPATH_INTERVAL: f32 = 1.0;
+
+// furniture further than this cant be reached before the search gives up anyway
+const FURNITURE_DISTANCE: f32 = TILE_SIZE * 24.0;
+
+const WAYPOINT_ARRIVE_DISTANCE: f32 = TILE_SIZE * 0.3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Node
+{
+    cost: u32,
+    pos: Pos3<i32>
+}
+
+impl PartialOrd for Node
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering>
+    {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Node
+{
+    // reversed so the heap pops the cheapest one first
+    fn cmp(&self, other: &Self) -> Ordering
+    {
+        other.cost.cmp(&self.cost)
+    }
+}
+
+fn heuristic(a: Pos3<i32>, b: Pos3<i32>) -> u32
+{
+    let dx = (a.x - b.x).unsigned_abs();
+    let dy = (a.y - b.y).unsigned_abs();
+
+    let (short, long) = if dx < dy { (dx, dy) } else { (dy, dx) };
+
+    short * DIAGONAL_COST + (long - short) * STRAIGHT_COST
+}
+
+// tiles covered by furniture around the start, relative to it
+// doors arent furniture, physics pushes them open
+fn furniture_tiles(entities: &impl AnyEntities, world: &World, start: Vector3<f32>) -> HashSet<Pos3<i32>>
+{
+    let start_tile = world.tile_of(start.into());
+
+    let mut blocked = HashSet::new();
+
+    entities.colliders().into_iter().for_each(|entity|
+    {
+        let is_furniture = entities.collider(entity).map(|collider|
+        {
+            collider.layer == ColliderLayer::NORMAL && !collider.ghost
+        }).unwrap_or(false);
+
+        if !is_furniture || entities.character_exists(entity)
+        {
+            return;
+        }
+
+        let transform = some_or_return!(entities.transform(entity));
+
+        if (transform.position - start).xy().magnitude() > FURNITURE_DISTANCE
+        {
+            return;
+        }
+
+        let half = transform.scale.xy() / 2.0;
+
+        let corner = |x: f32, y: f32|
+        {
+            let position = Vector3::new(transform.position.x + x, transform.position.y + y, start.z);
+
+            start_tile.distance(world.tile_of(position.into()))
+        };
+
+        let low = corner(-half.x, -half.y);
+        let high = corner(half.x, half.y);
+
+        (low.x..=high.x).for_each(|x|
+        {
+            (low.y..=high.y).for_each(|y|
+            {
+                blocked.insert(Pos3::new(x, y, 0));
+            });
+        });
+    });
+
+    blocked
+}
+
+// a* from zero to the goal, gives back every position along the way without the start
+pub fn astar(passable: impl Fn(Pos3<i32>) -> bool, goal_pos: Pos3<i32>) -> Option<Vec<Pos3<i32>>>
+{
+    let zero = Pos3::new(0, 0, 0);
+
+    if !passable(goal_pos)
+    {
+        return None;
+    }
+
+    let mut open = BinaryHeap::from([Node{cost: heuristic(zero, goal_pos), pos: zero}]);
+
+    let mut came_from: HashMap<Pos3<i32>, Pos3<i32>> = HashMap::new();
+    let mut costs: HashMap<Pos3<i32>, u32> = HashMap::from([(zero, 0)]);
+
+    while let Some(Node{pos, ..}) = open.pop()
+    {
+        if pos == goal_pos
+        {
+            let mut path = vec![pos];
+
+            let mut current = pos;
+            while let Some(previous) = came_from.get(&current)
+            {
+                current = *previous;
+
+                if current != zero
+                {
+                    path.push(current);
+                }
+            }
+
+            path.reverse();
+
+            return Some(path);
+        }
+
+        if costs.len() > MAX_SEARCHED
+        {
+            return None;
+        }
+
+        let current_cost = costs[&pos];
+
+        for (dx, dy) in [(1, 0), (-1, 0), (0, 1), (0, -1), (1, 1), (1, -1), (-1, 1), (-1, -1)]
+        {
+            let next = Pos3::new(pos.x + dx, pos.y + dy, 0);
+
+            if !passable(next)
+            {
+                continue;
+            }
+
+            let diagonal = dx != 0 && dy != 0;
+
+            // dont cut corners through walls
+            if diagonal
+            {
+                let clear = passable(Pos3::new(pos.x + dx, pos.y, 0))
+                    && passable(Pos3::new(pos.x, pos.y + dy, 0));
+
+                if !clear
+                {
+                    continue;
+                }
+            }
+
+            let cost = current_cost + if diagonal { DIAGONAL_COST } else { STRAIGHT_COST };
+
+            if costs.get(&next).map(|x| cost < *x).unwrap_or(true)
+            {
+                costs.insert(next, cost);
+                came_from.insert(next, pos);
+
+                open.push(Node{cost: cost + heuristic(next, goal_pos), pos: next});
+            }
+        }
+    }
+
+    None
+}
+
+// over the tiles on the same floor going around furniture, gives back the middle of every tile along the way
+pub fn find_path(
+    entities: &impl AnyEntities,
+    world: &World,
+    start: Vector3<f32>,
+    goal: Vector3<f32>
+) -> Option<Vec<Vector3<f32>>>
+{
+    let start_tile = world.tile_of(start.into());
+
+    let mut goal_pos = start_tile.distance(world.tile_of(goal.into()));
+    goal_pos.z = 0;
+
+    let furniture = furniture_tiles(entities, world, start);
+
+    let passable = |pos: Pos3<i32>|
+    {
+        let tile_passable = world.tile(start_tile.offset(pos)).map(|tile|
+        {
+            !world.tile_info(*tile).colliding
+        }).unwrap_or(false);
+
+        // whatever is being chased is usually standing right next to something
+        tile_passable && (pos == goal_pos || !furniture.contains(&pos))
+    };
+
+    astar(passable, goal_pos).map(|path|
+    {
+        path.into_iter().map(|pos| start_tile.offset(pos).entity_position()).collect()
+    })
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PathCache
+{
+    goal: Option<Vector3<f32>>,
+    // next waypoint is at the end
+    waypoints: Vec<Vector3<f32>>,
+    // no point looking again every frame if theres no way there
+    failed: bool,
+    repath_timer: f32
+}
+
+impl PathCache
+{
+    pub fn clear(&mut self)
+    {
+        self.goal = None;
+        self.waypoints.clear();
+        self.failed = false;
+    }
+
+    // which way to walk to get to the goal, straight at it if nothing is in the way
+    pub fn direction(
+        &mut self,
+        entities: &impl AnyEntities,
+        world: &World,
+        position: Vector3<f32>,
+        goal: Vector3<f32>,
+        dt: f32
+    ) -> Option<Unit<Vector3<f32>>>
+    {
+        let direct = || Unit::try_new(goal - position, 0.01);
+
+        if world.tile_hit(position, goal).is_none()
+        {
+            self.clear();
+
+            return direct();
+        }
+
+        self.repath_timer -= dt;
+
+        let goal_moved = self.goal.map(|x| (x - goal).xy().magnitude() > TILE_SIZE).unwrap_or(true);
+
+        let outdated = (goal_moved || self.waypoints.is_empty()) && !self.failed;
+
+        if outdated || self.repath_timer <= 0.0
+        {
+            self.repath_timer = REPATH_INTERVAL;
+            self.goal = Some(goal);
+
+            let path = find_path(entities, world, position, goal);
+
+            self.failed = path.is_none();
+            self.waypoints = path.map(|mut path|
+            {
+                path.reverse();
+
+                path
+            }).unwrap_or_default();
+        }
+
+        // skip ahead to whatever can already be walked to directly
+        while self.waypoints.len() > 1
+        {
+            let next = self.waypoints[self.waypoints.len() - 2];
+
+            let arrived = (self.waypoints.last().unwrap() - position).xy().magnitude()
+                < WAYPOINT_ARRIVE_DISTANCE;
+
+            if arrived || world.tile_hit(position, next).is_none()
+            {
+                self.waypoints.pop();
+            } else
+            {
+                break;
+            }
+        }
+
+        self.waypoints.last().and_then(|waypoint|
+        {
+            Unit::try_new(Vector3::new(waypoint.x - position.x, waypoint.y - position.y, 0.0), 0.01)
+        }).or_else(direct)
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+
+    // # is a wall, s is the start, g is the goal
+    fn grid(rows: &[&str]) -> (impl Fn(Pos3<i32>) -> bool, Pos3<i32>)
+    {
+        let find = |c: char|
+        {
+            rows.iter().enumerate().find_map(|(y, row)|
+            {
+                row.chars().position(|x| x == c).map(|x| (x as i32, y as i32))
+            }).unwrap()
+        };
+
+        let (start_x, start_y) = find('s');
+        let (goal_x, goal_y) = find('g');
+
+        let walls: HashSet<Pos3<i32>> = rows.iter().enumerate().flat_map(|(y, row)|
+        {
+            row.chars().enumerate().filter(|(_, c)| *c == '#').map(move |(x, _)|
+            {
+                Pos3::new(x as i32 - start_x, y as i32 - start_y, 0)
+            })
+        }).collect();
+
+        let width = rows[0].len() as i32;
+        let height = rows.len() as i32;
+
+        let passable = move |pos: Pos3<i32>|
+        {
+            let x = pos.x + start_x;
+            let y = pos.y + start_y;
+
+            (0..width).contains(&x) && (0..height).contains(&y) && !walls.contains(&pos)
+        };
+
+        (passable, Pos3::new(goal_x - start_x, goal_y - start_y, 0))
+    }
+
+    fn assert_walkable(passable: &impl Fn(Pos3<i32>) -> bool, path: &[Pos3<i32>])
+    {
+        path.iter().fold(Pos3::new(0, 0, 0), |previous, pos|
+        {
+            let (dx, dy) = (pos.x - previous.x, pos.y - previous.y);
+
+            assert!(dx.abs() <= 1 && dy.abs() <= 1, "{previous:?} to {pos:?} isnt a single step");
+            assert!(passable(*pos), "walked through a wall at {pos:?}");
+
+            if dx != 0 && dy != 0
+            {
+                assert!(
+                    passable(Pos3::new(previous.x + dx, previous.y, 0))
+                        && passable(Pos3::new(previous.x, previous.y + dy, 0)),
+                    "cut a corner at {pos:?}"
+                );
+            }
+
+            *pos
+        });
+    }
+
+    #[test]
+    fn straight_line()
+    {
+        let (passable, goal) = grid(&["s....g"]);
+
+        let path = astar(&passable, goal).unwrap();
+
+        assert_eq!(path.len(), 5);
+        assert_eq!(path.last(), Some(&goal));
+
+        assert_walkable(&passable, &path);
+    }
+
+    #[test]
+    fn around_a_wall()
+    {
+        let (passable, goal) = grid(&[
+            ".....",
+            ".###.",
+            "s#.#g",
+            ".#.#.",
+            "....."
+        ]);
+
+        let path = astar(&passable, goal).unwrap();
+
+        assert_eq!(path.last(), Some(&goal));
+        assert_walkable(&passable, &path);
+
+        // all the way around the top or the bottom, the wall corners block every diagonal
+        assert_eq!(path.len(), 8);
+    }
+
+    #[test]
+    fn no_corner_cutting()
+    {
+        let (passable, goal) = grid(&[
+            "s#",
+            "#g"
+        ]);
+
+        assert!(astar(&passable, goal).is_none());
+    }
+
+    #[test]
+    fn unreachable()
+    {
+        let (passable, goal) = grid(&[
+            "s.#..",
+            "..#.g",
+            "..#.."
+        ]);
+
+        assert!(astar(&passable, goal).is_none());
+
+        let (passable, goal) = grid(&["s#g"]);
+
+        assert!(astar(&passable, goal).is_none());
+    }
+}
@@ -0,0 +1,118 @@
+use std::{
+    fs::File,
+    path::Path
+};
+
+use serde::Deserialize;
+
+use crate::common::{
+    generic_info::*,
+    overrides::overridden,
+    rng::{self, RngStream},
+    Anatomy,
+    Player
+};
+
+
+#[derive(Debug, Clone, Deserialize)]
+pub enum PerkEffect
+{
+    Speed(f32),
+    Strength(f32),
+    Charisma(f32),
+    Unlock(String)
+}
+
+impl PerkEffect
+{
+    // anatomy isnt saved with the player so this gets reapplied on every load
+    pub fn apply_anatomy(&self, anatomy: &mut Anatomy)
+    {
+        match self
+        {
+            Self::Speed(amount) =>
+            {
+                let speed = anatomy.base_speed() * (1.0 + amount);
+                anatomy.set_speed(speed);
+            },
+            Self::Strength(amount) =>
+            {
+                let strength = anatomy.base_strength() * (1.0 + amount);
+                anatomy.set_strength(strength);
+            },
+            Self::Charisma(_)
+            | Self::Unlock(_) => ()
+        }
+    }
+
+    pub fn apply_player(&self, player: &mut Player)
+    {
+        match self
+        {
+            Self::Charisma(amount) => player.charisma += amount,
+            Self::Unlock(name) => player.unlocks.push(name.clone()),
+            Self::Speed(_)
+            | Self::Strength(_) => ()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PerkInfo
+{
+    pub name: String,
+    pub description: String,
+    pub effects: Vec<PerkEffect>
+}
+
+impl GenericItem for PerkInfo
+{
+    fn name(&self) -> String
+    {
+        self.name.clone()
+    }
+}
+
+impl PerkInfo
+{
+    pub fn apply_anatomy(&self, anatomy: &mut Anatomy)
+    {
+        self.effects.iter().for_each(|effect| effect.apply_anatomy(anatomy));
+    }
+
+    pub fn apply_player(&self, player: &mut Player)
+    {
+        self.effects.iter().for_each(|effect| effect.apply_player(player));
+    }
+}
+
+define_info_id!{PerkId}
+
+pub type PerksInfo = GenericInfo<PerkId, PerkInfo>;
+
+impl PerksInfo
+{
+    pub fn parse(info: impl AsRef<Path>) -> Self
+    {
+        let info = File::open(overridden(info)).unwrap();
+
+        let perks: Vec<PerkInfo> = serde_json::from_reader(info).unwrap();
+
+        GenericInfo::new(perks)
+    }
+
+    // perks the player doesnt have yet
+    pub fn choices(&self, player: &Player, amount: usize) -> Vec<PerkId>
+    {
+        let mut available: Vec<PerkId> = (0..self.items().len()).map(PerkId::from).filter(|id|
+        {
+            !player.perks.contains(&self.get(*id).name)
+        }).collect();
+
+        rng::shuffle(RngStream::Loot, &mut available);
+        available.truncate(amount);
+
+        available
+    }
+}
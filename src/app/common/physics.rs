@@ -15,6 +15,11 @@ use crate::{
 
 pub const GRAVITY: Vector3<f32> = Vector3::new(0.0, 0.0, -9.81 * ENTITY_SCALE);
 pub const MAX_VELOCITY: f32 = 10.0;
+
+// relative to water, anything under 1 floats
+pub const DEFAULT_DENSITY: f32 = 1.5;
+pub const BODY_DENSITY: f32 = 0.95;
+
 const SLEEP_THRESHOLD: f32 = 0.03;
 const MOVEMENT_BIAS: f32 = 0.8;
 
@@ -47,6 +52,7 @@ pub struct PhysicalProperties
     pub dynamic_friction: f32,
     pub can_sleep: bool,
     pub floating: bool,
+    pub density: f32,
     pub fixed: PhysicalFixed,
     pub target_non_lazy: bool,
     pub move_z: bool
@@ -65,6 +71,7 @@ impl Default for PhysicalProperties
             angular_damping: 0.4,
             can_sleep: true,
             floating: false,
+            density: DEFAULT_DENSITY,
             fixed: PhysicalFixed::default(),
             target_non_lazy: false,
             move_z: true
@@ -72,6 +79,11 @@ impl Default for PhysicalProperties
     }
 }
 
+fn default_density() -> f32
+{
+    DEFAULT_DENSITY
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Physical
 {
@@ -82,6 +94,8 @@ pub struct Physical
     pub fixed: PhysicalFixed,
     pub target_non_lazy: bool,
     pub move_z: bool,
+    #[serde(default = "default_density")]
+    pub density: f32,
     floating: bool,
     can_sleep: bool,
     sleeping: bool,
@@ -107,6 +121,7 @@ impl From<PhysicalProperties> for Physical
             static_friction: props.static_friction,
             dynamic_friction: props.dynamic_friction,
             floating: props.floating,
+            density: props.density,
             fixed: props.fixed,
             target_non_lazy: props.target_non_lazy,
             move_z: props.move_z,
@@ -139,6 +154,7 @@ impl Physical
             dynamic_friction: self.dynamic_friction,
             can_sleep: self.can_sleep,
             floating: self.floating,
+            density: self.density,
             fixed: self.fixed,
             target_non_lazy: self.target_non_lazy,
             move_z: self.move_z
@@ -221,11 +237,12 @@ impl Physical
         self.sleep_movement = bias * self.sleep_movement + (1.0 - bias) * new_movement;
 
         self.sleep_movement = self.sleep_movement.min(SLEEP_MOVEMENT_MAX);
+    }
 
-        if self.sleep_movement < SLEEP_THRESHOLD
-        {
-            self.set_sleeping(true);
-        }
+    // wants to sleep, it only actually falls asleep once everything its touching wants to too
+    pub fn resting(&self) -> bool
+    {
+        self.can_sleep && self.sleep_movement < SLEEP_THRESHOLD
     }
 
     pub fn set_sleeping(&mut self, state: bool)
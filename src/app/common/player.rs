@@ -1,5 +1,79 @@
 use serde::{Serialize, Deserialize};
 
+use nalgebra::Vector3;
+
+use crate::common::Reputation;
+
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Player;
+pub struct Player
+{
+    pub reputation: Reputation,
+    pub charisma: f32,
+    pub experience: f32,
+    pub level: u32,
+    pub perk_points: u32,
+    pub perks: Vec<String>,
+    pub unlocks: Vec<String>,
+    // hints and tutorial steps that already got shown
+    #[serde(default)]
+    pub hints_seen: Vec<String>,
+    // the last bed slept in, the world spawn point if none
+    #[serde(default)]
+    pub spawn: Option<Vector3<f32>>
+}
+
+impl Default for Player
+{
+    fn default() -> Self
+    {
+        Self{
+            reputation: Reputation::default(),
+            charisma: 0.0,
+            experience: 0.0,
+            level: 1,
+            perk_points: 0,
+            perks: Vec::new(),
+            unlocks: Vec::new(),
+            hints_seen: Vec::new(),
+            spawn: None
+        }
+    }
+}
+
+impl Player
+{
+    pub fn experience_needed(&self) -> f32
+    {
+        self.level as f32 * 100.0
+    }
+
+    // returns true if leveled up
+    pub fn add_experience(&mut self, amount: f32) -> bool
+    {
+        self.experience += amount;
+
+        let mut leveled = false;
+        while self.experience >= self.experience_needed()
+        {
+            self.experience -= self.experience_needed();
+
+            self.level += 1;
+            self.perk_points += 1;
+
+            leveled = true;
+        }
+
+        leveled
+    }
+
+    pub fn has_unlock(&self, name: &str) -> bool
+    {
+        self.unlocks.iter().any(|x| x == name)
+    }
+
+    pub fn seen_hint(&self, name: &str) -> bool
+    {
+        self.hints_seen.iter().any(|x| x == name)
+    }
+}
@@ -0,0 +1,440 @@
+use serde::{Serialize, Deserialize};
+
+use nalgebra::{Unit, Vector3};
+
+use yanyaengine::Transform;
+
+use crate::common::{
+    some_or_return,
+    damage::*,
+    damaging::*,
+    raycast::*,
+    watcher::*,
+    collider::ColliderLayer,
+    physics::PhysicalProperties,
+    ExplosionInfo,
+    ParticleEffects,
+    AnyEntities,
+    Entity,
+    EntityInfo,
+    Faction,
+    Physical,
+    entity::ClientEntities,
+    world::{TILE_SIZE, TileHit}
+};
+
+
+// how long hitscan tracers stay on screen
+const TRACER_DURATION: f32 = 0.08;
+
+// slower than this and its just lying on the floor
+const MIN_SPEED: f32 = TILE_SIZE;
+
+const EXPLOSION_EFFECTS: [&str; 2] = ["explosion_flash", "smoke"];
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SimulatedInfo
+{
+    pub speed: f32,
+    // pulls it down towards the floor
+    pub gravity: f32,
+    // fraction of the speed kept every second
+    pub drag: f32,
+    pub ricochets: u32,
+    // biggest angle from the surface that still bounces off
+    pub ricochet_angle: f32,
+    // fraction of the speed kept after a bounce
+    pub ricochet_speed: f32,
+    pub lifetime: f32
+}
+
+impl Default for SimulatedInfo
+{
+    fn default() -> Self
+    {
+        Self{
+            speed: TILE_SIZE * 60.0,
+            gravity: 0.0,
+            drag: 1.0,
+            ricochets: 0,
+            ricochet_angle: 0.3,
+            ricochet_speed: 0.6,
+            lifetime: 2.0
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub enum ProjectileKind
+{
+    // instant, pierce is how much stuff it can go through
+    Hitscan{pierce: Option<f32>},
+    Simulated(SimulatedInfo)
+}
+
+impl Default for ProjectileKind
+{
+    fn default() -> Self
+    {
+        Self::Hitscan{pierce: None}
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProjectileInfo
+{
+    pub kind: ProjectileKind,
+    // particle effect where it hits something that doesnt bleed
+    pub impact: Option<String>,
+    // particle effect where it gets fired from
+    pub muzzle: Option<String>,
+    pub explosion: Option<ExplosionInfo>
+}
+
+impl Default for ProjectileInfo
+{
+    fn default() -> Self
+    {
+        Self{
+            kind: ProjectileKind::default(),
+            impact: Some("hit_sparks".to_owned()),
+            muzzle: Some("muzzle_flash".to_owned()),
+            explosion: None
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tracer
+{
+    pub start: Vector3<f32>,
+    pub end: Vector3<f32>,
+    // particle effect where it stopped, if it stopped on something that doesnt bleed
+    #[serde(default)]
+    pub impact: Option<String>,
+    #[serde(skip, default)]
+    age: f32
+}
+
+impl Tracer
+{
+    pub fn new(start: Vector3<f32>, end: Vector3<f32>) -> Self
+    {
+        Self{start, end, impact: None, age: 0.0}
+    }
+
+    pub fn with_impact(mut self, impact: Option<String>) -> Self
+    {
+        self.impact = impact;
+
+        self
+    }
+
+    // 1 when fresh, 0 when gone
+    pub fn fade(&self) -> f32
+    {
+        (1.0 - self.age / TRACER_DURATION).max(0.0)
+    }
+}
+
+// the position and velocity live in the entitys transform and physical, the damage in its damaging
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Projectile
+{
+    pub info: SimulatedInfo,
+    pub impact: Option<String>,
+    // goes off wherever it stops
+    pub explosion: Option<ExplosionInfo>,
+    ricochets: u32,
+    age: f32
+}
+
+// only the server ever makes these, it decides what a shot actually hits
+pub struct FireInfo<'a>
+{
+    pub info: &'a ProjectileInfo,
+    pub start: Vector3<f32>,
+    pub target: Vector3<f32>,
+    pub source: Entity,
+    pub faction: Faction,
+    pub damage: DamagePartial
+}
+
+pub enum ProjectileHit
+{
+    Nothing,
+    Entity(Entity),
+    Wall
+}
+
+pub enum StepResult
+{
+    Flying,
+    Bounced{position: Vector3<f32>, direction: Unit<Vector3<f32>>},
+    Stopped{position: Vector3<f32>, direction: Unit<Vector3<f32>>, hit: ProjectileHit}
+}
+
+impl Projectile
+{
+    pub fn new(info: &SimulatedInfo, impact: Option<String>, explosion: Option<ExplosionInfo>) -> Self
+    {
+        Self{
+            info: *info,
+            impact,
+            explosion,
+            ricochets: info.ricochets,
+            age: 0.0
+        }
+    }
+
+    // clients move it around with the physics in between, the server steps it and does the hits
+    pub fn entity_info(
+        self,
+        start: Vector3<f32>,
+        target: Vector3<f32>,
+        damaging: DamagingInfo
+    ) -> Option<EntityInfo>
+    {
+        let direction = (target - start).try_normalize(0.0001)?;
+
+        let mut physical: Physical = PhysicalProperties{
+            damping: self.info.drag,
+            can_sleep: false,
+            floating: true,
+            ..Default::default()
+        }.into();
+
+        physical.set_velocity_raw(direction * self.info.speed);
+        physical.set_acceleration(Vector3::new(0.0, 0.0, -self.info.gravity));
+
+        Some(EntityInfo{
+            transform: Some(Transform{
+                position: start,
+                ..Default::default()
+            }),
+            physical: Some(physical),
+            damaging: Some(damaging.into()),
+            projectile: Some(self),
+            ..Default::default()
+        })
+    }
+
+    fn stopped(position: Vector3<f32>, velocity: Vector3<f32>, hit: ProjectileHit) -> StepResult
+    {
+        let direction = Unit::try_new(velocity, 0.0001).unwrap_or_else(Vector3::z_axis);
+
+        StepResult::Stopped{position, direction, hit}
+    }
+
+    // only the server steps these, the source is ignored so it cant shoot itself
+    pub fn step(
+        &mut self,
+        entities: &impl AnyEntities,
+        source: Option<Entity>,
+        position: &mut Vector3<f32>,
+        velocity: &mut Vector3<f32>,
+        mut tile_hit: impl FnMut(Vector3<f32>, Vector3<f32>) -> Option<TileHit>,
+        dt: f32
+    ) -> StepResult
+    {
+        self.age += dt;
+
+        let info = self.info;
+        if self.age > info.lifetime
+        {
+            return Self::stopped(*position, *velocity, ProjectileHit::Nothing);
+        }
+
+        velocity.z -= info.gravity * dt;
+        *velocity *= info.drag.powf(dt);
+
+        let speed = velocity.magnitude();
+        if speed < MIN_SPEED
+        {
+            return Self::stopped(*position, *velocity, ProjectileHit::Nothing);
+        }
+
+        let direction = Unit::new_unchecked(*velocity / speed);
+
+        let start = *position;
+        let end = start + *velocity * dt;
+
+        let raycast_info = RaycastInfo{
+            pierce: None,
+            layer: ColliderLayer::DAMAGE,
+            ignore_entity: source,
+            ignore_end: false
+        };
+
+        let entity_hit = entities.raycast(raycast_info, &start, &end).hits.into_iter().find_map(|hit|
+        {
+            match hit.id
+            {
+                RaycastHitId::Entity(id) => Some((id, hit.result.distance.max(0.0))),
+                _ => None
+            }
+        });
+
+        let world_hit = tile_hit(start, end);
+
+        let entity_first = match (&entity_hit, &world_hit)
+        {
+            (Some((_, entity_distance)), Some(world_hit)) => *entity_distance <= world_hit.distance,
+            (Some(_), None) => true,
+            _ => false
+        };
+
+        if entity_first
+        {
+            let (entity, distance) = entity_hit.unwrap();
+            let hit_position = start + *direction * distance;
+
+            return StepResult::Stopped{position: hit_position, direction, hit: ProjectileHit::Entity(entity)};
+        }
+
+        if let Some(hit) = world_hit
+        {
+            let hit_position = start + *direction * hit.distance;
+
+            // shallow enough hits glance off the wall
+            let along = direction.dot(&hit.normal).abs();
+            if self.ricochets > 0 && along < info.ricochet_angle.sin()
+            {
+                *velocity = (*velocity - hit.normal * (2.0 * velocity.dot(&hit.normal))) * info.ricochet_speed;
+                *position = hit_position;
+
+                self.ricochets -= 1;
+
+                return StepResult::Bounced{position: hit_position, direction};
+            }
+
+            return StepResult::Stopped{position: hit_position, direction, hit: ProjectileHit::Wall};
+        }
+
+        *position = end;
+
+        StepResult::Flying
+    }
+}
+
+fn spawn_impact(
+    entities: &ClientEntities,
+    particle_effects: &ParticleEffects,
+    impact: Option<&str>,
+    position: Vector3<f32>,
+    direction: Unit<Vector3<f32>>
+)
+{
+    let impact = some_or_return!(impact);
+
+    entities.push(true, EntityInfo{
+        transform: Some(Transform{
+            position,
+            ..Default::default()
+        }),
+        watchers: Some(Watchers::new(vec![
+            Watcher{
+                kind: WatcherType::Instant,
+                action: WatcherAction::Explode(Box::new(
+                    particle_effects.get(impact).explode(false, direction)
+                )),
+                ..Default::default()
+            }
+        ])),
+        ..Default::default()
+    });
+}
+
+pub fn spawn_explosion(
+    entities: &ClientEntities,
+    particle_effects: &ParticleEffects,
+    position: Vector3<f32>
+)
+{
+    EXPLOSION_EFFECTS.into_iter().for_each(|effect|
+    {
+        spawn_impact(entities, particle_effects, Some(effect), position, Vector3::z_axis());
+    });
+}
+
+// only for drawing, the server simulates the real ones and sends them here
+#[derive(Debug, Default)]
+pub struct Projectiles
+{
+    tracers: Vec<Tracer>
+}
+
+impl Projectiles
+{
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    pub fn tracers(&self) -> &[Tracer]
+    {
+        &self.tracers
+    }
+
+    pub fn push_tracer(&mut self, tracer: Tracer)
+    {
+        self.tracers.push(tracer);
+    }
+
+    pub fn push_remote_tracer(
+        &mut self,
+        entities: &ClientEntities,
+        particle_effects: &ParticleEffects,
+        tracer: Tracer
+    )
+    {
+        if let Some(direction) = Unit::try_new(tracer.start - tracer.end, 0.0001)
+        {
+            spawn_impact(entities, particle_effects, tracer.impact.as_deref(), tracer.end, direction);
+        }
+
+        self.tracers.push(tracer);
+    }
+
+    // a projectile entity bounced off or stopped on something hard
+    pub fn remote_impact(
+        entities: &ClientEntities,
+        particle_effects: &ParticleEffects,
+        impact: &str,
+        position: Vector3<f32>,
+        direction: Vector3<f32>
+    )
+    {
+        if let Some(direction) = Unit::try_new(direction, 0.0001)
+        {
+            spawn_impact(entities, particle_effects, Some(impact), position, direction);
+        }
+    }
+
+    // the shot itself is up to the server, the flash shows up right away
+    pub fn muzzle(
+        entities: &ClientEntities,
+        particle_effects: &ParticleEffects,
+        info: &ProjectileInfo,
+        start: Vector3<f32>,
+        target: Vector3<f32>
+    )
+    {
+        if let Some(direction) = Unit::try_new(target - start, 0.0001)
+        {
+            spawn_impact(entities, particle_effects, info.muzzle.as_deref(), start, direction);
+        }
+    }
+
+    pub fn update(&mut self, dt: f32)
+    {
+        self.tracers.retain_mut(|tracer|
+        {
+            tracer.age += dt;
+
+            tracer.age < TRACER_DURATION
+        });
+    }
+}
@@ -0,0 +1,136 @@
+use std::f32;
+
+use nalgebra::Vector3;
+
+use yanyaengine::Transform;
+
+use crate::common::{
+    some_or_return,
+    rotate_point_z_3d,
+    collider::*,
+    render_info::*,
+    physics::*,
+    lazy_transform::*,
+    joint::*,
+    rng::{self, RngStream},
+    EntityInfo,
+    Parent,
+    AnyEntities,
+    Entity
+};
+
+
+// relative to the character scale
+const TORSO_RADIUS: f32 = 0.35;
+const SEGMENT_SIZE: f32 = 0.22;
+
+// every limb is a chain of these, the last one is the hand or foot
+const LIMB_SEGMENTS: usize = 3;
+
+const SEGMENT_MASS: f32 = 1.5;
+
+// how far every joint can bend from where it started
+const JOINT_SWING: f32 = f32::consts::FRAC_PI_2 * 0.6;
+
+// the ends of the limbs get whipped around more than the torso
+const WHIP: f32 = 0.25;
+
+// sideways arms and legs trailing behind, relative to the body rotation
+const LIMB_ANGLES: [f32; 4] = [
+    f32::consts::FRAC_PI_2,
+    -f32::consts::FRAC_PI_2,
+    f32::consts::PI - 0.4,
+    f32::consts::PI + 0.4
+];
+
+fn random_spin(impulse: Vector3<f32>) -> f32
+{
+    (rng::f32(RngStream::Cosmetic) - 0.5) * impulse.magnitude() * 20.0
+}
+
+// the body itself is the torso, every limb is a chain of segments hinged to it and to each other
+// the killing hit flings all of them and they fall asleep like any other physical
+pub fn spawn_ragdoll(entities: &impl AnyEntities, entity: Entity, impulse: Vector3<f32>)
+{
+    let id = some_or_return!(entities.character(entity)).id;
+    let texture = entities.infos().characters_info.get(id).hand;
+
+    let transform = some_or_return!(entities.transform(entity)).clone();
+
+    let scale = transform.scale.x;
+
+    if let Some(mut physical) = entities.physical_mut(entity)
+    {
+        physical.add_velocity_raw(impulse);
+        physical.add_angular_velocity_raw(random_spin(impulse) * 0.2);
+    }
+
+    LIMB_ANGLES.into_iter().for_each(|angle|
+    {
+        let rotation = transform.rotation + angle;
+
+        (0..LIMB_SEGMENTS).fold(entity, |previous, index|
+        {
+            // the first one hangs off the edge of the torso, the rest off the end of the one before
+            let (distance, origin) = if index == 0
+            {
+                (TORSO_RADIUS + SEGMENT_SIZE * 0.5, -(TORSO_RADIUS / SEGMENT_SIZE + 0.5))
+            } else
+            {
+                (SEGMENT_SIZE, -1.0)
+            };
+
+            let mut physical: Physical = PhysicalProperties{
+                inverse_mass: SEGMENT_MASS.recip(),
+                restitution: 0.1,
+                floating: true,
+                move_z: false,
+                ..Default::default()
+            }.into();
+
+            // a bit of spread so they dont all swing the same way
+            let spread = 0.7 + rng::f32(RngStream::Cosmetic) * 0.6;
+            let whip = 1.0 + index as f32 * WHIP;
+
+            physical.add_velocity_raw(impulse * spread * whip);
+            physical.add_angular_velocity_raw(random_spin(impulse));
+
+            entities.push(true, EntityInfo{
+                lazy_transform: Some(LazyTransformInfo{
+                    scaling: Scaling::Ignore,
+                    transform: Transform{
+                        position: rotate_point_z_3d(
+                            Vector3::new(scale * distance, 0.0, 0.0),
+                            rotation
+                        ),
+                        rotation,
+                        scale: Vector3::repeat(SEGMENT_SIZE * scale),
+                        ..Default::default()
+                    },
+                    inherit_rotation: false,
+                    ..Default::default()
+                }.into()),
+                render: Some(RenderInfo{
+                    object: Some(RenderObjectKind::TextureId{id: texture}.into()),
+                    z_level: ZLevel::BelowFeet,
+                    ..Default::default()
+                }),
+                collider: Some(ColliderInfo{
+                    kind: ColliderType::Circle,
+                    layer: ColliderLayer::CORPSE,
+                    ..Default::default()
+                }.into()),
+                physical: Some(physical),
+                parent: Some(Parent::new(previous, true)),
+                joint: Some(Joint::Hinge(HingeJoint{
+                    origin: Vector3::new(origin, 0.0, 0.0),
+                    angle_limit: Some(HingeAngleLimit{
+                        base: rotation,
+                        distance: JOINT_SWING
+                    })
+                })),
+                ..Default::default()
+            })
+        });
+    });
+}
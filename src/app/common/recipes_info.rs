@@ -0,0 +1,90 @@
+use std::{
+    fs::File,
+    path::Path
+};
+
+use serde::Deserialize;
+
+use crate::common::{
+    generic_info::*,
+    mods_info::merge_by_name,
+    items_info::{ItemId, ItemsInfo},
+    inventory::{Inventory, InventoryItem}
+};
+
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RecipeInfoRaw
+{
+    name: String,
+    // item names, the same name twice needs two of them
+    ingredients: Vec<String>,
+    result: String
+}
+
+#[derive(Debug, Clone)]
+pub struct RecipeInfo
+{
+    pub name: String,
+    pub ingredients: Vec<ItemId>,
+    pub result: ItemId
+}
+
+impl GenericItem for RecipeInfo
+{
+    fn name(&self) -> String
+    {
+        self.name.clone()
+    }
+}
+
+impl RecipeInfo
+{
+    // which items get used up, none if something is missing
+    pub fn ingredients_in(&self, inventory: &Inventory) -> Option<Vec<InventoryItem>>
+    {
+        let mut used: Vec<InventoryItem> = Vec::new();
+
+        self.ingredients.iter().map(|id|
+        {
+            let item = inventory.items_ids().find(|(item, x)| x.id == *id && !used.contains(item))?.0;
+
+            used.push(item);
+
+            Some(())
+        }).collect::<Option<()>>()?;
+
+        Some(used)
+    }
+}
+
+define_info_id!{RecipeId}
+
+pub type RecipesInfo = GenericInfo<RecipeId, RecipeInfo>;
+
+impl RecipesInfo
+{
+    pub fn parse(items_info: &ItemsInfo, infos: impl IntoIterator<Item=impl AsRef<Path>>) -> Self
+    {
+        let recipes = infos.into_iter().flat_map(|info|
+        {
+            let info = File::open(info.as_ref()).unwrap();
+
+            let recipes: Vec<RecipeInfoRaw> = serde_json::from_reader(info).unwrap();
+
+            recipes
+        });
+
+        let recipes = merge_by_name(recipes, |x| &x.name).into_iter().map(|raw|
+        {
+            RecipeInfo{
+                ingredients: raw.ingredients.iter().map(|name| items_info.id(name)).collect(),
+                result: items_info.id(&raw.result),
+                name: raw.name
+            }
+        }).collect();
+
+        GenericInfo::new(recipes)
+    }
+}
@@ -33,7 +33,7 @@ pub use yanyaengine::{TextCreateInfo, FontStyle, TextAlign, HorizontalAlign, Ver
 
 use crate::{
     client::{RenderCreateInfo, VisibilityChecker},
-    common::ServerToClient
+    common::{ServerToClient, outlineable::OutlineStyle}
 };
 
 
@@ -45,7 +45,37 @@ pub struct OutlinedInfo
     other_mix: f32,
     animation: f32,
     outlined: f32,
-    keep_transparency: u32
+    keep_transparency: u32,
+    emissive: f32,
+    // w is how much of the rainbow gets mixed in
+    outline_color: [f32; 4],
+    outline_thickness: f32,
+    dissolve: f32
+}
+
+// glow halo size relative to the sprite at full intensity
+const GLOW_SCALE: f32 = 2.5;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Emissive
+{
+    pub color: [f32; 3],
+    pub intensity: f32,
+    // how much the intensity dips with the animation, 0 is steady
+    pub flicker: f32
+}
+
+impl Emissive
+{
+    pub fn current(&self, animation: f32) -> f32
+    {
+        self.intensity * (1.0 - self.flicker * (animation * 0.5 + 0.5))
+    }
+
+    fn glow_mix(&self) -> MixColor
+    {
+        MixColor{color: self.color, amount: 1.0, keep_transparency: true}
+    }
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
@@ -100,9 +130,42 @@ impl OutlinedInfo
             other_mix: other_color.other_mix,
             animation,
             outlined,
-            keep_transparency: other_color.keep_transparency
+            keep_transparency: other_color.keep_transparency,
+            emissive: 0.0,
+            outline_color: [0.0, 0.0, 0.0, 1.0],
+            outline_thickness: 0.0,
+            dissolve: 0.0
         }
     }
+
+    pub fn with_outline_style(mut self, style: &OutlineStyle) -> Self
+    {
+        self.outline_color = if let Some([r, g, b]) = style.color
+        {
+            [r, g, b, 0.0]
+        } else
+        {
+            [0.0, 0.0, 0.0, 1.0]
+        };
+
+        self.outline_thickness = style.thickness;
+
+        self
+    }
+
+    pub fn with_emissive(mut self, emissive: f32) -> Self
+    {
+        self.emissive = emissive;
+
+        self
+    }
+
+    pub fn with_dissolve(mut self, dissolve: f32) -> Self
+    {
+        self.dissolve = dissolve;
+
+        self
+    }
 }
 
 #[repr(C)]
@@ -130,6 +193,72 @@ impl UiOutlinedInfo
     }
 }
 
+#[repr(C)]
+#[derive(BufferContents)]
+pub struct WorldInfo
+{
+    tint: [f32; 3],
+    tint_amount: f32
+}
+
+impl WorldInfo
+{
+    pub fn new(tint: [f32; 3], tint_amount: f32) -> Self
+    {
+        Self{tint, tint_amount}
+    }
+}
+
+impl Default for WorldInfo
+{
+    fn default() -> Self
+    {
+        Self::new([0.0; 3], 0.0)
+    }
+}
+
+#[repr(C)]
+#[derive(BufferContents)]
+pub struct TilesInfo
+{
+    world: WorldInfo,
+    // how far apart neighboring tiles are on the screen, the vertex shader places every instance with it
+    tile_step: [f32; 2]
+}
+
+impl TilesInfo
+{
+    pub fn new(world: WorldInfo, tile_step: Vector2<f32>) -> Self
+    {
+        Self{world, tile_step: tile_step.into()}
+    }
+}
+
+#[repr(C)]
+#[derive(BufferContents)]
+pub struct ReflectionInfo
+{
+    tint: [f32; 3],
+    strength: f32,
+    animation: f32
+}
+
+impl ReflectionInfo
+{
+    pub fn new(tint: [f32; 3], strength: f32, animation: f32) -> Self
+    {
+        Self{tint, strength, animation}
+    }
+}
+
+#[repr(C)]
+#[derive(BufferContents)]
+pub struct LightOcclusionInfo
+{
+    // the light quad inside the distance field as xy offset and zw size
+    pub area: [f32; 4]
+}
+
 #[derive(Debug)]
 pub enum RenderComponent
 {
@@ -167,13 +296,9 @@ impl RenderObjectKind
         {
             Self::TextureId{id} =>
             {
-                let info = ObjectInfo{
-                    model: assets.model(create_info.square).clone(),
-                    texture: assets.texture(id).clone(),
-                    transform
-                };
+                drop(assets);
 
-                let object = create_info.object_info.partial.object_factory.create(info);
+                let object = create_info.create_object(id, transform);
 
                 Some(ClientRenderObject{
                     kind: ClientObjectType::Normal(object)
@@ -328,6 +453,7 @@ pub struct RenderInfo
     pub object: Option<RenderObject>,
     pub visibility_check: bool,
     pub mix: Option<MixColor>,
+    pub emissive: Option<Emissive>,
     pub aspect: Aspect,
     pub z_level: ZLevel
 }
@@ -343,6 +469,7 @@ impl Default for RenderInfo
             object: None,
             visibility_check: true,
             mix: None,
+            emissive: None,
             aspect: Aspect::Fill,
             z_level: ZLevel::Shoulders
         }
@@ -444,6 +571,11 @@ pub struct ClientRenderInfo
     pub object: Option<ClientRenderObject>,
     pub visibility_check: bool,
     pub mix: Option<MixColor>,
+    pub emissive: Option<Emissive>,
+    // only exists if it was emissive when created
+    glow: Option<Object>,
+    // client side only, corpses fading out
+    pub dissolve: f32,
     pub aspect: Aspect,
     z_level: ZLevel
 }
@@ -457,6 +589,14 @@ impl ServerToClient<ClientRenderInfo> for RenderInfo
     ) -> ClientRenderInfo
     {
         let transform = transform();
+
+        let glow = self.emissive.map(|_|
+        {
+            let id = create_info.object_info.partial.assets.lock().texture_id("glow.png");
+
+            create_info.create_object(id, transform.clone())
+        });
+
         let object = self.object.and_then(|object|
         {
             object.into_client(transform.clone(), create_info)
@@ -474,6 +614,9 @@ impl ServerToClient<ClientRenderInfo> for RenderInfo
             object,
             visibility_check: self.visibility_check,
             mix: self.mix,
+            emissive: self.emissive,
+            glow,
+            dissolve: 0.0,
             aspect: self.aspect,
             z_level: self.z_level
         };
@@ -561,6 +704,21 @@ impl ClientRenderInfo
         }
     }
 
+    // plain textured squares, the only things that can get batched together
+    pub fn sprite(&self) -> Option<(&Transform, &Arc<RwLock<Texture>>)>
+    {
+        if !self.visible || self.scissor.is_some()
+        {
+            return None;
+        }
+
+        match &self.object.as_ref()?.kind
+        {
+            ClientObjectType::Normal(x) => Some((x.transform_ref(), x.texture())),
+            ClientObjectType::Text(_) => None
+        }
+    }
+
     pub fn set_sprite(
         &mut self,
         create_info: &mut RenderCreateInfo,
@@ -609,6 +767,11 @@ impl ClientRenderInfo
         }
     }
 
+    pub fn emissive_intensity(&self, animation: f32) -> f32
+    {
+        self.emissive.map(|x| x.current(animation)).unwrap_or(0.0)
+    }
+
     pub fn set_visibility(&mut self, visible: bool)
     {
         self.visible = visible;
@@ -686,6 +849,36 @@ impl ClientRenderInfo
         {
             object.update_buffers(info);
         }
+
+        let transform = self.object.as_ref().and_then(|x| x.transform()).cloned();
+        if let (Some(glow), Some(emissive), Some(mut transform)) = (self.glow.as_mut(), self.emissive, transform)
+        {
+            transform.scale *= GLOW_SCALE * emissive.intensity.min(1.0);
+
+            glow.set_transform(transform);
+            glow.update_buffers(info);
+        }
+    }
+
+    // drawn before the sprite itself so it sits behind it
+    pub fn draw_glow(&self, info: &mut DrawInfo, animation: f32)
+    {
+        if !self.visible
+        {
+            return;
+        }
+
+        if let (Some(glow), Some(emissive)) = (self.glow.as_ref(), self.emissive)
+        {
+            let intensity = emissive.current(animation);
+
+            let outline = OutlinedInfo::new(Some(emissive.glow_mix()), 0.0, animation)
+                .with_emissive(intensity);
+
+            info.push_constants(outline);
+
+            glow.draw(info);
+        }
     }
 
     pub fn draw<T: BufferContents>(
@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+
+use serde::{Serialize, Deserialize};
+
+use strum::IntoEnumIterator;
+
+use crate::common::{Faction, FactionsInfo, world::TILE_SIZE};
+
+
+pub const HOSTILE_STANDING: f32 = -20.0;
+
+// below this a faction doesnt want u around where its people live
+pub const TRESPASS_STANDING: f32 = -5.0;
+pub const TERRITORY_RADIUS: f32 = TILE_SIZE * 8.0;
+
+pub const MIN_STANDING: f32 = -100.0;
+pub const MAX_STANDING: f32 = 100.0;
+
+#[derive(Debug, Clone, Copy)]
+pub enum ReputationEvent
+{
+    Hit,
+    Kill,
+    Theft
+}
+
+impl ReputationEvent
+{
+    fn change(&self) -> f32
+    {
+        match self
+        {
+            Self::Hit => -4.0,
+            Self::Kill => -15.0,
+            Self::Theft => -8.0
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Reputation
+{
+    standings: HashMap<Faction, f32>
+}
+
+impl Reputation
+{
+    pub fn standing(&self, faction: Faction) -> f32
+    {
+        self.standings.get(&faction).copied().unwrap_or(0.0)
+    }
+
+    pub fn change(&mut self, faction: Faction, amount: f32)
+    {
+        let standing = self.standings.entry(faction).or_insert(0.0);
+
+        *standing = (*standing + amount).clamp(MIN_STANDING, MAX_STANDING);
+    }
+
+    pub fn on_event(&mut self, factions_info: &FactionsInfo, faction: Faction, event: ReputationEvent)
+    {
+        if !faction.has_reputation()
+        {
+            return;
+        }
+
+        let change = event.change();
+
+        self.change(faction, change);
+
+        if let ReputationEvent::Kill = event
+        {
+            // whoever hates the victim likes u a bit more, their friends like u less
+            Faction::iter().filter(|other| other.has_reputation() && *other != faction).for_each(|other|
+            {
+                if factions_info.aggressive(other, faction)
+                {
+                    self.change(other, -change * 0.2);
+                } else if factions_info.allied(other, faction)
+                {
+                    self.change(other, change * 0.5);
+                }
+            });
+        }
+    }
+
+    pub fn hostile(&self, faction: Faction) -> bool
+    {
+        self.standing(faction) <= HOSTILE_STANDING
+    }
+
+    pub fn welcome(&self, faction: Faction) -> bool
+    {
+        self.standing(faction) > TRESPASS_STANDING
+    }
+
+    pub fn price_multiplier(&self, faction: Faction) -> f32
+    {
+        let standing = self.standing(faction) / MAX_STANDING;
+
+        1.0 - standing * 0.3
+    }
+
+    pub fn description(&self) -> String
+    {
+        Faction::iter().filter(Faction::has_reputation).map(|faction|
+        {
+            let standing = self.standing(faction);
+
+            let name: &str = faction.into();
+
+            let state = if self.hostile(faction)
+            {
+                "hostile"
+            } else if !self.welcome(faction)
+            {
+                "unwelcome"
+            } else if standing > -HOSTILE_STANDING
+            {
+                "friendly"
+            } else
+            {
+                "neutral"
+            };
+
+            let prices = self.price_multiplier(faction);
+
+            format!("{}: {standing:.0} ({state}, prices x{prices:.2})", name.to_lowercase())
+        }).reduce(|acc, x| acc + "\n" + &x).unwrap_or_default()
+    }
+}
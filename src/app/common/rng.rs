@@ -0,0 +1,82 @@
+use std::{
+    array,
+    ops::Range,
+    sync::LazyLock
+};
+
+use parking_lot::Mutex;
+
+use strum::EnumCount;
+
+use fastrand::Rng;
+
+
+// separate streams so rolling loot doesnt change what the world generator or the ai does next
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumCount)]
+pub enum RngStream
+{
+    WorldGen,
+    Loot,
+    Ai,
+    Combat,
+    Weather,
+    // particles, decals and anything else that doesnt change how the game plays
+    Cosmetic
+}
+
+// random until a world seeds them
+static STREAMS: LazyLock<[Mutex<Rng>; RngStream::COUNT]> = LazyLock::new(||
+{
+    array::from_fn(|_| Mutex::new(Rng::new()))
+});
+
+pub fn seed(world_seed: u64)
+{
+    STREAMS.iter().enumerate().for_each(|(index, rng)|
+    {
+        let mixed = world_seed ^ (index as u64 + 1).wrapping_mul(0x9e3779b97f4a7c15);
+
+        *rng.lock() = Rng::with_seed(mixed);
+    });
+}
+
+// the lock gets dropped right away so these can be nested freely
+fn with<T>(stream: RngStream, f: impl FnOnce(&mut Rng) -> T) -> T
+{
+    f(&mut STREAMS[stream as usize].lock())
+}
+
+pub fn f32(stream: RngStream) -> f32
+{
+    with(stream, |rng| rng.f32())
+}
+
+pub fn f64(stream: RngStream) -> f64
+{
+    with(stream, |rng| rng.f64())
+}
+
+pub fn usize(stream: RngStream, range: Range<usize>) -> usize
+{
+    with(stream, |rng| rng.usize(range))
+}
+
+pub fn i32(stream: RngStream, range: Range<i32>) -> i32
+{
+    with(stream, |rng| rng.i32(range))
+}
+
+pub fn u32(stream: RngStream, range: Range<u32>) -> u32
+{
+    with(stream, |rng| rng.u32(range))
+}
+
+pub fn u64(stream: RngStream, range: Range<u64>) -> u64
+{
+    with(stream, |rng| rng.u64(range))
+}
+
+pub fn shuffle<T>(stream: RngStream, values: &mut [T])
+{
+    with(stream, |rng| rng.shuffle(values))
+}
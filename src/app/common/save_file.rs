@@ -0,0 +1,421 @@
+use std::{
+    fmt,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    fs::{self, File}
+};
+
+use serde::{Serialize, de::DeserializeOwned};
+
+
+const MAGIC: &[u8; 4] = b"STSF";
+
+// magic, format version, then the checksum header
+const HEADER_SIZE: usize = 4 + 4 + CHECKSUM_SIZE;
+
+// saves from before format versions, the checksum header with bincode after it
+const CHECKSUM_MAGIC: &[u8; 4] = b"STSV";
+
+// crc32 of the data, length of the data
+const CHECKSUM_SIZE: usize = 4 + 8;
+
+// saves from before the header existed, plain bincode
+const LEGACY_VERSION: u32 = 0;
+
+// messagepack with field names, unknown fields get skipped and missing option fields become none
+// so new fields that arent options need #[serde(default)] to keep old saves loading
+pub const FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug)]
+pub enum SaveFileError
+{
+    Io(io::Error),
+    TooShort,
+    WrongLength{expected: usize, got: usize},
+    Checksum,
+    UnknownVersion(u32),
+    Encode(String),
+    Decode{version: u32, err: String}
+}
+
+impl fmt::Display for SaveFileError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match self
+        {
+            Self::Io(x) => write!(f, "{x}"),
+            Self::TooShort => write!(f, "file is too short to have a header"),
+            Self::WrongLength{expected, got} => write!(f, "expected {expected} bytes, got {got}"),
+            Self::Checksum => write!(f, "checksum doesnt match"),
+            Self::UnknownVersion(x) => write!(f, "save format version {x} is newer than {FORMAT_VERSION}"),
+            Self::Encode(x) => write!(f, "error encoding: {x}"),
+            Self::Decode{version, err} => write!(f, "error decoding (format version {version}): {err}")
+        }
+    }
+}
+
+impl From<io::Error> for SaveFileError
+{
+    fn from(value: io::Error) -> Self
+    {
+        Self::Io(value)
+    }
+}
+
+impl SaveFileError
+{
+    fn is_missing(&self) -> bool
+    {
+        if let Self::Io(err) = self
+        {
+            err.kind() == io::ErrorKind::NotFound
+        } else
+        {
+            false
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SaveBytes
+{
+    pub version: u32,
+    pub data: Vec<u8>
+}
+
+pub fn serialize_into<T: Serialize>(writer: &mut impl Write, value: &T) -> Result<(), SaveFileError>
+{
+    rmp_serde::encode::write_named(writer, value).map_err(|err| SaveFileError::Encode(err.to_string()))
+}
+
+pub fn serialize<T: Serialize>(value: &T) -> Result<Vec<u8>, SaveFileError>
+{
+    let mut bytes = Vec::new();
+    serialize_into(&mut bytes, value)?;
+
+    Ok(bytes)
+}
+
+pub fn deserialize_from<T: DeserializeOwned>(version: u32, reader: impl Read) -> Result<T, SaveFileError>
+{
+    let decode_error = |err: String| SaveFileError::Decode{version, err};
+
+    match version
+    {
+        LEGACY_VERSION => bincode::deserialize_from(reader).map_err(|err| decode_error(err.to_string())),
+        FORMAT_VERSION => rmp_serde::from_read(reader).map_err(|err| decode_error(err.to_string())),
+        x => Err(SaveFileError::UnknownVersion(x))
+    }
+}
+
+pub fn deserialize<T: DeserializeOwned>(bytes: &SaveBytes) -> Result<T, SaveFileError>
+{
+    deserialize_from(bytes.version, &bytes.data[..])
+}
+
+// the last save that was fully written before the current one
+pub fn backup_path(path: &Path) -> PathBuf
+{
+    path.with_extension("bak")
+}
+
+// the old file only gets replaced after the new one is fully on disk, and is kept around as a backup
+pub fn write(path: &Path, data: &[u8]) -> io::Result<()>
+{
+    let temp_path = path.with_extension("tmp");
+
+    {
+        let mut file = File::create(&temp_path)?;
+
+        file.write_all(MAGIC)?;
+        file.write_all(&FORMAT_VERSION.to_le_bytes())?;
+        file.write_all(&crc32fast::hash(data).to_le_bytes())?;
+        file.write_all(&(data.len() as u64).to_le_bytes())?;
+        file.write_all(data)?;
+
+        file.sync_all()?;
+    }
+
+    if path.exists()
+    {
+        fs::rename(path, backup_path(path))?;
+    }
+
+    fs::rename(&temp_path, path)?;
+
+    // makes the renames stick, cant open directories like this on every platform so its fine if it fails
+    if let Some(parent) = path.parent()
+    {
+        let _ = File::open(parent).and_then(|x| x.sync_all());
+    }
+
+    Ok(())
+}
+
+fn read_checked(path: &Path) -> Result<SaveBytes, SaveFileError>
+{
+    let bytes = fs::read(path)?;
+
+    if bytes.starts_with(MAGIC)
+    {
+        if bytes.len() < HEADER_SIZE
+        {
+            return Err(SaveFileError::TooShort);
+        }
+
+        let version = u32::from_le_bytes(bytes[4..8].try_into().unwrap());
+
+        return checked_data(bytes, 8).map(|data| SaveBytes{version, data});
+    }
+
+    let data = if bytes.starts_with(CHECKSUM_MAGIC)
+    {
+        checked_data(bytes, CHECKSUM_MAGIC.len())?
+    } else
+    {
+        bytes
+    };
+
+    Ok(SaveBytes{version: LEGACY_VERSION, data})
+}
+
+// checks the checksum header starting at start, returns everything after it
+fn checked_data(mut bytes: Vec<u8>, start: usize) -> Result<Vec<u8>, SaveFileError>
+{
+    let header_end = start + CHECKSUM_SIZE;
+
+    if bytes.len() < header_end
+    {
+        return Err(SaveFileError::TooShort);
+    }
+
+    let checksum = u32::from_le_bytes(bytes[start..start + 4].try_into().unwrap());
+    let expected = u64::from_le_bytes(bytes[start + 4..header_end].try_into().unwrap()) as usize;
+
+    let data = bytes.split_off(header_end);
+
+    if data.len() != expected
+    {
+        return Err(SaveFileError::WrongLength{expected, got: data.len()});
+    }
+
+    if crc32fast::hash(&data) != checksum
+    {
+        return Err(SaveFileError::Checksum);
+    }
+
+    Ok(data)
+}
+
+// falls back to the backup if the file is broken or got lost mid rename, none if neither exist
+pub fn read(path: &Path) -> Result<Option<SaveBytes>, SaveFileError>
+{
+    let err = match read_checked(path)
+    {
+        Ok(x) => return Ok(Some(x)),
+        Err(err) if err.is_missing() => None,
+        Err(err) => Some(err)
+    };
+
+    match read_checked(&backup_path(path))
+    {
+        Ok(x) =>
+        {
+            if let Some(err) = err
+            {
+                eprintln!("save {} is broken ({err}), using the previous one", path.display());
+            }
+
+            Ok(Some(x))
+        },
+        Err(backup_err) if backup_err.is_missing() => err.map(Err).unwrap_or(Ok(None)),
+        Err(backup_err) => Err(err.unwrap_or(backup_err))
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::env;
+
+    use serde::Deserialize;
+
+    use super::*;
+
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Thing
+    {
+        a: u32,
+        b: String
+    }
+
+    fn thing() -> Thing
+    {
+        Thing{a: 5, b: "thing".to_owned()}
+    }
+
+    // how saves looked before format versions
+    fn checksum_layout(data: &[u8]) -> Vec<u8>
+    {
+        let mut bytes = CHECKSUM_MAGIC.to_vec();
+
+        bytes.extend(crc32fast::hash(data).to_le_bytes());
+        bytes.extend((data.len() as u64).to_le_bytes());
+        bytes.extend(data);
+
+        bytes
+    }
+
+
+    fn test_path(name: &str) -> PathBuf
+    {
+        let dir = env::temp_dir().join("stephanie_save_file_tests");
+        fs::create_dir_all(&dir).unwrap();
+
+        let path = dir.join(name);
+
+        let _ = fs::remove_file(&path);
+        let _ = fs::remove_file(backup_path(&path));
+
+        path
+    }
+
+    fn read_data(path: &Path) -> Vec<u8>
+    {
+        read(path).unwrap().unwrap().data
+    }
+
+    #[test]
+    fn checksum_roundtrip()
+    {
+        let path = test_path("roundtrip");
+
+        assert!(read(&path).unwrap().is_none());
+
+        write(&path, &[1, 2, 3, 4]).unwrap();
+
+        assert_eq!(read_data(&path), vec![1, 2, 3, 4]);
+        assert!(!backup_path(&path).exists());
+
+        write(&path, &[5, 6]).unwrap();
+
+        assert_eq!(read_data(&path), vec![5, 6]);
+        assert_eq!(read_checked(&backup_path(&path)).unwrap().data, vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn broken_falls_back()
+    {
+        let path = test_path("broken");
+
+        write(&path, &[1, 2, 3]).unwrap();
+        write(&path, &[4, 5, 6]).unwrap();
+
+        let mut bytes = fs::read(&path).unwrap();
+        *bytes.last_mut().unwrap() ^= 0xff;
+        fs::write(&path, &bytes).unwrap();
+
+        assert!(matches!(read_checked(&path), Err(SaveFileError::Checksum)));
+        assert_eq!(read_data(&path), vec![1, 2, 3]);
+
+        // cut off in the middle of writing
+        fs::write(&path, &bytes[..bytes.len() - 1]).unwrap();
+
+        assert!(matches!(read_checked(&path), Err(SaveFileError::WrongLength{..})));
+        assert_eq!(read_data(&path), vec![1, 2, 3]);
+
+        fs::write(&path, &MAGIC[..]).unwrap();
+
+        assert!(matches!(read_checked(&path), Err(SaveFileError::TooShort)));
+        assert_eq!(read_data(&path), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn missing_falls_back()
+    {
+        let path = test_path("missing");
+
+        write(&path, &[7, 8]).unwrap();
+        write(&path, &[9]).unwrap();
+
+        // died between the two renames
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(read_data(&path), vec![7, 8]);
+    }
+
+    #[test]
+    fn both_broken()
+    {
+        let path = test_path("both_broken");
+
+        write(&path, &[1]).unwrap();
+        write(&path, &[2]).unwrap();
+
+        fs::write(&path, &MAGIC[..]).unwrap();
+        fs::write(backup_path(&path), &MAGIC[..]).unwrap();
+
+        assert!(matches!(read(&path), Err(SaveFileError::TooShort)));
+
+        fs::remove_file(backup_path(&path)).unwrap();
+
+        assert!(matches!(read(&path), Err(SaveFileError::TooShort)));
+    }
+
+    #[test]
+    fn version_dispatch()
+    {
+        let path = test_path("versions");
+
+        write(&path, &serialize(&thing()).unwrap()).unwrap();
+
+        let bytes = read(&path).unwrap().unwrap();
+        assert_eq!(bytes.version, FORMAT_VERSION);
+        assert_eq!(deserialize::<Thing>(&bytes).unwrap(), thing());
+
+        let legacy = bincode::serialize(&thing()).unwrap();
+
+        fs::write(&path, &legacy).unwrap();
+
+        let bytes = read(&path).unwrap().unwrap();
+        assert_eq!(bytes.version, LEGACY_VERSION);
+        assert_eq!(deserialize::<Thing>(&bytes).unwrap(), thing());
+
+        fs::write(&path, checksum_layout(&legacy)).unwrap();
+
+        let bytes = read(&path).unwrap().unwrap();
+        assert_eq!(bytes.version, LEGACY_VERSION);
+        assert_eq!(deserialize::<Thing>(&bytes).unwrap(), thing());
+
+        let newer = SaveBytes{version: FORMAT_VERSION + 1, data: bytes.data};
+        assert!(matches!(deserialize::<Thing>(&newer), Err(SaveFileError::UnknownVersion(_))));
+
+        let mismatched = SaveBytes{version: FORMAT_VERSION, data: vec![0xc1]};
+        assert!(matches!(
+            deserialize::<Thing>(&mismatched),
+            Err(SaveFileError::Decode{version: FORMAT_VERSION, ..})
+        ));
+    }
+
+    #[test]
+    fn checksum_layout_falls_back()
+    {
+        let path = test_path("checksum_layout");
+
+        let legacy = bincode::serialize(&thing()).unwrap();
+
+        let mut broken = checksum_layout(&legacy);
+        *broken.last_mut().unwrap() ^= 0xff;
+
+        fs::write(&path, broken).unwrap();
+        fs::write(backup_path(&path), checksum_layout(&legacy)).unwrap();
+
+        assert!(matches!(read_checked(&path), Err(SaveFileError::Checksum)));
+
+        let bytes = read(&path).unwrap().unwrap();
+        assert_eq!(bytes.version, LEGACY_VERSION);
+        assert_eq!(deserialize::<Thing>(&bytes).unwrap(), thing());
+    }
+}
@@ -0,0 +1,54 @@
+use serde::{Serialize, Deserialize};
+
+use nalgebra::Vector3;
+
+use crate::common::{
+    Message,
+    EntityPasser,
+    world::TILE_SIZE
+};
+
+
+// past this distance nothing reaches the camera
+const SHAKE_RANGE: f32 = TILE_SIZE * 12.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Shake
+{
+    pub position: Vector3<f32>,
+    // 0 to 1, gets added to the camera trauma
+    pub trauma: f32,
+    // seconds the game slows down for
+    pub hit_stop: f32
+}
+
+impl Shake
+{
+    pub fn new(position: Vector3<f32>, trauma: f32) -> Self
+    {
+        Self{position, trauma, hit_stop: 0.0}
+    }
+
+    pub fn with_hit_stop(mut self, hit_stop: f32) -> Self
+    {
+        self.hit_stop = hit_stop;
+
+        self
+    }
+
+    // how much of it is felt at this position
+    pub fn falloff(&self, position: Vector3<f32>) -> f32
+    {
+        let distance = (self.position.xy() - position.xy()).magnitude();
+
+        (1.0 - distance / SHAKE_RANGE).clamp(0.0, 1.0)
+    }
+
+    // goes through the server so every client nearby feels it
+    pub fn spawn(self, passer: &mut impl EntityPasser)
+    {
+        let message = Message::SpawnShake{shake: self};
+
+        passer.send_message(Message::RepeatMessage{message: Box::new(message)});
+    }
+}
@@ -0,0 +1,501 @@
+use std::collections::{HashMap, VecDeque};
+
+use serde::{Serialize, Deserialize};
+
+use nalgebra::Vector3;
+
+use crate::common::{
+    some_or_value,
+    some_or_return,
+    Entity,
+    EntityInfo,
+    entity::ComponentHashes,
+    message::Message
+};
+
+
+// a full snapshot goes out this often even if the client acks everything
+const KEYFRAME_INTERVAL: u32 = 300;
+
+// the client is so far behind that deltas against its last ack r pointless
+const MAX_UNACKED: u32 = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct SyncState
+{
+    position: Vector3<f32>,
+    rotation: Option<f32>
+}
+
+impl SyncState
+{
+    fn from_message(message: &Message) -> Option<(Entity, Self)>
+    {
+        match message
+        {
+            Message::SyncPosition{entity, position} =>
+            {
+                Some((*entity, Self{position: *position, rotation: None}))
+            },
+            Message::SyncPositionRotation{entity, position, rotation} =>
+            {
+                Some((*entity, Self{position: *position, rotation: Some(*rotation)}))
+            },
+            _ => None
+        }
+    }
+
+    fn into_message(self, entity: Entity) -> Message
+    {
+        match self.rotation
+        {
+            Some(rotation) => Message::SyncPositionRotation{entity, position: self.position, rotation},
+            None => Message::SyncPosition{entity, position: self.position}
+        }
+    }
+
+    fn delta(&self, base: &Self) -> SyncDelta
+    {
+        SyncDelta{
+            position: (self.position != base.position).then_some(self.position),
+            rotation: self.rotation.filter(|_| self.rotation != base.rotation)
+        }
+    }
+
+    fn apply(&self, delta: SyncDelta) -> Self
+    {
+        Self{
+            position: delta.position.unwrap_or(self.position),
+            rotation: delta.rotation.or(self.rotation)
+        }
+    }
+}
+
+// only the fields that changed since the snapshot the client last acked
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncDelta
+{
+    position: Option<Vector3<f32>>,
+    rotation: Option<f32>
+}
+
+#[derive(Debug)]
+struct StateHistory<T>
+{
+    states: HashMap<Entity, VecDeque<(u32, T)>>
+}
+
+impl<T> Default for StateHistory<T>
+{
+    fn default() -> Self
+    {
+        Self{states: HashMap::new()}
+    }
+}
+
+impl<T: Clone> StateHistory<T>
+{
+    // whatever the state was when that snapshot got sent
+    fn get(&self, entity: Entity, sequence: u32) -> Option<&T>
+    {
+        self.states.get(&entity)?.iter().rev().find(|(at, _)| *at <= sequence).map(|(_, state)| state)
+    }
+
+    fn push(&mut self, entity: Entity, sequence: u32, state: T)
+    {
+        self.states.entry(entity).or_default().push_back((sequence, state));
+    }
+
+    // changes the newest state, nothing happens if theres none yet
+    fn update(&mut self, entity: Entity, sequence: u32, f: impl FnOnce(&mut T))
+    {
+        let states = some_or_return!(self.states.get_mut(&entity));
+        let (at, latest) = some_or_return!(states.back_mut());
+
+        if *at == sequence
+        {
+            f(latest);
+        } else
+        {
+            let mut state = latest.clone();
+            f(&mut state);
+
+            states.push_back((sequence, state));
+        }
+    }
+
+    fn remove(&mut self, entity: Entity)
+    {
+        self.states.remove(&entity);
+    }
+
+    // nothing before this snapshot ever gets used as a base again
+    fn prune(&mut self, sequence: u32)
+    {
+        self.states.values_mut().for_each(|states|
+        {
+            while states.get(1).map(|(at, _)| *at <= sequence).unwrap_or(false)
+            {
+                states.pop_front();
+            }
+        });
+    }
+}
+
+// lives on the server, one for every connection
+#[derive(Debug, Default)]
+pub struct SnapshotEncoder
+{
+    sequence: u32,
+    acked: Option<u32>,
+    // deltas only go against snapshots from after the last keyframe
+    keyframe: u32,
+    history: StateHistory<SyncState>,
+    components: StateHistory<ComponentHashes>
+}
+
+impl SnapshotEncoder
+{
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    pub fn ack(&mut self, sequence: u32)
+    {
+        let newer = self.acked.map(|acked| sequence > acked).unwrap_or(true);
+
+        if !newer || sequence > self.sequence
+        {
+            return;
+        }
+
+        self.acked = Some(sequence);
+        self.history.prune(sequence);
+        self.components.prune(sequence);
+    }
+
+    // none means everything has to go out in full
+    fn base(&mut self) -> Option<u32>
+    {
+        // still waiting on the keyframe to get acked
+        let acked = self.acked.filter(|acked| *acked >= self.keyframe)?;
+
+        let behind = self.sequence - acked > MAX_UNACKED;
+        let keyframe_due = self.sequence - self.keyframe >= KEYFRAME_INTERVAL;
+
+        if behind || keyframe_due
+        {
+            self.keyframe = self.sequence;
+            self.history.prune(self.sequence - 1);
+            self.components.prune(self.sequence - 1);
+
+            return None;
+        }
+
+        Some(acked)
+    }
+
+    pub fn encode(&mut self, messages: Vec<Message>) -> Vec<Message>
+    {
+        if messages.is_empty()
+        {
+            return messages;
+        }
+
+        self.sequence += 1;
+
+        let sequence = self.sequence;
+        let base = self.base();
+
+        let encoded = messages.into_iter().map(|message|
+        {
+            if let Message::EntityDestroy{entity} = &message
+            {
+                self.history.remove(*entity);
+                self.components.remove(*entity);
+            }
+
+            if let Message::EntitySet{entity, info} = message
+            {
+                return self.encode_entity(sequence, base, entity, info);
+            }
+
+            if let Some((entity, index, hash)) = EntityInfo::set_message_hash(&message)
+            {
+                self.components.update(entity, sequence, |hashes| hashes[index] = Some(hash));
+            }
+
+            let (entity, state) = some_or_value!(SyncState::from_message(&message), message);
+
+            let base_state = base.and_then(|base| self.history.get(entity, base).copied()).filter(|base_state|
+            {
+                base_state.rotation.is_some() == state.rotation.is_some()
+            });
+
+            self.history.push(entity, sequence, state);
+
+            match base_state
+            {
+                Some(base_state) => Message::SyncDelta{entity, delta: state.delta(&base_state)},
+                None => message
+            }
+        });
+
+        Some(Message::Snapshot{sequence, base}).into_iter().chain(encoded).collect()
+    }
+
+    // only the components that changed since the base go out
+    fn encode_entity(
+        &mut self,
+        sequence: u32,
+        base: Option<u32>,
+        entity: Entity,
+        info: Box<EntityInfo>
+    ) -> Message
+    {
+        let hashes = info.component_hashes();
+
+        let base_hashes = base.and_then(|base| self.components.get(entity, base).copied());
+
+        self.components.push(entity, sequence, hashes);
+
+        match base_hashes
+        {
+            Some(base_hashes) =>
+            {
+                let (changed, removed) = (*info).changed_since(&base_hashes);
+
+                Message::EntitySetDelta{entity, info: Box::new(changed), removed}
+            },
+            None => Message::EntitySet{entity, info}
+        }
+    }
+}
+
+// lives on the client, turns the deltas back into full messages
+#[derive(Debug, Default)]
+pub struct SnapshotDecoder
+{
+    sequence: u32,
+    base: Option<u32>,
+    history: StateHistory<SyncState>,
+    infos: StateHistory<EntityInfo>
+}
+
+impl SnapshotDecoder
+{
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    // gives back the ack for the server
+    pub fn start(&mut self, sequence: u32, base: Option<u32>) -> Message
+    {
+        self.sequence = sequence;
+        self.base = base;
+
+        if let Some(base) = base
+        {
+            self.history.prune(base);
+            self.infos.prune(base);
+        }
+
+        Message::SnapshotAck{sequence}
+    }
+
+    pub fn decode(&mut self, message: Message) -> Option<Message>
+    {
+        match message
+        {
+            Message::SyncDelta{entity, delta} =>
+            {
+                let state = self.history.get(entity, self.base?)?.apply(delta);
+
+                self.history.push(entity, self.sequence, state);
+
+                Some(state.into_message(entity))
+            },
+            Message::EntitySetDelta{entity, info, removed} =>
+            {
+                let info = self.infos.get(entity, self.base?)?.clone().with_changes(*info, &removed);
+
+                self.infos.push(entity, self.sequence, info.clone());
+
+                Some(Message::EntitySet{entity, info: Box::new(info)})
+            },
+            Message::EntitySet{entity, info} =>
+            {
+                self.infos.push(entity, self.sequence, (*info).clone());
+
+                Some(Message::EntitySet{entity, info})
+            },
+            Message::EntityDestroy{entity} =>
+            {
+                self.history.remove(entity);
+                self.infos.remove(entity);
+
+                Some(message)
+            },
+            message =>
+            {
+                if let Some((entity, state)) = SyncState::from_message(&message)
+                {
+                    self.history.push(entity, self.sequence, state);
+                }
+
+                if let Some(entity) = EntityInfo::set_message_entity(&message)
+                {
+                    self.infos.update(entity, self.sequence, |info| { info.apply_set_message(&message); });
+                }
+
+                Some(message)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use yanyaengine::Transform;
+
+    use crate::common::Component;
+
+    use super::*;
+
+
+    fn position_message(entity: Entity, x: f32) -> Message
+    {
+        Message::SyncPositionRotation{entity, position: Vector3::new(x, 1.0, 0.0), rotation: 0.5}
+    }
+
+    fn deliver(decoder: &mut SnapshotDecoder, messages: Vec<Message>) -> (Option<Message>, Vec<Message>)
+    {
+        let mut ack = None;
+
+        let decoded = messages.into_iter().filter_map(|message|
+        {
+            if let Message::Snapshot{sequence, base} = message
+            {
+                ack = Some(decoder.start(sequence, base));
+
+                return None;
+            }
+
+            decoder.decode(message)
+        }).collect();
+
+        (ack, decoded)
+    }
+
+    #[test]
+    fn deltas_round_trip()
+    {
+        let entity = Entity::from_raw(false, 0);
+
+        let mut encoder = SnapshotEncoder::new();
+        let mut decoder = SnapshotDecoder::new();
+
+        (0..10).for_each(|i|
+        {
+            let sent = vec![position_message(entity, i as f32)];
+
+            let encoded = encoder.encode(sent.clone());
+
+            if i > 1
+            {
+                let delta = SyncDelta{position: Some(Vector3::new(i as f32, 1.0, 0.0)), rotation: None};
+                assert_eq!(encoded.last().map(|x| format!("{x:?}")), Some(format!("{:?}", Message::SyncDelta{entity, delta})));
+            }
+
+            let (ack, decoded) = deliver(&mut decoder, encoded);
+
+            assert_eq!(format!("{decoded:?}"), format!("{sent:?}"));
+
+            if let Some(Message::SnapshotAck{sequence}) = ack
+            {
+                encoder.ack(sequence);
+            }
+        });
+    }
+
+    #[test]
+    fn entity_deltas()
+    {
+        let entity = Entity::from_raw(false, 0);
+
+        let mut encoder = SnapshotEncoder::new();
+        let mut decoder = SnapshotDecoder::new();
+
+        let info = |x: f32, name: Option<&str>| EntityInfo{
+            transform: Some(Transform{position: Vector3::new(x, 0.0, 0.0), ..Default::default()}),
+            named: name.map(|name| name.to_owned()),
+            ..Default::default()
+        };
+
+        let infos = [info(0.0, Some("a")), info(1.0, Some("a")), info(1.0, None)];
+
+        infos.into_iter().enumerate().for_each(|(i, info)|
+        {
+            let sent = vec![Message::EntitySet{entity, info: Box::new(info)}];
+
+            let encoded = encoder.encode(sent.clone());
+
+            match (i, &encoded[1])
+            {
+                (0, Message::EntitySet{..}) => (),
+                (1, Message::EntitySetDelta{info, removed, ..}) =>
+                {
+                    assert!(info.transform.is_some() && info.named.is_none() && removed.is_empty());
+                },
+                (2, Message::EntitySetDelta{info, removed, ..}) =>
+                {
+                    assert!(info.transform.is_none() && info.named.is_none());
+                    assert_eq!(removed, &[Component::named as usize]);
+                },
+                x => panic!("wrong encoding {x:?}")
+            }
+
+            let (ack, decoded) = deliver(&mut decoder, encoded);
+
+            assert_eq!(format!("{decoded:?}"), format!("{sent:?}"));
+
+            if let Some(Message::SnapshotAck{sequence}) = ack
+            {
+                encoder.ack(sequence);
+            }
+        });
+    }
+
+    #[test]
+    fn keyframe_without_acks()
+    {
+        let entity = Entity::from_raw(false, 0);
+
+        let mut encoder = SnapshotEncoder::new();
+        let mut decoder = SnapshotDecoder::new();
+
+        let (ack, _) = deliver(&mut decoder, encoder.encode(vec![position_message(entity, 0.0)]));
+
+        if let Some(Message::SnapshotAck{sequence}) = ack
+        {
+            encoder.ack(sequence);
+        }
+
+        // the client stops acking so eventually it gets everything in full again
+        let last = (1..=MAX_UNACKED + 1).map(|i|
+        {
+            let encoded = encoder.encode(vec![position_message(entity, i as f32)]);
+
+            let (_, decoded) = deliver(&mut decoder, encoded.clone());
+            assert_eq!(format!("{decoded:?}"), format!("{:?}", vec![position_message(entity, i as f32)]));
+
+            encoded
+        }).last().unwrap();
+
+        assert!(matches!(last[0], Message::Snapshot{base: None, ..}));
+        assert!(matches!(last[1], Message::SyncPositionRotation{..}));
+    }
+}
@@ -0,0 +1,101 @@
+use serde::{Serialize, Deserialize};
+
+use nalgebra::Vector3;
+
+use crate::common::{
+    Entity,
+    Message,
+    EntityPasser
+};
+
+
+// a one shot sound somewhere in the world
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Sound
+{
+    // name from sounds.json
+    pub name: String,
+    pub position: Vector3<f32>,
+    pub volume: f32
+}
+
+impl Sound
+{
+    pub fn new(name: impl Into<String>, position: Vector3<f32>) -> Self
+    {
+        Self{name: name.into(), position, volume: 1.0}
+    }
+
+    pub fn with_volume(mut self, volume: f32) -> Self
+    {
+        self.volume = volume;
+
+        self
+    }
+
+    // goes through the server so every client nearby hears it
+    pub fn spawn(self, passer: &mut impl EntityPasser)
+    {
+        let message = Message::PlaySound{sound: self};
+
+        passer.send_message(Message::RepeatMessage{message: Box::new(message)});
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoiseKind
+{
+    Gunshot,
+    Footstep,
+    Door,
+    Container
+}
+
+// something enemies can hear, also what the sound cues show
+#[derive(Debug, Clone)]
+pub struct Noise
+{
+    pub kind: NoiseKind,
+    pub position: Vector3<f32>,
+    pub radius: f32,
+    // whoever made it, if anyone
+    pub source: Option<Entity>
+}
+
+impl Noise
+{
+    pub fn new(kind: NoiseKind, position: Vector3<f32>, radius: f32) -> Self
+    {
+        Self{kind, position, radius, source: None}
+    }
+
+    pub fn with_source(mut self, source: Entity) -> Self
+    {
+        self.source = Some(source);
+
+        self
+    }
+}
+
+// plays for as long as the entity its attached to is around, follows it when it moves
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SoundEmitter
+{
+    pub name: String,
+    pub volume: f32,
+    // otherwise it only plays once after getting added
+    pub looping: bool
+}
+
+impl SoundEmitter
+{
+    pub fn new(name: impl Into<String>) -> Self
+    {
+        Self{name: name.into(), volume: 1.0, looping: false}
+    }
+
+    pub fn looping(name: impl Into<String>) -> Self
+    {
+        Self{looping: true, ..Self::new(name)}
+    }
+}
@@ -1,12 +1,15 @@
-use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
 
 use nalgebra::Vector3;
 
-use crate::common::Entity;
+use crate::common::{
+    some_or_return,
+    unique_pairs_no_self,
+    Entity,
+    world::TILE_SIZE
+};
 
 
-const MAX_DEPTH: usize = 10;
-
 pub type CellPos = Vector3<i32>;
 
 #[derive(Debug, Clone)]
@@ -17,159 +20,190 @@ pub struct SpatialInfo
     pub scale: Vector3<f32>
 }
 
-#[derive(Debug)]
-enum KNode
+// uniform grid keyed by tile coordinates, entities go into every cell their bounds touch
+// kept between frames and only entities that changed cells get moved around
+#[derive(Debug, Default)]
+pub struct SpatialGrid
 {
-    Node{left: Box<KNode>, right: Box<KNode>},
-    Leaf{entities: Vec<Entity>}
+    cells: HashMap<CellPos, Vec<Entity>>,
+    bounds: HashMap<Entity, (CellPos, CellPos)>
 }
 
-impl KNode
+impl SpatialGrid
 {
-    pub fn empty() -> Self
+    pub fn new() -> Self
     {
-        Self::Leaf{entities: Vec::new()}
+        Self::default()
     }
 
-    fn find_median(infos: &mut [SpatialInfo], axis_i: usize) -> f32
+    fn cell_of(position: Vector3<f32>) -> CellPos
     {
-        infos.sort_unstable_by(|a, b|
-        {
-            a.position.index(axis_i).partial_cmp(b.position.index(axis_i))
-                .unwrap_or(Ordering::Equal)
-        });
-
-        let query_index = |i: usize| -> f32
-        {
-            *infos[i].position.index(axis_i)
-        };
+        (position / TILE_SIZE).map(|x| x.floor() as i32)
+    }
 
-        let len = infos.len();
-        if len % 2 == 0
+    fn for_each_cell(low: CellPos, high: CellPos, mut f: impl FnMut(CellPos))
+    {
+        for z in low.z..=high.z
         {
-            if len == 0
+            for y in low.y..=high.y
             {
-                return query_index(0);
+                for x in low.x..=high.x
+                {
+                    f(CellPos::new(x, y, z));
+                }
             }
-
-            let a = len / 2;
-            let b = a - 1;
-
-            (query_index(a) + query_index(b)) / 2.0
-        } else
-        {
-            query_index(len / 2)
         }
     }
 
-    fn new_leaf(infos: Vec<SpatialInfo>) -> Self
+    pub fn cell_size() -> f32
+    {
+        TILE_SIZE
+    }
+
+    // every occupied cell with how many entities are in it
+    pub fn cells(&self) -> impl Iterator<Item=(CellPos, usize)> + '_
     {
-        Self::Leaf{entities: infos.into_iter().map(|x| x.entity).collect()}
+        self.cells.iter().map(|(cell, entities)| (*cell, entities.len()))
     }
 
-    fn random_sample<T: Clone>(values: &[T], amount: usize) -> Vec<T>
+    // anything not in the infos gets removed
+    pub fn build(&mut self, infos: impl Iterator<Item=SpatialInfo>)
     {
-        let s = fastrand::usize(0..values.len());
+        let mut seen = HashSet::new();
+
+        infos.for_each(|info|
+        {
+            seen.insert(info.entity);
+
+            self.update(info);
+        });
+
+        let removed: Vec<Entity> = self.bounds.keys().filter(|entity| !seen.contains(entity)).copied().collect();
 
-        values.iter().chain(values.iter()).skip(s).take(amount).cloned().collect()
+        removed.into_iter().for_each(|entity| self.remove(entity));
     }
 
-    pub fn new(mut infos: Vec<SpatialInfo>, depth: usize) -> Self
+    pub fn update(&mut self, info: SpatialInfo)
     {
-        if depth > MAX_DEPTH || infos.len() < 2
+        let low = Self::cell_of(info.position - info.scale);
+        let high = Self::cell_of(info.position + info.scale);
+
+        if self.bounds.get(&info.entity) == Some(&(low, high))
         {
-            return Self::new_leaf(infos);
+            return;
         }
 
-        let axis_i = depth % 3;
+        self.remove(info.entity);
+
+        self.bounds.insert(info.entity, (low, high));
+
+        Self::for_each_cell(low, high, |cell|
+        {
+            self.cells.entry(cell).or_default().push(info.entity);
+        });
+    }
 
-        let median = {
-            const AMOUNT: usize = 16;
+    pub fn remove(&mut self, entity: Entity)
+    {
+        let (low, high) = some_or_return!(self.bounds.remove(&entity));
 
-            if infos.len() <= AMOUNT
+        Self::for_each_cell(low, high, |cell|
+        {
+            if let Some(entities) = self.cells.get_mut(&cell)
             {
-                Self::find_median(&mut infos, axis_i)
-            } else
+                entities.retain(|x| *x != entity);
+
+                if entities.is_empty()
+                {
+                    self.cells.remove(&cell);
+                }
+            }
+        });
+    }
+
+    // every entity in the cells the line passes through, each one only once
+    pub fn along_line(&self, start: Vector3<f32>, end: Vector3<f32>) -> Vec<Entity>
+    {
+        let mut found = Vec::new();
+
+        let mut add = |cell: &CellPos|
+        {
+            if let Some(entities) = self.cells.get(cell)
             {
-                let mut random_sample = Self::random_sample(&infos, AMOUNT);
-                Self::find_median(&mut random_sample, axis_i)
+                found.extend(entities.iter().copied());
             }
         };
 
-        let mut left_infos = Vec::new();
-        let mut right_infos = Vec::new();
+        let direction = end - start;
 
-        infos.into_iter().for_each(|info|
-        {
-            let this_scale = *info.scale.index(axis_i);
-            let this_distance = *info.position.index(axis_i);
+        let mut cell = Self::cell_of(start);
+        let last = Self::cell_of(end);
 
-            let median_distance = this_distance - median;
+        let step = direction.map(|x| if x > 0.0 { 1 } else if x < 0.0 { -1 } else { 0 });
 
-            if median_distance.abs() < this_scale
-            {
-                // in both halfspaces
-                left_infos.push(info.clone());
-                right_infos.push(info);
-            } else if median_distance < 0.0
+        // fraction of the line it takes to cross a whole cell on each axis
+        let delta = direction.map(|x| if x == 0.0 { f32::INFINITY } else { TILE_SIZE / x.abs() });
+
+        // fraction of the line until the next cell boundary on each axis
+        let mut next = Vector3::from_fn(|i, _|
+        {
+            if step[i] == 0
             {
-                // in left halfspace
-                left_infos.push(info);
+                f32::INFINITY
             } else
             {
-                // in right halfspace
-                right_infos.push(info);
+                let boundary = (cell[i] + (step[i] > 0) as i32) as f32 * TILE_SIZE;
+
+                (boundary - start[i]) / direction[i]
             }
         });
 
-        Self::Node{
-            left: Box::new(Self::new(left_infos, depth + 1)),
-            right: Box::new(Self::new(right_infos, depth + 1))
-        }
-    }
+        add(&cell);
 
-    fn possible_pairs(&self, f: &mut impl FnMut(&[Entity]))
-    {
-        match self
+        while cell != last
         {
-            Self::Node{left, right} =>
-            {
-                left.possible_pairs(f);
-                right.possible_pairs(f);
-            },
-            Self::Leaf{entities} =>
+            let axis = next.imin();
+
+            if next[axis] > 1.0
             {
-                f(entities);
+                break;
             }
-        }
-    }
-}
 
-#[derive(Debug)]
-pub struct SpatialGrid
-{
-    node: KNode
-}
+            cell[axis] += step[axis];
+            next[axis] += delta[axis];
 
-impl SpatialGrid
-{
-    pub fn new() -> Self
-    {
-        Self{
-            node: KNode::empty()
+            add(&cell);
         }
-    }
 
-    pub fn build(&mut self, infos: impl Iterator<Item=SpatialInfo>)
-    {
-        let queued: Vec<SpatialInfo> = infos.collect();
+        found.sort_unstable();
+        found.dedup();
 
-        self.node = KNode::new(queued, 0);
+        found
     }
 
-    pub fn possible_pairs(&self, mut f: impl FnMut(&[Entity]))
+    // every pair sharing a cell, each one only once
+    // sorted so the physics doesnt depend on the hashmap order and plays out the same every run
+    pub fn possible_pairs(&self, mut f: impl FnMut(Entity, Entity))
     {
-        self.node.possible_pairs(&mut f);
+        let mut pairs = Vec::new();
+
+        self.cells.iter().for_each(|(cell, entities)|
+        {
+            unique_pairs_no_self(entities.iter().copied(), |a, b|
+            {
+                // only the first cell both of them are in reports the pair
+                let first = self.bounds[&a].0.sup(&self.bounds[&b].0);
+
+                if first == *cell
+                {
+                    pairs.push((a.min(b), a.max(b)));
+                }
+            });
+        });
+
+        pairs.sort_unstable();
+
+        pairs.into_iter().for_each(|(a, b)| f(a, b));
     }
 }
 
@@ -177,28 +211,71 @@ impl SpatialGrid
 #[cfg(test)]
 mod tests
 {
+    use std::iter;
+
     use super::*;
 
-    
-    fn almost_equal(a: f32, b: f32)
+
+    #[test]
+    fn pairs()
     {
-        assert!((a - b).abs() < 0.0001);
+        let infos = [(0, 0.5, 0.1), (1, 0.7, 0.1), (2, 10.5, 0.1), (3, 0.5, 3.0)];
+
+        let mut space = SpatialGrid::new();
+        space.build(infos.into_iter().map(|(id, x, scale)|
+        {
+            SpatialInfo{
+                entity: Entity::from_raw(false, id),
+                position: Vector3::new(x, 0.5, 0.5) * TILE_SIZE,
+                scale: Vector3::repeat(scale * TILE_SIZE)
+            }
+        }));
+
+        let mut pairs = Vec::new();
+        space.possible_pairs(|a, b|
+        {
+            let (a, b) = (a.id(), b.id());
+
+            pairs.push((a.min(b), a.max(b)));
+        });
+
+        pairs.sort_unstable();
+
+        assert_eq!(pairs, vec![(0, 1), (0, 3), (1, 3)]);
     }
 
     #[test]
-    fn median()
+    fn moving_and_lines()
     {
-        let mut values = [5.3, 0.2, 11.3, 31.2].map(|x|
+        let info = |id, x: f32, y: f32|
         {
             SpatialInfo{
-                entity: Entity::from_raw(false, 0),
-                position: Vector3::new(x, 0.0, 0.0),
-                scale: Vector3::zeros()
+                entity: Entity::from_raw(false, id),
+                position: Vector3::new(x, y, 0.5) * TILE_SIZE,
+                scale: Vector3::repeat(0.1 * TILE_SIZE)
             }
-        });
+        };
+
+        let mut space = SpatialGrid::new();
+        space.build([info(0, 2.5, 0.5), info(1, 2.5, 3.5)].into_iter());
+
+        let line = |space: &SpatialGrid|
+        {
+            let start = Vector3::new(0.5, 0.5, 0.5) * TILE_SIZE;
+            let end = Vector3::new(5.5, 0.5, 0.5) * TILE_SIZE;
+
+            space.along_line(start, end).into_iter().map(|x| x.id()).collect::<Vec<_>>()
+        };
+
+        assert_eq!(line(&space), vec![0]);
+
+        space.build([info(0, 2.5, 2.5), info(1, 3.5, 0.5)].into_iter());
+
+        assert_eq!(line(&space), vec![1]);
 
-        let x = KNode::find_median(&mut values, 0);
+        space.build(iter::empty());
 
-        almost_equal(x, 8.3);
+        assert!(line(&space).is_empty());
+        assert_eq!(space.cells().count(), 0);
     }
 }
@@ -24,11 +24,17 @@ use yanyaengine::{
     }
 };
 
-use crate::common::world::Tile;
+use crate::common::{
+    overrides::overridden,
+    world::{Tile, TileRotation, PosDirection, DirectionsGroup}
+};
 
 
 const TEXTURE_TILE_SIZE: usize = 16;
 
+// fraction of the tile a blended neighbor reaches into
+const BLEND_DEPTH: f32 = 0.5;
+
 // this makes the texture size always a power of 2
 pub const PADDING: usize = TEXTURE_TILE_SIZE / 2;
 const PADDED_TILE_SIZE: usize = TEXTURE_TILE_SIZE + PADDING * 2;
@@ -36,7 +42,9 @@ const PADDED_TILE_SIZE: usize = TEXTURE_TILE_SIZE + PADDING * 2;
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SpawnerTile
 {
-    Door{width: u32}
+    Door{width: u32},
+    // blows air out in front of it for length tiles
+    Vent{length: u32}
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -47,6 +55,43 @@ pub enum SpecialTile
     Spawner(SpawnerTile)
 }
 
+// what walking on it sounds like
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TileMaterial
+{
+    #[default]
+    Concrete,
+    Wood,
+    Grass,
+    Water
+}
+
+impl TileMaterial
+{
+    pub fn footstep(self) -> &'static str
+    {
+        match self
+        {
+            Self::Concrete => "step_concrete",
+            Self::Wood => "step_wood",
+            Self::Grass => "step_grass",
+            Self::Water => "step_water"
+        }
+    }
+
+    // how far away enemies can hear it, relative to concrete
+    pub fn loudness(self) -> f32
+    {
+        match self
+        {
+            Self::Concrete => 1.0,
+            Self::Wood => 1.3,
+            Self::Grass => 0.5,
+            Self::Water => 1.6
+        }
+    }
+}
+
 impl SpecialTile
 {
     fn is_spawner(&self) -> bool
@@ -59,6 +104,109 @@ impl SpecialTile
     }
 }
 
+// variant textures have their connected sides drawn like this:
+// end connects at the top, straight at the top and bottom, corner at the top and right
+// and edge everywhere except the left
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AutotileRaw
+{
+    // other tiles that count as connected, the tile itself always does
+    pub connects: Vec<String>,
+    pub single: Option<PathBuf>,
+    pub end: Option<PathBuf>,
+    pub straight: Option<PathBuf>,
+    pub corner: Option<PathBuf>,
+    pub edge: Option<PathBuf>
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutotileVariant
+{
+    Single,
+    End,
+    Straight,
+    Corner,
+    Edge
+}
+
+// which sides of the world the top and the right of a texture end up on
+fn rotated_sides(rotation: TileRotation) -> (PosDirection, PosDirection)
+{
+    match rotation
+    {
+        TileRotation::Up => (PosDirection::Down, PosDirection::Right),
+        TileRotation::Right => (PosDirection::Right, PosDirection::Up),
+        TileRotation::Down => (PosDirection::Up, PosDirection::Left),
+        TileRotation::Left => (PosDirection::Left, PosDirection::Down)
+    }
+}
+
+const ROTATIONS: [TileRotation; 4] = [
+    TileRotation::Up,
+    TileRotation::Right,
+    TileRotation::Down,
+    TileRotation::Left
+];
+
+// rotation that puts the top of the texture on this side
+pub fn rotation_facing(direction: PosDirection) -> TileRotation
+{
+    ROTATIONS.into_iter().find(|rotation| rotated_sides(*rotation).0 == direction)
+        .unwrap_or_default()
+}
+
+#[derive(Debug, Clone)]
+pub struct Autotile
+{
+    connects: Vec<usize>,
+    variants: [Option<usize>; 5]
+}
+
+impl Autotile
+{
+    pub fn connects(&self, tile: Tile) -> bool
+    {
+        self.connects.contains(&tile.id())
+    }
+
+    // texture index and rotation, none means the normal texture fits
+    pub fn pick(&self, connected: DirectionsGroup<bool>) -> Option<(usize, TileRotation)>
+    {
+        let amount = connected.fold(0, |acc, (_, x)| acc + x as usize);
+
+        let find = |f: fn(bool, bool, bool) -> bool| -> TileRotation
+        {
+            ROTATIONS.into_iter().find(|rotation|
+            {
+                let (top, right) = rotated_sides(*rotation);
+
+                f(connected[top], connected[right], connected[top.opposite()])
+            }).unwrap_or_default()
+        };
+
+        let (variant, rotation) = match amount
+        {
+            0 => (AutotileVariant::Single, TileRotation::Up),
+            1 => (AutotileVariant::End, find(|top, _, _| top)),
+            2 =>
+            {
+                if (connected.up && connected.down) || (connected.left && connected.right)
+                {
+                    (AutotileVariant::Straight, find(|top, _, bottom| top && bottom))
+                } else
+                {
+                    (AutotileVariant::Corner, find(|top, right, _| top && right))
+                }
+            },
+            3 => (AutotileVariant::Edge, find(|top, right, bottom| top && right && bottom)),
+            _ => return None
+        };
+
+        self.variants[variant as usize].map(|texture| (texture, rotation))
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TileInfoRaw
 {
@@ -67,7 +215,19 @@ pub struct TileInfoRaw
     pub special: Option<SpecialTile>,
     pub colliding: Option<bool>,
     pub transparent: Option<bool>,
-    pub texture: Option<PathBuf>
+    pub texture: Option<PathBuf>,
+    pub autotile: Option<AutotileRaw>,
+    // dithers into neighboring tiles that also blend
+    pub blend: Option<bool>,
+    // how strongly nearby sprites show up in it
+    pub reflective: Option<f32>,
+    // density of the liquid, anything lighter floats in it
+    pub liquid: Option<f32>,
+    pub material: Option<TileMaterial>,
+    // how much explosion damage it takes to knock it out, nothing can if its missing
+    pub toughness: Option<f32>,
+    // seconds it burns for before its gone, doesnt catch fire if its missing
+    pub flammable: Option<f32>
 }
 
 impl TileInfoRaw
@@ -86,7 +246,15 @@ pub struct TileInfo
     pub drawable: bool,
     pub special: Option<SpecialTile>,
     pub colliding: bool,
-    pub transparent: bool
+    pub transparent: bool,
+    pub autotile: Option<Autotile>,
+    // texture index of the dithered edge
+    pub blend: Option<usize>,
+    pub reflective: f32,
+    pub liquid: Option<f32>,
+    pub material: TileMaterial,
+    pub toughness: Option<f32>,
+    pub flammable: Option<f32>
 }
 
 impl TileInfo
@@ -104,7 +272,14 @@ impl TileInfo
                 {
                     color.a != u8::MAX
                 })).unwrap_or(true)
-            })
+            }),
+            autotile: None,
+            blend: None,
+            reflective: tile_raw.reflective.unwrap_or(0.0),
+            liquid: tile_raw.liquid,
+            material: tile_raw.material.unwrap_or_default(),
+            toughness: tile_raw.toughness,
+            flammable: tile_raw.flammable
         };
 
         #[allow(clippy::collapsible_match, clippy::single_match)]
@@ -131,6 +306,14 @@ impl TileInfo
                                 eprintln!("width must be above 0, replacing with 1");
                                 *width = 1;
                             }
+                        },
+                        SpawnerTile::Vent{length} =>
+                        {
+                            if *length == 0
+                            {
+                                eprintln!("length must be above 0, replacing with 1");
+                                *length = 1;
+                            }
                         }
                     }
                 },
@@ -157,7 +340,8 @@ pub struct TileMapWithTextures
 pub enum TileMapError
 {
     Io(io::Error),
-    Image{error: ImageError, path: Option<PathBuf>}
+    Image{error: ImageError, path: Option<PathBuf>},
+    UnknownTile(String)
 }
 
 impl fmt::Display for TileMapError
@@ -167,6 +351,7 @@ impl fmt::Display for TileMapError
         let s = match self
         {
             Self::Io(x) => x.to_string(),
+            Self::UnknownTile(name) => format!("tile named {name} doesnt exist"),
             Self::Image{error, path} =>
             {
                 let err = error.to_string();
@@ -206,7 +391,8 @@ impl From<io::Error> for TileMapError
 #[derive(Debug)]
 pub struct TileMap
 {
-    tiles: Vec<TileInfo>
+    tiles: Vec<TileInfo>,
+    textures_amount: usize
 }
 
 #[allow(dead_code)]
@@ -219,9 +405,9 @@ impl TileMap
     {
         let textures_root = Path::new(textures_root);
 
-        let tiles = serde_json::from_reader::<_, Vec<TileInfoRaw>>(File::open(tiles_path)?)?;
+        let tiles = serde_json::from_reader::<_, Vec<TileInfoRaw>>(File::open(overridden(tiles_path))?)?;
 
-        let textures = tiles.iter().map(|tile_raw|
+        let mut textures = tiles.iter().map(|tile_raw|
         {
             if tile_raw.has_texture()
             {
@@ -241,19 +427,86 @@ impl TileMap
             }
         }).collect::<Result<Vec<Option<SimpleImage>>, _>>()?;
 
+        let ids: HashMap<&str, usize> = tiles.iter().enumerate().map(|(index, tile_raw)|
+        {
+            (tile_raw.name.as_str(), index + 1)
+        }).collect();
+
+        let load_extra = |textures: &mut Vec<Option<SimpleImage>>, path: &PathBuf|
+        {
+            let texture = Self::load_texture(
+                TEXTURE_TILE_SIZE as u32,
+                TEXTURE_TILE_SIZE as u32,
+                textures_root.join(path)
+            )?;
+
+            textures.push(Some(texture));
+
+            Ok::<_, TileMapError>(textures.len() - 1)
+        };
+
+        let extras = tiles.iter().enumerate().map(|(index, tile_raw)|
+        {
+            let autotile = tile_raw.autotile.as_ref().map(|autotile|
+            {
+                let connects = iter::once(Ok(index + 1)).chain(autotile.connects.iter().map(|name|
+                {
+                    ids.get(name.as_str()).copied().ok_or_else(|| TileMapError::UnknownTile(name.clone()))
+                })).collect::<Result<Vec<_>, _>>()?;
+
+                let mut variants = [None; 5];
+                let paths = [&autotile.single, &autotile.end, &autotile.straight, &autotile.corner, &autotile.edge];
+                for (variant, path) in variants.iter_mut().zip(paths)
+                {
+                    if let Some(path) = path
+                    {
+                        *variant = Some(load_extra(&mut textures, path)?);
+                    }
+                }
+
+                Ok::<_, TileMapError>(Autotile{connects, variants})
+            }).transpose()?;
+
+            let blend = if tile_raw.blend.unwrap_or(false)
+            {
+                textures[index].as_ref().map(Self::blend_texture).map(|texture|
+                {
+                    textures.push(Some(texture));
+
+                    textures.len() - 1
+                })
+            } else
+            {
+                None
+            };
+
+            Ok((autotile, blend))
+        }).collect::<Result<Vec<_>, TileMapError>>()?;
+
         let tiles = iter::once(TileInfo{
             name: "air".to_owned(),
             drawable: false,
             special: None,
             colliding: false,
-            transparent: true
-        }).chain(tiles.into_iter().zip(textures.iter()).map(|(tile_raw, texture)|
+            transparent: true,
+            autotile: None,
+            blend: None,
+            reflective: 0.0,
+            liquid: None,
+            material: TileMaterial::default(),
+            toughness: None,
+            flammable: None
+        }).chain(tiles.into_iter().zip(textures.iter()).zip(extras).map(|((tile_raw, texture), (autotile, blend))|
         {
-            TileInfo::from_raw(texture, tile_raw)
+            TileInfo{
+                autotile,
+                blend,
+                ..TileInfo::from_raw(texture, tile_raw)
+            }
         })).collect();
 
         Ok(TileMapWithTextures{
-            tilemap: Self{tiles},
+            tilemap: Self{tiles, textures_amount: textures.len()},
             textures
         })
     }
@@ -296,7 +549,7 @@ impl TileMap
 
     pub fn texture_row_size(&self) -> usize
     {
-        (((self.tiles.len() - 1) as f64).sqrt().ceil() as usize).max(2)
+        ((self.textures_amount as f64).sqrt().ceil() as usize).max(2)
     }
 
     pub fn pixel_fraction(&self, fraction: f32) -> f32
@@ -304,12 +557,44 @@ impl TileMap
         fraction / (self.texture_row_size() * PADDED_TILE_SIZE) as f32
     }
 
+    // fades out from the top edge, dithered so it doesnt need any blending
+    fn blend_texture(texture: &SimpleImage) -> SimpleImage
+    {
+        const BAYER: [[u8; 4]; 4] = [
+            [0, 8, 2, 10],
+            [12, 4, 14, 6],
+            [3, 11, 1, 9],
+            [15, 7, 13, 5]
+        ];
+
+        let size = TEXTURE_TILE_SIZE;
+        let mut image = SimpleImage::new(vec![Color::new(0, 0, 0, 0); size * size], size, size);
+
+        for y in 0..size
+        {
+            for x in 0..size
+            {
+                let fade = 1.0 - (y as f32 + 0.5) / (size as f32 * BLEND_DEPTH);
+                let threshold = (BAYER[y % 4][x % 4] as f32 + 0.5) / 16.0;
+
+                if fade > threshold
+                {
+                    image.set_pixel(texture.get_pixel(x, y), x, y);
+                }
+            }
+        }
+
+        image
+    }
+
     fn load_texture(
         width: u32,
         height: u32,
         path: PathBuf
     ) -> Result<SimpleImage, TileMapError>
     {
+        let path = overridden(path);
+
         let image = image::open(&path).map_err(|error|
         {
             TileMapError::Image{error, path: Some(path)}
@@ -4,9 +4,9 @@ use std::{
     borrow::Borrow,
     cmp::Ordering,
     hash::Hash,
-    io::Write,
+    io::{self, Write},
     fmt::Debug,
-    fs::File,
+    fs::{self, File},
     collections::HashMap,
     path::{Path, Component},
     ops::{Index, Range, RangeInclusive}
@@ -32,6 +32,8 @@ pub use crate::{
     }
 };
 
+use crate::common::rng::{self, RngStream};
+
 
 #[macro_export]
 macro_rules! define_layers_enum
@@ -231,6 +233,7 @@ where
 }
 
 pub fn pick_by_commonness<I, T, F>(
+    stream: RngStream,
     this_commonness: f64,
     iter: I,
     f: F
@@ -245,7 +248,7 @@ where
         c.powf(this_commonness)
     };
 
-    WeightedPicker::pick_from(fastrand::f64(), iter, move |value|
+    WeightedPicker::pick_from(rng::f64(stream), iter, move |value|
     {
         scaled_commonness(f(value))
     })
@@ -280,7 +283,7 @@ pub fn normalize_path(path: impl AsRef<Path>) -> String
 
 pub fn random_f32(range: RangeInclusive<f32>) -> f32
 {
-    fastrand::f32() * (range.end() - range.start()) + range.start()
+    rng::f32(RngStream::Cosmetic) * (range.end() - range.start()) + range.start()
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -298,7 +301,7 @@ impl SeededRandom
 {
     pub fn new() -> Self
     {
-        Self(fastrand::u64(0..u64::MAX))
+        Self(rng::u64(RngStream::Ai, 0..u64::MAX))
     }
 
     pub fn set_state(&mut self, value: u64)
@@ -375,7 +378,7 @@ impl SeededRandom
 
 pub fn random_rotation() -> f32
 {
-    fastrand::f32() * (f32::consts::PI * 2.0)
+    rng::f32(RngStream::Cosmetic) * (f32::consts::PI * 2.0)
 }
 
 pub fn short_rotation(rotation: f32) -> f32
@@ -439,6 +442,44 @@ impl EaseOut for Vector3<f32>
     }
 }
 
+pub trait Lerp
+{
+    fn lerp(&self, target: Self, amount: f32) -> Self;
+}
+
+impl Lerp for f32
+{
+    fn lerp(&self, target: Self, amount: f32) -> Self
+    {
+        lerp(*self, target, amount)
+    }
+}
+
+impl Lerp for [f32; 3]
+{
+    fn lerp(&self, target: Self, amount: f32) -> Self
+    {
+        let mut output = *self;
+        output.iter_mut().zip(target).for_each(|(current, target)|
+        {
+            *current = lerp(*current, target, amount);
+        });
+
+        output
+    }
+}
+
+impl Lerp for Vector3<f32>
+{
+    fn lerp(&self, target: Self, amount: f32) -> Self
+    {
+        self.zip_map(&target, |a, b|
+            {
+                lerp(a, b, amount)
+            })
+    }
+}
+
 // thanks freya holmer
 pub fn ease_out(current: f32, target: f32, decay: f32, dt: f32) -> f32
 {
@@ -624,6 +665,35 @@ pub fn direction_arrow_info(
     })
 }
 
+pub fn line_info(
+    start: Vector3<f32>,
+    end: Vector3<f32>,
+    thickness: f32,
+    color: [f32; 3]
+) -> EntityInfo
+{
+    let diff = end - start;
+
+    EntityInfo{
+        transform: Some(Transform{
+            position: (start + end) / 2.0,
+            scale: Vector3::new(diff.xy().magnitude(), thickness, thickness),
+            rotation: diff.y.atan2(diff.x),
+            ..Default::default()
+        }),
+        render: Some(RenderInfo{
+            object: Some(RenderObjectKind::Texture{
+                name: "ui/solid.png".to_owned()
+            }.into()),
+            z_level: ZLevel::highest_non_ui(),
+            mix: Some(MixColor{color, amount: 1.0, keep_transparency: true}),
+            ..Default::default()
+        }),
+        watchers: Some(Watchers::simple_one_frame()),
+        ..Default::default()
+    }
+}
+
 pub fn debug_env() -> Option<String>
 {
     env::var("STEPHANIE_DEBUG").ok()
@@ -659,6 +729,27 @@ pub fn write_log_ln(text: impl Into<String>)
     write_log(text)
 }
 
+pub fn copy_dir(from: &Path, to: &Path) -> io::Result<()>
+{
+    fs::create_dir_all(to)?;
+
+    for entry in fs::read_dir(from)?
+    {
+        let entry = entry?;
+        let path = to.join(entry.file_name());
+
+        if entry.file_type()?.is_dir()
+        {
+            copy_dir(&entry.path(), &path)?;
+        } else
+        {
+            fs::copy(entry.path(), path)?;
+        }
+    }
+
+    Ok(())
+}
+
 pub fn insertion_sort_with<T, KeyGetter, Swapper, Sortable>(
     values: &mut [T],
     get_key: KeyGetter,
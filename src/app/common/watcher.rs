@@ -141,6 +141,8 @@ pub enum WatcherAction
     OutlineableDisable,
     SetVisible(bool),
     SetMixColor(Option<MixColor>),
+    SetEmissive(Option<Emissive>),
+    SetEmissiveIntensity(f32),
     SetTargetPosition(Vector3<f32>),
     SetTargetScale(Vector3<f32>),
     SetTargetRotation(f32),
@@ -191,6 +193,23 @@ impl WatcherAction
                     *target = value;
                 }
             },
+            Self::SetEmissive(value) =>
+            {
+                if let Some(mut target) = entities.emissive_target(entity)
+                {
+                    *target = value;
+                }
+            },
+            Self::SetEmissiveIntensity(intensity) =>
+            {
+                if let Some(mut target) = entities.emissive_target(entity)
+                {
+                    if let Some(emissive) = target.as_mut()
+                    {
+                        emissive.intensity = intensity;
+                    }
+                }
+            },
             Self::SetTargetPosition(position) =>
             {
                 if let Some(mut target) = entities.target(entity)
@@ -0,0 +1,144 @@
+use std::f32;
+
+use serde::{Serialize, Deserialize};
+
+use nalgebra::Vector2;
+
+use crate::common::rng::{self, RngStream};
+
+
+// seconds between weather changes, inclusive on both ends
+const WEATHER_DURATION: (f32, f32) = (60.0, 240.0);
+
+// at full intensity
+const FOG_SIGHT_SCALE: f32 = 0.4;
+const SNOW_SIGHT_SCALE: f32 = 0.8;
+const RAIN_HEARING_SCALE: f32 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WeatherKind
+{
+    Clear,
+    Rain,
+    Snow,
+    Fog
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Weather
+{
+    pub kind: WeatherKind,
+    pub intensity: f32,
+    // direction and strength, strength goes up to 1
+    pub wind: Vector2<f32>
+}
+
+impl Default for Weather
+{
+    fn default() -> Self
+    {
+        Self{
+            kind: WeatherKind::Clear,
+            intensity: 0.0,
+            wind: Vector2::zeros()
+        }
+    }
+}
+
+impl Weather
+{
+    pub fn random() -> Self
+    {
+        let kind = match rng::f32(RngStream::Weather)
+        {
+            x if x < 0.5 => WeatherKind::Clear,
+            x if x < 0.8 => WeatherKind::Rain,
+            x if x < 0.9 => WeatherKind::Snow,
+            _ => WeatherKind::Fog
+        };
+
+        let intensity = if kind == WeatherKind::Clear
+        {
+            0.0
+        } else
+        {
+            0.3 + rng::f32(RngStream::Weather) * 0.7
+        };
+
+        let angle = rng::f32(RngStream::Weather) * (f32::consts::PI * 2.0);
+        let wind = Vector2::new(angle.cos(), angle.sin()) * rng::f32(RngStream::Weather);
+
+        Self{kind, intensity, wind}
+    }
+
+    // how far anything can see compared to clear weather
+    pub fn sight_scale(&self) -> f32
+    {
+        let lowest = match self.kind
+        {
+            WeatherKind::Fog => FOG_SIGHT_SCALE,
+            WeatherKind::Snow => SNOW_SIGHT_SCALE,
+            _ => return 1.0
+        };
+
+        1.0 - (1.0 - lowest) * self.intensity
+    }
+
+    // rain drowns out noises so they dont carry as far
+    pub fn hearing_scale(&self) -> f32
+    {
+        if self.kind == WeatherKind::Rain
+        {
+            1.0 - (1.0 - RAIN_HEARING_SCALE) * self.intensity
+        } else
+        {
+            1.0
+        }
+    }
+}
+
+// only the server changes the weather, clients just get told about it
+pub struct WeatherState
+{
+    weather: Weather,
+    timer: f32
+}
+
+impl WeatherState
+{
+    pub fn new() -> Self
+    {
+        Self{
+            weather: Weather::default(),
+            timer: Self::duration()
+        }
+    }
+
+    fn duration() -> f32
+    {
+        let (low, high) = WEATHER_DURATION;
+
+        low + rng::f32(RngStream::Weather) * (high - low)
+    }
+
+    pub fn weather(&self) -> Weather
+    {
+        self.weather
+    }
+
+    // returns the new weather if it changed
+    pub fn update(&mut self, dt: f32) -> Option<Weather>
+    {
+        self.timer -= dt;
+
+        if self.timer > 0.0
+        {
+            return None;
+        }
+
+        self.timer = Self::duration();
+        self.weather = Weather::random();
+
+        Some(self.weather)
+    }
+}
@@ -0,0 +1,97 @@
+use std::f32;
+
+use serde::{Serialize, Deserialize};
+
+use nalgebra::Vector3;
+
+use yanyaengine::Transform;
+
+use crate::common::{
+    rotate_point_z_3d,
+    watcher::*,
+    rng::{self, RngStream},
+    EntityInfo,
+    Weather,
+    WeatherKind
+};
+
+
+// how fast gusting zones go between calm and full strength
+const GUST_SPEED: f32 = 1.5;
+
+// weather wind weaker than this doesnt make gusts
+const GUST_MIN_WIND: f32 = 0.3;
+
+// acceleration at full weather wind
+const GUST_STRENGTH: f32 = 0.6;
+
+const GUST_SIZE: f32 = 2.0;
+const GUST_LIFETIME: f32 = 6.0;
+
+// pushes everything physical inside of it, the transform is the volume
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WindZone
+{
+    // acceleration so heavy and light stuff get pushed the same way
+    pub force: Vector3<f32>,
+    // 0 is a steady stream, 1 dies down completely between gusts
+    pub gustiness: f32,
+    #[serde(skip)]
+    phase: f32
+}
+
+impl WindZone
+{
+    pub fn new(force: Vector3<f32>, gustiness: f32) -> Self
+    {
+        Self{
+            force,
+            gustiness,
+            phase: rng::f32(RngStream::Weather) * (f32::consts::PI * 2.0)
+        }
+    }
+
+    pub fn update(&mut self, dt: f32)
+    {
+        self.phase = (self.phase + dt * GUST_SPEED) % (f32::consts::PI * 2.0);
+    }
+
+    pub fn acceleration(&self) -> Vector3<f32>
+    {
+        let wave = (self.phase.sin() + 1.0) / 2.0;
+
+        self.force * (1.0 - self.gustiness * wave)
+    }
+
+    pub fn contains(transform: &Transform, position: Vector3<f32>) -> bool
+    {
+        let local = rotate_point_z_3d(position - transform.position, -transform.rotation);
+
+        let half = transform.scale / 2.0;
+
+        local.x.abs() <= half.x && local.y.abs() <= half.y && local.z.abs() <= half.z
+    }
+
+    // storms blow short lived gusts around
+    pub fn gust(weather: &Weather, position: Vector3<f32>) -> Option<EntityInfo>
+    {
+        let wind = weather.wind.magnitude();
+        if weather.kind == WeatherKind::Clear || wind < GUST_MIN_WIND
+        {
+            return None;
+        }
+
+        let force = Vector3::new(weather.wind.x, weather.wind.y, 0.0) * GUST_STRENGTH;
+
+        Some(EntityInfo{
+            transform: Some(Transform{
+                position,
+                scale: Vector3::repeat(GUST_SIZE),
+                ..Default::default()
+            }),
+            wind: Some(WindZone::new(force, 1.0)),
+            watchers: Some(Watchers::simple_disappearing(GUST_LIFETIME)),
+            ..Default::default()
+        })
+    }
+}
@@ -14,6 +14,7 @@ use crate::{
         collider::*,
         TileMap,
         TileInfo,
+        TileMaterial,
         Entity,
         OccludingCaster,
         message::Message
@@ -68,6 +69,57 @@ pub struct ChunkWithEntities
     pub entities: Vec<Entity>
 }
 
+#[derive(Debug, Clone)]
+pub struct TileHit
+{
+    // distance along the line to just before the tile
+    pub distance: f32,
+    pub normal: Vector3<f32>
+}
+
+// first colliding tile along the line, checked every half tile so a single wall cant get skipped
+pub fn line_tile_hit(
+    start: Vector3<f32>,
+    end: Vector3<f32>,
+    mut colliding: impl FnMut(TilePos) -> bool
+) -> Option<TileHit>
+{
+    let offset = end - start;
+    let distance = offset.magnitude();
+
+    if distance == 0.0
+    {
+        return None;
+    }
+
+    let direction = offset / distance;
+
+    let step = TILE_SIZE * 0.5;
+    let steps = (distance / step).ceil() as usize;
+
+    (1..=steps).find_map(|i|
+    {
+        let travelled = (i as f32 * step).min(distance);
+        let position = start + direction * travelled;
+
+        let pos = TilePos::from(Pos3::from(position));
+
+        if !colliding(pos)
+        {
+            return None;
+        }
+
+        // the face it came in from is the one closest to the point
+        let local = position - pos.entity_position();
+        let axis = local.iamax();
+
+        let mut normal = Vector3::zeros();
+        normal[axis] = local[axis].signum();
+
+        Some(TileHit{distance: (travelled - step * 0.5).max(0.0), normal})
+    })
+}
+
 pub struct World
 {
     tilemap: Arc<TileMap>,
@@ -193,7 +245,7 @@ impl World
 
             let mut world_collider = ColliderInfo{
                 kind: ColliderType::Tile(world),
-                layer: ColliderLayer::World,
+                layer: ColliderLayer::WORLD,
                 ghost: false,
                 scale: None
             }.into();
@@ -218,6 +270,14 @@ impl World
         })
     }
 
+    pub fn tile_hit(&self, start: Vector3<f32>, end: Vector3<f32>) -> Option<TileHit>
+    {
+        line_tile_hit(start, end, |pos|
+        {
+            self.tile(pos).map(|tile| self.tile_info(*tile).colliding).unwrap_or(false)
+        })
+    }
+
     pub fn tile(&self, index: TilePos) -> Option<&Tile>
     {
         self.overmap.tile(index)
@@ -228,6 +288,31 @@ impl World
         self.overmap.tile_of(position)
     }
 
+    // whatever is getting stepped on at this position
+    pub fn material_at(&self, position: Vector3<f32>) -> TileMaterial
+    {
+        let pos = self.tile_of(position.into());
+
+        let info = |pos| self.tile(pos).map(|tile| self.tile_info(*tile));
+
+        // wading through liquid drowns out whatever is under it
+        info(pos).filter(|info| info.liquid.is_some())
+            .or_else(|| info(pos.offset(Pos3::new(0, 0, -1))))
+            .map(|info| info.material)
+            .unwrap_or_default()
+    }
+
+    // anything solid a few tiles above counts as a roof
+    pub fn under_roof(&self, position: Vector3<f32>) -> bool
+    {
+        let tile = self.tile_of(position.into());
+
+        (1..=3).any(|z|
+        {
+            self.tile(tile.offset(Pos3::new(0, 0, z))).map(|x| !x.is_none()).unwrap_or(false)
+        })
+    }
+
     pub fn set_tile(&mut self, pos: TilePos, tile: Tile)
     {
         if self.set_tile_local(pos, tile)
@@ -291,6 +376,11 @@ impl World
         self.overmap.update_buffers(info, visibility, caster);
     }
 
+    pub fn occluders_near(&self, position: Vector3<f32>, radius: f32) -> Vec<Transform>
+    {
+        self.overmap.occluders_near(position, radius)
+    }
+
     pub fn draw_shadows(
         &self,
         info: &mut DrawInfo,
@@ -7,7 +7,7 @@ use nalgebra::{Vector2, Vector3};
 
 use serde::{Serialize, Deserialize};
 
-use yanyaengine::game_object::*;
+use yanyaengine::{Transform, game_object::*};
 
 use crate::{
     client::{
@@ -76,6 +76,17 @@ pub struct TilePos
     pub local: ChunkLocal
 }
 
+impl From<Pos3<f32>> for TilePos
+{
+    fn from(position: Pos3<f32>) -> Self
+    {
+        TilePos{
+            chunk: position.rounded(),
+            local: ChunkLocal::from(position.to_tile())
+        }
+    }
+}
+
 impl TilePos
 {
     pub fn position(&self) -> Pos3<f32>
@@ -358,10 +369,7 @@ impl ClientOvermap
 
     pub fn tile_of(&self, position: Pos3<f32>) -> TilePos
     {
-        TilePos{
-            chunk: position.rounded(),
-            local: ChunkLocal::from(position.to_tile())
-        }
+        TilePos::from(position)
     }
 
     pub fn camera_moved(&mut self, position: Pos3<f32>)
@@ -414,6 +422,11 @@ impl ClientOvermap
         self.visual_overmap.update_buffers(info, visibility, caster);
     }
 
+    pub fn occluders_near(&self, position: Vector3<f32>, radius: f32) -> Vec<Transform>
+    {
+        self.visual_overmap.occluders_near(position, radius)
+    }
+
     pub fn draw_shadows(
         &self,
         info: &mut DrawInfo,
@@ -8,8 +8,8 @@ use std::{
 use nalgebra::{Vector2, Vector3};
 
 use yanyaengine::{
-    Object,
     SolidObject,
+    Transform,
     game_object::*
 };
 
@@ -22,12 +22,14 @@ use crate::{
             OccluderInfo,
             VerticalOccluder,
             ChunkInfo,
-            ChunkModelBuilder
+            ChunkModelBuilder,
+            TileObject
         }
     },
     common::{
         OccludingPlane,
         OccludingCaster,
+        light::light_reaches,
         TileMap,
         world::{
             Pos3,
@@ -124,7 +126,7 @@ pub struct VisualChunkInfo
 #[derive(Debug)]
 pub struct VisualChunk
 {
-    objects: ChunkSlice<Option<Object>>,
+    objects: ChunkSlice<Option<TileObject>>,
     occluders: ChunkSlice<Box<[OccludingPlane]>>,
     vertical_occluders: ChunkSlice<Box<[SolidObject]>>,
     draw_height: ChunkSlice<usize>,
@@ -572,11 +574,10 @@ impl VisualChunk
             return false;
         }
 
-        model_builder.create(pos, tiles.this);
-
-        #[allow(clippy::let_and_return)]
         let occluding = !tilemap[tiles.this].transparent;
 
+        model_builder.create(pos, tiles);
+
         occluding
     }
 
@@ -656,6 +657,19 @@ impl VisualChunk
         });
     }
 
+    pub fn occluders_near(
+        &self,
+        height: usize,
+        position: Vector3<f32>,
+        radius: f32
+    ) -> impl Iterator<Item=&Transform>
+    {
+        self.occluders[height].iter().map(|x| x.transform()).filter(move |transform|
+        {
+            light_reaches(position, radius, transform)
+        })
+    }
+
     pub fn draw_shadows(
         &self,
         info: &mut DrawInfo,
@@ -9,9 +9,9 @@ use std::{
 
 use parking_lot::RwLock;
 
-use nalgebra::Vector2;
+use nalgebra::{Vector2, Vector3};
 
-use yanyaengine::game_object::*;
+use yanyaengine::{Transform, game_object::*};
 
 use crate::{
     client::{VisibilityChecker as EntityVisibilityChecker, TilesFactory},
@@ -414,6 +414,32 @@ impl VisualOvermap
         });
     }
 
+    // walls on the visible level that a light at this position could cast shadows from
+    pub fn occluders_near(
+        &self,
+        position: Vector3<f32>,
+        radius: f32
+    ) -> Vec<Transform>
+    {
+        let mut occluders = Vec::new();
+
+        self.chunks.positions_2d()
+            .filter(|pos| self.visible(*pos))
+            .for_each(|pos|
+            {
+                if let Some(pos) = self.visibility_checker.visible_z(&self.chunks, pos).next()
+                {
+                    let height = self.visibility_checker.height(pos);
+
+                    occluders.extend(
+                        self.chunks[pos].1.occluders_near(height, position, radius).cloned()
+                    );
+                }
+            });
+
+        occluders
+    }
+
     pub fn draw_shadows(
         &self,
         info: &mut DrawInfo,
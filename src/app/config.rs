@@ -1,14 +1,30 @@
 use std::{
     env,
+    fs,
     iter,
     process,
-    path::PathBuf,
+    io::ErrorKind,
+    path::{Path, PathBuf},
     fmt::{self, Display},
     collections::HashSet,
     num::{ParseIntError, ParseFloatError}
 };
 
-use crate::complain;
+use strum::IntoEnumIterator;
+
+use crate::{
+    complain,
+    debug_config::DebugTool,
+    client::{GraphicsSettings, CameraSettings, TelemetrySettings, AudioSettings, DEFAULT_PHYSICS_RATE},
+    server::world::AutosaveSettings,
+    common::{overrides::USER_OVERRIDES, corpse::DEFAULT_CORPSE_DURATION, DeathPolicy, Difficulty, CharacterCreation}
+};
+
+
+const DEFAULT_WORLD: &str = "default";
+
+// shipped with the game, the one in the user overrides folder goes on top of it and the arguments on top of both
+const CONFIG_PATH: &str = "config.txt";
 
 
 #[allow(dead_code)]
@@ -18,7 +34,9 @@ enum ArgError
     EnumParse{value: String, all: String},
     UnexpectedArg(String),
     DuplicateArg(String),
-    MissingValue(String)
+    MissingValue(String),
+    Read{path: String, err: String},
+    InFile{path: String, line: usize, error: Box<ArgError>}
 }
 
 impl Display for ArgError
@@ -31,7 +49,9 @@ impl Display for ArgError
             Self::EnumParse{value: x, all} => format!("error parsing {x}, available options: {all}"),
             Self::UnexpectedArg(x) => format!("unexpected argument {x}"),
             Self::DuplicateArg(x) => format!("duplicate argument {x}"),
-            Self::MissingValue(x) => format!("missing value after {x} argument")
+            Self::MissingValue(x) => format!("missing value after {x} argument"),
+            Self::Read{path, err} => format!("error reading {path}: {err}"),
+            Self::InFile{path, line, error} => format!("{error} (in {path} at line {line})")
         })
     }
 }
@@ -214,6 +234,62 @@ impl<'a> ArgParser<'a>
         }
     }
 
+    // missing files are fine, later files and the arguments override whatever was set here
+    pub fn parse_file(&mut self, path: &Path) -> Result<(), ArgError>
+    {
+        let text = match fs::read_to_string(path)
+        {
+            Ok(x) => x,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(()),
+            Err(err) => return Err(ArgError::Read{path: path.display().to_string(), err: err.to_string()})
+        };
+
+        text.lines().enumerate().try_for_each(|(index, line)|
+        {
+            self.parse_line(line).map_err(|error|
+            {
+                ArgError::InFile{path: path.display().to_string(), line: index + 1, error: Box::new(error)}
+            })
+        })
+    }
+
+    // lines look like `name = value` with the long argument name, flags take true or false
+    fn parse_line(&mut self, line: &str) -> Result<(), ArgError>
+    {
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#')
+        {
+            return Ok(());
+        }
+
+        let (name, value) = line.split_once('=').ok_or_else(||
+        {
+            ArgError::MissingValue(line.to_owned())
+        })?;
+
+        let (name, value) = (name.trim(), value.trim());
+
+        let arg = self.args.iter_mut().find(|arg| arg.long == name).ok_or_else(||
+        {
+            ArgError::UnexpectedArg(name.to_owned())
+        })?;
+
+        let info = match arg.kind
+        {
+            ArgType::Variable => ArgParseInfo::Variable(value.to_owned()),
+            ArgType::Flag(state) => match value
+            {
+                "true" => ArgParseInfo::Flag(state),
+                "false" => ArgParseInfo::Flag(!state),
+                _ => return Err(ArgError::Parse(value.to_owned()))
+            },
+            ArgType::Help => return Err(ArgError::UnexpectedArg(name.to_owned()))
+        };
+
+        arg.value.as_mut().unwrap().parse(info)
+    }
+
     pub fn parse(mut self, mut args: impl Iterator<Item=String>) -> Result<(), ArgError>
     {
         self.args.push(ArgInfo{
@@ -430,6 +506,7 @@ macro_rules! iterable_enum
         $($key:ident),+
     }) =>
     {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
         pub enum $enum_name
         {
             $($key,)+
@@ -523,6 +600,71 @@ iterable_enum!
     }
 }
 
+iterable_enum!
+{
+    enum WindowMode
+    {
+        Windowed,
+        Fullscreen
+    }
+}
+
+iterable_enum!
+{
+    enum DifficultyPreset
+    {
+        Easy,
+        Normal,
+        Hard
+    }
+}
+
+impl From<DifficultyPreset> for Difficulty
+{
+    fn from(preset: DifficultyPreset) -> Self
+    {
+        match preset
+        {
+            DifficultyPreset::Easy => Difficulty::easy(),
+            DifficultyPreset::Normal => Difficulty::normal(),
+            DifficultyPreset::Hard => Difficulty::hard()
+        }
+    }
+}
+
+iterable_enum!
+{
+    enum DeathMode
+    {
+        Respawn,
+        CorpseRun,
+        Permadeath
+    }
+}
+
+impl From<DeathMode> for DeathPolicy
+{
+    fn from(mode: DeathMode) -> Self
+    {
+        match mode
+        {
+            DeathMode::Respawn => DeathPolicy::Respawn,
+            DeathMode::CorpseRun => DeathPolicy::CorpseRun,
+            DeathMode::Permadeath => DeathPolicy::Permadeath
+        }
+    }
+}
+
+iterable_enum!
+{
+    enum CameraFollow
+    {
+        Locked,
+        DeadZone,
+        LookAhead
+    }
+}
+
 impl<T: ParsableEnum> ParsableInner for T
 {
     fn parse_inner(value: &str) -> Result<Self, ArgError>
@@ -569,6 +711,14 @@ impl ParsableInner for u32
     }
 }
 
+impl ParsableInner for u64
+{
+    fn parse_inner(value: &str) -> Result<Self, ArgError>
+    {
+        value.parse::<u64>().map_err(|err| (value, err).into())
+    }
+}
+
 impl ParsableInner for f32
 {
     fn parse_inner(value: &str) -> Result<Self, ArgError>
@@ -612,11 +762,62 @@ impl<T: ParsableInner + DisplayableDefault> ArgParsable for Option<T>
     }
 }
 
+fn parse_debug_tools(value: &str) -> Result<Vec<DebugTool>, ArgError>
+{
+    value.split(',').map(str::trim).filter(|x| !x.is_empty()).map(|name|
+    {
+        DebugTool::iter().find(|tool| <&str>::from(*tool) == name).ok_or_else(||
+        {
+            let all = DebugTool::iter().map(<&str>::from).collect::<Vec<_>>().join(", ");
+
+            ArgError::EnumParse{value: name.to_owned(), all}
+        })
+    }).collect()
+}
+
+fn parse_list(value: &str) -> Vec<String>
+{
+    value.split(',').map(str::trim).filter(|x| !x.is_empty()).map(ToOwned::to_owned).collect()
+}
+
+fn parse_tint(value: &str) -> Result<[f32; 3], ArgError>
+{
+    let values = value.split(',').map(|x| x.trim().parse::<f32>()).collect::<Result<Vec<_>, _>>()
+        .map_err(|err| ArgError::from(("tint", err)))?;
+
+    values.try_into().map_err(|_| ArgError::Parse("tint, expected 3 comma separated numbers".to_owned()))
+}
+
 pub struct Config
 {
     pub name: String,
+    pub character: CharacterCreation,
     pub address: Option<String>,
+    pub connect: Option<String>,
+    pub save_address: Option<String>,
+    pub addresses: bool,
     pub port: Option<u32>,
+    pub server: bool,
+    pub seed: Option<u64>,
+    pub preview: Option<usize>,
+    pub world: String,
+    pub worlds: bool,
+    pub continue_world: bool,
+    pub delete_world: Option<String>,
+    pub duplicate_world: Option<String>,
+    pub difficulty: Option<Difficulty>,
+    pub window_mode: WindowMode,
+    pub corpse_duration: f32,
+    pub autosave: AutosaveSettings,
+    pub graphics: GraphicsSettings,
+    pub camera: CameraSettings,
+    pub physics_rate: u32,
+    pub audio: AudioSettings,
+    pub sound_cues: bool,
+    pub telemetry: TelemetrySettings,
+    pub bench: Option<u32>,
+    pub crash_autosave: bool,
+    pub debug_tools: Vec<DebugTool>,
     pub debug: bool
 }
 
@@ -626,27 +827,179 @@ impl Config
     {
         let mut name = "player_name".to_owned();
 
+        let mut tint: Option<String> = None;
+        let mut traits: Option<String> = None;
+        let mut kit: Option<String> = None;
+
         let mut address = None;
+        let mut connect = None;
+        let mut save_address = None;
+        let mut addresses = false;
         let mut port = None;
 
+        let mut server = false;
+        let mut seed = None;
+        let mut preview = None;
+
+        let mut world = DEFAULT_WORLD.to_owned();
+        let mut worlds = false;
+        let mut continue_world = false;
+        let mut delete_world = None;
+        let mut duplicate_world = None;
+
+        let mut difficulty: Option<DifficultyPreset> = None;
+        let mut enemy_damage: Option<f32> = None;
+        let mut enemy_health: Option<f32> = None;
+        let mut loot: Option<f32> = None;
+        let mut hunger_rate: Option<f32> = None;
+        let mut death_penalty: Option<f32> = None;
+        let mut death: Option<DeathMode> = None;
+
+        let mut window_mode = WindowMode::Windowed;
+
+        let mut corpse_duration = DEFAULT_CORPSE_DURATION;
+
+        let mut autosave = AutosaveSettings::default();
+
+        let mut graphics = GraphicsSettings::default();
+        let mut camera = CameraSettings::default();
+
+        let mut physics_rate = DEFAULT_PHYSICS_RATE;
+
+        let mut audio = AudioSettings::default();
+        let mut sound_cues = false;
+
+        let mut telemetry = TelemetrySettings::default();
+
+        let mut bench = None;
+
+        let mut crash_autosave = false;
+
+        let mut debug_tools: Option<String> = None;
         let mut debug = false;
 
         let mut parser = ArgParser::new();
 
         parser.push(&mut name, 'n', "name", "player name");
+        parser.push(&mut tint, None, "tint", "comma separated rgb color (0 to 1) tinting a new character");
+        parser.push(&mut traits, None, "traits", "comma separated perks a new character starts with");
+        parser.push(&mut kit, None, "kit", "comma separated items a new character starts with");
         parser.push(&mut address, 'a', "address", "connection address");
+        parser.push(&mut connect, 'c', "connect", "connect to an address saved with --save-address");
+        parser.push(&mut save_address, None, "save-address", "save the connection address under this name");
+        parser.push_flag(&mut addresses, None, "addresses", "list saved addresses and quit", true);
         parser.push(&mut port, 'p', "port", "hosting port");
+        parser.push_flag(&mut server, 's', "server", "only host the server without playing", true);
+        parser.push(&mut seed, None, "seed", "seed for a newly created world");
+        parser.push(&mut world, None, "world", "name of the world to host");
+        parser.push_flag(&mut worlds, None, "worlds", "list existing worlds and quit", true);
+        parser.push_flag(&mut continue_world, None, "continue", "host the most recently played world instead of --world", true);
+        parser.push(&mut delete_world, None, "delete-world", "delete a world with its autosaves and quit");
+        parser.push(&mut duplicate_world, None, "duplicate-world", "copy the world picked with --world to this name and quit");
+        parser.push(&mut preview, None, "preview", "print a map this many worldchunks wide for the seed (random without one) and quit");
+        parser.push(&mut difficulty, None, "difficulty", "difficulty preset for a newly created world");
+        parser.push(&mut enemy_damage, None, "enemy-damage", "multiplier for damage enemies do, goes on top of the preset");
+        parser.push(&mut enemy_health, None, "enemy-health", "multiplier for how tough enemies are, goes on top of the preset");
+        parser.push(&mut loot, None, "loot", "multiplier for how much loot spawns, goes on top of the preset");
+        parser.push(&mut hunger_rate, None, "hunger-rate", "multiplier for how fast hunger and thirst go down, goes on top of the preset");
+        parser.push(&mut death_penalty, None, "death-penalty", "fraction of experience lost on death, goes on top of the preset");
+        parser.push(&mut death, None, "death", "what happens when a player dies, goes on top of the preset");
+        parser.push(&mut window_mode, 'w', "window", "windowed or fullscreen");
+        parser.push(&mut corpse_duration, None, "corpse-time", "seconds before corpses get removed");
+        parser.push(&mut autosave.interval, None, "autosave", "seconds between autosaves, 0 disables them");
+        parser.push(&mut autosave.amount, None, "autosaves", "how many rotating autosaves to keep");
+        parser.push_flag(&mut graphics.vignette, None, "no-vignette", "disable vignette", false);
+        parser.push_flag(&mut graphics.bloom, None, "no-bloom", "disable the glow around bright things", false);
+        parser.push_flag(&mut graphics.aberration, None, "no-aberration", "disable the color channels splitting when taking damage", false);
+        parser.push_flag(&mut graphics.desaturation, None, "no-desaturation", "disable the colors fading at low health", false);
+        parser.push_flag(&mut graphics.reflections, None, "no-reflections", "disable water and glass reflections", false);
+        parser.push(&mut graphics.render_scale, None, "render-scale", "fraction of the window resolution the world is drawn at, the ui stays sharp");
+        parser.push_flag(&mut graphics.adaptive_scale, None, "adaptive-scale", "lower the render scale (down to half) when frames get slow", true);
+        parser.push(&mut camera.follow, None, "camera-follow", "how the camera follows the player");
+        parser.push(&mut camera.min_scale, None, "zoom-min", "smallest camera scale (most zoomed in)");
+        parser.push(&mut camera.max_scale, None, "zoom-max", "biggest camera scale (most zoomed out)");
+        parser.push(&mut camera.shake, None, "shake", "screen shake strength, 0 disables it");
+        parser.push_flag(&mut camera.hit_stop, None, "no-hit-stop", "disable the slowdown on heavy hits", false);
+        parser.push(&mut physics_rate, None, "physics-rate", "physics steps per second");
+        parser.push(&mut audio.master, None, "volume", "sound volume, 0 mutes everything");
+        parser.push(&mut audio.sfx, None, "sfx-volume", "volume of sound effects");
+        parser.push(&mut audio.music, None, "music-volume", "volume of music");
+        parser.push(&mut audio.ui, None, "ui-volume", "volume of menu sounds");
+        parser.push_flag(&mut sound_cues, None, "sound-cues", "show arrows pointing at gunshots, footsteps and doors", true);
+        parser.push_flag(&mut telemetry.enabled, None, "telemetry", "save anonymous gameplay stats to the telemetry folder", true);
+        parser.push(&mut telemetry.endpoint, None, "telemetry-endpoint", "also post the gameplay stats to this url");
+        parser.push(&mut bench, None, "bench", "run this many server ticks with a bot instead of playing and print timings");
+        parser.push_flag(&mut crash_autosave, None, "crash-autosave", "include the latest autosave in crash reports", true);
+        parser.push(&mut debug_tools, None, "debug-tools", "comma separated debug tools to start with enabled");
         parser.push_flag(&mut debug, 'd', "debug", "enable debug mode", true);
 
-        if let Err(err) = parser.parse(args)
+        let user_config = Path::new(USER_OVERRIDES).join(CONFIG_PATH);
+
+        let result = parser.parse_file(Path::new(CONFIG_PATH))
+            .and_then(|_| parser.parse_file(&user_config))
+            .and_then(|_| parser.parse(args));
+
+        if let Err(err) = result
         {
             complain(err)
         }
 
+        let customized = [enemy_damage, enemy_health, loot, hunger_rate, death_penalty].iter().any(Option::is_some)
+            || death.is_some();
+
+        let difficulty = (difficulty.is_some() || customized).then(||
+        {
+            let preset: Difficulty = difficulty.unwrap_or(DifficultyPreset::Normal).into();
+
+            Difficulty{
+                enemy_damage: enemy_damage.unwrap_or(preset.enemy_damage),
+                enemy_health: enemy_health.unwrap_or(preset.enemy_health),
+                loot: loot.unwrap_or(preset.loot),
+                hunger_rate: hunger_rate.unwrap_or(preset.hunger_rate),
+                death_penalty: death_penalty.unwrap_or(preset.death_penalty),
+                death: death.map(DeathPolicy::from).unwrap_or(preset.death)
+            }
+        });
+
+        let character = CharacterCreation{
+            tint: tint.map(|x| parse_tint(&x)).transpose().unwrap_or_else(|err| complain(err)),
+            traits: traits.map(|x| parse_list(&x)).unwrap_or_default(),
+            kit: kit.map(|x| parse_list(&x)).unwrap_or_default()
+        };
+
+        let debug_tools = debug_tools.map(|x| parse_debug_tools(&x)).transpose()
+            .unwrap_or_else(|err| complain(err))
+            .unwrap_or_default();
+
         Self{
             name,
+            character,
             address,
+            connect,
+            save_address,
+            addresses,
             port,
+            server,
+            seed,
+            preview,
+            world,
+            worlds,
+            continue_world,
+            delete_world,
+            duplicate_world,
+            difficulty,
+            window_mode,
+            corpse_duration,
+            autosave,
+            graphics,
+            camera,
+            physics_rate,
+            audio,
+            sound_cues,
+            telemetry,
+            bench,
+            crash_autosave,
+            debug_tools,
             debug
         }
     }
@@ -8,6 +8,8 @@ use crate::common::{
     TileMapWithTextures
 };
 
+use world::AutosaveSettings;
+
 use game_server::{GameServer, ParseError};
 
 pub use connections_handler::ConnectionsHandler;
@@ -16,6 +18,8 @@ mod game_server;
 
 pub mod connections_handler;
 
+mod projectiles;
+
 pub mod world;
 
 
@@ -31,7 +35,10 @@ impl Server
         tilemap: TileMapWithTextures,
         data_infos: DataInfos,
         address: &str,
-        connections_limit: usize
+        connections_limit: usize,
+        corpse_duration: f32,
+        autosave: AutosaveSettings,
+        world_name: &str
     ) -> Result<(GameServer, Self), ParseError>
     {
         let listener = TcpListener::bind(address)?;
@@ -39,7 +46,10 @@ impl Server
         let (connector, game_server) = GameServer::new(
             tilemap.tilemap,
             data_infos,
-            connections_limit
+            connections_limit,
+            corpse_duration,
+            autosave,
+            world_name
         )?;
 
         Ok((game_server, Self{
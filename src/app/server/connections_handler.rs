@@ -1,4 +1,9 @@
+use std::collections::HashSet;
+
+use nalgebra::Vector3;
+
 use crate::common::{
+    some_or_value,
     MessageSerError,
     ObjectsStore,
     BufferSender,
@@ -6,17 +11,39 @@ use crate::common::{
     EntityPasser,
     MessagePasser,
     ConnectionId,
-    message::{Message, MessageBuffer}
+    EntityInfo,
+    message::{Message, MessageBuffer},
+    snapshot::SnapshotEncoder,
+    world::{CLIENT_OVERMAP_SIZE, CHUNK_VISUAL_SIZE}
 };
 
 
+// entities further than this from a player dont get sent to them
+const RELEVANCY_DISTANCE: f32 = (CLIENT_OVERMAP_SIZE / 2) as f32 * CHUNK_VISUAL_SIZE;
+
+// anything without a position is always in range
+pub fn in_relevancy_range(player: Option<Vector3<f32>>, position: Option<Vector3<f32>>) -> bool
+{
+    match (player, position)
+    {
+        (Some(a), Some(b)) => a.xy().metric_distance(&b.xy()) <= RELEVANCY_DISTANCE,
+        _ => true
+    }
+}
+
+
 #[derive(Debug)]
 pub struct PlayerInfo
 {
     message_buffer: MessageBuffer,
+    snapshots: SnapshotEncoder,
     message_passer: MessagePasser,
     entity: Entity,
-    name: String
+    name: String,
+    // everything this player knows about
+    relevant: HashSet<Entity>,
+    // no position means everything is relevant
+    position: Option<Vector3<f32>>
 }
 
 impl PlayerInfo
@@ -28,7 +55,15 @@ impl PlayerInfo
         name: String
     ) -> Self
     {
-        Self{message_buffer, message_passer, entity, name}
+        Self{
+            message_buffer,
+            snapshots: SnapshotEncoder::new(),
+            message_passer,
+            entity,
+            name,
+            relevant: HashSet::new(),
+            position: None
+        }
     }
 
     pub fn name(&self) -> &str
@@ -41,6 +76,54 @@ impl PlayerInfo
         self.message_buffer.set_message(message);
     }
 
+    pub fn ack_snapshot(&mut self, sequence: u32)
+    {
+        self.snapshots.ack(sequence);
+    }
+
+    pub fn mark_relevant(&mut self, entity: Entity)
+    {
+        self.relevant.insert(entity);
+    }
+
+    fn knows(&self, entity: Entity) -> bool
+    {
+        self.position.is_none() || self.relevant.contains(&entity)
+    }
+
+    fn becomes_relevant(&self, info: &EntityInfo) -> bool
+    {
+        if let Some(parent) = info.parent.as_ref()
+        {
+            return self.knows(parent.entity());
+        }
+
+        in_relevancy_range(self.position, info.transform.as_ref().map(|x| x.position))
+    }
+
+    // false if the message is about something this player doesnt need to know about
+    fn filter_message(&mut self, message: &Message) -> bool
+    {
+        let entity = some_or_value!(message.entity(), true);
+
+        match message
+        {
+            Message::EntitySet{info, ..} =>
+            {
+                let relevant = self.relevant.contains(&entity) || self.becomes_relevant(info);
+
+                if relevant
+                {
+                    self.relevant.insert(entity);
+                }
+
+                relevant
+            },
+            Message::EntityDestroy{..} => self.relevant.remove(&entity) || self.position.is_none(),
+            _ => self.knows(entity)
+        }
+    }
+
     pub fn send_blocking(&mut self, message: Message) -> Result<(), MessageSerError>
     {
         self.message_passer.send_one(&message)
@@ -123,6 +206,46 @@ impl ConnectionsHandler
         self.connections.iter().next().map(|(id, _)| ConnectionId(id))
     }
 
+    pub fn players(&self) -> impl Iterator<Item=(&str, Entity)>
+    {
+        self.connections.iter().map(|(_, info)| (info.name(), info.entity))
+    }
+
+    pub fn connections(&self) -> impl Iterator<Item=(ConnectionId, Entity)> + '_
+    {
+        self.connections.iter().map(|(id, info)| (ConnectionId(id), info.entity))
+    }
+
+    pub fn is_trusted(&self, id: ConnectionId) -> bool
+    {
+        self.trusted_player == Some(id)
+    }
+
+    // destroys whatever stopped being relevant, returns whatever just became relevant so it can get sent
+    pub fn set_relevant(
+        &mut self,
+        id: ConnectionId,
+        position: Option<Vector3<f32>>,
+        relevant: HashSet<Entity>
+    ) -> Vec<Entity>
+    {
+        let player_info = &mut self.connections[id.0];
+
+        player_info.position = position;
+
+        let left: Vec<Entity> = player_info.relevant.difference(&relevant).copied().collect();
+        let entered: Vec<Entity> = relevant.difference(&player_info.relevant).copied().collect();
+
+        left.into_iter().for_each(|entity|
+        {
+            player_info.set_message(Message::EntityDestroy{entity});
+        });
+
+        player_info.relevant = relevant;
+
+        entered
+    }
+
     pub fn get(&self, id: ConnectionId) -> &PlayerInfo
     {
         self.connections.get(id.0).unwrap()
@@ -138,6 +261,15 @@ impl ConnectionsHandler
         self.send_message_inner(Some(id), message);
     }
 
+    // players normally dont get messages about their own entity since they control it themselves
+    pub fn send_message_with_owner(&mut self, message: Message)
+    {
+        self.connections.iter_mut().for_each(|(_, player_info)|
+        {
+            player_info.set_message(message.clone());
+        });
+    }
+
     fn send_message_inner(&mut self, skip_id: Option<ConnectionId>, message: Message)
     {
         let entity_type = message.entity();
@@ -149,7 +281,10 @@ impl ConnectionsHandler
             !same_sync && skip_id != Some(ConnectionId(*index))
         }).for_each(|(_, player_info)|
         {
-            player_info.set_message(message.clone());
+            if player_info.filter_message(&message)
+            {
+                player_info.set_message(message.clone());
+            }
         });
     }
 }
@@ -173,7 +308,7 @@ impl BufferSender for ConnectionsHandler
     {
         self.connections.iter_mut().try_for_each(|(_, connection)|
         {
-            let buffer = connection.message_buffer.get_buffered();
+            let buffer = connection.snapshots.encode(connection.message_buffer.get_buffered());
 
             connection.message_passer.send_many(&buffer)
         })
@@ -2,7 +2,9 @@ use std::{
     f32,
     fmt,
     mem,
+    cell::RefCell,
     thread::JoinHandle,
+    collections::HashSet,
     ops::ControlFlow,
     net::TcpStream,
     sync::{
@@ -19,43 +21,81 @@ use yanyaengine::Transform;
 
 use super::{
     ConnectionsHandler,
-    connections_handler::PlayerInfo,
-    world::World
+    connections_handler::{in_relevancy_range, PlayerInfo},
+    projectiles::{ServerProjectiles, Outcome, Hit},
+    world::{World, AutosaveSettings}
 };
 
 pub use super::world::ParseError;
 
 use crate::{
+    LONGEST_FRAME,
     debug_config::*,
     common::{
         some_or_return,
+        some_or_false,
+        angle_between,
         sender_loop,
         receiver_loop,
         ENTITY_SCALE,
         render_info::*,
         lazy_transform::*,
         physics::*,
+        rng::{self, RngStream},
         MessageSerError,
         MessageDeError,
         AnyEntities,
         TileMap,
         DataInfos,
         Inventory,
+        InventoryItem,
+        Equipment,
+        Item,
+        TradeOffer,
+        BarterResponse,
         Entity,
         EntityInfo,
         Faction,
+        Damage,
+        DamagePartial,
+        DamageType,
+        DamageHeight,
+        Explosion,
+        ReputationEvent,
         CharactersInfo,
         CharacterId,
         Character,
         Player,
+        Needs,
+        CharacterCreation,
+        PerksInfo,
+        PerkId,
+        RecipeId,
         Entities,
         Anatomy,
-        HumanAnatomy,
+        PlanAnatomy,
         HumanAnatomyInfo,
+        BodyPlansInfo,
+        WeatherState,
+        WeatherKind,
+        DeathPolicy,
+        WindZone,
         EntityPasser,
         EntitiesController,
         MessagePasser,
         ConnectionId,
+        entity::{for_each_component, damaging_system},
+        character::BlockResult,
+        projectile::FireInfo,
+        Decal,
+        DecalKind,
+        Sound,
+        Noise,
+        NoiseKind,
+        Unlock,
+        Shake,
+        dialogue::{DIALOGUE_DISTANCE, DialogueEffect},
+        barter::TRADE_DISTANCE,
         message::{
             Message,
             MessageBuffer
@@ -64,13 +104,59 @@ use crate::{
 };
 
 
+// seconds between storm gusts around each player
+const GUST_INTERVAL: f32 = 3.0;
+
+// how far from the player gusts can start
+const GUST_SPREAD: f32 = 1.5;
+
+// minimum seconds between autosaves caused by something happening
+const AUTOSAVE_COOLDOWN: f32 = 30.0;
+
+// seconds a dead player lies there before coming back
+const RESPAWN_DELAY: f32 = 5.0;
+
+// how close to a bed u have to be to sleep in it
+const BED_DISTANCE: f32 = 0.5;
+
+const BUTCHER_DISTANCE: f32 = 0.5;
+
+const CONTAINER_NOISE: f32 = 0.5;
+// forcing a lock is a lot louder than just opening something
+const PRY_NOISE: f32 = 1.5;
+
+// seconds between checking which entities each player should know about
+const RELEVANCY_INTERVAL: f32 = 0.5;
+
+const KILL_EXPERIENCE: f32 = 25.0;
+
+// fastest a single player can speed up the world
+const MAX_TIME_SCALE: f32 = 8.0;
+
+// the physics cant move anything further than this in one frame, with some leeway
+const MAX_MOVE_DISTANCE: f32 = MAX_VELOCITY * LONGEST_FRAME as f32 * 1.5;
+
+fn player_anatomy(body_plans: &BodyPlansInfo) -> Anatomy
+{
+    let base_health = 1.2;
+
+    Anatomy::Plan(PlanAnatomy::new(body_plans.human(), HumanAnatomyInfo{
+        bone_toughness: base_health,
+        muscle_toughness: base_health,
+        skin_toughness: base_health,
+        base_speed: 0.9,
+        ..Default::default()
+    }))
+}
+
 #[derive(Debug)]
 pub enum ConnectionError
 {
     MessageSerError(MessageSerError),
     MessageDeError(MessageDeError),
     ReceiverError(TryRecvError),
-    WrongConnectionMessage
+    WrongConnectionMessage,
+    ModsMismatch{client: Vec<String>, server: Vec<String>}
 }
 
 impl fmt::Display for ConnectionError
@@ -82,7 +168,8 @@ impl fmt::Display for ConnectionError
             Self::MessageSerError(x) => x.to_string(),
             Self::MessageDeError(x) => x.to_string(),
             Self::ReceiverError(x) => x.to_string(),
-            Self::WrongConnectionMessage => "wrong connection message".to_owned()
+            Self::WrongConnectionMessage => "wrong connection message".to_owned(),
+            Self::ModsMismatch{client, server} => format!("client has mods {client:?} but server has {server:?}")
         };
 
         write!(f, "{s}")
@@ -118,12 +205,28 @@ pub struct GameServer
     entities: Entities,
     player_character: CharacterId,
     characters_info: Arc<CharactersInfo>,
+    perks_info: Arc<PerksInfo>,
+    corpse_duration: f32,
+    weather: WeatherState,
+    gust_timer: f32,
+    // players that already got the death penalty
+    dead_players: HashSet<Entity>,
+    respawns: Vec<(Entity, f32)>,
+    projectiles: ServerProjectiles,
+    // only ever changed when theres a single player
+    time_scale: f32,
+    relevancy_timer: f32,
+    autosave: AutosaveSettings,
+    autosave_timer: f32,
+    autosave_cooldown: f32,
     world: World,
     sender: Sender<(ConnectionId, Message, Entity)>,
     receiver: Receiver<(ConnectionId, Message, Entity)>,
     connection_receiver: Receiver<TcpStream>,
     connection_handler: Arc<RwLock<ConnectionsHandler>>,
     receiver_handles: Vec<JoinHandle<()>>,
+    // someone died with permadeath on
+    world_ended: bool,
     exited: bool,
     rare_timer: f32
 }
@@ -148,19 +251,27 @@ impl GameServer
     pub fn new(
         tilemap: TileMap,
         data_infos: DataInfos,
-        limit: usize
+        limit: usize,
+        corpse_duration: f32,
+        autosave: AutosaveSettings,
+        world_name: &str
     ) -> Result<(Sender<TcpStream>, Self), ParseError>
     {
-        let entities = Entities::new(data_infos.clone());
+        let mut entities = Entities::new(data_infos.clone());
         let connection_handler = Arc::new(RwLock::new(ConnectionsHandler::new(limit)));
 
         let world = World::new(
             connection_handler.clone(),
             tilemap,
             data_infos.enemies_info.clone(),
-            data_infos.items_info.clone()
+            data_infos.items_info.clone(),
+            data_infos.containers_info.clone(),
+            world_name.to_owned(),
+            autosave.amount
         )?;
 
+        entities.set_difficulty(world.difficulty());
+
         let _sender_handle = sender_loop(connection_handler.clone());
 
         let (sender, receiver) = mpsc::channel();
@@ -171,12 +282,25 @@ impl GameServer
             entities,
             player_character: data_infos.player_character,
             characters_info: data_infos.characters_info,
+            perks_info: data_infos.perks_info,
+            corpse_duration,
+            weather: WeatherState::new(),
+            gust_timer: GUST_INTERVAL,
+            dead_players: HashSet::new(),
+            respawns: Vec::new(),
+            projectiles: ServerProjectiles::new(),
+            time_scale: 1.0,
+            relevancy_timer: 0.0,
+            autosave,
+            autosave_timer: autosave.interval,
+            autosave_cooldown: 0.0,
             world,
             sender,
             receiver,
             connection_receiver,
             connection_handler,
             receiver_handles: Vec::new(),
+            world_ended: false,
             exited: false,
             rare_timer: 0.0
         }))
@@ -186,6 +310,11 @@ impl GameServer
     {
         self.process_messages();
 
+        if self.world_ended
+        {
+            return self.exited;
+        }
+
         self.entities.update_sprites(&self.characters_info);
 
         {
@@ -193,7 +322,43 @@ impl GameServer
             self.entities.create_queued(&mut writer);
         }
 
+        self.update_relevancy(dt);
+
+        if self.time_scale == 0.0
+        {
+            return self.exited;
+        }
+
+        self.world.add_playtime(dt);
+
+        let dt = dt * self.time_scale;
+
         self.entities.update_watchers(dt);
+        self.entities.update_anatomy(dt);
+        self.entities.update_needs(dt);
+
+        let outcome = self.projectiles.update(&self.entities, &mut self.world, dt);
+        self.projectiles_outcome(outcome);
+
+        let raining = self.weather.weather().kind == WeatherKind::Rain;
+        self.world.update_fires(&mut self.entities, raining, dt);
+
+        if let Some(weather) = self.weather.update(dt)
+        {
+            self.entities.set_weather(weather);
+            self.send_message(Message::SetWeather{weather});
+        }
+
+        self.update_gusts(dt);
+
+        self.update_deaths(dt);
+
+        {
+            let mut writer = self.connection_handler.write();
+            self.entities.update_corpses(&mut writer, self.corpse_duration, dt);
+        }
+
+        self.update_autosave(dt);
 
         if self.rare_timer <= 0.0
         {
@@ -208,8 +373,749 @@ impl GameServer
         self.exited
     }
 
+    fn update_relevancy(&mut self, dt: f32)
+    {
+        self.relevancy_timer -= dt;
+
+        if self.relevancy_timer > 0.0
+        {
+            return;
+        }
+
+        self.relevancy_timer = RELEVANCY_INTERVAL;
+
+        let root_position = |mut entity: Entity|
+        {
+            while let Some(parent) = self.entities.parent(entity)
+            {
+                entity = parent.entity();
+            }
+
+            self.entities.transform(entity).map(|x| x.position)
+        };
+
+        let connections: Vec<_> = self.connection_handler.read().connections().collect();
+
+        connections.into_iter().for_each(|(id, player_entity)|
+        {
+            // the trusted player simulates the enemies so it needs all of them
+            let position = if self.connection_handler.read().is_trusted(id)
+            {
+                None
+            } else
+            {
+                self.entities.transform(player_entity).map(|x| x.position)
+            };
+
+            let mut relevant = HashSet::new();
+            self.entities.for_each_entity(|entity|
+            {
+                if entity.local()
+                {
+                    return;
+                }
+
+                if in_relevancy_range(position, root_position(entity))
+                {
+                    relevant.insert(entity);
+                }
+            });
+
+            let mut writer = self.connection_handler.write();
+
+            // parents have to exist before their children get sent
+            let (mut entered, children): (Vec<_>, Vec<_>) = writer.set_relevant(id, position, relevant)
+                .into_iter()
+                .partition(|entity| self.entities.parent(*entity).is_none());
+
+            entered.extend(children);
+
+            entered.into_iter().for_each(|entity|
+            {
+                writer.send_single(id, Message::EntitySet{entity, info: self.entities.info(entity)});
+            });
+        });
+    }
+
+    fn update_gusts(&mut self, dt: f32)
+    {
+        self.gust_timer -= dt;
+
+        if self.gust_timer > 0.0
+        {
+            return;
+        }
+
+        self.gust_timer = GUST_INTERVAL;
+
+        let weather = self.weather.weather();
+
+        let mut positions = Vec::new();
+        for_each_component!(self.entities, player, |entity, _player: &RefCell<Player>|
+        {
+            if let Some(transform) = self.entities.transform(entity)
+            {
+                positions.push(transform.position);
+            }
+        });
+
+        positions.into_iter().for_each(|position|
+        {
+            let offset = Vector3::new(rng::f32(RngStream::Weather) - 0.5, rng::f32(RngStream::Weather) - 0.5, 0.0) * GUST_SPREAD;
+
+            if let Some(info) = WindZone::gust(&weather, position + offset)
+            {
+                self.entities.push(false, info);
+            }
+        });
+    }
+
+    fn update_deaths(&mut self, dt: f32)
+    {
+        let difficulty = self.entities.difficulty();
+        let penalty = difficulty.death_penalty;
+
+        let mut died = Vec::new();
+        for_each_component!(self.entities, player, |entity, _player: &RefCell<Player>|
+        {
+            let alive = self.entities.anatomy(entity).map(|x| x.speed().is_some()).unwrap_or(true);
+
+            if alive
+            {
+                self.dead_players.remove(&entity);
+            } else if self.dead_players.insert(entity)
+            {
+                died.push(entity);
+            }
+        });
+
+        died.into_iter().for_each(|entity|
+        {
+            if penalty > 0.0
+            {
+                let component = {
+                    let mut player = some_or_return!(self.entities.player_mut(entity));
+                    player.experience *= 1.0 - penalty.min(1.0);
+
+                    player.clone()
+                };
+
+                self.send_message_with_owner(Message::SetPlayer{entity, component: component.into()});
+            }
+
+            self.on_player_death(entity, difficulty.death);
+        });
+
+        self.respawns.iter_mut().for_each(|(_, timer)| *timer -= dt);
+
+        let (ready, waiting): (Vec<_>, Vec<_>) = mem::take(&mut self.respawns).into_iter()
+            .partition(|(_, timer)| *timer <= 0.0);
+
+        self.respawns = waiting;
+
+        ready.into_iter().for_each(|(entity, _)| self.respawn(entity));
+    }
+
+    fn on_player_death(&mut self, entity: Entity, policy: DeathPolicy)
+    {
+        if policy == DeathPolicy::Permadeath
+        {
+            self.end_world();
+
+            return;
+        }
+
+        let mut inventory = some_or_return!(self.entities.inventory_mut(entity).map(|mut inventory|
+        {
+            mem::replace(&mut *inventory, Inventory::new())
+        }));
+
+        self.send_message_with_owner(Message::SetInventory{entity, component: Inventory::new().into()});
+
+        // whatever was worn goes with everything else
+        let worn = self.entities.equipment_mut(entity).map(|mut equipment| equipment.take_all());
+        if let Some(worn) = worn
+        {
+            worn.into_iter().for_each(|item| inventory.push(item));
+
+            self.send_message_with_owner(Message::SetEquipment{entity, component: Equipment::new().into()});
+        }
+
+        if policy == DeathPolicy::CorpseRun && !inventory.is_empty()
+        {
+            if let Some(position) = self.entities.transform(entity).map(|x| x.position)
+            {
+                self.world.spawn_remains(&self.entities, position, inventory);
+            }
+        }
+
+        self.respawns.push((entity, RESPAWN_DELAY));
+    }
+
+    fn respawn(&mut self, entity: Entity)
+    {
+        if !self.entities.exists(entity)
+        {
+            return;
+        }
+
+        let mut anatomy = player_anatomy(&self.entities.infos().body_plans);
+
+        if let Some(player) = self.entities.player(entity)
+        {
+            player.perks.iter().filter_map(|name| self.perks_info.get_id(name)).for_each(|id|
+            {
+                self.perks_info.get(id).apply_anatomy(&mut anatomy);
+            });
+        }
+
+        self.entities.set_anatomy(entity, Some(anatomy.clone()));
+        self.entities.set_needs(entity, Some(Needs::default()));
+
+        let spawn = self.entities.player(entity).and_then(|player| player.spawn)
+            .unwrap_or_else(|| self.world.spawn_point());
+
+        let target = {
+            let mut target = some_or_return!(self.entities.target(entity));
+            target.position = spawn;
+
+            target.clone()
+        };
+
+        self.send_message_with_owner(Message::SetAnatomy{entity, component: anatomy.into()});
+        self.send_message_with_owner(Message::SetNeeds{entity, component: Needs::default().into()});
+        self.send_message_with_owner(Message::SetTarget{entity, target});
+    }
+
+    fn butcher(&mut self, player: Entity, corpse: Entity)
+    {
+        if !self.in_range(player, corpse, BUTCHER_DISTANCE)
+        {
+            return;
+        }
+
+        let infos = self.entities.infos().clone();
+
+        let sharp = self.entities.character(player).and_then(|character| character.holding()).and_then(|held|
+        {
+            let info = infos.items_info.get(self.entities.inventory(player)?.get(held)?.id);
+
+            Some(info.sharpness > 0.0 || info.side_sharpness > 0.0)
+        }).unwrap_or(false);
+
+        if !sharp
+        {
+            return;
+        }
+
+        let butcher = some_or_return!(self.entities.enemy(corpse).and_then(|enemy|
+        {
+            enemy.info(&infos.enemies_info).butcher.clone()
+        }));
+
+        let id = some_or_return!(infos.items_info.get_id(&butcher.item));
+
+        let corpse_component = {
+            let mut corpse_component = some_or_return!(self.entities.corpse_mut(corpse));
+
+            if !corpse_component.butcher()
+            {
+                return;
+            }
+
+            corpse_component.clone()
+        };
+
+        let (low, high) = butcher.amount;
+        let amount = rng::usize(RngStream::Loot, low..(high + 1));
+
+        // goes in the corpse so its looted like anything else
+        let inventory = {
+            let mut inventory = some_or_return!(self.entities.inventory_mut(corpse));
+            (0..amount).for_each(|_| { inventory.push(Item{id}); });
+
+            inventory.clone()
+        };
+
+        self.send_message(Message::SetCorpse{entity: corpse, component: corpse_component.into()});
+        self.send_message(Message::SetInventory{entity: corpse, component: inventory.into()});
+    }
+
+    fn open_container(&mut self, player: Entity, container: Entity, open: bool)
+    {
+        // closing is fine from anywhere, walking away closes it
+        if open && !(self.entities.within_interactable_distance(player, container) && self.entities.is_lootable(container))
+        {
+            return;
+        }
+
+        let component = {
+            let mut component = some_or_return!(self.entities.container_mut(container));
+
+            if component.is_open() == open
+            {
+                return;
+            }
+
+            component.set_open(open);
+
+            component.clone()
+        };
+
+        self.send_message(Message::SetContainer{entity: container, component: component.into()});
+    }
+
+    fn unlock_container(&mut self, player: Entity, container: Entity)
+    {
+        if !self.entities.within_interactable_distance(player, container)
+        {
+            return;
+        }
+
+        let unlock = some_or_return!(self.entities.container_unlock(player, container));
+
+        let component = {
+            let mut component = some_or_return!(self.entities.container_mut(container));
+            component.locked = false;
+
+            component.clone()
+        };
+
+        self.send_message(Message::SetContainer{entity: container, component: component.into()});
+
+        let position = some_or_return!(self.entities.transform(container).map(|x| x.position));
+
+        // picking is quiet, prying isnt
+        let loudness = match unlock
+        {
+            Unlock::Pry =>
+            {
+                self.send_message(Message::PlaySound{sound: Sound::new("hit_hard", position)});
+
+                PRY_NOISE
+            },
+            Unlock::Pick => CONTAINER_NOISE
+        };
+
+        let noise = Noise::new(NoiseKind::Container, position, loudness).with_source(player);
+        self.entities.make_noise(&mut *self.connection_handler.write(), noise);
+    }
+
+    fn client_player(&mut self, entity: Entity, target: Entity, component: Player) -> Option<Message>
+    {
+        if target != entity
+        {
+            return None;
+        }
+
+        let component = {
+            let mut player = self.entities.player_mut(entity)?;
+            player.hints_seen = component.hints_seen;
+
+            player.clone()
+        };
+
+        Some(Message::SetPlayer{entity, component: component.into()})
+    }
+
+    fn check_theft(&mut self, player: Entity, other: Entity, component: &Inventory)
+    {
+        let taken = self.entities.inventory(other).map(|inventory|
+        {
+            component.items().len() < inventory.items().len()
+        }).unwrap_or(false);
+
+        let stolen = taken
+            && self.entities.character_exists(other)
+            && !self.entities.is_hostile(player, other)
+            && !self.entities.is_companion_of(other, player);
+
+        if stolen
+        {
+            self.reputation_event(player, other, ReputationEvent::Theft);
+        }
+    }
+
+    fn is_alive(&self, entity: Entity) -> bool
+    {
+        self.entities.anatomy(entity).map(|x| x.speed().is_some()).unwrap_or(true)
+    }
+
+    // clients only get credit for their own hits, or the enemies ones if theyre the one simulating them
+    fn verified_source(&self, id: ConnectionId, player: Entity, source: Option<Entity>) -> Option<Entity>
+    {
+        source.filter(|source|
+        {
+            *source == player
+                || (self.entities.enemy_exists(*source) && self.connection_handler.read().is_trusted(id))
+        })
+    }
+
+    fn entity_damage(&mut self, entity: Entity, source: Option<Entity>, faction: Faction, damage: Damage)
+    {
+        let was_alive = self.is_alive(entity);
+
+        let (result, damage) = some_or_return!(self.entities.resolve_damage(entity, source, faction, damage));
+
+        if result != BlockResult::Hit
+        {
+            let parried = result == BlockResult::Parried;
+            self.send_message_with_owner(Message::EntityBlocked{entity, source, parried});
+        }
+
+        // a parry or a block that soaked all of it doesnt hurt anyone
+        if let Some(damage) = damage
+        {
+            self.entities.apply_damage(entity, damage.clone());
+            self.hit_effects(entity, damage.data.as_flat(), was_alive && !self.is_alive(entity));
+
+            self.send_message_with_owner(Message::EntityDamaged{entity, source, damage});
+        }
+
+        let source = some_or_return!(source.filter(|source|
+        {
+            *source != entity && self.entities.player_exists(*source)
+        }));
+
+        let event = if was_alive && !self.is_alive(entity)
+        {
+            self.add_experience(source, KILL_EXPERIENCE);
+
+            ReputationEvent::Kill
+        } else
+        {
+            let aggressive = self.entities.faction(entity).map(|other|
+            {
+                self.entities.infos().factions_info.aggressive(faction, other)
+            }).unwrap_or(true);
+
+            if aggressive
+            {
+                return;
+            }
+
+            ReputationEvent::Hit
+        };
+
+        self.reputation_event(source, entity, event);
+    }
+
+    fn fire_projectile(
+        &mut self,
+        id: ConnectionId,
+        player: Entity,
+        entity: Entity,
+        item: InventoryItem,
+        target: Vector3<f32>
+    )
+    {
+        // the trusted client is the one simulating the enemies
+        let allowed = entity == player
+            || (self.entities.enemy_exists(entity) && self.connection_handler.read().is_trusted(id));
+
+        if !allowed || !self.is_alive(entity)
+        {
+            return;
+        }
+
+        let items_info = self.entities.infos().items_info.clone();
+
+        let item_id = some_or_return!(self.entities.inventory(entity).and_then(|inventory| inventory.get(item).map(|x| x.id)));
+        let item_info = items_info.get(item_id);
+
+        let start = some_or_return!(self.entities.transform(entity)).position;
+        let faction = some_or_return!(self.entities.faction(entity));
+
+        let outcome = if let Some(ranged) = &item_info.ranged
+        {
+            let data = some_or_return!(ranged.damage());
+            let projectile = some_or_return!(ranged.projectile());
+
+            if !self.projectiles.ready(entity, ranged.cooldown())
+            {
+                return;
+            }
+
+            // enemies dont carry ammo around so they never run out
+            if let Some(ammo) = ranged.ammo().filter(|_| self.entities.player_exists(entity))
+            {
+                if !self.use_ammo(entity, ammo)
+                {
+                    return;
+                }
+            }
+
+            // the client only says where it aimed, how far off the shot goes is rolled here
+            let target = {
+                let angle = (rng::f32(RngStream::Combat) * 2.0 - 1.0) * ranged.spread();
+
+                let offset = target - start;
+                let (sin, cos) = angle.sin_cos();
+
+                start + Vector3::new(offset.x * cos - offset.y * sin, offset.x * sin + offset.y * cos, offset.z)
+            };
+
+            self.projectiles.fire(&self.entities, FireInfo{
+                info: projectile,
+                start,
+                target,
+                source: entity,
+                faction,
+                damage: DamagePartial{data, height: DamageHeight::random()}
+            })
+        } else if let Some(explosive) = &item_info.explosive
+        {
+            let inventory = {
+                let mut inventory = some_or_return!(self.entities.inventory_mut(entity));
+                inventory.remove(item);
+
+                inventory.clone()
+            };
+
+            self.send_message_with_owner(Message::SetInventory{entity, component: inventory.into()});
+
+            self.projectiles.fire(&self.entities, FireInfo{
+                info: &explosive.projectile(),
+                start,
+                target,
+                source: entity,
+                faction,
+                damage: DamagePartial{data: DamageType::Blunt(item_info.mass), height: DamageHeight::random()}
+            })
+        } else
+        {
+            return;
+        };
+
+        self.projectiles_outcome(outcome);
+    }
+
+    // false if theres none left
+    fn use_ammo(&mut self, entity: Entity, ammo: &str) -> bool
+    {
+        let ammo = some_or_false!(self.entities.infos().items_info.get_id(ammo));
+
+        let inventory = {
+            let mut inventory = some_or_false!(self.entities.inventory_mut(entity));
+            let used = some_or_false!(inventory.find(ammo));
+
+            inventory.remove(used);
+
+            inventory.clone()
+        };
+
+        self.send_message_with_owner(Message::SetInventory{entity, component: inventory.into()});
+
+        true
+    }
+
+    fn projectiles_outcome(&mut self, outcome: Outcome)
+    {
+        outcome.messages.into_iter().for_each(|message| self.send_message(message));
+
+        outcome.removed.into_iter().for_each(|entity|
+        {
+            let message = self.entities.remove_message(entity);
+            self.send_message(message);
+        });
+
+        outcome.hits.into_iter().for_each(|Hit{entity, source, faction, damage}|
+        {
+            self.entity_damage(entity, source, faction, damage);
+        });
+
+        outcome.explosions.into_iter().for_each(|explosion| self.explosion(explosion));
+    }
+
+    fn explosion(&mut self, explosion: Explosion)
+    {
+        let explosion = Explosion{info: explosion.info.clamped(), ..explosion};
+        let Explosion{position, info, source, faction} = explosion;
+
+        let world = &mut self.world;
+        let caught = info.caught(position, &self.entities, |start, end| world.tile_hit(start, end).is_some());
+
+        caught.into_iter().for_each(|(entity, entity_position, strength)|
+        {
+            let angle = angle_between(position, entity_position);
+            let side = some_or_return!(damaging_system::hit_side(&self.entities, entity, angle));
+
+            let bleeds = self.entities.anatomy_exists(entity);
+            explosion.damages(bleeds, strength).into_iter().for_each(|damage|
+            {
+                self.entity_damage(entity, source, faction, damage.with_direction(side));
+            });
+        });
+
+        self.world.explode(&mut self.entities, position, info);
+
+        self.send_message(Message::Explode{position, info});
+
+        self.send_message(Message::SpawnDecal{decal: Decal::new(DecalKind::Scorch, position)});
+        self.send_message(Message::SpawnShake{shake: Shake::new(position, (info.damage / 100.0).min(1.0))});
+        self.send_message(Message::PlaySound{sound: Sound::new("explosion", position)});
+    }
+
+    // everything about a hit that every client should see the same
+    fn hit_effects(&mut self, entity: Entity, damage_amount: f32, killed: bool)
+    {
+        let position = some_or_return!(self.entities.transform(entity)).position;
+
+        let bleeds = self.entities.anatomy_exists(entity);
+
+        if bleeds
+        {
+            self.send_message(Message::SpawnDecal{decal: Decal::new(DecalKind::Blood, position)});
+        }
+
+        let sound = if bleeds { "hit_flesh" } else { "hit_hard" };
+        self.send_message(Message::PlaySound{sound: Sound::new(sound, position)});
+
+        let gibbed = killed && self.entities.enemy(entity).map(|enemy|
+        {
+            enemy.death().gibs_for(damage_amount).is_some()
+        }).unwrap_or(false);
+
+        if let Some((trauma, hit_stop)) = damaging_system::hit_shake(gibbed, damage_amount)
+        {
+            let shake = Shake::new(position, trauma).with_hit_stop(hit_stop);
+            self.send_message(Message::SpawnShake{shake});
+        }
+    }
+
+    fn add_experience(&mut self, player: Entity, amount: f32)
+    {
+        let component = {
+            let mut player_component = some_or_return!(self.entities.player_mut(player));
+            player_component.add_experience(amount);
+
+            player_component.clone()
+        };
+
+        self.send_message_with_owner(Message::SetPlayer{entity: player, component: component.into()});
+    }
+
+    fn reputation_event(&mut self, player: Entity, other: Entity, event: ReputationEvent)
+    {
+        let faction = some_or_return!(self.entities.faction(other));
+
+        let component = {
+            let mut player_component = some_or_return!(self.entities.player_mut(player));
+            player_component.reputation.on_event(&self.entities.infos().factions_info, faction, event);
+
+            player_component.clone()
+        };
+
+        self.send_message_with_owner(Message::SetPlayer{entity: player, component: component.into()});
+    }
+
+    fn pick_perk(&mut self, entity: Entity, id: PerkId)
+    {
+        let perk = some_or_return!(self.perks_info.items().get(usize::from(id)));
+
+        let component = {
+            let mut player = some_or_return!(self.entities.player_mut(entity));
+
+            if player.perk_points == 0 || player.perks.contains(&perk.name)
+            {
+                return;
+            }
+
+            player.perk_points -= 1;
+            player.perks.push(perk.name.clone());
+
+            perk.apply_player(&mut player);
+
+            player.clone()
+        };
+
+        let anatomy = {
+            let mut anatomy = some_or_return!(self.entities.anatomy_mut(entity));
+            perk.apply_anatomy(&mut anatomy);
+
+            anatomy.clone()
+        };
+
+        self.send_message_with_owner(Message::SetPlayer{entity, component: component.into()});
+        self.send_message_with_owner(Message::SetAnatomy{entity, component: anatomy.into()});
+    }
+
+    fn set_spawn(&mut self, entity: Entity, bed: Entity)
+    {
+        let is_bed = self.entities.container(bed).and_then(|container|
+        {
+            self.entities.infos().containers_info.get(&container.name).map(|info| info.bed)
+        }).unwrap_or(false);
+
+        if !is_bed || !self.in_range(entity, bed, BED_DISTANCE)
+        {
+            return;
+        }
+
+        let position = some_or_return!(self.entities.transform(bed)).position;
+
+        let component = {
+            let mut player = some_or_return!(self.entities.player_mut(entity));
+            player.spawn = Some(position);
+
+            player.clone()
+        };
+
+        self.send_message_with_owner(Message::SetPlayer{entity, component: component.into()});
+    }
+
+    // the world stays locked, everyone gets kicked out and nobody else can join
+    fn end_world(&mut self)
+    {
+        if self.world_ended
+        {
+            return;
+        }
+
+        println!("a player died, the world is over");
+
+        self.world_ended = true;
+        self.world.lock();
+
+        self.send_message(Message::WorldEnded);
+    }
+
+    fn update_autosave(&mut self, dt: f32)
+    {
+        if self.autosave.interval <= 0.0
+        {
+            return;
+        }
+
+        self.autosave_timer -= dt;
+        self.autosave_cooldown -= dt;
+
+        let area_changed = self.world.take_area_changed() && self.autosave_cooldown <= 0.0;
+
+        if self.autosave_timer <= 0.0 || area_changed
+        {
+            self.autosave();
+        }
+    }
+
+    fn autosave(&mut self)
+    {
+        self.autosave_timer = self.autosave.interval;
+        self.autosave_cooldown = AUTOSAVE_COOLDOWN;
+
+        self.send_message(Message::Autosaving);
+
+        self.world.autosave(&self.entities);
+    }
+
     fn rare(&mut self)
     {
+        {
+            let mut writer = self.connection_handler.write();
+            self.entities.restock_vendors(&mut writer);
+            self.entities.sync_needs(&mut writer);
+        }
+
         if DebugConfig::is_debug()
         {
             self.entities.check_guarantees();
@@ -273,7 +1179,7 @@ impl GameServer
 
     pub fn connect(&mut self, stream: TcpStream) -> Result<(), ConnectionError>
     {
-        if self.connection_handler.read().under_limit()
+        if !self.world_ended && self.connection_handler.read().under_limit()
         {
             self.player_connect(stream)
         } else
@@ -318,6 +1224,12 @@ impl GameServer
 
         self.receiver_handles.push(receiver_handle);
 
+        // pausing or speeding up only works when nobody else is affected
+        if self.time_scale != 1.0 && !self.is_single_player()
+        {
+            self.set_time_scale(1.0);
+        }
+
         Ok(())
     }
 
@@ -329,23 +1241,17 @@ impl GameServer
         let player_index = self.entities.player.len() + 1;
 
         let transform = Transform{
+            position: self.world.spawn_point(),
             scale: Vector3::repeat(ENTITY_SCALE),
             ..Default::default()
         };
 
-        let base_health = 1.2;
-        let anatomy = Anatomy::Human(HumanAnatomy::new(HumanAnatomyInfo{
-            bone_toughness: base_health,
-            muscle_toughness: base_health,
-            skin_toughness: base_health,
-            base_speed: 0.9,
-            ..Default::default()
-        }));
+        let anatomy = player_anatomy(&self.entities.infos().body_plans);
 
         let position = transform.position;
 
         let info = EntityInfo{
-            player: Some(Player),
+            player: Some(Player::default()),
             named: Some(format!("stephanie #{player_index}")),
             lazy_transform: Some(LazyTransformInfo{
                 transform: transform.clone(),
@@ -363,13 +1269,16 @@ impl GameServer
                 inverse_mass: 50.0_f32.recip(),
                 static_friction: 0.9,
                 dynamic_friction: 0.8,
+                density: BODY_DENSITY,
                 fixed: PhysicalFixed{rotation: true, ..Default::default()},
                 can_sleep: false,
                 ..Default::default()
             }.into()),
             inventory: Some(Inventory::new()),
+            equipment: Some(Equipment::new()),
             character: Some(Character::new(self.player_character, Faction::Player)),
             anatomy: Some(anatomy),
+            needs: Some(Needs::default()),
             ..Default::default()
         };
 
@@ -387,7 +1296,15 @@ impl GameServer
 
         let player_entity = inserter(info);
 
-        let player_info = self.player_info(stream, player_entity)?;
+        let (player_info, creation) = self.player_info(stream, player_entity)?;
+
+        if let Some(player) = self.world.load_player(player_info.name())
+        {
+            self.restore_player(player_entity, player);
+        } else
+        {
+            self.create_character(player_entity, creation);
+        }
 
         let (connection, mut messager) = self.player_create(
             player_entity,
@@ -395,29 +1312,106 @@ impl GameServer
             position
         )?;
 
+        messager.send_one(&Message::SetWeather{weather: self.weather.weather()})?;
+        messager.send_one(&Message::SetDifficulty{difficulty: self.world.difficulty()})?;
+        messager.send_one(&Message::SetTimeScale{scale: self.time_scale})?;
+
         messager.send_one(&Message::PlayerFullyConnected)?;
 
         Ok((player_entity, connection, messager))
     }
 
-    fn player_info(&self, stream: TcpStream, entity: Entity) -> Result<PlayerInfo, ConnectionError>
+    fn player_info(
+        &self,
+        stream: TcpStream,
+        entity: Entity
+    ) -> Result<(PlayerInfo, CharacterCreation), ConnectionError>
     {
         let mut message_passer = MessagePasser::new(stream);
 
-        let name = match message_passer.receive_one()?
+        let (name, creation, mods) = match message_passer.receive_one()?
         {
-            Some(Message::PlayerConnect{name}) => name,
+            Some(Message::PlayerConnect{name, creation, mods}) => (name, creation, mods),
             _ =>
             {
                 return Err(ConnectionError::WrongConnectionMessage);
             }
         };
 
+        // different mods would mean different item ids and everything else after that
+        let server_mods = self.entities.infos().mods_info.names();
+        if mods != server_mods
+        {
+            message_passer.send_one(&Message::ModsMismatch{mods: server_mods.clone()})?;
+
+            return Err(ConnectionError::ModsMismatch{client: mods, server: server_mods});
+        }
+
         println!("player \"{name}\" connected");
 
         self.entities.named_mut(entity).unwrap().clone_from(&name);
 
-        Ok(PlayerInfo::new(MessageBuffer::new(), message_passer, entity, name))
+        let player_info = PlayerInfo::new(MessageBuffer::new(), message_passer, entity, name);
+
+        Ok((player_info, creation))
+    }
+
+    // only for players joining the world for the first time
+    fn create_character(&mut self, entity: Entity, creation: CharacterCreation)
+    {
+        let creation = {
+            let infos = self.entities.infos();
+
+            creation.validated(&infos.perks_info, &infos.items_info)
+        };
+
+        if let Some(mut mix) = self.entities.mix_color_target(entity)
+        {
+            *mix = creation.mix();
+        }
+
+        if let Some(mut inventory) = self.entities.inventory_mut(entity)
+        {
+            let items_info = self.entities.infos().items_info.clone();
+
+            creation.kit.iter().for_each(|name|
+            {
+                inventory.push(Item{id: items_info.id(name)});
+            });
+        }
+
+        let player = Player{perks: creation.traits, ..Default::default()};
+
+        self.restore_player(entity, player);
+
+        let render = self.entities.render(entity).unwrap().clone();
+        let inventory = self.entities.inventory(entity).unwrap().clone();
+
+        let mut writer = self.connection_handler.write();
+
+        writer.send_message(Message::SetRender{entity, component: render.into()});
+        writer.send_message(Message::SetInventory{entity, component: inventory.into()});
+    }
+
+    fn restore_player(&mut self, entity: Entity, player: Player)
+    {
+        if let Some(mut anatomy) = self.entities.anatomy_mut(entity)
+        {
+            player.perks.iter().filter_map(|name| self.perks_info.get_id(name)).for_each(|id|
+            {
+                self.perks_info.get(id).apply_anatomy(&mut anatomy);
+            });
+        }
+
+        *self.entities.player_mut(entity).unwrap() = player;
+
+        let player = self.entities.player(entity).unwrap().clone();
+        let anatomy = self.entities.anatomy(entity).unwrap().clone();
+
+        let mut writer = self.connection_handler.write();
+
+        writer.send_message(Message::SetPlayer{entity, component: player.into()});
+        writer.send_message(Message::SetAnatomy{entity, component: anatomy.into()});
     }
 
     fn player_create(
@@ -457,6 +1451,7 @@ impl GameServer
             let info = self.entities.info(entity);
             let message = Message::EntitySet{entity, info};
 
+            messager.mark_relevant(entity);
             messager.send_blocking(message)
         })?;
 
@@ -476,6 +1471,14 @@ impl GameServer
 
         let removed_name = removed.as_ref().map(|x| x.name().to_owned());
 
+        if let Some(name) = removed_name.as_ref()
+        {
+            if let Some(player) = self.entities.player(entity)
+            {
+                self.world.save_player(name, &player);
+            }
+        }
+
         if let Some(mut removed) = removed
         {
             if let Err(err) = removed.send_blocking(Message::PlayerDisconnectFinished)
@@ -508,6 +1511,51 @@ impl GameServer
 
                 return;
             },
+            Message::SnapshotAck{sequence} =>
+            {
+                self.connection_handler.write().get_mut(id).ack_snapshot(sequence);
+
+                return;
+            },
+            x => x
+        };
+
+        if self.world_ended
+        {
+            // only leaving is allowed after the world is over
+            if let Message::PlayerDisconnect{host} = message
+            {
+                self.connection_close(host, id, entity);
+            }
+
+            return;
+        }
+
+        // the player component belongs to the server, clients only get to say which hints they saw
+        let message = match message
+        {
+            Message::SetPlayer{entity: target, component} =>
+            {
+                some_or_return!(self.client_player(entity, target, *component))
+            },
+            Message::PlayerMove{entity: moved, sequence, target} =>
+            {
+                some_or_return!(self.player_move(id, entity, moved, sequence, target))
+            },
+            // open and locked state belongs to the server too
+            Message::SetContainer{..} => return,
+            Message::SetInventory{entity: target, component} if target != entity =>
+            {
+                // nothing comes out of a locked container
+                if self.entities.container_exists(target) && !self.entities.is_lootable(target)
+                {
+                    return;
+                }
+
+                self.check_theft(entity, target, &component);
+
+                Message::SetInventory{entity: target, component}
+            },
             x => x
         };
 
@@ -523,19 +1571,398 @@ impl GameServer
             message
         )};
 
+        let message = match message
+        {
+            Message::EntityDamage{entity: target, source, faction, damage} =>
+            {
+                let source = self.verified_source(id, entity, source);
+
+                // reputation goes by the attackers actual faction so a client cant pin a hit on someone else
+                let faction = source.and_then(|source| self.entities.faction(source)).unwrap_or(faction);
+
+                self.entity_damage(target, source, faction, damage);
+
+                return;
+            },
+            x => x
+        };
+
         let message = some_or_return!{self.entities.handle_message(message)};
 
         match message
         {
             Message::PlayerDisconnect{host} => self.connection_close(host, id, entity),
+            Message::SetTimeScale{scale} => self.request_time_scale(scale),
+            Message::SpawnEnemy{name, position} => self.request_spawn_enemy(id, &name, position),
+            Message::DialogueStart{entity: speaker} => self.dialogue_start(entity, speaker),
+            Message::DialogueChoose{entity: speaker, choice} => self.dialogue_choose(id, entity, speaker, choice),
+            Message::TradeOffer{entity: vendor, offer} => self.trade_offer(id, entity, vendor, offer),
+            Message::ConsumeItem{item} => self.consume_item(entity, item),
+            Message::Craft{recipe} => self.craft(entity, recipe),
+            Message::Butcher{entity: corpse} => self.butcher(entity, corpse),
+            Message::OpenContainer{entity: container, open} => self.open_container(entity, container, open),
+            Message::UnlockContainer{entity: container} => self.unlock_container(entity, container),
+            Message::SetSpawn{bed} => self.set_spawn(entity, bed),
+            Message::PickPerk{perk} => self.pick_perk(entity, perk),
+            Message::FireProjectile{entity: shooter, item, target} => self.fire_projectile(id, entity, shooter, item, target),
+            // only the server gets to say where these happen
+            Message::SpawnTracer{..}
+            | Message::SpawnImpact{..}
+            | Message::Explode{..}
+            | Message::TradeResponse{..}
+            | Message::OpenTrade{..}
+            | Message::RunScript{..} => (),
             x => panic!("unhandled message: {x:?}")
         }
     }
 
+    // the client moves its own player ahead of time, anything further than it couldve gone gets thrown out
+    fn player_move(
+        &mut self,
+        id: ConnectionId,
+        player: Entity,
+        entity: Entity,
+        sequence: u32,
+        target: Transform
+    ) -> Option<Message>
+    {
+        if entity != player
+        {
+            return None;
+        }
+
+        let current = self.entities.target_ref(entity)?.position;
+
+        let allowed = current.metric_distance(&target.position) <= MAX_MOVE_DISTANCE;
+
+        let position = if allowed { target.position } else { current };
+
+        self.connection_handler.write().send_single(id, Message::PlayerMoveAck{sequence, position});
+
+        allowed.then_some(Message::SetTarget{entity, target})
+    }
+
+    fn in_range(&self, a: Entity, b: Entity, distance: f32) -> bool
+    {
+        let position = |entity| self.entities.transform(entity).map(|x| x.position);
+
+        match (position(a), position(b))
+        {
+            (Some(a), Some(b)) => a.metric_distance(&b) <= distance,
+            _ => false
+        }
+    }
+
+    fn dialogue_start(&mut self, player: Entity, speaker: Entity)
+    {
+        let alive = self.entities.anatomy(speaker).map(|x| x.speed().is_some()).unwrap_or(false);
+
+        if !alive || !self.in_range(player, speaker, DIALOGUE_DISTANCE) || self.entities.is_hostile(speaker, player)
+        {
+            return;
+        }
+
+        let component = {
+            let mut dialogue = some_or_return!(self.entities.dialogue_mut(speaker));
+
+            // someone else is still talking to them
+            let busy = dialogue.session().map(|session|
+            {
+                session.player != player && self.in_range(session.player, speaker, DIALOGUE_DISTANCE)
+            }).unwrap_or(false);
+
+            if busy
+            {
+                return;
+            }
+
+            let start = some_or_return!(self.entities.infos().dialogues_info.get(&dialogue.name)).start.clone();
+            dialogue.start(player, start);
+
+            dialogue.clone()
+        };
+
+        self.send_message(Message::SetDialogue{entity: speaker, component: component.into()});
+    }
+
+    fn dialogue_choose(&mut self, id: ConnectionId, player: Entity, speaker: Entity, choice: Option<usize>)
+    {
+        let dialogues_info = self.entities.infos().dialogues_info.clone();
+
+        let (component, choice) = {
+            let mut dialogue = some_or_return!(self.entities.dialogue_mut(speaker));
+
+            if !dialogue.talking_to(player)
+            {
+                return;
+            }
+
+            let choice = choice.filter(|_| self.in_range(player, speaker, DIALOGUE_DISTANCE)).and_then(|index|
+            {
+                dialogue.current_node(&dialogues_info)?.choices.get(index)
+            }).filter(|choice| choice.available(&self.entities, player, speaker)).cloned();
+
+            // walking away or picking something that isnt there ends it
+            dialogue.advance(choice.as_ref().and_then(|choice| choice.next.clone()));
+
+            (dialogue.clone(), choice)
+        };
+
+        self.send_message(Message::SetDialogue{entity: speaker, component: component.into()});
+
+        some_or_return!(choice).effects.iter().for_each(|effect|
+        {
+            self.dialogue_effect(id, player, speaker, effect);
+        });
+    }
+
+    fn dialogue_effect(&mut self, id: ConnectionId, player: Entity, speaker: Entity, effect: &DialogueEffect)
+    {
+        match effect
+        {
+            DialogueEffect::GiveItem(name) | DialogueEffect::TakeItem(name) =>
+            {
+                let id = some_or_return!(self.entities.infos().items_info.get_id(name));
+
+                let component = {
+                    let mut inventory = some_or_return!(self.entities.inventory_mut(player));
+
+                    if let DialogueEffect::GiveItem(_) = effect
+                    {
+                        inventory.push(Item{id});
+                    } else
+                    {
+                        let item = some_or_return!(inventory.find(id));
+                        inventory.remove(item);
+                    }
+
+                    inventory.clone()
+                };
+
+                self.send_message_with_owner(Message::SetInventory{entity: player, component: component.into()});
+            },
+            DialogueEffect::Reputation(amount) =>
+            {
+                let faction = some_or_return!(self.entities.character(speaker)).faction;
+
+                let component = {
+                    let mut player_component = some_or_return!(self.entities.player_mut(player));
+                    player_component.reputation.change(faction, *amount);
+
+                    player_component.clone()
+                };
+
+                self.send_message_with_owner(Message::SetPlayer{entity: player, component: component.into()});
+            },
+            DialogueEffect::Trade =>
+            {
+                // the offers themselves get checked when they come in
+                self.connection_handler.write().get_mut(id).set_message(Message::OpenTrade{entity: speaker});
+            },
+            DialogueEffect::Recruit => self.recruit(player, speaker),
+            DialogueEffect::Script(script) =>
+            {
+                self.connection_handler.write().get_mut(id).set_message(Message::RunScript{script: script.clone()});
+            }
+        }
+    }
+
+    fn recruit(&mut self, player: Entity, entity: Entity)
+    {
+        let player_name = some_or_return!(self.entities.named(player)).clone();
+        let player_faction = some_or_return!(self.entities.character(player)).faction;
+
+        let position = some_or_return!(self.entities.transform(entity)).position;
+
+        let enemy = {
+            let mut enemy = some_or_return!(self.entities.enemy_mut(entity));
+            enemy.recruit(player_name, player, position);
+
+            enemy.clone()
+        };
+
+        let character = {
+            let mut character = some_or_return!(self.entities.character_mut(entity));
+            character.faction = player_faction;
+
+            character.clone()
+        };
+
+        self.send_message(Message::SetEnemy{entity, component: enemy.into()});
+        self.send_message(Message::SetCharacter{entity, component: character.into()});
+    }
+
+    fn trade_offer(&mut self, id: ConnectionId, player: Entity, vendor: Entity, offer: TradeOffer)
+    {
+        let response = self.trade_response(player, vendor, &offer).unwrap_or(BarterResponse::Refuse);
+
+        if response == BarterResponse::Accept
+        {
+            self.trade_exchange(player, vendor, &offer);
+        }
+
+        self.connection_handler.write().get_mut(id).set_message(Message::TradeResponse{entity: vendor, response});
+    }
+
+    // None if the offer doesnt make sense
+    fn trade_response(&mut self, player: Entity, vendor: Entity, offer: &TradeOffer) -> Option<BarterResponse>
+    {
+        let alive = self.entities.anatomy(vendor).map(|x| x.speed().is_some()).unwrap_or(false);
+
+        if offer.is_empty()
+            || !alive
+            || !self.in_range(player, vendor, TRADE_DISTANCE)
+            || self.entities.is_hostile(vendor, player)
+        {
+            return None;
+        }
+
+        let items = |entity, indices: &[InventoryItem]| -> Option<Vec<Item>>
+        {
+            // no selling the same thing twice
+            let repeated = indices.iter().enumerate().any(|(index, item)| indices[..index].contains(item));
+            if repeated
+            {
+                return None;
+            }
+
+            let inventory = self.entities.inventory(entity)?;
+
+            indices.iter().map(|item| inventory.get(*item).cloned()).collect()
+        };
+
+        let given = items(player, &offer.given)?;
+        let taken = items(vendor, &offer.taken)?;
+
+        let mut writer = self.connection_handler.write();
+
+        self.entities.barter_offer(&mut writer, player, vendor, &given, &taken)
+    }
+
+    fn trade_exchange(&mut self, player: Entity, vendor: Entity, offer: &TradeOffer)
+    {
+        // going from the back so the indices stay valid
+        let take_all = |inventory: &mut Inventory, items: &[InventoryItem]| -> Vec<Item>
+        {
+            let mut items = items.to_vec();
+            items.sort_unstable_by(|a, b| b.cmp(a));
+
+            items.into_iter().filter_map(|item| inventory.remove(item)).collect()
+        };
+
+        let (given, taken) = {
+            let mut player_inventory = some_or_return!(self.entities.inventory_mut(player));
+            let mut vendor_inventory = some_or_return!(self.entities.inventory_mut(vendor));
+
+            (take_all(&mut player_inventory, &offer.given), take_all(&mut vendor_inventory, &offer.taken))
+        };
+
+        [(player, taken), (vendor, given)].into_iter().for_each(|(entity, items)|
+        {
+            let component = {
+                let mut inventory = some_or_return!(self.entities.inventory_mut(entity));
+                items.into_iter().for_each(|item| inventory.push(item));
+
+                inventory.clone()
+            };
+
+            self.send_message_with_owner(Message::SetInventory{entity, component: component.into()});
+        });
+    }
+
+    fn consume_item(&mut self, entity: Entity, item: InventoryItem)
+    {
+        let food = {
+            let inventory = some_or_return!(self.entities.inventory(entity));
+            let id = some_or_return!(inventory.get(item)).id;
+
+            some_or_return!(self.entities.infos().items_info.get(id).food.clone())
+        };
+
+        let inventory = {
+            let mut inventory = some_or_return!(self.entities.inventory_mut(entity));
+            inventory.remove(item);
+
+            inventory.clone()
+        };
+
+        let needs = {
+            let mut needs = some_or_return!(self.entities.needs_mut(entity));
+            needs.consume(&food);
+
+            needs.clone()
+        };
+
+        self.send_message_with_owner(Message::SetInventory{entity, component: inventory.into()});
+        self.send_message_with_owner(Message::SetNeeds{entity, component: needs.into()});
+    }
+
+    fn craft(&mut self, entity: Entity, recipe: RecipeId)
+    {
+        let inventory = {
+            let recipes_info = self.entities.infos().recipes_info.clone();
+            let recipe = some_or_return!(recipes_info.items().get(usize::from(recipe)));
+
+            let mut inventory = some_or_return!(self.entities.inventory_mut(entity));
+            let mut used = some_or_return!(recipe.ingredients_in(&inventory));
+
+            // removing shifts everything after it back
+            used.sort_unstable();
+            used.into_iter().rev().for_each(|item| { inventory.remove(item); });
+
+            inventory.push(Item{id: recipe.result});
+
+            inventory.clone()
+        };
+
+        self.send_message_with_owner(Message::SetInventory{entity, component: inventory.into()});
+    }
+
+    fn is_single_player(&self) -> bool
+    {
+        self.connection_handler.read().players().count() == 1
+    }
+
+    fn request_time_scale(&mut self, scale: f32)
+    {
+        if !self.is_single_player()
+        {
+            eprintln!("cant change the time scale with multiple players connected");
+            return;
+        }
+
+        self.set_time_scale(scale.clamp(0.0, MAX_TIME_SCALE));
+    }
+
+    // a console command, only the host or someone playing alone gets to use it
+    fn request_spawn_enemy(&mut self, id: ConnectionId, name: &str, position: Vector3<f32>)
+    {
+        if !self.connection_handler.read().is_trusted(id) && !self.is_single_player()
+        {
+            eprintln!("only the host can spawn enemies with multiple players connected");
+            return;
+        }
+
+        self.world.spawn_enemy(&self.entities, name, position);
+    }
+
+    fn set_time_scale(&mut self, scale: f32)
+    {
+        self.time_scale = scale;
+
+        self.send_message(Message::SetTimeScale{scale});
+    }
+
     fn send_message(&mut self, message: Message)
     {
         self.connection_handler.write().send_message(message);
     }
+
+    // for when the server changes something about a player
+    fn send_message_with_owner(&mut self, message: Message)
+    {
+        self.connection_handler.write().send_message_with_owner(message);
+    }
 }
 
 impl EntitiesController for GameServer
@@ -0,0 +1,283 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap
+};
+
+use nalgebra::Vector3;
+
+use crate::common::{
+    some_or_value,
+    some_or_return,
+    angle_between,
+    damage::*,
+    damaging::*,
+    raycast::*,
+    collider::ColliderLayer,
+    projectile::{FireInfo, ProjectileKind, ProjectileHit, StepResult},
+    Decal,
+    DecalKind,
+    Explosion,
+    Tracer,
+    Projectile,
+    AnyEntities,
+    Entity,
+    Faction,
+    Message,
+    entity::{for_each_component, damaging_system, ServerEntities}
+};
+
+use super::world::World;
+
+
+// shots can bunch up on the way here so the cooldown is a bit forgiving
+const COOLDOWN_LENIENCY: f32 = 0.8;
+
+pub struct Hit
+{
+    pub entity: Entity,
+    pub source: Option<Entity>,
+    pub faction: Faction,
+    pub damage: Damage
+}
+
+// everything that came out of the shots, the game server applies it
+#[derive(Default)]
+pub struct Outcome
+{
+    pub hits: Vec<Hit>,
+    pub explosions: Vec<Explosion>,
+    pub messages: Vec<Message>,
+    // projectile entities that stopped
+    pub removed: Vec<Entity>
+}
+
+impl Outcome
+{
+    fn hit(
+        &mut self,
+        entities: &ServerEntities,
+        entity: Entity,
+        position: Vector3<f32>,
+        source: Option<Entity>,
+        faction: Faction,
+        damage: DamagePartial
+    )
+    {
+        let target = some_or_return!(entities.transform(entity)).position;
+
+        let side = some_or_return!(damaging_system::hit_side(entities, entity, angle_between(position, target)));
+
+        self.hits.push(Hit{entity, source, faction, damage: damage.with_direction(side)});
+    }
+}
+
+// the only place shots actually hit anything, clients only get told what to draw
+#[derive(Default)]
+pub struct ServerProjectiles
+{
+    cooldowns: HashMap<Entity, f32>
+}
+
+impl ServerProjectiles
+{
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    // false if this entity is still on cooldown from its last shot
+    pub fn ready(&mut self, entity: Entity, cooldown: f32) -> bool
+    {
+        if self.cooldowns.contains_key(&entity)
+        {
+            return false;
+        }
+
+        self.cooldowns.insert(entity, cooldown * COOLDOWN_LENIENCY);
+
+        true
+    }
+
+    pub fn fire(&mut self, entities: &ServerEntities, fire: FireInfo) -> Outcome
+    {
+        let mut outcome = Outcome::default();
+
+        match &fire.info.kind
+        {
+            ProjectileKind::Hitscan{pierce} => Self::fire_hitscan(entities, &mut outcome, *pierce, fire),
+            ProjectileKind::Simulated(info) =>
+            {
+                let projectile = Projectile::new(info, fire.info.impact.clone(), fire.info.explosion);
+
+                let damaging = DamagingInfo{
+                    damage: DamagingType::Damage{angle: 0.0, damage: fire.damage},
+                    source: Some(fire.source),
+                    faction: Some(fire.faction),
+                    ..Default::default()
+                };
+
+                let info = some_or_value!(projectile.entity_info(fire.start, fire.target, damaging), outcome);
+
+                entities.push(false, info);
+            }
+        }
+
+        outcome
+    }
+
+    fn fire_hitscan(
+        entities: &ServerEntities,
+        outcome: &mut Outcome,
+        pierce: Option<f32>,
+        fire: FireInfo
+    )
+    {
+        let info = RaycastInfo{
+            pierce,
+            layer: ColliderLayer::DAMAGE,
+            ignore_entity: Some(fire.source),
+            ignore_end: true
+        };
+
+        let hits = entities.raycast(info, &fire.start, &fire.target);
+
+        // nothing stopped the bullet so it ends up in the floor
+        if hits.hits.is_empty()
+        {
+            outcome.messages.push(Message::SpawnDecal{decal: Decal::new(DecalKind::BulletHole, fire.target)});
+        }
+
+        let mut end = fire.target;
+        let mut impact = None;
+        for hit in &hits.hits
+        {
+            let id = if let RaycastHitId::Entity(id) = hit.id
+            {
+                id
+            } else
+            {
+                continue;
+            };
+
+            let hit_position = hits.hit_position(hit);
+            end = hit_position;
+
+            let bleeds = entities.anatomy_exists(id);
+            if !bleeds
+            {
+                outcome.messages.push(Message::SpawnDecal{decal: Decal::new(DecalKind::Scorch, hit_position)});
+            }
+
+            impact = if bleeds { None } else { fire.info.impact.clone() };
+
+            outcome.hit(entities, id, hit_position, Some(fire.source), fire.faction, fire.damage.clone());
+        }
+
+        if let Some(info) = fire.info.explosion
+        {
+            outcome.explosions.push(Explosion{position: end, info, source: Some(fire.source), faction: fire.faction});
+        }
+
+        let tracer = Tracer::new(fire.start, end).with_impact(impact);
+
+        outcome.messages.push(Message::SpawnTracer{tracer});
+    }
+
+    pub fn update(&mut self, entities: &ServerEntities, world: &mut World, dt: f32) -> Outcome
+    {
+        self.cooldowns.retain(|_, cooldown|
+        {
+            *cooldown -= dt;
+
+            *cooldown > 0.0
+        });
+
+        let mut outcome = Outcome::default();
+
+        for_each_component!(entities, projectile, |entity, projectile: &RefCell<Projectile>|
+        {
+            let mut projectile = projectile.borrow_mut();
+
+            let (source, faction, damage) = {
+                let damaging = some_or_return!(entities.damaging(entity));
+
+                let damage = if let DamagingType::Damage{damage, ..} = &damaging.damage
+                {
+                    damage.clone()
+                } else
+                {
+                    return;
+                };
+
+                (damaging.source, damaging.faction, damage)
+            };
+
+            let mut position = some_or_return!(entities.transform(entity)).position;
+            let mut velocity = *some_or_return!(entities.physical(entity)).velocity();
+
+            let result = projectile.step(
+                entities,
+                source,
+                &mut position,
+                &mut velocity,
+                |start, end| world.tile_hit(start, end),
+                dt
+            );
+
+            some_or_return!(entities.transform_mut(entity)).position = position;
+            some_or_return!(entities.physical_mut(entity)).set_velocity_raw(velocity);
+
+            let impact = |position: Vector3<f32>, direction: Vector3<f32>|
+            {
+                projectile.impact.clone().map(|impact| Message::SpawnImpact{impact, position, direction})
+            };
+
+            let (position, direction, hit) = match result
+            {
+                StepResult::Flying => return,
+                StepResult::Bounced{position, direction} =>
+                {
+                    outcome.messages.extend(impact(position, -*direction));
+
+                    // the clients physics doesnt know about walls so it gets the new path straight away
+                    let physical = some_or_return!(entities.physical(entity)).clone();
+
+                    outcome.messages.push(Message::SyncPosition{entity, position});
+                    outcome.messages.push(Message::SetPhysical{entity, component: Box::new(physical)});
+
+                    return;
+                },
+                StepResult::Stopped{position, direction, hit} => (position, direction, hit)
+            };
+
+            match hit
+            {
+                ProjectileHit::Nothing => (),
+                ProjectileHit::Entity(hit) =>
+                {
+                    if !entities.anatomy_exists(hit)
+                    {
+                        outcome.messages.extend(impact(position, -*direction));
+                    }
+
+                    outcome.hit(entities, hit, position, source, faction, damage);
+                },
+                ProjectileHit::Wall =>
+                {
+                    outcome.messages.extend(impact(position, -*direction));
+
+                    outcome.messages.push(Message::SpawnDecal{decal: Decal::new(DecalKind::BulletHole, position)});
+                }
+            }
+
+            if let Some(info) = projectile.explosion
+            {
+                outcome.explosions.push(Explosion{position, info, source, faction});
+            }
+
+            outcome.removed.push(entity);
+        });
+
+        outcome
+    }
+}
@@ -1,5 +1,10 @@
 use std::{
-    path::PathBuf,
+    f32,
+    io,
+    mem,
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
     sync::Arc,
     rc::Rc,
     cell::RefCell,
@@ -15,6 +20,11 @@ use crate::{
     server::ConnectionsHandler,
     common::{
         self,
+        some_or_return,
+        some_or_false,
+        save_file,
+        rng::{self, RngStream},
+        copy_dir,
         SpecialTile,
         FurnitureBuilder,
         EnemyBuilder,
@@ -22,8 +32,22 @@ use crate::{
         WorldChunkSaver,
         ChunkSaver,
         ItemsInfo,
+        ContainersInfo,
         EntitiesSaver,
+        EnemyInfo,
         EnemiesInfo,
+        Faction,
+        ExplosionInfo,
+        Transform,
+        TileInfo,
+        DamagePartial,
+        DamageType,
+        DamageHeight,
+        Side2d,
+        fire::*,
+        Player,
+        Difficulty,
+        Inventory,
         SaveLoad,
         AnyEntities,
         EntityPasser,
@@ -31,7 +55,7 @@ use crate::{
         EntityInfo,
         FullEntityInfo,
         ConnectionId,
-        entity::ServerEntities,
+        entity::{for_each_component, ServerEntities},
         message::Message,
         world::{
             CHUNK_SIZE,
@@ -44,6 +68,8 @@ use crate::{
             ChunkLocal,
             GlobalPos,
             Pos3,
+            TileHit,
+            line_tile_hit,
             overmap::{Overmap, OvermapIndexing, CommonIndexing}
         }
     }
@@ -53,10 +79,16 @@ use world_generator::WorldGenerator;
 
 use server_overmap::ServerOvermap;
 
+pub use server_overmap::surface_preview;
+
 pub use world_generator::ParseError;
 
+pub use save_info::SaveInfo;
+
 pub mod world_generator;
+pub mod world_list;
 mod server_overmap;
+mod save_info;
 
 mod spawner;
 
@@ -64,6 +96,23 @@ mod spawner;
 pub const SERVER_OVERMAP_SIZE: usize = CLIENT_OVERMAP_SIZE + 1;
 pub const SERVER_OVERMAP_SIZE_Z: usize = CLIENT_OVERMAP_SIZE_Z + 1;
 
+#[derive(Debug, Clone, Copy)]
+pub struct AutosaveSettings
+{
+    // in seconds, 0 turns autosaving off
+    pub interval: f32,
+    // how many autosave copies to rotate between
+    pub amount: usize
+}
+
+impl Default for AutosaveSettings
+{
+    fn default() -> Self
+    {
+        Self{interval: 300.0, amount: 3}
+    }
+}
+
 type OvermapsType = Rc<RefCell<HashMap<ConnectionId, ServerOvermap<WorldChunkSaver>>>>;
 
 #[derive(Debug, Clone)]
@@ -99,8 +148,14 @@ pub struct World
     entities_saver: EntitiesSaver,
     enemies_info: Arc<EnemiesInfo>,
     items_info: Arc<ItemsInfo>,
+    containers_info: Arc<ContainersInfo>,
     overmaps: OvermapsType,
-    client_indexers: HashMap<ConnectionId, ClientIndexer>
+    client_indexers: HashMap<ConnectionId, ClientIndexer>,
+    autosaves: usize,
+    difficulty: Difficulty,
+    // in seconds, carries over between loads
+    playtime: f64,
+    area_changed: bool
 }
 
 impl World
@@ -109,14 +164,22 @@ impl World
         message_handler: Arc<RwLock<ConnectionsHandler>>,
         tilemap: TileMap,
         enemies_info: Arc<EnemiesInfo>,
-        items_info: Arc<ItemsInfo>
+        items_info: Arc<ItemsInfo>,
+        containers_info: Arc<ContainersInfo>,
+        world_name: String,
+        autosaves: usize
     ) -> Result<Self, ParseError>
     {
         let tilemap = Rc::new(tilemap);
 
-        let world_name = "default".to_owned();
-
         let world_path = Self::world_path_associated(&world_name);
+
+        rng::seed(Self::load_seed(&world_path));
+
+        let difficulty = Self::load_difficulty(&world_path);
+
+        let playtime = SaveInfo::load(&world_path).map(|x| x.playtime).unwrap_or(0.0);
+
         let chunk_saver = ChunkSaver::new(world_path.join("chunks"), 100);
         let entities_saver = EntitiesSaver::new(world_path.join("entities"), 0);
 
@@ -140,11 +203,174 @@ impl World
             entities_saver,
             enemies_info,
             items_info,
+            containers_info,
             overmaps,
-            client_indexers
+            client_indexers,
+            autosaves,
+            difficulty,
+            playtime,
+            area_changed: false
         })
     }
 
+    // a new world gets a random seed which then stays the same for every load
+    fn load_seed(world_path: &Path) -> u64
+    {
+        let path = world_path.join("seed");
+
+        if let Some(seed) = fs::read_to_string(&path).ok().and_then(|x| x.trim().parse().ok())
+        {
+            return seed;
+        }
+
+        let seed = fastrand::u64(..);
+
+        Self::write_seed(world_path, seed);
+
+        seed
+    }
+
+    fn write_seed(world_path: &Path, seed: u64)
+    {
+        let result = fs::create_dir_all(world_path).and_then(|_| fs::write(world_path.join("seed"), seed.to_string()));
+        if let Err(err) = result
+        {
+            eprintln!("error saving world seed: {err}");
+        }
+    }
+
+    // only new worlds pick up the seed, changing it later would mismatch the already generated chunks
+    pub fn create_seed(world_name: &str, seed: u64)
+    {
+        let world_path = Self::world_path_associated(world_name);
+
+        if world_path.join("seed").exists()
+        {
+            eprintln!("world {world_name} already exists, ignoring the seed");
+            return;
+        }
+
+        Self::write_seed(&world_path, seed);
+    }
+
+    // worlds from before difficulties existed just stay on normal
+    fn load_difficulty(world_path: &Path) -> Difficulty
+    {
+        let path = world_path.join("difficulty.json");
+
+        let difficulty = fs::read_to_string(&path).ok().and_then(|x|
+        {
+            serde_json::from_str(&x).map_err(|err|
+            {
+                eprintln!("error parsing difficulty, using the default: {err}")
+            }).ok()
+        });
+
+        difficulty.unwrap_or_else(||
+        {
+            let difficulty = Difficulty::default();
+            Self::write_difficulty(world_path, difficulty);
+
+            difficulty
+        })
+    }
+
+    fn write_difficulty(world_path: &Path, difficulty: Difficulty)
+    {
+        let result = serde_json::to_string_pretty(&difficulty).map_err(io::Error::other)
+            .and_then(|json|
+            {
+                fs::create_dir_all(world_path)?;
+                fs::write(world_path.join("difficulty.json"), json)
+            });
+
+        if let Err(err) = result
+        {
+            eprintln!("error saving world difficulty: {err}");
+        }
+    }
+
+    // same as the seed, only new worlds pick it up
+    pub fn create_difficulty(world_name: &str, difficulty: Difficulty)
+    {
+        let world_path = Self::world_path_associated(world_name);
+
+        if world_path.join("difficulty.json").exists()
+        {
+            eprintln!("world {world_name} already exists, ignoring the difficulty");
+            return;
+        }
+
+        Self::write_difficulty(&world_path, difficulty);
+    }
+
+    pub fn difficulty(&self) -> Difficulty
+    {
+        self.difficulty
+    }
+
+    // the world gets generated around the origin on the surface
+    pub fn spawn_point(&self) -> Vector3<f32>
+    {
+        Vector3::zeros()
+    }
+
+    pub fn add_playtime(&mut self, dt: f32)
+    {
+        self.playtime += dt as f64;
+    }
+
+    fn write_save_info(&self)
+    {
+        if let Err(err) = SaveInfo::new(self.playtime).write(&self.world_path())
+        {
+            eprintln!("error saving world info: {err}");
+        }
+    }
+
+    // a world that ended with permadeath cant be played again
+    pub fn is_locked(world_name: &str) -> bool
+    {
+        Self::world_path_associated(world_name).join("locked").exists()
+    }
+
+    pub fn lock(&self)
+    {
+        let path = Self::world_path_associated(&self.world_name).join("locked");
+
+        if let Err(err) = fs::write(&path, "")
+        {
+            eprintln!("error locking world {}: {err}", self.world_name);
+        }
+    }
+
+    pub fn spawn_remains(&self, entities: &ServerEntities, position: Vector3<f32>, inventory: Inventory)
+    {
+        let mut info = FurnitureBuilder::new(&self.items_info, &self.difficulty, position)
+            .build_remains(inventory);
+
+        info.saveable = Some(());
+
+        entities.push(false, info);
+    }
+
+    pub fn spawn_enemy(&self, entities: &ServerEntities, name: &str, position: Vector3<f32>)
+    {
+        let id = some_or_return!(self.enemies_info.get_id(name));
+
+        let mut info = EnemyBuilder::new(
+            &self.enemies_info,
+            &self.items_info,
+            &self.difficulty,
+            id,
+            position
+        ).build();
+
+        info.saveable = Some(());
+
+        entities.push(false, info);
+    }
+
     fn set_tile_local(&mut self, pos: TilePos, tile: Tile)
     {
         if let Some(chunk) = self.chunk_saver.load(pos.chunk)
@@ -155,6 +381,371 @@ impl World
         }
     }
 
+    fn tile_at(&mut self, pos: TilePos) -> Option<Tile>
+    {
+        self.chunk_saver.load(pos.chunk).map(|chunk| chunk[pos.local])
+    }
+
+    fn tile_info_at(&mut self, position: Vector3<f32>) -> Option<&TileInfo>
+    {
+        let tile = self.tile_at(TilePos::from(Pos3::from(position)))?;
+
+        Some(self.tilemap.info(tile))
+    }
+
+    fn in_liquid(&mut self, position: Vector3<f32>) -> bool
+    {
+        self.tile_info_at(position).map(|info| info.liquid.is_some()).unwrap_or(false)
+    }
+
+    fn under_roof(&mut self, position: Vector3<f32>) -> bool
+    {
+        let pos = TilePos::from(Pos3::from(position));
+
+        (1..=3).any(|z|
+        {
+            self.tile_at(pos.offset(Pos3::new(0, 0, z))).map(|tile| !tile.is_none()).unwrap_or(false)
+        })
+    }
+
+    fn tile_center(pos: TilePos) -> Vector3<f32>
+    {
+        Vector3::from(pos.position()) + Vector3::repeat(TILE_SIZE * 0.5)
+    }
+
+    // spilled means it can burn on empty tiles too, otherwise only flammable tiles catch
+    fn ignite_tile(
+        &mut self,
+        entities: &ServerEntities,
+        burning: &mut Vec<Vector3<f32>>,
+        pos: TilePos,
+        spilled: bool
+    )
+    {
+        let position = Self::tile_center(pos);
+
+        if burning.iter().any(|x| x.metric_distance(&position) < TILE_SIZE * 0.5)
+        {
+            return;
+        }
+
+        let tile = some_or_return!(self.tile_at(pos));
+
+        let fuel = if tile.is_none()
+        {
+            if !spilled
+            {
+                return;
+            }
+
+            SPILL_FUEL
+        } else
+        {
+            some_or_return!(self.tilemap.info(tile).flammable)
+        };
+
+        if self.in_liquid(position)
+        {
+            return;
+        }
+
+        entities.push(false, Fire::new(fuel).entity_info(position, None));
+        burning.push(position);
+    }
+
+    fn ignite_entity(&self, entities: &ServerEntities, entity: Entity, fuel: f32)
+    {
+        let burning = entities.children_of(entity).any(|child| entities.fire_exists(child));
+        if burning
+        {
+            return;
+        }
+
+        let position = some_or_return!(entities.transform(entity)).position;
+
+        entities.push(false, Fire::new(fuel).entity_info(position, Some(entity)));
+    }
+
+    // anything flammable that isnt a tile, characters always catch
+    fn flammable_entities(&self, entities: &ServerEntities, position: Vector3<f32>, radius: f32) -> Vec<(Entity, f32)>
+    {
+        let mut flammable = Vec::new();
+        for_each_component!(entities, transform, |entity, transform: &RefCell<Transform>|
+        {
+            let offset = transform.borrow().position - position;
+            if offset.xy().magnitude() > radius || offset.z.abs() > TILE_SIZE
+            {
+                return;
+            }
+
+            let fuel = if entities.anatomy_exists(entity)
+            {
+                Some(BURNING_FUEL)
+            } else
+            {
+                entities.container(entity).and_then(|container|
+                {
+                    self.containers_info.get(&container.name).and_then(|info| info.flammable)
+                })
+            };
+
+            if let Some(fuel) = fuel
+            {
+                flammable.push((entity, fuel));
+            }
+        });
+
+        flammable
+    }
+
+    fn ground_fires(entities: &ServerEntities) -> Vec<Vector3<f32>>
+    {
+        let mut burning = Vec::new();
+        for_each_component!(entities, fire, |entity, _fire: &RefCell<Fire>|
+        {
+            if entities.parent_exists(entity)
+            {
+                return;
+            }
+
+            if let Some(transform) = entities.transform(entity)
+            {
+                burning.push(transform.position);
+            }
+        });
+
+        burning
+    }
+
+    fn ignite_area(&mut self, entities: &ServerEntities, position: Vector3<f32>, radius: f32)
+    {
+        let mut burning = Self::ground_fires(entities);
+
+        let reach = (radius / TILE_SIZE).ceil() as i32;
+        let center = TilePos::from(Pos3::from(position));
+
+        (-reach..=reach).flat_map(|y|
+        {
+            (-reach..=reach).map(move |x| center.offset(Pos3::new(x, y, 0)))
+        }).filter(|pos|
+        {
+            Self::tile_center(*pos).metric_distance(&position) <= radius
+        }).for_each(|pos|
+        {
+            self.ignite_tile(entities, &mut burning, pos, true);
+        });
+
+        self.flammable_entities(entities, position, radius).into_iter().for_each(|(entity, fuel)|
+        {
+            self.ignite_entity(entities, entity, fuel);
+        });
+    }
+
+    pub fn update_fires(&mut self, entities: &mut ServerEntities, raining: bool, dt: f32)
+    {
+        let mut burnt = Vec::new();
+        let mut heating = Vec::new();
+        let mut spreading = Vec::new();
+
+        for_each_component!(entities, fire, |entity, fire: &RefCell<Fire>|
+        {
+            let parent = entities.parent(entity).map(|parent| parent.entity());
+
+            // the server doesnt move children around so go by whatever is burning
+            let position = some_or_return!(entities.transform(parent.unwrap_or(entity))).position;
+
+            let mut fire = fire.borrow_mut();
+
+            if self.in_liquid(position)
+            {
+                fire.extinguish();
+            }
+
+            // stop drop and roll
+            let rolling = parent.and_then(|parent| entities.character(parent))
+                .map(|character| character.is_dodging())
+                .unwrap_or(false);
+
+            if rolling
+            {
+                fire.smother(ROLL_SMOTHER * dt);
+            }
+
+            // rain only gets to whats out in the open
+            let rained_on = raining && !self.under_roof(position);
+
+            fire.update(if rained_on { RAIN_BURN } else { 1.0 }, dt);
+
+            if fire.burnt_out()
+            {
+                burnt.push((entity, parent, position));
+                return;
+            }
+
+            if fire.consume_heat()
+            {
+                heating.push((parent, position));
+            }
+
+            if fire.consume_spread() && !rained_on
+            {
+                spreading.push(position);
+            }
+        });
+
+        let mut messages = Vec::new();
+        let mut damaged = Vec::new();
+
+        heating.into_iter().for_each(|(parent, position)|
+        {
+            if let Some(parent) = parent.filter(|parent| entities.anatomy_exists(*parent))
+            {
+                let faction = some_or_return!(entities.faction(parent));
+
+                let damage = DamagePartial{
+                    data: DamageType::Heat(HEAT_DAMAGE),
+                    height: DamageHeight::random()
+                }.with_direction(Side2d::from_angle(rng::f32(RngStream::Combat) * f32::consts::TAU));
+
+                // burning hurts anyone no matter whose side theyre on
+                let (_, damage) = some_or_return!(entities.resolve_damage(parent, Some(parent), faction, damage));
+                let damage = some_or_return!(damage);
+
+                entities.apply_damage(parent, damage.clone());
+
+                damaged.push(Message::EntityDamaged{entity: parent, source: Some(parent), damage});
+            } else
+            {
+                self.flammable_entities(entities, position, IGNITE_DISTANCE).into_iter()
+                    .filter(|(entity, _)| entities.anatomy_exists(*entity))
+                    .filter(|_| rng::f32(RngStream::Combat) < IGNITE_CHANCE)
+                    .for_each(|(entity, fuel)|
+                    {
+                        self.ignite_entity(entities, entity, fuel);
+                    });
+            }
+        });
+
+        let mut burning = Self::ground_fires(entities);
+        spreading.into_iter().for_each(|position|
+        {
+            let center = TilePos::from(Pos3::from(position));
+
+            (-1..=1).flat_map(|y| (-1..=1).map(move |x| center.offset(Pos3::new(x, y, 0))))
+                .filter(|_| rng::f32(RngStream::Combat) < SPREAD_CHANCE)
+                .for_each(|pos|
+                {
+                    self.ignite_tile(entities, &mut burning, pos, false);
+                });
+
+            self.flammable_entities(entities, position, SPREAD_DISTANCE).into_iter()
+                .filter(|(entity, _)| !entities.anatomy_exists(*entity))
+                .filter(|_| rng::f32(RngStream::Combat) < SPREAD_CHANCE)
+                .for_each(|(entity, fuel)|
+                {
+                    self.ignite_entity(entities, entity, fuel);
+                });
+        });
+
+        burnt.into_iter().for_each(|(entity, parent, position)|
+        {
+            match parent
+            {
+                // burnt furniture goes with its fire
+                Some(parent) if entities.container_exists(parent) =>
+                {
+                    messages.push(entities.remove_message(parent));
+                },
+                Some(_) =>
+                {
+                    messages.push(entities.remove_message(entity));
+                },
+                None =>
+                {
+                    let pos = TilePos::from(Pos3::from(position));
+
+                    let flammable = self.tile_at(pos)
+                        .map(|tile| self.tilemap.info(tile).flammable.is_some())
+                        .unwrap_or(false);
+
+                    if flammable
+                    {
+                        self.set_tile_local(pos, Tile::none());
+                        messages.push(Message::SetTile{pos, tile: Tile::none()});
+                    }
+
+                    messages.push(entities.remove_message(entity));
+                }
+            }
+        });
+
+        let mut writer = self.message_handler.write();
+        messages.into_iter().for_each(|message| writer.send_message(message));
+
+        // whoever is burning has to know about it too
+        damaged.into_iter().for_each(|message| writer.send_message_with_owner(message));
+    }
+
+    pub fn tile_hit(&mut self, start: Vector3<f32>, end: Vector3<f32>) -> Option<TileHit>
+    {
+        line_tile_hit(start, end, |pos|
+        {
+            self.tile_at(pos).map(|tile| self.tilemap.info(tile).colliding).unwrap_or(false)
+        })
+    }
+
+    // only the tiles and the fire, the server hurts whoever got caught in it separately
+    pub fn explode(&mut self, entities: &mut ServerEntities, position: Vector3<f32>, info: ExplosionInfo)
+    {
+        let reach = (info.radius / TILE_SIZE).ceil() as i32;
+
+        let center = TilePos::from(Pos3::from(position));
+
+        let tilemap = &self.tilemap;
+        let chunk_saver = &mut self.chunk_saver;
+
+        // only whats on the same level, floors and ceilings stay
+        let destroyed: Vec<TilePos> = (-reach..=reach).flat_map(|y|
+        {
+            (-reach..=reach).map(move |x| center.offset(Pos3::new(x, y, 0)))
+        }).filter(|pos|
+        {
+            let chunk = some_or_false!(chunk_saver.load(pos.chunk));
+
+            let tile = chunk[pos.local];
+            if tile.is_none()
+            {
+                return false;
+            }
+
+            let toughness = some_or_false!(tilemap.info(tile).toughness);
+
+            let distance = (Self::tile_center(*pos) - position).magnitude();
+
+            let strength = (1.0 - distance / info.radius).max(0.0).powi(2);
+
+            info.damage * strength >= toughness
+        }).collect();
+
+        {
+            let mut writer = self.message_handler.write();
+            destroyed.into_iter().for_each(|pos|
+            {
+                if let Some(chunk) = self.chunk_saver.load(pos.chunk)
+                {
+                    self.chunk_saver.save(pos.chunk, chunk.with_set_tile(pos.local, Tile::none()));
+                }
+
+                writer.send_message(Message::SetTile{pos, tile: Tile::none()});
+            });
+        }
+
+        if info.fire
+        {
+            self.ignite_area(entities, position, info.radius);
+        }
+    }
+
     pub fn add_player(
         &mut self,
         container: &mut ServerEntities,
@@ -205,6 +796,12 @@ impl World
 
             let position_changed = *previous_position != new_position;
 
+            // going up or down a floor counts as a new area
+            if previous_position.0.z != new_position.0.z
+            {
+                self.area_changed = true;
+            }
+
             *previous_position = new_position;
 
             if position_changed
@@ -234,6 +831,8 @@ impl World
 
     pub fn exit(&mut self, container: &mut ServerEntities)
     {
+        self.write_save_info();
+
         let mut writer = self.message_handler.write();
         Self::unload_entities_inner(&mut self.entities_saver, container, &mut writer, |_global|
         {
@@ -241,6 +840,86 @@ impl World
         });
     }
 
+    pub fn take_area_changed(&mut self) -> bool
+    {
+        mem::take(&mut self.area_changed)
+    }
+
+    // saves everything without unloading it and copies the save into the oldest autosave slot
+    pub fn autosave(&mut self, container: &ServerEntities)
+    {
+        let players: Vec<(String, Entity)> = self.message_handler.read().players().map(|(name, entity)|
+        {
+            (name.to_owned(), entity)
+        }).collect();
+
+        players.into_iter().for_each(|(name, entity)|
+        {
+            if let Some(player) = container.player(entity)
+            {
+                self.save_player(&name, &player);
+            }
+        });
+
+        self.chunk_saver.flush();
+        self.entities_saver.flush();
+        self.world_generator.borrow_mut().flush();
+
+        self.write_save_info();
+
+        if self.autosaves == 0
+        {
+            return;
+        }
+
+        let slot = self.autosave_slot();
+        if let Err(err) = self.autosave_into(container, &slot)
+        {
+            eprintln!("error autosaving into {}: {err}", slot.display());
+        }
+    }
+
+    fn autosave_into(&self, container: &ServerEntities, slot: &Path) -> io::Result<()>
+    {
+        let temp_path = slot.with_extension("tmp");
+
+        if temp_path.exists()
+        {
+            fs::remove_dir_all(&temp_path)?;
+        }
+
+        copy_dir(&self.world_path(), &temp_path)?;
+
+        {
+            // loaded entities only live in memory, they go straight into the copy
+            let mut saver = EntitiesSaver::new(temp_path.join("entities"), 0);
+
+            let (_, entities) = Self::collect_saveable(container, |_global| false);
+            Self::save_entities(&mut saver, entities);
+        }
+
+        // the previous autosave in this slot stays around until the new one is complete
+        if slot.exists()
+        {
+            fs::remove_dir_all(slot)?;
+        }
+
+        fs::rename(temp_path, slot)
+    }
+
+    // an unused slot or the one that was written to longest ago
+    fn autosave_slot(&self) -> PathBuf
+    {
+        let slots = (0..self.autosaves).map(|index| self.autosaves_path().join(index.to_string()));
+
+        let modified = |path: &PathBuf|
+        {
+            fs::metadata(path).and_then(|x| x.modified()).unwrap_or(SystemTime::UNIX_EPOCH)
+        };
+
+        slots.min_by_key(modified).unwrap()
+    }
+
     pub fn send_all(
         &mut self,
         container: &mut ServerEntities,
@@ -315,16 +994,26 @@ impl World
         chunk_pos: Pos3<f32>,
         chunk: &'a Chunk,
         amount: usize,
+        outdoors: bool,
         f: impl Fn(Vector3<f32>) -> Option<EntityInfo> + 'a
     ) -> impl Iterator<Item=EntityInfo> + 'a
     {
         (0..amount)
-            .map(|_|
+            .map(move |_|
             {
+                // falling from the top of the chunk always lands on the first ground under the sky
+                let z = if outdoors
+                {
+                    CHUNK_SIZE - 2
+                } else
+                {
+                    rng::usize(RngStream::WorldGen, 0..CHUNK_SIZE - 1)
+                };
+
                 ChunkLocal::new(
-                    fastrand::usize(0..CHUNK_SIZE),
-                    fastrand::usize(0..CHUNK_SIZE),
-                    fastrand::usize(0..CHUNK_SIZE - 1)
+                    rng::usize(RngStream::WorldGen, 0..CHUNK_SIZE),
+                    rng::usize(RngStream::WorldGen, 0..CHUNK_SIZE),
+                    z
                 )
             })
             .filter_map(|pos|
@@ -394,7 +1083,7 @@ impl World
     fn add_entities(
         &self,
         container: &mut ServerEntities,
-        chunk_pos: Pos3<f32>,
+        pos: GlobalPos,
         chunk: &mut Chunk
     )
     {
@@ -403,24 +1092,39 @@ impl World
             return;
         }
 
+        let chunk_pos: Pos3<f32> = pos.into();
+
         self.create_spawners(container, chunk_pos, chunk);
 
-        let spawns = fastrand::usize(0..3);
-        let crates = fastrand::usize(0..2);
+        let spawns = rng::usize(RngStream::WorldGen, 0..3);
+        let wildlife = rng::usize(RngStream::WorldGen, 0..3);
 
-        let entities = Self::add_on_ground(chunk_pos, chunk, spawns, |pos|
+        // animals only live outside on the surface, not in tunnels or up in buildings
+        let wildlife = if pos.0.z == 0 { wildlife } else { 0 };
+        let crates = rng::usize(RngStream::WorldGen, 0..2);
+
+        let enemy_with = |pos, predicate: fn(&EnemyInfo) -> bool|
         {
-            let picked = self.enemies_info.weighted_random(1.0)?;
+            let picked = self.enemies_info.weighted_random_with(1.0, predicate)?;
 
             Some(EnemyBuilder::new(
                 &self.enemies_info,
                 &self.items_info,
+                &self.difficulty,
                 picked,
                 pos
             ).build())
-        }).chain(Self::add_on_ground(chunk_pos, chunk, crates, |pos|
+        };
+
+        let entities = Self::add_on_ground(chunk_pos, chunk, spawns, false, move |pos|
         {
-            Some(FurnitureBuilder::new(&self.items_info, pos).build())
+            enemy_with(pos, |info| info.faction != Faction::Animal)
+        }).chain(Self::add_on_ground(chunk_pos, chunk, wildlife, true, move |pos|
+        {
+            enemy_with(pos, |info| info.faction == Faction::Animal)
+        })).chain(Self::add_on_ground(chunk_pos, chunk, crates, false, |pos|
+        {
+            FurnitureBuilder::new(&self.items_info, &self.difficulty, pos).build(&self.containers_info)
         })).map(|mut entity_info|
         {
             if entity_info.saveable.is_none()
@@ -467,7 +1171,7 @@ impl World
                 .expect("id must be valid")
                 .generate_chunk(pos);
 
-            self.add_entities(container, pos.into(), &mut chunk);
+            self.add_entities(container, pos, &mut chunk);
 
             self.chunk_saver.save(pos, chunk.clone());
 
@@ -502,16 +1206,14 @@ impl World
         (delete_ids, delete_entities)
     }
 
-    fn unload_entities_inner<F>(
-        saver: &mut EntitiesSaver,
-        container: &mut ServerEntities,
-        message_handler: &mut ConnectionsHandler,
+    fn collect_saveable<F>(
+        container: &ServerEntities,
         keep: F
-    )
+    ) -> (Vec<Entity>, HashMap<GlobalPos, Vec<FullEntityInfo>>)
     where
         F: Fn(GlobalPos) -> bool
     {
-        let delete_entities = container.saveable.iter()
+        let entities = container.saveable.iter()
             .rev()
             .map(|(_, x)| x.entity)
             .filter_map(|entity|
@@ -534,9 +1236,12 @@ impl World
                 (entity, info.to_full(container), pos)
             });
 
-        let (delete_ids, delete_entities) = Self::collect_to_delete(delete_entities);
+        Self::collect_to_delete(entities)
+    }
 
-        delete_entities.into_iter().for_each(|(pos, mut entities)|
+    fn save_entities(saver: &mut EntitiesSaver, entities: HashMap<GlobalPos, Vec<FullEntityInfo>>)
+    {
+        entities.into_iter().for_each(|(pos, mut entities)|
         {
             if let Some(mut previous) = saver.load(pos)
             {
@@ -547,6 +1252,20 @@ impl World
 
             saver.save(pos, entities);
         });
+    }
+
+    fn unload_entities_inner<F>(
+        saver: &mut EntitiesSaver,
+        container: &mut ServerEntities,
+        message_handler: &mut ConnectionsHandler,
+        keep: F
+    )
+    where
+        F: Fn(GlobalPos) -> bool
+    {
+        let (delete_ids, delete_entities) = Self::collect_saveable(container, keep);
+
+        Self::save_entities(saver, delete_entities);
 
         delete_ids.into_iter().for_each(|entity|
         {
@@ -556,17 +1275,68 @@ impl World
         });
     }
 
-    #[allow(dead_code)]
     fn world_path(&self) -> PathBuf
     {
         Self::world_path_associated(&self.world_name)
     }
 
-    fn world_path_associated(name: &str) -> PathBuf
+    fn player_path(&self, name: &str) -> PathBuf
+    {
+        let name: String = name.chars().map(|c|
+        {
+            if c.is_alphanumeric()
+            {
+                c
+            } else
+            {
+                '_'
+            }
+        }).collect();
+
+        self.world_path().join("players").join(name)
+    }
+
+    pub fn save_player(&self, name: &str, player: &Player)
+    {
+        let path = self.player_path(name);
+
+        let result = fs::create_dir_all(path.parent().unwrap()).map_err(|err| err.to_string())
+            .and_then(|_|
+            {
+                let bytes = save_file::serialize(player).map_err(|err| err.to_string())?;
+
+                save_file::write(&path, &bytes).map_err(|err| err.to_string())
+            });
+
+        if let Err(err) = result
+        {
+            eprintln!("error saving player \"{name}\": {err}");
+        }
+    }
+
+    pub fn load_player(&self, name: &str) -> Option<Player>
+    {
+        let bytes = save_file::read(&self.player_path(name)).map_err(|err|
+        {
+            eprintln!("error loading player \"{name}\": {err}");
+        }).ok()??;
+
+        save_file::deserialize(&bytes).map_err(|err|
+        {
+            eprintln!("error loading player \"{name}\": {err}");
+        }).ok()
+    }
+
+    pub fn world_path_associated(name: &str) -> PathBuf
     {
         PathBuf::from("worlds").join(name)
     }
 
+    fn autosaves_path(&self) -> PathBuf
+    {
+        PathBuf::from("worlds").join(format!("{}_autosaves", self.world_name))
+    }
+
     pub fn handle_message(
         &mut self,
         container: &mut ServerEntities,
@@ -666,7 +1436,10 @@ mod tests
                 passer.clone(),
                 tilemap.tilemap,
                 Arc::new(EnemiesInfo::empty()),
-                Arc::new(ItemsInfo::empty())
+                Arc::new(ItemsInfo::empty()),
+                Arc::new(ContainersInfo::empty()),
+                "default".to_owned(),
+                0
             ).unwrap();
 
             world.add_player(&mut entities, player, Pos3::new(0.0, 0.0, 0.0));
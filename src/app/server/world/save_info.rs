@@ -0,0 +1,71 @@
+use std::{
+    io,
+    fs,
+    path::Path,
+    time::{Duration, SystemTime}
+};
+
+use serde::{Serialize, Deserialize};
+
+use crate::common::save_file;
+
+
+const SAVE_INFO_NAME: &str = "save.json";
+
+// written next to the world every time it gets saved, only for showing in the world list
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveInfo
+{
+    // seconds since the unix epoch
+    pub saved: u64,
+    // seconds spent with the world loaded
+    pub playtime: f64,
+    pub version: String,
+    pub format_version: u32
+}
+
+impl SaveInfo
+{
+    pub fn new(playtime: f64) -> Self
+    {
+        let saved = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH)
+            .map(|x| x.as_secs())
+            .unwrap_or(0);
+
+        Self{
+            saved,
+            playtime,
+            version: env!("CARGO_PKG_VERSION").to_owned(),
+            format_version: save_file::FORMAT_VERSION
+        }
+    }
+
+    // worlds saved before this existed dont have one
+    pub fn load(world_path: &Path) -> Option<Self>
+    {
+        let text = fs::read_to_string(world_path.join(SAVE_INFO_NAME)).ok()?;
+
+        serde_json::from_str(&text).map_err(|err|
+        {
+            eprintln!("error parsing save info in {}: {err}", world_path.display());
+        }).ok()
+    }
+
+    pub fn write(&self, world_path: &Path) -> io::Result<()>
+    {
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+
+        fs::create_dir_all(world_path)?;
+        fs::write(world_path.join(SAVE_INFO_NAME), json)
+    }
+
+    pub fn saved_time(&self) -> SystemTime
+    {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(self.saved)
+    }
+
+    pub fn playtime(&self) -> Duration
+    {
+        Duration::from_secs_f64(self.playtime.max(0.0))
+    }
+}
@@ -4,12 +4,14 @@ use super::world_generator::{
     WORLD_CHUNK_SIZE,
     CHUNK_RATIO,
     ConditionalInfo,
+    ParseError,
     WorldGenerator,
     WorldChunk
 };
 
 use crate::common::{
     SaveLoad,
+    TileMap,
     WorldChunksBlock,
     world::{
         CHUNK_SIZE,
@@ -79,6 +81,58 @@ impl WorldPlane
     }
 }
 
+// doesnt touch the disk so previews dont leave anything behind
+struct NoSave;
+
+impl<T> SaveLoad<T> for NoSave
+{
+    fn save(&mut self, _pos: GlobalPos, _chunk: T) {}
+    fn load(&mut self, _pos: GlobalPos) -> Option<T> { None }
+}
+
+fn preview_char(name: &str) -> char
+{
+    match name
+    {
+        "building" => '#',
+        "bunker" => 'B',
+        "park" => '"',
+        "road_horizontal" => '-',
+        "road_vertical" => '|',
+        "road_intersection" => '+',
+        "none" => ' ',
+        _ => '?'
+    }
+}
+
+// one character per surface worldchunk around the spawn, rng has to be seeded before this
+pub fn surface_preview(tilemap: Rc<TileMap>, size: usize) -> Result<String, ParseError>
+{
+    let mut generator = WorldGenerator::new(NoSave, tilemap, "world_generation/")?;
+
+    let size = Pos3::new(size, size, 1);
+    let indexer = Indexer::new(size, GlobalPos::new(0, 0, 0));
+
+    let mut world_chunks = FlatChunksContainer::new(size);
+    let mut plane = WorldPlane(FlatChunksContainer::new(size));
+
+    generator.generate_surface(&mut world_chunks, &mut plane, &indexer);
+
+    let rules = generator.rules();
+
+    let text = (0..size.y).map(|y|
+    {
+        (0..size.x).map(|x|
+        {
+            let chunk = plane.world_chunk(LocalPos::new(Pos3::new(x, y, 0), size));
+
+            preview_char(rules.name(chunk.id()))
+        }).collect::<String>()
+    }).collect::<Vec<_>>().join("\n");
+
+    Ok(text)
+}
+
 fn worldchunk_pos(pos: GlobalPos) -> GlobalPos
 {
     pos * GlobalPos::from(Pos3{z: 1, ..CHUNK_RATIO})
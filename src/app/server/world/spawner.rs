@@ -12,6 +12,8 @@ use crate::common::{
     lazy_transform::*,
     joint::*,
     Occluder,
+    WindZone,
+    SoundEmitter,
     EntityInfo,
     Parent,
     SpawnerTile,
@@ -20,6 +22,9 @@ use crate::common::{
 };
 
 
+// acceleration right in front of a vent
+const VENT_STRENGTH: f32 = 1.5;
+
 pub fn create_spawner(
     entities: &mut ServerEntities,
     pos: Pos3<f32>,
@@ -84,7 +89,7 @@ pub fn create_spawner(
                 }),
                 collider: Some(ColliderInfo{
                     kind: ColliderType::Rectangle,
-                    layer: ColliderLayer::Door,
+                    layer: ColliderLayer::DOOR,
                     ..Default::default()
                 }.into()),
                 physical: Some(PhysicalProperties{
@@ -106,6 +111,29 @@ pub fn create_spawner(
                 })),
                 ..Default::default()
             });
+        },
+        SpawnerTile::Vent{length} =>
+        {
+            let rotation = rotation.to_angle();
+
+            let direction = rotate_point_z_3d(Vector3::x(), rotation);
+
+            let length = *length as f32 * TILE_SIZE;
+
+            let position = Vector3::from(pos) + Vector3::repeat(TILE_SIZE / 2.0) + direction * (length / 2.0);
+
+            entities.push(false, EntityInfo{
+                transform: Some(Transform{
+                    position,
+                    scale: Vector3::new(length, TILE_SIZE, TILE_SIZE),
+                    rotation,
+                    ..Default::default()
+                }),
+                wind: Some(WindZone::new(direction * VENT_STRENGTH, 0.2)),
+                sound: Some(SoundEmitter::looping("vent")),
+                saveable: Some(()),
+                ..Default::default()
+            });
         }
     }
 }
@@ -16,6 +16,8 @@ use crate::common::{
     WeightedPicker,
     WorldChunksBlock,
     lisp::{self, *},
+    overrides::overridden,
+    rng::{self, RngStream},
     world::{
         Pos3,
         LocalPos,
@@ -251,7 +253,7 @@ impl ChunkGenerator
     {
         fn load(name: &Path) -> String
         {
-            fs::read_to_string(name)
+            fs::read_to_string(overridden(name))
                 .unwrap_or_else(|err| panic!("{} must exist >_< ({err})", name.display()))
         }
 
@@ -285,7 +287,7 @@ impl ChunkGenerator
         name: &str
     ) -> Result<(), ParseError>
     {
-        let code = fs::read_to_string(&filepath).map_err(|err|
+        let code = fs::read_to_string(overridden(&filepath)).map_err(|err|
         {
             // cant remove the clone cuz ? is cringe or something
             ParseError::new_named(filepath.clone(), err)
@@ -399,6 +401,11 @@ impl<S: SaveLoad<WorldChunksBlock>> WorldGenerator<S>
         Ok(Self{generator, saver, rules})
     }
 
+    pub fn flush(&mut self)
+    {
+        self.saver.flush();
+    }
+
     pub fn generate_surface<M: OvermapIndexing + Debug>(
         &mut self,
         world_chunks: &mut FlatChunksContainer<Option<WorldChunksBlock>>,
@@ -682,7 +689,7 @@ impl PossibleStates
         } else
         {
             *WeightedPicker::new(self.total, &self.states)
-                .pick_with(fastrand::f64(), |value|
+                .pick_with(rng::f64(RngStream::WorldGen), |value|
                 {
                     let rule = rules.get(*value);
 
@@ -800,7 +807,7 @@ impl Entropies
             None
         } else
         {
-            let r = fastrand::usize(0..mins.len());
+            let r = rng::usize(RngStream::WorldGen, 0..mins.len());
 
             Some(mins.remove(r))
         }
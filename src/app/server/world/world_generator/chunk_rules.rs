@@ -16,6 +16,7 @@ use super::{PossibleStates, ParseError};
 use crate::common::{
     BiMap,
     lisp::{self, Program, Primitives, LispMemory},
+    overrides::overridden,
     world::{
         CHUNK_SIZE,
         GlobalPos,
@@ -689,7 +690,7 @@ impl ChunkRulesGroup
         path: PathBuf
     ) -> Result<T, ParseError>
     {
-        let file = File::open(&path).map_err(|err|
+        let file = File::open(overridden(&path)).map_err(|err|
         {
             ParseError::new_named(path.to_owned(), err)
         })?;
@@ -0,0 +1,190 @@
+use std::{
+    io,
+    fs,
+    fmt::{self, Display},
+    path::Path,
+    time::{Duration, SystemTime}
+};
+
+use crate::common::{copy_dir, Difficulty};
+
+use super::{World, SaveInfo};
+
+
+const WORLDS_PATH: &str = "worlds";
+
+const AUTOSAVES_SUFFIX: &str = "_autosaves";
+
+pub struct WorldSummary
+{
+    pub name: String,
+    pub seed: Option<u64>,
+    pub difficulty: Option<Difficulty>,
+    pub players: usize,
+    pub locked: bool,
+    pub modified: Option<SystemTime>,
+    pub info: Option<SaveInfo>
+}
+
+impl Display for WorldSummary
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        let seed = self.seed.map(|x| x.to_string()).unwrap_or_else(|| "unknown".to_owned());
+
+        let difficulty = self.difficulty.map(difficulty_name).unwrap_or("normal");
+
+        let modified = self.modified.and_then(|x| x.elapsed().ok())
+            .map(time_ago)
+            .unwrap_or_else(|| "never".to_owned());
+
+        let locked = if self.locked { ", ended" } else { "" };
+
+        let playtime = self.info.as_ref().map(|info|
+        {
+            format!(", {} total, version {}", playtime_text(info.playtime()), info.version)
+        }).unwrap_or_default();
+
+        write!(
+            f,
+            "{} (seed {seed}, {difficulty}, {} players, played {modified}{playtime}{locked})",
+            self.name,
+            self.players
+        )
+    }
+}
+
+fn difficulty_name(difficulty: Difficulty) -> &'static str
+{
+    if difficulty == Difficulty::easy()
+    {
+        "easy"
+    } else if difficulty == Difficulty::normal()
+    {
+        "normal"
+    } else if difficulty == Difficulty::hard()
+    {
+        "hard"
+    } else
+    {
+        "custom"
+    }
+}
+
+fn time_ago(duration: Duration) -> String
+{
+    let minutes = duration.as_secs() / 60;
+    let hours = minutes / 60;
+    let days = hours / 24;
+
+    if days > 0
+    {
+        format!("{days} days ago")
+    } else if hours > 0
+    {
+        format!("{hours} hours ago")
+    } else
+    {
+        format!("{minutes} minutes ago")
+    }
+}
+
+fn playtime_text(duration: Duration) -> String
+{
+    let minutes = duration.as_secs() / 60;
+
+    format!("{}h {}m", minutes / 60, minutes % 60)
+}
+
+fn summary(name: String, path: &Path) -> WorldSummary
+{
+    let seed = fs::read_to_string(path.join("seed")).ok().and_then(|x| x.trim().parse().ok());
+
+    let difficulty = fs::read_to_string(path.join("difficulty.json")).ok().and_then(|x|
+    {
+        serde_json::from_str(&x).ok()
+    });
+
+    let players = fs::read_dir(path.join("players")).map(|x| x.count()).unwrap_or(0);
+
+    let info = SaveInfo::load(path);
+
+    // the folder itself only changes when something gets added to it so look one level in
+    let modified = info.as_ref().map(|x| x.saved_time()).or_else(||
+    {
+        fs::read_dir(path).into_iter().flatten().flatten()
+            .filter_map(|entry| entry.metadata().and_then(|x| x.modified()).ok())
+            .max()
+    });
+
+    WorldSummary{
+        locked: World::is_locked(&name),
+        name,
+        seed,
+        difficulty,
+        players,
+        modified,
+        info
+    }
+}
+
+// newest first
+pub fn list() -> Vec<WorldSummary>
+{
+    let mut worlds: Vec<_> = fs::read_dir(WORLDS_PATH).into_iter().flatten().flatten()
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| entry.file_name().into_string().ok().map(|name| (name, entry.path())))
+        .filter(|(name, _)| !name.ends_with(AUTOSAVES_SUFFIX))
+        .map(|(name, path)| summary(name, &path))
+        .collect();
+
+    worlds.sort_by(|a, b| b.modified.cmp(&a.modified));
+
+    worlds
+}
+
+// the most recently played world that can still be played
+pub fn latest() -> Option<String>
+{
+    list().into_iter().find(|world| !world.locked).map(|world| world.name)
+}
+
+fn exists(name: &str) -> io::Result<()>
+{
+    if World::world_path_associated(name).is_dir()
+    {
+        Ok(())
+    } else
+    {
+        Err(io::Error::new(io::ErrorKind::NotFound, format!("world {name} doesnt exist")))
+    }
+}
+
+// also gets rid of the autosaves
+pub fn delete(name: &str) -> io::Result<()>
+{
+    exists(name)?;
+
+    fs::remove_dir_all(World::world_path_associated(name))?;
+
+    let autosaves = Path::new(WORLDS_PATH).join(format!("{name}{AUTOSAVES_SUFFIX}"));
+    if autosaves.exists()
+    {
+        fs::remove_dir_all(autosaves)?;
+    }
+
+    Ok(())
+}
+
+pub fn duplicate(from: &str, to: &str) -> io::Result<()>
+{
+    exists(from)?;
+
+    let to_path = World::world_path_associated(to);
+    if to.ends_with(AUTOSAVES_SUFFIX) || to_path.exists()
+    {
+        return Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("cant use {to} as a world name")));
+    }
+
+    copy_dir(&World::world_path_associated(from), &to_path)
+}
@@ -1,4 +1,6 @@
-use std::{env, sync::LazyLock};
+use std::{
+    sync::{LazyLock, atomic::{AtomicBool, Ordering}}
+};
 
 use serde::{Serialize, Deserialize};
 
@@ -17,7 +19,8 @@ use crate::app::{
 use strum::{IntoEnumIterator, EnumIter, EnumCount, IntoStaticStr};
 
 
-#[derive(Clone, Copy, EnumIter, EnumCount, IntoStaticStr)]
+#[derive(Debug, Clone, Copy, EnumIter, EnumCount, IntoStaticStr)]
+#[strum(serialize_all = "snake_case")]
 pub enum DebugTool
 {
     Lisp,
@@ -32,7 +35,26 @@ pub enum DebugTool
     NoGravity,
     NoResolve,
     NoJoints,
-    NoSpawns
+    NoSpawns,
+    Joints,
+    Broadphase,
+    PhysicsOverlay
+}
+
+impl DebugTool
+{
+    // everything the physics overlay turns on at once
+    pub fn in_physics_overlay(self) -> bool
+    {
+        matches!(
+            self,
+            Self::CollisionBounds
+            | Self::Contacts
+            | Self::Velocity
+            | Self::Joints
+            | Self::Broadphase
+        )
+    }
 }
 
 pub trait DebugNameTrait
@@ -73,12 +95,28 @@ pub trait DebugConfigTrait
     fn is_debug() -> bool;
 
     fn is_enabled(tool: DebugTool) -> bool;
+    fn set_enabled(tool: DebugTool, state: bool);
+
+    fn toggle(tool: DebugTool) -> bool
+    {
+        let state = !Self::is_enabled(tool);
+        Self::set_enabled(tool, state);
+
+        state
+    }
+
     fn is_disabled(tool: DebugTool) -> bool
     {
         !Self::is_enabled(tool)
     }
 }
 
+// tools start off from the config but can get flipped while running
+static DEBUG_STATES: LazyLock<[AtomicBool; DebugTool::COUNT]> = LazyLock::new(||
+{
+    DebugTool::iter().map(|_| AtomicBool::new(false)).collect::<Vec<_>>().try_into().unwrap()
+});
+
 pub struct DebugConfigTrue;
 pub struct DebugConfigFalse;
 
@@ -90,46 +128,23 @@ impl DebugConfigTrait for DebugConfigTrue
 
     fn on_start()
     {
-        let available = DebugTool::iter().map(|tool| -> String
-        {
-            let s: &str = tool.into();
-
-            format!("STEPHANIE_{}", s.to_uppercase())
-        }).reduce(|acc, x|
-        {
-            format!("{acc}\n{x}")
-        }).unwrap_or_default();
+        let available = DebugTool::iter().map(<&str>::from).collect::<Vec<_>>().join(", ");
 
-        eprintln!("running in debug mode, available tools:\n{available}");
+        eprintln!("running in debug mode, available tools (for --debug-tools): {available}");
     }
 
     fn is_debug() -> bool { true }
 
     fn is_enabled(tool: DebugTool) -> bool
     {
-        static STATES: LazyLock<[bool; DebugTool::COUNT]> = LazyLock::new(||
-        {
-            DebugTool::iter().map(|tool|
-            {
-                let s: &str = tool.into();
-                env::var(format!("STEPHANIE_{}", s.to_uppercase())).map(|x|
-                {
-                    match x.to_lowercase().as_ref()
-                    {
-                        "0" | "false" => false,
-                        "1" | "true" => true,
-                        x =>
-                        {
-                            eprintln!("{s} is set to `{x}` which isnt a valid boolean");
-
-                            false
-                        }
-                    }
-                }).unwrap_or(false)
-            }).collect::<Vec<_>>().try_into().unwrap()
-        });
-
-        STATES[tool as usize]
+        let is_set = |tool: DebugTool| DEBUG_STATES[tool as usize].load(Ordering::Relaxed);
+
+        is_set(tool) || (tool.in_physics_overlay() && is_set(DebugTool::PhysicsOverlay))
+    }
+
+    fn set_enabled(tool: DebugTool, state: bool)
+    {
+        DEBUG_STATES[tool as usize].store(state, Ordering::Relaxed);
     }
 }
 
@@ -144,6 +159,7 @@ impl DebugConfigTrait for DebugConfigFalse
     fn is_debug() -> bool { false }
 
     fn is_enabled(_tool: DebugTool) -> bool { false }
+    fn set_enabled(_tool: DebugTool, _state: bool) {}
 }
 
 #[cfg(debug_assertions)]
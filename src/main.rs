@@ -16,7 +16,7 @@
 // ITS MORE DESCRIPTIVE OF WUT IT IS
 #![allow(clippy::let_and_return)]
 
-use std::{process, fmt::Display};
+use std::{env, process, fmt::Display};
 
 use nalgebra::Vector3;
 
@@ -24,7 +24,9 @@ use yanyaengine::App;
 
 pub use app::{common, server, client, ProgramShaders};
 
-use app::AppInfo;
+use app::{AppInfo, Config, WindowMode};
+
+use debug_config::{DebugConfig, DebugConfigTrait};
 
 pub use common::{debug_env, is_debug_env};
 pub mod debug_config;
@@ -56,18 +58,31 @@ fn main()
 {
     // unsafe{ float_excepts() };
 
+    common::crash_report::install();
+
+    let config = Config::parse(env::args().skip(1));
+
+    config.debug_tools.iter().for_each(|tool| DebugConfig::set_enabled(*tool, true));
+
+    let fullscreen = config.window_mode == WindowMode::Fullscreen;
+
     let shaders::ShadersCreated{shaders, group, query} = shaders::create();
 
+    let targets = rendering::RenderTargets::new();
+
     let init = AppInfo{
-        shaders: group
+        shaders: group,
+        targets: targets.clone(),
+        config
     };
 
-    let rendering = rendering::create();
+    let rendering = rendering::create(targets);
 
     App::<app::App>::new()
         .with_title("stey funy")
         .with_textures_path("textures/normal")
         .with_icon("icon.png")
+        .with_fullscreen(fullscreen)
         .with_shaders(shaders, query)
         .with_app_init(Some(init))
         .with_rendering(rendering)
@@ -1,5 +1,7 @@
 use std::sync::Arc;
 
+use parking_lot::RwLock;
+
 use vulkano::{
     format::{Format, FormatFeatures},
     memory::allocator::{
@@ -20,16 +22,85 @@ use yanyaengine::Rendering;
 use crate::BACKGROUND_COLOR;
 
 
+// float so bright stuff can go over 1 and still be told apart in the bloom
+const WORLD_FORMAT: Format = Format::R16G16B16A16_SFLOAT;
+
+// has to match the passes below, every frame has to go through all of them
+pub const SUBPASSES: u32 = 3;
+
+// the world gets drawn into its own image first, the post pass samples it while drawing to the screen
+#[derive(Clone)]
+pub struct RenderTargets
+{
+    world: Arc<RwLock<Option<Arc<ImageView>>>>
+}
+
+impl RenderTargets
+{
+    pub fn new() -> Self
+    {
+        Self{world: Arc::new(RwLock::new(None))}
+    }
+
+    pub fn world(&self) -> Option<Arc<ImageView>>
+    {
+        self.world.read().clone()
+    }
+
+    // the world image is always full resolution so its the size of the screen in pixels
+    pub fn size(&self) -> Option<[u32; 2]>
+    {
+        self.world.read().as_ref().map(|world|
+        {
+            let [width, height, _] = world.image().extent();
+
+            [width, height]
+        })
+    }
+
+    // every swapchain image shares the same one, only a resize makes a new one
+    fn world_for(&self, allocator: Arc<StandardMemoryAllocator>, view: &ImageView) -> Arc<ImageView>
+    {
+        let extent = view.image().extent();
+
+        let mut world = self.world.write();
+
+        if let Some(world) = world.as_ref().filter(|world| world.image().extent() == extent)
+        {
+            return world.clone();
+        }
+
+        let image = Image::new(
+            allocator,
+            ImageCreateInfo{
+                image_type: ImageType::Dim2d,
+                format: WORLD_FORMAT,
+                extent,
+                usage: ImageUsage::COLOR_ATTACHMENT | ImageUsage::INPUT_ATTACHMENT | ImageUsage::SAMPLED,
+                ..Default::default()
+            },
+            AllocationCreateInfo::default()
+        ).unwrap();
+
+        let view = ImageView::new_default(image).unwrap();
+
+        *world = Some(view.clone());
+
+        view
+    }
+}
+
 #[derive(Clone)]
 pub struct ThisSetup
 {
-    supported_format: Format
+    supported_format: Format,
+    targets: RenderTargets
 }
 
-pub fn create() -> Rendering<ThisSetup>
+pub fn create(targets: RenderTargets) -> Rendering<ThisSetup>
 {
     Rendering{
-        setup: Box::new(|physical_device|
+        setup: Box::new(move |physical_device|
         {
             let supported_format = [
                 Format::D32_SFLOAT_S8_UINT,
@@ -42,17 +113,18 @@ pub fn create() -> Rendering<ThisSetup>
                     .intersects(FormatFeatures::DEPTH_STENCIL_ATTACHMENT)
             }).expect("depth/stencil format must exist!!");
 
-            ThisSetup{supported_format}
+            ThisSetup{supported_format, targets: targets.clone()}
         }),
         render_pass: Box::new(|setup, device, image_format|
         {
-            vulkano::single_pass_renderpass!(
+            // the world pass, then the post pass reading it, then the ui on top at full resolution
+            vulkano::ordered_passes_renderpass!(
                 device,
                 attachments: {
                     color: {
                         format: image_format,
                         samples: 1,
-                        load_op: Clear,
+                        load_op: DontCare,
                         store_op: Store
                     },
                     depth: {
@@ -60,18 +132,37 @@ pub fn create() -> Rendering<ThisSetup>
                         samples: 1,
                         load_op: Clear,
                         store_op: DontCare
+                    },
+                    world: {
+                        format: WORLD_FORMAT,
+                        samples: 1,
+                        load_op: Clear,
+                        store_op: DontCare
                     }
                 },
-                pass: {
-                    color: [color],
-                    depth_stencil: {depth}
-                }
+                passes: [
+                    {
+                        color: [world],
+                        depth_stencil: {depth},
+                        input: []
+                    },
+                    {
+                        color: [color],
+                        depth_stencil: {},
+                        input: [world]
+                    },
+                    {
+                        color: [color],
+                        depth_stencil: {},
+                        input: []
+                    }
+                ]
             ).unwrap()
         }),
         attachments: Box::new(|setup, allocator: Arc<StandardMemoryAllocator>, view: Arc<ImageView>|
         {
             let depth_stencil_image = Image::new(
-                allocator,
+                allocator.clone(),
                 ImageCreateInfo{
                     image_type: ImageType::Dim2d,
                     format: setup.supported_format,
@@ -84,11 +175,14 @@ pub fn create() -> Rendering<ThisSetup>
 
             let depth_stencil = ImageView::new_default(depth_stencil_image).unwrap();
 
-            vec![view, depth_stencil]
+            let world = setup.targets.world_for(allocator, &view);
+
+            vec![view, depth_stencil, world]
         }),
         clear: vec![
-            Some([BACKGROUND_COLOR.x, BACKGROUND_COLOR.y, BACKGROUND_COLOR.z, 1.0].into()),
-            Some((1.0, 1).into())
+            None,
+            Some((1.0, 1).into()),
+            Some([BACKGROUND_COLOR.x, BACKGROUND_COLOR.y, BACKGROUND_COLOR.z, 1.0].into())
         ]
     }
 }
@@ -7,6 +7,8 @@ use vulkano::pipeline::graphics::depth_stencil::{
     CompareOp
 };
 
+use vulkano::pipeline::graphics::vertex_input::Vertex;
+
 use nalgebra::Vector3;
 
 use yanyaengine::{
@@ -21,6 +23,7 @@ use yanyaengine::{
 use crate::{
     BACKGROUND_COLOR,
     app::ProgramShaders,
+    client::TileInstance,
     common::world::TILE_SIZE
 };
 
@@ -43,6 +46,15 @@ mod default_fragment
     }
 }
 
+mod tiles_vertex
+{
+    vulkano_shaders::shader!
+    {
+        ty: "vertex",
+        path: "shaders/tiles.vert"
+    }
+}
+
 mod world_fragment
 {
     vulkano_shaders::shader!
@@ -52,6 +64,24 @@ mod world_fragment
     }
 }
 
+mod background_fragment
+{
+    vulkano_shaders::shader!
+    {
+        ty: "fragment",
+        path: "shaders/background.frag"
+    }
+}
+
+mod reflection_fragment
+{
+    vulkano_shaders::shader!
+    {
+        ty: "fragment",
+        path: "shaders/reflection.frag"
+    }
+}
+
 mod default_shaded_fragment
 {
     vulkano_shaders::shader!
@@ -97,6 +127,15 @@ mod shadow_fragment
     }
 }
 
+mod light_occlusion_fragment
+{
+    vulkano_shaders::shader!
+    {
+        ty: "fragment",
+        path: "shaders/light_occlusion.frag"
+    }
+}
+
 mod ui_vertex
 {
     vulkano_shaders::shader!
@@ -115,9 +154,19 @@ mod ui_fragment
     }
 }
 
+mod post_fragment
+{
+    vulkano_shaders::shader!
+    {
+        ty: "fragment",
+        path: "shaders/post.frag"
+    }
+}
+
 
 const DARKEN: f32 = 0.97;
 const SHADOW_COLOR: Vector3<f32> = Vector3::new(0.07, 0.02, 0.1);
+const LIGHT_SHADOW_STRENGTH: f32 = 0.4;
 
 pub struct ShadersCreated
 {
@@ -166,13 +215,51 @@ pub fn create() -> ShadersCreated
         ..Default::default()
     });
 
+    // the tile quad and then every tile placed on it
+    let tiles_input = ||
+    {
+        let mut input = Object::per_vertex();
+        input.push(TileInstance::per_instance());
+
+        input
+    };
+
     let world_shader = shaders.push(Shader{
         shader: ShadersGroup::new(
-            default_vertex,
+            |device|
+            {
+                tiles_vertex::load(device).unwrap().specialize(
+                    [(0, TILE_SIZE.into())].into_iter().collect()
+                )
+            },
             world_fragment::load
         ),
-        stencil: Some(default_stencil),
+        stencil: Some(default_stencil.clone()),
         depth: Some(world_depth),
+        per_vertex: Some(tiles_input()),
+        ..Default::default()
+    });
+
+    let reflection_shader = shaders.push(Shader{
+        shader: ShadersGroup::new(
+            default_vertex,
+            reflection_fragment::load
+        ),
+        stencil: Some(default_stencil.clone()),
+        depth: Some(DepthState{
+            write_enable: false,
+            compare_op: CompareOp::Less
+        }),
+        per_vertex: Some(Object::per_vertex()),
+        ..Default::default()
+    });
+
+    // drawn before anything else so it doesnt touch depth or stencil
+    let background_shader = shaders.push(Shader{
+        shader: ShadersGroup::new(
+            default_vertex,
+            background_fragment::load
+        ),
         per_vertex: Some(Object::per_vertex()),
         ..Default::default()
     });
@@ -212,7 +299,7 @@ pub fn create() -> ShadersCreated
             ),
             stencil: Some(shaded_stencil.clone()),
             depth: Some(world_depth),
-            per_vertex: Some(Object::per_vertex()),
+            per_vertex: Some(tiles_input()),
             ..Default::default()
         })
     };
@@ -261,12 +348,45 @@ pub fn create() -> ShadersCreated
         ..Default::default()
     });
 
+    // only darkens the parts the player can see, stencil stays untouched
+    let light_occlusion_shader = shaders.push(Shader{
+        shader: ShadersGroup::new(
+            default_vertex,
+            move |device|
+            {
+                light_occlusion_fragment::load(device).unwrap().specialize(
+                    [
+                        (0, SHADOW_COLOR.x.into()),
+                        (1, SHADOW_COLOR.y.into()),
+                        (2, SHADOW_COLOR.z.into()),
+                        (3, LIGHT_SHADOW_STRENGTH.into())
+                    ].into_iter().collect()
+                )
+            }
+        ),
+        stencil: Some(default_stencil),
+        per_vertex: Some(Object::per_vertex()),
+        ..Default::default()
+    });
+
+    // everything above draws into the world image, the ui gets its own pass after the post one
     let ui_shader = shaders.push(Shader{
         shader: ShadersGroup::new(
             ui_vertex::load,
             ui_fragment::load
         ),
         per_vertex: Some(Object::per_vertex()),
+        subpass: 2,
+        ..Default::default()
+    });
+
+    let post_shader = shaders.push(Shader{
+        shader: ShadersGroup::new(
+            ui_vertex::load,
+            post_fragment::load
+        ),
+        per_vertex: Some(Object::per_vertex()),
+        subpass: 1,
         ..Default::default()
     });
 
@@ -277,7 +397,11 @@ pub fn create() -> ShadersCreated
             default_shaded: default_shaded_shader,
             world: world_shader,
             world_shaded: world_shaded_shader,
+            background: background_shader,
+            reflection: reflection_shader,
             shadow: shadow_shader,
+            light_occlusion: light_occlusion_shader,
+            post: post_shader,
             ui: ui_shader
         },
         query: Box::new(move |path|